@@ -1,3 +1,7 @@
+/// Config to evaluate the blob polynomial of a batch
+mod barycentric;
+/// Config to bind the blob of a batch to the L2 transactions of its chunks
+mod blob_data;
 /// Circuit implementation of aggregation circuit.
 mod circuit;
 /// Config for aggregation circuit
@@ -5,6 +9,8 @@ mod config;
 /// config for RLC circuit
 mod rlc;
 
+pub use barycentric::BarycentricEvaluationConfig;
+pub use blob_data::BlobDataConfig;
 pub use circuit::AggregationCircuit;
 pub use config::AggregationConfig;
 pub(crate) use rlc::RlcConfig;
@@ -0,0 +1,271 @@
+use halo2_proofs::{
+    circuit::Value,
+    halo2curves::bn256::{Fq, Fr},
+};
+use itertools::Itertools;
+use num_bigint::{BigInt, BigUint};
+use snark_verifier::loader::halo2::halo2_ecc::{
+    bigint::{CRTInteger, OverflowInteger},
+    fields::{fp::FpConfig, FieldChip},
+    halo2_base::{
+        gates::{range::RangeConfig, GateInstructions, RangeInstructions},
+        utils::decompose_biguint,
+        AssignedValue, Context, QuantumCell,
+    },
+};
+use std::iter;
+
+use crate::{
+    blob::{
+        barycentric_terms, bls_modulus, roots_of_unity_brp, BlobData, BLOB_WIDTH, LOG_BLOB_WIDTH,
+        N_DATA_BYTES_PER_COEFFICIENT,
+    },
+    constants::{BITS, DIGEST_LEN, LIMBS},
+};
+
+/// Number of bytes in a limb of a non-native field element
+const N_BYTES_PER_LIMB: usize = BITS / 8;
+
+/// Non-native field chip over the BLS12-381 scalar field.
+///
+/// halo2curves does not provide the BLS12-381 scalar field, so the chip is constructed with its
+/// modulus, and `Fq` is only a placeholder for the type parameter: the witnesses are computed as
+/// [`BigUint`] and loaded by limbs, and the methods that interpret the values as `Fq`, i.e.,
+/// `load_private`, `get_assigned_value` and `divide`, are never called.
+type ScalarFieldChip = FpConfig<Fr, Fq>;
+
+#[derive(Debug, Clone)]
+/// Config to evaluate the blob polynomial of a batch at the challenge point, with the barycentric
+/// formula. It shares the range chip, hence the columns, of the aggregation.
+pub struct BarycentricEvaluationConfig {
+    /// Non-native field chip over the BLS12-381 scalar field
+    pub scalar: ScalarFieldChip,
+}
+
+/// The cells of a blob point evaluation, all in big endian bytes
+pub(crate) struct AssignedBarycentricEvaluation {
+    /// the bytes of the blob, padded to N_BLOB_BYTES
+    pub(crate) blob_bytes: Vec<AssignedValue<Fr>>,
+    /// the challenge digest, reduced to the challenge point
+    pub(crate) challenge_digest: Vec<AssignedValue<Fr>>,
+    /// the challenge point z
+    pub(crate) challenge: Vec<AssignedValue<Fr>>,
+    /// the evaluation y = p(z)
+    pub(crate) evaluation: Vec<AssignedValue<Fr>>,
+}
+
+impl BarycentricEvaluationConfig {
+    /// Build the config from the range chip of the aggregation.
+    pub fn construct(range: RangeConfig<Fr>) -> Self {
+        Self {
+            scalar: FpConfig::construct(range, BITS, LIMBS, bls_modulus()),
+        }
+    }
+
+    /// Assign the evaluation of the polynomial of `blob` at the challenge point.
+    ///
+    /// The returned cells are not constrained against the keccak table yet: the caller must copy
+    /// the blob bytes and the challenge digest from the hashes of the blob.
+    pub(crate) fn assign(
+        &self,
+        ctx: &mut Context<Fr>,
+        blob: &BlobData,
+    ) -> AssignedBarycentricEvaluation {
+        let modulus = bls_modulus();
+        let point_evaluation = blob.point_evaluation();
+
+        // ====================================================
+        // z := challenge_digest mod BLS_MODULUS
+        // ====================================================
+        // the digest bytes are copied from the keccak table, hence not range checked here
+        let challenge_digest = self.load_bytes(ctx, blob.challenge_digest().as_bytes(), false);
+        let challenge_digest_value = BigUint::from_bytes_be(blob.challenge_digest().as_bytes());
+        let challenge_digest_crt = {
+            let limbs = self.limbs_from_bytes(ctx, &challenge_digest);
+            self.crt_from_limbs(ctx, limbs, &challenge_digest_value)
+        };
+        let z = self.scalar.carry_mod(ctx, &challenge_digest_crt);
+        self.scalar.enforce_less_than_p(ctx, &z);
+        let z_value = BigUint::from_bytes_be(point_evaluation.challenge.as_bytes());
+        let challenge = self.assign_bytes_of(ctx, &z, point_evaluation.challenge.as_bytes());
+
+        // ====================================================
+        // sum_i f_i * ω_i / (z - ω_i)
+        // ====================================================
+        // the blob bytes are copied from the keccak table, hence not range checked here
+        let blob_bytes = self.load_bytes(ctx, &blob.padded_bytes(), false);
+        let roots = roots_of_unity_brp();
+        let coefficients = blob.coefficients();
+        let terms = barycentric_terms(&coefficients, &roots, &z_value);
+
+        let mut sum = self.scalar.load_constant(ctx, BigUint::from(0u64));
+        for (((bytes, coefficient), root), term) in blob_bytes
+            .chunks(N_DATA_BYTES_PER_COEFFICIENT)
+            .zip_eq(coefficients.iter())
+            .zip_eq(roots.into_iter())
+            .zip_eq(terms.iter())
+        {
+            // the coefficient f_i is read from the blob bytes
+            let coefficient = {
+                let limbs = self.limbs_from_bytes(ctx, bytes);
+                self.crt_from_limbs(ctx, limbs, coefficient)
+            };
+            let root = self.scalar.load_constant(ctx, root);
+            let term = self.load_private(ctx, term);
+
+            // term * (z - ω_i) == f_i * ω_i
+            let z_minus_root = self.scalar.sub_no_carry(ctx, &z, &root);
+            let lhs = self.scalar.mul_no_carry(ctx, &term, &z_minus_root);
+            let rhs = self.scalar.mul_no_carry(ctx, &coefficient, &root);
+            let diff = self.scalar.sub_no_carry(ctx, &lhs, &rhs);
+            self.scalar.check_carry_mod_to_zero(ctx, &diff);
+
+            let sum_no_carry = self.scalar.add_no_carry(ctx, &sum, &term);
+            sum = self.scalar.carry_mod(ctx, &sum_no_carry);
+        }
+
+        // ====================================================
+        // y := (z^BLOB_WIDTH - 1) / BLOB_WIDTH * sum
+        // ====================================================
+        let mut z_pow = z;
+        for _ in 0..LOG_BLOB_WIDTH {
+            z_pow = self.scalar.mul(ctx, &z_pow, &z_pow);
+        }
+        let one = self.scalar.load_constant(ctx, BigUint::from(1u64));
+        let z_pow_minus_one = self.scalar.sub_no_carry(ctx, &z_pow, &one);
+        let width_inv = self.scalar.load_constant(
+            ctx,
+            BigUint::from(BLOB_WIDTH).modpow(&(&modulus - 2u64), &modulus),
+        );
+        let factor = self.scalar.mul(ctx, &z_pow_minus_one, &width_inv);
+        let y = self.scalar.mul(ctx, &factor, &sum);
+        self.scalar.enforce_less_than_p(ctx, &y);
+        let evaluation = self.assign_bytes_of(ctx, &y, point_evaluation.evaluation.as_bytes());
+
+        log::trace!(
+            "barycentric evaluation: z = {:?}, y = {:?}",
+            point_evaluation.challenge,
+            point_evaluation.evaluation,
+        );
+
+        AssignedBarycentricEvaluation {
+            blob_bytes,
+            challenge_digest,
+            challenge,
+            evaluation,
+        }
+    }
+
+    /// Load `bytes` as witnesses, range checked if `range_check` is set.
+    fn load_bytes(
+        &self,
+        ctx: &mut Context<Fr>,
+        bytes: &[u8],
+        range_check: bool,
+    ) -> Vec<AssignedValue<Fr>> {
+        let assigned = self.scalar.range.gate.assign_witnesses(
+            ctx,
+            bytes
+                .iter()
+                .map(|&byte| Value::known(Fr::from(byte as u64))),
+        );
+        if range_check {
+            for byte in assigned.iter() {
+                self.scalar.range.range_check(ctx, byte, 8);
+            }
+        }
+        assigned
+    }
+
+    /// Recover the little endian limbs of an integer from its big endian `bytes`, i.e., the
+    /// limbs from the last [`N_BYTES_PER_LIMB`] bytes and so on.
+    fn limbs_from_bytes(
+        &self,
+        ctx: &mut Context<Fr>,
+        bytes: &[AssignedValue<Fr>],
+    ) -> Vec<AssignedValue<Fr>> {
+        assert!(bytes.len() <= N_BYTES_PER_LIMB * LIMBS);
+
+        let powers_of_256 = iter::successors(Some(Fr::one()), |x| Some(*x * Fr::from(256)))
+            .take(N_BYTES_PER_LIMB)
+            .collect_vec();
+        let mut limbs = bytes
+            .rchunks(N_BYTES_PER_LIMB)
+            .map(|limb_bytes| {
+                self.scalar.range.gate.inner_product(
+                    ctx,
+                    limb_bytes
+                        .iter()
+                        .rev()
+                        .map(|byte| QuantumCell::Existing(*byte))
+                        .collect_vec(),
+                    powers_of_256
+                        .iter()
+                        .map(|power| QuantumCell::Constant(*power))
+                        .collect_vec(),
+                )
+            })
+            .collect_vec();
+        // an integer of less than LIMBS limbs has zero upper limbs
+        while limbs.len() < LIMBS {
+            limbs.push(self.scalar.range.gate.load_zero(ctx));
+        }
+        limbs
+    }
+
+    /// Build a CRT integer from its proper `limbs`, i.e., each limb has at most [`BITS`] bits.
+    fn crt_from_limbs(
+        &self,
+        ctx: &mut Context<Fr>,
+        limbs: Vec<AssignedValue<Fr>>,
+        value: &BigUint,
+    ) -> CRTInteger<Fr> {
+        let native = OverflowInteger::<Fr>::evaluate(
+            &self.scalar.range.gate,
+            ctx,
+            &limbs,
+            self.scalar.limb_bases.iter().cloned(),
+        );
+        CRTInteger::construct(
+            OverflowInteger::construct(limbs, BITS),
+            native,
+            Value::known(BigInt::from(value.clone())),
+        )
+    }
+
+    /// Load a field element of BLS12-381 as witness, range checked to the bits of the modulus.
+    fn load_private(&self, ctx: &mut Context<Fr>, value: &BigUint) -> CRTInteger<Fr> {
+        let limbs = self.scalar.range.gate.assign_witnesses(
+            ctx,
+            decompose_biguint::<Fr>(value, LIMBS, BITS)
+                .into_iter()
+                .map(Value::known),
+        );
+        let loaded = self.crt_from_limbs(ctx, limbs, value);
+        self.scalar
+            .range_check(ctx, &loaded, bls_modulus().bits() as usize);
+        loaded
+    }
+
+    /// Assign the big endian bytes of `x`, a proper field element of BLS12-381 whose bytes are
+    /// `bytes`, and constrain they recover the limbs of `x`.
+    fn assign_bytes_of(
+        &self,
+        ctx: &mut Context<Fr>,
+        x: &CRTInteger<Fr>,
+        bytes: &[u8],
+    ) -> Vec<AssignedValue<Fr>> {
+        assert_eq!(bytes.len(), DIGEST_LEN);
+
+        let assigned = self.load_bytes(ctx, bytes, true);
+        let limbs = self.limbs_from_bytes(ctx, &assigned);
+        for (limb, recovered) in x.truncation.limbs.iter().zip_eq(limbs.into_iter()) {
+            self.scalar.range.gate.assert_equal(
+                ctx,
+                QuantumCell::Existing(*limb),
+                QuantumCell::Existing(recovered),
+            );
+        }
+        assigned
+    }
+}
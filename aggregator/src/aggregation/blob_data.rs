@@ -0,0 +1,779 @@
+use ethers_core::utils::keccak256;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    halo2curves::bn256::Fr,
+    plonk::{
+        Advice, Column, ConstraintSystem, Error, Expression, Fixed, SecondPhase, Selector,
+        VirtualCells,
+    },
+    poly::Rotation,
+};
+use itertools::Itertools;
+use snark_verifier::loader::halo2::halo2_ecc::halo2_base;
+use zkevm_circuits::{
+    table::{KeccakTable, LookupTable},
+    util::{Challenges, Expr},
+};
+
+use crate::{
+    blob::{BlobData, MAX_BLOB_TXS, N_BLOB_BYTES},
+    constants::DIGEST_LEN,
+};
+
+#[derive(Debug, Clone)]
+/// Config to bind the bytes of the blob of a batch to the L2 transactions of its chunks, i.e.,
+/// to prove that, for each chunk, the blob data commitment in its pi hash preimage is
+///  keccak(keccak(tx_0) || ... || keccak(tx_n-1))
+/// for the transactions tx_0, ..., tx_n-1 that the chunk takes in the blob, in order.
+///
+/// The config lays out a single region of three sections:
+/// - bytes: the blob bytes, copied from the preimage of the blob bytes digest, split into
+///   transactions; each transaction is looked up in the keccak table, and its digest is looked up
+///   in the digests section by its index. The row after the bytes is a padding sentinel, whose
+///   `num` is 1 + the number of transactions.
+/// - digests: [`MAX_BLOB_TXS`] slots of 32 rows, the t-th slot holding the digest of the t-th
+///   transaction. The slots of the transactions of each non-empty chunk form a run, whose bytes and
+///   length are looked up in the chunks section by the chunk index, the chunk indices of the runs
+///   being increasing. The last `num` is 1 + the number of non-padding slots.
+/// - chunks: 32 rows per chunk holding its blob data commitment, copied from its pi hash preimage,
+///   and the RLC and length of the preimage of the commitment, which are looked up in the keccak
+///   table for each valid chunk. The preimage is empty for a padding chunk, and the last `num` is
+///   one more than the number of digests in all the preimages.
+///
+/// As the three `num` are equal, each transaction of the blob has a digest slot, and the runs
+/// cover all the digests of the preimages, i.e., each non-empty chunk is the run of its
+/// transactions.
+pub struct BlobDataConfig {
+    /// a blob byte, a digest byte or a commitment byte, depending on the section
+    byte: Column<Advice>,
+    /// RLC of the bytes of the current transaction (bytes) or run (digests), or of the preimage
+    /// of the commitment (chunks)
+    rlc: Column<Advice>,
+    /// length of the bytes of the current transaction or run, or of the preimage of the
+    /// commitment
+    len: Column<Advice>,
+    /// RLC of the current digest (digests) or commitment (chunks); the digest of the current
+    /// transaction at its end (bytes)
+    digest_rlc: Column<Advice>,
+    /// the index of the current transaction, or counters of the slots or digests, see above
+    num: Column<Advice>,
+    /// the chunk index of the current slot (digests)
+    counter: Column<Advice>,
+    /// the difference of the chunk indices of two consecutive runs, minus 1 (digests)
+    run_step: Column<Advice>,
+    /// whether the row is a padding byte or slot, or a padding chunk
+    is_padding: Column<Advice>,
+    /// whether the row is the last byte of a transaction (bytes)
+    is_tx_end: Column<Advice>,
+    /// whether the row is the last byte of a run (digests)
+    is_chunk_end: Column<Advice>,
+    /// the inverse of `len`, if any (chunks)
+    len_inv: Column<Advice>,
+    /// whether the row is the last byte of the commitment of a valid chunk (chunks)
+    is_chunk_valid: Column<Advice>,
+    /// whether the row is the sentinel, the last row of a slot or the last row of a chunk
+    is_boundary: Column<Fixed>,
+    /// the 1-based index of a slot on its last row, or 0
+    slot_index: Column<Fixed>,
+    /// the 1-based index of a chunk on its last row, or 0
+    chunk_index: Column<Fixed>,
+    q_bytes_first: Selector,
+    q_bytes: Selector,
+    q_digests_first: Selector,
+    q_digests: Selector,
+    q_chunks_first: Selector,
+    q_chunks: Selector,
+}
+
+/// The values of a row of the region
+#[derive(Default, Clone)]
+struct BlobDataRow {
+    byte: Value<Fr>,
+    rlc: Value<Fr>,
+    len: u64,
+    digest_rlc: Value<Fr>,
+    num: u64,
+    counter: u64,
+    run_step: u64,
+    is_padding: bool,
+    is_tx_end: bool,
+    is_chunk_end: bool,
+    is_chunk_valid: bool,
+    is_boundary: bool,
+    slot_index: u64,
+    chunk_index: u64,
+}
+
+impl BlobDataConfig {
+    /// Number of rows of the bytes section, including the sentinel
+    const N_BYTES_ROWS: usize = N_BLOB_BYTES + 1;
+    /// Number of rows of the digests section
+    const N_DIGESTS_ROWS: usize = MAX_BLOB_TXS * DIGEST_LEN;
+
+    /// Build the config. The transactions and the preimages of the commitments are looked up in
+    /// `keccak_table`.
+    pub fn configure(
+        meta: &mut ConstraintSystem<Fr>,
+        challenges: Challenges,
+        keccak_table: &KeccakTable,
+    ) -> Self {
+        let challenges = challenges.exprs(meta);
+
+        let config = Self {
+            byte: meta.advice_column(),
+            rlc: meta.advice_column_in(SecondPhase),
+            len: meta.advice_column(),
+            digest_rlc: meta.advice_column_in(SecondPhase),
+            num: meta.advice_column(),
+            counter: meta.advice_column(),
+            run_step: meta.advice_column(),
+            is_padding: meta.advice_column(),
+            is_tx_end: meta.advice_column(),
+            is_chunk_end: meta.advice_column(),
+            len_inv: meta.advice_column(),
+            is_chunk_valid: meta.advice_column(),
+            is_boundary: meta.fixed_column(),
+            slot_index: meta.fixed_column(),
+            chunk_index: meta.fixed_column(),
+            q_bytes_first: meta.complex_selector(),
+            q_bytes: meta.complex_selector(),
+            q_digests_first: meta.complex_selector(),
+            q_digests: meta.complex_selector(),
+            q_chunks_first: meta.complex_selector(),
+            q_chunks: meta.complex_selector(),
+        };
+        // the blob bytes, the commitments and the padding flags of the chunks are copied in, and
+        // the three sections end with a same num
+        meta.enable_equality(config.byte);
+        meta.enable_equality(config.is_padding);
+        meta.enable_equality(config.num);
+
+        let boolean = |value: Expression<Fr>| value.clone() * (1.expr() - value);
+        let cur = |meta: &mut VirtualCells<Fr>, column: Column<Advice>| {
+            meta.query_advice(column, Rotation::cur())
+        };
+        let prev = |meta: &mut VirtualCells<Fr>, column: Column<Advice>| {
+            meta.query_advice(column, Rotation::prev())
+        };
+
+        // ====================================================
+        // bytes
+        // ====================================================
+        meta.create_gate("blob data: bytes", |meta| {
+            let q_first = meta.query_selector(config.q_bytes_first);
+            let q = meta.query_selector(config.q_bytes);
+
+            let byte = cur(meta, config.byte);
+            let rlc = cur(meta, config.rlc);
+            let len = cur(meta, config.len);
+            let num = cur(meta, config.num);
+            let is_padding = cur(meta, config.is_padding);
+            let is_tx_end = cur(meta, config.is_tx_end);
+            let is_boundary = meta.query_fixed(config.is_boundary, Rotation::cur());
+            let rlc_prev = prev(meta, config.rlc);
+            let len_prev = prev(meta, config.len);
+            let num_prev = prev(meta, config.num);
+            let is_padding_prev = prev(meta, config.is_padding);
+            let is_tx_end_prev = prev(meta, config.is_tx_end);
+
+            // on every row: the padding bytes are zero, and do not end a transaction
+            let every_row = vec![
+                boolean(is_padding.clone()),
+                boolean(is_tx_end.clone()),
+                is_padding.clone() * byte.clone(),
+                is_padding.clone() * is_tx_end,
+            ];
+
+            // the first row starts the first transaction
+            let first_row = vec![
+                rlc.clone() - byte.clone(),
+                len.clone() - 1.expr(),
+                num.clone() - 1.expr(),
+            ];
+
+            // the next rows continue or start a transaction; the padding bytes follow the end of
+            // the last transaction, up to the sentinel
+            let not_tx_end_prev = 1.expr() - is_tx_end_prev.clone();
+            let next_rows = vec![
+                is_padding_prev.clone() * (1.expr() - is_padding.clone()),
+                (1.expr() - is_padding_prev) * is_padding.clone() * not_tx_end_prev.clone(),
+                rlc - (not_tx_end_prev.clone() * rlc_prev * challenges.keccak_input() + byte),
+                len - (not_tx_end_prev * len_prev + 1.expr()),
+                num - (num_prev + is_tx_end_prev),
+                is_boundary * (1.expr() - is_padding),
+            ];
+
+            every_row
+                .into_iter()
+                .map(|constraint| (q_first.clone() + q.clone()) * constraint)
+                .chain(
+                    first_row
+                        .into_iter()
+                        .map(|constraint| q_first.clone() * constraint),
+                )
+                .chain(
+                    next_rows
+                        .into_iter()
+                        .map(|constraint| q.clone() * constraint),
+                )
+                .collect::<Vec<_>>()
+        });
+
+        // each transaction is hashed
+        meta.lookup_any("blob data: transaction digest", |meta| {
+            let is_tx_end = cur(meta, config.is_tx_end);
+            let input = [
+                1.expr(),
+                1.expr(),
+                cur(meta, config.rlc),
+                cur(meta, config.len),
+                cur(meta, config.digest_rlc),
+            ];
+            let table = keccak_table.table_exprs(meta);
+
+            input
+                .into_iter()
+                .map(|value| is_tx_end.clone() * value)
+                .zip(table)
+                .collect()
+        });
+
+        // the digest of the t-th transaction is in the t-th slot
+        meta.lookup_any("blob data: transaction digest slot", |meta| {
+            let is_tx_end = cur(meta, config.is_tx_end);
+            let num = cur(meta, config.num);
+            let digest_rlc = cur(meta, config.digest_rlc);
+            let slot_index = meta.query_fixed(config.slot_index, Rotation::cur());
+
+            vec![
+                (is_tx_end.clone() * num, slot_index),
+                (is_tx_end * digest_rlc.clone(), digest_rlc),
+            ]
+        });
+
+        // ====================================================
+        // digests
+        // ====================================================
+        meta.create_gate("blob data: digests", |meta| {
+            let q_first = meta.query_selector(config.q_digests_first);
+            let q = meta.query_selector(config.q_digests);
+
+            let byte = cur(meta, config.byte);
+            let rlc = cur(meta, config.rlc);
+            let len = cur(meta, config.len);
+            let digest_rlc = cur(meta, config.digest_rlc);
+            let num = cur(meta, config.num);
+            let counter = cur(meta, config.counter);
+            let run_step = cur(meta, config.run_step);
+            let is_padding = cur(meta, config.is_padding);
+            let is_chunk_end = cur(meta, config.is_chunk_end);
+            let is_boundary = meta.query_fixed(config.is_boundary, Rotation::cur());
+            let rlc_prev = prev(meta, config.rlc);
+            let len_prev = prev(meta, config.len);
+            let digest_rlc_prev = prev(meta, config.digest_rlc);
+            let num_prev = prev(meta, config.num);
+            let counter_prev = prev(meta, config.counter);
+            let is_padding_prev = prev(meta, config.is_padding);
+            let is_chunk_end_prev = prev(meta, config.is_chunk_end);
+            let is_boundary_prev = meta.query_fixed(config.is_boundary, Rotation::prev());
+
+            // on every row: a run ends at the end of a non-padding slot
+            let every_row = vec![
+                boolean(is_padding.clone()),
+                boolean(is_chunk_end.clone()),
+                (1.expr() - is_boundary) * is_chunk_end.clone(),
+                is_chunk_end * is_padding.clone(),
+            ];
+
+            // the first row starts the first slot and the first run, whose chunk index is
+            // counter - 1
+            let not_padding = 1.expr() - is_padding.clone();
+            let first_row = vec![
+                digest_rlc.clone() - byte.clone(),
+                rlc.clone() - byte.clone(),
+                len.clone() - 1.expr(),
+                num.clone() - (2.expr() - is_padding.clone()),
+                run_step.clone() - not_padding.clone() * (counter.clone() - 1.expr()),
+            ];
+
+            // the next rows continue or start a slot, and continue or start a run; a slot is all
+            // padding or not, and the padding slots follow the end of the last run
+            let not_boundary_prev = 1.expr() - is_boundary_prev.clone();
+            let not_chunk_end_prev = 1.expr() - is_chunk_end_prev.clone();
+            let next_rows = vec![
+                digest_rlc
+                    - (not_boundary_prev.clone() * digest_rlc_prev * challenges.evm_word()
+                        + byte.clone()),
+                not_boundary_prev.clone() * (is_padding.clone() - is_padding_prev.clone()),
+                not_boundary_prev * (counter.clone() - counter_prev.clone()),
+                is_padding_prev.clone() * not_padding.clone(),
+                (1.expr() - is_padding_prev) * is_padding * not_chunk_end_prev.clone(),
+                not_chunk_end_prev.clone()
+                    * not_padding.clone()
+                    * (counter.clone() - counter_prev.clone()),
+                run_step
+                    - is_chunk_end_prev * not_padding.clone() * (counter - counter_prev - 1.expr()),
+                rlc - (not_chunk_end_prev.clone() * rlc_prev * challenges.keccak_input() + byte),
+                len - (not_chunk_end_prev * len_prev + 1.expr()),
+                num - (num_prev + is_boundary_prev * not_padding),
+            ];
+
+            every_row
+                .into_iter()
+                .map(|constraint| (q_first.clone() + q.clone()) * constraint)
+                .chain(
+                    first_row
+                        .into_iter()
+                        .map(|constraint| q_first.clone() * constraint),
+                )
+                .chain(
+                    next_rows
+                        .into_iter()
+                        .map(|constraint| q.clone() * constraint),
+                )
+                .collect::<Vec<_>>()
+        });
+
+        // the chunk indices of the runs are increasing
+        meta.lookup_any("blob data: run step", |meta| {
+            vec![(
+                cur(meta, config.run_step),
+                meta.query_fixed(config.chunk_index, Rotation::cur()),
+            )]
+        });
+
+        // each run is the preimage of the commitment of its chunk
+        meta.lookup_any("blob data: run", |meta| {
+            let is_chunk_end = cur(meta, config.is_chunk_end);
+            let counter = cur(meta, config.counter);
+            let rlc = cur(meta, config.rlc);
+            let len = cur(meta, config.len);
+            let chunk_index = meta.query_fixed(config.chunk_index, Rotation::cur());
+
+            vec![
+                (is_chunk_end.clone() * counter, chunk_index),
+                (is_chunk_end.clone() * rlc.clone(), rlc),
+                (is_chunk_end * len.clone(), len),
+            ]
+        });
+
+        // ====================================================
+        // chunks
+        // ====================================================
+        meta.create_gate("blob data: chunks", |meta| {
+            let q_first = meta.query_selector(config.q_chunks_first);
+            let q = meta.query_selector(config.q_chunks);
+
+            let byte = cur(meta, config.byte);
+            let rlc = cur(meta, config.rlc);
+            let len = cur(meta, config.len);
+            let len_inv = cur(meta, config.len_inv);
+            let digest_rlc = cur(meta, config.digest_rlc);
+            let num = cur(meta, config.num);
+            let is_padding = cur(meta, config.is_padding);
+            let is_chunk_valid = cur(meta, config.is_chunk_valid);
+            let is_boundary = meta.query_fixed(config.is_boundary, Rotation::cur());
+            let digest_rlc_prev = prev(meta, config.digest_rlc);
+            let num_prev = prev(meta, config.num);
+            let is_padding_prev = prev(meta, config.is_padding);
+            let is_chunk_end_prev = prev(meta, config.is_chunk_end);
+            let is_boundary_prev = meta.query_fixed(config.is_boundary, Rotation::prev());
+
+            // on every row: an empty preimage has a zero RLC, and is the one of a padding chunk
+            let is_len_zero = 1.expr() - len.clone() * len_inv;
+            let every_row = vec![
+                len.clone() * is_len_zero.clone(),
+                rlc * is_len_zero,
+                is_padding.clone() * len.clone(),
+                is_chunk_valid - is_boundary.clone() * (1.expr() - is_padding),
+            ];
+
+            // the first row follows the last slot, which ends a run unless it is padding, and
+            // starts the commitment of the first chunk
+            let first_row = vec![
+                (1.expr() - is_padding_prev) * (1.expr() - is_chunk_end_prev),
+                digest_rlc.clone() - byte.clone(),
+                num.clone() - 1.expr(),
+            ];
+
+            // the next rows continue or start the commitment of a chunk, and count the digests
+            // in the preimages
+            let next_rows = vec![
+                digest_rlc
+                    - ((1.expr() - is_boundary_prev) * digest_rlc_prev * challenges.evm_word()
+                        + byte),
+                (num - num_prev) * DIGEST_LEN.expr() - is_boundary * len,
+            ];
+
+            every_row
+                .into_iter()
+                .map(|constraint| (q_first.clone() + q.clone()) * constraint)
+                .chain(
+                    first_row
+                        .into_iter()
+                        .map(|constraint| q_first.clone() * constraint),
+                )
+                .chain(
+                    next_rows
+                        .into_iter()
+                        .map(|constraint| q.clone() * constraint),
+                )
+                .collect::<Vec<_>>()
+        });
+
+        // the preimage of the commitment of each valid chunk is hashed to the commitment
+        meta.lookup_any("blob data: chunk commitment", |meta| {
+            let is_chunk_valid = cur(meta, config.is_chunk_valid);
+            let input = [
+                1.expr(),
+                1.expr(),
+                cur(meta, config.rlc),
+                cur(meta, config.len),
+                cur(meta, config.digest_rlc),
+            ];
+            let table = keccak_table.table_exprs(meta);
+
+            input
+                .into_iter()
+                .map(|value| is_chunk_valid.clone() * value)
+                .zip(table)
+                .collect()
+        });
+
+        config
+    }
+
+    /// The selectors of the config
+    pub(crate) fn selectors(&self) -> [Selector; 6] {
+        [
+            self.q_bytes_first,
+            self.q_bytes,
+            self.q_digests_first,
+            self.q_digests,
+            self.q_chunks_first,
+            self.q_chunks,
+        ]
+    }
+
+    /// Assign the region binding `blob` to the chunks of the batch.
+    ///
+    /// - `blob_bytes` are the cells of the blob bytes in the preimage of the blob bytes digest;
+    /// - `chunk_commitments` are the cells of the blob data commitment of each chunk in its pi hash
+    ///   preimage;
+    /// - `chunks_are_padding` are the cells of the padding flags of the chunks.
+    pub(crate) fn assign<const N_SNARKS: usize>(
+        &self,
+        layouter: &mut impl Layouter<Fr>,
+        challenges: Challenges<Value<Fr>>,
+        blob: &BlobData,
+        blob_bytes: &[AssignedCell<Fr, Fr>],
+        chunk_commitments: &[&[AssignedCell<Fr, Fr>]],
+        chunks_are_padding: &[AssignedCell<Fr, Fr>],
+    ) -> Result<(), Error> {
+        assert_eq!(blob_bytes.len(), N_BLOB_BYTES);
+        assert_eq!(chunk_commitments.len(), N_SNARKS);
+        assert_eq!(chunks_are_padding.len(), N_SNARKS);
+        assert!(blob.chunk_txs.len() <= N_SNARKS);
+
+        let rows = self.rows::<N_SNARKS>(challenges, blob, chunk_commitments);
+        let mut first_pass = halo2_base::SKIP_FIRST_PASS;
+
+        layouter.assign_region(
+            || "blob data",
+            |mut region| -> Result<(), Error> {
+                if first_pass {
+                    first_pass = false;
+                    return Ok(());
+                }
+
+                let chunks_offset = Self::N_BYTES_ROWS + Self::N_DIGESTS_ROWS;
+                let mut num_cells = vec![];
+                for (offset, row) in rows.iter().enumerate() {
+                    let selector = match offset {
+                        0 => self.q_bytes_first,
+                        o if o < Self::N_BYTES_ROWS => self.q_bytes,
+                        o if o == Self::N_BYTES_ROWS => self.q_digests_first,
+                        o if o < chunks_offset => self.q_digests,
+                        o if o == chunks_offset => self.q_chunks_first,
+                        _ => self.q_chunks,
+                    };
+                    selector.enable(&mut region, offset)?;
+
+                    // the blob bytes and the commitments are copied from the keccak table
+                    if offset < N_BLOB_BYTES {
+                        blob_bytes[offset].copy_advice(
+                            || "blob byte",
+                            &mut region,
+                            self.byte,
+                            offset,
+                        )?;
+                    } else if offset >= chunks_offset {
+                        let index = offset - chunks_offset;
+                        chunk_commitments[index / DIGEST_LEN][index % DIGEST_LEN].copy_advice(
+                            || "blob data commitment byte",
+                            &mut region,
+                            self.byte,
+                            offset,
+                        )?;
+                    } else {
+                        region.assign_advice(|| "byte", self.byte, offset, || row.byte)?;
+                    }
+
+                    // the padding flags of the chunks are copied on their last rows
+                    if offset >= chunks_offset && row.is_boundary {
+                        let index = (offset - chunks_offset) / DIGEST_LEN;
+                        chunks_are_padding[index].copy_advice(
+                            || "chunk is padding",
+                            &mut region,
+                            self.is_padding,
+                            offset,
+                        )?;
+                    } else {
+                        region.assign_advice(
+                            || "is_padding",
+                            self.is_padding,
+                            offset,
+                            || Value::known(Fr::from(row.is_padding as u64)),
+                        )?;
+                    }
+
+                    region.assign_advice(|| "rlc", self.rlc, offset, || row.rlc)?;
+                    region.assign_advice(
+                        || "digest_rlc",
+                        self.digest_rlc,
+                        offset,
+                        || row.digest_rlc,
+                    )?;
+                    let num = region.assign_advice(
+                        || "num",
+                        self.num,
+                        offset,
+                        || Value::known(Fr::from(row.num)),
+                    )?;
+                    if [Self::N_BYTES_ROWS - 1, chunks_offset - 1, rows.len() - 1].contains(&offset)
+                    {
+                        num_cells.push(num);
+                    }
+                    let len = Fr::from(row.len);
+                    for (name, column, value) in [
+                        ("len", self.len, len),
+                        ("counter", self.counter, Fr::from(row.counter)),
+                        ("run_step", self.run_step, Fr::from(row.run_step)),
+                        ("is_tx_end", self.is_tx_end, Fr::from(row.is_tx_end as u64)),
+                        (
+                            "is_chunk_end",
+                            self.is_chunk_end,
+                            Fr::from(row.is_chunk_end as u64),
+                        ),
+                        ("len_inv", self.len_inv, len.invert().unwrap_or(Fr::zero())),
+                        (
+                            "is_chunk_valid",
+                            self.is_chunk_valid,
+                            Fr::from(row.is_chunk_valid as u64),
+                        ),
+                    ] {
+                        region.assign_advice(|| name, column, offset, || Value::known(value))?;
+                    }
+                    for (name, column, value) in [
+                        ("is_boundary", self.is_boundary, row.is_boundary as u64),
+                        ("slot_index", self.slot_index, row.slot_index),
+                        ("chunk_index", self.chunk_index, row.chunk_index),
+                    ] {
+                        region.assign_fixed(
+                            || name,
+                            column,
+                            offset,
+                            || Value::known(Fr::from(value)),
+                        )?;
+                    }
+                }
+
+                // the number of transactions is the number of slots and the number of digests
+                // in the preimages of the commitments
+                for (lhs, rhs) in num_cells.iter().tuple_windows() {
+                    region.constrain_equal(lhs.cell(), rhs.cell())?;
+                }
+
+                Ok(())
+            },
+        )
+    }
+
+    /// The values of the rows of the region
+    fn rows<const N_SNARKS: usize>(
+        &self,
+        challenges: Challenges<Value<Fr>>,
+        blob: &BlobData,
+        chunk_commitments: &[&[AssignedCell<Fr, Fr>]],
+    ) -> Vec<BlobDataRow> {
+        let keccak_input = challenges.keccak_input();
+        let evm_word = challenges.evm_word();
+        let rlc = |bytes: &[u8], randomness: Value<Fr>| {
+            bytes.iter().fold(Value::known(Fr::zero()), |acc, &byte| {
+                acc * randomness + Value::known(Fr::from(byte as u64))
+            })
+        };
+
+        // the transactions, with the 1-based index of their chunk and whether they end it
+        let txs = blob
+            .chunk_txs
+            .iter()
+            .enumerate()
+            .flat_map(|(chunk_index, txs)| {
+                txs.iter()
+                    .enumerate()
+                    .map(move |(i, tx)| (tx, chunk_index as u64 + 1, i == txs.len() - 1))
+            })
+            .collect::<Vec<_>>();
+        let tx_digests = txs
+            .iter()
+            .map(|(tx, _, _)| keccak256(tx))
+            .collect::<Vec<_>>();
+
+        let num_bytes = blob.bytes().len();
+        let mut rows: Vec<BlobDataRow> =
+            Vec::with_capacity(Self::N_BYTES_ROWS + Self::N_DIGESTS_ROWS + N_SNARKS * DIGEST_LEN);
+
+        // ====================================================
+        // bytes
+        // ====================================================
+        let tx_bytes = txs
+            .iter()
+            .zip_eq(tx_digests.iter())
+            .flat_map(|((tx, _, _), digest)| {
+                tx.iter()
+                    .enumerate()
+                    .map(move |(i, &byte)| (byte, (i == tx.len() - 1).then_some(digest)))
+            });
+        let padding = (num_bytes..Self::N_BYTES_ROWS).map(|_| (0, None));
+        for (offset, (byte, tx_digest)) in tx_bytes.chain(padding).enumerate() {
+            let byte_value = Value::known(Fr::from(byte as u64));
+            let row = match rows.last() {
+                Some(prev) if !prev.is_tx_end => BlobDataRow {
+                    rlc: prev.rlc * keccak_input + byte_value,
+                    len: prev.len + 1,
+                    num: prev.num,
+                    ..Default::default()
+                },
+                Some(prev) => BlobDataRow {
+                    rlc: byte_value,
+                    len: 1,
+                    num: prev.num + 1,
+                    ..Default::default()
+                },
+                None => BlobDataRow {
+                    rlc: byte_value,
+                    len: 1,
+                    num: 1,
+                    ..Default::default()
+                },
+            };
+            rows.push(BlobDataRow {
+                byte: byte_value,
+                digest_rlc: tx_digest
+                    .map_or(Value::known(Fr::zero()), |digest| rlc(digest, evm_word)),
+                is_padding: offset >= num_bytes,
+                is_tx_end: tx_digest.is_some(),
+                is_boundary: offset == Self::N_BYTES_ROWS - 1,
+                ..row
+            });
+        }
+
+        // ====================================================
+        // digests
+        // ====================================================
+        for slot in 0..MAX_BLOB_TXS {
+            let tx = txs.get(slot);
+            for i in 0..DIGEST_LEN {
+                let byte = tx_digests.get(slot).map_or(0, |digest| digest[i]);
+                let byte_value = Value::known(Fr::from(byte as u64));
+                let is_padding = tx.is_none();
+                let counter = tx.map_or(0, |&(_, chunk_index, _)| chunk_index);
+                let row = if slot == 0 && i == 0 {
+                    BlobDataRow {
+                        digest_rlc: byte_value,
+                        rlc: byte_value,
+                        len: 1,
+                        num: 2 - is_padding as u64,
+                        run_step: if is_padding { 0 } else { counter - 1 },
+                        ..Default::default()
+                    }
+                } else {
+                    let prev = rows.last().unwrap(); // safe unwrap
+                    BlobDataRow {
+                        digest_rlc: if prev.is_boundary {
+                            byte_value
+                        } else {
+                            prev.digest_rlc * evm_word + byte_value
+                        },
+                        rlc: if prev.is_chunk_end {
+                            byte_value
+                        } else {
+                            prev.rlc * keccak_input + byte_value
+                        },
+                        len: if prev.is_chunk_end { 1 } else { prev.len + 1 },
+                        num: prev.num + (prev.is_boundary && !is_padding) as u64,
+                        run_step: if prev.is_chunk_end && !is_padding {
+                            counter - prev.counter - 1
+                        } else {
+                            0
+                        },
+                        ..Default::default()
+                    }
+                };
+                let is_boundary = i == DIGEST_LEN - 1;
+                rows.push(BlobDataRow {
+                    byte: byte_value,
+                    counter,
+                    is_padding,
+                    is_chunk_end: is_boundary
+                        && tx.map_or(false, |&(_, _, is_chunk_end)| is_chunk_end),
+                    is_boundary,
+                    slot_index: if is_boundary { slot as u64 + 1 } else { 0 },
+                    ..row
+                });
+            }
+        }
+
+        // ====================================================
+        // chunks
+        // ====================================================
+        let mut num = 1;
+        for (chunk_index, commitment) in chunk_commitments.iter().enumerate() {
+            // the preimage of the commitment is empty for a padding chunk
+            let preimage = blob
+                .chunk_txs
+                .get(chunk_index)
+                .map(|txs| txs.iter().flat_map(|tx| keccak256(tx)).collect::<Vec<_>>())
+                .unwrap_or_default();
+            let is_padding = chunk_index >= blob.chunk_txs.len();
+            let mut digest_rlc = Value::known(Fr::zero());
+            for (i, byte) in commitment.iter().enumerate() {
+                let byte_value = byte.value().copied();
+                digest_rlc = digest_rlc * evm_word + byte_value;
+                let is_boundary = i == DIGEST_LEN - 1;
+                if is_boundary {
+                    num += preimage.len() as u64 / DIGEST_LEN as u64;
+                }
+                rows.push(BlobDataRow {
+                    byte: byte_value,
+                    rlc: rlc(&preimage, keccak_input),
+                    len: preimage.len() as u64,
+                    digest_rlc,
+                    num,
+                    is_padding,
+                    is_chunk_valid: is_boundary && !is_padding,
+                    is_boundary,
+                    chunk_index: if is_boundary {
+                        chunk_index as u64 + 1
+                    } else {
+                        0
+                    },
+                    ..Default::default()
+                });
+            }
+        }
+
+        rows
+    }
+}
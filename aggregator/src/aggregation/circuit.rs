@@ -1,4 +1,5 @@
 use ark_std::{end_timer, start_timer};
+use eth_types::pi_schema::PiField;
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner, Value},
     halo2curves::bn256::{Bn256, Fr, G1Affine},
@@ -22,43 +23,51 @@ use snark_verifier::{
 };
 #[cfg(not(feature = "disable_proof_aggregation"))]
 use snark_verifier_sdk::{aggregate, flatten_accumulator};
+
+#[cfg(not(feature = "disable_proof_aggregation"))]
+use super::barycentric::AssignedBarycentricEvaluation;
 use snark_verifier_sdk::{CircuitExt, Snark, SnarkWitness};
 use zkevm_circuits::util::Challenges;
 
 use crate::{
     batch::BatchHash,
-    constants::{ACC_LEN, DIGEST_LEN, MAX_AGG_SNARKS},
+    constants::{ACC_LEN, DIGEST_LEN},
     core::{assign_batch_hashes, extract_proof_and_instances_with_pairing_check},
-    util::parse_hash_digest_cells,
-    ConfigParams,
+    util::{
+        parse_blob_hash_digest_cells, parse_blob_hash_preimage_cells, parse_hash_digest_cells,
+        parse_hash_preimage_cells,
+    },
+    ConfigParams, BLOB_PI_LEN,
 };
 
 use super::AggregationConfig;
 
-/// Aggregation circuit that does not re-expose any public inputs from aggregated snarks
+/// Aggregation circuit that does not re-expose any public inputs from aggregated snarks.
+/// The circuit is generated for a fixed number of snarks, N_SNARKS.
 #[derive(Clone)]
-pub struct AggregationCircuit {
+pub struct AggregationCircuit<const N_SNARKS: usize> {
     pub svk: KzgSuccinctVerifyingKey<G1Affine>,
     // the input snarks for the aggregation circuit
-    // it is padded already so it will have a fixed length of MAX_AGG_SNARKS
+    // it is padded already so it will have a fixed length of N_SNARKS
     pub snarks_with_padding: Vec<SnarkWitness>,
     // the public instance for this circuit consists of
     // - an accumulator (12 elements)
     // - the batch's public_input_hash (32 elements)
+    // - the batch's blob point evaluation, if any (96 elements)
     pub flattened_instances: Vec<Fr>,
     // accumulation scheme proof, private input
     pub as_proof: Value<Vec<u8>>,
     // batch hash circuit for which the snarks are generated
     // the chunks in this batch are also padded already
-    pub batch_hash: BatchHash,
+    pub batch_hash: BatchHash<N_SNARKS>,
 }
 
-impl AggregationCircuit {
+impl<const N_SNARKS: usize> AggregationCircuit<N_SNARKS> {
     pub fn new(
         params: &ParamsKZG<Bn256>,
         snarks_with_padding: &[Snark],
         rng: impl Rng + Send,
-        batch_hash: BatchHash,
+        batch_hash: BatchHash<N_SNARKS>,
     ) -> Result<Self, snark_verifier::Error> {
         let timer = start_timer!(|| "generate aggregation circuit");
 
@@ -93,12 +102,13 @@ impl AggregationCircuit {
         let (as_proof, acc_instances) =
             extract_proof_and_instances_with_pairing_check(params, snarks_with_padding, rng)?;
 
-        // extract batch's public input hash
+        // extract batch's public input hash, and blob point evaluation if any
         let public_input_hash = &batch_hash.instances_exclude_acc()[0];
 
         // the public instance for this circuit consists of
         // - an accumulator (12 elements)
         // - the batch's public_input_hash (32 elements)
+        // - the batch's blob point evaluation, if any (96 elements)
         let flattened_instances: Vec<Fr> =
             [acc_instances.as_slice(), public_input_hash.as_slice()].concat();
 
@@ -117,7 +127,7 @@ impl AggregationCircuit {
     }
 }
 
-impl Circuit<Fr> for AggregationCircuit {
+impl<const N_SNARKS: usize> Circuit<Fr> for AggregationCircuit<N_SNARKS> {
     type Config = (AggregationConfig, Challenges);
     type FloorPlanner = SimpleFloorPlanner;
     fn without_witnesses(&self) -> Self {
@@ -161,7 +171,7 @@ impl Circuit<Fr> for AggregationCircuit {
         // Step 1: snark aggregation circuit
         // ==============================================
         #[cfg(not(feature = "disable_proof_aggregation"))]
-        let (accumulator_instances, snark_inputs) = {
+        let (accumulator_instances, snark_inputs, barycentric) = {
             config
                 .range()
                 .load_lookup_table(&mut layouter)
@@ -169,12 +179,19 @@ impl Circuit<Fr> for AggregationCircuit {
 
             let mut first_pass = halo2_base::SKIP_FIRST_PASS;
 
-            let (accumulator_instances, snark_inputs) = layouter.assign_region(
+            let (accumulator_instances, snark_inputs, barycentric) = layouter.assign_region(
                 || "aggregation",
-                |region| -> Result<(Vec<AssignedValue<Fr>>, Vec<AssignedValue<Fr>>), Error> {
+                |region| -> Result<
+                    (
+                        Vec<AssignedValue<Fr>>,
+                        Vec<AssignedValue<Fr>>,
+                        Option<AssignedBarycentricEvaluation>,
+                    ),
+                    Error,
+                > {
                     if first_pass {
                         first_pass = false;
-                        return Ok((vec![], vec![]));
+                        return Ok((vec![], vec![], None));
                     }
 
                     // stores accumulators for all snarks, including the padded ones
@@ -222,16 +239,23 @@ impl Circuit<Fr> for AggregationCircuit {
                             .flat_map(|instance_column| instance_column.iter().skip(ACC_LEN)),
                     );
 
+                    // evaluate the blob polynomial at the challenge point; it shares the context
+                    // of the aggregation so that the range checks are finalized together
+                    let barycentric =
+                        self.batch_hash.blob.as_ref().map(|(blob, _)| {
+                            config.barycentric.assign(&mut loader.ctx_mut(), blob)
+                        });
+
                     config.range().finalize(&mut loader.ctx_mut());
 
                     loader.ctx_mut().print_stats(&["Range"]);
 
-                    Ok((accumulator_instances, snark_inputs))
+                    Ok((accumulator_instances, snark_inputs, barycentric))
                 },
             )?;
 
-            assert_eq!(snark_inputs.len(), MAX_AGG_SNARKS * DIGEST_LEN);
-            (accumulator_instances, snark_inputs)
+            assert_eq!(snark_inputs.len(), N_SNARKS * DIGEST_LEN);
+            (accumulator_instances, snark_inputs, barycentric)
         };
         end_timer!(timer);
         // ==============================================
@@ -242,7 +266,8 @@ impl Circuit<Fr> for AggregationCircuit {
 
         let timer = start_timer!(|| "load aux table");
 
-        let hash_digest_cells = {
+        let with_blob = self.batch_hash.blob.is_some();
+        let (hash_cells, chunks_are_padding) = {
             config
                 .keccak_circuit_config
                 .load_aux_tables(&mut layouter)?;
@@ -251,14 +276,24 @@ impl Circuit<Fr> for AggregationCircuit {
             let timer = start_timer!(|| "extract hash");
             // orders:
             // - batch_public_input_hash
-            // - chunk\[i\].piHash for i in \[0, MAX_AGG_SNARKS)
+            // - chunk\[i\].piHash for i in \[0, N_SNARKS)
+            // - blob bytes and challenge preimage, if the batch has a blob
             // - batch_data_hash_preimage
             let preimages = self.batch_hash.extract_hash_preimages();
+            let num_blob_hashes = if with_blob { 2 } else { 0 };
             assert_eq!(
                 preimages.len(),
-                MAX_AGG_SNARKS + 2,
+                N_SNARKS + 2 + num_blob_hashes,
                 "error extracting preimages"
             );
+            // the transactions of the blob and the preimages of the blob data commitments of the
+            // chunks are only looked up
+            let lookup_preimages = self
+                .batch_hash
+                .blob
+                .as_ref()
+                .map(|(blob, _)| blob.extract_tx_hash_preimages())
+                .unwrap_or_default();
             end_timer!(timer);
 
             let timer = start_timer!(|| ("assign hash cells").to_string());
@@ -268,20 +303,23 @@ impl Circuit<Fr> for AggregationCircuit {
                 .iter()
                 .map(|chunk| !chunk.is_padding)
                 .collect::<Vec<_>>();
-            let hash_digest_cells = assign_batch_hashes(
+            let hash_cells = assign_batch_hashes::<N_SNARKS>(
                 &config,
                 &mut layouter,
                 challenges,
                 &chunks_are_valid,
+                self.batch_hash.pi_schema,
+                with_blob,
                 &preimages,
+                &lookup_preimages,
             )
             .map_err(|_e| Error::ConstraintSystemFailure)?;
             end_timer!(timer);
-            hash_digest_cells
+            hash_cells
         };
         // digests
         let (batch_pi_hash_digest, chunk_pi_hash_digests, _potential_batch_data_hash_digest) =
-            parse_hash_digest_cells(&hash_digest_cells);
+            parse_hash_digest_cells::<N_SNARKS>(&hash_cells.hash_output_cells, with_blob);
 
         // ==============================================
         // step 3: assert public inputs to the snarks are correct
@@ -313,7 +351,7 @@ impl Circuit<Fr> for AggregationCircuit {
                     return Ok(());
                 }
 
-                for i in 0..MAX_AGG_SNARKS {
+                for i in 0..N_SNARKS {
                     for j in 0..4 {
                         for k in 0..8 {
                             let mut t1 = Fr::default();
@@ -343,6 +381,71 @@ impl Circuit<Fr> for AggregationCircuit {
             },
         )?;
 
+        // ==============================================
+        // step 3.1: assert the blob evaluated is the one hashed to the challenge
+        // ==============================================
+        #[cfg(not(feature = "disable_proof_aggregation"))]
+        if let Some(barycentric) = barycentric.as_ref() {
+            let (blob_bytes, _challenge_preimage) = parse_blob_hash_preimage_cells::<N_SNARKS>(
+                &hash_cells.hash_input_cells,
+                self.batch_hash.pi_schema,
+            );
+            let (_blob_bytes_digest, challenge_digest) =
+                parse_blob_hash_digest_cells::<N_SNARKS>(&hash_cells.hash_output_cells);
+
+            layouter.assign_region(
+                || "blob checks",
+                |mut region| -> Result<(), Error> {
+                    // the blob bytes are evaluated as they are hashed
+                    for (byte, hashed) in barycentric.blob_bytes.iter().zip_eq(blob_bytes.iter()) {
+                        region.constrain_equal(byte.cell(), hashed.cell())?;
+                    }
+                    // the challenge point is reduced from the challenge digest
+                    for i in 0..4 {
+                        for j in 0..8 {
+                            // in the keccak table, the input and output data have different
+                            // endianess
+                            region.constrain_equal(
+                                barycentric.challenge_digest[i * 8 + j].cell(),
+                                challenge_digest[(3 - i) * 8 + j].cell(),
+                            )?;
+                        }
+                    }
+
+                    Ok(())
+                },
+            )?;
+        }
+
+        // ==============================================
+        // step 3.2: assert the blob bytes are the L2 transactions of the chunks
+        // ==============================================
+        if let Some((blob, _)) = self.batch_hash.blob.as_ref() {
+            let pi_schema = self.batch_hash.pi_schema;
+            let (blob_bytes, _challenge_preimage) =
+                parse_blob_hash_preimage_cells::<N_SNARKS>(&hash_cells.hash_input_cells, pi_schema);
+            let (_batch_pi_hash_preimage, chunk_pi_hash_preimages, _) =
+                parse_hash_preimage_cells::<N_SNARKS>(
+                    &hash_cells.hash_input_cells,
+                    pi_schema,
+                    with_blob,
+                );
+            let index = pi_schema.index(PiField::BlobDataCommitment);
+            let chunk_commitments = chunk_pi_hash_preimages
+                .iter()
+                .map(|preimage| &preimage[index..index + DIGEST_LEN])
+                .collect::<Vec<_>>();
+
+            config.blob_data.assign::<N_SNARKS>(
+                &mut layouter,
+                challenges,
+                blob,
+                blob_bytes,
+                &chunk_commitments,
+                &chunks_are_padding,
+            )?;
+        }
+
         // ==============================================
         // step 4: assert public inputs to the aggregator circuit are correct
         // ==============================================
@@ -372,20 +475,60 @@ impl Circuit<Fr> for AggregationCircuit {
             }
         }
 
+        // blob point evaluation
+        if with_blob {
+            let (_blob_bytes, challenge_preimage) = parse_blob_hash_preimage_cells::<N_SNARKS>(
+                &hash_cells.hash_input_cells,
+                self.batch_hash.pi_schema,
+            );
+            // the versioned hash is the second half of the challenge preimage
+            for (i, cell) in challenge_preimage[DIGEST_LEN..].iter().enumerate() {
+                layouter.constrain_instance(
+                    cell.cell(),
+                    config.instance,
+                    ACC_LEN + DIGEST_LEN + i,
+                )?;
+            }
+
+            #[cfg(not(feature = "disable_proof_aggregation"))]
+            {
+                let barycentric = barycentric.as_ref().expect("blob is evaluated");
+                for (i, cell) in barycentric
+                    .challenge
+                    .iter()
+                    .chain(barycentric.evaluation.iter())
+                    .enumerate()
+                {
+                    layouter.constrain_instance(
+                        cell.cell(),
+                        config.instance,
+                        ACC_LEN + 2 * DIGEST_LEN + i,
+                    )?;
+                }
+            }
+        }
+
         end_timer!(witness_time);
         Ok(())
     }
 }
 
-impl CircuitExt<Fr> for AggregationCircuit {
+impl<const N_SNARKS: usize> CircuitExt<Fr> for AggregationCircuit<N_SNARKS> {
     fn num_instance(&self) -> Vec<usize> {
         // 12 elements from accumulator
         // 32 elements from batch's public_input_hash
-        vec![ACC_LEN + DIGEST_LEN]
+        // 96 elements from batch's blob point evaluation, if any
+        let blob_pi_len = if self.batch_hash.blob.is_some() {
+            BLOB_PI_LEN
+        } else {
+            0
+        };
+        vec![ACC_LEN + DIGEST_LEN + blob_pi_len]
     }
 
     // 12 elements from accumulator
     // 32 elements from batch's public_input_hash
+    // 96 elements from batch's blob point evaluation, if any
     fn instances(&self) -> Vec<Vec<Fr>> {
         vec![self.flattened_instances.clone()]
     }
@@ -398,6 +541,7 @@ impl CircuitExt<Fr> for AggregationCircuit {
     fn selectors(config: &Self::Config) -> Vec<Selector> {
         // - advice columns from flex gate
         // - selector from RLC gate
+        // - selectors from blob data config
         config.0.flex_gate().basic_gates[0]
             .iter()
             .map(|gate| gate.q_enable)
@@ -410,6 +554,7 @@ impl CircuitExt<Fr> for AggregationCircuit {
                 .iter()
                 .cloned(),
             )
+            .chain(config.0.blob_data.selectors())
             .collect()
     }
 }
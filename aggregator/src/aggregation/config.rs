@@ -19,7 +19,7 @@ use zkevm_circuits::{
 use crate::{
     constants::{BITS, LIMBS},
     param::ConfigParams,
-    RlcConfig,
+    BarycentricEvaluationConfig, BlobDataConfig, RlcConfig,
 };
 
 #[derive(Debug, Clone)]
@@ -33,10 +33,15 @@ pub struct AggregationConfig {
     pub keccak_circuit_config: KeccakCircuitConfig<Fr>,    
     /// RLC config
     pub rlc_config: RlcConfig,
+    /// Barycentric evaluation config, for the blob of a batch
+    pub barycentric: BarycentricEvaluationConfig,
+    /// Blob data config, binding the blob of a batch to the L2 transactions of its chunks
+    pub blob_data: BlobDataConfig,
     /// Instance for public input; stores
     /// - accumulator from aggregation (12 elements)
     /// - batch_public_input_hash (32 elements)
     /// - the number of valid SNARKs (1 element)
+    /// - the blob point evaluation, if any (96 elements)
     pub instance: Column<Instance>,
 }
 
@@ -83,6 +88,14 @@ impl AggregationConfig {
             params.degree as usize,
         );
 
+        // blob point evaluation shares the columns of the base field chip
+        let barycentric = BarycentricEvaluationConfig::construct(base_field_config.range.clone());
+
+        // the transactions of the blob and the preimages of the blob data commitments of the
+        // chunks are looked up in the keccak table
+        let blob_data =
+            BlobDataConfig::configure(meta, challenges, &keccak_circuit_config.keccak_table);
+
         let columns = keccak_circuit_config.cell_manager.columns();
         log::info!("keccak uses {} columns", columns.len(),);
 
@@ -101,12 +114,15 @@ impl AggregationConfig {
         // - the accumulator
         // - the batch public input hash
         // - the number of valid SNARKs
+        // - the blob point evaluation, if any
         let instance = meta.instance_column();
         meta.enable_equality(instance);
 
         Self {
             base_field_config,
             rlc_config,
+            barycentric,
+            blob_data,
             keccak_circuit_config,
             instance,
         }
@@ -10,26 +10,20 @@ use crate::{constants::LOG_DEGREE, util::assert_equal};
 
 use super::RlcConfig;
 
+/// Number of rows of the fixed column that are taken by the constants assigned in `init`
+const FIXED_CONSTANTS: usize = 5;
+
 impl RlcConfig {
     /// initialize the chip with fixed cells
     pub(crate) fn init(&self, region: &mut Region<Fr>) -> Result<(), Error> {
         region.assign_fixed(|| "const zero", self.fixed, 0, || Value::known(Fr::zero()))?;
         region.assign_fixed(|| "const one", self.fixed, 1, || Value::known(Fr::one()))?;
         region.assign_fixed(|| "const two", self.fixed, 2, || Value::known(Fr::from(2)))?;
-        region.assign_fixed(|| "const five", self.fixed, 3, || Value::known(Fr::from(5)))?;
-        region.assign_fixed(|| "const nine", self.fixed, 4, || Value::known(Fr::from(9)))?;
-        region.assign_fixed(|| "const 13", self.fixed, 5, || Value::known(Fr::from(13)))?;
-        region.assign_fixed(|| "const 32", self.fixed, 6, || Value::known(Fr::from(32)))?;
-        region.assign_fixed(
-            || "const 136",
-            self.fixed,
-            7,
-            || Value::known(Fr::from(136)),
-        )?;
+        region.assign_fixed(|| "const 32", self.fixed, 3, || Value::known(Fr::from(32)))?;
         region.assign_fixed(
             || "const 2^32",
             self.fixed,
-            8,
+            4,
             || Value::known(Fr::from(1 << 32)),
         )?;
         Ok(())
@@ -62,55 +56,19 @@ impl RlcConfig {
         }
     }
 
-    #[inline]
-    pub(crate) fn five_cell(&self, region_index: RegionIndex) -> Cell {
-        Cell {
-            region_index,
-            row_offset: 3,
-            column: self.fixed.into(),
-        }
-    }
-
-    #[inline]
-    pub(crate) fn nine_cell(&self, region_index: RegionIndex) -> Cell {
-        Cell {
-            region_index,
-            row_offset: 4,
-            column: self.fixed.into(),
-        }
-    }
-
-    #[inline]
-    pub(crate) fn thirteen_cell(&self, region_index: RegionIndex) -> Cell {
-        Cell {
-            region_index,
-            row_offset: 5,
-            column: self.fixed.into(),
-        }
-    }
-
     #[inline]
     pub(crate) fn thirty_two_cell(&self, region_index: RegionIndex) -> Cell {
         Cell {
             region_index,
-            row_offset: 6,
-            column: self.fixed.into(),
-        }
-    }
-    #[inline]
-    pub(crate) fn one_hundred_and_thirty_six_cell(&self, region_index: RegionIndex) -> Cell {
-        Cell {
-            region_index,
-            row_offset: 7,
+            row_offset: 3,
             column: self.fixed.into(),
         }
     }
-
     #[inline]
     pub(crate) fn two_to_thirty_two_cell(&self, region_index: RegionIndex) -> Cell {
         Cell {
             region_index,
-            row_offset: 8,
+            row_offset: 4,
             column: self.fixed.into(),
         }
     }
@@ -131,6 +89,27 @@ impl RlcConfig {
         res
     }
 
+    /// Load a constant that is not one of the cells assigned in `init`.
+    /// The constant is assigned to the `index`-th free row of the fixed column,
+    /// so each constant loaded into a region needs a distinct index.
+    pub(crate) fn load_constant(
+        &self,
+        region: &mut Region<Fr>,
+        index: usize,
+        f: &Fr,
+        offset: &mut usize,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        let fixed = region.assign_fixed(
+            || "load constant",
+            self.fixed,
+            FIXED_CONSTANTS + index,
+            || Value::known(*f),
+        )?;
+        let res = self.load_private(region, f, offset)?;
+        region.constrain_equal(fixed.cell(), res.cell())?;
+        Ok(res)
+    }
+
     pub(crate) fn read_challenge(
         &self,
         region: &mut Region<Fr>,
@@ -1,40 +1,180 @@
 //! This module implements related functions that aggregates public inputs of many chunks into a
 //! single one.
 
-use eth_types::{Field, H256};
+use eth_types::{
+    pi_schema::{PiBatchLayout, PiBatchSource, PiField, PiSchema},
+    Field, H256,
+};
 use ethers_core::utils::keccak256;
 
-use crate::constants::MAX_AGG_SNARKS;
+use crate::{
+    blob::{BlobData, BlobPointEvaluation},
+    constants::AGG_SNARKS_TIERS,
+};
 
 use super::chunk::ChunkHash;
 
-#[derive(Default, Debug, Clone)]
-/// A batch is a set of MAX_AGG_SNARKS num of continuous chunks
+/// Return the smallest tier in [`AGG_SNARKS_TIERS`] that is able to hold `num_chunks` chunks,
+/// or `None` if there are more chunks than the largest tier supports.
+pub fn agg_snarks_tier(num_chunks: usize) -> Option<usize> {
+    AGG_SNARKS_TIERS
+        .iter()
+        .find(|&&tier| tier >= num_chunks)
+        .copied()
+}
+
+/// Return the chunk hash that a batch of `chunks` (without padding) is equivalent to, i.e., the
+/// chunk from `chunks[0].prev_state_root` to the last chunk's `post_state_root` whose data hash is
+/// the batch's data hash.
+///
+/// Its public input hash is the batch's public input hash, so the snark of a batch can be
+/// aggregated again as a chunk of a parent batch. A batch aggregated again as a chunk is not
+/// posted as a blob, so its blob data commitment is zero.
+pub fn chunk_hash_of_batch(chunks: &[ChunkHash]) -> ChunkHash {
+    assert!(!chunks.is_empty(), "input chunk slice is empty");
+
+    let preimage = chunks
+        .iter()
+        .flat_map(|chunk_hash| chunk_hash.data_hash.0.iter())
+        .cloned()
+        .collect::<Vec<_>>();
+    let last = chunks.last().unwrap(); // safe unwrap
+
+    ChunkHash {
+        chain_id: chunks[0].chain_id,
+        prev_state_root: chunks[0].prev_state_root,
+        post_state_root: last.post_state_root,
+        withdraw_root: last.withdraw_root,
+        data_hash: keccak256(preimage).into(),
+        is_padding: false,
+        pi_schema: chunks[0].pi_schema,
+        l1_msg_queue_hash: last.l1_msg_queue_hash,
+        blob_data_commitment: H256::zero(),
+    }
+}
+
+/// Return the data hash of a batch of `chunks` (without padding), as laid out by `layout`.
+pub fn batch_data_hash(chunks: &[ChunkHash], layout: PiBatchLayout) -> H256 {
+    let arity = match layout {
+        PiBatchLayout::Flat => return chunk_hash_of_batch(chunks).data_hash,
+        PiBatchLayout::Tree { arity } => arity,
+    };
+    assert!(!chunks.is_empty(), "input chunk slice is empty");
+
+    // Replay the tree aggregation, each node being the chunk hash of its children.
+    let mut levels: Vec<Vec<ChunkHash>> = vec![vec![]];
+    let merge = |levels: &mut Vec<Vec<ChunkHash>>, finalize: bool| {
+        while let Some(level) = agg_tree_next_merge_level(
+            &levels.iter().map(Vec::len).collect::<Vec<_>>(),
+            arity,
+            finalize,
+        ) {
+            let node = chunk_hash_of_batch(&levels[level]);
+            levels[level].clear();
+            if levels.len() == level + 1 {
+                levels.push(vec![]);
+            }
+            levels[level + 1].push(node);
+        }
+    };
+    for chunk in chunks {
+        levels[0].push(*chunk);
+        merge(&mut levels, false);
+    }
+    merge(&mut levels, true);
+
+    levels.last().unwrap()[0].data_hash // safe unwrap
+}
+
+/// Return the level of which the pending nodes of a tree aggregation of `arity` nodes at a time
+/// should be aggregated next, given the number of pending nodes of each level, level 0 being the
+/// chunks.
+/// - a level is aggregated as soon as it has `arity` nodes;
+/// - on finalizing, the lowest non-empty level is aggregated until a single node (the root) is
+///   left, which is not a chunk.
+pub fn agg_tree_next_merge_level(
+    level_lens: &[usize],
+    arity: usize,
+    finalize: bool,
+) -> Option<usize> {
+    if let Some(level) = level_lens.iter().position(|&len| len >= arity) {
+        return Some(level);
+    }
+    if !finalize {
+        return None;
+    }
+
+    let mut non_empty_levels = level_lens
+        .iter()
+        .enumerate()
+        .filter(|(_, &len)| len > 0)
+        .map(|(level, _)| level);
+    match (non_empty_levels.next(), non_empty_levels.next()) {
+        (None, _) => None,
+        (Some(level), None) if level > 0 && level_lens[level] == 1 => None,
+        (Some(level), _) => Some(level),
+    }
+}
+
+/// Build the public input hash preimage of a batch of `chunks_with_padding` whose data hash is
+/// `data_hash` and whose blob bytes digest is `blob_bytes_digest` (zero without a blob), where each
+/// field is taken from the chunks as its
+/// [`batch_source`](eth_types::pi_schema::PiField::batch_source).
+fn batch_pi_hash_preimage(
+    chunks_with_padding: &[ChunkHash],
+    data_hash: &H256,
+    blob_bytes_digest: &H256,
+) -> Vec<u8> {
+    let first = chunks_with_padding[0].pi_values();
+    let last = chunks_with_padding.last().unwrap().pi_values(); // safe unwrap
+
+    chunks_with_padding[0]
+        .pi_schema
+        .fields()
+        .iter()
+        .flat_map(|&field| match field.batch_source() {
+            PiBatchSource::Same | PiBatchSource::First => first.field_bytes(field),
+            PiBatchSource::Last => last.field_bytes(field),
+            PiBatchSource::Hash => data_hash.as_bytes().to_vec(),
+            PiBatchSource::Blob => blob_bytes_digest.as_bytes().to_vec(),
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+/// A batch is a set of N_SNARKS num of continuous chunks
 /// - the first k chunks are from real traces
-/// - the last (#MAX_AGG_SNARKS-k) chunks are from empty traces
+/// - the last (#N_SNARKS-k) chunks are from empty traces
 /// A BatchHash consists of 2 hashes.
 /// - batch_pi_hash   := keccak(chain_id || chunk_0.prev_state_root || chunk_k-1.post_state_root ||
-///   chunk_k-1.withdraw_root || batch_data_hash)
+///   chunk_k-1.withdraw_root || batch_data_hash) for [`PiSchema::V1`], the fields added by the
+///   later schemas are taken from chunk_k-1
 /// - batch_data_hash := keccak(chunk_0.data_hash || ... || chunk_k-1.data_hash)
-pub struct BatchHash {
+///
+/// A batch posted as an EIP-4844 blob also commits to the point evaluation of its [`BlobData`].
+pub struct BatchHash<const N_SNARKS: usize> {
     pub(crate) chain_id: u64,
+    // layout of the public input hash preimages of the batch and its chunks
+    pub(crate) pi_schema: PiSchema,
     // chunks with padding.
     // - the first [0..number_of_valid_chunks) are real ones
-    // - the last [number_of_valid_chunks, MAX_AGG_SNARKS) are padding
-    pub(crate) chunks_with_padding: [ChunkHash; MAX_AGG_SNARKS],
+    // - the last [number_of_valid_chunks, N_SNARKS) are padding
+    pub(crate) chunks_with_padding: [ChunkHash; N_SNARKS],
     pub(crate) data_hash: H256,
     pub(crate) public_input_hash: H256,
     pub(crate) number_of_valid_chunks: usize,
+    // the blob of the batch and its point evaluation, if the batch is posted as a blob
+    pub(crate) blob: Option<(BlobData, BlobPointEvaluation)>,
 }
 
-impl BatchHash {
-    /// Build Batch hash from an ordered list of #MAX_AGG_SNARKS of chunks.
+impl<const N_SNARKS: usize> BatchHash<N_SNARKS> {
+    /// Build Batch hash from an ordered list of #N_SNARKS of chunks.
     #[allow(dead_code)]
     pub fn construct(chunks_with_padding: &[ChunkHash]) -> Self {
         assert_eq!(
             chunks_with_padding.len(),
-            MAX_AGG_SNARKS,
-            "input chunk slice does not match MAX_AGG_SNARKS"
+            N_SNARKS,
+            "input chunk slice does not match N_SNARKS"
         );
 
         let number_of_valid_chunks = match chunks_with_padding
@@ -43,7 +183,7 @@ impl BatchHash {
             .find(|(_index, chunk)| chunk.is_padding)
         {
             Some((index, _)) => index,
-            None => MAX_AGG_SNARKS,
+            None => N_SNARKS,
         };
 
         assert_ne!(
@@ -61,11 +201,15 @@ impl BatchHash {
         // sanity checks
         // ========================
         // todo: return errors instead
-        for i in 0..MAX_AGG_SNARKS - 1 {
+        for i in 0..N_SNARKS - 1 {
             assert_eq!(
                 chunks_with_padding[i].chain_id,
                 chunks_with_padding[i + 1].chain_id,
             );
+            assert_eq!(
+                chunks_with_padding[i].pi_schema,
+                chunks_with_padding[i + 1].pi_schema,
+            );
             if chunks_with_padding[i + 1].is_padding {
                 assert_eq!(
                     chunks_with_padding[i + 1].data_hash,
@@ -83,6 +227,14 @@ impl BatchHash {
                     chunks_with_padding[i + 1].withdraw_root,
                     chunks_with_padding[i].withdraw_root
                 );
+                assert_eq!(
+                    chunks_with_padding[i + 1].l1_msg_queue_hash,
+                    chunks_with_padding[i].l1_msg_queue_hash
+                );
+                assert_eq!(
+                    chunks_with_padding[i + 1].blob_data_commitment,
+                    chunks_with_padding[i].blob_data_commitment
+                );
             } else {
                 assert_eq!(
                     chunks_with_padding[i].post_state_root,
@@ -101,43 +253,93 @@ impl BatchHash {
             .collect::<Vec<_>>();
         let data_hash = keccak256(preimage);
 
-        // public input hash is build as
+        // public input hash is build as (for PiSchema::V1)
         //  keccak(
         //      chain_id ||
         //      chunk[0].prev_state_root ||
         //      chunk[k-1].post_state_root ||
         //      chunk[k-1].withdraw_root ||
         //      batch_data_hash )
-        let preimage = [
-            chunks_with_padding[0].chain_id.to_be_bytes().as_ref(),
-            chunks_with_padding[0].prev_state_root.as_bytes(),
-            chunks_with_padding[MAX_AGG_SNARKS - 1]
-                .post_state_root
-                .as_bytes(),
-            chunks_with_padding[MAX_AGG_SNARKS - 1]
-                .withdraw_root
-                .as_bytes(),
-            data_hash.as_slice(),
-        ]
-        .concat();
+        let preimage =
+            batch_pi_hash_preimage(chunks_with_padding, &data_hash.into(), &H256::zero());
         let public_input_hash = keccak256(preimage);
 
         Self {
             chain_id: chunks_with_padding[0].chain_id,
+            pi_schema: chunks_with_padding[0].pi_schema,
             chunks_with_padding: chunks_with_padding.try_into().unwrap(), // safe unwrap
             data_hash: data_hash.into(),
             public_input_hash: public_input_hash.into(),
             number_of_valid_chunks,
+            blob: None,
         }
     }
 
+    /// Build Batch hash from an ordered list of #N_SNARKS of chunks, whose data is posted as
+    /// `blob`.
+    ///
+    /// The pi schema of the chunks must have a blob data commitment, and the commitment of each
+    /// valid chunk must be the one of its L2 transactions in the blob. The batch's commitment is
+    /// the digest of the blob bytes.
+    pub fn construct_with_blob(chunks_with_padding: &[ChunkHash], blob: BlobData) -> Self {
+        let mut batch_hash = Self::construct(chunks_with_padding);
+
+        assert!(
+            batch_hash
+                .pi_schema
+                .offset(PiField::BlobDataCommitment)
+                .is_some(),
+            "pi schema {:?} has no blob data commitment",
+            batch_hash.pi_schema
+        );
+        assert_eq!(
+            blob.chunk_txs.len(),
+            batch_hash.number_of_valid_chunks,
+            "blob data does not match the valid chunks"
+        );
+        for (i, chunk) in chunks_with_padding
+            .iter()
+            .take(batch_hash.number_of_valid_chunks)
+            .enumerate()
+        {
+            assert_eq!(
+                chunk.blob_data_commitment,
+                blob.chunk_commitment(i),
+                "blob data commitment of chunk {i} does not match the blob"
+            );
+        }
+
+        let preimage = batch_pi_hash_preimage(
+            chunks_with_padding,
+            &batch_hash.data_hash,
+            &blob.bytes_digest(),
+        );
+        batch_hash.public_input_hash = keccak256(preimage).into();
+
+        let point_evaluation = blob.point_evaluation();
+        log::trace!("blob point evaluation: {:?}", point_evaluation);
+        batch_hash.blob = Some((blob, point_evaluation));
+        batch_hash
+    }
+
+    /// The point evaluation of the blob of the batch, if it is posted as a blob
+    pub fn point_evaluation(&self) -> Option<BlobPointEvaluation> {
+        self.blob
+            .as_ref()
+            .map(|(_, point_evaluation)| *point_evaluation)
+    }
+
     /// Extract all the hash inputs that will ever be used.
-    /// There are MAX_AGG_SNARKS + 2 hashes.
+    /// There are N_SNARKS + 2 hashes, and 2 more for a batch with a blob.
     ///
     /// orders:
     /// - batch_public_input_hash
-    /// - chunk\[i\].piHash for i in \[0, MAX_AGG_SNARKS)
+    /// - chunk\[i\].piHash for i in \[0, N_SNARKS)
+    /// - blob bytes and challenge preimage, if the batch has a blob
     /// - batch_data_hash_preimage
+    ///
+    /// The hash inputs binding the blob bytes to the chunks are extracted by
+    /// [`BlobData::extract_tx_hash_preimages`].
     pub(crate) fn extract_hash_preimages(&self) -> Vec<Vec<u8>> {
         let mut res = vec![];
 
@@ -148,36 +350,31 @@ impl BatchHash {
         //      chunk[k-1].post_state_root ||
         //      chunk[k-1].withdraw_root ||
         //      batch_data_hash )
-        let batch_public_input_hash_preimage = [
-            self.chain_id.to_be_bytes().as_ref(),
-            self.chunks_with_padding[0].prev_state_root.as_bytes(),
-            self.chunks_with_padding[MAX_AGG_SNARKS - 1]
-                .post_state_root
-                .as_bytes(),
-            self.chunks_with_padding[MAX_AGG_SNARKS - 1]
-                .withdraw_root
-                .as_bytes(),
-            self.data_hash.as_bytes(),
-        ]
-        .concat();
-        res.push(batch_public_input_hash_preimage);
-
-        // compute piHash for each chunk for i in [0..MAX_AGG_SNARKS)
+        // followed by the last chunk's fields of the later schemas, and the blob bytes digest
+        res.push(batch_pi_hash_preimage(
+            &self.chunks_with_padding,
+            &self.data_hash,
+            &self
+                .blob
+                .as_ref()
+                .map_or(H256::zero(), |(blob, _)| blob.bytes_digest()),
+        ));
+
+        // compute piHash for each chunk for i in [0..N_SNARKS)
         // chunk[i].piHash =
         // keccak(
         //        chain id ||
         //        chunk[i].prevStateRoot || chunk[i].postStateRoot || chunk[i].withdrawRoot ||
         //        chunk[i].datahash)
+        // followed by the chunk's fields of the later schemas
         for chunk in self.chunks_with_padding.iter() {
-            let chunk_public_input_hash_preimage = [
-                self.chain_id.to_be_bytes().as_ref(),
-                chunk.prev_state_root.as_bytes(),
-                chunk.post_state_root.as_bytes(),
-                chunk.withdraw_root.as_bytes(),
-                chunk.data_hash.as_bytes(),
-            ]
-            .concat();
-            res.push(chunk_public_input_hash_preimage)
+            res.push(chunk.extract_hash_preimage())
+        }
+
+        // blobBytesDigest = keccak(blob bytes, padded)
+        // challengeDigest = keccak(blobBytesDigest || versioned_hash)
+        if let Some((blob, _)) = &self.blob {
+            res.extend(blob.extract_hash_preimages());
         }
 
         // batchDataHash = keccak(chunk[0].dataHash || ... || chunk[k-1].dataHash)
@@ -194,13 +391,20 @@ impl BatchHash {
     }
 
     /// Compute the public inputs for this circuit, excluding the accumulator.
-    /// Content: the public_input_hash
+    /// Content:
+    /// - the public_input_hash
+    /// - the versioned hash, challenge and evaluation of the blob, if the batch has a blob
     pub(crate) fn instances_exclude_acc<F: Field>(&self) -> Vec<Vec<F>> {
         vec![self
             .public_input_hash
             .as_bytes()
             .iter()
             .map(|&x| F::from(x as u64))
+            .chain(
+                self.point_evaluation()
+                    .into_iter()
+                    .flat_map(|point_evaluation| point_evaluation.instances()),
+            )
             .collect()]
     }
 }
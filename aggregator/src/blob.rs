@@ -0,0 +1,313 @@
+//! This module implements the blob of a batch, i.e., the batch's L2 transaction bytes posted to L1
+//! as an EIP-4844 blob, and the evaluation of the blob polynomial at a challenge point.
+//!
+//! The blob is a polynomial over the BLS12-381 scalar field in evaluation form: its i-th field
+//! element is the evaluation at the i-th root of unity of order [`BLOB_WIDTH`], in bit-reversed
+//! order. Each field element carries [`N_DATA_BYTES_PER_COEFFICIENT`] bytes of the batch.
+//!
+//! The challenge point is derived from the blob bytes and the versioned hash of the KZG commitment
+//! to the blob:
+//! - blob_bytes_digest := keccak(blob bytes, zero padded to [`N_BLOB_BYTES`])
+//! - z := keccak(blob_bytes_digest || versioned_hash) mod BLS_MODULUS
+//! - y := p(z)
+//!
+//! The aggregation circuit proves `y` is the evaluation of the batch bytes at `z`, and exposes
+//! `(versioned_hash, z, y)` as public inputs. The verifier checks with the point evaluation
+//! precompile that the blob of `versioned_hash` also evaluates to `y` at `z`.
+//!
+//! The batch bytes are the signed L2 transactions of its chunks, in order. The aggregation circuit
+//! also proves they are the transactions committed to by the
+//! [`blob_data_commitment`](eth_types::pi_schema::blob_data_commitment) of each chunk.
+
+use eth_types::{pi_schema::blob_data_commitment, Field, H256};
+use ethers_core::utils::keccak256;
+use halo2_proofs::halo2curves::bn256::Fr;
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+use std::iter;
+use zkevm_circuits::witness::Block;
+
+use crate::constants::{DIGEST_LEN, INPUT_LEN_PER_ROUND};
+
+/// Number of field elements in a blob
+pub const BLOB_WIDTH: usize = 4096;
+/// log2 of [`BLOB_WIDTH`]
+pub(crate) const LOG_BLOB_WIDTH: usize = 12;
+/// Number of bytes of the batch in each field element of the blob. The most significant byte of
+/// each field element is zero, so that it is smaller than the BLS12-381 scalar modulus.
+pub const N_DATA_BYTES_PER_COEFFICIENT: usize = 31;
+/// Max number of bytes of the batch in a blob
+pub const N_BLOB_BYTES: usize = BLOB_WIDTH * N_DATA_BYTES_PER_COEFFICIENT;
+/// Max number of L2 transactions in a blob
+pub const MAX_BLOB_TXS: usize = 1024;
+/// Number of public inputs of a blob point evaluation:
+/// 32 bytes for each of the versioned hash, the challenge point and the evaluation
+pub const BLOB_PI_LEN: usize = 3 * DIGEST_LEN;
+
+/// Length of the preimage of the challenge digest: blob_bytes_digest || versioned_hash
+pub(crate) const CHALLENGE_PREIMAGE_LEN: usize = 2 * DIGEST_LEN;
+/// Number of keccak rounds to hash the blob bytes, including the padding
+pub(crate) const BLOB_BYTES_KECCAK_ROUNDS: usize = N_BLOB_BYTES / INPUT_LEN_PER_ROUND + 1;
+/// Number of keccak rounds to hash the challenge preimage, including the padding
+pub(crate) const CHALLENGE_KECCAK_ROUNDS: usize = CHALLENGE_PREIMAGE_LEN / INPUT_LEN_PER_ROUND + 1;
+
+/// Modulus of the BLS12-381 scalar field
+const BLS_MODULUS: &str = "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001";
+/// Generator of the multiplicative group of the BLS12-381 scalar field
+const BLS_PRIMITIVE_ROOT: u64 = 7;
+
+/// Modulus of the BLS12-381 scalar field
+pub(crate) fn bls_modulus() -> BigUint {
+    BigUint::parse_bytes(BLS_MODULUS.as_bytes(), 16).unwrap() // safe unwrap
+}
+
+/// The roots of unity of order [`BLOB_WIDTH`] in bit-reversed order, i.e., the evaluation domain
+/// of the blob polynomial.
+pub(crate) fn roots_of_unity_brp() -> Vec<BigUint> {
+    let modulus = bls_modulus();
+    let omega =
+        BigUint::from(BLS_PRIMITIVE_ROOT).modpow(&((&modulus - 1u64) / BLOB_WIDTH), &modulus);
+
+    let mut roots = Vec::with_capacity(BLOB_WIDTH);
+    let mut root = BigUint::from(1u64);
+    for _ in 0..BLOB_WIDTH {
+        roots.push(root.clone());
+        root = root * &omega % &modulus;
+    }
+
+    (0..BLOB_WIDTH)
+        .map(|i| roots[i.reverse_bits() >> (usize::BITS as usize - LOG_BLOB_WIDTH)].clone())
+        .collect()
+}
+
+/// Return the terms `f_i * ω_i / (z - ω_i)` of the barycentric formula, for the evaluations `f_i`
+/// of a polynomial at the roots `ω_i`.
+///
+/// Panics if `z` is one of the roots.
+pub(crate) fn barycentric_terms(
+    evaluations: &[BigUint],
+    roots: &[BigUint],
+    z: &BigUint,
+) -> Vec<BigUint> {
+    let modulus = bls_modulus();
+    evaluations
+        .iter()
+        .zip(roots.iter())
+        .map(|(f, omega)| {
+            let denominator = (z + &modulus - omega) % &modulus;
+            assert!(denominator.bits() > 0, "challenge point is a root of unity");
+            let inverse = denominator.modpow(&(&modulus - 2u64), &modulus);
+            f * omega % &modulus * inverse % &modulus
+        })
+        .collect()
+}
+
+/// Evaluate the polynomial with the `evaluations` on [`roots_of_unity_brp`] at `z` with the
+/// barycentric formula
+///
+/// p(z) = (z^BLOB_WIDTH - 1) / BLOB_WIDTH * sum_i f_i * ω_i / (z - ω_i)
+///
+/// Panics if `z` is one of the roots.
+pub(crate) fn barycentric_evaluate(evaluations: &[BigUint], z: &BigUint) -> BigUint {
+    assert_eq!(evaluations.len(), BLOB_WIDTH);
+    let modulus = bls_modulus();
+
+    let sum = barycentric_terms(evaluations, &roots_of_unity_brp(), z)
+        .into_iter()
+        .fold(BigUint::from(0u64), |acc, term| (acc + term) % &modulus);
+    let z_pow_minus_one =
+        (z.modpow(&BigUint::from(BLOB_WIDTH), &modulus) + &modulus - 1u64) % &modulus;
+    let width_inv = BigUint::from(BLOB_WIDTH).modpow(&(&modulus - 2u64), &modulus);
+
+    z_pow_minus_one * width_inv % &modulus * sum % &modulus
+}
+
+/// Big endian bytes of a field element of BLS12-381.
+pub(crate) fn biguint_to_h256(value: &BigUint) -> H256 {
+    let bytes = value.to_bytes_be();
+    assert!(bytes.len() <= 32, "{value} does not fit in 32 bytes");
+
+    let mut res = H256::zero();
+    res.0[32 - bytes.len()..].copy_from_slice(&bytes);
+    res
+}
+
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+/// The data of a batch posted as an EIP-4844 blob.
+pub struct BlobData {
+    /// the versioned hash of the KZG commitment to the blob, as posted on L1
+    pub versioned_hash: H256,
+    /// the signed L2 transactions of each valid chunk of the batch, in order; the bytes of the
+    /// batch are their concatenation, at most [`N_BLOB_BYTES`]
+    pub chunk_txs: Vec<Vec<Vec<u8>>>,
+}
+
+impl BlobData {
+    /// Build the blob data of a batch from the signed L2 transactions of each of its valid chunks.
+    pub fn new(versioned_hash: H256, chunk_txs: Vec<Vec<Vec<u8>>>) -> Self {
+        let txs = chunk_txs.iter().flatten();
+        assert!(
+            txs.clone().all(|tx| !tx.is_empty()),
+            "blob data has an empty tx"
+        );
+        let num_txs = txs.clone().count();
+        assert!(
+            num_txs <= MAX_BLOB_TXS,
+            "blob data of {num_txs} txs exceeds the max number of txs {MAX_BLOB_TXS}",
+        );
+        let num_bytes = txs.map(Vec::len).sum::<usize>();
+        assert!(
+            num_bytes <= N_BLOB_BYTES,
+            "blob data of {num_bytes} bytes exceeds the blob size {N_BLOB_BYTES}",
+        );
+
+        Self {
+            versioned_hash,
+            chunk_txs,
+        }
+    }
+
+    /// Build the blob data from the signed L2 transactions of the witness blocks of the chunks of
+    /// a batch, one block per chunk, in order.
+    pub fn from_witness_blocks(versioned_hash: H256, blocks: &[Block<Fr>]) -> Self {
+        let chunk_txs = blocks
+            .iter()
+            .map(|block| {
+                block
+                    .txs
+                    .iter()
+                    .filter(|tx| !tx.tx_type.is_l1_msg())
+                    .map(|tx| tx.rlp_signed.clone())
+                    .collect()
+            })
+            .collect();
+
+        Self::new(versioned_hash, chunk_txs)
+    }
+
+    /// The bytes of the batch, i.e., the concatenation of its L2 transactions
+    pub fn bytes(&self) -> Vec<u8> {
+        self.chunk_txs.iter().flatten().flatten().cloned().collect()
+    }
+
+    /// The bytes of the batch, padded with zeros to [`N_BLOB_BYTES`]
+    pub(crate) fn padded_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.bytes();
+        bytes.resize(N_BLOB_BYTES, 0);
+        bytes
+    }
+
+    /// The blob data commitment of the `index`-th valid chunk, i.e., the digest of the hashes of
+    /// its L2 transactions
+    pub fn chunk_commitment(&self, index: usize) -> H256 {
+        blob_data_commitment(self.chunk_txs[index].iter().map(|tx| keccak256(tx).into()))
+    }
+
+    /// The hash inputs that the aggregation circuit looks up to bind the blob bytes to the
+    /// chunks, besides the ones of [`Self::extract_hash_preimages`]
+    ///
+    /// orders:
+    /// - each L2 transaction
+    /// - the hashes of the L2 transactions of each chunk that has some
+    /// - the empty input, the preimage of the commitment of a chunk without L2 transactions
+    pub(crate) fn extract_tx_hash_preimages(&self) -> Vec<Vec<u8>> {
+        let txs = self.chunk_txs.iter().flatten().cloned();
+        let tx_hashes = self
+            .chunk_txs
+            .iter()
+            .filter(|txs| !txs.is_empty())
+            .map(|txs| txs.iter().flat_map(keccak256).collect());
+
+        txs.chain(tx_hashes).chain(iter::once(vec![])).collect()
+    }
+
+    /// The field elements of the blob, i.e., the evaluations of the blob polynomial
+    pub(crate) fn coefficients(&self) -> Vec<BigUint> {
+        self.padded_bytes()
+            .chunks(N_DATA_BYTES_PER_COEFFICIENT)
+            .map(BigUint::from_bytes_be)
+            .collect()
+    }
+
+    /// Digest of the padded bytes of the blob
+    pub fn bytes_digest(&self) -> H256 {
+        keccak256(self.padded_bytes()).into()
+    }
+
+    /// The preimage of the challenge digest
+    ///  blob_bytes_digest || versioned_hash
+    pub(crate) fn challenge_preimage(&self) -> Vec<u8> {
+        [
+            self.bytes_digest().as_bytes(),
+            self.versioned_hash.as_bytes(),
+        ]
+        .concat()
+    }
+
+    /// Digest of the challenge preimage, which is reduced to the challenge point
+    pub(crate) fn challenge_digest(&self) -> H256 {
+        keccak256(self.challenge_preimage()).into()
+    }
+
+    /// Extract all the hash inputs of the blob
+    ///
+    /// orders:
+    /// - blob bytes, padded to [`N_BLOB_BYTES`]
+    /// - challenge preimage
+    pub(crate) fn extract_hash_preimages(&self) -> Vec<Vec<u8>> {
+        vec![self.padded_bytes(), self.challenge_preimage()]
+    }
+
+    /// Evaluate the blob polynomial at the challenge point.
+    pub fn point_evaluation(&self) -> BlobPointEvaluation {
+        let modulus = bls_modulus();
+        let z = BigUint::from_bytes_be(self.challenge_digest().as_bytes()) % &modulus;
+        let y = barycentric_evaluate(&self.coefficients(), &z);
+
+        BlobPointEvaluation {
+            versioned_hash: self.versioned_hash,
+            challenge: biguint_to_h256(&z),
+            evaluation: biguint_to_h256(&y),
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+/// A point evaluation of a blob, as checked by the EIP-4844 point evaluation precompile.
+pub struct BlobPointEvaluation {
+    /// the versioned hash of the KZG commitment to the blob
+    pub versioned_hash: H256,
+    /// the challenge point z, big endian
+    pub challenge: H256,
+    /// the evaluation y = p(z), big endian
+    pub evaluation: H256,
+}
+
+impl BlobPointEvaluation {
+    /// Bytes of the public inputs
+    ///  versioned_hash || z || y
+    pub fn to_bytes(&self) -> Vec<u8> {
+        [
+            self.versioned_hash.as_bytes(),
+            self.challenge.as_bytes(),
+            self.evaluation.as_bytes(),
+        ]
+        .concat()
+    }
+
+    /// Recover from the bytes of the public inputs.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        assert_eq!(bytes.len(), BLOB_PI_LEN);
+
+        Self {
+            versioned_hash: H256::from_slice(&bytes[0..DIGEST_LEN]),
+            challenge: H256::from_slice(&bytes[DIGEST_LEN..2 * DIGEST_LEN]),
+            evaluation: H256::from_slice(&bytes[2 * DIGEST_LEN..]),
+        }
+    }
+
+    /// Public inputs of the point evaluation, one byte per element.
+    pub(crate) fn instances<F: Field>(&self) -> Vec<F> {
+        self.to_bytes().iter().map(|&x| F::from(x as u64)).collect()
+    }
+}
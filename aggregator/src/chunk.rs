@@ -1,6 +1,9 @@
 //! This module implements `Chunk` related data types.
 //! A chunk is a list of blocks.
-use eth_types::{ToBigEndian, H256};
+use eth_types::{
+    pi_schema::{blob_data_commitment, PiSchema, PiValues},
+    ToBigEndian, H256,
+};
 use ethers_core::utils::keccak256;
 use halo2_proofs::halo2curves::bn256::Fr;
 use serde::{Deserialize, Serialize};
@@ -15,6 +18,9 @@ use zkevm_circuits::witness::Block;
 /// - the withdraw root after this chunk
 /// - the data hash of this chunk
 /// - if the chunk is padded (en empty but valid chunk that is padded for aggregation)
+///
+/// and, depending on its [`PiSchema`], the L1 message queue hash and the blob data commitment
+/// after this chunk.
 pub struct ChunkHash {
     /// Chain identifier
     pub chain_id: u64,
@@ -28,6 +34,15 @@ pub struct ChunkHash {
     pub data_hash: H256,
     /// if the chunk is a padded chunk
     pub is_padding: bool,
+    /// layout of the public input hash preimage
+    #[serde(default)]
+    pub pi_schema: PiSchema,
+    /// the rolling hash of the L1 message queue after this chunk
+    #[serde(default)]
+    pub l1_msg_queue_hash: H256,
+    /// the commitment to the L2 transactions of this chunk, as posted in the blob of its batch
+    #[serde(default)]
+    pub blob_data_commitment: H256,
 }
 
 impl ChunkHash {
@@ -90,6 +105,14 @@ impl ChunkHash {
             .map(|(_, b_ctx)| b_ctx.eth_block.state_root)
             .unwrap_or(H256(block.prev_state_root.to_be_bytes()));
 
+        let blob_data_commitment = blob_data_commitment(
+            block
+                .txs
+                .iter()
+                .filter(|tx| !tx.tx_type.is_l1_msg())
+                .map(|tx| tx.hash),
+        );
+
         Self {
             chain_id: block.chain_id,
             prev_state_root: H256(block.prev_state_root.to_be_bytes()),
@@ -97,6 +120,9 @@ impl ChunkHash {
             withdraw_root: H256(block.withdraw_root.to_be_bytes()),
             data_hash,
             is_padding,
+            pi_schema: block.pi_schema,
+            l1_msg_queue_hash: block.l1_msg_queue_hash,
+            blob_data_commitment,
         }
     }
 
@@ -111,6 +137,10 @@ impl ChunkHash {
         r.fill_bytes(&mut withdraw_root);
         let mut data_hash = [0u8; 32];
         r.fill_bytes(&mut data_hash);
+        let mut l1_msg_queue_hash = [0u8; 32];
+        r.fill_bytes(&mut l1_msg_queue_hash);
+        let mut blob_data_commitment = [0u8; 32];
+        r.fill_bytes(&mut blob_data_commitment);
         Self {
             chain_id: 0,
             prev_state_root: prev_state_root.into(),
@@ -118,6 +148,9 @@ impl ChunkHash {
             withdraw_root: withdraw_root.into(),
             data_hash: data_hash.into(),
             is_padding: false,
+            pi_schema: PiSchema::default(),
+            l1_msg_queue_hash: l1_msg_queue_hash.into(),
+            blob_data_commitment: blob_data_commitment.into(),
         }
     }
 
@@ -129,32 +162,34 @@ impl ChunkHash {
             "previous chunk is padded already"
         );
         Self {
-            chain_id: previous_chunk.chain_id,
-            prev_state_root: previous_chunk.prev_state_root,
-            post_state_root: previous_chunk.post_state_root,
-            withdraw_root: previous_chunk.withdraw_root,
-            data_hash: previous_chunk.data_hash,
             is_padding: true,
+            ..*previous_chunk
         }
     }
 
     /// Public input hash for a given chunk is defined as
     ///  keccak( chain id || prev state root || post state root || withdraw root || data hash )
+    /// for [`PiSchema::V1`], see [`PiSchema`] for the other layouts.
     pub fn public_input_hash(&self) -> H256 {
         let preimage = self.extract_hash_preimage();
         keccak256::<&[u8]>(preimage.as_ref()).into()
     }
 
-    /// Extract the preimage for the hash
-    ///  chain id || prev state root || post state root || withdraw root || data hash
+    /// Extract the preimage for the hash, laid out as its [`PiSchema`]
     pub fn extract_hash_preimage(&self) -> Vec<u8> {
-        [
-            self.chain_id.to_be_bytes().as_ref(),
-            self.prev_state_root.as_bytes(),
-            self.post_state_root.as_bytes(),
-            self.withdraw_root.as_bytes(),
-            self.data_hash.as_bytes(),
-        ]
-        .concat()
+        self.pi_schema.preimage(&self.pi_values())
+    }
+
+    /// Values of the fields of the public input hash preimage
+    pub fn pi_values(&self) -> PiValues {
+        PiValues {
+            chain_id: self.chain_id,
+            prev_state_root: self.prev_state_root,
+            post_state_root: self.post_state_root,
+            withdraw_root: self.withdraw_root,
+            data_hash: self.data_hash,
+            l1_msg_queue_hash: self.l1_msg_queue_hash,
+            blob_data_commitment: self.blob_data_commitment,
+        }
     }
 }
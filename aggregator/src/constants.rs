@@ -1,7 +1,3 @@
-// A chain_id is u64 and uses 8 bytes
-#[allow(dead_code)]
-pub(crate) const CHAIN_ID_LEN: usize = 8;
-
 // ================================
 // hash parameters
 // ================================
@@ -11,26 +7,12 @@ pub(crate) const DIGEST_LEN: usize = 32;
 /// Input length per round
 pub(crate) const INPUT_LEN_PER_ROUND: usize = 136;
 
+// the layouts of the public input hash preimages, i.e., the byte positions of the state roots,
+// the data hash, etc., are given by `eth_types::pi_schema::PiSchema`
+
 // TODO(ZZ): update to the right degree
 #[allow(dead_code)]
-pub(crate) const LOG_DEGREE: u32 = 19;
-
-// ================================
-// indices for hash table
-// ================================
-//
-// the preimages are arranged as
-// - chain_id:          8 bytes
-// - prev_state_root    32 bytes
-// - post_state_root    32 bytes
-// - withdraw_root      32 bytes
-// - chunk_data_hash    32 bytes
-//
-
-pub(crate) const PREV_STATE_ROOT_INDEX: usize = 8;
-pub(crate) const POST_STATE_ROOT_INDEX: usize = 40;
-pub(crate) const WITHDRAW_ROOT_INDEX: usize = 72;
-pub(crate) const CHUNK_DATA_HASH_INDEX: usize = 104;
+pub(crate) const LOG_DEGREE: u32 = 20;
 
 // ================================
 // aggregator parameters
@@ -44,8 +26,10 @@ pub(crate) const LIMBS: usize = 3;
 /// number of bits in each limb in the ECC chip
 pub(crate) const BITS: usize = 88;
 
-/// Max number of snarks to be aggregated in a chunk.
-/// If the input size is less than this, dummy snarks
-/// will be padded.
-// TODO: update me(?)
-pub const MAX_AGG_SNARKS: usize = 15;
+/// Numbers of snarks an aggregation circuit can be generated for, in ascending order.
+/// Each tier has its own proving key; a batch is proven with the smallest tier that
+/// holds all of its chunks, and dummy snarks are padded up to the tier size.
+pub const AGG_SNARKS_TIERS: [usize; 4] = [4, 8, 15, 32];
+
+/// Max number of snarks to be aggregated in a batch, i.e., the largest tier.
+pub const MAX_AGG_SNARKS: usize = AGG_SNARKS_TIERS[AGG_SNARKS_TIERS.len() - 1];
@@ -1,4 +1,5 @@
 use ark_std::{end_timer, start_timer};
+use eth_types::pi_schema::{PiBatchSource, PiField, PiSchema};
 use halo2_proofs::{
     circuit::{AssignedCell, Layouter, Region, Value},
     halo2curves::{
@@ -33,13 +34,15 @@ use zkevm_circuits::{
 };
 
 use crate::{
-    constants::{CHAIN_ID_LEN, DIGEST_LEN, INPUT_LEN_PER_ROUND, LOG_DEGREE, MAX_AGG_SNARKS},
+    blob::{BLOB_BYTES_KECCAK_ROUNDS, CHALLENGE_PREIMAGE_LEN, N_BLOB_BYTES},
+    constants::{DIGEST_LEN, INPUT_LEN_PER_ROUND, LOG_DEGREE},
     util::{
-        assert_conditional_equal, assert_equal, assert_exist, get_indices, get_max_keccak_updates,
-        parse_hash_digest_cells, parse_hash_preimage_cells, parse_pi_hash_rlc_cells,
+        assert_conditional_equal, assert_equal, assert_exist, get_blob_keccak_updates,
+        get_data_hash_keccak_updates, get_indices, get_max_keccak_updates,
+        parse_blob_hash_digest_cells, parse_blob_hash_preimage_cells, parse_hash_digest_cells,
+        parse_hash_preimage_cells, parse_pi_hash_rlc_cells,
     },
-    AggregationConfig, RlcConfig, BITS, CHUNK_DATA_HASH_INDEX, LIMBS, POST_STATE_ROOT_INDEX,
-    PREV_STATE_ROOT_INDEX, WITHDRAW_ROOT_INDEX,
+    AggregationConfig, RlcConfig, BITS, LIMBS,
 };
 
 /// Subroutine for the witness generations.
@@ -148,54 +151,79 @@ pub fn extract_proof_and_instances_with_pairing_check(
 
 #[derive(Default)]
 pub(crate) struct ExtractedHashCells {
-    hash_input_cells: Vec<AssignedCell<Fr, Fr>>,
-    hash_output_cells: Vec<AssignedCell<Fr, Fr>>,
+    pub(crate) hash_input_cells: Vec<AssignedCell<Fr, Fr>>,
+    pub(crate) hash_output_cells: Vec<AssignedCell<Fr, Fr>>,
     data_rlc_cells: Vec<AssignedCell<Fr, Fr>>,
     hash_input_len_cells: Vec<AssignedCell<Fr, Fr>>,
     is_final_cells: Vec<AssignedCell<Fr, Fr>>,
 }
 
-/// Input the hash input bytes,
+/// Input the hash input bytes, and the inputs that are only looked up in the keccak table,
 /// assign the circuit for the hash function,
 /// return
-/// - cells of the hash digests
+/// - cells of the hash preimages and digests
+/// - cells of the padding flags of the chunks
 //
 // This function asserts the following constraints on the hashes
 //
 // 1. batch_data_hash digest is reused for public input hash
 // 2. batch_pi_hash used same roots as chunk_pi_hash
 // 2.1. batch_pi_hash and chunk[0] use a same prev_state_root
-// 2.2. batch_pi_hash and chunk[MAX_AGG_SNARKS-1] use a same post_state_root
-// 2.3. batch_pi_hash and chunk[MAX_AGG_SNARKS-1] use a same withdraw_root
+// 2.2. batch_pi_hash and chunk[N_SNARKS-1] use a same post_state_root
+// 2.3. batch_pi_hash and chunk[N_SNARKS-1] use a same withdraw_root
 // 3. batch_data_hash and chunk[i].pi_hash use a same chunk[i].data_hash when chunk[i] is not padded
 // 4. chunks are continuous: they are linked via the state roots
 // 5. batch and all its chunks use a same chain id
 // 6. chunk[i]'s chunk_pi_hash_rlc_cells == chunk[i-1].chunk_pi_hash_rlc_cells when chunk[i] is
 // padded
 // 7. the hash input length are correct
-// - first MAX_AGG_SNARKS + 1 hashes all have 136 bytes input
+// - first N_SNARKS + 1 hashes all have the preimage length of the pi schema
 // - batch's data_hash length is 32 * number_of_valid_snarks
 // 8. batch data hash is correct w.r.t. its RLCs
 // 9. is_final_cells are set correctly
-pub(crate) fn assign_batch_hashes(
+// 10. the challenge preimage of the blob starts with the digest of the blob bytes
+// 11. batch_pi_hash uses the digest of the blob bytes as its blob data commitment, or zero for a
+// batch without a blob, if the pi schema has one
+//
+// and, for a batch with a blob, 7 and 9 also hold for the hashes of the blob
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn assign_batch_hashes<const N_SNARKS: usize>(
     config: &AggregationConfig,
     layouter: &mut impl Layouter<Fr>,
     challenges: Challenges<Value<Fr>>,
     chunks_are_valid: &[bool],
+    pi_schema: PiSchema,
+    with_blob: bool,
     preimages: &[Vec<u8>],
-) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
-    let extracted_hash_cells = extract_hash_cells(
+    lookup_preimages: &[Vec<u8>],
+) -> Result<(ExtractedHashCells, Vec<AssignedCell<Fr, Fr>>), Error> {
+    let extracted_hash_cells = extract_hash_cells::<N_SNARKS>(
         &config.keccak_circuit_config,
         layouter,
         challenges,
+        pi_schema,
+        with_blob,
         preimages,
+        lookup_preimages,
     )?;
     // 2. batch_pi_hash used same roots as chunk_pi_hash
     // 2.1. batch_pi_hash and chunk[0] use a same prev_state_root
-    // 2.2. batch_pi_hash and chunk[MAX_AGG_SNARKS-1] use a same post_state_root
-    // 2.3. batch_pi_hash and chunk[MAX_AGG_SNARKS-1] use a same withdraw_root
+    // 2.2. batch_pi_hash and chunk[N_SNARKS-1] use a same post_state_root
+    // 2.3. batch_pi_hash and chunk[N_SNARKS-1] use a same withdraw_root
     // 5. batch and all its chunks use a same chain id
-    copy_constraints(layouter, &extracted_hash_cells.hash_input_cells)?;
+    copy_constraints::<N_SNARKS>(
+        layouter,
+        pi_schema,
+        with_blob,
+        &extracted_hash_cells.hash_input_cells,
+    )?;
+
+    // 10. the challenge preimage of the blob starts with the digest of the blob bytes
+    // 11. batch_pi_hash uses the digest of the blob bytes as its blob data commitment, if the pi
+    // schema has one
+    if with_blob {
+        blob_copy_constraints::<N_SNARKS>(layouter, pi_schema, &extracted_hash_cells)?;
+    }
 
     // 1. batch_data_hash digest is reused for public input hash
     // 3. batch_data_hash and chunk[i].pi_hash use a same chunk[i].data_hash when chunk[i] is not
@@ -204,30 +232,36 @@ pub(crate) fn assign_batch_hashes(
     // 6. chunk[i]'s chunk_pi_hash_rlc_cells == chunk[i-1].chunk_pi_hash_rlc_cells when chunk[i] is
     // padded
     // 7. the hash input length are correct
-    // - first MAX_AGG_SNARKS + 1 hashes all have 136 bytes input
+    // - first N_SNARKS + 1 hashes all have the preimage length of the pi schema
     // - batch's data_hash length is 32 * number_of_valid_snarks
     // 8. batch data hash is correct w.r.t. its RLCs
     // 9. is_final_cells are set correctly
-    conditional_constraints(
+    // 11. batch_pi_hash uses a zero blob data commitment for a batch without a blob
+    let chunks_are_padding = conditional_constraints::<N_SNARKS>(
         &config.rlc_config,
         layouter,
         challenges,
         chunks_are_valid,
+        pi_schema,
+        with_blob,
         &extracted_hash_cells,
     )?;
 
-    Ok(extracted_hash_cells.hash_output_cells)
+    Ok((extracted_hash_cells, chunks_are_padding))
 }
 
-pub(crate) fn extract_hash_cells(
+pub(crate) fn extract_hash_cells<const N_SNARKS: usize>(
     keccak_config: &KeccakCircuitConfig<Fr>,
     layouter: &mut impl Layouter<Fr>,
     challenges: Challenges<Value<Fr>>,
+    pi_schema: PiSchema,
+    with_blob: bool,
     preimages: &[Vec<u8>],
+    lookup_preimages: &[Vec<u8>],
 ) -> Result<ExtractedHashCells, Error> {
     let mut is_first_time = true;
     let keccak_capacity = KeccakCircuit::<Fr>::capacity_for_row(1 << LOG_DEGREE);
-    let max_keccak_updates = get_max_keccak_updates(MAX_AGG_SNARKS);
+    let max_keccak_updates = get_max_keccak_updates(N_SNARKS, pi_schema, with_blob);
     let keccak_f_rows = keccak_packed_multi::get_num_rows_per_update();
 
     let timer = start_timer!(|| ("multi keccak").to_string());
@@ -242,15 +276,28 @@ pub(crate) fn extract_hash_cells(
     //      (chain id ||
     //      chunk[i].prevStateRoot || chunk[i].postStateRoot ||
     //      chunk[i].withdrawRoot || chunk[i].datahash)
-    // (3) batchDataHash preimage =
+    // laid out as the pi schema, which may append more fields to (1) and (2)
+    // (3) if the batch has a blob, blobBytesDigest preimage =
+    //      (blob bytes, padded to N_BLOB_BYTES)
+    //     and challengeDigest preimage =
+    //      (blobBytesDigest || versioned_hash)
+    // (4) batchDataHash preimage =
     //      (chunk[0].dataHash || ... || chunk[k-1].dataHash)
     // each part of the preimage is mapped to image by Keccak256
-    let witness = multi_keccak(preimages, challenges, keccak_capacity)
+    //
+    // they are followed by the lookup preimages, i.e., the transactions of the blob and the
+    // preimages of the blob data commitments of the chunks, whose cells are not extracted
+    let all_preimages = preimages
+        .iter()
+        .chain(lookup_preimages.iter())
+        .cloned()
+        .collect::<Vec<_>>();
+    let witness = multi_keccak(&all_preimages, challenges, keccak_capacity)
         .map_err(|e| Error::AssertionFailure(format!("multi keccak assignment failed: {e:?}")))?;
     end_timer!(timer);
 
     // extract the indices of the rows for which the preimage and the digest cells lie in
-    let (preimage_indices, digest_indices) = get_indices(preimages);
+    let (preimage_indices, digest_indices) = get_indices::<N_SNARKS>(preimages);
 
     let extracted_hash_cells = layouter
         .assign_region(
@@ -316,7 +363,12 @@ pub(crate) fn extract_hash_cells(
                     hash_input_cells.len(),
                     max_keccak_updates * INPUT_LEN_PER_ROUND
                 );
-                assert_eq!(hash_output_cells.len(), (MAX_AGG_SNARKS + 5) * DIGEST_LEN);
+                let num_blob_hashes = if with_blob { 2 } else { 0 };
+                assert_eq!(
+                    hash_output_cells.len(),
+                    (N_SNARKS + 1 + num_blob_hashes + get_data_hash_keccak_updates(N_SNARKS))
+                        * DIGEST_LEN
+                );
 
                 keccak_config
                     .keccak_table
@@ -343,11 +395,16 @@ pub(crate) fn extract_hash_cells(
 // Assert the following constraints
 // 2. batch_pi_hash used same roots as chunk_pi_hash
 // 2.1. batch_pi_hash and chunk[0] use a same prev_state_root
-// 2.2. batch_pi_hash and chunk[MAX_AGG_SNARKS-1] use a same post_state_root
-// 2.3. batch_pi_hash and chunk[MAX_AGG_SNARKS-1] use a same withdraw_root
+// 2.2. batch_pi_hash and chunk[N_SNARKS-1] use a same post_state_root
+// 2.3. batch_pi_hash and chunk[N_SNARKS-1] use a same withdraw_root
 // 5. batch and all its chunks use a same chain id
-fn copy_constraints(
+//
+// In general, each field of the pi schema but the data hash is copied from the chunks as its
+// `PiBatchSource`.
+fn copy_constraints<const N_SNARKS: usize>(
     layouter: &mut impl Layouter<Fr>,
+    pi_schema: PiSchema,
+    with_blob: bool,
     hash_input_cells: &[AssignedCell<Fr, Fr>],
 ) -> Result<(), Error> {
     let mut is_first_time = true;
@@ -370,7 +427,7 @@ fn copy_constraints(
                     batch_pi_hash_preimage,
                     chunk_pi_hash_preimages,
                     _potential_batch_data_hash_preimage,
-                ) = parse_hash_preimage_cells(hash_input_cells);
+                ) = parse_hash_preimage_cells::<N_SNARKS>(hash_input_cells, pi_schema, with_blob);
 
                 // ====================================================
                 // Constraint the relations between hash preimages
@@ -395,81 +452,112 @@ fn copy_constraints(
                 //        chunk[i].withdrawRoot  ||
                 //        chunk[i].datahash)
                 //
-                // PREV_STATE_ROOT_INDEX, POST_STATE_ROOT_INDEX, WITHDRAW_ROOT_INDEX
-                // used below are byte positions for
-                // prev_state_root, post_state_root, withdraw_root
-                for i in 0..DIGEST_LEN {
-                    // 2.1 chunk[0].prev_state_root
-                    // sanity check
-                    assert_equal(
-                        &batch_pi_hash_preimage[i + PREV_STATE_ROOT_INDEX],
-                        &chunk_pi_hash_preimages[0][i + PREV_STATE_ROOT_INDEX],
-                        format!(
-                            "chunk and batch's prev_state_root do not match: {:?} {:?}",
-                            &batch_pi_hash_preimage[i + PREV_STATE_ROOT_INDEX].value(),
-                            &chunk_pi_hash_preimages[0][i + PREV_STATE_ROOT_INDEX].value(),
-                        )
-                        .as_str(),
-                    )?;
-                    region.constrain_equal(
-                        batch_pi_hash_preimage[i + PREV_STATE_ROOT_INDEX].cell(),
-                        chunk_pi_hash_preimages[0][i + PREV_STATE_ROOT_INDEX].cell(),
-                    )?;
-                    // 2.2 chunk[k-1].post_state_root
-                    // sanity check
-                    assert_equal(
-                        &batch_pi_hash_preimage[i + POST_STATE_ROOT_INDEX],
-                        &chunk_pi_hash_preimages[MAX_AGG_SNARKS - 1][i + POST_STATE_ROOT_INDEX],
-                        format!(
-                            "chunk and batch's post_state_root do not match: {:?} {:?}",
-                            &batch_pi_hash_preimage[i + POST_STATE_ROOT_INDEX].value(),
-                            &chunk_pi_hash_preimages[MAX_AGG_SNARKS - 1][i + POST_STATE_ROOT_INDEX]
-                                .value(),
-                        )
-                        .as_str(),
-                    )?;
-                    region.constrain_equal(
-                        batch_pi_hash_preimage[i + POST_STATE_ROOT_INDEX].cell(),
-                        chunk_pi_hash_preimages[MAX_AGG_SNARKS - 1][i + POST_STATE_ROOT_INDEX]
-                            .cell(),
-                    )?;
-                    // 2.3 chunk[k-1].withdraw_root
-                    assert_equal(
-                        &batch_pi_hash_preimage[i + WITHDRAW_ROOT_INDEX],
-                        &chunk_pi_hash_preimages[MAX_AGG_SNARKS - 1][i + WITHDRAW_ROOT_INDEX],
-                        format!(
-                            "chunk and batch's withdraw_root do not match: {:?} {:?}",
-                            &batch_pi_hash_preimage[i + WITHDRAW_ROOT_INDEX].value(),
-                            &chunk_pi_hash_preimages[MAX_AGG_SNARKS - 1][i + WITHDRAW_ROOT_INDEX]
-                                .value(),
-                        )
-                        .as_str(),
-                    )?;
-                    region.constrain_equal(
-                        batch_pi_hash_preimage[i + WITHDRAW_ROOT_INDEX].cell(),
-                        chunk_pi_hash_preimages[MAX_AGG_SNARKS - 1][i + WITHDRAW_ROOT_INDEX].cell(),
-                    )?;
+                // 5 assert hashes use a same chain id
+                //
+                // the byte positions of the fields are given by the pi schema
+                for &field in pi_schema.fields() {
+                    let chunk_indices = match field.batch_source() {
+                        PiBatchSource::Same => 0..N_SNARKS,
+                        PiBatchSource::First => 0..1,
+                        PiBatchSource::Last => N_SNARKS - 1..N_SNARKS,
+                        // constrained with the batch's data hash preimage
+                        PiBatchSource::Hash => continue,
+                        // constrained with the blob bytes digest, see constraint 11
+                        PiBatchSource::Blob => continue,
+                    };
+                    let index = pi_schema.index(field);
+                    for i in chunk_indices {
+                        for j in index..index + field.num_bytes() {
+                            let lhs = &batch_pi_hash_preimage[j];
+                            let rhs = &chunk_pi_hash_preimages[i][j];
+                            // sanity check
+                            assert_equal(
+                                lhs,
+                                rhs,
+                                format!(
+                                    "chunk_{i} and batch's {field:?} do not match: {:?} {:?}",
+                                    &lhs.value(),
+                                    &rhs.value(),
+                                )
+                                .as_str(),
+                            )?;
+                            region.constrain_equal(lhs.cell(), rhs.cell())?;
+                        }
+                    }
                 }
+                Ok(())
+            },
+        )
+        .map_err(|e| Error::AssertionFailure(format!("assign keccak rows: {e}")))?;
+    Ok(())
+}
 
-                // 5 assert hashes use a same chain id
-                for (i, chunk_pi_hash_preimage) in chunk_pi_hash_preimages.iter().enumerate() {
-                    for (lhs, rhs) in batch_pi_hash_preimage
-                        .iter()
-                        .take(CHAIN_ID_LEN)
-                        .zip(chunk_pi_hash_preimage.iter().take(CHAIN_ID_LEN))
-                    {
-                        // sanity check
-                        assert_equal(
-                            lhs,
-                            rhs,
-                            format!(
-                                "chunk_{i} and batch's chain id do not match: {:?} {:?}",
-                                &lhs.value(),
-                                &rhs.value(),
-                            )
-                            .as_str(),
-                        )?;
-                        region.constrain_equal(lhs.cell(), rhs.cell())?;
+// Assert the following constraints
+// 10. the challenge preimage of the blob starts with the digest of the blob bytes
+// 11. batch_pi_hash uses the digest of the blob bytes as its blob data commitment, if the pi
+// schema has one
+fn blob_copy_constraints<const N_SNARKS: usize>(
+    layouter: &mut impl Layouter<Fr>,
+    pi_schema: PiSchema,
+    extracted_hash_cells: &ExtractedHashCells,
+) -> Result<(), Error> {
+    let mut is_first_time = true;
+
+    layouter
+        .assign_region(
+            || "blob copy constraints",
+            |mut region| -> Result<(), halo2_proofs::plonk::Error> {
+                if is_first_time {
+                    // this region only use copy constraints and do not affect the shape of the
+                    // layouter
+                    is_first_time = false;
+                    return Ok(());
+                }
+                // ====================================================
+                // parse the hashes
+                // ====================================================
+                let (batch_pi_hash_preimage, _chunk_pi_hash_preimages, _) =
+                    parse_hash_preimage_cells::<N_SNARKS>(
+                        &extracted_hash_cells.hash_input_cells,
+                        pi_schema,
+                        true,
+                    );
+                let (_blob_bytes, challenge_preimage) = parse_blob_hash_preimage_cells::<N_SNARKS>(
+                    &extracted_hash_cells.hash_input_cells,
+                    pi_schema,
+                );
+                let (blob_bytes_digest, _challenge_digest) =
+                    parse_blob_hash_digest_cells::<N_SNARKS>(
+                        &extracted_hash_cells.hash_output_cells,
+                    );
+
+                // the preimages and the byte positions in them that the blob bytes digest is
+                // copied to
+                let mut targets = vec![("challenge preimage", challenge_preimage, 0)];
+                if let Some(index) = pi_schema.offset(PiField::BlobDataCommitment) {
+                    targets.push(("batch's blob data commitment", batch_pi_hash_preimage, index));
+                }
+
+                for (name, preimage, index) in targets {
+                    for i in 0..4 {
+                        for j in 0..8 {
+                            // in the keccak table, the input and output data have different
+                            // endianess
+                            let lhs = &preimage[index + i * 8 + j];
+                            let rhs = &blob_bytes_digest[(3 - i) * 8 + j];
+                            // sanity check
+                            assert_equal(
+                                lhs,
+                                rhs,
+                                format!(
+                                    "{name} and blob bytes digest do not match: {:?} {:?}",
+                                    &lhs.value(),
+                                    &rhs.value(),
+                                )
+                                .as_str(),
+                            )?;
+                            region.constrain_equal(lhs.cell(), rhs.cell())?;
+                        }
                     }
                 }
                 Ok(())
@@ -487,17 +575,26 @@ fn copy_constraints(
 // 6. chunk[i]'s chunk_pi_hash_rlc_cells == chunk[i-1].chunk_pi_hash_rlc_cells when chunk[i] is
 // padded
 // 7. the hash input length are correct
-// - first MAX_AGG_SNARKS + 1 hashes all have 136 bytes input
+// - first N_SNARKS + 1 hashes all have the preimage length of the pi schema
 // - batch's data_hash length is 32 * number_of_valid_snarks
 // 8. batch data hash is correct w.r.t. its RLCs
 // 9. is_final_cells are set correctly
-pub(crate) fn conditional_constraints(
+//
+// 11. batch_pi_hash uses a zero blob data commitment for a batch without a blob
+//
+// and, for a batch with a blob, 7 and 9 also hold for the hashes of the blob
+//
+// Return the cells of the padding flags of the chunks
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn conditional_constraints<const N_SNARKS: usize>(
     rlc_config: &RlcConfig,
     layouter: &mut impl Layouter<Fr>,
     challenges: Challenges<Value<Fr>>,
     chunks_are_valid: &[bool],
+    pi_schema: PiSchema,
+    with_blob: bool,
     extracted_hash_cells: &ExtractedHashCells,
-) -> Result<(), Error> {
+) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
     let mut first_pass = halo2_base::SKIP_FIRST_PASS;
     let ExtractedHashCells {
         hash_input_cells,
@@ -510,10 +607,10 @@ pub(crate) fn conditional_constraints(
     layouter
         .assign_region(
             || "rlc conditional constraints",
-            |mut region| -> Result<(), halo2_proofs::plonk::Error> {
+            |mut region| -> Result<Vec<AssignedCell<Fr, Fr>>, halo2_proofs::plonk::Error> {
                 if first_pass {
                     first_pass = false;
-                    return Ok(());
+                    return Ok(vec![]);
                 }
 
                 rlc_config.init(&mut region)?;
@@ -536,86 +633,79 @@ pub(crate) fn conditional_constraints(
 
                 log::trace!("number of valid chunks: {:?}", num_valid_snarks.value());
                 //
-                // if the num_of_valid_snarks <= 4, which only needs 1 keccak-f round. Therefore
+                // the batch's data hash takes 32 * num_of_valid_snarks bytes of input, which is
+                // absorbed in 1 + floor(32 * num_of_valid_snarks / 136) keccak-f rounds:
+                // - if the num_of_valid_snarks <= 4, it only needs 1 keccak-f round. Therefore
                 // the batch's data hash (input, len, data_rlc, output_rlc) are in the first 300
                 // keccak rows;
+                // - else if the num_of_valid_snarks <= 8, it needs 2 keccak-f rounds. Therefore
+                // the batch's data hash (input, len, data_rlc, output_rlc) are in the 2nd 300
+                // keccak rows;
+                // - and so on, up to get_data_hash_keccak_updates(N_SNARKS) rounds.
                 //
-                // else if the num_of_valid_snarks <= 8, which needs
-                // 2 keccak-f rounds. Therefore the batch's data hash (input, len, data_rlc,
-                // output_rlc) are in the 2nd 300 keccak rows;
-                //
-                // else if the
-                // num_of_valid_snarks <= 12, which needs 3 keccak-f rounds. Therefore the batch's
-                // data hash (input, len, data_rlc, output_rlc) are in the 3rd 300 keccak rows;
-                //
-                // else if the
-                // num_of_valid_snarks <= 16, which needs 4 keccak-f rounds. Therefore the batch's
-                // data hash (input, len, data_rlc, output_rlc) are in the 4th 300 keccak rows;
-                //
-                // the following flag is build to indicate which row the final data_rlc exists
+                // the following flags are build to indicate which row the final data_rlc exists
                 //
                 // #valid snarks | offset of data hash | flags
-                // 1,2,3,4       | 0                   | 1, 0, 0, 0
-                // 5,6,7,8       | 32                  | 0, 1, 0, 0
-                // 9,10,11,12    | 64                  | 0, 0, 1, 0
-                // 13,14,15,16   | 96                  | 0, 0, 0, 1
-
-                let five = {
-                    let five = rlc_config.load_private(&mut region, &Fr::from(5), &mut offset)?;
-                    let five_cell = rlc_config.five_cell(five.cell().region_index);
-                    region.constrain_equal(five_cell, five.cell())?;
-                    five
-                };
-                let nine = {
-                    let nine = rlc_config.load_private(&mut region, &Fr::from(9), &mut offset)?;
-                    let nine_cell = rlc_config.nine_cell(nine.cell().region_index);
-                    region.constrain_equal(nine_cell, nine.cell())?;
-                    nine
-                };
-                let thirteen = {
-                    let thirteen =
-                        rlc_config.load_private(&mut region, &Fr::from(13), &mut offset)?;
-                    let thirteen_cell = rlc_config.thirteen_cell(thirteen.cell().region_index);
-                    region.constrain_equal(thirteen_cell, thirteen.cell())?;
-                    thirteen
-                };
-
-                let smaller_or_eq_4 = rlc_config.is_smaller_than(
-                    &mut region,
-                    &num_valid_snarks,
-                    &five,
-                    &mut offset,
-                )?;
-                let greater_than_4 = rlc_config.not(&mut region, &smaller_or_eq_4, &mut offset)?;
-                let smaller_or_eq_8 = rlc_config.is_smaller_than(
-                    &mut region,
-                    &num_valid_snarks,
-                    &nine,
-                    &mut offset,
-                )?;
-                let greater_than_8 = rlc_config.not(&mut region, &smaller_or_eq_8, &mut offset)?;
-                let smaller_or_eq_12 = rlc_config.is_smaller_than(
-                    &mut region,
-                    &num_valid_snarks,
-                    &thirteen,
-                    &mut offset,
-                )?;
-                let greater_than_12 =
-                    rlc_config.not(&mut region, &smaller_or_eq_12, &mut offset)?;
+                // 1,2,3,4       | 0                   | 1, 0, 0, 0, ...
+                // 5,6,7,8       | 32                  | 0, 1, 0, 0, ...
+                // 9,10,11,12    | 64                  | 0, 0, 1, 0, ...
+                // 13,14,15,16   | 96                  | 0, 0, 0, 1, ...
+                // ...           | ...                 | ...
+                let num_data_hash_rounds = get_data_hash_keccak_updates(N_SNARKS);
+
+                // more_rounds[k-1] = 1 iff the batch's data hash needs more than k rounds, i.e.,
+                // iff num_of_valid_snarks >= ceil(136 * k / 32)
+                let more_rounds = (1..num_data_hash_rounds)
+                    .map(|k| -> Result<_, halo2_proofs::plonk::Error> {
+                        let threshold = (INPUT_LEN_PER_ROUND * k + DIGEST_LEN - 1) / DIGEST_LEN;
+                        let threshold = rlc_config.load_constant(
+                            &mut region,
+                            k - 1,
+                            &Fr::from(threshold as u64),
+                            &mut offset,
+                        )?;
+                        let is_smaller = rlc_config.is_smaller_than(
+                            &mut region,
+                            &num_valid_snarks,
+                            &threshold,
+                            &mut offset,
+                        )?;
+                        rlc_config.not(&mut region, &is_smaller, &mut offset)
+                    })
+                    .collect::<Result<Vec<_>, halo2_proofs::plonk::Error>>()?;
 
-                let flag1 = smaller_or_eq_4;
-                let flag2 =
-                    rlc_config.mul(&mut region, &greater_than_4, &smaller_or_eq_8, &mut offset)?;
-                let flag3 =
-                    rlc_config.mul(&mut region, &greater_than_8, &smaller_or_eq_12, &mut offset)?;
-                let flag4 = greater_than_12;
+                // round_flags[k] = 1 iff the batch's data hash uses exactly k+1 rounds
+                let mut round_flags = vec![];
+                for k in 0..num_data_hash_rounds {
+                    let flag = match (k.checked_sub(1), more_rounds.get(k)) {
+                        (None, None) => {
+                            let one =
+                                rlc_config.load_private(&mut region, &Fr::one(), &mut offset)?;
+                            let one_cell = rlc_config.one_cell(one.cell().region_index);
+                            region.constrain_equal(one.cell(), one_cell)?;
+                            one
+                        }
+                        (None, Some(next)) => rlc_config.not(&mut region, next, &mut offset)?,
+                        (Some(prev), None) => more_rounds[prev].clone(),
+                        (Some(prev), Some(next)) => {
+                            let not_next = rlc_config.not(&mut region, next, &mut offset)?;
+                            rlc_config.mul(
+                                &mut region,
+                                &more_rounds[prev],
+                                &not_next,
+                                &mut offset,
+                            )?
+                        }
+                    };
+                    round_flags.push(flag);
+                }
 
                 log::trace!(
-                    "flags: {:?} {:?} {:?} {:?}",
-                    flag1.value(),
-                    flag2.value(),
-                    flag3.value(),
-                    flag4.value()
+                    "flags: {:?}",
+                    round_flags
+                        .iter()
+                        .map(|flag| flag.value())
+                        .collect::<Vec<_>>()
                 );
                 // ====================================================
                 // parse the hashes
@@ -625,25 +715,20 @@ pub(crate) fn conditional_constraints(
                     batch_pi_hash_preimage,
                     chunk_pi_hash_preimages,
                     potential_batch_data_hash_preimage,
-                ) = parse_hash_preimage_cells(hash_input_cells);
+                ) = parse_hash_preimage_cells::<N_SNARKS>(hash_input_cells, pi_schema, with_blob);
 
                 // digests
                 let (
                     _batch_pi_hash_digest,
                     _chunk_pi_hash_digests,
                     potential_batch_data_hash_digest,
-                ) = parse_hash_digest_cells(hash_output_cells);
+                ) = parse_hash_digest_cells::<N_SNARKS>(hash_output_cells, with_blob);
                 // ====================================================
                 // start the actual statements
                 // ====================================================
                 //
                 // 1 batch_data_hash digest is reused for public input hash
                 //
-                // the following part of the code is hard coded for the case where
-                //   MAX_AGG_SNARKS <= 10
-                // in theory it may support up to 12 SNARKS (not tested)
-                // more SNARKs beyond 12 will require a revamp of the circuit
-                //
                 // public input hash is build as
                 //  keccak(
                 //      chain_id ||
@@ -654,61 +739,44 @@ pub(crate) fn conditional_constraints(
                 //
                 // batchDataHash = keccak(chunk[0].dataHash || ... || chunk[k-1].dataHash)
                 //
-                //
-                // #valid snarks | offset of data hash | flags
-                // 1,2,3,4       | 0                   | 1, 0, 0, 0
-                // 5,6,7,8       | 32                  | 0, 1, 0, 0
-                // 9,10,11,12    | 64                  | 0, 0, 1, 0
-                // 13,14,15,16   | 96                  | 0, 0, 0, 1
+                // the k-th candidate digest of the batch's data hash starts at offset 32 * k,
+                // and is selected by round_flags[k]
+                let data_hash_index = pi_schema.index(PiField::DataHash);
                 for i in 0..4 {
                     for j in 0..8 {
+                        let candidates = (0..num_data_hash_rounds)
+                            .map(|k| {
+                                potential_batch_data_hash_digest[(3 - i) * 8 + j + DIGEST_LEN * k]
+                                    .clone()
+                            })
+                            .collect::<Vec<_>>();
                         // sanity check
                         assert_exist(
-                            &batch_pi_hash_preimage[i * 8 + j + CHUNK_DATA_HASH_INDEX],
-                            &[
-                                potential_batch_data_hash_digest[(3 - i) * 8 + j].clone(),
-                                potential_batch_data_hash_digest[(3 - i) * 8 + j + 32].clone(),
-                                potential_batch_data_hash_digest[(3 - i) * 8 + j + 64].clone(),
-                                potential_batch_data_hash_digest[(3 - i) * 8 + j + 96].clone(),
-                            ],
+                            &batch_pi_hash_preimage[i * 8 + j + data_hash_index],
+                            &candidates,
                         )?;
                         // assert
-                        // batch_pi_hash_preimage[i * 8 + j + CHUNK_DATA_HASH_INDEX]
-                        // = flag1 * potential_batch_data_hash_digest[(3 - i) * 8 + j]
-                        // + flag2 * potential_batch_data_hash_digest[(3 - i) * 8 + j + 32]
-                        // + flag3 * potential_batch_data_hash_digest[(3 - i) * 8 + j + 64]
-                        // + flag4 * potential_batch_data_hash_digest[(3 - i) * 8 + j + 96]
-
-                        let rhs = rlc_config.mul(
-                            &mut region,
-                            &flag1,
-                            &potential_batch_data_hash_digest[(3 - i) * 8 + j],
-                            &mut offset,
-                        )?;
-                        let rhs = rlc_config.mul_add(
-                            &mut region,
-                            &flag2,
-                            &potential_batch_data_hash_digest[(3 - i) * 8 + j + 32],
-                            &rhs,
-                            &mut offset,
-                        )?;
-                        let rhs = rlc_config.mul_add(
-                            &mut region,
-                            &flag3,
-                            &potential_batch_data_hash_digest[(3 - i) * 8 + j + 64],
-                            &rhs,
-                            &mut offset,
-                        )?;
-                        let rhs = rlc_config.mul_add(
+                        // batch_pi_hash_preimage[i * 8 + j + data_hash_index]
+                        // = sum_k round_flags[k]
+                        //     * potential_batch_data_hash_digest[(3 - i) * 8 + j + 32 * k]
+                        let mut rhs = rlc_config.mul(
                             &mut region,
-                            &flag4,
-                            &potential_batch_data_hash_digest[(3 - i) * 8 + j + 96],
-                            &rhs,
+                            &round_flags[0],
+                            &candidates[0],
                             &mut offset,
                         )?;
+                        for (flag, candidate) in round_flags.iter().zip(candidates.iter()).skip(1) {
+                            rhs = rlc_config.mul_add(
+                                &mut region,
+                                flag,
+                                candidate,
+                                &rhs,
+                                &mut offset,
+                            )?;
+                        }
 
                         region.constrain_equal(
-                            batch_pi_hash_preimage[i * 8 + j + CHUNK_DATA_HASH_INDEX].cell(),
+                            batch_pi_hash_preimage[i * 8 + j + data_hash_index].cell(),
                             rhs.cell(),
                         )?;
                     }
@@ -726,15 +794,15 @@ pub(crate) fn conditional_constraints(
                 //        chunk[i].postStateRoot ||
                 //        chunk[i].withdrawRoot  ||
                 //        chunk[i].datahash)
-                for i in 0..MAX_AGG_SNARKS {
+                for i in 0..N_SNARKS {
                     for j in 0..DIGEST_LEN {
                         assert_conditional_equal(
-                            &chunk_pi_hash_preimages[i][j + CHUNK_DATA_HASH_INDEX],
+                            &chunk_pi_hash_preimages[i][j + data_hash_index],
                             &potential_batch_data_hash_preimage[i * DIGEST_LEN + j],
                             &chunk_is_valid_cells[i],
                             format!(
                                 "chunk_{i}'s data hash does not match batch's: {:?} {:?} {:?}",
-                                &chunk_pi_hash_preimages[i][j + CHUNK_DATA_HASH_INDEX].value(),
+                                &chunk_pi_hash_preimages[i][j + data_hash_index].value(),
                                 &potential_batch_data_hash_preimage[i * DIGEST_LEN + j].value(),
                                 &chunk_is_valid_cells[i].value()
                             )
@@ -742,7 +810,7 @@ pub(crate) fn conditional_constraints(
                         )?;
                         rlc_config.conditional_enforce_equal(
                             &mut region,
-                            &chunk_pi_hash_preimages[i][j + CHUNK_DATA_HASH_INDEX],
+                            &chunk_pi_hash_preimages[i][j + data_hash_index],
                             &potential_batch_data_hash_preimage[i * DIGEST_LEN + j],
                             &chunk_is_valid_cells[i],
                             &mut offset,
@@ -751,25 +819,27 @@ pub(crate) fn conditional_constraints(
                 }
 
                 // 4  __valid__ chunks are continuous: they are linked via the state roots
-                for i in 0..MAX_AGG_SNARKS - 1 {
+                let prev_state_root_index = pi_schema.index(PiField::PrevStateRoot);
+                let post_state_root_index = pi_schema.index(PiField::PostStateRoot);
+                for i in 0..N_SNARKS - 1 {
                     for j in 0..DIGEST_LEN {
                         // sanity check
                         assert_conditional_equal(
-                            &chunk_pi_hash_preimages[i + 1][PREV_STATE_ROOT_INDEX + j],
-                            &chunk_pi_hash_preimages[i][POST_STATE_ROOT_INDEX + j],
+                            &chunk_pi_hash_preimages[i + 1][prev_state_root_index + j],
+                            &chunk_pi_hash_preimages[i][post_state_root_index + j],
                             &chunk_is_valid_cells[i + 1],
                             format!(
                                 "chunk_{i} is not continuous: {:?} {:?} {:?}",
-                                &chunk_pi_hash_preimages[i + 1][PREV_STATE_ROOT_INDEX + j].value(),
-                                &chunk_pi_hash_preimages[i][POST_STATE_ROOT_INDEX + j].value(),
+                                &chunk_pi_hash_preimages[i + 1][prev_state_root_index + j].value(),
+                                &chunk_pi_hash_preimages[i][post_state_root_index + j].value(),
                                 &chunk_is_valid_cells[i + 1].value(),
                             )
                             .as_str(),
                         )?;
                         rlc_config.conditional_enforce_equal(
                             &mut region,
-                            &chunk_pi_hash_preimages[i + 1][PREV_STATE_ROOT_INDEX + j],
-                            &chunk_pi_hash_preimages[i][POST_STATE_ROOT_INDEX + j],
+                            &chunk_pi_hash_preimages[i + 1][prev_state_root_index + j],
+                            &chunk_pi_hash_preimages[i][post_state_root_index + j],
                             &chunk_is_valid_cells[i + 1],
                             &mut offset,
                         )?;
@@ -783,9 +853,10 @@ pub(crate) fn conditional_constraints(
                     .map(|chunk_is_valid| rlc_config.not(&mut region, chunk_is_valid, &mut offset))
                     .collect::<Result<Vec<_>, halo2_proofs::plonk::Error>>()?;
 
-                let chunk_pi_hash_rlc_cells = parse_pi_hash_rlc_cells(data_rlc_cells);
+                let chunk_pi_hash_rlc_cells =
+                    parse_pi_hash_rlc_cells::<N_SNARKS>(data_rlc_cells, pi_schema);
 
-                for i in 1..MAX_AGG_SNARKS {
+                for i in 1..N_SNARKS {
                     rlc_config.conditional_enforce_equal(
                         &mut region,
                         chunk_pi_hash_rlc_cells[i - 1],
@@ -805,25 +876,52 @@ pub(crate) fn conditional_constraints(
                 }
 
                 // 7. the hash input length are correct
-                // - first MAX_AGG_SNARKS + 1 hashes all have 136 bytes input
+                // - first N_SNARKS + 1 hashes all have the preimage length of the pi schema
                 // - batch's data_hash length is 32 * number_of_valid_snarks
 
-                // - first MAX_AGG_SNARKS + 1 hashes all have 136 bytes input
+                // - first N_SNARKS + 1 hashes all have the preimage length of the pi schema
+                let pi_rounds = pi_schema.num_keccak_rounds();
+                // the constants loaded for more_rounds take the first free rows
+                let pi_hash_len = rlc_config.load_constant(
+                    &mut region,
+                    num_data_hash_rounds - 1,
+                    &Fr::from(pi_schema.preimage_len() as u64),
+                    &mut offset,
+                )?;
                 hash_input_len_cells
                     .iter()
                     .skip(1)
-                    .take((MAX_AGG_SNARKS + 1) * 2)
-                    .chunks(2)
+                    .take((N_SNARKS + 1) * pi_rounds)
+                    .chunks(pi_rounds)
                     .into_iter()
                     .try_for_each(|chunk| {
                         let cur_hash_len = chunk.last().unwrap(); // safe unwrap
-                        region.constrain_equal(
-                            cur_hash_len.cell(),
-                            rlc_config
-                                .one_hundred_and_thirty_six_cell(cur_hash_len.cell().region_index),
-                        )
+                        region.constrain_equal(cur_hash_len.cell(), pi_hash_len.cell())
                     })?;
 
+                // - the hashes of the blob have the length of the padded blob bytes and of the
+                //   challenge preimage
+                let blob_round_index = (N_SNARKS + 1) * pi_rounds + 1;
+                let num_blob_rounds = get_blob_keccak_updates(with_blob);
+                if with_blob {
+                    let blob_hash_lens = [
+                        (BLOB_BYTES_KECCAK_ROUNDS, N_BLOB_BYTES),
+                        (num_blob_rounds, CHALLENGE_PREIMAGE_LEN),
+                    ];
+                    for (index, (last_round, hash_len)) in blob_hash_lens.into_iter().enumerate() {
+                        let hash_len = rlc_config.load_constant(
+                            &mut region,
+                            num_data_hash_rounds + index,
+                            &Fr::from(hash_len as u64),
+                            &mut offset,
+                        )?;
+                        region.constrain_equal(
+                            hash_input_len_cells[blob_round_index + last_round - 1].cell(),
+                            hash_len.cell(),
+                        )?;
+                    }
+                }
+
                 // - batch's data_hash length is 32 * number_of_valid_snarks
                 let const32 = rlc_config.load_private(&mut region, &Fr::from(32), &mut offset)?;
                 let const32_cell = rlc_config.thirty_two_cell(const32.cell().region_index);
@@ -831,62 +929,46 @@ pub(crate) fn conditional_constraints(
                 let data_hash_inputs_len =
                     rlc_config.mul(&mut region, &num_valid_snarks, &const32, &mut offset)?;
 
+                // the data hash of the batch starts at the (pi_rounds * (N_SNARKS + 1) + 1)-th
+                // round, i.e., the (2 * N_SNARKS + 3)-th round for 2 rounds per pi hash, followed
+                // by the rounds of the hashes of the blob if any
+                let data_hash_round_index = blob_round_index + num_blob_rounds;
+                let data_hash_rounds =
+                    data_hash_round_index..data_hash_round_index + num_data_hash_rounds;
+
                 // sanity check
                 assert_exist(
                     &data_hash_inputs_len,
-                    &[
-                        hash_input_len_cells[MAX_AGG_SNARKS * 2 + 3].clone(),
-                        hash_input_len_cells[MAX_AGG_SNARKS * 2 + 4].clone(),
-                        hash_input_len_cells[MAX_AGG_SNARKS * 2 + 5].clone(),
-                        hash_input_len_cells[MAX_AGG_SNARKS * 2 + 6].clone(),
-                    ],
+                    &hash_input_len_cells[data_hash_rounds.clone()],
                 )?;
 
                 log::trace!("data_hash_inputs: {:?}", data_hash_inputs_len.value());
-                log::trace!(
-                    "candidate 1: {:?}",
-                    hash_input_len_cells[MAX_AGG_SNARKS * 2 + 3].value()
-                );
-                log::trace!(
-                    "candidate 2: {:?}",
-                    hash_input_len_cells[MAX_AGG_SNARKS * 2 + 4].value()
-                );
-                log::trace!(
-                    "candidate 3: {:?}",
-                    hash_input_len_cells[MAX_AGG_SNARKS * 2 + 5].value()
-                );
-                log::trace!(
-                    "candidate 4: {:?}",
-                    hash_input_len_cells[MAX_AGG_SNARKS * 2 + 6].value()
-                );
+                for (k, candidate) in hash_input_len_cells[data_hash_rounds.clone()]
+                    .iter()
+                    .enumerate()
+                {
+                    log::trace!("candidate {}: {:?}", k + 1, candidate.value());
+                }
 
                 let mut data_hash_inputs_len_rec = rlc_config.mul(
                     &mut region,
-                    &hash_input_len_cells[MAX_AGG_SNARKS * 2 + 3],
-                    &flag1,
-                    &mut offset,
-                )?;
-                data_hash_inputs_len_rec = rlc_config.mul_add(
-                    &mut region,
-                    &hash_input_len_cells[MAX_AGG_SNARKS * 2 + 4],
-                    &flag2,
-                    &data_hash_inputs_len_rec,
-                    &mut offset,
-                )?;
-                data_hash_inputs_len_rec = rlc_config.mul_add(
-                    &mut region,
-                    &hash_input_len_cells[MAX_AGG_SNARKS * 2 + 5],
-                    &flag3,
-                    &data_hash_inputs_len_rec,
-                    &mut offset,
-                )?;
-                data_hash_inputs_len_rec = rlc_config.mul_add(
-                    &mut region,
-                    &hash_input_len_cells[MAX_AGG_SNARKS * 2 + 6],
-                    &flag4,
-                    &data_hash_inputs_len_rec,
+                    &hash_input_len_cells[data_hash_round_index],
+                    &round_flags[0],
                     &mut offset,
                 )?;
+                for (hash_input_len, flag) in hash_input_len_cells[data_hash_rounds.clone()]
+                    .iter()
+                    .zip(round_flags.iter())
+                    .skip(1)
+                {
+                    data_hash_inputs_len_rec = rlc_config.mul_add(
+                        &mut region,
+                        hash_input_len,
+                        flag,
+                        &data_hash_inputs_len_rec,
+                        &mut offset,
+                    )?;
+                }
 
                 // sanity check
                 assert_equal(
@@ -917,67 +999,58 @@ pub(crate) fn conditional_constraints(
 
                 let rlc_cell = rlc_config.rlc_with_flag(
                     &mut region,
-                    potential_batch_data_hash_preimage[..DIGEST_LEN * MAX_AGG_SNARKS].as_ref(),
+                    potential_batch_data_hash_preimage[..DIGEST_LEN * N_SNARKS].as_ref(),
                     &challenge_cell,
                     &flags,
                     &mut offset,
                 )?;
 
-                assert_exist(
-                    &rlc_cell,
-                    &[
-                        data_rlc_cells[MAX_AGG_SNARKS * 2 + 3].clone(),
-                        data_rlc_cells[MAX_AGG_SNARKS * 2 + 4].clone(),
-                        data_rlc_cells[MAX_AGG_SNARKS * 2 + 5].clone(),
-                        data_rlc_cells[MAX_AGG_SNARKS * 2 + 6].clone(),
-                    ],
-                )?;
+                assert_exist(&rlc_cell, &data_rlc_cells[data_hash_rounds.clone()])?;
                 log::trace!("rlc from chip {:?}", rlc_cell.value());
-                log::trace!(
-                    "rlc from table {:?}",
-                    data_rlc_cells[MAX_AGG_SNARKS * 2 + 3].value()
-                );
-                log::trace!(
-                    "rlc from table {:?}",
-                    data_rlc_cells[MAX_AGG_SNARKS * 2 + 4].value()
-                );
-                log::trace!(
-                    "rlc from table {:?}",
-                    data_rlc_cells[MAX_AGG_SNARKS * 2 + 5].value()
-                );
+                for data_rlc in data_rlc_cells[data_hash_rounds.clone()].iter() {
+                    log::trace!("rlc from table {:?}", data_rlc.value());
+                }
 
-                // assertion
-                let t1 = rlc_config.sub(
+                // assertion: the rlc matches the candidate of the last round of the data hash,
+                // i.e.,
+                // rlc_cell == sum_k round_flags[k] * data_rlc_cells[data_hash_round_index + k]
+                // the rounds after the last one may hold the next hashes, so matching any of the
+                // candidates is not enough
+                let mut data_rlc_rec = rlc_config.mul(
                     &mut region,
-                    &rlc_cell,
-                    &data_rlc_cells[MAX_AGG_SNARKS * 2 + 3],
-                    &mut offset,
-                )?;
-                let t2 = rlc_config.sub(
-                    &mut region,
-                    &rlc_cell,
-                    &data_rlc_cells[MAX_AGG_SNARKS * 2 + 4],
-                    &mut offset,
-                )?;
-                let t3 = rlc_config.sub(
-                    &mut region,
-                    &rlc_cell,
-                    &data_rlc_cells[MAX_AGG_SNARKS * 2 + 5],
+                    &data_rlc_cells[data_hash_round_index],
+                    &round_flags[0],
                     &mut offset,
                 )?;
-                let t4 = rlc_config.sub(
-                    &mut region,
+                for (data_rlc, flag) in data_rlc_cells[data_hash_rounds.clone()]
+                    .iter()
+                    .zip(round_flags.iter())
+                    .skip(1)
+                {
+                    data_rlc_rec = rlc_config.mul_add(
+                        &mut region,
+                        data_rlc,
+                        flag,
+                        &data_rlc_rec,
+                        &mut offset,
+                    )?;
+                }
+                // sanity check
+                assert_equal(
                     &rlc_cell,
-                    &data_rlc_cells[MAX_AGG_SNARKS * 2 + 6],
-                    &mut offset,
+                    &data_rlc_rec,
+                    format!(
+                        "data_hash rlc do not match: {:?} {:?}",
+                        &rlc_cell.value(),
+                        &data_rlc_rec.value(),
+                    )
+                    .as_str(),
                 )?;
-                let t1t2 = rlc_config.mul(&mut region, &t1, &t2, &mut offset)?;
-                let t1t2t3 = rlc_config.mul(&mut region, &t1t2, &t3, &mut offset)?;
-                let t1t2t3t4 = rlc_config.mul(&mut region, &t1t2t3, &t4, &mut offset)?;
-                rlc_config.enforce_zero(&mut region, &t1t2t3t4)?;
+                region.constrain_equal(rlc_cell.cell(), data_rlc_rec.cell())?;
 
                 // 9. is_final_cells are set correctly
-                // the is_final_cells are set as
+                // the is_final_cells are set as follows, for 2 rounds per pi hash (each pi hash
+                // uses pi_rounds rounds in general, of which only the last one is final)
                 // index                     | value | comments
                 // --------------------------|-------|------------
                 // 0                         | 0     | 0-th row is prefix pad
@@ -988,61 +1061,91 @@ pub(crate) fn conditional_constraints(
                 // 5                         | 0     | third keccak:
                 // 6                         | 1     |   chunk[1].pi_hash use 2 rounds
                 // ...
-                // 2*(MAX_AGG_SNARKS) + 1    | 0     | MAX_AGG_SNARKS+1's keccak
-                // 2*(MAX_AGG_SNARKS) + 2    | 1     |   chunk[MAX_AGG_SNARKS].pi_hash use 2 rounds
-                // 2*(MAX_AGG_SNARKS) + 3    | a     | MAX_AGG_SNARKS+2's keccak
-                // 2*(MAX_AGG_SNARKS) + 4    | b     |   batch_data_hash may use 1, 2, 3
-                // 2*(MAX_AGG_SNARKS) + 5    | c     |   or 4 rounds
-                // 2*(MAX_AGG_SNARKS) + 6    | d     |
+                // 2*(N_SNARKS) + 1          | 0     | N_SNARKS+1's keccak
+                // 2*(N_SNARKS) + 2          | 1     |   chunk[N_SNARKS].pi_hash use 2 rounds
+                // 2*(N_SNARKS) + 3          | a     | N_SNARKS+2's keccak
+                // 2*(N_SNARKS) + 4          | b     |   batch_data_hash may use 1, 2, 3, ...
+                // 2*(N_SNARKS) + 5          | c     |   up to
+                // get_data_hash_keccak_updates(N_SNARKS) 2*(N_SNARKS) + 6
+                // | d     |   rounds ...                       | ...   |
+                //
+                // so a,b,c,d,... are constrained as follows
                 //
-                // so a,b,c are constrained as follows
+                // #valid snarks | flags           | a | b | c | d | ...
+                // 1,2,3,4       | 1, 0, 0, 0, ... | 1 | - | - | - | -
+                // 5,6,7,8       | 0, 1, 0, 0, ... | 0 | 1 | - | - | -
+                // 9,10,11,12    | 0, 0, 1, 0, ... | 0 | 0 | 1 | - | -
+                // 13,14,15,16   | 0, 0, 0, 1, ... | 0 | 0 | 0 | 1 | -
+                // ...           | ...             | ...
                 //
-                // #valid snarks | flags        | a | b | c | d
-                // 1,2,3,4       | 1, 0, 0, 0   | 1 | - | - | -
-                // 5,6,7,8       | 0, 1, 0, 0   | 0 | 1 | - | -
-                // 9,10,11,12    | 0, 0, 1, 0   | 0 | 0 | 1 | -
-                // 13,14,15,16   | 0, 0, 0, 1   | 0 | 0 | 0 | 1
-
-                // first MAX_AGG_SNARKS + 1 keccak
-                for mut chunk in is_final_cells
+                // for a batch with a blob, the rounds of the blob bytes digest and the challenge
+                // digest are between the chunk[N_SNARKS].pi_hash and the batch_data_hash, of
+                // which only the last rounds are final
+
+                // first N_SNARKS + 1 keccak
+                for (i, is_final) in is_final_cells
                     .iter()
                     .skip(1)
-                    .take((MAX_AGG_SNARKS + 1) * 2)
-                    .into_iter()
-                    .chunks(2)
-                    .into_iter()
+                    .take((N_SNARKS + 1) * pi_rounds)
+                    .enumerate()
                 {
-                    // first round
-                    let first_round_cell = chunk.next().unwrap();
-                    let second_round_cell = chunk.next().unwrap();
-                    region.constrain_equal(
-                        first_round_cell.cell(),
-                        rlc_config.zero_cell(first_round_cell.cell().region_index),
-                    )?;
-                    region.constrain_equal(
-                        second_round_cell.cell(),
-                        rlc_config.one_cell(second_round_cell.cell().region_index),
-                    )?;
+                    let expected = if i % pi_rounds == pi_rounds - 1 {
+                        rlc_config.one_cell(is_final.cell().region_index)
+                    } else {
+                        rlc_config.zero_cell(is_final.cell().region_index)
+                    };
+                    region.constrain_equal(is_final.cell(), expected)?;
+                }
+                // keccak of the blob
+                for (i, is_final) in is_final_cells
+                    .iter()
+                    .skip(blob_round_index)
+                    .take(num_blob_rounds)
+                    .enumerate()
+                {
+                    let expected = if i == BLOB_BYTES_KECCAK_ROUNDS - 1 || i == num_blob_rounds - 1
+                    {
+                        rlc_config.one_cell(is_final.cell().region_index)
+                    } else {
+                        rlc_config.zero_cell(is_final.cell().region_index)
+                    };
+                    region.constrain_equal(is_final.cell(), expected)?;
                 }
                 // last keccak
-                // we constrain a * flag1 + b * flag2 + c * flag3 + d * flag4 == 1
-                let a = &is_final_cells[2 * (MAX_AGG_SNARKS) + 3];
-                let b = &is_final_cells[2 * (MAX_AGG_SNARKS) + 4];
-                let c = &is_final_cells[2 * (MAX_AGG_SNARKS) + 5];
-                let d = &is_final_cells[2 * (MAX_AGG_SNARKS) + 6];
-                let mut left = rlc_config.mul(&mut region, a, &flag1, &mut offset)?;
-                left = rlc_config.mul_add(&mut region, b, &flag2, &left, &mut offset)?;
-                left = rlc_config.mul_add(&mut region, c, &flag3, &left, &mut offset)?;
-                left = rlc_config.mul_add(&mut region, d, &flag4, &left, &mut offset)?;
+                // we constrain a * flag1 + b * flag2 + c * flag3 + d * flag4 + ... == 1
+                let mut left = rlc_config.mul(
+                    &mut region,
+                    &is_final_cells[data_hash_round_index],
+                    &round_flags[0],
+                    &mut offset,
+                )?;
+                for (is_final, flag) in is_final_cells[data_hash_rounds]
+                    .iter()
+                    .zip(round_flags.iter())
+                    .skip(1)
+                {
+                    left = rlc_config.mul_add(&mut region, is_final, flag, &left, &mut offset)?;
+                }
                 region
                     .constrain_equal(left.cell(), rlc_config.one_cell(left.cell().region_index))?;
 
+                // 11. batch_pi_hash uses a zero blob data commitment for a batch without a blob
+                if let Some(index) = pi_schema.offset(PiField::BlobDataCommitment) {
+                    if !with_blob {
+                        for byte in batch_pi_hash_preimage[index..index + DIGEST_LEN].iter() {
+                            region.constrain_equal(
+                                byte.cell(),
+                                rlc_config.zero_cell(byte.cell().region_index),
+                            )?;
+                        }
+                    }
+                }
+
                 log::trace!("rlc chip uses {} rows", offset);
-                Ok(())
+                Ok(chunks_are_padding)
             },
         )
-        .map_err(|e| Error::AssertionFailure(format!("aggregation: {e}")))?;
-    Ok(())
+        .map_err(|e| Error::AssertionFailure(format!("aggregation: {e}")))
 }
 
 /// Input a list of flags whether the snark is valid
@@ -3,6 +3,8 @@ mod aggregation;
 /// This module implements `Batch` related data types.
 /// A batch is a list of chunk.
 mod batch;
+/// This module implements the blob of a batch and its point evaluation.
+mod blob;
 // This module implements `Chunk` related data types.
 // A chunk is a list of blocks.
 mod chunk;
@@ -22,9 +24,15 @@ mod tests;
 
 pub use self::core::extract_proof_and_instances_with_pairing_check;
 pub use aggregation::*;
-pub use batch::BatchHash;
+pub use batch::{
+    agg_snarks_tier, agg_tree_next_merge_level, batch_data_hash, chunk_hash_of_batch, BatchHash,
+};
+pub use blob::{
+    BlobData, BlobPointEvaluation, BLOB_PI_LEN, BLOB_WIDTH, MAX_BLOB_TXS, N_BLOB_BYTES,
+    N_DATA_BYTES_PER_COEFFICIENT,
+};
 pub use chunk::ChunkHash;
 pub use compression::*;
-pub use constants::MAX_AGG_SNARKS;
 pub(crate) use constants::*;
+pub use constants::{AGG_SNARKS_TIERS, MAX_AGG_SNARKS};
 pub use param::*;
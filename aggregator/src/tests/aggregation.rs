@@ -1,24 +1,187 @@
 use std::{fs, path::Path, process};
 
 use ark_std::{end_timer, start_timer, test_rng};
+use eth_types::{
+    pi_schema::{PiBatchLayout, PiSchema},
+    H256,
+};
+use ethers_core::utils::keccak256;
 use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr, poly::commitment::Params};
 use itertools::Itertools;
 use snark_verifier::loader::halo2::halo2_ecc::halo2_base::utils::fs::gen_srs;
 use snark_verifier_sdk::{gen_pk, gen_snark_shplonk, verify_snark_shplonk, CircuitExt};
 
 use crate::{
-    aggregation::AggregationCircuit, batch::BatchHash, constants::MAX_AGG_SNARKS, layer_0,
-    tests::mock_chunk::MockChunkCircuit, ChunkHash,
+    aggregation::AggregationCircuit,
+    batch::{
+        agg_snarks_tier, agg_tree_next_merge_level, batch_data_hash, chunk_hash_of_batch, BatchHash,
+    },
+    constants::{AGG_SNARKS_TIERS, MAX_AGG_SNARKS},
+    layer_0,
+    tests::mock_chunk::MockChunkCircuit,
+    BlobData, ChunkHash, ConfigParams,
 };
 
+#[test]
+fn test_agg_snarks_tier() {
+    assert_eq!(agg_snarks_tier(1), Some(4));
+    assert_eq!(agg_snarks_tier(4), Some(4));
+    assert_eq!(agg_snarks_tier(5), Some(8));
+    assert_eq!(agg_snarks_tier(15), Some(15));
+    assert_eq!(agg_snarks_tier(16), Some(32));
+    assert_eq!(agg_snarks_tier(MAX_AGG_SNARKS), Some(MAX_AGG_SNARKS));
+    assert_eq!(agg_snarks_tier(MAX_AGG_SNARKS + 1), None);
+    assert!(AGG_SNARKS_TIERS.windows(2).all(|w| w[0] < w[1]));
+}
+
+#[test]
+fn test_chunk_hash_of_batch() {
+    let mut rng = test_rng();
+
+    for pi_schema in PiSchema::ALL {
+        let mut chunks = (0..3)
+            .map(|_| ChunkHash::mock_random_chunk_hash_for_testing(&mut rng))
+            .collect_vec();
+        for chunk in chunks.iter_mut() {
+            chunk.pi_schema = pi_schema;
+        }
+        for i in 0..2 {
+            chunks[i + 1].prev_state_root = chunks[i].post_state_root;
+        }
+        let padded_chunk = ChunkHash::mock_padded_chunk_hash_for_testing(&chunks[2]);
+        let chunks_with_padding = [chunks.clone(), vec![padded_chunk]].concat();
+        let batch_hash = BatchHash::<4>::construct(&chunks_with_padding);
+
+        // a batch's snark can be aggregated again as a chunk with the same public input hash
+        let chunk_hash = chunk_hash_of_batch(&chunks);
+        assert_eq!(chunk_hash.public_input_hash(), batch_hash.public_input_hash);
+        assert_eq!(chunk_hash.data_hash, batch_hash.data_hash);
+        assert_eq!(chunk_hash.prev_state_root, chunks[0].prev_state_root);
+        assert_eq!(chunk_hash.post_state_root, chunks[2].post_state_root);
+        assert_eq!(chunk_hash.l1_msg_queue_hash, chunks[2].l1_msg_queue_hash);
+    }
+}
+
+#[test]
+fn test_agg_tree_next_merge_level() {
+    // full levels are aggregated eagerly, from the lowest one
+    assert_eq!(agg_tree_next_merge_level(&[3], 4, false), None);
+    assert_eq!(agg_tree_next_merge_level(&[4], 4, false), Some(0));
+    assert_eq!(agg_tree_next_merge_level(&[0, 4], 4, false), Some(1));
+    assert_eq!(agg_tree_next_merge_level(&[1, 3], 4, false), None);
+
+    // finalizing aggregates the remaining nodes into a root
+    assert_eq!(agg_tree_next_merge_level(&[], 4, true), None);
+    assert_eq!(agg_tree_next_merge_level(&[1], 4, true), Some(0));
+    assert_eq!(agg_tree_next_merge_level(&[0, 1], 4, true), None);
+    assert_eq!(agg_tree_next_merge_level(&[1, 3], 4, true), Some(0));
+    assert_eq!(agg_tree_next_merge_level(&[0, 4], 4, true), Some(1));
+    assert_eq!(agg_tree_next_merge_level(&[0, 1, 1], 4, true), Some(1));
+    assert_eq!(agg_tree_next_merge_level(&[0, 0, 2], 4, true), Some(2));
+    assert_eq!(agg_tree_next_merge_level(&[0, 0, 0, 1], 4, true), None);
+}
+
+#[test]
+fn test_batch_data_hash() {
+    let mut rng = test_rng();
+
+    let chunks = (0..3)
+        .map(|_| ChunkHash::mock_random_chunk_hash_for_testing(&mut rng))
+        .collect_vec();
+    let hash = |data_hashes: &[H256]| -> H256 {
+        keccak256(data_hashes.iter().flat_map(|h| h.0).collect_vec()).into()
+    };
+    let data_hashes = chunks.iter().map(|chunk| chunk.data_hash).collect_vec();
+
+    // a batch aggregated at once hashes the chunk data hashes flat
+    let flat = hash(&data_hashes);
+    assert_eq!(batch_data_hash(&chunks, PiBatchLayout::Flat), flat);
+    assert_eq!(chunk_hash_of_batch(&chunks).data_hash, flat);
+
+    // a tree of a single level is the same as the flat layout
+    let tree_4 = PiBatchLayout::Tree { arity: 4 };
+    assert_eq!(batch_data_hash(&chunks, tree_4), flat);
+
+    // a deeper tree nests the hashes of its nodes
+    let tree_2 = PiBatchLayout::Tree { arity: 2 };
+    let nested = hash(&[hash(&data_hashes[..2]), hash(&data_hashes[2..])]);
+    assert_eq!(batch_data_hash(&chunks, tree_2), nested);
+    assert_ne!(nested, flat);
+}
+
 #[test]
 fn test_aggregation_circuit() {
     env_logger::init();
 
-    let k = 20;
+    let k = 21;
 
     // This set up requires one round of keccak for chunk's data hash
-    let circuit = build_new_aggregation_circuit(2);
+    let circuit = build_new_aggregation_circuit::<15>(2);
+    let instance = circuit.instances();
+    let mock_prover = MockProver::<Fr>::run(k, &circuit, instance).unwrap();
+    mock_prover.assert_satisfied_par();
+}
+
+#[test]
+fn test_aggregation_circuit_smallest_tier() {
+    let k = 21;
+
+    // The smallest tier always uses a single round of keccak for chunk's data hash
+    let circuit = build_new_aggregation_circuit::<{ AGG_SNARKS_TIERS[0] }>(3);
+    let instance = circuit.instances();
+    let mock_prover = MockProver::<Fr>::run(k, &circuit, instance).unwrap();
+    mock_prover.assert_satisfied_par();
+}
+
+#[test]
+fn test_aggregation_circuit_pi_schema_v2() {
+    let k = 21;
+
+    // The pi hashes of the batch and its chunks carry the L1 message queue hash and the blob data
+    // commitment
+    let circuit =
+        build_new_aggregation_circuit_with_schema::<{ AGG_SNARKS_TIERS[0] }>(3, PiSchema::V2);
+    let instance = circuit.instances();
+    let mock_prover = MockProver::<Fr>::run(k, &circuit, instance).unwrap();
+    mock_prover.assert_satisfied_par();
+}
+
+#[ignore = "it takes too much time"]
+#[test]
+fn test_aggregation_circuit_largest_tier() {
+    // The largest tier needs a larger circuit, set by its own config.
+    let config_path = "./configs/aggregation_32.config";
+    let config: ConfigParams =
+        serde_json::from_reader(fs::File::open(config_path).unwrap()).unwrap();
+    std::env::set_var("AGGREGATION_CONFIG", config_path);
+
+    let circuit = build_new_aggregation_circuit::<{ AGG_SNARKS_TIERS[3] }>(MAX_AGG_SNARKS);
+    let instance = circuit.instances();
+    let mock_prover = MockProver::<Fr>::run(config.degree, &circuit, instance).unwrap();
+    mock_prover.assert_satisfied_par();
+}
+
+#[ignore = "it takes too much time"]
+#[test]
+fn test_aggregation_circuit_with_blob() {
+    let k = 21;
+
+    // The batch is posted as a blob of the L2 txs of its chunks, the second chunk having none.
+    // Each chunk commits to its txs, and the batch to the blob bytes.
+    let tx = |len: usize, seed: usize| (0..len).map(|i| ((i + seed) % 251) as u8).collect();
+    let blob = BlobData::new(
+        [1u8; 32].into(),
+        vec![
+            vec![tx(30_000, 0), tx(1, 1)],
+            vec![],
+            (0..500).map(|i| tx(100, i)).collect(),
+        ],
+    );
+    let circuit = build_new_aggregation_circuit_with_blob::<{ AGG_SNARKS_TIERS[0] }>(
+        3,
+        PiSchema::V2,
+        Some(blob),
+    );
     let instance = circuit.instances();
     let mock_prover = MockProver::<Fr>::run(k, &circuit, instance).unwrap();
     mock_prover.assert_satisfied_par();
@@ -29,12 +192,18 @@ fn test_aggregation_circuit() {
 fn test_aggregation_circuit_all_possible_num_snarks() {
     env_logger::init();
 
-    let k = 20;
+    mock_all_possible_num_snarks::<{ AGG_SNARKS_TIERS[0] }>();
+    mock_all_possible_num_snarks::<{ AGG_SNARKS_TIERS[1] }>();
+    mock_all_possible_num_snarks::<{ AGG_SNARKS_TIERS[2] }>();
+    mock_all_possible_num_snarks::<{ AGG_SNARKS_TIERS[3] }>();
+}
+
+fn mock_all_possible_num_snarks<const N_SNARKS: usize>() {
+    let k = 21;
 
-    for i in 1..=MAX_AGG_SNARKS {
-        println!("{i} real chunks and {} padded chunks", MAX_AGG_SNARKS - i);
-        // This set up requires one round of keccak for chunk's data hash
-        let circuit = build_new_aggregation_circuit(i);
+    for i in 1..=N_SNARKS {
+        println!("{i} real chunks and {} padded chunks", N_SNARKS - i);
+        let circuit = build_new_aggregation_circuit::<N_SNARKS>(i);
         let instance = circuit.instances();
         let mock_prover = MockProver::<Fr>::run(k, &circuit, instance).unwrap();
         mock_prover.assert_satisfied_par();
@@ -54,7 +223,7 @@ fn test_aggregation_circuit_full() {
     fs::create_dir(path).unwrap();
 
     // This set up requires one round of keccak for chunk's data hash
-    let circuit = build_new_aggregation_circuit(2);
+    let circuit = build_new_aggregation_circuit::<15>(2);
     let instance = circuit.instances();
     let mock_prover = MockProver::<Fr>::run(25, &circuit, instance).unwrap();
     mock_prover.assert_satisfied_par();
@@ -62,7 +231,7 @@ fn test_aggregation_circuit_full() {
     log::trace!("finished mock proving");
 
     let mut rng = test_rng();
-    let param = gen_srs(20);
+    let param = gen_srs(21);
 
     let pk = gen_pk(&param, &circuit, None);
     log::trace!("finished pk generation for circuit");
@@ -70,7 +239,7 @@ fn test_aggregation_circuit_full() {
     let snark = gen_snark_shplonk(&param, &pk, circuit.clone(), &mut rng, None::<String>);
     log::trace!("finished snark generation for circuit");
 
-    assert!(verify_snark_shplonk::<AggregationCircuit>(
+    assert!(verify_snark_shplonk::<AggregationCircuit<15>>(
         &param,
         snark,
         pk.get_vk()
@@ -78,11 +247,11 @@ fn test_aggregation_circuit_full() {
     log::trace!("finished verification for circuit");
 
     // This set up requires two rounds of keccak for chunk's data hash
-    let circuit = build_new_aggregation_circuit(5);
+    let circuit = build_new_aggregation_circuit::<15>(5);
     let snark = gen_snark_shplonk(&param, &pk, circuit, &mut rng, None::<String>);
     log::trace!("finished snark generation for circuit");
 
-    assert!(verify_snark_shplonk::<AggregationCircuit>(
+    assert!(verify_snark_shplonk::<AggregationCircuit<15>>(
         &param,
         snark,
         pk.get_vk()
@@ -90,7 +259,24 @@ fn test_aggregation_circuit_full() {
     log::trace!("finished verification for circuit");
 }
 
-fn build_new_aggregation_circuit(num_real_chunks: usize) -> AggregationCircuit {
+fn build_new_aggregation_circuit<const N_SNARKS: usize>(
+    num_real_chunks: usize,
+) -> AggregationCircuit<N_SNARKS> {
+    build_new_aggregation_circuit_with_schema(num_real_chunks, PiSchema::default())
+}
+
+fn build_new_aggregation_circuit_with_schema<const N_SNARKS: usize>(
+    num_real_chunks: usize,
+    pi_schema: PiSchema,
+) -> AggregationCircuit<N_SNARKS> {
+    build_new_aggregation_circuit_with_blob(num_real_chunks, pi_schema, None)
+}
+
+fn build_new_aggregation_circuit_with_blob<const N_SNARKS: usize>(
+    num_real_chunks: usize,
+    pi_schema: PiSchema,
+    blob: Option<BlobData>,
+) -> AggregationCircuit<N_SNARKS> {
     // inner circuit: Mock circuit
     let k0 = 8;
 
@@ -98,16 +284,24 @@ fn build_new_aggregation_circuit(num_real_chunks: usize) -> AggregationCircuit {
     let params = gen_srs(k0);
 
     let mut chunks_without_padding = (0..num_real_chunks)
-        .map(|_| ChunkHash::mock_random_chunk_hash_for_testing(&mut rng))
+        .map(|_| ChunkHash {
+            pi_schema,
+            ..ChunkHash::mock_random_chunk_hash_for_testing(&mut rng)
+        })
         .collect_vec();
     for i in 0..num_real_chunks - 1 {
         chunks_without_padding[i + 1].prev_state_root = chunks_without_padding[i].post_state_root;
     }
+    if let Some(blob) = blob.as_ref() {
+        for (i, chunk) in chunks_without_padding.iter_mut().enumerate() {
+            chunk.blob_data_commitment = blob.chunk_commitment(i);
+        }
+    }
     let padded_chunk =
         ChunkHash::mock_padded_chunk_hash_for_testing(&chunks_without_padding[num_real_chunks - 1]);
     let chunks_with_padding = [
         chunks_without_padding,
-        vec![padded_chunk; MAX_AGG_SNARKS - num_real_chunks],
+        vec![padded_chunk; N_SNARKS - num_real_chunks],
     ]
     .concat();
 
@@ -129,13 +323,15 @@ fn build_new_aggregation_circuit(num_real_chunks: usize) -> AggregationCircuit {
     // ==========================
     // padded chunks
     // ==========================
-    let padded_snarks =
-        { vec![real_snarks.last().unwrap().clone(); MAX_AGG_SNARKS - num_real_chunks] };
+    let padded_snarks = { vec![real_snarks.last().unwrap().clone(); N_SNARKS - num_real_chunks] };
 
     // ==========================
     // batch
     // ==========================
-    let batch_hash = BatchHash::construct(&chunks_with_padding);
+    let batch_hash = match blob {
+        Some(blob) => BatchHash::construct_with_blob(&chunks_with_padding, blob),
+        None => BatchHash::construct(&chunks_with_padding),
+    };
 
     AggregationCircuit::new(
         &params,
@@ -0,0 +1,146 @@
+use c_kzg::{Blob, Bytes32, KzgCommitment, KzgProof};
+use eth_types::{keccak256, H256};
+use num_bigint::BigUint;
+use sha2::{Digest, Sha256};
+use std::iter;
+
+use crate::{
+    blob::{
+        barycentric_evaluate, biguint_to_h256, bls_modulus, roots_of_unity_brp, BlobData,
+        BlobPointEvaluation, BLOB_WIDTH, N_BLOB_BYTES, N_DATA_BYTES_PER_COEFFICIENT,
+    },
+    BLOB_PI_LEN,
+};
+
+#[test]
+fn test_blob_coefficients() {
+    let blob = BlobData::new(H256::repeat_byte(1), vec![vec![vec![0xff; 1000]]]);
+
+    let coefficients = blob.coefficients();
+    assert_eq!(blob.padded_bytes().len(), N_BLOB_BYTES);
+    assert_eq!(coefficients.len(), BLOB_WIDTH);
+    // each coefficient carries 31 bytes, hence is smaller than the modulus
+    assert!(coefficients
+        .iter()
+        .all(|coefficient| coefficient.bits() <= 8 * N_DATA_BYTES_PER_COEFFICIENT as u64));
+    assert_eq!(
+        coefficients[0],
+        BigUint::from_bytes_be(&[0xff; N_DATA_BYTES_PER_COEFFICIENT])
+    );
+    assert_eq!(coefficients[BLOB_WIDTH - 1], BigUint::from(0u64));
+}
+
+#[test]
+fn test_blob_chunk_commitments() {
+    let txs = vec![vec![vec![1, 2], vec![3]], vec![], vec![vec![4; 100]]];
+    let blob = BlobData::new(H256::repeat_byte(4), txs.clone());
+
+    // the blob bytes are the L2 txs of the chunks, in order
+    assert_eq!(blob.bytes(), [vec![1, 2, 3], vec![4; 100]].concat());
+    // each chunk commits to the hashes of its own txs, an empty chunk to the empty preimage
+    for (i, chunk_txs) in txs.iter().enumerate() {
+        let preimage = chunk_txs
+            .iter()
+            .flat_map(|tx| keccak256(tx))
+            .collect::<Vec<_>>();
+        assert_eq!(blob.chunk_commitment(i), H256(keccak256(preimage)));
+    }
+    assert_eq!(blob.chunk_commitment(1), H256(keccak256([])));
+}
+
+#[test]
+fn test_blob_roots_of_unity() {
+    let modulus = bls_modulus();
+    let roots = roots_of_unity_brp();
+
+    assert_eq!(roots.len(), BLOB_WIDTH);
+    // in bit-reversed order, the first two roots are 1 and -1
+    assert_eq!(roots[0], BigUint::from(1u64));
+    assert_eq!(roots[1], &modulus - 1u64);
+    for root in roots.iter() {
+        assert_eq!(
+            root.modpow(&BigUint::from(BLOB_WIDTH), &modulus),
+            BigUint::from(1u64)
+        );
+    }
+}
+
+#[test]
+fn test_blob_point_evaluation_of_constant() {
+    // a blob of identical field elements is a constant polynomial
+    let coefficient = (1..=N_DATA_BYTES_PER_COEFFICIENT as u8).collect::<Vec<_>>();
+    let bytes = coefficient.repeat(BLOB_WIDTH);
+    let blob = BlobData::new(H256::repeat_byte(2), vec![vec![bytes]]);
+
+    let point_evaluation = blob.point_evaluation();
+    assert_eq!(
+        point_evaluation.evaluation,
+        biguint_to_h256(&BigUint::from_bytes_be(&coefficient))
+    );
+    assert_eq!(point_evaluation.versioned_hash, blob.versioned_hash);
+}
+
+#[test]
+fn test_blob_point_evaluation_of_lagrange_basis() {
+    // the polynomial of evaluations (1, 0, ..., 0) is the lagrange basis of the root 1
+    //  L(z) = (z^BLOB_WIDTH - 1) / (BLOB_WIDTH * (z - 1))
+    let modulus = bls_modulus();
+    let mut evaluations = vec![BigUint::from(0u64); BLOB_WIDTH];
+    evaluations[0] = BigUint::from(1u64);
+    let z = BigUint::from(0x1234_5678u64);
+
+    let numerator = (z.modpow(&BigUint::from(BLOB_WIDTH), &modulus) + &modulus - 1u64) % &modulus;
+    let denominator = BigUint::from(BLOB_WIDTH) * (&z - 1u64) % &modulus;
+    let expected = numerator * denominator.modpow(&(&modulus - 2u64), &modulus) % &modulus;
+
+    assert_eq!(barycentric_evaluate(&evaluations, &z), expected);
+}
+
+#[test]
+fn test_blob_point_evaluation_bytes() {
+    let blob = BlobData::new(H256::repeat_byte(3), vec![vec![vec![1, 2, 3]]]);
+    let point_evaluation = blob.point_evaluation();
+
+    let bytes = point_evaluation.to_bytes();
+    assert_eq!(bytes.len(), BLOB_PI_LEN);
+    assert_eq!(BlobPointEvaluation::from_bytes(&bytes), point_evaluation);
+    // the challenge is reduced modulo the BLS12-381 scalar field
+    assert!(BigUint::from_bytes_be(point_evaluation.challenge.as_bytes()) < bls_modulus());
+}
+
+#[test]
+fn test_blob_point_evaluation_against_kzg() {
+    let settings = c_kzg::ethereum_kzg_settings();
+
+    // the blob as posted to L1, whose field elements are 32 big endian bytes each
+    let bytes = (0..50_000).map(|i| (i * 7 % 256) as u8).collect::<Vec<_>>();
+    let kzg_blob_bytes = BlobData::new(H256::zero(), vec![vec![bytes.clone()]])
+        .padded_bytes()
+        .chunks(N_DATA_BYTES_PER_COEFFICIENT)
+        .flat_map(|chunk| iter::once(0).chain(chunk.iter().copied()))
+        .collect::<Vec<_>>();
+    let kzg_blob = Blob::from_bytes(&kzg_blob_bytes).unwrap();
+    let commitment = KzgCommitment::blob_to_kzg_commitment(&kzg_blob, settings)
+        .unwrap()
+        .to_bytes();
+
+    // versioned_hash := 0x01 || sha256(commitment)[1..]
+    let mut versioned_hash: [u8; 32] = Sha256::digest(commitment.as_slice()).into();
+    versioned_hash[0] = 1;
+    let point_evaluation =
+        BlobData::new(versioned_hash.into(), vec![vec![bytes]]).point_evaluation();
+
+    // the evaluation is the one of the KZG reference implementation, and its proof is accepted
+    // against the commitment of the versioned hash, as by the point evaluation precompile
+    let z = Bytes32::from(point_evaluation.challenge.0);
+    let (proof, y) = KzgProof::compute_kzg_proof(&kzg_blob, &z, settings).unwrap();
+    assert_eq!(y.as_slice(), point_evaluation.evaluation.as_bytes());
+    assert!(KzgProof::verify_kzg_proof(
+        &commitment,
+        &z,
+        &Bytes32::from(point_evaluation.evaluation.0),
+        &proof.to_bytes(),
+        settings,
+    )
+    .unwrap());
+}
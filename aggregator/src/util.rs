@@ -1,5 +1,10 @@
-use crate::constants::{DIGEST_LEN, INPUT_LEN_PER_ROUND, MAX_AGG_SNARKS};
-use eth_types::Field;
+use crate::{
+    blob::{
+        BLOB_BYTES_KECCAK_ROUNDS, CHALLENGE_KECCAK_ROUNDS, CHALLENGE_PREIMAGE_LEN, N_BLOB_BYTES,
+    },
+    constants::{DIGEST_LEN, INPUT_LEN_PER_ROUND},
+};
+use eth_types::{pi_schema::PiSchema, Field};
 use halo2_proofs::{circuit::AssignedCell, halo2curves::bn256::Fr, plonk::Error};
 use itertools::Itertools;
 use zkevm_circuits::keccak_circuit::keccak_packed_multi::{
@@ -8,20 +13,36 @@ use zkevm_circuits::keccak_circuit::keccak_packed_multi::{
 
 // Calculates the maximum keccak updates (1 absorb, or 1 f-box invoke)
 // needed for the number of snarks
-pub(crate) fn get_max_keccak_updates(max_snarks: usize) -> usize {
+pub(crate) fn get_max_keccak_updates(
+    max_snarks: usize,
+    pi_schema: PiSchema,
+    with_blob: bool,
+) -> usize {
     // The public input hash for the batch is derived from hashing
     // chain_id || chunk_0's prev_state || chunk_k-1's post_state ||
-    // chunk_k-1's withdraw_root || batch_data_hash.
-    // In total there're 168 bytes. Therefore 2 pi rounds are required.
-    let pi_rounds = 2;
+    // chunk_k-1's withdraw_root || batch_data_hash (for PiSchema::V1).
+    // In total there're 136 bytes. Therefore 2 pi rounds are required.
+    let pi_rounds = pi_schema.num_keccak_rounds();
     // Hash for each chunk is derived from hashing the chunk's
     // chain_id || prev_state || post_state || withdraw_root || data_hash
-    // Each chunk hash therefore also requires 2 keccak rounds for 168 bytes.
-    let chunk_hash_rounds = 2 * max_snarks;
+    // Each chunk hash therefore requires as many keccak rounds as the batch's.
+    let chunk_hash_rounds = pi_rounds * max_snarks;
+    let blob_rounds = get_blob_keccak_updates(with_blob);
     let data_hash_rounds = get_data_hash_keccak_updates(max_snarks);
 
-    pi_rounds + chunk_hash_rounds + data_hash_rounds
+    pi_rounds + chunk_hash_rounds + blob_rounds + data_hash_rounds
+}
+
+// Calculates the keccak updates of the hashes of a blob, i.e., the digest of the blob bytes and
+// the challenge digest, or 0 for a batch without blob
+pub(crate) fn get_blob_keccak_updates(with_blob: bool) -> usize {
+    if with_blob {
+        BLOB_BYTES_KECCAK_ROUNDS + CHALLENGE_KECCAK_ROUNDS
+    } else {
+        0
+    }
 }
+
 pub(crate) fn get_data_hash_keccak_updates(max_snarks: usize) -> usize {
     let data_hash_rounds = (32 * max_snarks) / INPUT_LEN_PER_ROUND;
     // when `32 * max_snarks` happens to match a multiple of 136, a padding round will still be
@@ -38,7 +59,9 @@ pub(crate) fn get_data_hash_keccak_updates(max_snarks: usize) -> usize {
 /// Return
 /// - the indices of the rows that contain the input preimages
 /// - the indices of the rows that contain the output digest
-pub(crate) fn get_indices(preimages: &[Vec<u8>]) -> (Vec<usize>, Vec<usize>) {
+pub(crate) fn get_indices<const N_SNARKS: usize>(
+    preimages: &[Vec<u8>],
+) -> (Vec<usize>, Vec<usize>) {
     let mut preimage_indices = vec![];
     let mut digest_indices = vec![];
     let mut round_ctr = 0;
@@ -46,7 +69,8 @@ pub(crate) fn get_indices(preimages: &[Vec<u8>]) -> (Vec<usize>, Vec<usize>) {
     let keccak_f_rows = get_num_rows_per_update();
     let inner_round_rows = get_num_rows_per_round();
 
-    for preimage in preimages.iter().take(MAX_AGG_SNARKS + 1) {
+    // all the hashes but the batch's data hash, which is the last one, have fixed length
+    for preimage in preimages.iter().take(preimages.len() - 1) {
         //  136 = 17 * 8 is the size in bytes of each
         //  input chunk that can be processed by Keccak circuit using absorb
 
@@ -82,7 +106,7 @@ pub(crate) fn get_indices(preimages: &[Vec<u8>]) -> (Vec<usize>, Vec<usize>) {
         }
     }
     // last hash is for data_hash and has various length, so we output all the possible cells
-    for _i in 0..get_data_hash_keccak_updates(MAX_AGG_SNARKS) {
+    for _i in 0..get_data_hash_keccak_updates(N_SNARKS) {
         for (j, _) in (0..INPUT_LEN_PER_ROUND)
             .into_iter()
             .chunks(8)
@@ -175,25 +199,28 @@ fn is_ascending(a: &[usize]) -> bool {
 
 #[inline]
 #[allow(clippy::type_complexity)]
-pub(crate) fn parse_hash_preimage_cells(
+pub(crate) fn parse_hash_preimage_cells<const N_SNARKS: usize>(
     hash_input_cells: &[AssignedCell<Fr, Fr>],
+    pi_schema: PiSchema,
+    with_blob: bool,
 ) -> (
     &[AssignedCell<Fr, Fr>],
     Vec<&[AssignedCell<Fr, Fr>]>,
     &[AssignedCell<Fr, Fr>],
 ) {
-    // each pi hash has INPUT_LEN_PER_ROUND bytes as input
-    // keccak will pad the input with another INPUT_LEN_PER_ROUND bytes
+    // each pi hash takes the rounds of its schema, including the padding of keccak,
     // we extract all those bytes
-    let batch_pi_hash_preimage = &hash_input_cells[0..INPUT_LEN_PER_ROUND * 2];
+    let pi_hash_input_len = INPUT_LEN_PER_ROUND * pi_schema.num_keccak_rounds();
+    let batch_pi_hash_preimage = &hash_input_cells[0..pi_hash_input_len];
     let mut chunk_pi_hash_preimages = vec![];
-    for i in 0..MAX_AGG_SNARKS {
-        chunk_pi_hash_preimages.push(
-            &hash_input_cells[INPUT_LEN_PER_ROUND * 2 * (i + 1)..INPUT_LEN_PER_ROUND * 2 * (i + 2)],
-        );
+    for i in 0..N_SNARKS {
+        chunk_pi_hash_preimages
+            .push(&hash_input_cells[pi_hash_input_len * (i + 1)..pi_hash_input_len * (i + 2)]);
     }
+    // the hashes of the blob, if any, are between the chunks' and the batch's data hash
+    let blob_hash_input_len = INPUT_LEN_PER_ROUND * get_blob_keccak_updates(with_blob);
     let potential_batch_data_hash_preimage =
-        &hash_input_cells[INPUT_LEN_PER_ROUND * 2 * (MAX_AGG_SNARKS + 1)..];
+        &hash_input_cells[pi_hash_input_len * (N_SNARKS + 1) + blob_hash_input_len..];
 
     (
         batch_pi_hash_preimage,
@@ -204,8 +231,9 @@ pub(crate) fn parse_hash_preimage_cells(
 
 #[inline]
 #[allow(clippy::type_complexity)]
-pub(crate) fn parse_hash_digest_cells(
+pub(crate) fn parse_hash_digest_cells<const N_SNARKS: usize>(
     hash_output_cells: &[AssignedCell<Fr, Fr>],
+    with_blob: bool,
 ) -> (
     &[AssignedCell<Fr, Fr>],
     Vec<&[AssignedCell<Fr, Fr>]>,
@@ -213,10 +241,12 @@ pub(crate) fn parse_hash_digest_cells(
 ) {
     let batch_pi_hash_digest = &hash_output_cells[0..DIGEST_LEN];
     let mut chunk_pi_hash_digests = vec![];
-    for i in 0..MAX_AGG_SNARKS {
+    for i in 0..N_SNARKS {
         chunk_pi_hash_digests.push(&hash_output_cells[DIGEST_LEN * (i + 1)..DIGEST_LEN * (i + 2)]);
     }
-    let potential_batch_data_hash_digest = &hash_output_cells[DIGEST_LEN * (MAX_AGG_SNARKS + 1)..];
+    let num_blob_hashes = if with_blob { 2 } else { 0 };
+    let potential_batch_data_hash_digest =
+        &hash_output_cells[DIGEST_LEN * (N_SNARKS + 1 + num_blob_hashes)..];
     (
         batch_pi_hash_digest,
         chunk_pi_hash_digests,
@@ -224,15 +254,52 @@ pub(crate) fn parse_hash_digest_cells(
     )
 }
 
+/// Return the cells of the preimages of the hashes of a blob
+/// - the blob bytes, without the keccak padding
+/// - the challenge preimage, i.e., blob_bytes_digest || versioned_hash
+#[inline]
+pub(crate) fn parse_blob_hash_preimage_cells<const N_SNARKS: usize>(
+    hash_input_cells: &[AssignedCell<Fr, Fr>],
+    pi_schema: PiSchema,
+) -> (&[AssignedCell<Fr, Fr>], &[AssignedCell<Fr, Fr>]) {
+    let blob_bytes_index = INPUT_LEN_PER_ROUND * pi_schema.num_keccak_rounds() * (N_SNARKS + 1);
+    let challenge_preimage_index =
+        blob_bytes_index + INPUT_LEN_PER_ROUND * BLOB_BYTES_KECCAK_ROUNDS;
+
+    (
+        &hash_input_cells[blob_bytes_index..blob_bytes_index + N_BLOB_BYTES],
+        &hash_input_cells
+            [challenge_preimage_index..challenge_preimage_index + CHALLENGE_PREIMAGE_LEN],
+    )
+}
+
+/// Return the cells of the digests of a blob
+/// - the digest of the blob bytes
+/// - the challenge digest
+#[inline]
+pub(crate) fn parse_blob_hash_digest_cells<const N_SNARKS: usize>(
+    hash_output_cells: &[AssignedCell<Fr, Fr>],
+) -> (&[AssignedCell<Fr, Fr>], &[AssignedCell<Fr, Fr>]) {
+    let blob_bytes_digest_index = DIGEST_LEN * (N_SNARKS + 1);
+
+    (
+        &hash_output_cells[blob_bytes_digest_index..blob_bytes_digest_index + DIGEST_LEN],
+        &hash_output_cells
+            [blob_bytes_digest_index + DIGEST_LEN..blob_bytes_digest_index + 2 * DIGEST_LEN],
+    )
+}
+
 #[inline]
-pub(crate) fn parse_pi_hash_rlc_cells(
+pub(crate) fn parse_pi_hash_rlc_cells<const N_SNARKS: usize>(
     data_rlc_cells: &[AssignedCell<Fr, Fr>],
+    pi_schema: PiSchema,
 ) -> Vec<&AssignedCell<Fr, Fr>> {
+    let pi_rounds = pi_schema.num_keccak_rounds();
     data_rlc_cells
         .iter()
-        .skip(3) // the first 3 rlc cells are pad (1) + batch pi hash (2)
-        .take(MAX_AGG_SNARKS * 2) // each chunk hash takes 2 rounds
-        .chunks(2)
+        .skip(1 + pi_rounds) // the first rlc cells are pad (1) + batch pi hash
+        .take(N_SNARKS * pi_rounds) // each chunk hash takes as many rounds as the batch's
+        .chunks(pi_rounds)
         .into_iter()
         .map(|t| t.last().unwrap())
         .collect()
@@ -30,14 +30,16 @@ use eth_types::{
     self,
     evm_types::{GasCost, OpcodeId},
     geth_types,
+    pi_schema::{blob_data_commitment_preimage, PiSchema, PiValues},
     sign_types::{pk_bytes_le, pk_bytes_swap_endianness, SignData},
     Address, GethExecStep, GethExecTrace, ToBigEndian, ToWord, Word, H256,
 };
 use ethers_providers::JsonRpcClient;
 pub use execution::{
-    BigModExp, CopyBytes, CopyDataType, CopyEvent, CopyEventStepsBuilder, CopyStep, EcAddOp,
-    EcMulOp, EcPairingOp, EcPairingPair, ExecState, ExecStep, ExpEvent, ExpStep, NumberOrHash,
-    PrecompileEvent, PrecompileEvents, N_BYTES_PER_PAIR, N_PAIRING_PER_OP,
+    BigModExp, Blake2fEvent, CopyBytes, CopyDataType, CopyEvent, CopyEventStepsBuilder, CopyStep,
+    EcAddOp, EcMulOp, EcPairingOp, EcPairingPair, ExecState, ExecStep, ExpEvent, ExpStep,
+    NumberOrHash, PrecompileEvent, PrecompileEvents, Ripemd160Event, Sha256Event, N_BYTES_PER_PAIR,
+    N_PAIRING_PER_OP,
 };
 use hex::decode_to_slice;
 
@@ -49,7 +51,7 @@ use log::warn;
 #[cfg(feature = "scroll")]
 use mpt_zktrie::state::ZktrieState;
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet},
     iter,
 };
 pub use transaction::{
@@ -114,10 +116,23 @@ pub struct CircuitsParams {
     /// calculated, so the same circuit will not be able to prove different
     /// witnesses.
     pub max_keccak_rows: usize,
+    /// Maximum number of rows that the SHA-256 Circuit can have.
+    /// When 0, the SHA-256 circuit number of rows will be dynamically
+    /// calculated.
+    pub max_sha256_rows: usize,
+    /// Maximum number of rows that the RIPEMD-160 Circuit can have.
+    /// When 0, the RIPEMD-160 circuit number of rows will be dynamically
+    /// calculated.
+    pub max_ripemd160_rows: usize,
     /// Maximum number of rows that the Poseidon Circuit can have
     pub max_poseidon_rows: usize,
     /// Max number of ECC-related ops supported in the ECC circuit.
     pub max_ec_ops: PrecompileEcParams,
+    /// Max number of rounds supported in the Blake2f circuit, where every
+    /// compression takes two more rounds for its setup and finalization.
+    /// When 0, the Blake2f circuit number of rows will be dynamically
+    /// calculated.
+    pub max_blake2f_rounds: usize,
     /// This number indicate what 100% usage means, for example if we can support up to 2
     /// ecPairing inside circuit, and max_vertical_circuit_rows is set to 1_000_000,
     /// then if there is 1 ecPairing in the input, we will return 500_000 as the "row usage"
@@ -141,10 +156,13 @@ impl Default for CircuitsParams {
             max_bytecode: 512,
             max_evm_rows: 0,
             max_keccak_rows: 0,
+            max_sha256_rows: 0,
+            max_ripemd160_rows: 0,
             max_poseidon_rows: 0,
             max_vertical_circuit_rows: 0,
             max_rlp_rows: 1000,
             max_ec_ops: PrecompileEcParams::default(),
+            max_blake2f_rounds: 0,
         }
     }
 }
@@ -441,6 +459,14 @@ impl<'a> CircuitInputBuilder {
         );
         log::debug!("tx_receipt num: {}", self.block.container.tx_receipt.len());
         log::debug!("tx_log num: {}", self.block.container.tx_log.len());
+        log::debug!(
+            "tx_created_account num: {}",
+            self.block.container.tx_created_account.len()
+        );
+        log::debug!(
+            "transient_storage num: {}",
+            self.block.container.transient_storage.len()
+        );
         log::debug!("start num: {}", self.block.container.start.len());
     }
 
@@ -731,14 +757,7 @@ pub fn keccak_inputs(block: &Block, code_db: &CodeDB) -> Result<Vec<Vec<u8>>, Er
         keccak_inputs.iter().map(|i| i.len()).sum::<usize>()
     );
     // PI circuit
-    keccak_inputs.extend(keccak_inputs_pi_circuit(
-        block.chain_id,
-        block.start_l1_queue_index,
-        block.prev_state_root,
-        block.withdraw_root,
-        &block.headers,
-        block.txs(),
-    ));
+    keccak_inputs.extend(keccak_inputs_pi_circuit_of_block(block));
     // Bytecode Circuit
     for _bytecode in code_db.0.values() {
         // keccak_inputs.push(bytecode.clone());
@@ -766,6 +785,48 @@ pub fn keccak_inputs(block: &Block, code_db: &CodeDB) -> Result<Vec<Vec<u8>>, Er
     Ok(keccak_inputs)
 }
 
+/// Return the keccak inputs of the block that do not depend on its transactions, i.e., the
+/// public input hash of the PI circuit, and the dummy transaction and signature the Tx Circuit
+/// and the SignVerify Chip are padded with. They are a part of [`keccak_inputs`].
+pub fn keccak_inputs_block_level(block: &Block) -> Vec<Vec<u8>> {
+    let dummy_hash_data = {
+        let (dummy_tx, dummy_sig) = get_dummy_tx();
+        dummy_tx.rlp_signed(&dummy_sig).to_vec()
+    };
+
+    iter::once(dummy_hash_data)
+        .chain(keccak_inputs_sign_verify(&[]))
+        .chain(keccak_inputs_pi_circuit_of_block(block))
+        .unique()
+        .collect()
+}
+
+/// Return the keccak inputs of the transactions of the block, i.e., those of [`keccak_inputs`]
+/// but the ones of [`keccak_inputs_block_level`].
+pub fn keccak_inputs_tx_level(block: &Block, code_db: &CodeDB) -> Result<Vec<Vec<u8>>, Error> {
+    let block_level_inputs: HashSet<_> = keccak_inputs_block_level(block).into_iter().collect();
+    Ok(keccak_inputs(block, code_db)?
+        .into_iter()
+        .filter(|input| !block_level_inputs.contains(input))
+        .collect())
+}
+
+fn keccak_inputs_pi_circuit_of_block(block: &Block) -> Vec<Vec<u8>> {
+    keccak_inputs_pi_circuit(
+        block.pi_schema,
+        PiValues {
+            chain_id: block.chain_id,
+            prev_state_root: H256(block.prev_state_root.to_be_bytes()),
+            withdraw_root: H256(block.withdraw_root.to_be_bytes()),
+            l1_msg_queue_hash: block.l1_msg_queue_hash,
+            ..Default::default()
+        },
+        block.start_l1_queue_index,
+        &block.headers,
+        block.txs(),
+    )
+}
+
 /// Generate the keccak inputs required by the SignVerify Chip from the
 /// signature datas.
 pub fn keccak_inputs_sign_verify(sigs: &[SignData]) -> Vec<Vec<u8>> {
@@ -795,11 +856,12 @@ pub fn get_dummy_tx_hash() -> H256 {
     H256(tx_hash)
 }
 
+// `pi_values` holds all the fields of the public input hash preimage but the post state root, the
+// data hash and the blob data commitment, which are computed from the blocks.
 fn keccak_inputs_pi_circuit(
-    chain_id: u64,
+    pi_schema: PiSchema,
+    pi_values: PiValues,
     start_l1_queue_index: u64,
-    prev_state_root: Word,
-    withdraw_trie_root: Word,
     block_headers: &BTreeMap<u64, BlockHead>,
     transactions: &[Transaction],
 ) -> Vec<Vec<u8>> {
@@ -849,19 +911,25 @@ fn keccak_inputs_pi_circuit(
         "chunk data hash: {}",
         hex::encode(data_hash.to_fixed_bytes())
     );
+    let blob_data_bytes = blob_data_commitment_preimage(
+        transactions
+            .iter()
+            .filter(|tx| !tx.tx_type.is_l1_msg())
+            .map(|tx| tx.hash),
+    );
+    let blob_data_commitment = H256(keccak256(&blob_data_bytes));
     let after_state_root = block_headers
         .last_key_value()
         .map(|(_, blk)| blk.eth_block.state_root)
-        .unwrap_or(H256(prev_state_root.to_be_bytes()));
-    let pi_bytes = iter::empty()
-        .chain(chain_id.to_be_bytes())
-        .chain(prev_state_root.to_be_bytes())
-        .chain(after_state_root.to_fixed_bytes())
-        .chain(withdraw_trie_root.to_be_bytes())
-        .chain(data_hash.to_fixed_bytes())
-        .collect::<Vec<u8>>();
-
-    vec![data_bytes, pi_bytes]
+        .unwrap_or(pi_values.prev_state_root);
+    let pi_bytes = pi_schema.preimage(&PiValues {
+        post_state_root: after_state_root,
+        data_hash,
+        blob_data_commitment,
+        ..pi_values
+    });
+
+    vec![data_bytes, blob_data_bytes, pi_bytes]
 }
 
 /// Generate the keccak inputs required by the Tx Circuit from the transactions.
@@ -9,7 +9,11 @@ use crate::{
     operation::{OperationContainer, RWCounter},
     Error,
 };
-use eth_types::{Address, Hash, ToWord, Word};
+use eth_types::{
+    geth_types::get_blob_base_fee,
+    pi_schema::{PiSchema, PI_SCHEMA},
+    Address, Hash, ToWord, Word, H256,
+};
 use std::collections::{BTreeMap, HashMap};
 
 /// Context of a [`Block`] which can mutate in a [`Transaction`].
@@ -88,6 +92,8 @@ pub struct BlockHead {
     pub difficulty: Word,
     /// base fee
     pub base_fee: Word,
+    /// blob base fee
+    pub blob_base_fee: Word,
     /// start l1 queue index
     pub start_l1_queue_index: u64,
     /// Original block from geth
@@ -131,6 +137,7 @@ impl BlockHead {
                 eth_block.difficulty
             },
             base_fee: eth_block.base_fee_per_gas.unwrap_or_default(),
+            blob_base_fee: get_blob_base_fee(eth_block),
             eth_block: eth_block.clone(),
         })
     }
@@ -173,6 +180,7 @@ impl BlockHead {
                 eth_block.difficulty
             },
             base_fee: eth_block.base_fee_per_gas.unwrap_or_default(),
+            blob_base_fee: get_blob_base_fee(eth_block),
             eth_block: eth_block.clone(),
         })
     }
@@ -210,6 +218,10 @@ pub struct Block {
     pub chain_id: u64,
     /// start_l1_queue_index
     pub start_l1_queue_index: u64,
+    /// Layout of the public input hash preimage of the chunk
+    pub pi_schema: PiSchema,
+    /// Rolling hash of the L1 message queue after the chunk, used by [`PiSchema::V2`]
+    pub l1_msg_queue_hash: H256,
     /// IO to/from the precompiled contract calls.
     pub precompile_events: PrecompileEvents,
     /// circuit capacity counter
@@ -235,6 +247,7 @@ impl Block {
                 .map(|b| (b.number.as_u64(), b.clone()))
                 .collect::<BTreeMap<_, _>>(),
             circuits_params,
+            pi_schema: PI_SCHEMA,
             ..Default::default()
         }
     }
@@ -259,6 +272,7 @@ impl Block {
             exp_events: Vec::new(),
             chain_id,
             circuits_params,
+            pi_schema: PI_SCHEMA,
             ..Default::default()
         };
         let info = BlockHead::new(chain_id, history_hashes, eth_block)?;
@@ -289,6 +303,7 @@ impl Block {
             chain_id,
             start_l1_queue_index,
             circuits_params,
+            pi_schema: PI_SCHEMA,
             ..Default::default()
         };
         let info = BlockHead::new_with_l1_queue_index(
@@ -444,6 +444,15 @@ impl CopyEvent {
         self.dst_type == CopyDataType::Memory || self.dst_type == CopyDataType::TxLog
     }
 
+    /// Whether the event copies within the memory of a single call, as MCOPY
+    /// does. Such an event reads all source words before writing any
+    /// destination word.
+    pub fn is_memory_copy(&self) -> bool {
+        self.src_type == CopyDataType::Memory
+            && self.dst_type == CopyDataType::Memory
+            && self.src_id == self.dst_id
+    }
+
     /// Whether the RLC of data must be computed.
     pub fn has_rlc(&self) -> bool {
         matches!(
@@ -908,6 +917,48 @@ impl PrecompileEvents {
             .cloned()
             .collect()
     }
+    /// Get all SHA256 events.
+    pub fn get_sha256_events(&self) -> Vec<Sha256Event> {
+        self.events
+            .iter()
+            .filter_map(|e| {
+                if let PrecompileEvent::Sha256(op) = e {
+                    Some(op)
+                } else {
+                    None
+                }
+            })
+            .cloned()
+            .collect()
+    }
+    /// Get all BLAKE2F events.
+    pub fn get_blake2f_events(&self) -> Vec<Blake2fEvent> {
+        self.events
+            .iter()
+            .filter_map(|e| {
+                if let PrecompileEvent::Blake2F(op) = e {
+                    Some(op)
+                } else {
+                    None
+                }
+            })
+            .cloned()
+            .collect()
+    }
+    /// Get all RIPEMD160 events.
+    pub fn get_ripemd160_events(&self) -> Vec<Ripemd160Event> {
+        self.events
+            .iter()
+            .filter_map(|e| {
+                if let PrecompileEvent::Ripemd160(op) = e {
+                    Some(op)
+                } else {
+                    None
+                }
+            })
+            .cloned()
+            .collect()
+    }
 }
 
 /// I/O from a precompiled contract call.
@@ -923,6 +974,12 @@ pub enum PrecompileEvent {
     EcPairing(Box<EcPairingOp>),
     /// Represents the I/O from Modexp call.
     ModExp(BigModExp),
+    /// Represents the I/O from SHA256 call.
+    Sha256(Sha256Event),
+    /// Represents the I/O from RIPEMD160 call.
+    Ripemd160(Ripemd160Event),
+    /// Represents the input of a BLAKE2F call.
+    Blake2F(Blake2fEvent),
 }
 
 impl Default for PrecompileEvent {
@@ -1348,3 +1405,36 @@ impl Default for BigModExp {
         }
     }
 }
+
+/// Event representating a SHA256 hash in precompile sha256.
+#[derive(Clone, Debug, Default)]
+pub struct Sha256Event {
+    /// input bytes
+    pub input: Vec<u8>,
+    /// digest
+    pub digest: [u8; 32],
+}
+
+/// Event representating a compression in precompile blake2f.
+#[derive(Clone, Debug, Default)]
+pub struct Blake2fEvent {
+    /// number of rounds
+    pub rounds: u32,
+    /// state vector
+    pub h: [u64; 8],
+    /// message block
+    pub m: [u64; 16],
+    /// offset counters
+    pub t: [u64; 2],
+    /// final block indicator
+    pub f: bool,
+}
+
+/// Event representating a RIPEMD160 hash in precompile ripemd160.
+#[derive(Clone, Debug, Default)]
+pub struct Ripemd160Event {
+    /// input bytes
+    pub input: Vec<u8>,
+    /// digest
+    pub digest: [u8; 20],
+}
@@ -16,10 +16,10 @@ use crate::{
     exec_trace::OperationRef,
     operation::{
         AccountField, AccountOp, CallContextField, CallContextOp, MemoryOp, Op, OpEnum, Operation,
-        StackOp, Target, TxAccessListAccountOp, TxLogField, TxLogOp, TxReceiptField, TxReceiptOp,
-        RW,
+        StackOp, Target, TxAccessListAccountOp, TxCreatedAccountOp, TxLogField, TxLogOp,
+        TxReceiptField, TxReceiptOp, RW,
     },
-    precompile::{is_precompiled, PrecompileCalls},
+    precompile::{is_precompiled, Blake2fAuxData, PrecompileCalls},
     state_db::{CodeDB, StateDB},
     Error,
 };
@@ -401,7 +401,11 @@ impl<'a> CircuitInputStateRef<'a> {
                 }
             }
             AccountField::CodeHash => {
-                if account.is_empty() {
+                if account.code_hash.is_zero() {
+                    // The account has been destructed by a SELFDESTRUCT in a call that is going
+                    // to be reverted, code_hash=0 is kept until the reversion is applied.
+                    Word::zero()
+                } else if account.is_empty() {
                     if op.value.is_zero() {
                         // Writing code_hash=0 to empty account is a noop to the StateDB.
                         return;
@@ -428,8 +432,8 @@ impl<'a> CircuitInputStateRef<'a> {
         // account (only CodeHash reads with value=0 can be done to non-existing
         // accounts, which the State Circuit translates to MPT
         // AccountNonExisting proofs lookups).
-        if (account.is_empty() && !self.sdb.is_touched(&op.address))
-            && !matches!(op.field, AccountField::CodeHash)
+        if !matches!(op.field, AccountField::CodeHash)
+            && (account.is_empty() && !self.sdb.is_touched(&op.address))
         {
             panic!(
                 "RWTable Account field {:?} lookup to non-existing account rwc: {}, op: {:?}",
@@ -585,6 +589,44 @@ impl<'a> CircuitInputStateRef<'a> {
         )
     }
 
+    /// Mark address as created in the current transaction.
+    pub fn tx_created_account_write(
+        &mut self,
+        step: &mut ExecStep,
+        address: Address,
+    ) -> Result<(), Error> {
+        let is_created = self.sdb.is_created_account(&address);
+        self.push_op_reversible(
+            step,
+            TxCreatedAccountOp {
+                tx_id: self.tx_ctx.id(),
+                address,
+                is_created: true,
+                is_created_prev: is_created,
+            },
+        )
+    }
+
+    /// Read whether address has been created in the current transaction.
+    pub fn tx_created_account_read(
+        &mut self,
+        step: &mut ExecStep,
+        address: Address,
+    ) -> Result<bool, Error> {
+        let is_created = self.sdb.is_created_account(&address);
+        self.push_op(
+            step,
+            RW::READ,
+            TxCreatedAccountOp {
+                tx_id: self.tx_ctx.id(),
+                address,
+                is_created,
+                is_created_prev: is_created,
+            },
+        )?;
+        Ok(is_created)
+    }
+
     /// Push a write type [`TxAccessListAccountOp`] into the
     /// [`OperationContainer`](crate::operation::OperationContainer) with the
     /// next [`RWCounter`](crate::operation::RWCounter), and then
@@ -1107,6 +1149,22 @@ impl<'a> CircuitInputStateRef<'a> {
                     None
                 }
             }
+            OperationRef(Target::TxCreatedAccount, idx) => {
+                let operation = &self.block.container.tx_created_account[*idx];
+                if operation.rw().is_write() && operation.reversible() {
+                    Some(OpEnum::TxCreatedAccount(operation.op().reverse()))
+                } else {
+                    None
+                }
+            }
+            OperationRef(Target::TransientStorage, idx) => {
+                let operation = &self.block.container.transient_storage[*idx];
+                if operation.rw().is_write() && operation.reversible() {
+                    Some(OpEnum::TransientStorage(operation.op().reverse()))
+                } else {
+                    None
+                }
+            }
             _ => None,
         }
     }
@@ -1139,6 +1197,18 @@ impl<'a> CircuitInputStateRef<'a> {
             OpEnum::TxRefund(op) => {
                 self.sdb.set_refund(op.value);
             }
+            OpEnum::TxCreatedAccount(op) => {
+                if !op.is_created_prev && op.is_created {
+                    self.sdb.add_created_account(op.address);
+                }
+                if op.is_created_prev && !op.is_created {
+                    self.sdb.remove_created_account(&op.address);
+                }
+            }
+            OpEnum::TransientStorage(op) => {
+                self.sdb
+                    .set_transient_storage(&op.address, &op.key, &op.value);
+            }
             _ => unreachable!(),
         };
     }
@@ -1559,6 +1629,7 @@ impl<'a> CircuitInputStateRef<'a> {
                     OpcodeId::RETURNDATACOPY => Some(ExecError::ReturnDataOutOfBounds),
                     // Break write protection (CALL with value will be handled below)
                     OpcodeId::SSTORE
+                    | OpcodeId::TSTORE
                     | OpcodeId::CREATE
                     | OpcodeId::CREATE2
                     | OpcodeId::SELFDESTRUCT
@@ -1721,17 +1792,29 @@ impl<'a> CircuitInputStateRef<'a> {
                 if is_precompiled(&code_address) {
                     let precompile_call: PrecompileCalls = code_address[19].into();
                     match precompile_call {
-                        PrecompileCalls::Sha256
-                        | PrecompileCalls::Ripemd160
-                        | PrecompileCalls::Blake2F => {
-                            // Log the precompile address and gas left. Since this failure is mainly
-                            // caused by out of gas.
-                            log::trace!(
-                                "Precompile failed: code_address = {}, step.gas = {}",
-                                code_address,
-                                step.gas.0,
-                            );
-                            return Ok(Some(ExecError::PrecompileFailed));
+                        PrecompileCalls::Blake2F => {
+                            // A malformed input fails before running any round. Running out of
+                            // gas for the requested rounds is handled by the Blake2F gadget.
+                            let nth = if matches!(step.op, OpcodeId::CALL | OpcodeId::CALLCODE) {
+                                3
+                            } else {
+                                2
+                            };
+                            let (args_offset, args_length) =
+                                get_call_memory_offset_length(step, nth)?;
+                            let input = self
+                                .call_ctx()?
+                                .memory
+                                .read_chunk(MemoryRange::new_with_length(args_offset, args_length));
+                            if !Blake2fAuxData::check_input(&input) {
+                                log::trace!(
+                                    "Precompile failed: code_address = {}, input len = {}",
+                                    code_address,
+                                    input.len(),
+                                );
+                                return Ok(Some(ExecError::PrecompileFailed));
+                            }
+                            return Ok(None);
                         }
                         pre_call => {
                             log::trace!(
@@ -2100,6 +2183,74 @@ impl<'a> CircuitInputStateRef<'a> {
         Ok((read_steps, write_steps, prev_bytes))
     }
 
+    /// Generate copy steps for a copy within the memory of the current call.
+    /// All source words are read before any destination word is written, so
+    /// overlapping ranges copy the bytes as they were before the copy.
+    pub(crate) fn gen_copy_steps_for_memory_to_memory(
+        &mut self,
+        exec_step: &mut ExecStep,
+        src_addr: impl Into<MemoryAddress>,
+        dst_addr: impl Into<MemoryAddress>,
+        copy_length: impl Into<MemoryAddress>,
+    ) -> Result<(CopyEventSteps, CopyEventSteps, Vec<u8>), Error> {
+        let copy_length = copy_length.into().0;
+        if copy_length == 0 {
+            return Ok((vec![], vec![], vec![]));
+        }
+
+        let src_addr = src_addr.into().0;
+        let call_ctx = self.call_ctx_mut()?;
+        // The source range is expanded as well as the destination range.
+        call_ctx
+            .memory
+            .extend_for_range(src_addr.into(), copy_length.into());
+        let src_memory = call_ctx.memory.clone();
+        let (src_range, dst_range, write_slot_bytes) = combine_copy_slot_bytes(
+            src_addr,
+            dst_addr.into().0,
+            copy_length,
+            &src_memory.0,
+            &mut call_ctx.memory,
+        );
+        let read_slot_bytes = src_memory.read_chunk(src_range);
+
+        let read_steps = CopyEventStepsBuilder::memory_range(src_range)
+            .source(read_slot_bytes.as_slice())
+            .build();
+        let write_steps = CopyEventStepsBuilder::memory_range(dst_range)
+            .source(write_slot_bytes.as_slice())
+            .build();
+
+        let call_id = self.call()?.call_id;
+        let mut src_chunk_index = src_range.start_slot().0;
+        for read_chunk in read_slot_bytes.chunks(32) {
+            self.push_op(
+                exec_step,
+                RW::READ,
+                MemoryOp::new(
+                    call_id,
+                    src_chunk_index.into(),
+                    Word::from_big_endian(read_chunk),
+                ),
+            )?;
+            src_chunk_index += 32;
+        }
+
+        let mut dst_chunk_index = dst_range.start_slot().0;
+        let mut prev_bytes: Vec<u8> = vec![];
+        for write_chunk in write_slot_bytes.chunks(32) {
+            self.write_chunk_for_copy_step(
+                exec_step,
+                write_chunk,
+                dst_chunk_index,
+                &mut prev_bytes,
+            )?;
+            dst_chunk_index += 32;
+        }
+
+        Ok((read_steps, write_steps, prev_bytes))
+    }
+
     pub(crate) fn gen_copy_steps_for_log(
         &mut self,
         exec_step: &mut ExecStep,
@@ -5,7 +5,10 @@ use std::collections::BTreeMap;
 use eth_types::{
     evm_types::{gas_utils::tx_data_gas_cost, Memory, OpcodeId},
     geth_types,
-    geth_types::{get_rlp_unsigned, TxType},
+    geth_types::{
+        get_blob_versioned_hashes, get_max_fee_per_blob_gas, get_rlp_signed, get_rlp_unsigned,
+        TxType,
+    },
     AccessList, Address, GethExecTrace, Signature, Word, H256,
 };
 use ethers_core::utils::get_contract_address;
@@ -238,6 +241,10 @@ pub struct Transaction {
     pub l1_fee_committed: TxL1Fee,
     /// EIP2930
     pub access_list: Option<AccessList>,
+    /// EIP4844 max fee per blob gas
+    pub max_fee_per_blob_gas: Word,
+    /// EIP4844 blob versioned hashes
+    pub blob_versioned_hashes: Vec<H256>,
     /// Calls made in the transaction
     pub(crate) calls: Vec<Call>,
     /// Execution steps
@@ -263,6 +270,8 @@ impl From<&Transaction> for geth_types::Transaction {
             rlp_unsigned_bytes: tx.rlp_unsigned_bytes.clone(),
             rlp_bytes: tx.rlp_bytes.clone(),
             tx_type: tx.tx_type,
+            max_fee_per_blob_gas: tx.max_fee_per_blob_gas,
+            blob_versioned_hashes: tx.blob_versioned_hashes.clone(),
             ..Default::default()
         }
     }
@@ -297,6 +306,8 @@ impl Transaction {
             l1_fee: Default::default(),
             l1_fee_committed: Default::default(),
             access_list: None,
+            max_fee_per_blob_gas: Word::zero(),
+            blob_versioned_hashes: vec![],
         }
     }
 
@@ -308,6 +319,8 @@ impl Transaction {
         eth_tx: &eth_types::Transaction,
         is_success: bool,
     ) -> Result<Self, Error> {
+        let tx_type = TxType::get_tx_type(eth_tx);
+
         let (found, _) = sdb.get_account(&eth_tx.from);
         if !found {
             return Err(Error::AccountNotFound(eth_tx.from));
@@ -368,7 +381,6 @@ impl Transaction {
             }
         );
 
-        let tx_type = TxType::get_tx_type(eth_tx);
         let (l1_fee, l1_fee_committed) = if tx_type.is_l1_msg() {
             Default::default()
         } else {
@@ -388,7 +400,7 @@ impl Transaction {
             block_num: eth_tx.block_number.unwrap().as_u64(),
             hash: eth_tx.hash,
             tx_type,
-            rlp_bytes: eth_tx.rlp().to_vec(),
+            rlp_bytes: get_rlp_signed(eth_tx),
             rlp_unsigned_bytes: get_rlp_unsigned(eth_tx),
             nonce: eth_tx.nonce.as_u64(),
             gas: eth_tx.gas.as_u64(),
@@ -410,6 +422,8 @@ impl Transaction {
             l1_fee,
             l1_fee_committed,
             access_list: eth_tx.access_list.clone(),
+            max_fee_per_blob_gas: get_max_fee_per_blob_gas(eth_tx),
+            blob_versioned_hashes: get_blob_versioned_hashes(eth_tx),
         })
     }
 
@@ -556,3 +570,45 @@ impl TxL1Fee {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_db::Account;
+
+    #[test]
+    fn blob_tx_is_accepted() {
+        let eth_tx: eth_types::Transaction = serde_json::from_value(serde_json::json!({
+            "type": "0x3",
+            "hash": "0x3e2a4c9a6f8fb2d5ad8b2c1e4f1dd3b4d2fbd5b0b9f33b2aa5bcc7ee1e3e6e4f",
+            "blockNumber": "0x1",
+            "chainId": "0x1",
+            "nonce": "0x0",
+            "maxPriorityFeePerGas": "0x3b9aca00",
+            "maxFeePerGas": "0x4a817c800",
+            "gas": "0x5208",
+            "from": "0x0000000000000000000000000000000000cafe01",
+            "to": "0x5f65f7b609678448494de4c87521cdf6cef1e932",
+            "value": "0x0",
+            "input": "0x",
+            "accessList": [],
+            "maxFeePerBlobGas": "0x3",
+            "blobVersionedHashes": [
+                "0x01a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8"
+            ],
+            "v": "0x1",
+            "r": "0x821193127789b107351f670025dd3b862f5836e5155f627a29741a251e8d28e8",
+            "s": "0x7ea1e82b1bf6f29c5d0f1e4024acdb698086ac40c353704d7d5e301fb916f2e3"
+        }))
+        .unwrap();
+
+        let mut sdb = StateDB::new();
+        sdb.set_account(&eth_tx.from, Account::zero());
+        sdb.set_account(&eth_tx.to.unwrap(), Account::zero());
+
+        let tx = Transaction::new(1, &sdb, &mut CodeDB::new(), &eth_tx, true).unwrap();
+        assert_eq!(tx.tx_type, TxType::Eip4844);
+        assert_eq!(tx.max_fee_per_blob_gas, Word::from(3));
+        assert_eq!(tx.blob_versioned_hashes, get_blob_versioned_hashes(&eth_tx));
+    }
+}
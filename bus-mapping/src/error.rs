@@ -78,7 +78,7 @@ pub enum OogError {
     /// cost
     DynamicMemoryExpansion,
     /// Out of Gas for CALLDATACOPY, CODECOPY, EXTCODECOPY, RETURNDATACOPY,
-    /// which copy a specified chunk of memory
+    /// MCOPY, which copy a specified chunk of memory
     MemoryCopy,
     /// Out of Gas for BALANCE, EXTCODESIZE, EXTCODEHASH, which possibly touch
     /// an extra account
@@ -98,7 +98,7 @@ pub enum OogError {
     Call,
     /// Out of Gas for Precompile.
     /// ecrecover/ecadd/ecmul/ecpairing/identity oog can should be handled by this.
-    /// modexp oog is handled inside modexp gadget.
+    /// modexp and blake2f oog are handled inside their gadgets.
     /// disabled precompiles are handled by PrecompileFailedGadget.
     Precompile,
     /// Out of Gas for CREATE and CREATE2
@@ -196,7 +196,8 @@ pub(crate) fn get_step_reported_error(op: &OpcodeId, error: &str) -> ExecError {
             OpcodeId::CALLDATACOPY
             | OpcodeId::CODECOPY
             | OpcodeId::EXTCODECOPY
-            | OpcodeId::RETURNDATACOPY => OogError::MemoryCopy,
+            | OpcodeId::RETURNDATACOPY
+            | OpcodeId::MCOPY => OogError::MemoryCopy,
             OpcodeId::BALANCE | OpcodeId::EXTCODESIZE | OpcodeId::EXTCODEHASH => {
                 OogError::AccountAccess
             }
@@ -315,6 +315,7 @@ pub fn gen_begin_tx_steps(state: &mut CircuitInputStateRef) -> Result<ExecStep,
                     value_prev: 0.into(),
                 },
             )?;
+            state.tx_created_account_write(&mut exec_step, call.address)?;
             for (field, value) in [
                 (CallContextField::Depth, call.depth.into()),
                 (
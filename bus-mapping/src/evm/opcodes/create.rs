@@ -277,6 +277,8 @@ impl<const IS_CREATE2: bool> Opcode for Create<IS_CREATE2> {
                     value_prev: 0.into(),
                 },
             )?;
+            // mark callee as created in this tx, so that it can be destructed (EIP-6780)
+            state.tx_created_account_write(&mut exec_step, callee.address)?;
 
             if length > 0 {
                 for (field, value) in [
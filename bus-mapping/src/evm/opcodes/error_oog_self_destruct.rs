@@ -0,0 +1,73 @@
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    error::{ExecError, OogError},
+    evm::{Opcode, OpcodeId},
+    operation::{AccountField, CallContextField, TxAccessListAccountOp, RW},
+    Error,
+};
+use eth_types::{GethExecStep, ToAddress, ToWord, Word, U256};
+
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ErrorOOGSelfDestruct;
+
+impl Opcode for ErrorOOGSelfDestruct {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        exec_step.error = Some(ExecError::OutOfGas(OogError::SelfDestruct));
+
+        assert_eq!(geth_step.op, OpcodeId::SELFDESTRUCT);
+        let beneficiary_word = geth_step.stack.last()?;
+        let beneficiary = beneficiary_word.to_address();
+        state.stack_read(
+            &mut exec_step,
+            geth_step.stack.last_filled(),
+            beneficiary_word,
+        )?;
+
+        let call = state.call()?.clone();
+        for (field, value) in [
+            (CallContextField::TxId, U256::from(state.tx_ctx.id())),
+            (CallContextField::CalleeAddress, call.address.to_word()),
+        ] {
+            state.call_context_read(&mut exec_step, call.call_id, field, value)?;
+        }
+
+        // read `is_warm` state of the beneficiary
+        let is_warm = state.sdb.check_account_in_access_list(&beneficiary);
+        state.push_op(
+            &mut exec_step,
+            RW::READ,
+            TxAccessListAccountOp {
+                tx_id: state.tx_ctx.id(),
+                address: beneficiary,
+                is_warm,
+                is_warm_prev: is_warm,
+            },
+        )?;
+
+        // The balance and the existence of the beneficiary decide whether the
+        // new account cost is charged.
+        let balance = state.sdb.get_account(&call.address).1.balance;
+        state.account_read(&mut exec_step, call.address, AccountField::Balance, balance)?;
+        let (_, beneficiary_account) = state.sdb.get_account(&beneficiary);
+        let beneficiary_code_hash = if beneficiary_account.is_empty() {
+            Word::zero()
+        } else {
+            beneficiary_account.code_hash.to_word()
+        };
+        state.account_read(
+            &mut exec_step,
+            beneficiary,
+            AccountField::CodeHash,
+            beneficiary_code_hash,
+        )?;
+
+        // common error handling
+        state.handle_return(&mut [&mut exec_step], geth_steps, true)?;
+        Ok(vec![exec_step])
+    }
+}
@@ -0,0 +1,220 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{
+        CircuitInputStateRef, CopyBytes, CopyDataType, CopyEvent, ExecStep, NumberOrHash,
+    },
+    Error,
+};
+use eth_types::GethExecStep;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::MCOPY`](crate::evm::OpcodeId::MCOPY)
+/// `OpcodeId`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Mcopy;
+
+impl Opcode for Mcopy {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+
+        let dst_offset = geth_step.stack.nth_last(0)?;
+        let src_offset = geth_step.stack.nth_last(1)?;
+        let length = geth_step.stack.nth_last(2)?;
+        state.stack_read(
+            &mut exec_step,
+            geth_step.stack.nth_last_filled(0),
+            dst_offset,
+        )?;
+        state.stack_read(
+            &mut exec_step,
+            geth_step.stack.nth_last_filled(1),
+            src_offset,
+        )?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(2), length)?;
+
+        let copy_event = gen_copy_event(state, geth_step, &mut exec_step)?;
+        state.push_copy(&mut exec_step, copy_event);
+        Ok(vec![exec_step])
+    }
+}
+
+fn gen_copy_event(
+    state: &mut CircuitInputStateRef,
+    geth_step: &GethExecStep,
+    exec_step: &mut ExecStep,
+) -> Result<CopyEvent, Error> {
+    let rw_counter_start = state.block_ctx.rwc;
+
+    // Offsets are only meaningful when length is non-zero, so take the low
+    // Uint64 of them.
+    let dst_addr = geth_step.stack.nth_last(0)?.low_u64();
+    let src_addr = geth_step.stack.nth_last(1)?.low_u64();
+    let length = geth_step.stack.nth_last(2)?.as_u64();
+
+    let (read_steps, write_steps, prev_bytes) =
+        state.gen_copy_steps_for_memory_to_memory(exec_step, src_addr, dst_addr, length)?;
+
+    let call_id = state.call()?.call_id;
+    Ok(CopyEvent {
+        src_type: CopyDataType::Memory,
+        src_id: NumberOrHash::Number(call_id),
+        src_addr,
+        src_addr_end: src_addr + length,
+        dst_type: CopyDataType::Memory,
+        dst_id: NumberOrHash::Number(call_id),
+        dst_addr,
+        log_id: None,
+        rw_counter_start,
+        copy_bytes: CopyBytes::new(read_steps, Some(write_steps), Some(prev_bytes)),
+    })
+}
+
+#[cfg(test)]
+mod mcopy_tests {
+    use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData, Word};
+    use mock::{
+        test_ctx::{
+            helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+            LoggerConfig,
+        },
+        TestContext,
+    };
+
+    use crate::{
+        circuit_input_builder::{CopyDataType, ExecState, NumberOrHash},
+        mock::BlockData,
+        operation::{Target, RW},
+    };
+
+    #[test]
+    fn mcopy_disjoint() {
+        test_ok(0x00, 0x40, 0x20);
+        test_ok(0x05, 0x43, 0x1a);
+    }
+
+    #[test]
+    fn mcopy_overlap_forward() {
+        test_ok(0x00, 0x10, 0x30);
+    }
+
+    #[test]
+    fn mcopy_overlap_backward() {
+        test_ok(0x10, 0x00, 0x30);
+    }
+
+    #[test]
+    fn mcopy_zero_length() {
+        test_ok(0x100, 0x200, 0x00);
+    }
+
+    #[test]
+    fn mcopy_expands_from_source() {
+        // The source range lies beyond the memory in use, the destination does not.
+        test_ok(0xa0, 0x00, 0x20);
+    }
+
+    fn test_ok(src_offset: usize, dst_offset: usize, length: usize) {
+        let code = bytecode! {
+            PUSH32(Word::from_big_endian(&(0x00..0x20).collect::<Vec<u8>>()))
+            PUSH1(0x00)
+            MSTORE
+            PUSH32(Word::from_big_endian(&(0x20..0x40).collect::<Vec<u8>>()))
+            PUSH1(0x20)
+            MSTORE
+            PUSH32(length)
+            PUSH32(src_offset)
+            PUSH32(dst_offset)
+            MCOPY
+            STOP
+        };
+
+        let block: GethData = TestContext::<2, 1>::new_with_logger_config(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+            LoggerConfig {
+                enable_memory: true,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .into();
+
+        let geth_steps = &block.geth_traces[0].struct_logs;
+        let mcopy_idx = geth_steps
+            .iter()
+            .position(|step| step.op == OpcodeId::MCOPY)
+            .unwrap();
+        let memory_before = &geth_steps[mcopy_idx].memory;
+        let memory_after = &geth_steps[mcopy_idx + 1].memory;
+
+        // The copied bytes are the source bytes from before the copy.
+        let mut expected = memory_before.clone();
+        if length > 0 {
+            expected.extend_at_least(src_offset.max(dst_offset) + length);
+            let src = expected.0[src_offset..src_offset + length].to_vec();
+            expected.0[dst_offset..dst_offset + length].copy_from_slice(&src);
+        }
+        assert_eq!(memory_after, &expected);
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::MCOPY))
+            .unwrap();
+        let call_id = builder.block.txs()[0].calls()[step.call_index].call_id;
+
+        let memory_ops = step
+            .bus_mapping_instance
+            .iter()
+            .filter(|op_ref| op_ref.target() == Target::Memory)
+            .map(|op_ref| &builder.block.container.memory[op_ref.as_usize()])
+            .collect::<Vec<_>>();
+
+        let copy_event = builder.block.copy_events.last().unwrap();
+        if length == 0 {
+            assert!(memory_ops.is_empty());
+            assert_eq!(copy_event.full_length(), 0);
+            assert_eq!(step.copy_rw_counter_delta, 0);
+            return;
+        }
+
+        assert_eq!(copy_event.src_type, CopyDataType::Memory);
+        assert_eq!(copy_event.dst_type, CopyDataType::Memory);
+        assert_eq!(copy_event.src_id, NumberOrHash::Number(call_id));
+        assert_eq!(copy_event.dst_id, NumberOrHash::Number(call_id));
+        assert_eq!(copy_event.src_addr as usize, src_offset);
+        assert_eq!(copy_event.src_addr_end as usize, src_offset + length);
+        assert_eq!(copy_event.dst_addr as usize, dst_offset);
+        assert!(copy_event.is_memory_copy());
+        assert_eq!(step.copy_rw_counter_delta, copy_event.rw_counter_delta());
+        assert_eq!(memory_ops.len() as u64, copy_event.rw_counter_delta());
+
+        // All source words are read before any destination word is written.
+        let (reads, writes) = memory_ops.split_at(memory_ops.len() / 2);
+        assert!(reads.iter().all(|op| op.rw() == RW::READ));
+        assert!(writes.iter().all(|op| op.rw() == RW::WRITE));
+
+        let mut memory_expanded = memory_before.clone();
+        memory_expanded.extend_at_least(src_offset + length);
+        for op in reads {
+            assert_eq!(
+                op.op().value(),
+                memory_expanded.read_word(*op.op().address())
+            );
+        }
+        for op in writes {
+            assert_eq!(op.op().value(), memory_after.read_word(*op.op().address()));
+        }
+    }
+}
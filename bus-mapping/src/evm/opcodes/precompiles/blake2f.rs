@@ -0,0 +1,28 @@
+use crate::{
+    circuit_input_builder::{Blake2fEvent, PrecompileEvent},
+    precompile::{Blake2fAuxData, PrecompileAuxData},
+};
+
+pub(crate) fn opt_data(
+    input_bytes: Option<Vec<u8>>,
+    output_bytes: Option<Vec<u8>>,
+) -> (Option<PrecompileEvent>, Option<PrecompileAuxData>) {
+    let aux_data = Blake2fAuxData::new(
+        input_bytes.unwrap_or_default(),
+        output_bytes.unwrap_or_default(),
+    );
+    // the call runs out of gas when there is no output.
+    let event = (aux_data.output_bytes.len() == 64).then(|| {
+        let input = &aux_data.input_bytes;
+        let word =
+            |idx: usize| u64::from_le_bytes(input[4 + idx * 8..12 + idx * 8].try_into().unwrap());
+        PrecompileEvent::Blake2F(Blake2fEvent {
+            rounds: aux_data.rounds,
+            h: std::array::from_fn(word),
+            m: std::array::from_fn(|idx| word(8 + idx)),
+            t: std::array::from_fn(|idx| word(24 + idx)),
+            f: input[212] == 1,
+        })
+    });
+    (event, Some(PrecompileAuxData::Blake2F(aux_data)))
+}
@@ -7,17 +7,23 @@ use crate::{
     Error,
 };
 
+mod blake2f;
 mod ec_add;
 mod ec_mul;
 mod ec_pairing;
 mod ecrecover;
 mod modexp;
+mod ripemd160;
+mod sha256;
 
+use blake2f::opt_data as opt_data_blake2f;
 use ec_add::opt_data as opt_data_ec_add;
 use ec_mul::opt_data as opt_data_ec_mul;
 use ec_pairing::opt_data as opt_data_ec_pairing;
 use ecrecover::opt_data as opt_data_ecrecover;
 use modexp::opt_data as opt_data_modexp;
+use ripemd160::opt_data as opt_data_ripemd160;
+use sha256::opt_data as opt_data_sha256;
 
 type InOutRetData = (Option<Vec<u8>>, Option<Vec<u8>>, Option<Vec<u8>>);
 
@@ -40,6 +46,9 @@ pub fn gen_associated_ops(
         PrecompileCalls::Bn128Mul => opt_data_ec_mul(input_bytes, output_bytes),
         PrecompileCalls::Bn128Pairing => opt_data_ec_pairing(input_bytes, output_bytes),
         PrecompileCalls::Modexp => opt_data_modexp(input_bytes, output_bytes),
+        PrecompileCalls::Sha256 => opt_data_sha256(input_bytes, output_bytes),
+        PrecompileCalls::Ripemd160 => opt_data_ripemd160(input_bytes, output_bytes),
+        PrecompileCalls::Blake2F => opt_data_blake2f(input_bytes, output_bytes),
         PrecompileCalls::Identity => (None, None),
         _ => {
             log::warn!("precompile {:?} unsupported in circuits", precompile);
@@ -0,0 +1,26 @@
+use crate::{
+    circuit_input_builder::{PrecompileEvent, Ripemd160Event},
+    precompile::{PrecompileAuxData, Ripemd160AuxData},
+};
+
+pub(crate) fn opt_data(
+    input_bytes: Option<Vec<u8>>,
+    output_bytes: Option<Vec<u8>>,
+) -> (Option<PrecompileEvent>, Option<PrecompileAuxData>) {
+    let input_bytes = input_bytes.unwrap_or_default();
+    let output_bytes = output_bytes.unwrap_or_default();
+    // the 20-byte digest is returned left-padded to a 32-byte word.
+    let event = (output_bytes.len() == 32).then(|| {
+        PrecompileEvent::Ripemd160(Ripemd160Event {
+            input: input_bytes.clone(),
+            digest: output_bytes[12..].try_into().unwrap(),
+        })
+    });
+    (
+        event,
+        Some(PrecompileAuxData::Ripemd160(Ripemd160AuxData {
+            input_bytes,
+            output_bytes,
+        })),
+    )
+}
@@ -0,0 +1,26 @@
+use crate::{
+    circuit_input_builder::{PrecompileEvent, Sha256Event},
+    precompile::{PrecompileAuxData, Sha256AuxData},
+};
+
+pub(crate) fn opt_data(
+    input_bytes: Option<Vec<u8>>,
+    output_bytes: Option<Vec<u8>>,
+) -> (Option<PrecompileEvent>, Option<PrecompileAuxData>) {
+    let input_bytes = input_bytes.unwrap_or_default();
+    let output_bytes = output_bytes.unwrap_or_default();
+    // the digest is only available for a successful call.
+    let event = (output_bytes.len() == 32).then(|| {
+        PrecompileEvent::Sha256(Sha256Event {
+            input: input_bytes.clone(),
+            digest: output_bytes.clone().try_into().unwrap(),
+        })
+    });
+    (
+        event,
+        Some(PrecompileAuxData::Sha256(Sha256AuxData {
+            input_bytes,
+            output_bytes,
+        })),
+    )
+}
@@ -0,0 +1,339 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    operation::{AccountField, AccountOp, CallContextField},
+    Error,
+};
+use eth_types::{GethExecStep, ToAddress, ToWord, Word};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::SELFDESTRUCT`](crate::evm::OpcodeId::SELFDESTRUCT) `OpcodeId`.
+///
+/// The whole balance of the current account is always sent to the
+/// beneficiary, but following EIP-6780 the account itself is only destructed
+/// when it has been created in the same transaction.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct SelfDestruct;
+
+impl Opcode for SelfDestruct {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+
+        let call = state.call()?.clone();
+        let address = call.address;
+        let beneficiary_word = geth_step.stack.last()?;
+        let beneficiary = beneficiary_word.to_address();
+
+        for (field, value) in [
+            (CallContextField::TxId, state.tx_ctx.id().into()),
+            (CallContextField::IsStatic, (call.is_static as u64).into()),
+        ] {
+            state.call_context_read(&mut exec_step, call.call_id, field, value)?;
+        }
+        state.reversion_info_read(&mut exec_step, &call)?;
+        state.call_context_read(
+            &mut exec_step,
+            call.call_id,
+            CallContextField::CalleeAddress,
+            address.to_word(),
+        )?;
+
+        state.stack_read(
+            &mut exec_step,
+            geth_step.stack.last_filled(),
+            beneficiary_word,
+        )?;
+
+        state.tx_access_list_write(&mut exec_step, beneficiary)?;
+        let is_created = state.tx_created_account_read(&mut exec_step, address)?;
+
+        let (found, account) = state.sdb.get_account(&address);
+        if !found {
+            return Err(Error::AccountNotFound(address));
+        }
+        let balance = account.balance;
+        state.account_read(&mut exec_step, address, AccountField::Balance, balance)?;
+
+        let (_, beneficiary_account) = state.sdb.get_account(&beneficiary);
+        let beneficiary_exists = !beneficiary_account.is_empty();
+        let beneficiary_code_hash = if beneficiary_exists {
+            beneficiary_account.code_hash.to_word()
+        } else {
+            Word::zero()
+        };
+        state.account_read(
+            &mut exec_step,
+            beneficiary,
+            AccountField::CodeHash,
+            beneficiary_code_hash,
+        )?;
+
+        log::trace!(
+            "self destruct, address {:?} beneficiary {:?} value {:?} is_created {}",
+            address,
+            beneficiary,
+            balance,
+            is_created
+        );
+        state.transfer(
+            &mut exec_step,
+            address,
+            beneficiary,
+            beneficiary_exists,
+            false,
+            balance,
+        )?;
+
+        // EIP-6780: only the contracts created in the same transaction are
+        // destructed, any other contract just sends out its balance.
+        if is_created {
+            let account = state.sdb.get_account(&address).1.clone();
+            for (field, value_prev) in [
+                // non-zero only when the beneficiary is the account itself, in
+                // which case the ether is burnt.
+                (AccountField::Balance, account.balance),
+                (AccountField::Nonce, account.nonce),
+                (AccountField::CodeHash, account.code_hash.to_word()),
+            ] {
+                state.push_op_reversible(
+                    &mut exec_step,
+                    AccountOp {
+                        address,
+                        field,
+                        value: Word::zero(),
+                        value_prev,
+                    },
+                )?;
+            }
+            if call.is_persistent {
+                state.sdb.destruct_account(address);
+            }
+        }
+
+        state.call_context_read(
+            &mut exec_step,
+            call.call_id,
+            CallContextField::IsSuccess,
+            1.into(),
+        )?;
+        if let Ok(caller) = state.caller_ctx_mut() {
+            caller.return_data.clear();
+        }
+        state.handle_return(&mut [&mut exec_step], geth_steps, !call.is_root)?;
+
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod selfdestruct_tests {
+    use crate::{
+        circuit_input_builder::ExecState,
+        mock::BlockData,
+        operation::{
+            AccountField, AccountOp, CallContextField, CallContextOp, StackOp, Target,
+            TxAccessListAccountOp, TxCreatedAccountOp, RW,
+        },
+        state_db::CodeDB,
+    };
+    use eth_types::{address, bytecode, evm_types::OpcodeId, geth_types::GethData, ToWord, Word};
+    use mock::{eth, TestContext};
+    use pretty_assertions::assert_eq;
+
+    fn test_ok(is_created: bool, self_beneficiary: bool) {
+        let sender = address!("0x0000000000000000000000000000000000cafe01");
+        let contract = address!("0x0000000000000000000000000000000000000010");
+        let other = address!("0x00000000000000000000000000000000000000be");
+        let balance = eth(1);
+
+        // The init code of a creation tx runs at the not yet known contract
+        // address, so the contract refers to itself through ADDRESS.
+        let code = if self_beneficiary {
+            bytecode! {
+                ADDRESS
+                SELFDESTRUCT
+            }
+        } else {
+            bytecode! {
+                PUSH20(other.to_word())
+                SELFDESTRUCT
+            }
+        };
+
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(sender).balance(eth(10));
+                accs[1].address(other).balance(eth(1));
+                if is_created {
+                    accs[2].address(contract).balance(eth(1));
+                } else {
+                    accs[2]
+                        .address(contract)
+                        .balance(balance)
+                        .code(code.clone());
+                }
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[0].address).gas(100_000.into());
+                if is_created {
+                    txs[0].value(balance).input(code.clone().into());
+                } else {
+                    txs[0].to(accs[2].address);
+                }
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let tx_id = 1;
+        let transaction = &builder.block.txs()[tx_id - 1];
+        let call = &transaction.calls()[0];
+        let address = call.address;
+        let (beneficiary, beneficiary_balance_prev) = if self_beneficiary {
+            (address, Word::zero())
+        } else {
+            (other, eth(1))
+        };
+
+        let step = transaction
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::SELFDESTRUCT))
+            .unwrap();
+        let container = &builder.block.container;
+
+        assert_eq!(
+            (0..5)
+                .map(|idx| {
+                    let operation =
+                        &container.call_context[step.bus_mapping_instance[idx].as_usize()];
+                    (operation.rw(), operation.op().field.clone())
+                })
+                .collect::<Vec<_>>(),
+            vec![
+                (RW::READ, CallContextField::TxId),
+                (RW::READ, CallContextField::IsStatic),
+                (RW::READ, CallContextField::RwCounterEndOfReversion),
+                (RW::READ, CallContextField::IsPersistent),
+                (RW::READ, CallContextField::CalleeAddress),
+            ]
+        );
+
+        let operation = &container.stack[step.bus_mapping_instance[5].as_usize()];
+        assert_eq!(operation.rw(), RW::READ);
+        assert_eq!(
+            operation.op(),
+            &StackOp::new(call.call_id, 1023.into(), beneficiary.to_word())
+        );
+
+        let operation = &container.tx_access_list_account[step.bus_mapping_instance[6].as_usize()];
+        assert_eq!(operation.rw(), RW::WRITE);
+        assert_eq!(
+            operation.op(),
+            &TxAccessListAccountOp {
+                tx_id,
+                address: beneficiary,
+                is_warm: true,
+                is_warm_prev: self_beneficiary,
+            }
+        );
+
+        let operation = &container.tx_created_account[step.bus_mapping_instance[7].as_usize()];
+        assert_eq!(operation.rw(), RW::READ);
+        assert_eq!(
+            operation.op(),
+            &TxCreatedAccountOp {
+                tx_id,
+                address,
+                is_created,
+                is_created_prev: is_created,
+            }
+        );
+
+        // Balance and beneficiary code hash reads, then the transfer and the
+        // destruction writes.
+        let account_ops = step.bus_mapping_instance[8..]
+            .iter()
+            .filter(|op_ref| op_ref.target() == Target::Account)
+            .map(|op_ref| container.account[op_ref.as_usize()].op().clone())
+            .collect::<Vec<_>>();
+        let mut expected = vec![
+            AccountOp::new(address, AccountField::Balance, balance, balance),
+            AccountOp::new(
+                beneficiary,
+                AccountField::CodeHash,
+                account_ops[1].value,
+                account_ops[1].value,
+            ),
+            AccountOp::new(address, AccountField::Balance, Word::zero(), balance),
+            AccountOp::new(
+                beneficiary,
+                AccountField::Balance,
+                beneficiary_balance_prev + balance,
+                beneficiary_balance_prev,
+            ),
+        ];
+        if is_created {
+            let balance_prev = if self_beneficiary {
+                balance
+            } else {
+                Word::zero()
+            };
+            expected.extend([
+                AccountOp::new(address, AccountField::Balance, Word::zero(), balance_prev),
+                AccountOp::new(address, AccountField::Nonce, Word::zero(), Word::one()),
+                AccountOp::new(
+                    address,
+                    AccountField::CodeHash,
+                    Word::zero(),
+                    CodeDB::empty_code_hash().to_word(),
+                ),
+            ]);
+        }
+        assert_eq!(account_ops, expected);
+        assert!(!account_ops[1].value.is_zero());
+
+        let operation = &container.call_context[step
+            .bus_mapping_instance
+            .iter()
+            .rev()
+            .find(|op_ref| op_ref.target() == Target::CallContext)
+            .unwrap()
+            .as_usize()];
+        assert_eq!(
+            operation.op(),
+            &CallContextOp {
+                call_id: call.call_id,
+                field: CallContextField::IsSuccess,
+                value: Word::one(),
+            }
+        );
+
+        let (_, account) = builder.sdb.get_account(&address);
+        assert_eq!(account.is_empty(), is_created);
+    }
+
+    #[test]
+    fn selfdestruct_pre_existing_contract() {
+        test_ok(false, false);
+        test_ok(false, true);
+    }
+
+    #[test]
+    fn selfdestruct_contract_created_in_tx() {
+        test_ok(true, false);
+        test_ok(true, true);
+    }
+}
@@ -0,0 +1,67 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    operation::{CallContextField, TransientStorageOp, RW},
+    Error,
+};
+use eth_types::{GethExecStep, ToWord, Word};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::TLOAD`](crate::evm::OpcodeId::TLOAD)
+/// `OpcodeId`.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Tload;
+
+impl Opcode for Tload {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+
+        let call_id = state.call()?.call_id;
+        let contract_addr = state.call()?.address;
+
+        state.call_context_read(
+            &mut exec_step,
+            call_id,
+            CallContextField::TxId,
+            Word::from(state.tx_ctx.id()),
+        )?;
+
+        state.call_context_read(
+            &mut exec_step,
+            call_id,
+            CallContextField::CalleeAddress,
+            contract_addr.to_word(),
+        )?;
+
+        let key = geth_step.stack.last()?;
+        state.stack_read(&mut exec_step, geth_step.stack.last_filled(), key)?;
+
+        let value = *state.sdb.get_transient_storage(&contract_addr, &key);
+        {
+            let value_from_stack = geth_steps[1].stack.last()?;
+            if value != value_from_stack {
+                panic!("inconsistent tload: local statedb {value:?}, result {value_from_stack:?} in contract {contract_addr:?}, key {key:?}");
+            }
+        }
+
+        state.push_op(
+            &mut exec_step,
+            RW::READ,
+            TransientStorageOp {
+                tx_id: state.tx_ctx.id(),
+                address: contract_addr,
+                key,
+                value,
+                value_prev: value,
+            },
+        )?;
+
+        state.stack_write(&mut exec_step, geth_step.stack.last_filled(), value)?;
+
+        Ok(vec![exec_step])
+    }
+}
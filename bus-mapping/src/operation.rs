@@ -106,6 +106,10 @@ pub enum Target {
     TxReceipt,
     /// Means the target of the operation is the TxLog.
     TxLog,
+    /// Means the target of the operation is the TxCreatedAccount.
+    TxCreatedAccount,
+    /// Means the target of the operation is the TransientStorage.
+    TransientStorage,
 }
 
 /// Trait used for Operation Kinds.
@@ -443,6 +447,110 @@ impl Op for TxAccessListAccountOp {
     }
 }
 
+/// Represents a change in the set of accounts created in the current
+/// transaction, implied by a `BeginTx` of a creation tx or a `CREATE*` step,
+/// and read by `SELFDESTRUCT` to apply EIP-6780.
+#[derive(Clone, PartialEq, Eq)]
+pub struct TxCreatedAccountOp {
+    /// Transaction ID: Transaction index in the block starting at 1.
+    pub tx_id: usize,
+    /// Account Address
+    pub address: Address,
+    /// Whether the account has been created in the transaction.
+    pub is_created: bool,
+    /// Whether the account had been created in the transaction before this
+    /// operation.
+    pub is_created_prev: bool,
+}
+
+impl fmt::Debug for TxCreatedAccountOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TxCreatedAccountOp { ")?;
+        f.write_fmt(format_args!(
+            "tx_id: {:?}, addr: {:?}, is_created_prev: {:?}, is_created: {:?}",
+            self.tx_id, self.address, self.is_created_prev, self.is_created
+        ))?;
+        f.write_str(" }")
+    }
+}
+
+impl PartialOrd for TxCreatedAccountOp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TxCreatedAccountOp {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.tx_id, &self.address).cmp(&(&other.tx_id, &other.address))
+    }
+}
+
+impl Op for TxCreatedAccountOp {
+    fn into_enum(self) -> OpEnum {
+        OpEnum::TxCreatedAccount(self)
+    }
+
+    fn reverse(&self) -> Self {
+        let mut rev = self.clone();
+        swap(&mut rev.is_created, &mut rev.is_created_prev);
+        rev
+    }
+}
+
+/// Represents a change in the transient storage (EIP-1153) implied by a
+/// `TLOAD` or `TSTORE` step of the
+/// [`ExecStep`](crate::circuit_input_builder::ExecStep). Transient storage is
+/// keyed by `tx_id` so that it starts empty in every transaction.
+#[derive(Clone, PartialEq, Eq)]
+pub struct TransientStorageOp {
+    /// Transaction ID: Transaction index in the block starting at 1.
+    pub tx_id: usize,
+    /// Account Address
+    pub address: Address,
+    /// Transient Storage Key
+    pub key: Word,
+    /// Transient Storage Value after the operation
+    pub value: Word,
+    /// Transient Storage Value before the operation
+    pub value_prev: Word,
+}
+
+impl fmt::Debug for TransientStorageOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TransientStorageOp { ")?;
+        f.write_fmt(format_args!(
+            "tx_id: {:?}, addr: {:?}, key: {:?}, val_prev: 0x{:x}, val: 0x{:x}",
+            self.tx_id, self.address, self.key, self.value_prev, self.value
+        ))?;
+        f.write_str(" }")
+    }
+}
+
+impl PartialOrd for TransientStorageOp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TransientStorageOp {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.tx_id, &self.address, &self.key).cmp(&(&other.tx_id, &other.address, &other.key))
+    }
+}
+
+impl Op for TransientStorageOp {
+    fn into_enum(self) -> OpEnum {
+        OpEnum::TransientStorage(self)
+    }
+
+    fn reverse(&self) -> Self {
+        let mut rev = self.clone();
+        swap(&mut rev.value, &mut rev.value_prev);
+        rev
+    }
+}
+
 /// Represents a change in the Storage AccessList implied by an `SSTORE` or
 /// `SLOAD` step of the [`ExecStep`](crate::circuit_input_builder::ExecStep).
 #[derive(Clone, PartialEq, Eq)]
@@ -947,6 +1055,10 @@ pub enum OpEnum {
     TxReceipt(TxReceiptOp),
     /// TxLog
     TxLog(TxLogOp),
+    /// TxCreatedAccount
+    TxCreatedAccount(TxCreatedAccountOp),
+    /// TransientStorage
+    TransientStorage(TransientStorageOp),
     /// Start
     Start(StartOp),
 }
@@ -1,7 +1,7 @@
 use super::{
     AccountOp, CallContextOp, MemoryOp, Op, OpEnum, Operation, RWCounter, StackOp, StartOp,
-    StorageOp, Target, TxAccessListAccountOp, TxAccessListAccountStorageOp, TxLogOp, TxReceiptOp,
-    TxRefundOp, RW,
+    StorageOp, Target, TransientStorageOp, TxAccessListAccountOp, TxAccessListAccountStorageOp,
+    TxCreatedAccountOp, TxLogOp, TxReceiptOp, TxRefundOp, RW,
 };
 use crate::exec_trace::OperationRef;
 use itertools::Itertools;
@@ -42,6 +42,10 @@ pub struct OperationContainer {
     pub tx_receipt: Vec<Operation<TxReceiptOp>>,
     /// Operations of TxLogOp
     pub tx_log: Vec<Operation<TxLogOp>>,
+    /// Operations of TxCreatedAccountOp
+    pub tx_created_account: Vec<Operation<TxCreatedAccountOp>>,
+    /// Operations of TransientStorageOp
+    pub transient_storage: Vec<Operation<TransientStorageOp>>,
     /// Operations of Start
     pub start: Vec<Operation<StartOp>>,
 }
@@ -67,6 +71,8 @@ impl OperationContainer {
             call_context: Vec::new(),
             tx_receipt: Vec::new(),
             tx_log: Vec::new(),
+            tx_created_account: Vec::new(),
+            transient_storage: Vec::new(),
             start: Vec::new(),
         }
     }
@@ -161,6 +167,22 @@ impl OperationContainer {
                 self.tx_log.push(Operation::new(rwc, rw, op));
                 OperationRef::from((Target::TxLog, self.tx_log.len() - 1))
             }
+            OpEnum::TxCreatedAccount(op) => {
+                self.tx_created_account.push(if reversible {
+                    Operation::new_reversible(rwc, rw, op)
+                } else {
+                    Operation::new(rwc, rw, op)
+                });
+                OperationRef::from((Target::TxCreatedAccount, self.tx_created_account.len() - 1))
+            }
+            OpEnum::TransientStorage(op) => {
+                self.transient_storage.push(if reversible {
+                    Operation::new_reversible(rwc, rw, op)
+                } else {
+                    Operation::new(rwc, rw, op)
+                });
+                OperationRef::from((Target::TransientStorage, self.transient_storage.len() - 1))
+            }
             OpEnum::Start(op) => {
                 self.start.push(Operation::new(rwc, rw, op));
                 OperationRef::from((Target::Start, self.start.len() - 1))
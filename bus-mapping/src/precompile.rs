@@ -33,9 +33,6 @@ pub(crate) fn execute_precompiled(
                 // Revm behavior is different from scroll evm,
                 // so we need to override the behavior of invalid input
                 match PrecompileCalls::from(address.0[19]) {
-                    PrecompileCalls::Blake2F
-                    | PrecompileCalls::Sha256
-                    | PrecompileCalls::Ripemd160 => (vec![], gas, false, false),
                     PrecompileCalls::Bn128Pairing => {
                         if input.len() > N_PAIRING_PER_OP * N_BYTES_PER_PAIR {
                             (vec![], gas, false, false)
@@ -397,6 +394,56 @@ pub enum EcPairingError {
     InvalidInputLen(Vec<u8>),
 }
 
+/// Auxiliary data for Sha256.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Sha256AuxData {
+    /// The bytes being hashed.
+    pub input_bytes: Vec<u8>,
+    /// The 32-byte digest.
+    pub output_bytes: Vec<u8>,
+}
+
+/// Auxiliary data for Ripemd160.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Ripemd160AuxData {
+    /// The bytes being hashed.
+    pub input_bytes: Vec<u8>,
+    /// The 20-byte digest, left-padded with zeroes to 32 bytes.
+    pub output_bytes: Vec<u8>,
+}
+
+/// Length of the BLAKE2F input: `rounds | h | m | t | f`.
+pub const BLAKE2F_INPUT_LEN: usize = 213;
+
+/// Auxiliary data for Blake2F.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Blake2fAuxData {
+    /// The number of rounds, from the first 4 bytes of the input.
+    pub rounds: u32,
+    /// The input bytes.
+    pub input_bytes: Vec<u8>,
+    /// The 64-byte output, empty if the call ran out of gas.
+    pub output_bytes: Vec<u8>,
+}
+
+impl Blake2fAuxData {
+    /// Create a new instance of blake2f auxiliary data.
+    pub fn new(input_bytes: Vec<u8>, output_bytes: Vec<u8>) -> Self {
+        assert!(Self::check_input(&input_bytes));
+        Self {
+            rounds: u32::from_be_bytes(input_bytes[..4].try_into().unwrap()),
+            input_bytes,
+            output_bytes,
+        }
+    }
+
+    /// Check the input length and the final block indicator flag, which must
+    /// be 0 or 1.
+    pub fn check_input(input: &[u8]) -> bool {
+        input.len() == BLAKE2F_INPUT_LEN && input[BLAKE2F_INPUT_LEN - 1] <= 1
+    }
+}
+
 /// Auxiliary data attached to an internal state for precompile verification.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum PrecompileAuxData {
@@ -410,6 +457,12 @@ pub enum PrecompileAuxData {
     EcMul(EcMulAuxData),
     /// EcPairing.
     EcPairing(Box<Result<EcPairingAuxData, EcPairingError>>),
+    /// Sha256.
+    Sha256(Sha256AuxData),
+    /// Ripemd160.
+    Ripemd160(Ripemd160AuxData),
+    /// Blake2F.
+    Blake2F(Blake2fAuxData),
 }
 
 impl Default for PrecompileAuxData {
@@ -417,3 +470,48 @@ impl Default for PrecompileAuxData {
         Self::Ecrecover(EcrecoverAuxData::default())
     }
 }
+
+#[cfg(all(test, feature = "scroll"))]
+mod tests {
+    use super::*;
+
+    fn call(precompile: PrecompileCalls, input: &[u8]) -> (Vec<u8>, u64, bool) {
+        execute_precompiled(&precompile.into(), input, 1_000_000)
+    }
+
+    #[test]
+    fn sha256_is_executed() {
+        let (output, gas_cost, is_oog) = call(PrecompileCalls::Sha256, &[]);
+        assert!(!is_oog);
+        assert_eq!(gas_cost, GasCost::PRECOMPILE_SHA256_BASE.0);
+        assert_eq!(
+            hex::encode(output),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn ripemd160_is_executed() {
+        let (output, gas_cost, is_oog) = call(PrecompileCalls::Ripemd160, &[]);
+        assert!(!is_oog);
+        assert_eq!(gas_cost, GasCost::PRECOMPILE_RIPEMD160_BASE.0);
+        assert_eq!(
+            hex::encode(output),
+            "0000000000000000000000009c1185a5c5e9fc54612808977ee8f548b2258d31"
+        );
+    }
+
+    #[test]
+    fn blake2f_is_executed() {
+        let rounds = 12u32;
+        let mut input = vec![0u8; BLAKE2F_INPUT_LEN];
+        input[..4].copy_from_slice(&rounds.to_be_bytes());
+        // final block indicator
+        input[BLAKE2F_INPUT_LEN - 1] = 1;
+
+        let (output, gas_cost, is_oog) = call(PrecompileCalls::Blake2F, &input);
+        assert!(!is_oog);
+        assert_eq!(gas_cost, u64::from(rounds));
+        assert_eq!(output.len(), 64);
+    }
+}
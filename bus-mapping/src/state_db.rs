@@ -142,6 +142,12 @@ pub struct StateDB {
     // has already been applied.
     // TODO: a better name?
     touched_account: HashSet<Address>,
+    // Accounts that have been created in the current transaction, which are the only ones
+    // `SELFDESTRUCT` is allowed to destruct since EIP-6780.
+    created_account: HashSet<Address>,
+    // Transient storage (EIP-1153) of the current transaction, which is discarded once
+    // `commit_tx` is called.
+    transient_storage: HashMap<(Address, Word), Word>,
     refund: u64,
 }
 
@@ -165,6 +171,11 @@ impl StateDB {
         }
     }
 
+    /// Iterate over all the accounts in current state db
+    pub fn accounts(&self) -> impl Iterator<Item = (&Address, &Account)> {
+        self.state.iter()
+    }
+
     /// List all account addresses in current state db
     pub fn list_accounts(&self) {
         let addrs: BTreeSet<_> = self.state.keys().collect();
@@ -250,6 +261,20 @@ impl StateDB {
         self.dirty_storage.insert((*addr, *key), *value);
     }
 
+    /// Get the transient storage value at `addr` and `key` in the current
+    /// transaction. Returns zero if it hasn't been written.
+    pub fn get_transient_storage(&self, addr: &Address, key: &Word) -> &Word {
+        self.transient_storage
+            .get(&(*addr, *key))
+            .unwrap_or(&VALUE_ZERO)
+    }
+
+    /// Set the transient storage value at `addr` and `key` in the current
+    /// transaction.
+    pub fn set_transient_storage(&mut self, addr: &Address, key: &Word, value: &Word) {
+        self.transient_storage.insert((*addr, *key), *value);
+    }
+
     /// Get balance of account with the given address.
     pub fn get_balance(&self, addr: &Address) -> Word {
         let (_, account) = self.get_account(addr);
@@ -307,6 +332,23 @@ impl StateDB {
         debug_assert!(exist);
     }
 
+    /// Check whether `addr` has been created in the current transaction.
+    pub fn is_created_account(&self, addr: &Address) -> bool {
+        self.created_account.contains(addr)
+    }
+
+    /// Mark `addr` as created in the current transaction. Returns `true` if it
+    /// wasn't marked before.
+    pub fn add_created_account(&mut self, addr: Address) -> bool {
+        self.created_account.insert(addr)
+    }
+
+    /// Unmark `addr` as created in the current transaction.
+    pub fn remove_created_account(&mut self, addr: &Address) {
+        let exist = self.created_account.remove(addr);
+        debug_assert!(exist);
+    }
+
     /// Set account as self destructed.
     pub fn destruct_account(&mut self, addr: Address) {
         self.state.insert(addr, Account::zero());
@@ -335,6 +377,8 @@ impl StateDB {
         }
         self.dirty_storage = HashMap::new();
         self.touched_account = HashSet::new();
+        self.created_account = HashSet::new();
+        self.transient_storage = HashMap::new();
         for addr in self.destructed_account.clone() {
             let (_, account) = self.get_account_mut(&addr);
             *account = ACCOUNT_ZERO.clone();
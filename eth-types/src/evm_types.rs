@@ -71,6 +71,9 @@ pub const MAX_EXPANDED_MEMORY_ADDRESS: u64 = 0x1FFFFFFFE0;
 pub const MAX_REFUND_QUOTIENT_OF_GAS_USED: usize = 5;
 /// Gas stipend when CALL or CALLCODE is attached with value.
 pub const GAS_STIPEND_CALL_WITH_VALUE: u64 = 2300;
+/// Maximum number of blobs a tx can carry, i.e. MAX_BLOB_GAS_PER_BLOCK / GAS_PER_BLOB
+/// (786432 / 131072) as per EIP-4844.
+pub const MAX_BLOBS_PER_TX: usize = 6;
 
 #[cfg(feature = "shanghai")]
 mod gas_create {
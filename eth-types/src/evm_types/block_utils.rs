@@ -27,3 +27,40 @@ pub fn is_valid_block_number(block_number: U256, current_block_number: U256) ->
                 .checked_sub(NUM_PREV_BLOCK_ALLOWED.into())
                 .unwrap_or_default()
 }
+
+/// Minimum base fee per blob gas as per EIP-4844.
+pub const MIN_BLOB_BASE_FEE: u64 = 1;
+/// Controls the maximum rate of change of the blob base fee as per EIP-4844.
+pub const BLOB_BASE_FEE_UPDATE_FRACTION: u64 = 3338477;
+
+/// Calculate the blob base fee from the excess blob gas of a block, i.e.
+/// `fake_exponential(MIN_BLOB_BASE_FEE, excess_blob_gas, BLOB_BASE_FEE_UPDATE_FRACTION)`.
+pub fn calculate_blob_base_fee(excess_blob_gas: u64) -> U256 {
+    let factor = U256::from(MIN_BLOB_BASE_FEE);
+    let numerator = U256::from(excess_blob_gas);
+    let denominator = U256::from(BLOB_BASE_FEE_UPDATE_FRACTION);
+
+    let mut output = U256::zero();
+    let mut accum = factor * denominator;
+    let mut i = U256::one();
+    while !accum.is_zero() {
+        output += accum;
+        accum = accum * numerator / (denominator * i);
+        i += U256::one();
+    }
+
+    output / denominator
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blob_base_fee() {
+        assert_eq!(calculate_blob_base_fee(0), U256::from(MIN_BLOB_BASE_FEE));
+        assert_eq!(calculate_blob_base_fee(2314057), U256::from(1));
+        assert_eq!(calculate_blob_base_fee(2314058), U256::from(2));
+        assert_eq!(calculate_blob_base_fee(10 * 1024 * 1024), U256::from(23));
+    }
+}
@@ -1,13 +1,17 @@
 //! Types needed for generating Ethereum traces
 
 use crate::{
+    evm_types::block_utils::calculate_blob_base_fee,
     sign_types::{biguint_to_32bytes_le, ct_option_ok_or, recover_pk, SignData, SECP256K1_Q},
     AccessList, Address, Block, Bytes, Error, GethExecTrace, Hash, ToBigEndian, ToLittleEndian,
     Word, U64,
 };
-use ethers_core::types::{
-    transaction::eip2718::TypedTransaction, Eip1559TransactionRequest, Eip2930TransactionRequest,
-    NameOrAddress, TransactionRequest, H256,
+use ethers_core::{
+    types::{
+        transaction::eip2718::TypedTransaction, Eip1559TransactionRequest,
+        Eip2930TransactionRequest, NameOrAddress, TransactionRequest, H256,
+    },
+    utils::rlp::RlpStream,
 };
 use halo2_proofs::halo2curves::{group::ff::PrimeField, secp256k1};
 use num::Integer;
@@ -32,6 +36,8 @@ pub enum TxType {
     Eip2930,
     /// L1 Message tx
     L1Msg,
+    /// EIP 4844 blob tx
+    Eip4844,
 }
 
 impl From<TxType> for usize {
@@ -57,11 +63,17 @@ impl TxType {
         matches!(*self, TxType::Eip155)
     }
 
+    /// If this type is Eip4844 or not
+    pub fn is_eip4844_tx(&self) -> bool {
+        matches!(*self, TxType::Eip4844)
+    }
+
     /// Get the type of transaction
     pub fn get_tx_type(tx: &crate::Transaction) -> Self {
         match tx.transaction_type {
             Some(x) if x == U64::from(1) => Self::Eip2930,
             Some(x) if x == U64::from(2) => Self::Eip1559,
+            Some(x) if x == U64::from(3) => Self::Eip4844,
             Some(x) if x == U64::from(0x7e) => Self::L1Msg,
             _ => {
                 if cfg!(feature = "scroll") {
@@ -102,6 +114,10 @@ impl TxType {
             TxType::L1Msg => {
                 unreachable!("L1 msg does not have signature")
             }
+            TxType::Eip4844 => {
+                assert!(v <= 1);
+                v
+            }
         };
 
         recovery_id as u8
@@ -133,9 +149,75 @@ pub fn get_rlp_unsigned(tx: &crate::Transaction) -> Vec<u8> {
             // L1 msg does not have signature
             vec![]
         }
+        TxType::Eip4844 => eip4844_rlp(tx, false),
     }
 }
 
+/// Get the RLP bytes of the signed tx
+pub fn get_rlp_signed(tx: &crate::Transaction) -> Vec<u8> {
+    match TxType::get_tx_type(tx) {
+        // ethers-rs does not know about blob txs and would encode them as legacy txs.
+        TxType::Eip4844 => eip4844_rlp(tx, true),
+        _ => tx.rlp().to_vec(),
+    }
+}
+
+/// Get the max fee per blob gas of an EIP-4844 tx (zero for other tx types)
+pub fn get_max_fee_per_blob_gas(tx: &crate::Transaction) -> Word {
+    tx.other
+        .get_deserialized::<Word>("maxFeePerBlobGas")
+        .and_then(Result::ok)
+        .unwrap_or_default()
+}
+
+/// Get the blob versioned hashes of an EIP-4844 tx (empty for other tx types)
+pub fn get_blob_versioned_hashes(tx: &crate::Transaction) -> Vec<H256> {
+    tx.other
+        .get_deserialized::<Vec<H256>>("blobVersionedHashes")
+        .and_then(Result::ok)
+        .unwrap_or_default()
+}
+
+/// Get the blob base fee of a block, derived from its excess blob gas (the
+/// minimum blob base fee for pre-Cancun blocks)
+pub fn get_blob_base_fee<TX>(block: &Block<TX>) -> Word {
+    let excess_blob_gas = block
+        .other
+        .get_deserialized::<U64>("excessBlobGas")
+        .and_then(Result::ok)
+        .unwrap_or_default();
+    calculate_blob_base_fee(excess_blob_gas.as_u64())
+}
+
+// rlp([chain_id, nonce, max_priority_fee_per_gas, max_fee_per_gas, gas_limit, to, value, data,
+//      access_list, max_fee_per_blob_gas, blob_versioned_hashes, (y_parity, r, s)])
+// prefixed by the tx type 0x03.
+fn eip4844_rlp(tx: &crate::Transaction, signed: bool) -> Vec<u8> {
+    let mut stream = RlpStream::new();
+    stream.begin_list(if signed { 14 } else { 11 });
+    stream.append(&tx.chain_id.unwrap_or_default());
+    stream.append(&tx.nonce);
+    stream.append(&tx.max_priority_fee_per_gas.unwrap_or_default());
+    stream.append(&tx.max_fee_per_gas.unwrap_or_default());
+    stream.append(&tx.gas);
+    match tx.to {
+        Some(to) => stream.append(&to),
+        None => stream.append(&""),
+    };
+    stream.append(&tx.value);
+    stream.append(&tx.input);
+    stream.append(&tx.access_list.clone().unwrap_or_default());
+    stream.append(&get_max_fee_per_blob_gas(tx));
+    stream.append_list(&get_blob_versioned_hashes(tx));
+    if signed {
+        stream.append(&tx.v);
+        stream.append(&tx.r);
+        stream.append(&tx.s);
+    }
+
+    [vec![0x03], stream.out().to_vec()].concat()
+}
+
 /// Definition of all of the data related to an account.
 #[serde_as]
 #[derive(PartialEq, Eq, Debug, Default, Clone, Serialize)]
@@ -255,6 +337,10 @@ pub struct Transaction {
     pub call_data: Bytes,
     /// Access list
     pub access_list: Option<AccessList>,
+    /// Max fee per blob gas (EIP-4844)
+    pub max_fee_per_blob_gas: Word,
+    /// Blob versioned hashes (EIP-4844)
+    pub blob_versioned_hashes: Vec<H256>,
 
     /// "v" value of the transaction signature
     pub v: u64,
@@ -308,10 +394,12 @@ impl From<&crate::Transaction> for Transaction {
             gas_tip_cap: tx.max_fee_per_gas.unwrap_or_default(),
             call_data: tx.input.clone(),
             access_list: tx.access_list.clone(),
+            max_fee_per_blob_gas: get_max_fee_per_blob_gas(tx),
+            blob_versioned_hashes: get_blob_versioned_hashes(tx),
             v: tx.v.as_u64(),
             r: tx.r,
             s: tx.s,
-            rlp_bytes: tx.rlp().to_vec(),
+            rlp_bytes: get_rlp_signed(tx),
             rlp_unsigned_bytes: get_rlp_unsigned(tx),
             hash: tx.hash,
         }
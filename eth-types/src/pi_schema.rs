@@ -0,0 +1,295 @@
+//! Layouts of the preimage of the public input hash of a chunk, or a batch of chunks.
+//!
+//! The public input hash is `keccak(f_0 || f_1 || ...)` where the fields `f_i` and their order
+//! are given by a [`PiSchema`]. Every component that builds or constrains the preimage (the
+//! keccak inputs of bus-mapping, the PI circuit, and the aggregator) reads the layout from here.
+//!
+//! The schema is a part of the data of a chunk and of a batch, and the circuits lay out the
+//! preimage as it says. Since the layout shapes the copy constraints of the circuits, a proving
+//! key only proves the schema it was generated for. [`PI_SCHEMA`] is the schema of the chunks
+//! built from new blocks: a protocol upgrade adds a [`PiSchema`] variant, the fields it needs,
+//! and moves [`PI_SCHEMA`] to the new variant.
+//!
+//! The data hash of a batch is laid out by a [`PiBatchLayout`], which depends on how the batch is
+//! aggregated from its chunks.
+
+use crate::H256;
+use ethers_core::utils::keccak256;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Number of bytes absorbed by each keccak-f round.
+const KECCAK_RATE: usize = 136;
+
+/// A field of the public input hash preimage.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PiField {
+    /// Chain id
+    ChainId,
+    /// State root before the chunk
+    PrevStateRoot,
+    /// State root after the chunk
+    PostStateRoot,
+    /// Withdraw root after the chunk
+    WithdrawRoot,
+    /// Hash of the chunk data
+    DataHash,
+    /// Rolling hash of the L1 message queue after the chunk
+    L1MsgQueueHash,
+    /// Commitment to the blob data: for a chunk, the [`blob_data_commitment`] of its L2 txs; for
+    /// a batch, the digest of the bytes of its blob
+    BlobDataCommitment,
+}
+
+/// How the field of a batch is derived from the same field of its chunks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PiBatchSource {
+    /// All chunks and the batch share a same value.
+    Same,
+    /// The value of the first chunk.
+    First,
+    /// The value of the last chunk.
+    Last,
+    /// The hash of the values of all (non padding) chunks, as laid out by a [`PiBatchLayout`].
+    Hash,
+    /// Computed from the blob of the batch, or zero for a batch without a blob.
+    Blob,
+}
+
+/// Layout of the [`PiBatchSource::Hash`] fields of a batch.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PiBatchLayout {
+    /// keccak(chunk_0 || ... || chunk_k-1), of a batch aggregated from its chunks at once.
+    #[default]
+    Flat,
+    /// Of a batch aggregated by a tree of aggregations of `arity` nodes at a time, more than one
+    /// level deep. Each node is the `Flat` hash of its children, e.g., for an arity of 2,
+    /// keccak(keccak(chunk_0 || chunk_1) || keccak(chunk_2)).
+    Tree {
+        /// Number of nodes aggregated at a time.
+        arity: usize,
+    },
+}
+
+impl PiField {
+    /// Number of bytes of the field in the preimage.
+    pub const fn num_bytes(&self) -> usize {
+        match self {
+            Self::ChainId => 8,
+            _ => 32,
+        }
+    }
+
+    /// How the field of a batch is derived from its chunks.
+    pub const fn batch_source(&self) -> PiBatchSource {
+        match self {
+            Self::ChainId => PiBatchSource::Same,
+            Self::PrevStateRoot => PiBatchSource::First,
+            Self::PostStateRoot | Self::WithdrawRoot | Self::L1MsgQueueHash => PiBatchSource::Last,
+            Self::DataHash => PiBatchSource::Hash,
+            Self::BlobDataCommitment => PiBatchSource::Blob,
+        }
+    }
+}
+
+/// Version of the layout of the public input hash preimage.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PiSchema {
+    /// chain_id || prev_state_root || post_state_root || withdraw_root || data_hash
+    #[default]
+    V1,
+    /// chain_id || prev_state_root || post_state_root || withdraw_root || data_hash ||
+    /// l1_msg_queue_hash || blob_data_commitment
+    V2,
+}
+
+/// Schema of the chunks built from new blocks.
+pub const PI_SCHEMA: PiSchema = PiSchema::V2;
+
+impl PiSchema {
+    /// All the schemas, in ascending version.
+    pub const ALL: [PiSchema; 2] = [PiSchema::V1, PiSchema::V2];
+
+    /// Fields of the preimage, in order.
+    pub const fn fields(&self) -> &'static [PiField] {
+        match self {
+            Self::V1 => &[
+                PiField::ChainId,
+                PiField::PrevStateRoot,
+                PiField::PostStateRoot,
+                PiField::WithdrawRoot,
+                PiField::DataHash,
+            ],
+            Self::V2 => &[
+                PiField::ChainId,
+                PiField::PrevStateRoot,
+                PiField::PostStateRoot,
+                PiField::WithdrawRoot,
+                PiField::DataHash,
+                PiField::L1MsgQueueHash,
+                PiField::BlobDataCommitment,
+            ],
+        }
+    }
+
+    /// Byte offset of `field` in the preimage, or `None` if the schema does not have the field.
+    pub fn offset(&self, field: PiField) -> Option<usize> {
+        let mut offset = 0;
+        for f in self.fields() {
+            if *f == field {
+                return Some(offset);
+            }
+            offset += f.num_bytes();
+        }
+        None
+    }
+
+    /// Byte offset of `field` in the preimage.
+    ///
+    /// Panics if the schema does not have the field.
+    pub fn index(&self, field: PiField) -> usize {
+        self.offset(field)
+            .unwrap_or_else(|| panic!("{field:?} is not a field of pi schema {self:?}"))
+    }
+
+    /// Length in bytes of the preimage.
+    pub fn preimage_len(&self) -> usize {
+        self.fields().iter().map(PiField::num_bytes).sum()
+    }
+
+    /// Number of keccak-f rounds to hash the preimage, including the padding.
+    pub fn num_keccak_rounds(&self) -> usize {
+        self.preimage_len() / KECCAK_RATE + 1
+    }
+
+    /// Build the preimage from the values of its fields.
+    pub fn preimage(&self, values: &PiValues) -> Vec<u8> {
+        self.fields()
+            .iter()
+            .flat_map(|field| values.field_bytes(*field))
+            .collect()
+    }
+}
+
+impl fmt::Display for PiSchema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::V1 => write!(f, "v1"),
+            Self::V2 => write!(f, "v2"),
+        }
+    }
+}
+
+impl fmt::Display for PiBatchLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Flat => write!(f, "flat"),
+            Self::Tree { arity } => write!(f, "tree-{arity}"),
+        }
+    }
+}
+
+/// Values of all the fields a [`PiSchema`] may have. The fields that are not in the schema are
+/// ignored when building its preimage.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PiValues {
+    /// Chain id
+    pub chain_id: u64,
+    /// State root before the chunk
+    pub prev_state_root: H256,
+    /// State root after the chunk
+    pub post_state_root: H256,
+    /// Withdraw root after the chunk
+    pub withdraw_root: H256,
+    /// Hash of the chunk data
+    pub data_hash: H256,
+    /// Rolling hash of the L1 message queue after the chunk
+    pub l1_msg_queue_hash: H256,
+    /// Commitment to the blob data
+    pub blob_data_commitment: H256,
+}
+
+impl PiValues {
+    /// Big endian bytes of `field`.
+    pub fn field_bytes(&self, field: PiField) -> Vec<u8> {
+        match field {
+            PiField::ChainId => self.chain_id.to_be_bytes().to_vec(),
+            PiField::PrevStateRoot => self.prev_state_root.as_bytes().to_vec(),
+            PiField::PostStateRoot => self.post_state_root.as_bytes().to_vec(),
+            PiField::WithdrawRoot => self.withdraw_root.as_bytes().to_vec(),
+            PiField::DataHash => self.data_hash.as_bytes().to_vec(),
+            PiField::L1MsgQueueHash => self.l1_msg_queue_hash.as_bytes().to_vec(),
+            PiField::BlobDataCommitment => self.blob_data_commitment.as_bytes().to_vec(),
+        }
+    }
+}
+
+/// Preimage of the [`blob_data_commitment`] of a chunk: the concatenated hashes of its L2 txs.
+pub fn blob_data_commitment_preimage(l2_tx_hashes: impl IntoIterator<Item = H256>) -> Vec<u8> {
+    l2_tx_hashes
+        .into_iter()
+        .flat_map(|hash| hash.to_fixed_bytes())
+        .collect()
+}
+
+/// Blob data commitment of a chunk, i.e., keccak(tx_hash_0 || ... || tx_hash_n-1) of its L2 txs
+/// in order. A tx hash is the keccak of the signed tx, i.e., of the bytes the tx takes in the
+/// blob of its batch, so the aggregator can check the blob against the commitments of the chunks.
+pub fn blob_data_commitment(l2_tx_hashes: impl IntoIterator<Item = H256>) -> H256 {
+    H256(keccak256(blob_data_commitment_preimage(l2_tx_hashes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pi_schema_layout() {
+        let v1 = PiSchema::V1;
+        assert_eq!(v1.index(PiField::ChainId), 0);
+        assert_eq!(v1.index(PiField::PrevStateRoot), 8);
+        assert_eq!(v1.index(PiField::PostStateRoot), 40);
+        assert_eq!(v1.index(PiField::WithdrawRoot), 72);
+        assert_eq!(v1.index(PiField::DataHash), 104);
+        assert_eq!(v1.offset(PiField::L1MsgQueueHash), None);
+        assert_eq!(v1.preimage_len(), 136);
+        assert_eq!(v1.num_keccak_rounds(), 2);
+
+        // V2 extends V1, so the offsets of the fields of V1 are unchanged
+        let v2 = PiSchema::V2;
+        for field in v1.fields() {
+            assert_eq!(v2.offset(*field), v1.offset(*field));
+        }
+        assert_eq!(v2.index(PiField::L1MsgQueueHash), 136);
+        assert_eq!(v2.index(PiField::BlobDataCommitment), 168);
+        assert_eq!(v2.preimage_len(), 200);
+        assert_eq!(v2.num_keccak_rounds(), 2);
+    }
+
+    #[test]
+    fn test_pi_schema_preimage() {
+        let values = PiValues {
+            chain_id: 0x0102,
+            prev_state_root: H256::repeat_byte(1),
+            post_state_root: H256::repeat_byte(2),
+            withdraw_root: H256::repeat_byte(3),
+            data_hash: H256::repeat_byte(4),
+            l1_msg_queue_hash: H256::repeat_byte(5),
+            blob_data_commitment: H256::repeat_byte(6),
+        };
+
+        for schema in PiSchema::ALL {
+            let preimage = schema.preimage(&values);
+            assert_eq!(preimage.len(), schema.preimage_len());
+            for field in schema.fields() {
+                let offset = schema.index(*field);
+                assert_eq!(
+                    &preimage[offset..offset + field.num_bytes()],
+                    values.field_bytes(*field).as_slice()
+                );
+            }
+        }
+    }
+}
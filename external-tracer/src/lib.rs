@@ -1,4 +1,24 @@
 //! This module generates traces by connecting to an external tracer
+//!
+//! The tracer behind [`trace`] is picked by features:
+//! - `geth` (default) runs the geth library built by `geth-utils`.
+//! - `rust-tracer` runs the revm interpreter, so no Go toolchain is needed. It is only used by
+//!   [`trace`] and [`l2trace`] when `geth` is disabled.
+//! - `tracer-diff` enables both and checks that every trace of the revm interpreter matches the one
+//!   of geth, but for the traces of the Cancun opcodes geth-utils does not know yet, which come
+//!   from the revm interpreter alone, see [`rust_tracer`].
+//!
+//! When [`remote::REMOTE_TRACER_URL`] is set, [`trace`] sends the config to the node at that url
+//! with a [`RemoteTracer`] instead.
+
+#[cfg(not(any(feature = "geth", feature = "rust-tracer")))]
+compile_error!("external-tracer needs the `geth` or the `rust-tracer` feature");
+
+pub mod remote;
+#[cfg(feature = "rust-tracer")]
+pub mod rust_tracer;
+
+pub use remote::RemoteTracer;
 
 #[cfg(feature = "scroll")]
 use eth_types::l2_types::BlockTrace;
@@ -96,9 +116,27 @@ impl ChainConfig {
     }
 }
 
-/// Creates a trace for the specified config
-#[cfg(not(feature = "scroll"))]
+/// Creates a trace for the specified config, with the node at [`remote::REMOTE_TRACER_URL`] if
+/// it is set, or else with the tracer built into the process.
 pub fn trace(config: &TraceConfig) -> Result<Vec<GethExecTrace>, Error> {
+    match RemoteTracer::from_env()? {
+        Some(tracer) => tracer.trace(config),
+        None => local_trace(config),
+    }
+}
+
+#[cfg(all(
+    feature = "rust-tracer",
+    not(feature = "geth"),
+    not(feature = "scroll")
+))]
+use rust_tracer::trace as local_trace;
+
+#[cfg(all(feature = "rust-tracer", feature = "scroll", not(feature = "geth")))]
+pub use rust_tracer::l2trace;
+
+#[cfg(all(feature = "geth", not(feature = "scroll")))]
+fn local_trace(config: &TraceConfig) -> Result<Vec<GethExecTrace>, Error> {
     let trace_config = &serde_json::to_string_pretty(&config).unwrap();
     log::trace!("trace config: {}", trace_config);
     // Get the trace
@@ -109,11 +147,72 @@ pub fn trace(config: &TraceConfig) -> Result<Vec<GethExecTrace>, Error> {
     log::trace!("trace: {}", trace_string);
 
     let trace = serde_json::from_str(&trace_string).map_err(Error::SerdeError)?;
+    #[cfg(feature = "tracer-diff")]
+    {
+        let rust_trace = rust_tracer::trace(config);
+        if has_cancun_opcodes(&trace) {
+            log::warn!("geth-utils does not know the Cancun opcodes, use the trace of revm");
+            return rust_trace;
+        }
+        assert_same_traces(&trace, &rust_trace);
+    }
     Ok(trace)
 }
 
+/// Returns whether one of the traces of geth runs an opcode of Cancun, which
+/// geth-utils traces as an undefined one.
+#[cfg(feature = "tracer-diff")]
+fn has_cancun_opcodes(traces: &[GethExecTrace]) -> bool {
+    use eth_types::evm_types::OpcodeId;
+
+    let cancun_opcodes = [
+        OpcodeId::TLOAD,
+        OpcodeId::TSTORE,
+        OpcodeId::MCOPY,
+        OpcodeId::BLOBHASH,
+        OpcodeId::BLOBBASEFEE,
+    ]
+    .map(|op| op.as_u8());
+    traces
+        .iter()
+        .flat_map(|trace| &trace.struct_logs)
+        .any(|step| cancun_opcodes.contains(&step.op.as_u8()))
+}
+
+/// Panics with the first difference between the traces of geth and of the
+/// revm interpreter.
+#[cfg(feature = "tracer-diff")]
+fn assert_same_traces(geth: &[GethExecTrace], rust: &Result<Vec<GethExecTrace>, Error>) {
+    let rust = match rust {
+        Ok(rust) => rust,
+        Err(e) => panic!("rust tracer failed where geth succeeded: {e:?}"),
+    };
+    assert_eq!(geth.len(), rust.len(), "number of traces differs");
+    for (tx_idx, (geth, rust)) in geth.iter().zip(rust).enumerate() {
+        for (step_idx, (geth_step, rust_step)) in
+            geth.struct_logs.iter().zip(&rust.struct_logs).enumerate()
+        {
+            assert_eq!(
+                geth_step, rust_step,
+                "tx {tx_idx} step {step_idx} differs (left: geth, right: rust)"
+            );
+        }
+        assert_eq!(
+            geth.struct_logs.len(),
+            rust.struct_logs.len(),
+            "tx {tx_idx} number of steps differs"
+        );
+        assert_eq!(geth.gas, rust.gas, "tx {tx_idx} gas differs");
+        assert_eq!(geth.failed, rust.failed, "tx {tx_idx} status differs");
+        assert_eq!(
+            geth.return_value, rust.return_value,
+            "tx {tx_idx} return value differs"
+        );
+    }
+}
+
 /// Creates a l2-trace for the specified config
-#[cfg(feature = "scroll")]
+#[cfg(all(feature = "geth", feature = "scroll"))]
 pub fn l2trace(config: &TraceConfig) -> Result<BlockTrace, Error> {
     let mut l2_config = config.clone();
     if let Some(chain_config) = l2_config.chain_config.as_mut() {
@@ -139,7 +238,7 @@ pub fn l2trace(config: &TraceConfig) -> Result<BlockTrace, Error> {
 }
 
 #[cfg(feature = "scroll")]
-pub fn trace(config: &TraceConfig) -> Result<Vec<GethExecTrace>, Error> {
+fn local_trace(config: &TraceConfig) -> Result<Vec<GethExecTrace>, Error> {
     let block_trace = l2trace(config)?;
 
     Ok(block_trace
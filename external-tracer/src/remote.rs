@@ -0,0 +1,969 @@
+//! Tracer backend that sends the trace request to a running node over JSON-RPC
+//! instead of the geth library linked into the process.
+
+use crate::{LoggerConfig, TraceConfig};
+use eth_types::{
+    evm_types::OpcodeId,
+    geth_types::{Account, BlockConstants, Transaction},
+    AccessList, Address, Block, Bytes, Error, GethExecTrace, GethPrestateTrace,
+    ResultGethExecTraces, ResultGethPrestateTraces, Word, H256, U64,
+};
+use ethers_providers::{Http, JsonRpcClient};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, HashMap},
+    env,
+    str::FromStr,
+    sync::{mpsc, Arc, Mutex},
+};
+use tokio::runtime::{Handle, Runtime};
+
+/// Environment variable with the url of a node for [`crate::trace`] to trace with, instead of the
+/// tracer built into the process.
+pub const REMOTE_TRACER_URL: &str = "REMOTE_TRACER_URL";
+
+/// Tracer which drives `debug_traceCall` and `debug_traceBlockByNumber` on a
+/// node reachable over HTTP.
+///
+/// A [`TraceConfig`] is traced against its own pre-state: its accounts
+/// override the ones of the node, and every other account its transactions
+/// touch is overridden as empty. The transactions are traced in order, each on
+/// top of the state left by the previous ones. `BLOCKHASH` reads the history
+/// of the node, so the config is traced on top of the parent of its block, and
+/// a trace whose `BLOCKHASH` results differ from the history hashes of the
+/// config is rejected.
+#[derive(Debug, Clone)]
+pub struct RemoteTracer {
+    provider: Http,
+    runtime: Arc<TracerRuntime>,
+}
+
+impl RemoteTracer {
+    /// Create a tracer for the node at `url`.
+    pub fn new(url: &str) -> Result<Self, Error> {
+        let provider = Http::from_str(url)
+            .map_err(|e| Error::TracingError(format!("invalid tracer url {url}: {e}")))?;
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("remote-tracer")
+            .enable_all()
+            .build()
+            .map_err(|e| Error::TracingError(e.to_string()))?;
+        Ok(Self {
+            provider,
+            runtime: Arc::new(TracerRuntime(Some(runtime))),
+        })
+    }
+
+    /// The tracer of the node at the url of [`REMOTE_TRACER_URL`], if it is set. The tracer is
+    /// created once and shared by the later calls.
+    pub fn from_env() -> Result<Option<Self>, Error> {
+        static TRACER: Mutex<Option<(String, RemoteTracer)>> = Mutex::new(None);
+
+        let url = match env::var(REMOTE_TRACER_URL) {
+            Ok(url) if !url.is_empty() => url,
+            _ => return Ok(None),
+        };
+        let mut cached = TRACER.lock().unwrap();
+        if let Some((cached_url, tracer)) = cached.as_ref() {
+            if *cached_url == url {
+                return Ok(Some(tracer.clone()));
+            }
+        }
+        let tracer = Self::new(&url)?;
+        *cached = Some((url, tracer.clone()));
+        Ok(Some(tracer))
+    }
+
+    /// Trace the transactions of `config` with `debug_traceCall`, one after the
+    /// other.
+    pub fn trace(&self, config: &TraceConfig) -> Result<Vec<GethExecTrace>, Error> {
+        self.check_chain_id(config.chain_id)?;
+
+        // The node answers BLOCKHASH from the ancestors of the block the call is
+        // made on top of.
+        let number = config.block_constants.number.as_u64();
+        let parent = if config.history_hashes.is_empty() || number == 0 {
+            json!("latest")
+        } else {
+            json!(U64::from(number - 1))
+        };
+        let block_overrides = BlockOverrides::from(&config.block_constants);
+        // The stack holds the results of BLOCKHASH to check.
+        let logger_config = LoggerConfig {
+            disable_stack: false,
+            ..config.logger_config.clone()
+        };
+
+        // the pre-state of the next transaction
+        let mut state = config.accounts.clone();
+        let mut traces = Vec::with_capacity(config.transactions.len());
+        for tx in config.transactions.iter() {
+            let call = CallArgs::from(tx);
+            let trace_call = |tracer, state: &BTreeMap<Address, Account>| {
+                let params = (
+                    &call,
+                    &parent,
+                    TraceCallConfig::new(tracer, state, &block_overrides),
+                );
+                self.request::<_, Value>("debug_traceCall", params)
+            };
+
+            // Override the accounts the node has but the state has not as empty, until the
+            // transaction touches no other account.
+            loop {
+                let prestate: HashMap<Address, GethPrestateTrace> =
+                    from_value(trace_call(Tracer::Prestate { diff_mode: false }, &state)?)?;
+                let unknown = prestate
+                    .into_iter()
+                    .filter(|(address, account)| {
+                        !state.contains_key(address) && !is_empty_prestate(account)
+                    })
+                    .map(|(address, _)| address)
+                    .collect::<Vec<_>>();
+                if unknown.is_empty() {
+                    break;
+                }
+                for address in unknown {
+                    log::debug!("remote tracer: override {address:?} as an empty account");
+                    state.insert(
+                        address,
+                        Account {
+                            address,
+                            ..Default::default()
+                        },
+                    );
+                }
+            }
+
+            let mut trace: GethExecTrace =
+                from_value(trace_call(Tracer::StructLogger(&logger_config), &state)?)?;
+            check_block_hashes(&trace, config)?;
+            if config.logger_config.disable_stack {
+                for step in trace.struct_logs.iter_mut() {
+                    step.stack = Default::default();
+                }
+            }
+            traces.push(trace);
+
+            let diff: StateDiff =
+                from_value(trace_call(Tracer::Prestate { diff_mode: true }, &state)?)?;
+            diff.apply(&mut state);
+        }
+
+        Ok(traces)
+    }
+
+    /// Trace the transactions of `config` with `debug_traceBlockByNumber`, as
+    /// the block of the node at the number of the block constants of `config`.
+    ///
+    /// The block of the node must be the one of `config`: its header must match
+    /// the block constants and the history hashes, its transactions the ones of
+    /// `config`, and the accounts they touch must have the state of `config`.
+    pub fn trace_block_by_number(&self, config: &TraceConfig) -> Result<Vec<GethExecTrace>, Error> {
+        self.check_chain_id(config.chain_id)?;
+
+        let number = U64::from(config.block_constants.number.as_u64());
+        let block: Option<Block<H256>> = self.request("eth_getBlockByNumber", (number, false))?;
+        let block =
+            block.ok_or_else(|| Error::TracingError(format!("node has no block {number}")))?;
+        check_block(&block, config)?;
+
+        let prestates: ResultGethPrestateTraces = self.request(
+            "debug_traceBlockByNumber",
+            (number, json!({ "tracer": "prestateTracer" })),
+        )?;
+        check_prestates(prestates, config)?;
+
+        let resp: ResultGethExecTraces =
+            self.request("debug_traceBlockByNumber", (number, &config.logger_config))?;
+        Ok(resp.0.into_iter().map(|trace| trace.result).collect())
+    }
+
+    fn check_chain_id(&self, expected: u64) -> Result<(), Error> {
+        let chain_id: U64 = self.request("eth_chainId", ())?;
+        if chain_id.as_u64() != expected {
+            return Err(Error::TracingError(format!(
+                "node chain id {chain_id} does not match trace config chain id {expected}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Send a request on the runtime of the tracer and wait for its result, so
+    /// that it may be called from any thread, including the ones of another
+    /// runtime.
+    fn request<T, R>(&self, method: &str, params: T) -> Result<R, Error>
+    where
+        T: Serialize,
+        R: DeserializeOwned,
+    {
+        let params = serde_json::to_value(params).map_err(Error::SerdeError)?;
+        let (provider, method_name) = (self.provider.clone(), method.to_string());
+        let (sender, receiver) = mpsc::channel();
+        self.runtime.handle().spawn(async move {
+            let result = provider.request::<_, Value>(&method_name, params).await;
+            // the receiver only hangs up on a panic of the caller
+            let _ = sender.send(result);
+        });
+
+        let result = receiver
+            .recv()
+            .map_err(|e| Error::TracingError(format!("{method}: {e}")))?
+            .map_err(|e| Error::TracingError(format!("{method}: {e}")))?;
+        from_value(result)
+    }
+}
+
+/// The runtime the requests of a [`RemoteTracer`] run on. It is shut down in
+/// the background on drop, which unlike dropping a runtime is also allowed
+/// within another runtime.
+#[derive(Debug)]
+struct TracerRuntime(Option<Runtime>);
+
+impl TracerRuntime {
+    fn handle(&self) -> &Handle {
+        // only taken on drop
+        self.0.as_ref().unwrap().handle()
+    }
+}
+
+impl Drop for TracerRuntime {
+    fn drop(&mut self) {
+        if let Some(runtime) = self.0.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+fn from_value<R: DeserializeOwned>(value: Value) -> Result<R, Error> {
+    serde_json::from_value(value).map_err(Error::SerdeError)
+}
+
+fn is_empty_prestate(account: &GethPrestateTrace) -> bool {
+    account.balance.unwrap_or_default().is_zero()
+        && account.nonce.unwrap_or_default() == 0
+        && account.code.as_ref().map_or(true, |code| code.is_empty())
+        && account
+            .storage
+            .as_ref()
+            .map_or(true, |storage| storage.values().all(Word::is_zero))
+}
+
+/// The hash of block `number` seen by BLOCKHASH in the block of `config`.
+fn history_hash(config: &TraceConfig, number: Word) -> Word {
+    let current = Word::from(config.block_constants.number.as_u64());
+    let num_hashes = Word::from(config.history_hashes.len());
+    if number < current && current - number <= num_hashes.min(Word::from(256)) {
+        config.history_hashes[(num_hashes - (current - number)).as_usize()]
+    } else {
+        Word::zero()
+    }
+}
+
+/// Check that the results of BLOCKHASH in `trace` are the history hashes of
+/// `config`.
+fn check_block_hashes(trace: &GethExecTrace, config: &TraceConfig) -> Result<(), Error> {
+    for (idx, step) in trace.struct_logs.iter().enumerate() {
+        if step.op != OpcodeId::BLOCKHASH || step.error.is_some() {
+            continue;
+        }
+        // the result is on top of the stack of the next step
+        let result = match trace.struct_logs.get(idx + 1) {
+            Some(next) if next.depth == step.depth => next.stack.last()?,
+            _ => continue,
+        };
+        let number = step.stack.last()?;
+        let expected = history_hash(config, number);
+        if result != expected {
+            return Err(Error::TracingError(format!(
+                "BLOCKHASH of block {number} is {result:#x} on the node, but {expected:#x} in the history hashes"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Check that `block` of the node is the block of `config`.
+fn check_block(block: &Block<H256>, config: &TraceConfig) -> Result<(), Error> {
+    let constants = &config.block_constants;
+    let mismatch = |field: &str| {
+        Err(Error::TracingError(format!(
+            "{field} of block {} differs between the node and the trace config",
+            constants.number
+        )))
+    };
+
+    if block.author != Some(constants.coinbase) {
+        return mismatch("coinbase");
+    }
+    if block.timestamp != constants.timestamp {
+        return mismatch("timestamp");
+    }
+    if block.gas_limit != constants.gas_limit {
+        return mismatch("gas limit");
+    }
+    if block.base_fee_per_gas.unwrap_or_default() != constants.base_fee {
+        return mismatch("base fee");
+    }
+    // DIFFICULTY returns the random value after the merge.
+    if block.difficulty != constants.difficulty
+        && block.mix_hash != Some(H256::from_uint(&constants.difficulty))
+    {
+        return mismatch("difficulty");
+    }
+    if let Some(parent_hash) = config.history_hashes.last() {
+        if block.parent_hash != H256::from_uint(parent_hash) {
+            return mismatch("parent hash");
+        }
+    }
+    if block.transactions.len() != config.transactions.len()
+        || block
+            .transactions
+            .iter()
+            .zip(config.transactions.iter())
+            .any(|(hash, tx)| !tx.hash.is_zero() && *hash != tx.hash)
+    {
+        return mismatch("transactions");
+    }
+    Ok(())
+}
+
+/// Check that the accounts touched by the transactions of a block, as first
+/// touched, have the state of `config`.
+fn check_prestates(prestates: ResultGethPrestateTraces, config: &TraceConfig) -> Result<(), Error> {
+    let empty = Account::default();
+    let mut seen = BTreeMap::new();
+    for prestate in prestates.0 {
+        for (address, account) in prestate.result {
+            seen.entry(address).or_insert(account);
+        }
+    }
+
+    for (address, prestate) in seen {
+        let account = config.accounts.get(&address).unwrap_or(&empty);
+        let same = prestate.balance.unwrap_or_default() == account.balance
+            && Word::from(prestate.nonce.unwrap_or_default()) == account.nonce
+            && prestate.code.unwrap_or_default() == account.code
+            && prestate
+                .storage
+                .unwrap_or_default()
+                .iter()
+                .all(|(slot, value)| {
+                    account.storage.get(slot).copied().unwrap_or_default() == *value
+                });
+        if !same {
+            return Err(Error::TracingError(format!(
+                "state of {address:?} differs between the node and the trace config"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Corresponds to `TransactionArgs` in `go-ethereum/internal/ethapi`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CallArgs<'a> {
+    from: Address,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    to: Option<Address>,
+    gas: U64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gas_price: Option<Word>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_fee_per_gas: Option<Word>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_priority_fee_per_gas: Option<Word>,
+    value: Word,
+    nonce: U64,
+    input: &'a Bytes,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    access_list: Option<&'a AccessList>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_fee_per_blob_gas: Option<Word>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    blob_versioned_hashes: Option<&'a [H256]>,
+}
+
+impl<'a> From<&'a Transaction> for CallArgs<'a> {
+    fn from(tx: &'a Transaction) -> Self {
+        // geth rejects a call that sets both the legacy and the EIP-1559 fees.
+        let is_dynamic_fee = !tx.gas_fee_cap.is_zero();
+        let is_blob = !tx.blob_versioned_hashes.is_empty();
+        Self {
+            from: tx.from,
+            to: tx.to,
+            gas: U64::from(tx.gas_limit.as_u64()),
+            gas_price: (!is_dynamic_fee).then_some(tx.gas_price),
+            max_fee_per_gas: is_dynamic_fee.then_some(tx.gas_fee_cap),
+            max_priority_fee_per_gas: is_dynamic_fee.then_some(tx.gas_tip_cap),
+            value: tx.value,
+            nonce: U64::from(tx.nonce.as_u64()),
+            input: &tx.call_data,
+            access_list: tx.access_list.as_ref(),
+            max_fee_per_blob_gas: is_blob.then_some(tx.max_fee_per_blob_gas),
+            blob_versioned_hashes: is_blob.then_some(tx.blob_versioned_hashes.as_slice()),
+        }
+    }
+}
+
+/// The tracer of a `debug_traceCall`.
+#[derive(Clone, Copy, Debug)]
+enum Tracer<'a> {
+    /// The struct logger, which returns a [`GethExecTrace`].
+    StructLogger(&'a LoggerConfig),
+    /// The `prestateTracer`, which returns the accounts touched by the call
+    /// before it is executed, or the ones changed by the call before and after
+    /// it is executed in diff mode.
+    Prestate { diff_mode: bool },
+}
+
+/// Corresponds to `TraceCallConfig` in `go-ethereum/eth/tracers`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TraceCallConfig<'a> {
+    #[serde(flatten)]
+    logger_config: Option<&'a LoggerConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tracer: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tracer_config: Option<Value>,
+    state_overrides: BTreeMap<Address, AccountOverride<'a>>,
+    block_overrides: &'a BlockOverrides,
+}
+
+impl<'a> TraceCallConfig<'a> {
+    fn new(
+        tracer: Tracer<'a>,
+        state: &'a BTreeMap<Address, Account>,
+        block_overrides: &'a BlockOverrides,
+    ) -> Self {
+        let (logger_config, tracer, tracer_config) = match tracer {
+            Tracer::StructLogger(logger_config) => (Some(logger_config), None, None),
+            Tracer::Prestate { diff_mode } => (
+                None,
+                Some("prestateTracer"),
+                Some(json!({ "diffMode": diff_mode })),
+            ),
+        };
+        Self {
+            logger_config,
+            tracer,
+            tracer_config,
+            state_overrides: state
+                .iter()
+                .map(|(address, account)| (*address, AccountOverride::from(account)))
+                .collect(),
+            block_overrides,
+        }
+    }
+}
+
+/// Corresponds to `OverrideAccount` in `go-ethereum/internal/ethapi`.
+#[derive(Debug, Serialize)]
+struct AccountOverride<'a> {
+    nonce: U64,
+    code: &'a Bytes,
+    balance: Word,
+    /// Replaces the whole storage of the account.
+    state: BTreeMap<H256, H256>,
+}
+
+impl<'a> From<&'a Account> for AccountOverride<'a> {
+    fn from(account: &'a Account) -> Self {
+        Self {
+            nonce: U64::from(account.nonce.as_u64()),
+            code: &account.code,
+            balance: account.balance,
+            state: account
+                .storage
+                .iter()
+                .map(|(key, value)| (H256::from_uint(key), H256::from_uint(value)))
+                .collect(),
+        }
+    }
+}
+
+/// Corresponds to `BlockOverrides` in `go-ethereum/internal/ethapi`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BlockOverrides {
+    number: Word,
+    difficulty: Word,
+    time: U64,
+    gas_limit: U64,
+    coinbase: Address,
+    random: H256,
+    base_fee: Word,
+}
+
+impl From<&BlockConstants> for BlockOverrides {
+    fn from(block: &BlockConstants) -> Self {
+        Self {
+            number: Word::from(block.number.as_u64()),
+            difficulty: block.difficulty,
+            time: U64::from(block.timestamp.as_u64()),
+            gas_limit: U64::from(block.gas_limit.as_u64()),
+            coinbase: block.coinbase,
+            // DIFFICULTY returns the random value after the merge.
+            random: H256::from_uint(&block.difficulty),
+            base_fee: block.base_fee,
+        }
+    }
+}
+
+/// Result of the `prestateTracer` in diff mode: the accounts changed by a call,
+/// before and after it is executed.
+#[derive(Debug, Deserialize)]
+struct StateDiff {
+    pre: HashMap<Address, GethPrestateTrace>,
+    post: HashMap<Address, GethPrestateTrace>,
+}
+
+impl StateDiff {
+    /// Apply the changes to `state`. The fields and the slots that did not
+    /// change are left out of the diff, while a changed account missing after
+    /// the call was deleted, and a changed slot missing after the call was
+    /// cleared.
+    fn apply(self, state: &mut BTreeMap<Address, Account>) {
+        let Self { pre, post } = self;
+        let empty = |address| Account {
+            address,
+            ..Default::default()
+        };
+
+        for (address, pre_account) in pre {
+            let account = state.entry(address).or_insert_with(|| empty(address));
+            match post.get(&address) {
+                None => *account = empty(address),
+                Some(post_account) => {
+                    let post_storage = post_account.storage.as_ref();
+                    for slot in pre_account
+                        .storage
+                        .iter()
+                        .flat_map(|storage| storage.keys())
+                    {
+                        if !post_storage.map_or(false, |storage| storage.contains_key(slot)) {
+                            account.storage.remove(slot);
+                        }
+                    }
+                }
+            }
+        }
+
+        for (address, post_account) in post {
+            let account = state.entry(address).or_insert_with(|| empty(address));
+            if let Some(balance) = post_account.balance {
+                account.balance = balance;
+            }
+            if let Some(nonce) = post_account.nonce {
+                account.nonce = Word::from(nonce);
+            }
+            if let Some(code) = post_account.code {
+                account.code = code;
+            }
+            for (slot, value) in post_account.storage.into_iter().flatten() {
+                if value.is_zero() {
+                    account.storage.remove(&slot);
+                } else {
+                    account.storage.insert(slot, value);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eth_types::{address, evm_types::Gas};
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::{TcpListener, TcpStream},
+        thread,
+    };
+
+    /// A JSON-RPC server that answers each request with a canned result and
+    /// records the requests it receives. The results are keyed by the method,
+    /// followed by the tracer and `/diff` for the tracers in diff mode, like
+    /// `debug_traceCall/prestateTracer/diff`.
+    struct StubServer {
+        url: String,
+        requests: Arc<Mutex<Vec<Value>>>,
+    }
+
+    impl StubServer {
+        fn start(results: Vec<(&'static str, Value)>) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            let requests = Arc::new(Mutex::new(Vec::new()));
+            let results: BTreeMap<_, _> = results.into_iter().collect();
+
+            let recorded = requests.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let (results, recorded) = (results.clone(), recorded.clone());
+                    thread::spawn(move || serve(stream.unwrap(), &results, &recorded));
+                }
+            });
+
+            Self { url, requests }
+        }
+
+        fn requests(&self) -> Vec<Value> {
+            self.requests.lock().unwrap().clone()
+        }
+
+        /// The struct logger calls of `debug_traceCall`.
+        fn trace_calls(&self) -> Vec<Value> {
+            self.requests()
+                .into_iter()
+                .filter(|request| key(request) == "debug_traceCall")
+                .collect()
+        }
+    }
+
+    fn key(request: &Value) -> String {
+        let mut key = request["method"].as_str().unwrap().to_string();
+        let params = request["params"].as_array().unwrap();
+        if let Some(tracer) = params.last().and_then(|config| config["tracer"].as_str()) {
+            key = format!("{key}/{tracer}");
+            if params.last().unwrap()["tracerConfig"]["diffMode"] == true {
+                key.push_str("/diff");
+            }
+        }
+        key
+    }
+
+    fn serve(stream: TcpStream, results: &BTreeMap<&str, Value>, recorded: &Mutex<Vec<Value>>) {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut writer = stream;
+        loop {
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 {
+                    return;
+                }
+                let line = line.trim_end();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            let request: Value = serde_json::from_slice(&body).unwrap();
+            let result = &results[key(&request).as_str()];
+            let response =
+                json!({"jsonrpc": "2.0", "id": request["id"], "result": result}).to_string();
+            recorded.lock().unwrap().push(request);
+            write!(
+                writer,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                response.len(),
+                response
+            )
+            .unwrap();
+        }
+    }
+
+    const CONTRACT: &str = "0x00000000000000000000000000000000000000cc";
+    const SENDER: &str = "0x00000000000000000000000000000000000000fe";
+
+    fn exec_trace() -> Value {
+        json!({
+            "gas": 21000,
+            "failed": false,
+            "returnValue": "",
+            "structLogs": [],
+        })
+    }
+
+    /// A trace of `PUSH1 0xfe BLOCKHASH STOP` in block 0xcafe, where BLOCKHASH
+    /// returns `result`.
+    fn block_hash_trace(result: &str) -> Value {
+        let step = |pc, op, stack: Value| json!({"pc": pc, "op": op, "gas": 100, "gasCost": 3, "depth": 1, "stack": stack});
+        json!({
+            "gas": 21023,
+            "failed": false,
+            "returnValue": "",
+            "structLogs": [
+                step(0, "PUSH1", json!([])),
+                step(2, "BLOCKHASH", json!(["0xcafd"])),
+                step(3, "STOP", json!([result])),
+            ],
+        })
+    }
+
+    /// The pre-state of the accounts of [`trace_config`] as returned by the
+    /// `prestateTracer`.
+    fn prestate() -> Value {
+        json!({
+            CONTRACT: { "balance": "0x0", "code": "0x600100", "storage": {
+                "0x0000000000000000000000000000000000000000000000000000000000000001":
+                    "0x0000000000000000000000000000000000000000000000000000000000000002",
+            }},
+            SENDER: { "balance": "0x0" },
+        })
+    }
+
+    /// A diff where the contract overwrites slot 1, sets slot 3 and the sender
+    /// bumps its nonce.
+    fn state_diff() -> Value {
+        json!({
+            "pre": {
+                CONTRACT: { "balance": "0x0", "storage": {
+                    "0x0000000000000000000000000000000000000000000000000000000000000001":
+                        "0x0000000000000000000000000000000000000000000000000000000000000002",
+                }},
+                SENDER: { "balance": "0x0" },
+            },
+            "post": {
+                CONTRACT: { "storage": {
+                    "0x0000000000000000000000000000000000000000000000000000000000000003":
+                        "0x0000000000000000000000000000000000000000000000000000000000000004",
+                }},
+                SENDER: { "nonce": 1 },
+            },
+        })
+    }
+
+    fn trace_config() -> TraceConfig {
+        let contract = address!("0x00000000000000000000000000000000000000cc");
+        let sender = address!("0x00000000000000000000000000000000000000fe");
+        let account = Account {
+            address: contract,
+            code: Bytes::from(vec![0x60, 0x01, 0x00]),
+            storage: [(Word::from(1), Word::from(2))].into_iter().collect(),
+            ..Default::default()
+        };
+        TraceConfig {
+            chain_id: 1,
+            block_constants: BlockConstants {
+                number: U64::from(0xcafe),
+                gas_limit: Word::from(30_000_000),
+                ..Default::default()
+            },
+            accounts: [
+                (contract, account),
+                (
+                    sender,
+                    Account {
+                        address: sender,
+                        ..Default::default()
+                    },
+                ),
+            ]
+            .into_iter()
+            .collect(),
+            transactions: vec![Transaction {
+                from: sender,
+                to: Some(contract),
+                gas_limit: Word::from(1_000_000),
+                gas_price: Word::from(1),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn trace_call_results(trace: Value) -> Vec<(&'static str, Value)> {
+        vec![
+            ("eth_chainId", json!("0x1")),
+            ("debug_traceCall", trace),
+            ("debug_traceCall/prestateTracer", prestate()),
+            ("debug_traceCall/prestateTracer/diff", state_diff()),
+        ]
+    }
+
+    #[test]
+    fn trace_call() {
+        let server = StubServer::start(trace_call_results(exec_trace()));
+        let tracer = RemoteTracer::new(&server.url).unwrap();
+
+        let traces = tracer.trace(&trace_config()).unwrap();
+        assert_eq!(traces.len(), 1);
+        assert_eq!(traces[0].gas, Gas(21000));
+        assert!(traces[0].struct_logs.is_empty());
+
+        let requests = server.trace_calls();
+        let params = &requests[0]["params"];
+        assert_eq!(params[0]["to"], CONTRACT);
+        assert_eq!(params[0]["gas"], "0xf4240");
+        assert_eq!(params[0]["gasPrice"], "0x1");
+        assert!(params[0].get("maxFeePerGas").is_none());
+        assert_eq!(params[1], "latest");
+        assert_eq!(params[2]["EnableReturnData"], true);
+        assert_eq!(params[2]["blockOverrides"]["number"], "0xcafe");
+        let account = &params[2]["stateOverrides"][CONTRACT];
+        assert_eq!(account["code"], "0x600100");
+        assert_eq!(
+            account["state"]["0x0000000000000000000000000000000000000000000000000000000000000001"],
+            "0x0000000000000000000000000000000000000000000000000000000000000002"
+        );
+    }
+
+    #[test]
+    fn trace_call_chain_id_mismatch() {
+        let server = StubServer::start(vec![("eth_chainId", json!("0x5"))]);
+        let tracer = RemoteTracer::new(&server.url).unwrap();
+
+        assert!(matches!(
+            tracer.trace(&trace_config()),
+            Err(Error::TracingError(_))
+        ));
+    }
+
+    #[test]
+    fn trace_call_chains_transactions() {
+        let server = StubServer::start(trace_call_results(exec_trace()));
+        let tracer = RemoteTracer::new(&server.url).unwrap();
+        let mut config = trace_config();
+        config.transactions.push(config.transactions[0].clone());
+
+        let traces = tracer.trace(&config).unwrap();
+        assert_eq!(traces.len(), 2);
+
+        // The second transaction runs on the state left by the first one.
+        let requests = server.trace_calls();
+        let overrides = &requests[1]["params"][2]["stateOverrides"];
+        assert_eq!(overrides[SENDER]["nonce"], "0x1");
+        let storage = overrides[CONTRACT]["state"].as_object().unwrap();
+        assert_eq!(
+            storage.keys().collect::<Vec<_>>(),
+            vec!["0x0000000000000000000000000000000000000000000000000000000000000003"]
+        );
+        assert_eq!(
+            requests[0]["params"][2]["stateOverrides"][SENDER]["nonce"],
+            "0x0"
+        );
+    }
+
+    #[test]
+    fn trace_call_overrides_unknown_accounts() {
+        let other = "0x00000000000000000000000000000000000000dd";
+        let mut results = trace_call_results(exec_trace());
+        results[2].1[other] = json!({ "balance": "0x10", "code": "0x00" });
+        let server = StubServer::start(results);
+        let tracer = RemoteTracer::new(&server.url).unwrap();
+
+        tracer.trace(&trace_config()).unwrap();
+
+        let requests = server.trace_calls();
+        let account = &requests[0]["params"][2]["stateOverrides"][other];
+        assert_eq!(account["balance"], "0x0");
+        assert_eq!(account["code"], "0x");
+    }
+
+    #[test]
+    fn trace_call_checks_block_hashes() {
+        let mut config = trace_config();
+        config.history_hashes = vec![Word::from(0xaa), Word::from(0xbb)];
+
+        let server = StubServer::start(trace_call_results(block_hash_trace("0xbb")));
+        let tracer = RemoteTracer::new(&server.url).unwrap();
+        let traces = tracer.trace(&config).unwrap();
+        assert_eq!(traces[0].struct_logs.len(), 3);
+        // traced on top of the parent of the block for the node to have its history
+        assert_eq!(server.trace_calls()[0]["params"][1], "0xcafd");
+
+        let server = StubServer::start(trace_call_results(block_hash_trace("0xcc")));
+        let tracer = RemoteTracer::new(&server.url).unwrap();
+        assert!(matches!(tracer.trace(&config), Err(Error::TracingError(_))));
+    }
+
+    #[tokio::test]
+    async fn trace_call_within_runtime() {
+        let server = StubServer::start(trace_call_results(exec_trace()));
+        let tracer = RemoteTracer::new(&server.url).unwrap();
+
+        assert_eq!(tracer.trace(&trace_config()).unwrap().len(), 1);
+        drop(tracer);
+    }
+
+    fn block(config: &TraceConfig) -> Value {
+        json!({
+            "hash": H256::repeat_byte(1),
+            "parentHash": H256::zero(),
+            "sha3Uncles": H256::zero(),
+            "miner": config.block_constants.coinbase,
+            "stateRoot": H256::zero(),
+            "transactionsRoot": H256::zero(),
+            "receiptsRoot": H256::zero(),
+            "number": "0xcafe",
+            "gasUsed": "0x0",
+            "gasLimit": config.block_constants.gas_limit,
+            "extraData": "0x",
+            "logsBloom": null,
+            "timestamp": "0x0",
+            "difficulty": "0x0",
+            "totalDifficulty": "0x0",
+            "sealFields": [],
+            "uncles": [],
+            "transactions": [H256::repeat_byte(2)],
+            "size": "0x0",
+            "mixHash": H256::zero(),
+            "nonce": "0x0000000000000000",
+            "baseFeePerGas": "0x0",
+        })
+    }
+
+    fn trace_block_results(config: &TraceConfig, prestate: Value) -> Vec<(&'static str, Value)> {
+        vec![
+            ("eth_chainId", json!("0x1")),
+            ("eth_getBlockByNumber", block(config)),
+            (
+                "debug_traceBlockByNumber/prestateTracer",
+                json!([{ "txHash": H256::repeat_byte(2), "result": prestate }]),
+            ),
+            (
+                "debug_traceBlockByNumber",
+                json!([{ "result": exec_trace() }]),
+            ),
+        ]
+    }
+
+    #[test]
+    fn trace_block() {
+        let config = trace_config();
+        let server = StubServer::start(trace_block_results(&config, prestate()));
+        let tracer = RemoteTracer::new(&server.url).unwrap();
+
+        let traces = tracer.trace_block_by_number(&config).unwrap();
+        assert_eq!(traces.len(), 1);
+
+        let requests = server.requests();
+        let request = requests.last().unwrap();
+        assert_eq!(request["params"][0], "0xcafe");
+        assert_eq!(request["params"][1]["EnableReturnData"], true);
+    }
+
+    #[test]
+    fn trace_block_checks_config() {
+        let config = trace_config();
+        let mut other_state = prestate();
+        other_state[CONTRACT]["code"] = json!("0x00");
+        let server = StubServer::start(trace_block_results(&config, other_state));
+        let tracer = RemoteTracer::new(&server.url).unwrap();
+        assert!(matches!(
+            tracer.trace_block_by_number(&config),
+            Err(Error::TracingError(_))
+        ));
+
+        let mut config = trace_config();
+        config.block_constants.gas_limit = Word::from(1);
+        let server = StubServer::start(trace_block_results(&trace_config(), prestate()));
+        let tracer = RemoteTracer::new(&server.url).unwrap();
+        assert!(matches!(
+            tracer.trace_block_by_number(&config),
+            Err(Error::TracingError(_))
+        ));
+    }
+}
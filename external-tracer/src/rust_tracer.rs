@@ -0,0 +1,614 @@
+//! Tracer backend built on the revm interpreter, which needs no Go toolchain.
+//!
+//! The steps are recorded the way geth's `logger.StructLogger` records them,
+//! so that `bus-mapping` can consume either trace. A config on Shanghai runs on
+//! Cancun, so that the traces cover `TLOAD`, `TSTORE`, `MCOPY`, `BLOBHASH` and
+//! `BLOBBASEFEE` which geth-utils does not know yet. The known differences are:
+//! - a blob transaction pays its blob gas from the balance of its sender, which `bus-mapping` does
+//!   not do.
+//! - with the `scroll` feature, the precompiles and `SELFDESTRUCT` behave as on Ethereum, not as on
+//!   l2geth.
+//!
+//! revm 7 needs a rustc 1.75 or later, which is newer than the toolchain of the
+//! workspace, so the `rust-tracer` feature only builds with such a compiler.
+
+mod gas;
+#[cfg(feature = "scroll")]
+mod l2;
+
+#[cfg(feature = "scroll")]
+pub use l2::l2trace;
+
+use crate::{LoggerConfig, TraceConfig};
+use eth_types::{
+    evm_types::{Gas, GasCost, Memory, OpcodeId, ProgramCounter, Stack, Storage},
+    geth_types::{self, TxType},
+    Error, GethExecStep, GethExecTrace, Word, H256,
+};
+use gas::{geth_gas, GethGas};
+use revm::{
+    db::{CacheDB, EmptyDB},
+    inspector_handle_register,
+    interpreter::{InstructionResult, Interpreter, InterpreterAction},
+    primitives::{
+        keccak256, AccountInfo, Address, BlockEnv, Bytecode, Bytes, CreateScheme, ExecutionResult,
+        Output, SpecId, State, TransactTo, TxEnv, B256, KECCAK_EMPTY, U256,
+    },
+    Database, DatabaseCommit, Evm, EvmContext, Inspector,
+};
+use std::collections::{BTreeMap, HashMap};
+
+/// Creates a trace for the specified config with the revm interpreter.
+pub fn trace(config: &TraceConfig) -> Result<Vec<GethExecTrace>, Error> {
+    let block = &config.block_constants;
+    let mut block_env = block_env(config);
+    block_env.difficulty = to_u256(block.difficulty);
+    // For opcode PREVRANDAO, same as geth-utils.
+    block_env.prevrandao = Some(B256::from(H256::from_uint(&block.difficulty).0));
+
+    // `EmptyDB` makes up a hash for every block, while geth-utils returns zero
+    // for blocks out of `history_hashes`.
+    let mut tracer = Tracer::new(config, block_env, |n| {
+        let offset = config.block_constants.number.as_u64() - n - 1;
+        config
+            .history_hashes
+            .iter()
+            .rev()
+            .nth(offset as usize)
+            .map(|hash| B256::from(H256::from_uint(hash).0))
+            .unwrap_or_default()
+    })?;
+    (0..config.transactions.len())
+        .map(|i| tracer.trace_tx(i, false).map(|tx_trace| tx_trace.trace))
+        .collect()
+}
+
+/// The trace of a transaction, with what the l2 trace needs on top of it.
+struct TxTrace {
+    trace: GethExecTrace,
+    /// Codes read by the steps which call or inspect another account, by the
+    /// index of the step, see [`StructLogger::codes`].
+    codes: BTreeMap<usize, Vec<Bytes>>,
+    /// Accounts loaded by the transaction, as they are after it.
+    state: State,
+}
+
+/// Runs the transactions of a config one after the other.
+struct Tracer<'a> {
+    config: &'a TraceConfig,
+    spec: SpecId,
+    block_env: BlockEnv,
+    db: CacheDB<EmptyDB>,
+}
+
+impl<'a> Tracer<'a> {
+    /// Creates a tracer over the accounts of `config`, where the block `n`
+    /// before the current one has the hash `block_hash(n)`.
+    fn new(
+        config: &'a TraceConfig,
+        block_env: BlockEnv,
+        block_hash: impl Fn(u64) -> B256,
+    ) -> Result<Self, Error> {
+        let txs_gas_limit: u64 = config
+            .transactions
+            .iter()
+            .map(|tx| tx.gas_limit.as_u64())
+            .sum();
+        let block_gas_limit = config.block_constants.gas_limit.as_u64();
+        if txs_gas_limit > block_gas_limit {
+            return Err(Error::TracingError(format!(
+                "txs total gas: {txs_gas_limit} Exceeds block gas limit: {block_gas_limit}"
+            )));
+        }
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        for (address, account) in &config.accounts {
+            let address = Address::from(address.0);
+            let code = Bytes::from(account.code.to_vec());
+            db.insert_account_info(
+                address,
+                AccountInfo {
+                    balance: to_u256(account.balance),
+                    nonce: account.nonce.as_u64(),
+                    code_hash: keccak256(&code),
+                    code: Some(Bytecode::new_raw(code)),
+                },
+            );
+            for (key, value) in &account.storage {
+                db.insert_account_storage(address, to_u256(*key), to_u256(*value))
+                    .expect("EmptyDB never fails");
+            }
+        }
+        let number = config.block_constants.number.as_u64();
+        for n in number.saturating_sub(256)..number {
+            db.block_hashes.insert(U256::from(n), block_hash(n));
+        }
+
+        Ok(Self {
+            config,
+            spec: match &config.chain_config {
+                Some(chain_config) if chain_config.shanghai_time == Some(0) => SpecId::CANCUN,
+                _ => SpecId::MERGE,
+            },
+            block_env,
+            db,
+        })
+    }
+
+    /// Runs the transaction at `index` over the state left by the previous
+    /// ones, recording the codes read by its steps if `record_codes` is set.
+    fn trace_tx(&mut self, index: usize, record_codes: bool) -> Result<TxTrace, Error> {
+        let tx = &self.config.transactions[index];
+        let mut block_env = self.block_env.clone();
+        // geth-utils runs with `NoBaseFee`, which drops the base fee of the
+        // block for a transaction without gas price.
+        if tx.gas_price.is_zero() {
+            block_env.basefee = U256::ZERO;
+        }
+
+        let mut logger = StructLogger::new(&self.config.logger_config, self.spec, record_codes);
+        let chain_id = self.config.chain_id;
+        let result = Evm::builder()
+            .with_db(&mut self.db)
+            .with_external_context(&mut logger)
+            .modify_cfg_env(|cfg| cfg.chain_id = chain_id)
+            .with_block_env(block_env)
+            .with_tx_env(tx_env(tx, chain_id))
+            .append_handler_register(inspector_handle_register)
+            .with_spec_id(self.spec)
+            .build()
+            .transact()
+            .map_err(|e| {
+                Error::TracingError(format!(
+                    "Failed to apply config.Transactions[{index}]: {e:?}"
+                ))
+            })?;
+        self.db.commit(result.state.clone());
+
+        let return_data = match &result.result {
+            ExecutionResult::Success { output, .. } => match output {
+                Output::Call(data) | Output::Create(data, _) => data.clone(),
+            },
+            ExecutionResult::Revert { output, .. } => output.clone(),
+            ExecutionResult::Halt { .. } => Bytes::new(),
+        };
+        Ok(TxTrace {
+            trace: GethExecTrace {
+                l1_fee: 0,
+                gas: Gas(result.result.gas_used()),
+                failed: !result.result.is_success(),
+                return_value: hex::encode(return_data),
+                struct_logs: logger.struct_logs,
+                account_after: Vec::new(),
+            },
+            codes: logger.codes.unwrap_or_default(),
+            state: result.state,
+        })
+    }
+}
+
+/// The block of `config`, but for its difficulty.
+fn block_env(config: &TraceConfig) -> BlockEnv {
+    let block = &config.block_constants;
+    let mut block_env = BlockEnv {
+        number: U256::from(block.number.as_u64()),
+        coinbase: Address::from(block.coinbase.0),
+        timestamp: to_u256(block.timestamp),
+        gas_limit: to_u256(block.gas_limit),
+        basefee: to_u256(block.base_fee),
+        ..Default::default()
+    };
+    // The mock blocks have no excess blob gas.
+    block_env.set_blob_excess_gas_and_price(0);
+    block_env
+}
+
+fn tx_env(tx: &geth_types::Transaction, chain_id: u64) -> TxEnv {
+    TxEnv {
+        caller: Address::from(tx.from.0),
+        gas_limit: tx.gas_limit.as_u64(),
+        // geth-utils always sets the gas price, so every transaction is
+        // priced as a legacy one.
+        gas_price: to_u256(tx.gas_price),
+        gas_priority_fee: None,
+        transact_to: match tx.to {
+            Some(to) => TransactTo::Call(Address::from(to.0)),
+            None => TransactTo::Create(CreateScheme::Create),
+        },
+        value: to_u256(tx.value),
+        data: Bytes::from(tx.call_data.to_vec()),
+        chain_id: Some(chain_id),
+        // the nonce of a L1 message is its index in the L1 queue
+        nonce: (!tx.tx_type.is_l1_msg()).then(|| tx.nonce.as_u64()),
+        access_list: tx
+            .access_list
+            .iter()
+            .flat_map(|access_list| access_list.0.iter())
+            .map(|item| {
+                (
+                    Address::from(item.address.0),
+                    item.storage_keys
+                        .iter()
+                        .map(|key| U256::from_be_bytes(key.0))
+                        .collect(),
+                )
+            })
+            .collect(),
+        blob_hashes: tx
+            .blob_versioned_hashes
+            .iter()
+            .map(|hash| B256::from(hash.0))
+            .collect(),
+        max_fee_per_blob_gas: (tx.tx_type == TxType::Eip4844)
+            .then(|| to_u256(tx.max_fee_per_blob_gas)),
+    }
+}
+
+fn to_u256(word: Word) -> U256 {
+    U256::from_limbs(word.0)
+}
+
+fn to_word(value: &U256) -> Word {
+    Word(*value.as_limbs())
+}
+
+/// Returns the code of `address`, without loading the account into the
+/// journal, which would warm it.
+fn code<DB: Database>(context: &mut EvmContext<DB>, address: Address) -> Bytes {
+    let info = match context.journaled_state.state.get(&address) {
+        Some(account) => Some(account.info.clone()),
+        None => context.db.basic(address).ok().flatten(),
+    };
+    match info {
+        Some(AccountInfo {
+            code: Some(code), ..
+        }) => code.original_bytes(),
+        Some(info) if info.code_hash != KECCAK_EMPTY => context
+            .db
+            .code_by_hash(info.code_hash)
+            .map(|code| code.original_bytes())
+            .unwrap_or_default(),
+        _ => Bytes::new(),
+    }
+}
+
+/// A step which has been logged but whose gas cost is known only once it has
+/// been executed.
+struct PendingStep {
+    index: usize,
+    gas: u64,
+    /// Size of the stack before the step, which is logged only if enabled.
+    stack_len: usize,
+    /// Gas of the step as geth computes it, or `None` if the stack of the step
+    /// is invalid.
+    geth_gas: Option<GethGas>,
+}
+
+/// Records the steps of a transaction like geth's `logger.StructLogger`.
+struct StructLogger<'a> {
+    config: &'a LoggerConfig,
+    spec: SpecId,
+    struct_logs: Vec<GethExecStep>,
+    pending: Option<PendingStep>,
+    /// Refund counter of each context on the call stack, indexed by depth.
+    refunds: Vec<i64>,
+    /// Storage slots seen by SLOAD and SSTORE so far, per contract.
+    storage: HashMap<Address, HashMap<Word, Word>>,
+    /// If recorded, the codes of the running contract and of the callee for a
+    /// call, or of the inspected account for EXTCODESIZE and EXTCODECOPY, by
+    /// the index of the step.
+    codes: Option<BTreeMap<usize, Vec<Bytes>>>,
+}
+
+impl<'a> StructLogger<'a> {
+    fn new(config: &'a LoggerConfig, spec: SpecId, record_codes: bool) -> Self {
+        Self {
+            config,
+            spec,
+            struct_logs: Vec::new(),
+            pending: None,
+            refunds: Vec::new(),
+            storage: HashMap::new(),
+            codes: record_codes.then(BTreeMap::new),
+        }
+    }
+}
+
+impl<'a, DB: Database> Inspector<DB> for StructLogger<'a> {
+    fn step(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        let depth = context.journaled_state.depth() as usize;
+        self.refunds.resize(depth, 0);
+        self.refunds[depth - 1] = interp.gas.refunded();
+
+        let op = OpcodeId::from(interp.current_opcode());
+        let stack = interp.stack.data();
+        let (min_stack_ptr, max_stack_ptr) = op.valid_stack_ptr_range();
+        let stack_ptr = 1024 - stack.len() as u32;
+        let valid_stack = (min_stack_ptr..=max_stack_ptr).contains(&stack_ptr);
+
+        let storage = if !self.config.disable_storage
+            && matches!(op, OpcodeId::SLOAD | OpcodeId::SSTORE)
+            && !stack.is_empty()
+        {
+            let address = interp.contract.address;
+            let key = stack[stack.len() - 1];
+            let value = if op == OpcodeId::SSTORE {
+                stack.len().checked_sub(2).map(|i| stack[i])
+            } else {
+                let loaded = context
+                    .journaled_state
+                    .state
+                    .get(&address)
+                    .and_then(|account| account.storage.get(&key))
+                    .map(|slot| slot.present_value);
+                match loaded {
+                    Some(value) => Some(value),
+                    None => context.db.storage(address, key).ok(),
+                }
+            };
+            let storage = self.storage.entry(address).or_default();
+            if let Some(value) = value {
+                storage.insert(to_word(&key), to_word(&value));
+            }
+            Storage(storage.clone())
+        } else {
+            Storage::empty()
+        };
+
+        let index = self.struct_logs.len();
+        if let Some(codes) = self.codes.as_mut().filter(|_| valid_stack) {
+            let target = match op {
+                OpcodeId::CALL
+                | OpcodeId::CALLCODE
+                | OpcodeId::DELEGATECALL
+                | OpcodeId::STATICCALL => Some(stack[stack.len() - 2]),
+                OpcodeId::EXTCODESIZE | OpcodeId::EXTCODECOPY => Some(stack[stack.len() - 1]),
+                _ => None,
+            };
+            if let Some(target) = target {
+                let mut step_codes = Vec::new();
+                if !matches!(op, OpcodeId::EXTCODESIZE | OpcodeId::EXTCODECOPY) {
+                    step_codes.push(interp.contract.bytecode.original_bytecode());
+                }
+                step_codes.push(code(context, gas::to_address(target)));
+                codes.insert(index, step_codes);
+            }
+        }
+
+        let geth_gas = valid_stack.then(|| geth_gas(op, self.spec, interp, context));
+        self.pending = Some(PendingStep {
+            index,
+            gas: interp.gas.remaining(),
+            stack_len: stack.len(),
+            geth_gas,
+        });
+        self.struct_logs.push(GethExecStep {
+            pc: ProgramCounter(interp.program_counter()),
+            op,
+            gas: Gas(interp.gas.remaining()),
+            gas_cost: GasCost(0),
+            refund: Gas(self.refunds.iter().sum::<i64>() as u64),
+            depth: depth as u16,
+            error: None,
+            stack: if self.config.disable_stack {
+                Stack::new()
+            } else {
+                Stack(stack.iter().map(to_word).collect())
+            },
+            memory: if self.config.enable_memory {
+                Memory::from(
+                    interp
+                        .shared_memory
+                        .slice(0, interp.shared_memory.len())
+                        .to_vec(),
+                )
+            } else {
+                Memory::default()
+            },
+            storage,
+        });
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter, _context: &mut EvmContext<DB>) {
+        let pending = self.pending.take().expect("step_end without step");
+        let step = &mut self.struct_logs[pending.index];
+        let result = interp.instruction_result;
+
+        if !result.is_error() {
+            let mut gas_cost = pending.gas - interp.gas.remaining();
+            // revm charges the gas passed to a create with the step, while
+            // geth leaves it out of the cost.
+            if let InterpreterAction::Create { inputs } = &interp.next_action {
+                gas_cost -= inputs.gas_limit;
+            }
+            step.gas_cost = GasCost(gas_cost);
+            return;
+        }
+
+        // geth only reports the errors found before the step is executed, and
+        // logs the gas it computed by then.
+        let (gas_cost, error) = match pending.geth_gas {
+            Some(GethGas::Cost(cost)) => {
+                let out_of_gas = cost > pending.gas
+                    || matches!(
+                        result,
+                        InstructionResult::OutOfGas
+                            | InstructionResult::MemoryOOG
+                            | InstructionResult::MemoryLimitOOG
+                            | InstructionResult::InvalidOperandOOG
+                    );
+                (cost, out_of_gas.then(|| "out of gas".to_string()))
+            }
+            Some(GethGas::Fault(cost, error)) => (cost, Some(error)),
+            None => {
+                let (min_stack_ptr, max_stack_ptr) = step.op.valid_stack_ptr_range();
+                let stack_len = pending.stack_len;
+                let error = if 1024 - stack_len as u32 > max_stack_ptr {
+                    format!("stack underflow ({stack_len} <=> {})", 1024 - max_stack_ptr)
+                } else {
+                    format!("stack limit reached {stack_len} ({})", 1024 - min_stack_ptr)
+                };
+                (step.op.constant_gas_cost().0, Some(error))
+            }
+        };
+        step.gas_cost = GasCost(gas_cost);
+        step.error = error;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eth_types::{
+        address, bytecode,
+        geth_types::{Account, BlockConstants},
+        Bytecode, ToWord, U64,
+    };
+
+    const BLOCK_GAS_LIMIT: u64 = 1_000_000;
+
+    /// A block of a single transaction calling `code`.
+    fn config(code: Bytecode, gas_limit: u64) -> TraceConfig {
+        let from = address!("0x000000000000000000000000000000000000AAAA");
+        let to = address!("0x000000000000000000000000000000000000BBBB");
+        TraceConfig {
+            chain_id: 1,
+            block_constants: BlockConstants {
+                number: U64::from(1),
+                gas_limit: Word::from(BLOCK_GAS_LIMIT),
+                ..Default::default()
+            },
+            accounts: BTreeMap::from([
+                (
+                    from,
+                    Account {
+                        address: from,
+                        balance: Word::from(1u64 << 40),
+                        ..Default::default()
+                    },
+                ),
+                (
+                    to,
+                    Account {
+                        address: to,
+                        code: code.code().into(),
+                        ..Default::default()
+                    },
+                ),
+            ]),
+            transactions: vec![geth_types::Transaction {
+                from,
+                to: Some(to),
+                gas_limit: Word::from(gas_limit),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn add_code() -> Bytecode {
+        bytecode! {
+            PUSH1(1)
+            PUSH1(2)
+            ADD
+            STOP
+        }
+    }
+
+    #[test]
+    fn test_trace_steps() {
+        let traces = trace(&config(add_code(), 100_000)).unwrap();
+        assert_eq!(traces.len(), 1);
+        let trace = &traces[0];
+        assert!(!trace.failed);
+        assert_eq!(trace.gas, Gas(21_000 + 9));
+
+        let steps: Vec<_> = trace
+            .struct_logs
+            .iter()
+            .map(|step| (step.op, step.gas.0, step.gas_cost.0))
+            .collect();
+        assert_eq!(
+            steps,
+            [
+                (OpcodeId::PUSH1, 79_000, 3),
+                (OpcodeId::PUSH1, 78_997, 3),
+                (OpcodeId::ADD, 78_994, 3),
+                (OpcodeId::STOP, 78_991, 0),
+            ]
+        );
+        // the stack before each step, bottom first
+        assert_eq!(trace.struct_logs[2].stack.0, [Word::from(1), Word::from(2)]);
+        assert_eq!(trace.struct_logs[3].stack.0, [Word::from(3)]);
+    }
+
+    #[test]
+    fn test_trace_out_of_gas() {
+        let traces = trace(&config(add_code(), 21_000 + 5)).unwrap();
+        let trace = &traces[0];
+        assert!(trace.failed);
+        assert_eq!(trace.struct_logs.len(), 2);
+        assert_eq!(trace.struct_logs[0].error, None);
+        assert_eq!(trace.struct_logs[1].error.as_deref(), Some("out of gas"));
+    }
+
+    #[test]
+    fn test_trace_out_of_gas_memory_expansion() {
+        let code = bytecode! {
+            PUSH1(0)
+            PUSH2(0x400)
+            MSTORE
+            STOP
+        };
+        let traces = trace(&config(code, 21_000 + 100)).unwrap();
+        let step = &traces[0].struct_logs[2];
+        assert_eq!(step.op, OpcodeId::MSTORE);
+        assert_eq!(step.error.as_deref(), Some("out of gas"));
+        // geth logs the whole cost, with the expansion to 0x420 bytes
+        assert_eq!(step.gas_cost.0, 3 + 33 * 3 + 33 * 33 / 512);
+    }
+
+    #[test]
+    fn test_trace_static_violation() {
+        let callee = address!("0x000000000000000000000000000000000000CCCC");
+        let code = bytecode! {
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH20(callee.to_word())
+            PUSH2(0xffff)
+            STATICCALL
+            STOP
+        };
+        let mut config = config(code, 100_000);
+        let callee_code = bytecode! {
+            PUSH1(1)
+            PUSH1(0)
+            SSTORE
+        };
+        config.accounts.insert(
+            callee,
+            Account {
+                address: callee,
+                code: callee_code.code().into(),
+                ..Default::default()
+            },
+        );
+        let traces = trace(&config).unwrap();
+        let step = traces[0]
+            .struct_logs
+            .iter()
+            .find(|step| step.op == OpcodeId::SSTORE)
+            .unwrap();
+        // geth computes the cold SSTORE cost before finding out the write
+        // protection, which it reports with no error.
+        assert_eq!(step.error, None);
+        assert_eq!(step.gas_cost.0, 2100 + 20_000);
+    }
+
+    #[test]
+    fn test_trace_exceeds_block_gas_limit() {
+        assert!(trace(&config(add_code(), BLOCK_GAS_LIMIT + 1)).is_err());
+    }
+}
@@ -0,0 +1,294 @@
+//! The gas cost geth logs for a step which fails.
+//!
+//! revm stops charging the gas of a step at the first check which fails, while
+//! geth computes the constant and the dynamic gas of the step, logs them as its
+//! `gasCost`, and only then finds out that the gas left is not enough, or that
+//! the step writes in a static context. So the cost of a failing step is worked
+//! out here the way `core/vm/gas_table.go` of geth does.
+
+use eth_types::evm_types::{
+    gas_utils::memory_expansion_gas_cost, GasCost, OpcodeId, MAX_CODE_SIZE,
+    MAX_EXPANDED_MEMORY_ADDRESS,
+};
+use revm::{
+    interpreter::Interpreter,
+    primitives::{Address, SpecId, B256, U256},
+    Database, EvmContext,
+};
+
+/// Largest init code accepted since EIP-3860.
+const MAX_INIT_CODE_SIZE: u64 = 2 * MAX_CODE_SIZE;
+/// Gas per word of init code since EIP-3860.
+const INIT_CODE_WORD_GAS: u64 = 2;
+
+/// Gas of a step as geth computes it before executing the step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum GethGas {
+    /// Constant and dynamic gas of the step.
+    Cost(u64),
+    /// geth fails with the error while computing the dynamic gas, and logs
+    /// only the constant gas.
+    Fault(u64, String),
+}
+
+/// Returns the gas geth computes for the step `op` about to run in `interp`,
+/// whose stack must hold enough items for `op`.
+pub(super) fn geth_gas<DB: Database>(
+    op: OpcodeId,
+    spec: SpecId,
+    interp: &Interpreter,
+    context: &mut EvmContext<DB>,
+) -> GethGas {
+    let constant = op.constant_gas_cost().0;
+    let stack = interp.stack.data();
+    // the n-th item from the top of the stack
+    let back = |n: usize| stack[stack.len() - 1 - n];
+
+    let memory_size = match op {
+        OpcodeId::MLOAD | OpcodeId::MSTORE => memory_size(back(0), U256::from(32)),
+        OpcodeId::MSTORE8 => memory_size(back(0), U256::from(1)),
+        OpcodeId::SHA3 | OpcodeId::RETURN | OpcodeId::REVERT => memory_size(back(0), back(1)),
+        op if op.is_log() => memory_size(back(0), back(1)),
+        OpcodeId::CALLDATACOPY | OpcodeId::CODECOPY | OpcodeId::RETURNDATACOPY => {
+            memory_size(back(0), back(2))
+        }
+        OpcodeId::MCOPY => memory_size(back(0).max(back(1)), back(2)),
+        OpcodeId::EXTCODECOPY => memory_size(back(1), back(3)),
+        OpcodeId::CREATE | OpcodeId::CREATE2 => memory_size(back(1), back(2)),
+        OpcodeId::CALL | OpcodeId::CALLCODE => {
+            max_memory_size(memory_size(back(3), back(4)), memory_size(back(5), back(6)))
+        }
+        OpcodeId::DELEGATECALL | OpcodeId::STATICCALL => {
+            max_memory_size(memory_size(back(2), back(3)), memory_size(back(4), back(5)))
+        }
+        _ => Some(0),
+    };
+    let Some(memory_words) = memory_size.map(to_word_size).filter(|words| words.checked_mul(32).is_some()) else {
+        return GethGas::Fault(constant, "gas uint64 overflow".to_string());
+    };
+    if memory_words * 32 > MAX_EXPANDED_MEMORY_ADDRESS {
+        return GethGas::Fault(constant, "out of gas".to_string());
+    }
+    let current_words = interp.shared_memory.len() as u64 / 32;
+    let memory_gas = if memory_words > current_words {
+        memory_expansion_gas_cost(current_words, memory_words)
+    } else {
+        0
+    };
+
+    let address = interp.contract.address;
+    // the gas left once the constant gas is paid
+    let gas_left = interp.gas.remaining().saturating_sub(constant);
+    let dynamic = match op {
+        OpcodeId::SHA3 => copy_gas(memory_gas, back(1), GasCost::COPY_SHA3.0),
+        OpcodeId::CALLDATACOPY
+        | OpcodeId::CODECOPY
+        | OpcodeId::RETURNDATACOPY
+        | OpcodeId::MCOPY => copy_gas(memory_gas, back(2), GasCost::COPY.0),
+        OpcodeId::EXTCODECOPY => copy_gas(memory_gas, back(3), GasCost::COPY.0)
+            .and_then(|gas| gas.checked_add(cold_account_gas(context, to_address(back(0))))),
+        OpcodeId::EXP => Some(GasCost::EXP_BYTE_TIMES.0 * ((back(1).bit_len() as u64 + 7) / 8)),
+        op if op.is_log() => {
+            let topics = op.postfix().expect("LOG has a postfix") as u64;
+            u64::try_from(back(1))
+                .ok()
+                .and_then(|size| size.checked_mul(8))
+                .and_then(|gas| gas.checked_add(GasCost::LOG.0 * (topics + 1)))
+                .and_then(|gas| gas.checked_add(memory_gas))
+        }
+        OpcodeId::SLOAD => Some(if is_warm_slot(context, address, back(0)) {
+            GasCost::WARM_ACCESS.0
+        } else {
+            GasCost::COLD_SLOAD.0
+        }),
+        OpcodeId::SSTORE => {
+            if gas_left <= GasCost::SSTORE_SENTRY.0 {
+                None
+            } else {
+                Some(sstore_gas(context, address, back(0), back(1)))
+            }
+        }
+        OpcodeId::BALANCE | OpcodeId::EXTCODESIZE | OpcodeId::EXTCODEHASH => {
+            Some(cold_account_gas(context, to_address(back(0))))
+        }
+        OpcodeId::CALL | OpcodeId::CALLCODE | OpcodeId::DELEGATECALL | OpcodeId::STATICCALL => {
+            let callee = to_address(back(1));
+            let cold_gas = cold_account_gas(context, callee);
+            if cold_gas > gas_left {
+                None
+            } else {
+                let transfers_value = op.is_call_with_value() && !back(2).is_zero();
+                let mut gas = memory_gas;
+                if transfers_value {
+                    gas += GasCost::CALL_WITH_VALUE.0;
+                    if op == OpcodeId::CALL && is_empty(context, callee) {
+                        gas += GasCost::NEW_ACCOUNT.0;
+                    }
+                }
+                gas.checked_add(call_gas(gas_left - cold_gas, gas, back(0)))
+                    .and_then(|gas| gas.checked_add(cold_gas))
+            }
+        }
+        OpcodeId::CREATE | OpcodeId::CREATE2 => {
+            let shanghai = SpecId::enabled(spec, SpecId::SHANGHAI);
+            let mut word_gas = if shanghai { INIT_CODE_WORD_GAS } else { 0 };
+            if op == OpcodeId::CREATE2 {
+                word_gas += GasCost::COPY_SHA3.0;
+            }
+            // the memory size already checked that the size fits in 64 bits
+            let size = u64::try_from(back(2)).unwrap_or(u64::MAX);
+            if shanghai && size > MAX_INIT_CODE_SIZE {
+                return GethGas::Fault(
+                    constant,
+                    format!("max initcode size exceeded: size {size}"),
+                );
+            }
+            to_word_size(size)
+                .checked_mul(word_gas)
+                .and_then(|gas| gas.checked_add(memory_gas))
+        }
+        OpcodeId::SELFDESTRUCT => {
+            let beneficiary = to_address(back(0));
+            let mut gas = if is_warm_account(context, beneficiary) {
+                0
+            } else {
+                GasCost::COLD_ACCOUNT_ACCESS.0
+            };
+            if is_empty(context, beneficiary) && !balance(context, address).is_zero() {
+                gas += GasCost::NEW_ACCOUNT.0;
+            }
+            Some(gas)
+        }
+        _ => Some(memory_gas),
+    };
+
+    match dynamic.and_then(|dynamic| dynamic.checked_add(constant)) {
+        Some(cost) => GethGas::Cost(cost),
+        None => GethGas::Fault(constant, "out of gas".to_string()),
+    }
+}
+
+/// Memory size accessed by `len` bytes at `offset`, or `None` if it does not
+/// fit in 64 bits.
+fn memory_size(offset: U256, len: U256) -> Option<u64> {
+    if len.is_zero() {
+        return Some(0);
+    }
+    u64::try_from(offset)
+        .ok()?
+        .checked_add(u64::try_from(len).ok()?)
+}
+
+fn max_memory_size(a: Option<u64>, b: Option<u64>) -> Option<u64> {
+    Some(a?.max(b?))
+}
+
+fn to_word_size(size: u64) -> u64 {
+    if size > u64::MAX - 31 {
+        u64::MAX / 32 + 1
+    } else {
+        (size + 31) / 32
+    }
+}
+
+fn copy_gas(memory_gas: u64, len: U256, per_word: u64) -> Option<u64> {
+    u64::try_from(len)
+        .ok()
+        .and_then(|len| to_word_size(len).checked_mul(per_word))
+        .and_then(|gas| gas.checked_add(memory_gas))
+}
+
+/// Gas passed to a call, with geth's EIP-150 arithmetic, which wraps when the
+/// gas left does not cover `base`.
+fn call_gas(gas_left: u64, base: u64, requested: U256) -> u64 {
+    let available = gas_left.wrapping_sub(base);
+    let capped = available - available / 64;
+    match u64::try_from(requested) {
+        Ok(requested) if requested <= capped => requested,
+        _ => capped,
+    }
+}
+
+pub(super) fn to_address(word: U256) -> Address {
+    Address::from_word(B256::from(word.to_be_bytes::<32>()))
+}
+
+fn is_warm_account<DB: Database>(context: &EvmContext<DB>, address: Address) -> bool {
+    context.journaled_state.state.contains_key(&address)
+        || context
+            .journaled_state
+            .warm_preloaded_addresses
+            .contains(&address)
+}
+
+/// Gas charged on top of a warm access to `address`.
+fn cold_account_gas<DB: Database>(context: &EvmContext<DB>, address: Address) -> u64 {
+    if is_warm_account(context, address) {
+        0
+    } else {
+        GasCost::COLD_ACCOUNT_ACCESS.0 - GasCost::WARM_ACCESS.0
+    }
+}
+
+fn is_warm_slot<DB: Database>(context: &EvmContext<DB>, address: Address, key: U256) -> bool {
+    context
+        .journaled_state
+        .state
+        .get(&address)
+        .map_or(false, |account| account.storage.contains_key(&key))
+}
+
+fn is_empty<DB: Database>(context: &mut EvmContext<DB>, address: Address) -> bool {
+    match context.journaled_state.state.get(&address) {
+        Some(account) => account.is_empty(),
+        None => context
+            .db
+            .basic(address)
+            .ok()
+            .flatten()
+            .map_or(true, |info| info.is_empty()),
+    }
+}
+
+fn balance<DB: Database>(context: &mut EvmContext<DB>, address: Address) -> U256 {
+    match context.journaled_state.state.get(&address) {
+        Some(account) => account.info.balance,
+        None => context
+            .db
+            .basic(address)
+            .ok()
+            .flatten()
+            .map(|info| info.balance)
+            .unwrap_or_default(),
+    }
+}
+
+/// Gas of SSTORE under EIP-2929 and EIP-3529.
+fn sstore_gas<DB: Database>(
+    context: &mut EvmContext<DB>,
+    address: Address,
+    key: U256,
+    value: U256,
+) -> u64 {
+    let slot = context
+        .journaled_state
+        .state
+        .get(&address)
+        .and_then(|account| account.storage.get(&key))
+        .map(|slot| (slot.previous_or_original_value, slot.present_value));
+    let (cold_gas, (original, current)) = match slot {
+        Some(slot) => (0, slot),
+        None => {
+            let value = context.db.storage(address, key).unwrap_or_default();
+            (GasCost::COLD_SLOAD.0, (value, value))
+        }
+    };
+    cold_gas
+        + if current == value || original != current {
+            GasCost::WARM_ACCESS.0
+        } else if original.is_zero() {
+            GasCost::SSTORE_SET.0
+        } else {
+            GasCost::SSTORE_RESET.0
+        }
+}
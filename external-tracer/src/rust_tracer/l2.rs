@@ -0,0 +1,499 @@
+//! l2 block traces, in the form l2geth returns them.
+//!
+//! The block runs like in [`super::trace`], but for the scroll rules: the
+//! sender of a transaction also pays its L1 data fee to the coinbase, the block
+//! hashes are made of the chain id and the block number, and the difficulty is
+//! zero. The storage trace proves every account and every slot of the config
+//! against its zktrie, so that the partial trie built from the proofs holds the
+//! whole state before the block.
+
+use super::{block_env, to_u256, to_word, Tracer, TxTrace};
+use crate::TraceConfig;
+use eth_types::{
+    evm_types::{block_utils::calculate_block_hash, gas_utils::tx_data_gas_cost, OpcodeId},
+    geth_types::{get_rlp_signed, TxType},
+    hash_code_poseidon,
+    l2_types::{
+        AccountProofWrapper, BlockTrace, EthBlock, ExecStep, ExecutionResult, ExtraData,
+        StorageTrace, TransactionTrace,
+    },
+    Error, GethExecStep, Word, H256, U64,
+};
+use mpt_zktrie::state::{builder::HASH_SCHEME_DONE, ZkMemoryDb, ZkTrie};
+use revm::{
+    db::{CacheDB, EmptyDB},
+    primitives::{Address, Bytes, B256, KECCAK_EMPTY, U256},
+    Database,
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Address of the L1GasPriceOracle predeploy.
+const L1_GAS_PRICE_ORACLE: Address = Address::new([
+    0x53, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x02,
+]);
+/// Slots of the L1 base fee, the L1 fee overhead and the L1 fee scalar in the
+/// L1GasPriceOracle.
+const L1_FEE_SLOTS: [u64; 3] = [1, 2, 3];
+/// Precision of the L1 fee scalar.
+const TX_L1_FEE_PRECISION: u128 = 1_000_000_000;
+/// Extra L1 gas paid for committing a transaction.
+const TX_L1_COMMIT_EXTRA_COST: u64 = 64;
+
+/// Creates a l2-trace for the specified config with the revm interpreter.
+pub fn l2trace(config: &TraceConfig) -> Result<BlockTrace, Error> {
+    assert!(*HASH_SCHEME_DONE, "must set hash scheme into zktrie");
+
+    let chain_id = config.chain_id;
+    let block = &config.block_constants;
+    let mut block_env = block_env(config);
+    block_env.difficulty = U256::ZERO;
+    block_env.prevrandao = Some(B256::ZERO);
+    let mut tracer = Tracer::new(config, block_env, |n| {
+        let (_, hash) = calculate_block_hash(chain_id, Word::from(n));
+        B256::from(H256::from_uint(&hash).0)
+    })?;
+
+    let (_, hash) = calculate_block_hash(chain_id, Word::from(block.number.as_u64()));
+    let header = EthBlock {
+        hash: Some(H256::from_uint(&hash)),
+        author: Some(block.coinbase),
+        number: Some(block.number),
+        timestamp: block.timestamp,
+        gas_limit: block.gas_limit,
+        base_fee_per_gas: Some(block.base_fee),
+        ..Default::default()
+    };
+    let coinbase = Address::from(block.coinbase.0);
+
+    // the accounts and the slots to prove
+    let mut addresses: BTreeSet<Address> = config
+        .accounts
+        .keys()
+        .map(|address| Address::from(address.0))
+        .chain([coinbase, L1_GAS_PRICE_ORACLE])
+        .collect();
+    let mut slots: BTreeSet<(Address, U256)> = config
+        .accounts
+        .iter()
+        .flat_map(|(address, account)| {
+            account
+                .storage
+                .keys()
+                .map(|key| (Address::from(address.0), to_u256(*key)))
+        })
+        .chain(
+            L1_FEE_SLOTS
+                .into_iter()
+                .map(|slot| (L1_GAS_PRICE_ORACLE, U256::from(slot))),
+        )
+        .collect();
+    // the accounts changed by the block, which get deleted if they are left
+    // empty
+    let mut touched = BTreeSet::new();
+
+    let trie_before = StateTrie::new(config.accounts.values().map(|account| {
+        (
+            Address::from(account.address.0),
+            TrieAccount {
+                nonce: account.nonce.as_u64(),
+                balance: account.balance,
+                code: account.code.to_vec(),
+                storage: account
+                    .storage
+                    .iter()
+                    .map(|(key, value)| (to_u256(*key), to_u256(*value)))
+                    .collect(),
+            },
+        )
+    }));
+
+    let mut code_hashes = CodeHashes::default();
+    let mut transactions = Vec::with_capacity(config.transactions.len());
+    let mut execution_results = Vec::with_capacity(config.transactions.len());
+    for (index, tx) in config.transactions.iter().enumerate() {
+        let from = Address::from(tx.from.0);
+        let to = tx.to.map(|to| Address::from(to.0));
+        let tx_trace = TransactionTrace {
+            tx_hash: tx.hash,
+            type_: match tx.tx_type {
+                TxType::Eip155 | TxType::PreEip155 => 0,
+                TxType::Eip2930 => 1,
+                TxType::Eip1559 => 2,
+                TxType::Eip4844 => 3,
+                TxType::L1Msg => 0x7e,
+            },
+            nonce: tx.nonce.as_u64(),
+            gas: tx.gas_limit.as_u64(),
+            gas_price: tx.gas_price,
+            from: tx.from,
+            to: tx.to,
+            chain_id: Word::from(chain_id),
+            value: tx.value,
+            data: tx.call_data.clone(),
+            is_create: tx.to.is_none(),
+            v: U64::from(tx.v),
+            r: tx.r,
+            s: tx.s,
+        };
+
+        // bus-mapping prices the L1 data of the transaction as it is rebuilt
+        // from the trace.
+        let l1_fee = if tx.tx_type.is_l1_msg() {
+            U256::ZERO
+        } else {
+            let eth_tx = tx_trace.to_eth_tx(header.hash, header.number, Some(U64::from(index)));
+            l1_data_fee(&mut tracer.db, tx_data_gas_cost(&get_rlp_signed(&eth_tx)))
+        };
+        change_balance(&mut tracer.db, from, |balance| balance.checked_sub(l1_fee)).ok_or_else(
+            || {
+                Error::TracingError(format!(
+                    "Failed to apply config.Transactions[{index}]: insufficient funds for l1 fee"
+                ))
+            },
+        )?;
+        let callee_code = to.map(|to| code(&mut tracer.db, to));
+
+        let TxTrace {
+            trace,
+            codes,
+            state,
+        } = tracer.trace_tx(index, true)?;
+        change_balance(&mut tracer.db, coinbase, |balance| {
+            balance.checked_add(l1_fee)
+        })
+        .expect("coinbase balance overflow");
+        if !l1_fee.is_zero() {
+            touched.extend([from, coinbase]);
+        }
+
+        for (address, account) in &state {
+            addresses.insert(*address);
+            slots.extend(account.storage.keys().map(|key| (*address, *key)));
+            if account.is_touched() {
+                touched.insert(*address);
+            }
+        }
+
+        let exec_steps = trace
+            .struct_logs
+            .into_iter()
+            .enumerate()
+            .map(|(step_index, step)| {
+                let extra_data = codes.get(&step_index).map(|codes| {
+                    // bus-mapping reads the callee code of a call at index 1
+                    // in a transaction with a callee, after the code of the
+                    // caller, and at index 0 in a creation.
+                    let skip = usize::from(tx.to.is_none() && codes.len() == 2);
+                    ExtraData {
+                        code_list: Some(codes[skip..].iter().map(to_bytes).collect()),
+                        proof_list: None,
+                    }
+                });
+                exec_step(step, extra_data)
+            })
+            .collect();
+
+        let account_from = account(&mut tracer.db, &mut code_hashes, from);
+        let account_to = to.map(|to| account(&mut tracer.db, &mut code_hashes, to));
+        let account_coinbase = account(&mut tracer.db, &mut code_hashes, coinbase);
+        let created = to.is_none().then(|| {
+            account(
+                &mut tracer.db,
+                &mut code_hashes,
+                from.create(tx.nonce.as_u64()),
+            )
+        });
+        execution_results.push(ExecutionResult {
+            l1_fee: to_word(&l1_fee),
+            gas: trace.gas.0,
+            failed: trace.failed,
+            return_value: trace.return_value,
+            from: Some(account_from.clone()),
+            to: account_to.clone(),
+            account_after: [Some(account_from), account_to, Some(account_coinbase)]
+                .into_iter()
+                .flatten()
+                .collect(),
+            account_created: created,
+            code_hash: callee_code
+                .as_ref()
+                .map(|code| code_hashes.poseidon(&keccak(code), code)),
+            byte_code: callee_code.map(|code| format!("0x{}", hex::encode(code))),
+            exec_steps,
+        });
+        transactions.push(tx_trace);
+    }
+
+    let trie_after = StateTrie::new(addresses.iter().filter_map(|address| {
+        let db_account = tracer.db.accounts.get(address)?;
+        let info = db_account.info()?;
+        if info.is_empty() && touched.contains(address) {
+            return None;
+        }
+        let code = code(&mut tracer.db, *address).to_vec();
+        let storage = tracer.db.accounts[address]
+            .storage
+            .iter()
+            .map(|(key, value)| (*key, *value))
+            .collect();
+        Some((
+            *address,
+            TrieAccount {
+                nonce: info.nonce,
+                balance: to_word(&info.balance),
+                code,
+                storage,
+            },
+        ))
+    }));
+
+    let mut storage_proofs: HashMap<_, HashMap<_, _>> = HashMap::new();
+    for (address, key) in slots {
+        storage_proofs
+            .entry(eth_types::Address::from(address.into_array()))
+            .or_default()
+            .insert(to_word(&key), trie_before.storage_proof(address, key));
+    }
+    Ok(BlockTrace {
+        chain_id,
+        coinbase: account(&mut tracer.db, &mut code_hashes, coinbase),
+        header,
+        transactions,
+        execution_results,
+        storage_trace: StorageTrace {
+            root_before: trie_before.root(),
+            root_after: trie_after.root(),
+            proofs: Some(
+                addresses
+                    .into_iter()
+                    .map(|address| {
+                        (
+                            eth_types::Address::from(address.into_array()),
+                            trie_before.account_proof(address),
+                        )
+                    })
+                    .collect(),
+            ),
+            storage_proofs,
+            // the proofs above already cover every node of the trie
+            deletion_proofs: Vec::new(),
+        },
+        tx_storage_trace: Vec::new(),
+        start_l1_queue_index: config.l1_queue_index,
+    })
+}
+
+/// L1 data fee of a transaction, from the L1GasPriceOracle in `db`.
+fn l1_data_fee(db: &mut CacheDB<EmptyDB>, tx_data_gas_cost: u64) -> U256 {
+    let [base_fee, fee_overhead, fee_scalar] = L1_FEE_SLOTS.map(|slot| {
+        db.storage(L1_GAS_PRICE_ORACLE, U256::from(slot))
+            .expect("EmptyDB never fails")
+            .to::<u64>()
+    });
+    let tx_l1_gas = tx_data_gas_cost + fee_overhead + TX_L1_COMMIT_EXTRA_COST;
+    let tx_l1_fee = fee_scalar as u128 * base_fee as u128 * tx_l1_gas as u128;
+    U256::from(tx_l1_fee / TX_L1_FEE_PRECISION)
+}
+
+/// Sets the balance of `address` to `f` of its balance, or returns `None` if
+/// `f` does.
+fn change_balance(
+    db: &mut CacheDB<EmptyDB>,
+    address: Address,
+    f: impl FnOnce(U256) -> Option<U256>,
+) -> Option<()> {
+    let mut info = db
+        .basic(address)
+        .expect("EmptyDB never fails")
+        .unwrap_or_default();
+    info.balance = f(info.balance)?;
+    db.insert_account_info(address, info);
+    Some(())
+}
+
+fn code(db: &mut CacheDB<EmptyDB>, address: Address) -> Bytes {
+    match db.basic(address).expect("EmptyDB never fails") {
+        Some(info) if info.code_hash != KECCAK_EMPTY => db
+            .code_by_hash(info.code_hash)
+            .expect("EmptyDB never fails")
+            .original_bytes(),
+        _ => Bytes::new(),
+    }
+}
+
+fn keccak(code: &[u8]) -> H256 {
+    H256(revm::primitives::keccak256(code).0)
+}
+
+fn to_bytes(bytes: &Bytes) -> eth_types::Bytes {
+    eth_types::Bytes::from(bytes.to_vec())
+}
+
+/// Poseidon code hashes by keccak code hash, since they are slow to compute.
+#[derive(Default)]
+struct CodeHashes(HashMap<H256, H256>);
+
+impl CodeHashes {
+    fn poseidon(&mut self, keccak: &H256, code: &[u8]) -> H256 {
+        *self
+            .0
+            .entry(*keccak)
+            .or_insert_with(|| hash_code_poseidon(code))
+    }
+}
+
+/// The status of `address` in `db`, with zeros for an account which does not
+/// exist.
+fn account(
+    db: &mut CacheDB<EmptyDB>,
+    code_hashes: &mut CodeHashes,
+    address: Address,
+) -> AccountProofWrapper {
+    let info = db.basic(address).expect("EmptyDB never fails");
+    let code = code(db, address);
+    let (nonce, balance, keccak_code_hash, poseidon_code_hash) = match info {
+        Some(info) => {
+            let keccak_code_hash = H256(info.code_hash.0);
+            (
+                info.nonce,
+                to_word(&info.balance),
+                keccak_code_hash,
+                code_hashes.poseidon(&keccak_code_hash, &code),
+            )
+        }
+        None => Default::default(),
+    };
+    AccountProofWrapper {
+        address: Some(eth_types::Address::from(address.into_array())),
+        nonce: Some(nonce),
+        balance: Some(balance),
+        keccak_code_hash: Some(keccak_code_hash),
+        poseidon_code_hash: Some(poseidon_code_hash),
+        storage: None,
+    }
+}
+
+fn exec_step(step: GethExecStep, extra_data: Option<ExtraData>) -> ExecStep {
+    ExecStep {
+        pc: step.pc.0 as u64,
+        op: step.op,
+        gas: step.gas.0,
+        gas_cost: step.gas_cost.0,
+        refund: step.refund.0,
+        depth: step.depth as isize,
+        error: step.error,
+        stack: Some(step.stack.0),
+        memory: Some(
+            step.memory
+                .0
+                .chunks(32)
+                .map(Word::from_big_endian)
+                .collect(),
+        ),
+        storage: (!step.storage.0.is_empty()).then_some(step.storage.0),
+        extra_data: extra_data.filter(|_| {
+            matches!(
+                step.op,
+                OpcodeId::CALL
+                    | OpcodeId::CALLCODE
+                    | OpcodeId::DELEGATECALL
+                    | OpcodeId::STATICCALL
+                    | OpcodeId::EXTCODESIZE
+                    | OpcodeId::EXTCODECOPY
+            )
+        }),
+    }
+}
+
+/// An account as the zktrie stores it.
+struct TrieAccount {
+    nonce: u64,
+    balance: Word,
+    code: Vec<u8>,
+    storage: BTreeMap<U256, U256>,
+}
+
+/// The zktrie of a state, with the storage trie of each of its accounts.
+struct StateTrie {
+    trie: ZkTrie,
+    storage_tries: HashMap<Address, ZkTrie>,
+}
+
+impl StateTrie {
+    fn new(accounts: impl IntoIterator<Item = (Address, TrieAccount)>) -> Self {
+        let mut trie = ZkMemoryDb::new()
+            .new_trie(&[0; 32])
+            .expect("the empty trie always exists");
+        let mut storage_tries = HashMap::new();
+        let mut code_hashes = CodeHashes::default();
+        for (address, account) in accounts {
+            // all the tries share the db of the account trie
+            let mut storage_trie = trie
+                .get_db()
+                .new_trie(&[0; 32])
+                .expect("the empty trie always exists");
+            for (key, value) in account.storage {
+                if !value.is_zero() {
+                    storage_trie
+                        .update_store(&key.to_be_bytes::<32>(), &value.to_be_bytes::<32>())
+                        .expect("valid storage update");
+                }
+            }
+
+            let mut nonce_codesize = [0; 32];
+            nonce_codesize[16..24].copy_from_slice(&(account.code.len() as u64).to_be_bytes());
+            nonce_codesize[24..].copy_from_slice(&account.nonce.to_be_bytes());
+            let mut balance = [0; 32];
+            account.balance.to_big_endian(&mut balance);
+            let keccak_code_hash = keccak(&account.code);
+            let poseidon_code_hash = code_hashes.poseidon(&keccak_code_hash, &account.code);
+            trie.update_account(
+                address.as_slice(),
+                &[
+                    nonce_codesize,
+                    balance,
+                    storage_trie.root(),
+                    keccak_code_hash.0,
+                    poseidon_code_hash.0,
+                ],
+            )
+            .expect("valid account update");
+            storage_tries.insert(address, storage_trie);
+        }
+        Self {
+            trie,
+            storage_tries,
+        }
+    }
+
+    fn root(&self) -> H256 {
+        H256(self.trie.root())
+    }
+
+    fn account_proof(&self, address: Address) -> Vec<eth_types::Bytes> {
+        self.trie
+            .prove(address.as_slice())
+            .expect("valid account proof")
+            .into_iter()
+            .map(eth_types::Bytes::from)
+            .collect()
+    }
+
+    fn storage_proof(&self, address: Address, key: U256) -> Vec<eth_types::Bytes> {
+        let key = key.to_be_bytes::<32>();
+        let proof = match self.storage_tries.get(&address) {
+            Some(storage_trie) => storage_trie.prove(&key),
+            None => self
+                .trie
+                .get_db()
+                .new_trie(&[0; 32])
+                .expect("the empty trie always exists")
+                .prove(&key),
+        };
+        proof
+            .expect("valid storage proof")
+            .into_iter()
+            .map(eth_types::Bytes::from)
+            .collect()
+    }
+}
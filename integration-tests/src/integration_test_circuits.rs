@@ -65,6 +65,10 @@ pub const MAX_INNER_BLOCKS: usize = 64;
 const MAX_EXP_STEPS: usize = 1000;
 /// MAX_KECCAK_ROWS
 const MAX_KECCAK_ROWS: usize = 15000;
+/// MAX_SHA256_ROWS
+const MAX_SHA256_ROWS: usize = 15000;
+/// MAX_RIPEMD160_ROWS
+const MAX_RIPEMD160_ROWS: usize = 15000;
 /// MAX_POSEIDON_ROWS
 const MAX_POSEIDON_ROWS: usize = 15000;
 /// MAX_VERTICAL_CIRCUIT_ROWS
@@ -75,6 +79,8 @@ const MAX_EC_ADD: usize = 10;
 const MAX_EC_MUL: usize = 10;
 /// Max number of EcPairing ops.
 const MAX_EC_PAIRING: usize = 4;
+/// Max number of Blake2f rounds.
+const MAX_BLAKE2F_ROUNDS: usize = 200;
 
 const CIRCUITS_PARAMS: CircuitsParams = CircuitsParams {
     max_rws: MAX_RWS,
@@ -87,6 +93,8 @@ const CIRCUITS_PARAMS: CircuitsParams = CircuitsParams {
     max_evm_rows: MAX_EVM_ROWS,
     max_exp_steps: MAX_EXP_STEPS,
     max_keccak_rows: MAX_KECCAK_ROWS,
+    max_sha256_rows: MAX_SHA256_ROWS,
+    max_ripemd160_rows: MAX_RIPEMD160_ROWS,
     max_poseidon_rows: MAX_POSEIDON_ROWS,
     max_vertical_circuit_rows: MAX_VERTICAL_CIRCUIT_ROWS,
     max_rlp_rows: MAX_RLP_ROWS,
@@ -95,6 +103,7 @@ const CIRCUITS_PARAMS: CircuitsParams = CircuitsParams {
         ec_mul: MAX_EC_MUL,
         ec_pairing: MAX_EC_PAIRING,
     },
+    max_blake2f_rounds: MAX_BLAKE2F_ROUNDS,
 };
 
 const EVM_CIRCUIT_DEGREE: u32 = 18;
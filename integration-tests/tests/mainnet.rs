@@ -31,6 +31,8 @@ const CIRCUITS_PARAMS: CircuitsParams = CircuitsParams {
     max_bytecode: 30000,
     max_mpt_rows: 30000,
     max_keccak_rows: 0,
+    max_sha256_rows: 0,
+    max_ripemd160_rows: 0,
     max_poseidon_rows: 0,
     max_vertical_circuit_rows: 0,
     max_exp_steps: 1000,
@@ -41,6 +43,7 @@ const CIRCUITS_PARAMS: CircuitsParams = CircuitsParams {
         ec_mul: 10,
         ec_pairing: 4,
     },
+    max_blake2f_rounds: 0,
 };
 
 #[tokio::test]
@@ -2,12 +2,13 @@
 
 use super::{MOCK_ACCOUNTS, MOCK_CHAIN_ID, MOCK_GASPRICE};
 use eth_types::{
-    geth_types::Transaction as GethTransaction, word, AccessList, Address, Bytes, Hash,
-    Transaction, Word, U64,
+    geth_types::{get_rlp_signed, get_rlp_unsigned, Transaction as GethTransaction, TxType},
+    word, AccessList, Address, Bytes, Hash, Transaction, Word, H256, U64,
 };
 use ethers_core::{
     rand::{CryptoRng, RngCore},
     types::{OtherFields, TransactionRequest},
+    utils::keccak256,
 };
 use ethers_signers::{LocalWallet, Signer};
 use lazy_static::lazy_static;
@@ -160,6 +161,8 @@ pub struct MockTransaction {
     pub access_list: AccessList,
     pub max_priority_fee_per_gas: Word,
     pub max_fee_per_gas: Word,
+    pub max_fee_per_blob_gas: Word,
+    pub blob_versioned_hashes: Vec<Hash>,
     pub chain_id: u64,
 }
 
@@ -185,6 +188,8 @@ impl Default for MockTransaction {
             access_list: AccessList::default(),
             max_priority_fee_per_gas: Word::zero(),
             max_fee_per_gas: Word::zero(),
+            max_fee_per_blob_gas: Word::zero(),
+            blob_versioned_hashes: Vec::new(),
             chain_id: *MOCK_CHAIN_ID,
         }
     }
@@ -192,6 +197,19 @@ impl Default for MockTransaction {
 
 impl From<MockTransaction> for Transaction {
     fn from(mock: MockTransaction) -> Self {
+        // ethers-rs does not know about the blob fields, which are kept as extra fields the way
+        // they are returned by the JSON-RPC.
+        let mut other = OtherFields::default();
+        if mock.transaction_type == U64::from(3) {
+            other.insert(
+                "maxFeePerBlobGas".to_string(),
+                serde_json::to_value(mock.max_fee_per_blob_gas).expect("serialize word"),
+            );
+            other.insert(
+                "blobVersionedHashes".to_string(),
+                serde_json::to_value(&mock.blob_versioned_hashes).expect("serialize hashes"),
+            );
+        }
         Transaction {
             hash: mock.hash.unwrap_or_default(),
             nonce: mock.nonce,
@@ -212,7 +230,7 @@ impl From<MockTransaction> for Transaction {
             max_priority_fee_per_gas: Some(mock.max_priority_fee_per_gas),
             max_fee_per_gas: Some(mock.max_fee_per_gas),
             chain_id: Some(mock.chain_id.into()),
-            other: OtherFields::default(),
+            other,
         }
     }
 }
@@ -323,6 +341,18 @@ impl MockTransaction {
         self
     }
 
+    /// Set max_fee_per_blob_gas field for the MockTransaction.
+    pub fn max_fee_per_blob_gas(&mut self, max_fee_per_blob_gas: Word) -> &mut Self {
+        self.max_fee_per_blob_gas = max_fee_per_blob_gas;
+        self
+    }
+
+    /// Set blob_versioned_hashes field for the MockTransaction.
+    pub fn blob_versioned_hashes(&mut self, blob_versioned_hashes: Vec<Hash>) -> &mut Self {
+        self.blob_versioned_hashes = blob_versioned_hashes;
+        self
+    }
+
     /// Set chain_id field for the MockTransaction.
     pub fn chain_id(&mut self, chain_id: u64) -> &mut Self {
         self.chain_id = chain_id;
@@ -351,14 +381,21 @@ impl MockTransaction {
             (None, None, None) => {
                 // Compute sig params and set them in case we have a wallet as `from` attr.
                 if self.from.is_wallet() && self.hash.is_none() {
-                    let sig = self
-                        .from
-                        .as_wallet()
-                        .with_chain_id(self.chain_id)
-                        .sign_transaction_sync(&tx.into())
-                        .expect("sign mock tx");
-                    // Set sig parameters
-                    self.sig_data((sig.v, sig.r, sig.s));
+                    let wallet = self.from.as_wallet().with_chain_id(self.chain_id);
+                    let eth_tx = Transaction::from(self.to_owned());
+                    if TxType::get_tx_type(&eth_tx).is_eip4844_tx() {
+                        // ethers-rs would sign a blob tx as a legacy one, so sign its hash, with
+                        // the y-parity as v.
+                        let sighash = H256(keccak256(get_rlp_unsigned(&eth_tx)));
+                        let sig = wallet.sign_hash(sighash).expect("sign mock tx");
+                        self.sig_data((sig.v - 27, sig.r, sig.s));
+                    } else {
+                        let sig = wallet
+                            .sign_transaction_sync(&tx.into())
+                            .expect("sign mock tx");
+                        // Set sig parameters
+                        self.sig_data((sig.v, sig.r, sig.s));
+                    }
                 }
             }
             (Some(_), Some(_), Some(_)) => (),
@@ -370,7 +407,12 @@ impl MockTransaction {
             let tmp_tx = Transaction::from(self.to_owned());
             // FIXME: Note that tmp_tx does not have sigs if self.from.is_wallet() = false.
             //  This means tmp_tx.hash() is not correct.
-            self.hash(tmp_tx.hash());
+            let hash = if TxType::get_tx_type(&tmp_tx).is_eip4844_tx() {
+                H256(keccak256(get_rlp_signed(&tmp_tx)))
+            } else {
+                tmp_tx.hash()
+            };
+            self.hash(hash);
         }
 
         self.to_owned()
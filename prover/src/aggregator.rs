@@ -1,5 +1,7 @@
 mod prover;
+mod tree;
 mod verifier;
 
 pub use self::prover::Prover;
+pub use tree::AggTree;
 pub use verifier::Verifier;
@@ -1,22 +1,23 @@
 use crate::{
     common,
-    config::{LayerId, AGG_DEGREES},
-    consts::{AGG_KECCAK_ROW, AGG_VK_FILENAME, CHUNK_PROTOCOL_FILENAME},
+    config::{tier_degree, tier_layer_id, LayerId, AGG_DEGREES},
+    consts::{agg_vk_filename, AGG_KECCAK_ROW, CHUNK_PROTOCOL_FILENAME},
     io::{force_to_read, try_to_read},
     BatchProof, ChunkProof,
 };
-use aggregator::{ChunkHash, MAX_AGG_SNARKS};
-use anyhow::{bail, Result};
+use aggregator::{agg_snarks_tier, BlobData, ChunkHash, AGG_SNARKS_TIERS, MAX_AGG_SNARKS};
+use anyhow::{anyhow, bail, Result};
 use sha2::{Digest, Sha256};
 use snark_verifier_sdk::Snark;
-use std::{env, iter::repeat};
+use std::{collections::HashMap, env, iter::repeat};
 
 #[derive(Debug)]
 pub struct Prover {
     // Make it public for testing with inner functions (unnecessary for FFI).
     pub inner: common::Prover,
     pub chunk_protocol: Vec<u8>,
-    raw_vk: Option<Vec<u8>>,
+    // Init VKs of the batch proofs, indexed by tier and whether the batch is posted as a blob.
+    raw_vks: HashMap<(usize, bool), Vec<u8>>,
 }
 
 impl Prover {
@@ -26,49 +27,71 @@ impl Prover {
         let inner = common::Prover::from_params_dir(params_dir, &AGG_DEGREES);
         let chunk_protocol = force_to_read(assets_dir, &CHUNK_PROTOCOL_FILENAME);
 
-        let raw_vk = try_to_read(assets_dir, &AGG_VK_FILENAME);
-        if raw_vk.is_none() {
-            log::warn!(
-                "agg-prover: {} doesn't exist in {}",
-                *AGG_VK_FILENAME,
-                assets_dir
-            );
-        }
+        let raw_vks = AGG_SNARKS_TIERS
+            .iter()
+            .flat_map(|&tier| [(tier, false), (tier, true)])
+            .filter_map(|(tier, with_blob)| {
+                let filename = agg_vk_filename(tier, with_blob);
+                let raw_vk = try_to_read(assets_dir, &filename);
+                if raw_vk.is_none() {
+                    log::warn!("agg-prover: {filename} doesn't exist in {assets_dir}");
+                }
+
+                raw_vk.map(|raw_vk| ((tier, with_blob), raw_vk))
+            })
+            .collect();
 
         Self {
             inner,
             chunk_protocol,
-            raw_vk,
+            raw_vks,
         }
     }
 
     // Return true if chunk proofs are valid (same protocol), false otherwise.
+    // See `validate_chunk_proofs` for the reason they are invalid.
     pub fn check_chunk_proofs(&self, chunk_proofs: &[ChunkProof]) -> bool {
-        chunk_proofs.iter().enumerate().all(|(i, proof)| {
-            let result = proof.protocol == self.chunk_protocol;
-            if !result {
-                log::error!(
-                    "Non-match protocol of chunk-proof index-{}: expected = {:x}, actual = {:x}",
-                    i,
+        self.validate_chunk_proofs(chunk_proofs)
+            .map_err(|e| log::error!("{e}"))
+            .is_ok()
+    }
+
+    // Return an error if any chunk proof is incompatible, i.e., of another proof format or
+    // protocol (another chunk circuit build).
+    pub fn validate_chunk_proofs(&self, chunk_proofs: &[ChunkProof]) -> Result<()> {
+        for (i, proof) in chunk_proofs.iter().enumerate() {
+            proof
+                .proof
+                .check_compatibility()
+                .map_err(|e| anyhow!("incompatible chunk-proof index-{i}: {e}"))?;
+
+            if proof.protocol != self.chunk_protocol {
+                bail!(
+                    "non-match protocol of chunk-proof index-{i} (prover {}, vk hash {}): expected = {:x}, actual = {:x}",
+                    proof.proof.git_version.as_deref().unwrap_or("unknown"),
+                    proof.proof.vk_hash,
                     Sha256::digest(&self.chunk_protocol),
                     Sha256::digest(&proof.protocol),
                 );
             }
+        }
 
-            result
-        })
+        Ok(())
     }
 
-    pub fn get_vk(&self) -> Option<Vec<u8>> {
+    // Return the VK of batch proofs generated for `tier`, of batches posted as blobs or not.
+    pub fn get_vk(&self, tier: usize, with_blob: bool) -> Option<Vec<u8>> {
         self.inner
-            .raw_vk(LayerId::Layer4.id())
-            .or_else(|| self.raw_vk.clone())
+            .raw_vk(&tier_layer_id(LayerId::Layer4, tier, with_blob))
+            .or_else(|| self.raw_vks.get(&(tier, with_blob)).cloned())
     }
 
     // Return the EVM proof for verification.
+    // If the batch is posted as an EIP-4844 blob, the proof also proves its point evaluation.
     pub fn gen_agg_evm_proof(
         &mut self,
         chunk_hashes_proofs: Vec<(ChunkHash, ChunkProof)>,
+        blob: Option<&BlobData>,
         name: Option<&str>,
         output_dir: Option<&str>,
     ) -> Result<BatchProof> {
@@ -85,21 +108,26 @@ impl Prover {
             |name| name.to_string(),
         );
 
+        let real_chunk_count = chunk_hashes_proofs.len();
         let layer3_snark =
-            self.load_or_gen_last_agg_snark(&name, chunk_hashes_proofs, output_dir)?;
+            self.load_or_gen_last_agg_snark(&name, chunk_hashes_proofs, blob, output_dir)?;
+        let tier = agg_snarks_tier(real_chunk_count).unwrap();
+        let with_blob = blob.is_some();
 
         // Load or generate final compression thin EVM proof (layer-4).
+        let expected_vk = self.get_vk(tier, with_blob);
         let evm_proof = self.inner.load_or_gen_comp_evm_proof(
             &name,
-            LayerId::Layer4.id(),
+            &tier_layer_id(LayerId::Layer4, tier, with_blob),
             true,
-            LayerId::Layer4.degree(),
+            tier_degree(LayerId::Layer4, tier),
             layer3_snark,
+            expected_vk.as_deref(),
             output_dir,
         )?;
         log::info!("Got final compression thin EVM proof (layer-4): {name}");
 
-        self.check_and_clear_raw_vk();
+        self.check_and_clear_raw_vk(tier, with_blob);
 
         let batch_proof = BatchProof::from(evm_proof.proof);
         if let Some(output_dir) = output_dir {
@@ -111,54 +139,121 @@ impl Prover {
 
     // Generate previous snark before the final one.
     // Then it could be used to generate a normal or EVM proof for verification.
+    // The chunks are padded up to the smallest tier of aggregation circuit that fits them.
     pub fn load_or_gen_last_agg_snark(
         &mut self,
         name: &str,
         chunk_hashes_proofs: Vec<(ChunkHash, ChunkProof)>,
+        blob: Option<&BlobData>,
         output_dir: Option<&str>,
     ) -> Result<Snark> {
         let real_chunk_count = chunk_hashes_proofs.len();
         assert!((1..=MAX_AGG_SNARKS).contains(&real_chunk_count));
+        let tier = agg_snarks_tier(real_chunk_count).unwrap();
 
         check_chunk_hashes(name, &chunk_hashes_proofs)?;
         let (mut chunk_hashes, chunk_proofs): (Vec<_>, Vec<_>) =
             chunk_hashes_proofs.into_iter().unzip();
 
-        if !self.check_chunk_proofs(&chunk_proofs) {
-            bail!("non-match-chunk-protocol: {name}");
-        }
+        self.validate_chunk_proofs(&chunk_proofs)
+            .map_err(|e| anyhow!("non-match-chunk-protocol: {name}: {e}"))?;
 
         let mut layer2_snarks: Vec<_> = chunk_proofs.into_iter().map(|p| p.to_snark()).collect();
 
-        if real_chunk_count < MAX_AGG_SNARKS {
+        if real_chunk_count < tier {
             let padding_snark = layer2_snarks.last().unwrap().clone();
             let mut padding_chunk_hash = *chunk_hashes.last().unwrap();
             padding_chunk_hash.is_padding = true;
 
-            // Extend to MAX_AGG_SNARKS for both chunk hashes and layer-2 snarks.
-            chunk_hashes.extend(repeat(padding_chunk_hash).take(MAX_AGG_SNARKS - real_chunk_count));
-            layer2_snarks.extend(repeat(padding_snark).take(MAX_AGG_SNARKS - real_chunk_count));
+            // Extend to the tier size for both chunk hashes and layer-2 snarks.
+            chunk_hashes.extend(repeat(padding_chunk_hash).take(tier - real_chunk_count));
+            layer2_snarks.extend(repeat(padding_snark).take(tier - real_chunk_count));
         }
 
         // Load or generate aggregation snark (layer-3).
-        let layer3_snark = self.inner.load_or_gen_agg_snark(
+        let layer3_snark = self.load_or_gen_agg_snark_of_tier(
             name,
-            LayerId::Layer3.id(),
-            LayerId::Layer3.degree(),
+            &tier_layer_id(LayerId::Layer3, tier, blob.is_some()),
+            tier,
             &chunk_hashes,
             &layer2_snarks,
+            blob,
             output_dir,
         )?;
-        log::info!("Got aggregation snark (layer-3): {name}");
+        log::info!("Got aggregation snark (layer-3) of tier-{tier}: {name}");
 
         Ok(layer3_snark)
     }
 
-    fn check_and_clear_raw_vk(&mut self) {
-        if self.raw_vk.is_some() {
+    // Load or generate an aggregation snark (layer-3) by the circuit of `tier`. Chunk hashes and
+    // snarks must be padded to the tier size already.
+    pub(super) fn load_or_gen_agg_snark_of_tier(
+        &mut self,
+        name: &str,
+        id: &str,
+        tier: usize,
+        chunk_hashes: &[ChunkHash],
+        snarks: &[Snark],
+        blob: Option<&BlobData>,
+        output_dir: Option<&str>,
+    ) -> Result<Snark> {
+        let degree = tier_degree(LayerId::Layer3, tier);
+        match tier {
+            t if t == AGG_SNARKS_TIERS[0] => {
+                self.inner.load_or_gen_agg_snark::<{ AGG_SNARKS_TIERS[0] }>(
+                    name,
+                    id,
+                    degree,
+                    chunk_hashes,
+                    snarks,
+                    blob,
+                    output_dir,
+                )
+            }
+            t if t == AGG_SNARKS_TIERS[1] => {
+                self.inner.load_or_gen_agg_snark::<{ AGG_SNARKS_TIERS[1] }>(
+                    name,
+                    id,
+                    degree,
+                    chunk_hashes,
+                    snarks,
+                    blob,
+                    output_dir,
+                )
+            }
+            t if t == AGG_SNARKS_TIERS[2] => {
+                self.inner.load_or_gen_agg_snark::<{ AGG_SNARKS_TIERS[2] }>(
+                    name,
+                    id,
+                    degree,
+                    chunk_hashes,
+                    snarks,
+                    blob,
+                    output_dir,
+                )
+            }
+            t if t == AGG_SNARKS_TIERS[3] => {
+                self.inner.load_or_gen_agg_snark::<{ AGG_SNARKS_TIERS[3] }>(
+                    name,
+                    id,
+                    degree,
+                    chunk_hashes,
+                    snarks,
+                    blob,
+                    output_dir,
+                )
+            }
+            _ => unreachable!("Unsupported aggregation tier-{tier}"),
+        }
+    }
+
+    pub(super) fn check_and_clear_raw_vk(&mut self, tier: usize, with_blob: bool) {
+        if let Some(init_vk) = self.raw_vks.remove(&(tier, with_blob)) {
             // Check VK is same with the init one, and take (clear) init VK.
-            let gen_vk = self.inner.raw_vk(LayerId::Layer4.id()).unwrap_or_default();
-            let init_vk = self.raw_vk.take().unwrap_or_default();
+            let gen_vk = self
+                .inner
+                .raw_vk(&tier_layer_id(LayerId::Layer4, tier, with_blob))
+                .unwrap_or_default();
 
             if gen_vk != init_vk {
                 log::error!(
@@ -186,7 +281,10 @@ macro_rules! compare_field {
     };
 }
 
-fn check_chunk_hashes(name: &str, chunk_hashes_proofs: &[(ChunkHash, ChunkProof)]) -> Result<()> {
+pub(super) fn check_chunk_hashes(
+    name: &str,
+    chunk_hashes_proofs: &[(ChunkHash, ChunkProof)],
+) -> Result<()> {
     for (idx, (in_arg, chunk_proof)) in chunk_hashes_proofs.iter().enumerate() {
         if let Some(in_proof) = chunk_proof.chunk_hash {
             compare_field!(name, idx, chain_id, in_arg, in_proof);
@@ -194,6 +292,9 @@ fn check_chunk_hashes(name: &str, chunk_hashes_proofs: &[(ChunkHash, ChunkProof)
             compare_field!(name, idx, post_state_root, in_arg, in_proof);
             compare_field!(name, idx, withdraw_root, in_arg, in_proof);
             compare_field!(name, idx, data_hash, in_arg, in_proof);
+            compare_field!(name, idx, pi_schema, in_arg, in_proof);
+            compare_field!(name, idx, l1_msg_queue_hash, in_arg, in_proof);
+            compare_field!(name, idx, blob_data_commitment, in_arg, in_proof);
         }
     }
 
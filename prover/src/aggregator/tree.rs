@@ -0,0 +1,242 @@
+use super::{prover::check_chunk_hashes, Prover};
+use crate::{
+    config::{tier_degree, tree_layer_id, LayerId},
+    BatchProof, ChunkProof,
+};
+use aggregator::{agg_tree_next_merge_level, chunk_hash_of_batch, ChunkHash, AGG_SNARKS_TIERS};
+use anyhow::{anyhow, bail, Result};
+use eth_types::pi_schema::PiBatchLayout;
+use snark_verifier_sdk::Snark;
+use std::{iter::repeat, slice};
+
+/// State of a tree aggregation of a batch.
+///
+/// Chunk proofs are folded in as they arrive: as soon as `arity` nodes are pending at a level of
+/// the tree, they are aggregated into a single node of the next level. An intermediate node is the
+/// aggregation snark of a range of continuous chunks, and is aggregated again as a chunk whose
+/// hash is `chunk_hash_of_batch` of that range.
+///
+/// Note that the data hash of a batch aggregated by a tree of more than one level is nested, e.g.,
+/// keccak(keccak(chunk[0].data_hash || chunk[1].data_hash) || keccak(chunk[2].data_hash)), rather
+/// than the flat keccak of the chunk data hashes. Its batch proof is marked with the
+/// [`PiBatchLayout::Tree`] layout, see `aggregator::batch_data_hash`.
+#[derive(Debug)]
+pub struct AggTree {
+    name: String,
+    arity: usize,
+    // Pending nodes of each level in chunk order, level 0 being the chunk proofs.
+    levels: Vec<Vec<(ChunkHash, Snark)>>,
+    // The last chunk added, to check the chunks are continuous.
+    last_chunk_hash: Option<ChunkHash>,
+    // Number of intermediate nodes generated, used to name their snarks.
+    num_nodes: usize,
+}
+
+impl AggTree {
+    /// Start a tree aggregation of the batch `name`, which aggregates `arity` nodes at a time.
+    /// `arity` must be one of the tiers of aggregation circuit.
+    pub fn new(name: &str, arity: usize) -> Result<Self> {
+        if arity < 2 || !AGG_SNARKS_TIERS.contains(&arity) {
+            bail!("invalid-tree-arity: {name}, arity = {arity}, tiers = {AGG_SNARKS_TIERS:?}");
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            arity,
+            levels: vec![],
+            last_chunk_hash: None,
+            num_nodes: 0,
+        })
+    }
+
+    /// Number of chunk proofs added to the tree but not aggregated yet.
+    pub fn num_pending_chunks(&self) -> usize {
+        self.levels.first().map_or(0, Vec::len)
+    }
+
+    /// Depth of the tree once all its pending nodes are aggregated into the root.
+    pub fn final_depth(&self) -> usize {
+        final_depth(&self.level_lens(), self.arity)
+    }
+
+    fn push(&mut self, level: usize, chunk_hash: ChunkHash, snark: Snark) {
+        if self.levels.len() <= level {
+            self.levels.resize_with(level + 1, Vec::new);
+        }
+        self.levels[level].push((chunk_hash, snark));
+    }
+
+    fn level_lens(&self) -> Vec<usize> {
+        self.levels.iter().map(Vec::len).collect()
+    }
+}
+
+impl Prover {
+    /// Add the next chunk proof of the batch to the tree, and aggregate the levels that are full.
+    pub fn add_chunk_to_agg_tree(
+        &mut self,
+        tree: &mut AggTree,
+        chunk_hash: ChunkHash,
+        chunk_proof: ChunkProof,
+        output_dir: Option<&str>,
+    ) -> Result<()> {
+        let name = tree.name.clone();
+
+        check_chunk_hashes(&name, &[(chunk_hash, chunk_proof.clone())])?;
+        self.validate_chunk_proofs(slice::from_ref(&chunk_proof))
+            .map_err(|e| anyhow!("non-match-chunk-protocol: {name}: {e}"))?;
+        if let Some(last) = tree.last_chunk_hash {
+            if last.pi_schema != chunk_hash.pi_schema {
+                bail!(
+                    "non-match-pi-schema: {name}, last = {}, chunk = {}",
+                    last.pi_schema,
+                    chunk_hash.pi_schema,
+                );
+            }
+            if last.chain_id != chunk_hash.chain_id
+                || last.post_state_root != chunk_hash.prev_state_root
+            {
+                bail!(
+                    "non-continuous-chunk: {name}, last post_state_root = {}, prev_state_root = {}",
+                    last.post_state_root,
+                    chunk_hash.prev_state_root,
+                );
+            }
+        }
+        tree.last_chunk_hash = Some(chunk_hash);
+
+        tree.push(0, chunk_hash, chunk_proof.to_snark());
+        while let Some(level) = agg_tree_next_merge_level(&tree.level_lens(), tree.arity, false) {
+            self.merge_agg_tree_level(tree, level, output_dir)?;
+        }
+
+        Ok(())
+    }
+
+    /// Aggregate all the pending nodes of the tree into the root, and compress it to the EVM proof
+    /// of the batch.
+    pub fn gen_agg_tree_evm_proof(
+        &mut self,
+        mut tree: AggTree,
+        output_dir: Option<&str>,
+    ) -> Result<BatchProof> {
+        if tree.last_chunk_hash.is_none() {
+            bail!("empty-agg-tree: {}", tree.name);
+        }
+
+        while let Some(level) = agg_tree_next_merge_level(&tree.level_lens(), tree.arity, true) {
+            self.merge_agg_tree_level(&mut tree, level, output_dir)?;
+        }
+
+        // The root is the only node left, and it is not a chunk proof.
+        let root_level = tree.final_depth();
+        let (_, root_snark) = tree.levels[root_level].pop().unwrap();
+        let name = tree.name;
+
+        // Load or generate final compression thin EVM proof (layer-4). A tree of a single level
+        // is compressed by the same circuit as the flat aggregation.
+        let expected_vk = if root_level == 1 {
+            self.get_vk(tree.arity, false)
+        } else {
+            None
+        };
+        let evm_proof = self.inner.load_or_gen_comp_evm_proof(
+            &name,
+            &tree_layer_id(LayerId::Layer4, tree.arity, root_level - 1),
+            true,
+            tier_degree(LayerId::Layer4, tree.arity),
+            root_snark,
+            expected_vk.as_deref(),
+            output_dir,
+        )?;
+        log::info!("Got final compression thin EVM proof (layer-4) of depth-{root_level}: {name}");
+
+        if root_level == 1 {
+            // Same circuit as the flat aggregation.
+            self.check_and_clear_raw_vk(tree.arity, false);
+        }
+
+        // A tree of a single level hashes the chunks flat, as the flat aggregation does.
+        let layout = if root_level == 1 {
+            PiBatchLayout::Flat
+        } else {
+            PiBatchLayout::Tree { arity: tree.arity }
+        };
+        let batch_proof = BatchProof::from(evm_proof.proof).with_layout(layout);
+        if let Some(output_dir) = output_dir {
+            batch_proof.dump(output_dir, "agg")?;
+        }
+
+        Ok(batch_proof)
+    }
+
+    // Aggregate all pending nodes of `level` into a node of the next level.
+    fn merge_agg_tree_level(
+        &mut self,
+        tree: &mut AggTree,
+        level: usize,
+        output_dir: Option<&str>,
+    ) -> Result<()> {
+        let (mut chunk_hashes, mut snarks): (Vec<_>, Vec<_>) = tree.levels[level].drain(..).unzip();
+        let chunk_hash = chunk_hash_of_batch(&chunk_hashes);
+
+        let real_count = chunk_hashes.len();
+        if real_count < tree.arity {
+            let padding_snark = snarks.last().unwrap().clone();
+            let mut padding_chunk_hash = *chunk_hashes.last().unwrap();
+            padding_chunk_hash.is_padding = true;
+
+            chunk_hashes.extend(repeat(padding_chunk_hash).take(tree.arity - real_count));
+            snarks.extend(repeat(padding_snark).take(tree.arity - real_count));
+        }
+
+        let name = format!("{}_node{}", tree.name, tree.num_nodes);
+        let snark = self.load_or_gen_agg_snark_of_tier(
+            &name,
+            &tree_layer_id(LayerId::Layer3, tree.arity, level),
+            tree.arity,
+            &chunk_hashes,
+            &snarks,
+            // tree aggregated batches are not posted as blobs, see `tree_layer_id`
+            None,
+            output_dir,
+        )?;
+        log::info!(
+            "Got aggregation snark (layer-3) of {real_count} nodes at level-{level}: {name}"
+        );
+
+        tree.num_nodes += 1;
+        tree.push(level + 1, chunk_hash, snark);
+
+        Ok(())
+    }
+}
+
+fn final_depth(level_lens: &[usize], arity: usize) -> usize {
+    let mut level_lens = level_lens.to_vec();
+    while let Some(level) = agg_tree_next_merge_level(&level_lens, arity, true) {
+        if level_lens.len() == level + 1 {
+            level_lens.push(0);
+        }
+        level_lens[level] = 0;
+        level_lens[level + 1] += 1;
+    }
+
+    level_lens.len().saturating_sub(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_final_depth() {
+        assert_eq!(final_depth(&[], 4), 0);
+        assert_eq!(final_depth(&[1], 4), 1);
+        assert_eq!(final_depth(&[3], 4), 1);
+        assert_eq!(final_depth(&[0, 1], 4), 1);
+        assert_eq!(final_depth(&[1, 1], 4), 2);
+        assert_eq!(final_depth(&[0, 3, 1], 4), 3);
+        assert_eq!(final_depth(&[2, 0, 1], 4), 3);
+    }
+}
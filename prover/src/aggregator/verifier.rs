@@ -1,7 +1,7 @@
 use crate::{
     common,
-    config::{LAYER4_CONFIG_PATH, LAYER4_DEGREE},
-    consts::{AGG_VK_FILENAME, DEPLOYMENT_CODE_FILENAME},
+    config::{tier_config_path, tier_degree, LayerId},
+    consts::{agg_vk_filename, deployment_code_filename},
     io::force_to_read,
     BatchProof,
 };
@@ -35,12 +35,17 @@ impl Verifier {
         }
     }
 
-    pub fn from_dirs(params_dir: &str, assets_dir: &str) -> Self {
-        let raw_vk = force_to_read(assets_dir, &AGG_VK_FILENAME);
-        let deployment_code = force_to_read(assets_dir, &DEPLOYMENT_CODE_FILENAME);
+    // Load the verifier of batch proofs generated for `tier`, of batches posted as blobs or not.
+    pub fn from_dirs(params_dir: &str, assets_dir: &str, tier: usize, with_blob: bool) -> Self {
+        let raw_vk = force_to_read(assets_dir, &agg_vk_filename(tier, with_blob));
+        let deployment_code = force_to_read(assets_dir, &deployment_code_filename(tier, with_blob));
 
-        env::set_var("COMPRESSION_CONFIG", &*LAYER4_CONFIG_PATH);
-        let inner = common::Verifier::from_params_dir(params_dir, *LAYER4_DEGREE, &raw_vk);
+        env::set_var(
+            "COMPRESSION_CONFIG",
+            tier_config_path(LayerId::Layer4, tier),
+        );
+        let degree = tier_degree(LayerId::Layer4, tier);
+        let inner = common::Verifier::from_params_dir(params_dir, degree, &raw_vk);
 
         Self {
             inner,
@@ -0,0 +1,158 @@
+//! Serve the prover over a local HTTP/JSON API.
+//!
+//! - `POST /chunks` with the block traces of a chunk, i.e., `[BlockTrace]`
+//! - `POST /batches` with `{"chunk_jobs": [id], "blob": BlobData}`, the blob being optional
+//! - `GET /jobs/{id}` for the status of a job
+//! - `GET /jobs/{id}/proof` for the chunk or batch proof of a succeeded job
+//!
+//! The submissions return `{"id": id}`.
+
+use anyhow::Result;
+use clap::Parser;
+use eth_types::l2_types::BlockTrace;
+use prover::{
+    service::{JobRequest, JobStatus, JobStore, Worker},
+    utils::init_env_and_log,
+};
+use serde_derive::Deserialize;
+use serde_json::json;
+use std::{io::Read, sync::Arc};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// Address to listen on
+    #[clap(long, default_value = "127.0.0.1:8560")]
+    listen: String,
+
+    /// Dir of the setup params
+    #[clap(long, default_value = "test_params")]
+    params_dir: String,
+
+    /// Dir of the assets, i.e., the vks and the chunk protocol
+    #[clap(long, default_value = "test_assets")]
+    assets_dir: String,
+
+    /// Dir to persist the jobs in
+    #[clap(long, default_value = "jobs")]
+    jobs_dir: String,
+}
+
+#[derive(Deserialize)]
+struct BatchRequest {
+    chunk_jobs: Vec<String>,
+    #[serde(default)]
+    blob: Option<aggregator::BlobData>,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    init_env_and_log("prover_service");
+
+    let store = Arc::new(JobStore::open(&args.jobs_dir)?);
+    let (jobs, _worker) = Worker::new(store.clone(), &args.params_dir, &args.assets_dir).spawn()?;
+
+    let server = Server::http(&args.listen).map_err(|e| anyhow::anyhow!(e))?;
+    log::info!("prover-service: listening on {}", args.listen);
+
+    for mut request in server.incoming_requests() {
+        let (status, body) = match handle(&store, &mut request) {
+            Ok((status, body, submitted)) => {
+                if let Some(id) = submitted {
+                    jobs.send(id)?;
+                }
+                (status, body)
+            }
+            Err(e) => {
+                log::error!("prover-service: failed to handle {}: {e:#}", request.url());
+                (500, json!({ "error": format!("{e:#}") }))
+            }
+        };
+
+        let response = Response::from_string(body.to_string())
+            .with_status_code(status)
+            .with_header("Content-Type: application/json".parse::<Header>().unwrap());
+        if let Err(e) = request.respond(response) {
+            log::warn!("prover-service: failed to respond: {e}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle `request`, returning the status code, the body and the id of the submitted job (if any).
+/// Invalid requests are answered with 400, an `Err` is a failure of the service itself.
+fn handle(
+    store: &JobStore,
+    request: &mut Request,
+) -> Result<(u16, serde_json::Value, Option<String>)> {
+    let method = request.method().clone();
+    let url = request.url().to_string();
+    let segments: Vec<&str> = url.trim_matches('/').split('/').collect();
+
+    match (method, segments.as_slice()) {
+        (Method::Post, ["chunks"]) => {
+            let job =
+                read_json(request).map(|traces: Vec<BlockTrace>| JobRequest::Chunk { traces });
+            submit(store, job)
+        }
+        (Method::Post, ["batches"]) => {
+            let job = read_json(request)
+                .map(|BatchRequest { chunk_jobs, blob }| JobRequest::Batch { chunk_jobs, blob });
+            submit(store, job)
+        }
+        (Method::Get, ["jobs", id]) if store.contains(id) => {
+            let status = store.status(id)?;
+            let mut body = serde_json::to_value(status)?;
+            body["id"] = json!(id);
+            Ok((200, body, None))
+        }
+        (Method::Get, ["jobs", id, "proof"]) if store.contains(id) => {
+            if store.status(id)? != JobStatus::Succeeded {
+                return Ok((
+                    409,
+                    json!({ "error": format!("job {id} has not succeeded") }),
+                    None,
+                ));
+            }
+            let proof = if store.request(id)?.is_chunk() {
+                serde_json::to_value(store.chunk_proof(id)?)?
+            } else {
+                serde_json::to_value(store.batch_proof(id)?)?
+            };
+            Ok((200, proof, None))
+        }
+        (Method::Get, ["jobs", id, ..]) if !store.contains(id) => {
+            Ok((404, json!({ "error": format!("unknown job {id}") }), None))
+        }
+        _ => Ok((
+            404,
+            json!({ "error": format!("unknown route {url}") }),
+            None,
+        )),
+    }
+}
+
+/// Submit the job parsed from a request, rejecting it with 400 if it is malformed or invalid.
+fn submit(
+    store: &JobStore,
+    job: Result<JobRequest>,
+) -> Result<(u16, serde_json::Value, Option<String>)> {
+    let job = match job.and_then(|job| store.validate(&job).map(|_| job)) {
+        Ok(job) => job,
+        Err(e) => return Ok((400, json!({ "error": format!("{e:#}") }), None)),
+    };
+    let id = store.submit(&job)?;
+    Ok((200, json!({ "id": id }), Some(id)))
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(request: &mut Request) -> Result<T> {
+    let mut buf = vec![];
+    request.as_reader().read_to_end(&mut buf)?;
+
+    let mut deserializer = serde_json::Deserializer::from_slice(&buf);
+    deserializer.disable_recursion_limit();
+    let deserializer = serde_stacker::Deserializer::new(&mut deserializer);
+    Ok(serde::Deserialize::deserialize(deserializer)?)
+}
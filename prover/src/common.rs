@@ -1,5 +1,8 @@
 mod prover;
 mod verifier;
 
-pub use self::{prover::Prover, verifier::Verifier};
+pub use self::{
+    prover::{pk_disk_cache_key, Prover},
+    verifier::Verifier,
+};
 pub use aggregator::{ChunkHash, CompressionCircuit};
@@ -1,4 +1,7 @@
-use crate::utils::{load_params, param_path_for_degree};
+use crate::{
+    config::PK_DISK_CACHE_DIR,
+    utils::{load_params, param_path_for_degree},
+};
 use halo2_proofs::{
     halo2curves::bn256::{Bn256, G1Affine},
     plonk::ProvingKey,
@@ -12,14 +15,19 @@ mod compression;
 mod evm;
 mod inner;
 mod mock;
+mod pk_disk_cache;
 mod utils;
 
+pub use pk_disk_cache::pk_disk_cache_key;
+
 #[derive(Debug)]
 pub struct Prover {
     // degree -> params (use BTreeMap to find proper degree for params downsize)
     params_map: BTreeMap<u32, ParamsKZG<Bn256>>,
     // Cached id -> pk
     pk_map: HashMap<String, ProvingKey<G1Affine>>,
+    // Dir to persist pks across restarts
+    pk_disk_cache_dir: Option<String>,
 }
 
 impl Prover {
@@ -27,6 +35,7 @@ impl Prover {
         Self {
             params_map,
             pk_map: HashMap::new(),
+            pk_disk_cache_dir: PK_DISK_CACHE_DIR.clone(),
         }
     }
 
@@ -65,6 +74,7 @@ impl Prover {
         Self {
             params_map,
             pk_map: HashMap::new(),
+            pk_disk_cache_dir: PK_DISK_CACHE_DIR.clone(),
         }
     }
 }
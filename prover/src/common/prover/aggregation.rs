@@ -4,24 +4,28 @@ use crate::{
     io::{load_snark, write_snark},
     utils::gen_rng,
 };
-use aggregator::{AggregationCircuit, BatchHash, ChunkHash};
+use aggregator::{AggregationCircuit, BatchHash, BlobData, ChunkHash};
 use anyhow::{anyhow, Result};
 use rand::Rng;
 use snark_verifier_sdk::Snark;
 use std::env;
 
 impl Prover {
-    pub fn gen_agg_snark(
+    pub fn gen_agg_snark<const N_SNARKS: usize>(
         &mut self,
         id: &str,
         degree: u32,
         mut rng: impl Rng + Send,
         chunk_hashes: &[ChunkHash],
         previous_snarks: &[Snark],
+        blob: Option<&BlobData>,
     ) -> Result<Snark> {
         env::set_var("AGGREGATION_CONFIG", layer_config_path(id));
 
-        let batch_hash = BatchHash::construct(chunk_hashes);
+        let batch_hash = match blob {
+            Some(blob) => BatchHash::<N_SNARKS>::construct_with_blob(chunk_hashes, blob.clone()),
+            None => BatchHash::<N_SNARKS>::construct(chunk_hashes),
+        };
 
         let circuit =
             AggregationCircuit::new(self.params(degree), previous_snarks, &mut rng, batch_hash)
@@ -30,13 +34,14 @@ impl Prover {
         self.gen_snark(id, degree, &mut rng, circuit)
     }
 
-    pub fn load_or_gen_agg_snark(
+    pub fn load_or_gen_agg_snark<const N_SNARKS: usize>(
         &mut self,
         name: &str,
         id: &str,
         degree: u32,
         chunk_hashes: &[ChunkHash],
         previous_snarks: &[Snark],
+        blob: Option<&BlobData>,
         output_dir: Option<&str>,
     ) -> Result<Snark> {
         let file_path = format!(
@@ -50,7 +55,14 @@ impl Prover {
             Some(snark) => Ok(snark),
             None => {
                 let rng = gen_rng();
-                let result = self.gen_agg_snark(id, degree, rng, chunk_hashes, previous_snarks);
+                let result = self.gen_agg_snark::<N_SNARKS>(
+                    id,
+                    degree,
+                    rng,
+                    chunk_hashes,
+                    previous_snarks,
+                    blob,
+                );
                 if let (Some(_), Ok(snark)) = (output_dir, &result) {
                     write_snark(&file_path, snark);
                 }
@@ -8,6 +8,9 @@ use snark_verifier_sdk::{gen_evm_proof_shplonk, CircuitExt, Snark};
 use std::env;
 
 impl Prover {
+    /// Load the EVM proof of `name` from `output_dir`, unless it is not generated with
+    /// `expected_vk` (if known), or generate it.
+    #[allow(clippy::too_many_arguments)]
     pub fn load_or_gen_comp_evm_proof(
         &mut self,
         name: &str,
@@ -15,10 +18,14 @@ impl Prover {
         has_accumulator: bool,
         degree: u32,
         prev_snark: Snark,
+        expected_vk: Option<&[u8]>,
         output_dir: Option<&str>,
     ) -> Result<EvmProof> {
         let name = format!("{id}_{name}");
-        match output_dir.and_then(|output_dir| EvmProof::from_json_file(output_dir, &name).ok()) {
+        let expected_vk = self.raw_vk(id).or_else(|| expected_vk.map(<[u8]>::to_vec));
+        match output_dir.and_then(|output_dir| {
+            EvmProof::from_json_file(output_dir, &name, expected_vk.as_deref()).ok()
+        }) {
             Some(proof) => Ok(proof),
             None => {
                 env::set_var("COMPRESSION_CONFIG", layer_config_path(id));
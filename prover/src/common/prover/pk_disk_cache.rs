@@ -0,0 +1,244 @@
+use super::Prover;
+use crate::{
+    config::{tier_config_path, LayerId, LAYER1_CONFIG_PATH, LAYER2_CONFIG_PATH},
+    utils::{DEFAULT_SERDE_FORMAT, GIT_VERSION},
+};
+use aggregator::AGG_SNARKS_TIERS;
+use anyhow::{bail, Result};
+use eth_types::pi_schema::PI_SCHEMA;
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{Circuit, ConstraintSystem, ProvingKey},
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+};
+use memmap2::Mmap;
+use sha2::{Digest, Sha256};
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Cursor, Write},
+    path::PathBuf,
+};
+
+/// Length of the key at the head of a cached pk file.
+const PK_DISK_CACHE_KEY_LEN: usize = 32;
+
+/// Key of the pk of circuit `C` on disk, i.e., a hash of
+/// - the prover version,
+/// - the params (SRS), by their degree and `[s]_2`,
+/// - the public input schema, which lays out the pi hashes of the chunk and aggregation circuits,
+/// - the constraint system of `C`, and
+/// - the layer configs, which set the circuits up, and since a layer embeds the vk of the layer it
+///   compresses or aggregates.
+///
+/// It is cheap to compute, so that a cached pk is loaded without generating anything.
+pub fn pk_disk_cache_key<C: Circuit<Fr>>(params: &ParamsKZG<Bn256>) -> [u8; PK_DISK_CACHE_KEY_LEN] {
+    let mut cs = ConstraintSystem::default();
+    C::configure(&mut cs);
+
+    let mut hasher = Sha256::new();
+    hasher.update(GIT_VERSION.as_bytes());
+    hasher.update(params.k().to_be_bytes());
+    hasher.update(format!("{:?}", params.s_g2()).as_bytes());
+    hasher.update(PI_SCHEMA.to_string().as_bytes());
+    hasher.update(format!("{:?}", cs.pinned()).as_bytes());
+    let tier_config_paths = AGG_SNARKS_TIERS.iter().flat_map(|&tier| {
+        [LayerId::Layer3, LayerId::Layer4].map(|layer_id| tier_config_path(layer_id, tier))
+    });
+    for config_path in [LAYER1_CONFIG_PATH.clone(), LAYER2_CONFIG_PATH.clone()]
+        .into_iter()
+        .chain(tier_config_paths)
+    {
+        if let Ok(config) = fs::read(config_path) {
+            hasher.update(config);
+        }
+    }
+
+    hasher.finalize().into()
+}
+
+impl Prover {
+    /// Persist the generated pks in `pk_disk_cache_dir`, and load them from there (memory-mapped)
+    /// the first time they are used, instead of regenerating them. `None` disables the cache.
+    pub fn set_pk_disk_cache_dir(&mut self, pk_disk_cache_dir: Option<&str>) {
+        self.pk_disk_cache_dir = pk_disk_cache_dir.map(ToString::to_string);
+    }
+
+    fn pk_disk_cache_path(&self, id: &str) -> Option<PathBuf> {
+        self.pk_disk_cache_dir
+            .as_ref()
+            .map(|dir| PathBuf::from(dir).join(format!("{id}.pk")))
+    }
+
+    /// Load the cached pk of `id`, if any and its key is `key`. A cached pk which cannot be read
+    /// fails, so that the caller regenerates and caches it again.
+    pub(super) fn load_cached_pk<C: Circuit<Fr>>(
+        &self,
+        id: &str,
+        key: &[u8; PK_DISK_CACHE_KEY_LEN],
+    ) -> Result<Option<ProvingKey<G1Affine>>> {
+        let path = match self.pk_disk_cache_path(id) {
+            Some(path) if path.exists() => path,
+            _ => return Ok(None),
+        };
+
+        let file = File::open(&path)?;
+        // Safety: the cache file is only replaced by rename, never modified in place.
+        let mmap = unsafe { Mmap::map(&file)? };
+        if mmap.len() < PK_DISK_CACHE_KEY_LEN || mmap[..PK_DISK_CACHE_KEY_LEN] != key[..] {
+            log::warn!(
+                "Cached pk of {id} in {} mismatches the params or circuit config, regenerate it",
+                path.display()
+            );
+            return Ok(None);
+        }
+
+        log::info!("Before load pk of {id} from {}", path.display());
+        let mut reader = Cursor::new(&mmap[PK_DISK_CACHE_KEY_LEN..]);
+        let pk = ProvingKey::<G1Affine>::read::<_, C>(&mut reader, DEFAULT_SERDE_FORMAT)?;
+        if reader.position() != reader.get_ref().len() as u64 {
+            bail!("trailing bytes after the pk in {}", path.display());
+        }
+        log::info!("After load pk of {id}");
+
+        Ok(Some(pk))
+    }
+
+    /// Cache `pk` of `id` with `key`, replacing the previous one (if any).
+    pub(super) fn cache_pk(
+        &self,
+        id: &str,
+        key: &[u8; PK_DISK_CACHE_KEY_LEN],
+        pk: &ProvingKey<G1Affine>,
+    ) -> Result<()> {
+        let path = match self.pk_disk_cache_path(id) {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        fs::create_dir_all(path.parent().unwrap())?;
+
+        // Write to a temporary file first, so that other provers sharing the cache never read a
+        // partial pk.
+        let tmp_path = path.with_extension(format!("pk.{}.tmp", std::process::id()));
+        {
+            let mut writer = BufWriter::new(File::create(&tmp_path)?);
+            writer.write_all(key)?;
+            pk.write(&mut writer, DEFAULT_SERDE_FORMAT)?;
+            writer.flush()?;
+        }
+        fs::rename(&tmp_path, &path)?;
+        log::info!("Cached pk of {id} in {}", path.display());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::serialize_vk;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Column, Error, Fixed},
+        poly::Rotation,
+    };
+    use rand::rngs::OsRng;
+    use std::collections::BTreeMap;
+
+    const DEGREE: u32 = 4;
+
+    // Constrain an advice cell to the fixed `value`.
+    #[derive(Clone)]
+    struct TestCircuit {
+        value: u64,
+    }
+
+    impl Circuit<Fr> for TestCircuit {
+        type Config = (Column<Advice>, Column<Fixed>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let advice = meta.advice_column();
+            let fixed = meta.fixed_column();
+            meta.create_gate("advice = fixed", |meta| {
+                let advice = meta.query_advice(advice, Rotation::cur());
+                let fixed = meta.query_fixed(fixed, Rotation::cur());
+                vec![advice - fixed]
+            });
+            (advice, fixed)
+        }
+
+        fn synthesize(
+            &self,
+            (advice, fixed): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "advice = fixed",
+                |mut region| {
+                    let value = Value::known(Fr::from(self.value));
+                    region.assign_fixed(|| "fixed", fixed, 0, || value)?;
+                    region.assign_advice(|| "advice", advice, 0, || value)?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_pk_disk_cache() {
+        let dir = std::env::temp_dir().join(format!("pk_disk_cache_{}", std::process::id()));
+        let params = ParamsKZG::<Bn256>::setup(DEGREE, OsRng);
+        let mut prover = Prover::from_params(BTreeMap::from([(DEGREE, params.clone())]));
+        prover.set_pk_disk_cache_dir(dir.to_str());
+
+        let circuit = TestCircuit { value: 1 };
+        let vk = prover
+            .params_and_pk("test", DEGREE, &circuit)
+            .unwrap()
+            .1
+            .get_vk()
+            .clone();
+        let key = pk_disk_cache_key::<TestCircuit>(&params);
+
+        // the generated pk is loaded back
+        let pk = prover
+            .load_cached_pk::<TestCircuit>("test", &key)
+            .unwrap()
+            .expect("pk is cached");
+        assert_eq!(serialize_vk(pk.get_vk()), serialize_vk(&vk));
+
+        // the pk with other params misses the cache
+        let other_params = ParamsKZG::<Bn256>::setup(DEGREE, OsRng);
+        let other_key = pk_disk_cache_key::<TestCircuit>(&other_params);
+        assert_ne!(other_key, key);
+        assert!(prover
+            .load_cached_pk::<TestCircuit>("test", &other_key)
+            .unwrap()
+            .is_none());
+
+        // a corrupted pk fails to load, so it is regenerated and cached again
+        let path = prover.pk_disk_cache_path("test").unwrap();
+        let mut corrupted = fs::read(&path).unwrap();
+        corrupted.truncate(corrupted.len() / 2);
+        fs::write(&path, corrupted).unwrap();
+        assert!(prover.load_cached_pk::<TestCircuit>("test", &key).is_err());
+        prover.clear_pks();
+        let regenerated_vk = prover
+            .params_and_pk("test", DEGREE, &circuit)
+            .unwrap()
+            .1
+            .get_vk()
+            .clone();
+        assert_eq!(serialize_vk(&regenerated_vk), serialize_vk(&vk));
+        assert!(prover
+            .load_cached_pk::<TestCircuit>("test", &key)
+            .unwrap()
+            .is_some());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
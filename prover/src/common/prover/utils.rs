@@ -1,4 +1,4 @@
-use super::Prover;
+use super::{pk_disk_cache::pk_disk_cache_key, Prover};
 use crate::io::serialize_vk;
 use anyhow::Result;
 use halo2_proofs::{
@@ -61,9 +61,25 @@ impl Prover {
             return Ok((&self.params_map[&degree], &self.pk_map[id]));
         }
 
-        log::info!("Before generate pk of {}", &id);
-        let pk = keygen_pk2(self.params(degree), circuit)?;
-        log::info!("After generate pk of {}", &id);
+        self.params(degree);
+        let params = &self.params_map[&degree];
+
+        let key = pk_disk_cache_key::<C>(params);
+        let cached_pk = self.load_cached_pk::<C>(id, &key).unwrap_or_else(|e| {
+            log::warn!("Failed to load cached pk of {id}, regenerate it: {e}");
+            None
+        });
+        let pk = match cached_pk {
+            Some(pk) => pk,
+            None => {
+                log::info!("Before generate pk of {}", &id);
+                let pk = keygen_pk2(params, circuit)?;
+                log::info!("After generate pk of {}", &id);
+
+                self.cache_pk(id, &key, &pk)?;
+                pk
+            }
+        };
 
         self.pk_map.insert(id.to_string(), pk);
 
@@ -74,6 +90,7 @@ impl Prover {
         self.pk_map.get(id).map(|pk| serialize_vk(pk.get_vk()))
     }
 
+    /// Drop the pks in memory, the cached ones on disk (if any) are kept.
     pub fn clear_pks(&mut self) {
         self.pk_map.clear();
     }
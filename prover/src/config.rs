@@ -1,22 +1,34 @@
 use crate::utils::read_env_var;
-use aggregator::ConfigParams;
+use aggregator::{ConfigParams, AGG_SNARKS_TIERS};
 use once_cell::sync::Lazy;
-use std::{collections::HashSet, fmt, fs::File, path::Path};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    fs::File,
+    path::Path,
+    sync::Mutex,
+};
 
 pub static INNER_DEGREE: Lazy<u32> = Lazy::new(|| read_env_var("SCROLL_PROVER_INNER_DEGREE", 20));
 
 pub static ASSETS_DIR: Lazy<String> =
     Lazy::new(|| read_env_var("SCROLL_PROVER_ASSETS_DIR", "configs".to_string()));
 
+/// Dir to persist the proving keys in, see `common::Prover::set_pk_disk_cache_dir`. Unset (or
+/// empty) to disable the cache.
+pub static PK_DISK_CACHE_DIR: Lazy<Option<String>> = Lazy::new(|| {
+    Some(read_env_var(
+        "SCROLL_PROVER_PK_DISK_CACHE_DIR",
+        String::new(),
+    ))
+    .filter(|dir| !dir.is_empty())
+});
+
 pub static LAYER1_CONFIG_PATH: Lazy<String> = Lazy::new(|| asset_file_path("layer1.config"));
 pub static LAYER2_CONFIG_PATH: Lazy<String> = Lazy::new(|| asset_file_path("layer2.config"));
-pub static LAYER3_CONFIG_PATH: Lazy<String> = Lazy::new(|| asset_file_path("layer3.config"));
-pub static LAYER4_CONFIG_PATH: Lazy<String> = Lazy::new(|| asset_file_path("layer4.config"));
 
 pub static LAYER1_DEGREE: Lazy<u32> = Lazy::new(|| layer_degree(&LAYER1_CONFIG_PATH));
 pub static LAYER2_DEGREE: Lazy<u32> = Lazy::new(|| layer_degree(&LAYER2_CONFIG_PATH));
-pub static LAYER3_DEGREE: Lazy<u32> = Lazy::new(|| layer_degree(&LAYER3_CONFIG_PATH));
-pub static LAYER4_DEGREE: Lazy<u32> = Lazy::new(|| layer_degree(&LAYER4_CONFIG_PATH));
 
 pub static ZKEVM_DEGREES: Lazy<Vec<u32>> = Lazy::new(|| {
     Vec::from_iter(HashSet::from([
@@ -26,8 +38,22 @@ pub static ZKEVM_DEGREES: Lazy<Vec<u32>> = Lazy::new(|| {
     ]))
 });
 
-pub static AGG_DEGREES: Lazy<Vec<u32>> =
-    Lazy::new(|| Vec::from_iter(HashSet::from([*LAYER3_DEGREE, *LAYER4_DEGREE])));
+/// Degrees of the aggregation (layer-3) and batch compression (layer-4) circuits read so far,
+/// indexed by the ids of the tiers, e.g. `layer3_8`. The config of a tier is only read when the
+/// tier is used, see [`tier_degree`].
+static TIER_DEGREES: Lazy<Mutex<HashMap<String, u32>>> = Lazy::new(Default::default);
+
+/// Degrees of the aggregation (layer-3) and batch compression (layer-4) circuits of the tiers
+/// whose configs are in the assets dir. The params of another degree are downsized from these
+/// when its tier is used.
+pub static AGG_DEGREES: Lazy<Vec<u32>> = Lazy::new(|| {
+    let degrees = AGG_SNARKS_TIERS
+        .iter()
+        .flat_map(|&tier| [LayerId::Layer3, LayerId::Layer4].map(|layer_id| (layer_id, tier)))
+        .filter(|&(layer_id, tier)| Path::new(&tier_config_path(layer_id, tier)).exists())
+        .map(|(layer_id, tier)| tier_degree(layer_id, tier));
+    Vec::from_iter(HashSet::<u32>::from_iter(degrees))
+});
 
 #[derive(Clone, Copy, Debug)]
 pub enum LayerId {
@@ -65,8 +91,7 @@ impl LayerId {
             Self::Inner => *INNER_DEGREE,
             Self::Layer1 => *LAYER1_DEGREE,
             Self::Layer2 => *LAYER2_DEGREE,
-            Self::Layer3 => *LAYER3_DEGREE,
-            Self::Layer4 => *LAYER4_DEGREE,
+            Self::Layer3 | Self::Layer4 => unreachable!("Degree of {self} depends on the tier"),
         }
     }
 
@@ -74,8 +99,9 @@ impl LayerId {
         match self {
             Self::Layer1 => &LAYER1_CONFIG_PATH,
             Self::Layer2 => &LAYER2_CONFIG_PATH,
-            Self::Layer3 => &LAYER3_CONFIG_PATH,
-            Self::Layer4 => &LAYER4_CONFIG_PATH,
+            Self::Layer3 | Self::Layer4 => {
+                unreachable!("Config file of {self} depends on the tier")
+            }
             Self::Inner => unreachable!("No config file for super (inner) circuit"),
         }
     }
@@ -88,12 +114,60 @@ pub fn asset_file_path(filename: &str) -> String {
         .into_owned()
 }
 
-pub fn layer_config_path(id: &str) -> &str {
-    match id {
-        "layer1" => &LAYER1_CONFIG_PATH,
-        "layer2" => &LAYER2_CONFIG_PATH,
-        "layer3" => &LAYER3_CONFIG_PATH,
-        "layer4" => &LAYER4_CONFIG_PATH,
+/// Id of the aggregation (layer-3) or batch compression (layer-4) circuit generated for a
+/// tier of `aggregator::AGG_SNARKS_TIERS`, e.g. `layer3_8`.
+///
+/// The circuits of batches posted as EIP-4844 blobs also prove the point evaluation of the blob,
+/// which changes their shape and instances, so they have their own ids, e.g. `layer3_8_blob`,
+/// hence their own keys and verifiers.
+pub fn tier_layer_id(layer_id: LayerId, tier: usize, with_blob: bool) -> String {
+    match with_blob {
+        false => format!("{}_{tier}", layer_id.id()),
+        true => format!("{}_{tier}_blob", layer_id.id()),
+    }
+}
+
+/// Id of the circuit of a layer in tree aggregation, whose inputs are the nodes at `level` of the
+/// tree (level 0 being the chunk proofs). Circuits at level 0 are the same as those of flat
+/// aggregation. Tree aggregated batches are not posted as blobs.
+pub fn tree_layer_id(layer_id: LayerId, tier: usize, level: usize) -> String {
+    match level {
+        0 => tier_layer_id(layer_id, tier, false),
+        _ => format!("{}_level{level}", tier_layer_id(layer_id, tier, false)),
+    }
+}
+
+/// Config file of the aggregation (layer-3) or batch compression (layer-4) circuits of a tier,
+/// e.g. `layer3_8.config`, since the number of aggregated snarks sets the size of the circuit.
+/// The blob and tree circuits of a tier share its config.
+pub fn tier_config_path(layer_id: LayerId, tier: usize) -> String {
+    asset_file_path(&format!("{}.config", tier_layer_id(layer_id, tier, false)))
+}
+
+/// Degree of the aggregation (layer-3) or batch compression (layer-4) circuit of a tier, read
+/// from its config on the first use of the tier.
+pub fn tier_degree(layer_id: LayerId, tier: usize) -> u32 {
+    assert!(
+        AGG_SNARKS_TIERS.contains(&tier),
+        "No degree of {layer_id}, tier-{tier} is not supported"
+    );
+    *TIER_DEGREES
+        .lock()
+        .unwrap()
+        .entry(tier_layer_id(layer_id, tier, false))
+        .or_insert_with(|| layer_degree(&tier_config_path(layer_id, tier)))
+}
+
+pub fn layer_config_path(id: &str) -> String {
+    // Aggregation and batch compression ids are suffixed by the tier, then by the blob or tree
+    // level suffix (if any).
+    let mut parts = id.split('_');
+    match (parts.next(), parts.next()) {
+        (Some("layer1"), _) => LAYER1_CONFIG_PATH.clone(),
+        (Some("layer2"), _) => LAYER2_CONFIG_PATH.clone(),
+        (Some(layer @ ("layer3" | "layer4")), Some(tier)) => {
+            asset_file_path(&format!("{layer}_{tier}.config"))
+        }
         _ => panic!("Wrong id-{id} to get layer config path"),
     }
 }
@@ -106,3 +180,29 @@ fn layer_degree(config_file: &str) -> u32 {
 
     params.degree
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layer_config_path() {
+        assert_eq!(layer_config_path("layer2"), *LAYER2_CONFIG_PATH);
+        for tier in AGG_SNARKS_TIERS {
+            for layer_id in [LayerId::Layer3, LayerId::Layer4] {
+                let config_path = tier_config_path(layer_id, tier);
+                assert_eq!(
+                    config_path,
+                    asset_file_path(&format!("{}_{tier}.config", layer_id.id()))
+                );
+                for id in [
+                    tier_layer_id(layer_id, tier, false),
+                    tier_layer_id(layer_id, tier, true),
+                    tree_layer_id(layer_id, tier, 2),
+                ] {
+                    assert_eq!(layer_config_path(&id), config_path, "{id}");
+                }
+            }
+        }
+    }
+}
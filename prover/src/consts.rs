@@ -10,3 +10,24 @@ pub static CHUNK_VK_FILENAME: Lazy<String> =
     Lazy::new(|| read_env_var("CHUNK_VK_FILENAME", "chunk_vk.vkey".to_string()));
 pub static DEPLOYMENT_CODE_FILENAME: Lazy<String> =
     Lazy::new(|| read_env_var("DEPLOYMENT_CODE_FILENAME", "evm_verifier.bin".to_string()));
+
+/// Name of a per-tier asset of the batch prover, e.g. `agg_vk_8.vkey` for `agg_vk.vkey`, or
+/// `agg_vk_8_blob.vkey` for the batches posted as blobs.
+fn tier_filename(filename: &str, tier: usize, with_blob: bool) -> String {
+    let suffix = match with_blob {
+        false => tier.to_string(),
+        true => format!("{tier}_blob"),
+    };
+    match filename.rsplit_once('.') {
+        Some((stem, ext)) => format!("{stem}_{suffix}.{ext}"),
+        None => format!("{filename}_{suffix}"),
+    }
+}
+
+pub fn agg_vk_filename(tier: usize, with_blob: bool) -> String {
+    tier_filename(&AGG_VK_FILENAME, tier, with_blob)
+}
+
+pub fn deployment_code_filename(tier: usize, with_blob: bool) -> String {
+    tier_filename(&DEPLOYMENT_CODE_FILENAME, tier, with_blob)
+}
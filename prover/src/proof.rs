@@ -9,6 +9,7 @@ use halo2_proofs::{
     plonk::{Circuit, ProvingKey, VerifyingKey},
 };
 use serde_derive::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use snark_verifier::{
     util::{
         arithmetic::Domain,
@@ -30,6 +31,10 @@ pub use batch::BatchProof;
 pub use chunk::ChunkProof;
 pub use evm::EvmProof;
 
+/// Version of the serialized proofs, bumped on any change of their format or meaning. The proofs
+/// dumped before versioning are of version 0.
+pub const PROOF_FORMAT_VERSION: u32 = 1;
+
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct Proof {
     #[serde(with = "base64")]
@@ -39,37 +44,78 @@ pub struct Proof {
     #[serde(with = "base64")]
     vk: Vec<u8>,
     pub git_version: Option<String>,
+    #[serde(default)]
+    pub format_version: u32,
+    /// Hex of the SHA256 of the vk, empty if the proof has no vk
+    #[serde(default)]
+    pub vk_hash: String,
 }
 
 impl Proof {
     pub fn new(proof: Vec<u8>, instances: &[Vec<Fr>], pk: Option<&ProvingKey<G1Affine>>) -> Self {
         let instances = serialize_instances(instances);
         let vk = pk.map_or_else(Vec::new, |pk| serialize_vk(pk.get_vk()));
-        let git_version = Some(short_git_version());
 
+        Self::from_raw(proof, instances, vk)
+    }
+
+    fn from_raw(proof: Vec<u8>, instances: Vec<u8>, vk: Vec<u8>) -> Self {
         Self {
             proof,
             instances,
+            vk_hash: vk_hash(&vk),
             vk,
-            git_version,
+            git_version: Some(short_git_version()),
+            format_version: PROOF_FORMAT_VERSION,
         }
     }
 
     pub fn from_json_file(dir: &str, filename: &str) -> Result<Self> {
-        from_json_file(dir, filename)
+        let proof: Self = from_json_file(dir, filename)?;
+        proof.check_compatibility()?;
+
+        Ok(proof)
     }
 
     pub fn from_snark(snark: Snark, vk: Vec<u8>) -> Self {
-        let proof = snark.proof;
         let instances = serialize_instances(&snark.instances);
-        let git_version = Some(short_git_version());
 
-        Proof {
-            proof,
-            instances,
-            vk,
-            git_version,
+        Self::from_raw(snark.proof, instances, vk)
+    }
+
+    /// Check the proof is of the current format, and its vk is the one it was generated with.
+    pub fn check_compatibility(&self) -> Result<()> {
+        let git_version = self.git_version.as_deref().unwrap_or("unknown");
+        if self.format_version != PROOF_FORMAT_VERSION {
+            bail!(
+                "incompatible proof format version {} (prover {git_version}), expect {PROOF_FORMAT_VERSION}",
+                self.format_version,
+            );
+        }
+        if self.vk_hash != vk_hash(&self.vk) {
+            bail!(
+                "vk hash {} of proof (prover {git_version}) mismatches its vk {}",
+                self.vk_hash,
+                vk_hash(&self.vk),
+            );
         }
+
+        Ok(())
+    }
+
+    /// Check the proof is compatible, and generated with `expected_vk`.
+    pub fn check_vk(&self, expected_vk: &[u8]) -> Result<()> {
+        self.check_compatibility()?;
+        if self.vk_hash != vk_hash(expected_vk) {
+            bail!(
+                "vk hash {} of proof (prover {}) mismatches the expected {}",
+                self.vk_hash,
+                self.git_version.as_deref().unwrap_or("unknown"),
+                vk_hash(expected_vk),
+            );
+        }
+
+        Ok(())
     }
 
     pub fn dump(&self, dir: &str, filename: &str) -> Result<()> {
@@ -115,6 +161,15 @@ impl Proof {
     }
 }
 
+/// Hex of the SHA256 of `raw_vk`, or empty for an empty vk.
+pub fn vk_hash(raw_vk: &[u8]) -> String {
+    if raw_vk.is_empty() {
+        String::new()
+    } else {
+        hex::encode(Sha256::digest(raw_vk))
+    }
+}
+
 pub fn dump_as_json<P: serde::Serialize>(dir: &str, filename: &str, proof: &P) -> Result<()> {
     // Write full proof as json.
     let mut fd = File::create(dump_proof_path(dir, filename))?;
@@ -190,3 +245,57 @@ fn serialize_instances(instances: &[Vec<Fr>]) -> Vec<u8> {
     assert_eq!(instances.len(), 1);
     serialize_instance(&instances[0])
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proof_compatibility() {
+        let proof = Proof::from_raw(vec![1; 64], vec![2; 32], vec![3; 16]);
+        assert_eq!(proof.format_version, PROOF_FORMAT_VERSION);
+        proof.check_compatibility().unwrap();
+
+        // proofs without vk have no vk hash
+        Proof::from_raw(vec![1; 64], vec![2; 32], vec![])
+            .check_compatibility()
+            .unwrap();
+
+        // proofs dumped before versioning are rejected
+        let mut json = serde_json::to_value(&proof).unwrap();
+        let json = json.as_object_mut().unwrap();
+        json.remove("format_version");
+        json.remove("vk_hash");
+        let legacy: Proof = serde_json::from_value(json.clone().into()).unwrap();
+        assert_eq!(legacy.format_version, 0);
+        assert!(legacy.check_compatibility().is_err());
+
+        let tampered = Proof {
+            vk: vec![4; 16],
+            ..proof
+        };
+        assert!(tampered.check_compatibility().is_err());
+    }
+
+    #[test]
+    fn test_proof_vk() {
+        let dir = std::env::temp_dir().join(format!("proof_vk_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dir = dir.to_str().unwrap();
+
+        let vk = vec![3; 16];
+        let proof = EvmProof {
+            proof: Proof::from_raw(vec![1; 64], vec![2; 32], vk.clone()),
+            num_instance: vec![1],
+        };
+        proof.proof.check_vk(&vk).unwrap();
+        assert!(proof.proof.check_vk(&[4; 16]).is_err());
+
+        proof.dump(dir, "test").unwrap();
+        EvmProof::from_json_file(dir, "test", Some(&vk)).unwrap();
+        EvmProof::from_json_file(dir, "test", None).unwrap();
+        assert!(EvmProof::from_json_file(dir, "test", Some(&[4; 16])).is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
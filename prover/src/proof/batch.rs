@@ -1,6 +1,7 @@
 use super::{dump_as_json, dump_data, dump_vk, from_json_file, serialize_instance, Proof};
-use crate::utils::short_git_version;
+use aggregator::{BlobPointEvaluation, BLOB_PI_LEN};
 use anyhow::Result;
+use eth_types::pi_schema::PiBatchLayout;
 use serde_derive::{Deserialize, Serialize};
 use snark_verifier_sdk::encode_calldata;
 
@@ -9,45 +10,65 @@ const PI_LEN: usize = 32;
 
 const ACC_BYTES: usize = ACC_LEN * 32;
 const PI_BYTES: usize = PI_LEN * 32;
+const BLOB_PI_BYTES: usize = BLOB_PI_LEN * 32;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct BatchProof {
     #[serde(flatten)]
     raw: Proof,
+    // Layout of the data hash, proofs dumped before tree aggregation are all flat.
+    #[serde(default)]
+    layout: PiBatchLayout,
 }
 
 impl From<Proof> for BatchProof {
     fn from(proof: Proof) -> Self {
         let instances = proof.instances();
         assert_eq!(instances.len(), 1);
-        assert_eq!(instances[0].len(), ACC_LEN + PI_LEN);
-
-        let vk = proof.vk;
-        let git_version = proof.git_version;
+        // the point evaluation of the blob follows pi_data, if the batch is posted as a blob
+        assert!([ACC_LEN + PI_LEN, ACC_LEN + PI_LEN + BLOB_PI_LEN].contains(&instances[0].len()));
 
         // raw_proof = acc + proof
-        let proof = serialize_instance(&instances[0][..ACC_LEN])
+        let raw_proof = serialize_instance(&instances[0][..ACC_LEN])
             .into_iter()
             .chain(proof.proof)
             .collect();
 
-        // raw_instances = pi_data
-        let instances = serialize_instance(&instances[0][ACC_LEN..]);
+        // raw_instances = pi_data (+ blob point evaluation)
+        let raw_instances = serialize_instance(&instances[0][ACC_LEN..]);
 
         Self {
             raw: Proof {
-                proof,
-                instances,
-                vk,
-                git_version,
+                proof: raw_proof,
+                instances: raw_instances,
+                ..proof
             },
+            layout: PiBatchLayout::Flat,
         }
     }
 }
 
 impl BatchProof {
-    pub fn from_json_file(dir: &str, name: &str) -> Result<Self> {
-        from_json_file(dir, &dump_filename(name))
+    /// Load the proof dumped in `dir`, rejecting it unless it is compatible and, if `expected_vk`
+    /// is known, generated with it.
+    pub fn from_json_file(dir: &str, name: &str, expected_vk: Option<&[u8]>) -> Result<Self> {
+        let proof: Self = from_json_file(dir, &dump_filename(name))?;
+        match expected_vk {
+            Some(expected_vk) => proof.raw.check_vk(expected_vk)?,
+            None => proof.raw.check_compatibility()?,
+        }
+
+        Ok(proof)
+    }
+
+    pub fn with_layout(self, layout: PiBatchLayout) -> Self {
+        Self { layout, ..self }
+    }
+
+    // Return the layout of the data hash of the batch, the flat keccak of the chunk data hashes
+    // unless the batch is aggregated by a tree of more than one level.
+    pub fn layout(&self) -> PiBatchLayout {
+        self.layout
     }
 
     pub fn calldata(self) -> Vec<u8> {
@@ -73,23 +94,28 @@ impl BatchProof {
 
     pub fn proof_to_verify(self) -> Proof {
         assert!(self.raw.proof.len() > ACC_BYTES);
-        assert_eq!(self.raw.instances.len(), PI_BYTES);
+        assert!([PI_BYTES, PI_BYTES + BLOB_PI_BYTES].contains(&self.raw.instances.len()));
 
-        // instances = raw_proof[..12] (acc) + raw_instances (pi_data)
+        // instances = raw_proof[..12] (acc) + raw_instances (pi_data + blob point evaluation)
         // proof = raw_proof[12..]
         let mut instances = self.raw.proof;
         let proof = instances.split_off(ACC_BYTES);
         instances.extend(self.raw.instances);
 
-        let vk = self.raw.vk;
-        let git_version = Some(short_git_version());
+        Proof::from_raw(proof, instances, self.raw.vk)
+    }
 
-        Proof {
-            proof,
-            instances,
-            vk,
-            git_version,
+    // Return the point evaluation of the blob, if the batch is posted as a blob.
+    pub fn blob_point_evaluation(&self) -> Option<BlobPointEvaluation> {
+        let blob_instances = self.raw.instances.get(PI_BYTES..)?;
+        if blob_instances.is_empty() {
+            return None;
         }
+        assert_eq!(blob_instances.len(), BLOB_PI_BYTES);
+
+        // each instance is a byte, serialized as a big-endian 32-byte word
+        let bytes: Vec<u8> = blob_instances.chunks(32).map(|word| word[31]).collect();
+        Some(BlobPointEvaluation::from_bytes(&bytes))
     }
 
     pub fn assert_calldata(self) {
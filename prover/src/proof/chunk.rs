@@ -34,7 +34,10 @@ impl ChunkProof {
     }
 
     pub fn from_json_file(dir: &str, name: &str) -> Result<Self> {
-        from_json_file(dir, &dump_filename(name))
+        let proof: Self = from_json_file(dir, &dump_filename(name))?;
+        proof.proof.check_compatibility()?;
+
+        Ok(proof)
     }
 
     pub fn dump(&self, dir: &str, name: &str) -> Result<()> {
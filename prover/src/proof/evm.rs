@@ -27,8 +27,16 @@ impl EvmProof {
         })
     }
 
-    pub fn from_json_file(dir: &str, name: &str) -> Result<Self> {
-        from_json_file(dir, &dump_filename(name))
+    /// Load the proof dumped in `dir`, rejecting it unless it is compatible and, if `expected_vk`
+    /// is known, generated with it.
+    pub fn from_json_file(dir: &str, name: &str, expected_vk: Option<&[u8]>) -> Result<Self> {
+        let proof: Self = from_json_file(dir, &dump_filename(name))?;
+        match expected_vk {
+            Some(expected_vk) => proof.proof.check_vk(expected_vk)?,
+            None => proof.proof.check_compatibility()?,
+        }
+
+        Ok(proof)
     }
 
     pub fn dump(&self, dir: &str, name: &str) -> Result<()> {
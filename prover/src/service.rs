@@ -0,0 +1,380 @@
+//! A long-running prover of chunks and batches, submitted as jobs.
+//!
+//! Jobs are persisted in a [`JobStore`] directory, one sub directory per job:
+//! - `request.json`: the [`JobRequest`],
+//! - `status.json`: the [`JobStatus`],
+//! - `output/`: the output dir of the prover, i.e., the intermediate snarks and the final proof.
+//!
+//! A single worker runs the jobs one at a time, in the order of submission. On restart, the jobs
+//! that were queued or running are queued again, and resume from the snarks already in their
+//! output dir.
+
+use crate::{aggregator, zkevm, BatchProof, ChunkProof};
+use ::aggregator::{BlobData, MAX_AGG_SNARKS};
+use anyhow::{anyhow, bail, Result};
+use eth_types::l2_types::BlockTrace;
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    fs,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, Sender},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+
+const REQUEST_FILENAME: &str = "request.json";
+const STATUS_FILENAME: &str = "status.json";
+const OUTPUT_DIRNAME: &str = "output";
+/// Name of the batch proof in the output dir, see [`aggregator::Prover::gen_agg_evm_proof`].
+const BATCH_PROOF_NAME: &str = "agg";
+
+/// A job to prove.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JobRequest {
+    /// Prove a chunk of blocks.
+    Chunk { traces: Vec<BlockTrace> },
+    /// Prove a batch of the chunks proven by `chunk_jobs`, in order.
+    Batch {
+        chunk_jobs: Vec<String>,
+        /// The blob of the batch, if it is posted as an EIP-4844 blob
+        #[serde(default)]
+        blob: Option<BlobData>,
+    },
+}
+
+impl JobRequest {
+    pub fn is_chunk(&self) -> bool {
+        matches!(self, Self::Chunk { .. })
+    }
+}
+
+/// Status of a job.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed { error: String },
+}
+
+impl JobStatus {
+    /// If the job is (still) to be run by the worker.
+    pub fn is_pending(&self) -> bool {
+        matches!(self, Self::Queued | Self::Running)
+    }
+}
+
+/// The jobs persisted in a directory.
+#[derive(Debug)]
+pub struct JobStore {
+    dir: PathBuf,
+    next_id: AtomicU64,
+}
+
+impl JobStore {
+    /// Open the store in `dir`, creating it if missing.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+
+        let next_id = Self::list_ids(&dir)?
+            .last()
+            .map_or(0, |id| id.parse::<u64>().unwrap() + 1);
+
+        Ok(Self {
+            dir,
+            next_id: AtomicU64::new(next_id),
+        })
+    }
+
+    /// Ids of the jobs in `dir`, in the order of submission.
+    fn list_ids(dir: &Path) -> Result<Vec<String>> {
+        let mut ids = vec![];
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let id = entry.file_name().to_string_lossy().into_owned();
+            if entry.file_type()?.is_dir() && id.parse::<u64>().is_ok() {
+                ids.push(id);
+            }
+        }
+        ids.sort();
+        Ok(ids)
+    }
+
+    fn job_dir(&self, id: &str) -> PathBuf {
+        self.dir.join(id)
+    }
+
+    /// The dir for the prover to write the snarks and the proof of job `id` in.
+    pub fn output_dir(&self, id: &str) -> String {
+        self.job_dir(id)
+            .join(OUTPUT_DIRNAME)
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        id.parse::<u64>().is_ok() && self.job_dir(id).join(STATUS_FILENAME).exists()
+    }
+
+    /// Check that `request` is a valid job, i.e., a non-empty chunk or a batch of chunk jobs.
+    pub fn validate(&self, request: &JobRequest) -> Result<()> {
+        match request {
+            JobRequest::Chunk { traces } => {
+                if traces.is_empty() {
+                    bail!("chunk has no block traces");
+                }
+            }
+            JobRequest::Batch { chunk_jobs, .. } => {
+                if !(1..=MAX_AGG_SNARKS).contains(&chunk_jobs.len()) {
+                    bail!(
+                        "batch has {} chunks, expect 1 to {MAX_AGG_SNARKS}",
+                        chunk_jobs.len()
+                    );
+                }
+                for chunk_job in chunk_jobs {
+                    if !self.contains(chunk_job) || !self.request(chunk_job)?.is_chunk() {
+                        bail!("{chunk_job} is not a chunk job");
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Persist a new job of `request` as queued, and return its id.
+    pub fn submit(&self, request: &JobRequest) -> Result<String> {
+        self.validate(request)?;
+
+        let id = format!("{:012}", self.next_id.fetch_add(1, Ordering::SeqCst));
+        let job_dir = self.job_dir(&id);
+        fs::create_dir_all(job_dir.join(OUTPUT_DIRNAME))?;
+        write_json(&job_dir.join(REQUEST_FILENAME), request)?;
+        // The status is written last, a job without status was not submitted.
+        self.set_status(&id, &JobStatus::Queued)?;
+
+        Ok(id)
+    }
+
+    pub fn request(&self, id: &str) -> Result<JobRequest> {
+        let buf = fs::read(self.job_dir(id).join(REQUEST_FILENAME))?;
+        let mut deserializer = serde_json::Deserializer::from_slice(&buf);
+        deserializer.disable_recursion_limit();
+        let deserializer = serde_stacker::Deserializer::new(&mut deserializer);
+        Ok(serde::Deserialize::deserialize(deserializer)?)
+    }
+
+    pub fn status(&self, id: &str) -> Result<JobStatus> {
+        let buf = fs::read(self.job_dir(id).join(STATUS_FILENAME))?;
+        Ok(serde_json::from_slice(&buf)?)
+    }
+
+    pub fn set_status(&self, id: &str, status: &JobStatus) -> Result<()> {
+        write_json(&self.job_dir(id).join(STATUS_FILENAME), status)
+    }
+
+    /// Ids of the jobs queued or running, in the order of submission.
+    pub fn pending(&self) -> Result<Vec<String>> {
+        let mut ids = vec![];
+        for id in Self::list_ids(&self.dir)? {
+            if self.contains(&id) && self.status(&id)?.is_pending() {
+                ids.push(id);
+            }
+        }
+        Ok(ids)
+    }
+
+    pub fn chunk_proof(&self, id: &str) -> Result<ChunkProof> {
+        ChunkProof::from_json_file(&self.output_dir(id), id)
+    }
+
+    pub fn batch_proof(&self, id: &str) -> Result<BatchProof> {
+        // The vk is unknown to the store, the proof is checked against it by its verifier.
+        BatchProof::from_json_file(&self.output_dir(id), BATCH_PROOF_NAME, None)
+    }
+}
+
+/// Write `value` as JSON to `path` atomically, i.e., readers see either the old or the new file.
+fn write_json<T: serde::Serialize>(path: &Path, value: &T) -> Result<()> {
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, serde_json::to_vec(value)?)?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Runs the jobs of a [`JobStore`] one at a time. The provers are created on their first job.
+pub struct Worker {
+    store: Arc<JobStore>,
+    params_dir: String,
+    assets_dir: String,
+    zkevm_prover: Option<zkevm::Prover>,
+    agg_prover: Option<aggregator::Prover>,
+}
+
+impl Worker {
+    pub fn new(store: Arc<JobStore>, params_dir: &str, assets_dir: &str) -> Self {
+        Self {
+            store,
+            params_dir: params_dir.to_string(),
+            assets_dir: assets_dir.to_string(),
+            zkevm_prover: None,
+            agg_prover: None,
+        }
+    }
+
+    /// Spawn the worker thread, with the pending jobs of the store queued first. Return the
+    /// sender to queue the ids of new jobs.
+    pub fn spawn(self) -> Result<(Sender<String>, JoinHandle<()>)> {
+        let (sender, receiver) = mpsc::channel();
+        for id in self.store.pending()? {
+            log::info!("prover-service: resume job {id}");
+            sender.send(id)?;
+        }
+
+        let mut worker = self;
+        let handle = thread::spawn(move || {
+            for id in receiver {
+                worker.run_job(&id);
+            }
+        });
+
+        Ok((sender, handle))
+    }
+
+    /// Run job `id` and persist its status. A failing (or panicking) job is marked failed.
+    pub fn run_job(&mut self, id: &str) {
+        let result = self
+            .store
+            .set_status(id, &JobStatus::Running)
+            .and_then(|_| {
+                log::info!("prover-service: start job {id}");
+                panic::catch_unwind(AssertUnwindSafe(|| self.prove(id))).unwrap_or_else(|e| {
+                    let msg = e
+                        .downcast_ref::<String>()
+                        .cloned()
+                        .or_else(|| e.downcast_ref::<&str>().map(ToString::to_string))
+                        .unwrap_or_default();
+                    Err(anyhow!("prover panicked: {msg}"))
+                })
+            });
+
+        let status = match result {
+            Ok(()) => {
+                log::info!("prover-service: job {id} succeeded");
+                JobStatus::Succeeded
+            }
+            Err(e) => {
+                log::error!("prover-service: job {id} failed: {e:#}");
+                JobStatus::Failed {
+                    error: format!("{e:#}"),
+                }
+            }
+        };
+        if let Err(e) = self.store.set_status(id, &status) {
+            log::error!("prover-service: failed to set status of job {id}: {e:#}");
+        }
+    }
+
+    fn prove(&mut self, id: &str) -> Result<()> {
+        let output_dir = self.store.output_dir(id);
+        match self.store.request(id)? {
+            JobRequest::Chunk { traces } => {
+                let (params_dir, assets_dir) = (&self.params_dir, &self.assets_dir);
+                self.zkevm_prover
+                    .get_or_insert_with(|| zkevm::Prover::from_dirs(params_dir, assets_dir))
+                    .gen_chunk_proof(traces, Some(id), None, Some(&output_dir))?;
+            }
+            JobRequest::Batch { chunk_jobs, blob } => {
+                let chunk_hashes_proofs = chunk_jobs
+                    .iter()
+                    .map(|chunk_job| {
+                        if self.store.status(chunk_job)? != JobStatus::Succeeded {
+                            bail!("chunk job {chunk_job} has not succeeded");
+                        }
+                        let proof = self.store.chunk_proof(chunk_job)?;
+                        let chunk_hash = proof
+                            .chunk_hash
+                            .ok_or_else(|| anyhow!("chunk proof of {chunk_job} has no hash"))?;
+                        Ok((chunk_hash, proof))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                let (params_dir, assets_dir) = (&self.params_dir, &self.assets_dir);
+                self.agg_prover
+                    .get_or_insert_with(|| aggregator::Prover::from_dirs(params_dir, assets_dir))
+                    .gen_agg_evm_proof(
+                        chunk_hashes_proofs,
+                        blob.as_ref(),
+                        Some(id),
+                        Some(&output_dir),
+                    )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tmp_store(name: &str) -> JobStore {
+        let dir =
+            std::env::temp_dir().join(format!("prover-service-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        JobStore::open(dir).unwrap()
+    }
+
+    #[test]
+    fn test_job_store() {
+        let store = tmp_store("job-store");
+
+        let chunk = JobRequest::Chunk {
+            traces: vec![BlockTrace::default()],
+        };
+        let id0 = store.submit(&chunk).unwrap();
+        let id1 = store.submit(&chunk).unwrap();
+        assert!(id0 < id1);
+        assert_eq!(store.status(&id0).unwrap(), JobStatus::Queued);
+        assert!(store.request(&id1).unwrap().is_chunk());
+
+        // invalid jobs are rejected
+        let empty_chunk = JobRequest::Chunk { traces: vec![] };
+        assert!(store.submit(&empty_chunk).is_err());
+        let batch_of_unknown = JobRequest::Batch {
+            chunk_jobs: vec!["unknown".to_string()],
+            blob: None,
+        };
+        assert!(store.submit(&batch_of_unknown).is_err());
+
+        let batch = JobRequest::Batch {
+            chunk_jobs: vec![id0.clone(), id1.clone()],
+            blob: None,
+        };
+        let id2 = store.submit(&batch).unwrap();
+        let batch_of_batch = JobRequest::Batch {
+            chunk_jobs: vec![id2.clone()],
+            blob: None,
+        };
+        assert!(store.submit(&batch_of_batch).is_err());
+
+        store.set_status(&id0, &JobStatus::Succeeded).unwrap();
+        store.set_status(&id1, &JobStatus::Running).unwrap();
+
+        // a reopened store resumes the ids and the pending jobs
+        let dir = store.dir.clone();
+        drop(store);
+        let store = JobStore::open(&dir).unwrap();
+        assert_eq!(store.pending().unwrap(), vec![id1, id2.clone()]);
+        let id3 = store.submit(&chunk).unwrap();
+        assert!(id2 < id3);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
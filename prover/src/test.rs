@@ -2,6 +2,6 @@ mod batch;
 mod chunk;
 mod inner;
 
-pub use batch::batch_prove;
+pub use batch::{batch_prove, tree_batch_prove};
 pub use chunk::chunk_prove;
 pub use inner::inner_prove;
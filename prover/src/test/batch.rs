@@ -1,12 +1,21 @@
 use crate::{
-    aggregator::{Prover, Verifier},
-    config::LayerId,
-    consts::DEPLOYMENT_CODE_FILENAME,
+    aggregator::{AggTree, Prover, Verifier},
+    config::{tier_degree, tier_layer_id, tree_layer_id, LayerId},
+    consts::deployment_code_filename,
     io::force_to_read,
     utils::read_env_var,
     ChunkHash, ChunkProof,
 };
+use aggregator::{agg_snarks_tier, CompressionCircuit};
+use eth_types::pi_schema::PiBatchLayout;
+use halo2_proofs::halo2curves::bn256::Bn256;
 use once_cell::sync::Lazy;
+use snark_verifier::pcs::kzg::{Bdfg21, Kzg};
+use snark_verifier_sdk::gen_evm_verifier;
+
+// Number of instances of a batch proof: accumulator (12) and public input hash (32).
+const ACC_LEN: usize = 12;
+const DIGEST_LEN: usize = 32;
 
 static mut BATCH_PROVER: Lazy<Prover> = Lazy::new(|| {
     let assets_dir = read_env_var("SCROLL_PROVER_ASSETS_DIR", "./test_assets".to_string());
@@ -18,38 +27,102 @@ static mut BATCH_PROVER: Lazy<Prover> = Lazy::new(|| {
     prover
 });
 
-static mut BATCH_VERIFIER: Lazy<Verifier> = Lazy::new(|| {
+fn new_batch_verifier(tier: usize) -> Verifier {
     let assets_dir = read_env_var("SCROLL_PROVER_ASSETS_DIR", "./test_assets".to_string());
+    let deployment_code = force_to_read(&assets_dir, &deployment_code_filename(tier, false));
 
-    let prover = unsafe { &mut BATCH_PROVER };
-    let params = prover.inner.params(LayerId::Layer4.degree()).clone();
+    new_verifier(
+        &tier_layer_id(LayerId::Layer4, tier, false),
+        tier,
+        deployment_code,
+    )
+}
 
-    let pk = prover
+fn new_verifier(id: &str, tier: usize, deployment_code: Vec<u8>) -> Verifier {
+    let prover = unsafe { &mut BATCH_PROVER };
+    let params = prover
         .inner
-        .pk(LayerId::Layer4.id())
-        .expect("Failed to get batch-prove PK");
-    let vk = pk.get_vk().clone();
+        .params(tier_degree(LayerId::Layer4, tier))
+        .clone();
 
-    let deployment_code = force_to_read(&assets_dir, &DEPLOYMENT_CODE_FILENAME);
+    let pk = prover.inner.pk(id).expect("Failed to get batch-prove PK");
+    let vk = pk.get_vk().clone();
 
     let verifier = Verifier::new(params, vk, deployment_code);
-    log::info!("Constructed batch-verifier");
+    log::info!("Constructed batch-verifier of {id}");
 
     verifier
-});
+}
 
 pub fn batch_prove(test: &str, chunk_hashes_proofs: Vec<(ChunkHash, ChunkProof)>) {
     log::info!("{test}: batch-prove BEGIN");
 
+    let tier = agg_snarks_tier(chunk_hashes_proofs.len())
+        .unwrap_or_else(|| panic!("{test}: too many chunks for a batch"));
+
     let prover = unsafe { &mut BATCH_PROVER };
     let proof = prover
-        .gen_agg_evm_proof(chunk_hashes_proofs, None, None)
+        .gen_agg_evm_proof(chunk_hashes_proofs, None, None, None)
         .unwrap_or_else(|err| panic!("{test}: failed to generate batch proof: {err}"));
     log::info!("{test}: generated batch proof");
 
-    let verifier = unsafe { &mut BATCH_VERIFIER };
+    let verifier = new_batch_verifier(tier);
     let verified = verifier.verify_agg_evm_proof(proof);
     assert!(verified, "{test}: failed to verify batch proof");
 
     log::info!("{test}: batch-prove END");
 }
+
+pub fn tree_batch_prove(
+    test: &str,
+    chunk_hashes_proofs: Vec<(ChunkHash, ChunkProof)>,
+    arity: usize,
+) {
+    log::info!("{test}: tree-batch-prove BEGIN");
+
+    let prover = unsafe { &mut BATCH_PROVER };
+    let mut tree = AggTree::new(test, arity)
+        .unwrap_or_else(|err| panic!("{test}: failed to start tree aggregation: {err}"));
+    for (chunk_hash, chunk_proof) in chunk_hashes_proofs {
+        prover
+            .add_chunk_to_agg_tree(&mut tree, chunk_hash, chunk_proof, None)
+            .unwrap_or_else(|err| panic!("{test}: failed to add chunk to tree: {err}"));
+    }
+    let depth = tree.final_depth();
+    let proof = prover
+        .gen_agg_tree_evm_proof(tree, None)
+        .unwrap_or_else(|err| panic!("{test}: failed to generate tree batch proof: {err}"));
+    log::info!("{test}: generated tree batch proof of depth-{depth}");
+
+    let layout = if depth == 1 {
+        PiBatchLayout::Flat
+    } else {
+        PiBatchLayout::Tree { arity }
+    };
+    assert_eq!(
+        proof.layout(),
+        layout,
+        "{test}: unexpected data hash layout"
+    );
+
+    // The verifier contract of a tree depends on its depth, so it is generated from the PK.
+    let id = tree_layer_id(LayerId::Layer4, arity, depth - 1);
+    let deployment_code = {
+        let params = prover
+            .inner
+            .params(tier_degree(LayerId::Layer4, arity))
+            .clone();
+        let pk = prover.inner.pk(&id).expect("Failed to get batch-prove PK");
+        gen_evm_verifier::<CompressionCircuit, Kzg<Bn256, Bdfg21>>(
+            &params,
+            pk.get_vk(),
+            vec![ACC_LEN + DIGEST_LEN],
+            None,
+        )
+    };
+    let verifier = new_verifier(&id, arity, deployment_code);
+    let verified = verifier.verify_agg_evm_proof(proof);
+    assert!(verified, "{test}: failed to verify tree batch proof");
+
+    log::info!("{test}: tree-batch-prove END");
+}
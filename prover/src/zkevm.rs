@@ -6,5 +6,8 @@ mod verifier;
 
 pub use self::prover::Prover;
 #[cfg(feature = "scroll")]
-pub use capacity_checker::{CircuitCapacityChecker, RowUsage, SubCircuitRowUsage};
+pub use capacity_checker::{
+    sub_circuit_row_limit, CapacityReport, Checkpoint, CircuitCapacityChecker, RowContribution,
+    RowUsage, SubCircuitCapacity, SubCircuitRowUsage, TopOpcodes,
+};
 pub use verifier::Verifier;
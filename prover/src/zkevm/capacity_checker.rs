@@ -1,20 +1,24 @@
 use super::circuit::{
-    MAX_BYTECODE, MAX_CALLDATA, MAX_EXP_STEPS, MAX_KECCAK_ROWS, MAX_MPT_ROWS, MAX_POSEIDON_ROWS,
-    MAX_RWS, MAX_VERTICAL_ROWS,
-};
-
-use super::circuit::{
-    block_traces_to_witness_block_with_updated_state, calculate_row_usage_of_witness_block,
-    get_super_circuit_params,
+    calculate_base_row_usage, calculate_marginal_row_usage, get_super_circuit_params,
 };
 use bus_mapping::{
-    circuit_input_builder::{self, CircuitInputBuilder},
+    circuit_input_builder::{self, CircuitInputBuilder, CircuitsParams},
     state_db::{CodeDB, StateDB},
 };
-use eth_types::{l2_types::BlockTrace, ToWord, H256};
+use eth_types::{evm_types::OpcodeId, l2_types::BlockTrace, ToWord};
 use itertools::Itertools;
-use mpt_zktrie::state::ZktrieState;
 use serde_derive::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, iter,
+};
+use zkevm_circuits::{
+    blake2f_circuit::NUM_ROWS_PER_ROUND,
+    evm_circuit::ExecutionState,
+    super_circuit::SubcircuitRowUsage,
+    util::WitnessDelta,
+    witness::{BlockDeltas, ExecStep, MptUpdateKey, Transaction},
+};
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct SubCircuitRowUsage {
@@ -37,6 +41,41 @@ impl Default for RowUsage {
 
 const NORMALIZED_ROW_LIMIT: usize = 1_000_000;
 
+/// Number of opcodes kept per trace and sub circuit to explain its row usage in a
+/// [`CapacityReport`].
+const NUM_TOP_OPCODES_PER_TRACE: usize = 5;
+
+/// The most used opcodes (or precompiles) of a trace in a sub circuit, see [`top_opcodes`].
+pub type TopOpcodes = Vec<(String, usize)>;
+
+/// The rows of the sub circuit `name` that can be used by a chunk with `params`, with a margin
+/// for the estimation error.
+pub fn sub_circuit_row_limit(name: &str, params: &CircuitsParams) -> usize {
+    let (limit, confidence) = match name {
+        "evm" => (params.max_evm_rows, 0.95),
+        "state" | "pi" => (params.max_rws, 0.95),
+        "bytecode" => (params.max_bytecode, 0.95),
+        "copy" => (params.max_copy_rows, 0.95),
+        "keccak" | "mod_exp" => (params.max_keccak_rows, 0.95),
+        "sha256" => (params.max_sha256_rows, 0.95),
+        "ripemd160" => (params.max_ripemd160_rows, 0.95),
+        "tx" | "sig" => (params.max_vertical_circuit_rows, 0.95),
+        "ecc" => (params.max_vertical_circuit_rows, 1.0),
+        "rlp" => (params.max_calldata, 0.95),
+        "exp" => (7 * params.max_exp_steps, 0.95),
+        "poseidon" => (params.max_poseidon_rows, 0.95),
+        "blake2f" => (params.max_blake2f_rounds * NUM_ROWS_PER_ROUND, 0.95),
+        "mpt" => (params.max_mpt_rows, 0.95),
+        _ => panic!("unknown sub circuit {name}"),
+    };
+    (limit as f32 * confidence) as usize
+}
+
+/// Scale `row_number` to [`NORMALIZED_ROW_LIMIT`] for `row_limit`, i.e., 1M is 100%.
+fn normalized_row_number(row_number: usize, row_limit: usize) -> usize {
+    (1_000_000u64 * (row_number as u64) / (row_limit as u64)) as usize
+}
+
 impl RowUsage {
     pub fn new() -> Self {
         Self {
@@ -47,30 +86,16 @@ impl RowUsage {
     }
     // We treat 1M as 100%
     pub fn normalize(&self) -> Self {
-        let real_available_rows = [
-            (MAX_RWS, 0.95),           // evm
-            (MAX_RWS, 0.95),           // state
-            (MAX_BYTECODE, 0.95),      // bytecode
-            (MAX_RWS, 0.95),           // copy
-            (MAX_KECCAK_ROWS, 0.95),   // keccak
-            (MAX_VERTICAL_ROWS, 0.95), // tx
-            (MAX_CALLDATA, 0.95),      // rlp
-            (7 * MAX_EXP_STEPS, 0.95), // exp
-            (MAX_KECCAK_ROWS, 0.95),   // modexp
-            (MAX_RWS, 0.95),           // pi
-            (MAX_POSEIDON_ROWS, 0.95), // poseidon
-            (MAX_VERTICAL_ROWS, 0.95), // sig
-            (MAX_VERTICAL_ROWS, 1.0),  // ecc
-            (MAX_MPT_ROWS, 0.95),      // mpt
-        ]
-        .map(|(limit, confidence)| (limit as f32 * confidence) as usize);
+        let params = get_super_circuit_params();
         let details = self
             .row_usage_details
             .iter()
-            .zip_eq(real_available_rows.iter())
-            .map(|(x, limit)| SubCircuitRowUsage {
-                name: x.name.clone(),
-                row_number: (1_000_000u64 * (x.row_number as u64) / (*limit as u64)) as usize,
+            .map(|x| {
+                let limit = sub_circuit_row_limit(&x.name, &params);
+                SubCircuitRowUsage {
+                    name: x.name.clone(),
+                    row_number: normalized_row_number(x.row_number, limit),
+                }
             })
             .collect_vec();
         log::debug!(
@@ -112,14 +137,234 @@ impl RowUsage {
     }
 }
 
+/// The rows of the sub circuit `name` that `step` accounts for, to rank the opcodes of a trace
+/// by sub circuit. These are the rows of the step in the evm circuit, its rws in the state
+/// circuit and the rws of its copy events in the copy circuit. A step calling a hash or a
+/// precompile counts once in the circuit of the call, and not at all in the other circuits.
+fn step_row_number(step: &ExecStep, name: &str) -> usize {
+    let calls = |execution_states: &[ExecutionState]| {
+        execution_states.contains(&step.execution_state) as usize
+    };
+    match name {
+        "evm" => step.execution_state.get_step_height(),
+        "state" => step.rw_indices.len(),
+        "copy" => step.copy_rw_counter_delta as usize,
+        "keccak" => matches!(
+            step.opcode,
+            Some(OpcodeId::SHA3 | OpcodeId::CREATE | OpcodeId::CREATE2)
+        ) as usize,
+        "exp" => (step.opcode == Some(OpcodeId::EXP)) as usize,
+        "sig" => calls(&[ExecutionState::PrecompileEcrecover]),
+        "ecc" => calls(&[
+            ExecutionState::PrecompileBn256Add,
+            ExecutionState::PrecompileBn256ScalarMul,
+            ExecutionState::PrecompileBn256Pairing,
+        ]),
+        "mod_exp" => calls(&[ExecutionState::PrecompileBigModExp]),
+        "sha256" => calls(&[ExecutionState::PrecompileSha256]),
+        "ripemd160" => calls(&[ExecutionState::PrecompileRipemd160]),
+        "blake2f" => calls(&[ExecutionState::PrecompileBlake2f]),
+        _ => 0,
+    }
+}
+
+/// Sum the rows of the sub circuit `name` per opcode (or precompile) of the steps of `txs`, see
+/// [`step_row_number`], and return the top `n`, the most rows first. Virtual steps are skipped.
+fn top_opcodes(txs: &[Transaction], name: &str, n: usize) -> TopOpcodes {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for step in txs.iter().flat_map(|tx| tx.steps.iter()) {
+        let opcode = match (step.opcode, step.execution_state) {
+            (_, ExecutionState::BeginTx | ExecutionState::EndTx | ExecutionState::EndBlock) => {
+                continue
+            }
+            (Some(opcode), _) => format!("{opcode:?}"),
+            (None, execution_state) => format!("{execution_state:?}"),
+        };
+        let row_number = step_row_number(step, name);
+        if row_number > 0 {
+            *counts.entry(opcode).or_default() += row_number;
+        }
+    }
+    counts
+        .into_iter()
+        .sorted_by(|(name_a, count_a), (name_b, count_b)| {
+            count_b.cmp(count_a).then_with(|| name_a.cmp(name_b))
+        })
+        .take(n)
+        .collect()
+}
+
+/// The rows of a sub circuit used by one estimated trace.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RowContribution {
+    /// Index of the trace in the estimated traces, see [`CircuitCapacityChecker::get_tx_num`]
+    pub tx_index: usize,
+    pub row_number: usize,
+    /// Share of the rows of the sub circuit, in percent
+    pub percentage: f64,
+    /// The opcodes of the trace using the most rows of the sub circuit, see [`top_opcodes`]
+    pub top_opcodes: TopOpcodes,
+}
+
+/// The row usage of a sub circuit against its limit.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SubCircuitCapacity {
+    pub name: String,
+    pub row_number: usize,
+    /// The rows that do not depend on the traces, required once by the chunk
+    pub base_row_number: usize,
+    /// See [`sub_circuit_row_limit`]
+    pub row_limit: usize,
+    /// Share of the row limit, in percent
+    pub percentage: f64,
+    /// The traces using the most rows of the sub circuit, largest first
+    pub top_contributions: Vec<RowContribution>,
+}
+
+/// Per sub circuit capacity of the traces estimated by a [`CircuitCapacityChecker`], to tell why
+/// a chunk overflows (or is about to).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CapacityReport {
+    pub is_ok: bool,
+    /// The sub circuits, the most used first
+    pub sub_circuits: Vec<SubCircuitCapacity>,
+}
+
+impl CapacityReport {
+    /// Build the report of the checker state with `acc_row_usage` (not normalized), which
+    /// includes the base rows, and the traces with `row_usages` and `trace_top_opcodes` (per sub
+    /// circuit), in order, keeping the `top_n` contributing traces of each sub circuit.
+    pub fn new(
+        acc_row_usage: &RowUsage,
+        row_usages: &[RowUsage],
+        trace_top_opcodes: &[Vec<TopOpcodes>],
+        params: &CircuitsParams,
+        top_n: usize,
+    ) -> Self {
+        assert_eq!(row_usages.len(), trace_top_opcodes.len());
+
+        let mut sub_circuits = acc_row_usage
+            .row_usage_details
+            .iter()
+            .enumerate()
+            .map(|(i, acc)| {
+                let row_limit = sub_circuit_row_limit(&acc.name, params);
+                let contributions = row_usages
+                    .iter()
+                    .zip_eq(trace_top_opcodes)
+                    .enumerate()
+                    .map(|(tx_index, (row_usage, top_opcodes))| {
+                        let detail = &row_usage.row_usage_details[i];
+                        debug_assert_eq!(detail.name, acc.name);
+                        RowContribution {
+                            tx_index,
+                            row_number: detail.row_number,
+                            percentage: percentage(detail.row_number, acc.row_number),
+                            top_opcodes: top_opcodes[i].clone(),
+                        }
+                    })
+                    .collect_vec();
+                let trace_row_number = contributions.iter().map(|x| x.row_number).sum::<usize>();
+                let top_contributions = contributions
+                    .into_iter()
+                    .filter(|contribution| contribution.row_number > 0)
+                    .sorted_by(|a, b| b.row_number.cmp(&a.row_number))
+                    .take(top_n)
+                    .collect();
+                SubCircuitCapacity {
+                    name: acc.name.clone(),
+                    row_number: acc.row_number,
+                    base_row_number: acc.row_number.saturating_sub(trace_row_number),
+                    row_limit,
+                    percentage: percentage(acc.row_number, row_limit),
+                    top_contributions,
+                }
+            })
+            .collect_vec();
+        sub_circuits.sort_by(|a, b| b.percentage.total_cmp(&a.percentage));
+
+        // the same decision as the normalized row usage of the checker
+        let is_ok = sub_circuits
+            .iter()
+            .all(|x| normalized_row_number(x.row_number, x.row_limit) <= NORMALIZED_ROW_LIMIT);
+        Self {
+            is_ok,
+            sub_circuits,
+        }
+    }
+}
+
+fn percentage(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        100.0 * part as f64 / total as f64
+    }
+}
+
+impl fmt::Display for CapacityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for sub_circuit in &self.sub_circuits {
+            write!(
+                f,
+                "{}: {:.1}% ({}/{} rows, {} base)",
+                sub_circuit.name,
+                sub_circuit.percentage,
+                sub_circuit.row_number,
+                sub_circuit.row_limit,
+                sub_circuit.base_row_number
+            )?;
+            for contribution in &sub_circuit.top_contributions {
+                write!(
+                    f,
+                    ", {:.1}% from tx {}",
+                    contribution.percentage, contribution.tx_index
+                )?;
+                if !contribution.top_opcodes.is_empty() {
+                    let opcodes = contribution
+                        .top_opcodes
+                        .iter()
+                        .map(|(name, row_number)| format!("{name} x{row_number}"))
+                        .join(" ");
+                    write!(f, " {opcodes}")?;
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// A state of a [`CircuitCapacityChecker`] to revert to, see
+/// [`CircuitCapacityChecker::checkpoint`].
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    acc_row_usage: RowUsage,
+    num_row_usages: usize,
+    builder_ctx: Option<(CodeDB, StateDB)>,
+    keccak_inputs: HashSet<Vec<u8>>,
+    mpt_update_keys: HashSet<MptUpdateKey>,
+    num_txs: usize,
+}
+
 #[derive(Debug)]
 pub struct CircuitCapacityChecker {
     /// When "light_mode" enabled, we skip zktrie subcircuit in row estimation to avoid the heavy
-    /// poseidon cost.
+    /// poseidon cost. The estimation from the deltas of the traces never replays the zktrie, so
+    /// the poseidon rows of the mpt updates are estimated as in light mode either way.
     pub light_mode: bool,
     pub acc_row_usage: RowUsage,
     pub row_usages: Vec<RowUsage>,
-    pub builder_ctx: Option<(CodeDB, StateDB, Option<ZktrieState>)>,
+    /// The opcodes of each trace in `row_usages` using the most rows, per sub circuit
+    pub trace_top_opcodes: Vec<Vec<TopOpcodes>>,
+    /// The codes and the state after the traces estimated so far
+    pub builder_ctx: Option<(CodeDB, StateDB)>,
+    /// The keccak inputs of the traces estimated so far, counted once
+    keccak_inputs: HashSet<Vec<u8>>,
+    /// The keys of the mpt updates of the traces estimated so far, counted once
+    mpt_update_keys: HashSet<MptUpdateKey>,
+    /// The number of txs of the traces estimated so far
+    num_txs: usize,
 }
 
 impl Default for CircuitCapacityChecker {
@@ -134,14 +379,59 @@ impl CircuitCapacityChecker {
         Self {
             acc_row_usage: RowUsage::new(),
             row_usages: Vec::new(),
+            trace_top_opcodes: Vec::new(),
             light_mode: true,
             builder_ctx: None,
+            keccak_inputs: HashSet::new(),
+            mpt_update_keys: HashSet::new(),
+            num_txs: 0,
         }
     }
     pub fn reset(&mut self) {
         self.builder_ctx = None;
         self.acc_row_usage = RowUsage::new();
         self.row_usages = Vec::new();
+        self.trace_top_opcodes = Vec::new();
+        self.keccak_inputs = HashSet::new();
+        self.mpt_update_keys = HashSet::new();
+        self.num_txs = 0;
+    }
+    /// Save the current state, i.e., the traces estimated so far, to revert to by
+    /// [`Self::revert`].
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            acc_row_usage: self.acc_row_usage.clone(),
+            num_row_usages: self.row_usages.len(),
+            builder_ctx: self.builder_ctx.clone(),
+            keccak_inputs: self.keccak_inputs.clone(),
+            mpt_update_keys: self.mpt_update_keys.clone(),
+            num_txs: self.num_txs,
+        }
+    }
+    /// Revert to `checkpoint`, discarding the traces estimated after it.
+    pub fn revert(&mut self, checkpoint: Checkpoint) {
+        assert!(
+            checkpoint.num_row_usages <= self.row_usages.len(),
+            "checkpoint of {} traces is not from this checker with {} traces",
+            checkpoint.num_row_usages,
+            self.row_usages.len()
+        );
+
+        self.acc_row_usage = checkpoint.acc_row_usage;
+        self.row_usages.truncate(checkpoint.num_row_usages);
+        self.trace_top_opcodes.truncate(checkpoint.num_row_usages);
+        self.builder_ctx = checkpoint.builder_ctx;
+        self.keccak_inputs = checkpoint.keccak_inputs;
+        self.mpt_update_keys = checkpoint.mpt_update_keys;
+        self.num_txs = checkpoint.num_txs;
+    }
+    /// Return the (normalized) row usage if `trace` were estimated, without committing it.
+    /// The checker is unchanged, including when the estimation fails.
+    pub fn dry_run(&mut self, trace: BlockTrace) -> Result<RowUsage, anyhow::Error> {
+        let checkpoint = self.checkpoint();
+        let result = self.estimate_circuit_capacity(trace);
+        self.revert(checkpoint);
+        result
     }
     pub fn set_light_mode(&mut self, light_mode: bool) {
         self.light_mode = light_mode;
@@ -156,87 +446,272 @@ impl CircuitCapacityChecker {
             self.acc_row_usage.clone()
         }
     }
+    /// Report the row usage of each sub circuit against its limit, with the `top_n` traces
+    /// contributing the most rows.
+    pub fn get_capacity_report(&self, top_n: usize) -> CapacityReport {
+        CapacityReport::new(
+            &self.acc_row_usage,
+            &self.row_usages,
+            &self.trace_top_opcodes,
+            &get_super_circuit_params(),
+            top_n,
+        )
+    }
+    /// Estimate the rows `trace` adds to the traces estimated so far, from the deltas of its txs,
+    /// see [`BlockDeltas`]. The bytecodes, keccak inputs and mpt updates already in the chunk are
+    /// not counted again.
     pub fn estimate_circuit_capacity(
         &mut self,
         trace: BlockTrace,
     ) -> Result<RowUsage, anyhow::Error> {
-        let (mut estimate_builder, codedb_prev) =
-            if let Some((code_db, sdb, mpt_state)) = self.builder_ctx.take() {
-                // here we create a new builder for another (sealed) witness block
-                // this builder inherit the current execution state (sdb) of
-                // the previous one and do not use zktrie state,
-                // notice the prev_root in current builder may be not invalid (since the state has
-                // changed but we may not update it in light mode)
-                let mut builder_block =
-                    circuit_input_builder::Block::from_headers(&[], get_super_circuit_params());
-                builder_block.chain_id = trace.chain_id;
-                builder_block.start_l1_queue_index = trace.start_l1_queue_index;
-                builder_block.prev_state_root = mpt_state
-                    .as_ref()
-                    .map(|state| state.root())
-                    .map(|root| H256(*root))
-                    .unwrap_or(trace.header.state_root)
-                    .to_word();
-                // notice the trace has included all code required for builidng witness block,
-                // so we do not need to pick them from previous one, but we still keep the
-                // old codedb in previous run for some dedup work
-                let mut builder = if let Some(mpt_state) = mpt_state {
-                    CircuitInputBuilder::new_with_trie_state(
-                        sdb,
-                        CodeDB::new(),
-                        mpt_state,
-                        &builder_block,
-                    )
-                } else {
-                    CircuitInputBuilder::new(sdb, CodeDB::new(), &builder_block)
-                };
-                builder.add_more_l2_trace(trace, false)?;
-                (builder, Some(code_db))
-            } else {
-                (
-                    CircuitInputBuilder::new_from_l2_trace(
-                        get_super_circuit_params(),
-                        trace,
-                        false,
-                        self.light_mode,
-                    )?,
-                    None,
-                )
-            };
-        let witness_block =
-            block_traces_to_witness_block_with_updated_state(vec![], &mut estimate_builder)?;
-        let mut rows = calculate_row_usage_of_witness_block(&witness_block)?;
-
-        let mut code_db = codedb_prev.unwrap_or_else(CodeDB::new);
-        // merge current codes with previous , and dedup bytecode row usage
-        // for bytecode circuit / poseidon circuit
-        for (hash, bytes) in estimate_builder.code_db.0 {
-            let bytes_len = bytes.len();
-            // code for current run has been evaluated in previous
-            if code_db.0.insert(hash, bytes).is_some() {
-                assert_eq!(rows[2].name, "bytecode");
-                rows[2].row_num_real -= bytes_len + 1;
-                assert_eq!(rows[10].name, "poseidon");
-                rows[10].row_num_real -= bytes_len / (31 * 2) * 9;
-            }
-        }
+        // the txs of the trace are built as followed by more txs, as the chunk is not sealed
+        let (estimate_builder, mut code_db) = if let Some((code_db, sdb)) = self.builder_ctx.take()
+        {
+            // here we create a new builder for the txs of another trace, which inherits the
+            // current execution state (sdb) of the previous one and does not use zktrie state
+            let mut builder_block =
+                circuit_input_builder::Block::from_headers(&[], get_super_circuit_params());
+            builder_block.chain_id = trace.chain_id;
+            builder_block.start_l1_queue_index = trace.start_l1_queue_index;
+            builder_block.prev_state_root = trace.header.state_root.to_word();
+            // notice the trace has included all code required for builidng witness block,
+            // so we do not need to pick them from previous one, but we still keep the
+            // old codedb in previous run for some dedup work
+            let mut builder = CircuitInputBuilder::new(sdb, CodeDB::new(), &builder_block);
+            builder.add_more_l2_trace(trace, true)?;
+            (builder, code_db)
+        } else {
+            (
+                CircuitInputBuilder::new_from_l2_trace(
+                    get_super_circuit_params(),
+                    trace,
+                    true,
+                    true,
+                )?,
+                CodeDB::new(),
+            )
+        };
+        let block_deltas = BlockDeltas::new(
+            &estimate_builder.block,
+            &estimate_builder.code_db,
+            self.num_txs,
+        )?;
 
-        let row_usage_details: Vec<SubCircuitRowUsage> = rows
-            .into_iter()
-            .map(|x| SubCircuitRowUsage {
-                name: x.name,
-                row_number: x.row_num_real,
-            })
+        // merge current codes, keccak inputs and mpt updates with previous ones, their rows are
+        // only counted once
+        let new_bytecodes = estimate_builder
+            .code_db
+            .0
+            .iter()
+            .filter(|(hash, _)| !code_db.0.contains_key(*hash))
+            .map(|(_, bytes)| WitnessDelta::Bytecode(bytes));
+        let new_keccak_inputs = block_deltas
+            .keccak_inputs
+            .iter()
+            .filter(|bytes| !self.keccak_inputs.contains(*bytes))
+            .map(|bytes| WitnessDelta::KeccakInput(bytes));
+        let new_mpt_updates = block_deltas
+            .mpt_update_keys
+            .iter()
+            .filter(|key| !self.mpt_update_keys.contains(*key))
+            .map(|_| WitnessDelta::MptUpdate);
+        let deltas = iter::empty()
+            .chain(block_deltas.deltas())
+            .chain(new_bytecodes)
+            .chain(new_keccak_inputs)
+            .chain(new_mpt_updates)
             .collect_vec();
-        let tx_row_usage = RowUsage::from_row_usage_details(row_usage_details);
+        let rows = calculate_marginal_row_usage(&deltas)?;
+
+        let to_row_usage = |rows: Vec<SubcircuitRowUsage>| {
+            RowUsage::from_row_usage_details(
+                rows.into_iter()
+                    .map(|x| SubCircuitRowUsage {
+                        name: x.name,
+                        row_number: x.row_num_real,
+                    })
+                    .collect_vec(),
+            )
+        };
+        // the rows that do not depend on the traces are counted once, with the first trace
+        if self.row_usages.is_empty() {
+            let base_rows = calculate_base_row_usage()?;
+            self.acc_row_usage.add(&to_row_usage(base_rows));
+        }
+        let tx_row_usage = to_row_usage(rows);
+        self.trace_top_opcodes.push(
+            tx_row_usage
+                .row_usage_details
+                .iter()
+                .map(|x| top_opcodes(&block_deltas.txs, &x.name, NUM_TOP_OPCODES_PER_TRACE))
+                .collect(),
+        );
         self.row_usages.push(tx_row_usage.clone());
         self.acc_row_usage.add(&tx_row_usage);
 
-        self.builder_ctx.replace((
-            code_db,
-            estimate_builder.sdb,
-            estimate_builder.mpt_init_state,
-        ));
+        self.num_txs += block_deltas.txs.len();
+        self.keccak_inputs.extend(block_deltas.keccak_inputs);
+        self.mpt_update_keys.extend(block_deltas.mpt_update_keys);
+        code_db.0.extend(estimate_builder.code_db.0);
+        self.builder_ctx.replace((code_db, estimate_builder.sdb));
         Ok(self.acc_row_usage.normalize())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eth_types::bytecode;
+    use mock::TestContext;
+
+    fn row_usage(rows: &[(&str, usize)]) -> RowUsage {
+        RowUsage::from_row_usage_details(
+            rows.iter()
+                .map(|(name, row_number)| SubCircuitRowUsage {
+                    name: name.to_string(),
+                    row_number: *row_number,
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_capacity_report() {
+        let params = CircuitsParams {
+            max_evm_rows: 1000,
+            max_keccak_rows: 1000,
+            ..Default::default()
+        };
+        let base_row_usage = row_usage(&[("evm", 50), ("keccak", 0)]);
+        let row_usages = [
+            row_usage(&[("evm", 100), ("keccak", 270)]),
+            row_usage(&[("evm", 50), ("keccak", 0)]),
+            row_usage(&[("evm", 50), ("keccak", 630)]),
+        ];
+        let opcodes = |opcodes: &[(&str, usize)]| {
+            opcodes
+                .iter()
+                .map(|(name, row_number)| (name.to_string(), *row_number))
+                .collect_vec()
+        };
+        let trace_top_opcodes = vec![
+            vec![opcodes(&[("PUSH1", 60)]), opcodes(&[("CREATE2", 1)])],
+            vec![opcodes(&[("ADD", 50)]), vec![]],
+            vec![
+                opcodes(&[("SHA3", 40), ("JUMP", 10)]),
+                opcodes(&[("SHA3", 30)]),
+            ],
+        ];
+        let mut acc_row_usage = base_row_usage;
+        for row_usage in &row_usages {
+            acc_row_usage.add(row_usage);
+        }
+
+        let report =
+            CapacityReport::new(&acc_row_usage, &row_usages, &trace_top_opcodes, &params, 2);
+        assert!(report.is_ok);
+        assert_eq!(report.sub_circuits.len(), 2);
+
+        // the most used sub circuit first
+        let keccak = &report.sub_circuits[0];
+        assert_eq!(keccak.name, "keccak");
+        assert_eq!(keccak.row_number, 900);
+        assert_eq!(keccak.base_row_number, 0);
+        assert_eq!(keccak.row_limit, 950);
+        // the traces without rows in the sub circuit are not contributions
+        assert_eq!(
+            keccak
+                .top_contributions
+                .iter()
+                .map(|x| (x.tx_index, x.row_number))
+                .collect_vec(),
+            vec![(2, 630), (0, 270)]
+        );
+        assert!((keccak.top_contributions[0].percentage - 70.0).abs() < 1e-9);
+        // with the opcodes of the trace in the sub circuit
+        assert_eq!(
+            keccak.top_contributions[0].top_opcodes,
+            trace_top_opcodes[2][1]
+        );
+
+        // the base rows count in the usage of the sub circuit, as in the checker
+        let evm = &report.sub_circuits[1];
+        assert_eq!(evm.name, "evm");
+        assert_eq!(evm.row_number, 250);
+        assert_eq!(evm.base_row_number, 50);
+        assert!((evm.percentage - 100.0 * 250.0 / 950.0).abs() < 1e-9);
+        assert!((evm.top_contributions[0].percentage - 40.0).abs() < 1e-9);
+        assert_eq!(
+            evm.top_contributions[0].top_opcodes,
+            trace_top_opcodes[0][0]
+        );
+
+        let report = report.to_string();
+        assert_eq!(
+            report,
+            "keccak: 94.7% (900/950 rows, 0 base), 70.0% from tx 2 SHA3 x30, 30.0% from tx 0 CREATE2 x1\n\
+             evm: 26.3% (250/950 rows, 50 base), 40.0% from tx 0 PUSH1 x60, 20.0% from tx 1 ADD x50\n"
+        );
+
+        // the base rows alone may overflow a sub circuit
+        let acc_row_usage = row_usage(&[("evm", 960), ("keccak", 0)]);
+        let row_usages = [row_usage(&[("evm", 10), ("keccak", 0)])];
+        let overflow = CapacityReport::new(
+            &acc_row_usage,
+            &row_usages,
+            &[vec![vec![], vec![]]],
+            &params,
+            2,
+        );
+        assert!(!overflow.is_ok);
+        assert_eq!(overflow.sub_circuits[0].base_row_number, 950);
+    }
+
+    #[test]
+    fn test_checkpoint_revert_dry_run() {
+        let rows = |row_usage: &RowUsage| {
+            row_usage
+                .row_usage_details
+                .iter()
+                .map(|x| (x.name.clone(), x.row_number))
+                .collect_vec()
+        };
+        let ctx = TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode! {
+            PUSH1(0x01)
+            PUSH1(0x02)
+            ADD
+            STOP
+        })
+        .unwrap();
+        let trace = ctx.l2_trace().clone();
+
+        let mut checker = CircuitCapacityChecker::new();
+        let empty = rows(&checker.get_acc_row_usage(false));
+
+        // a dry run leaves the checker as it is
+        let dry_run = checker.dry_run(trace.clone()).unwrap();
+        assert_eq!(rows(&checker.get_acc_row_usage(false)), empty);
+        assert_eq!(checker.get_tx_num(), 0);
+        assert!(checker.builder_ctx.is_none());
+
+        // and estimates the same as committing the trace
+        let checkpoint = checker.checkpoint();
+        let committed = checker.estimate_circuit_capacity(trace.clone()).unwrap();
+        assert_eq!(rows(&committed), rows(&dry_run));
+        assert_eq!(checker.get_tx_num(), 1);
+        assert_ne!(rows(&checker.get_acc_row_usage(false)), empty);
+
+        checker.revert(checkpoint);
+        assert_eq!(rows(&checker.get_acc_row_usage(false)), empty);
+        assert_eq!(checker.get_tx_num(), 0);
+        assert!(checker.trace_top_opcodes.is_empty());
+        assert!(checker.builder_ctx.is_none());
+        assert!(checker.keccak_inputs.is_empty());
+        assert!(checker.mpt_update_keys.is_empty());
+        assert_eq!(checker.num_txs, 0);
+
+        // the state after the revert is the one of the checkpoint
+        let estimated = checker.estimate_circuit_capacity(trace).unwrap();
+        assert_eq!(rows(&estimated), rows(&committed));
+    }
+}
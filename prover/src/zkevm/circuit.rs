@@ -14,7 +14,8 @@ use l1_builder as builder;
 mod super_circuit;
 pub use self::builder::{
     block_trace_to_witness_block, block_traces_to_witness_block,
-    block_traces_to_witness_block_with_updated_state, calculate_row_usage_of_trace,
+    block_traces_to_witness_block_with_updated_state, calculate_base_row_usage,
+    calculate_marginal_row_usage, calculate_row_usage_of_trace,
     calculate_row_usage_of_witness_block, check_batch_capacity, get_super_circuit_params,
     validite_block_traces,
 };
@@ -29,12 +30,15 @@ pub const MAX_RLP_ROWS: usize = 800_000;
 pub const MAX_BYTECODE: usize = 600_000;
 pub const MAX_MPT_ROWS: usize = 1_000_000;
 pub const MAX_KECCAK_ROWS: usize = 1_000_000;
+pub const MAX_SHA256_ROWS: usize = 1_000_000;
+pub const MAX_RIPEMD160_ROWS: usize = 1_000_000;
 pub const MAX_POSEIDON_ROWS: usize = 1_000_000;
 pub const MAX_VERTICAL_ROWS: usize = 1_000_000;
 pub const MAX_RWS: usize = 1_000_000;
 pub const MAX_PRECOMPILE_EC_ADD: usize = 50;
 pub const MAX_PRECOMPILE_EC_MUL: usize = 50;
 pub const MAX_PRECOMPILE_EC_PAIRING: usize = 2;
+pub const MAX_BLAKE2F_ROUNDS: usize = 15_000;
 
 /// A target circuit trait is a wrapper of inner circuit, with convenient APIs for building
 /// circuits from traces.
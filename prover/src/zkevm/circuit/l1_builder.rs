@@ -2,7 +2,9 @@ use anyhow::Result;
 use bus_mapping::circuit_input_builder::{CircuitInputBuilder, CircuitsParams};
 use eth_types::l2_types::BlockTrace;
 use halo2_proofs::halo2curves::bn256::Fr;
-use zkevm_circuits::evm_circuit::witness::Block;
+use zkevm_circuits::{
+    evm_circuit::witness::Block, super_circuit::SubcircuitRowUsage, util::WitnessDelta,
+};
 
 pub fn get_super_circuit_params() -> CircuitsParams {
     unimplemented!("Must build with feature scroll")
@@ -12,15 +14,21 @@ pub fn validite_block_traces(_block_traces: &[BlockTrace]) -> Result<()> {
     unimplemented!("Must build with feature scroll")
 }
 
-pub fn calculate_row_usage_of_trace(
-    _block_trace: &BlockTrace,
-) -> Result<Vec<zkevm_circuits::super_circuit::SubcircuitRowUsage>> {
+pub fn calculate_row_usage_of_trace(_block_trace: &BlockTrace) -> Result<Vec<SubcircuitRowUsage>> {
     unimplemented!("Must build with feature scroll")
 }
 
 pub fn calculate_row_usage_of_witness_block(
     _witness_block: &Block<Fr>,
-) -> Result<Vec<zkevm_circuits::super_circuit::SubcircuitRowUsage>> {
+) -> Result<Vec<SubcircuitRowUsage>> {
+    unimplemented!("Must build with feature scroll")
+}
+
+pub fn calculate_marginal_row_usage(_deltas: &[WitnessDelta]) -> Result<Vec<SubcircuitRowUsage>> {
+    unimplemented!("Must build with feature scroll")
+}
+
+pub fn calculate_base_row_usage() -> Result<Vec<SubcircuitRowUsage>> {
     unimplemented!("Must build with feature scroll")
 }
 
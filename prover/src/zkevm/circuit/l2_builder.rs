@@ -13,7 +13,8 @@ use once_cell::sync::Lazy;
 use std::time::Instant;
 use zkevm_circuits::{
     evm_circuit::witness::{block_apply_mpt_state, Block},
-    util::SubCircuit,
+    super_circuit::SubcircuitRowUsage,
+    util::{SubCircuit, WitnessDelta},
     witness::block_convert,
 };
 
@@ -29,12 +30,15 @@ pub const MAX_RLP_ROWS: usize = 800_000;
 pub const MAX_BYTECODE: usize = 600_000;
 pub const MAX_MPT_ROWS: usize = 1_000_000;
 pub const MAX_KECCAK_ROWS: usize = 1_000_000;
+pub const MAX_SHA256_ROWS: usize = 1_000_000;
+pub const MAX_RIPEMD160_ROWS: usize = 1_000_000;
 pub const MAX_POSEIDON_ROWS: usize = 1_000_000;
 pub const MAX_VERTICAL_ROWS: usize = 1_000_000;
 pub const MAX_RWS: usize = 1_000_000;
 pub const MAX_PRECOMPILE_EC_ADD: usize = 50;
 pub const MAX_PRECOMPILE_EC_MUL: usize = 50;
 pub const MAX_PRECOMPILE_EC_PAIRING: usize = 2;
+pub const MAX_BLAKE2F_ROUNDS: usize = 15_000;
 
 /// default params for super circuit
 pub fn get_super_circuit_params() -> CircuitsParams {
@@ -47,6 +51,8 @@ pub fn get_super_circuit_params() -> CircuitsParams {
         max_bytecode: MAX_BYTECODE,
         max_inner_blocks: MAX_INNER_BLOCKS,
         max_keccak_rows: MAX_KECCAK_ROWS,
+        max_sha256_rows: MAX_SHA256_ROWS,
+        max_ripemd160_rows: MAX_RIPEMD160_ROWS,
         max_poseidon_rows: MAX_POSEIDON_ROWS,
         max_vertical_circuit_rows: MAX_VERTICAL_ROWS,
         max_exp_steps: MAX_EXP_STEPS,
@@ -57,34 +63,23 @@ pub fn get_super_circuit_params() -> CircuitsParams {
             ec_mul: MAX_PRECOMPILE_EC_MUL,
             ec_pairing: MAX_PRECOMPILE_EC_PAIRING,
         },
+        max_blake2f_rounds: MAX_BLAKE2F_ROUNDS,
     }
 }
 
 // TODO: optimize it later
-pub fn calculate_row_usage_of_trace(
-    block_trace: BlockTrace,
-) -> Result<Vec<zkevm_circuits::super_circuit::SubcircuitRowUsage>> {
+pub fn calculate_row_usage_of_trace(block_trace: BlockTrace) -> Result<Vec<SubcircuitRowUsage>> {
     let witness_block = block_traces_to_witness_block(vec![block_trace])?;
     calculate_row_usage_of_witness_block(&witness_block)
 }
 
 pub fn calculate_row_usage_of_witness_block(
     witness_block: &Block<Fr>,
-) -> Result<Vec<zkevm_circuits::super_circuit::SubcircuitRowUsage>> {
+) -> Result<Vec<SubcircuitRowUsage>> {
     let mut rows = <super::SuperCircuit as TargetCircuit>::Inner::min_num_rows_block_subcircuits(
         witness_block,
     );
-    assert_eq!(rows[10].name, "poseidon");
-    assert_eq!(rows[13].name, "mpt");
-    // empirical estimation is each row in mpt cost 1.5 hash (aka 12 rows)
-    let mpt_poseidon_rows = rows[13].row_num_real * 12;
-    if witness_block.mpt_updates.smt_traces.is_empty() {
-        rows[10].row_num_real += mpt_poseidon_rows;
-        log::debug!("calculate_row_usage_of_witness_block light mode, adding {mpt_poseidon_rows} poseidon rows");
-    } else {
-        //rows[10].row_num_real += mpt_poseidon_rows;
-        log::debug!("calculate_row_usage_of_witness_block normal mode, skip adding {mpt_poseidon_rows} poseidon rows");
-    }
+    add_mpt_poseidon_rows(&mut rows, witness_block);
 
     log::debug!(
         "row usage of block {:?}, tx num {:?}, tx calldata len sum {}, rows needed {:?}",
@@ -104,6 +99,57 @@ pub fn calculate_row_usage_of_witness_block(
     Ok(rows)
 }
 
+/// Return the rows each sub circuit requires for `deltas`, in addition to the rows of the witness
+/// before them, see [`WitnessDelta`].
+pub fn calculate_marginal_row_usage(deltas: &[WitnessDelta]) -> Result<Vec<SubcircuitRowUsage>> {
+    let mut rows = <super::SuperCircuit as TargetCircuit>::Inner::marginal_rows_subcircuits(
+        deltas,
+        &get_super_circuit_params(),
+    );
+    add_mpt_poseidon_rows_estimate(&mut rows);
+    Ok(rows)
+}
+
+/// Return the rows each sub circuit requires for any witness that do not depend on its deltas. A
+/// witness built from several blocks of deltas requires them once, in addition to the marginal rows
+/// of the deltas.
+pub fn calculate_base_row_usage() -> Result<Vec<SubcircuitRowUsage>> {
+    let mut rows = <super::SuperCircuit as TargetCircuit>::Inner::base_rows_subcircuits(
+        &get_super_circuit_params(),
+    );
+    add_mpt_poseidon_rows_estimate(&mut rows);
+    Ok(rows)
+}
+
+/// Return the row usage of the sub circuit `name`.
+fn subcircuit_row_usage_mut<'a>(
+    rows: &'a mut [SubcircuitRowUsage],
+    name: &str,
+) -> &'a mut SubcircuitRowUsage {
+    rows.iter_mut()
+        .find(|row| row.name == name)
+        .unwrap_or_else(|| panic!("no row usage of sub circuit {name}"))
+}
+
+// In light mode the zktrie is skipped, so the poseidon hashes of the mpt updates are estimated from
+// the rows of the mpt circuit.
+fn add_mpt_poseidon_rows(rows: &mut [SubcircuitRowUsage], witness_block: &Block<Fr>) {
+    if witness_block.mpt_updates.smt_traces.is_empty() {
+        add_mpt_poseidon_rows_estimate(rows);
+    } else {
+        log::debug!(
+            "calculate_row_usage_of_witness_block normal mode, skip adding mpt poseidon rows"
+        );
+    }
+}
+
+fn add_mpt_poseidon_rows_estimate(rows: &mut [SubcircuitRowUsage]) {
+    // empirical estimation is each row in mpt cost 1.5 hash (aka 12 rows)
+    let mpt_poseidon_rows = subcircuit_row_usage_mut(rows, "mpt").row_num_real * 12;
+    subcircuit_row_usage_mut(rows, "poseidon").row_num_real += mpt_poseidon_rows;
+    log::debug!("adding {mpt_poseidon_rows} estimated mpt poseidon rows");
+}
+
 // FIXME: we need better API name for this.
 // This function also mutates the block trace.
 pub fn check_batch_capacity(block_traces: &mut Vec<BlockTrace>) -> Result<()> {
@@ -12,7 +12,10 @@ use compiler::Compiler;
 use config::Config;
 use log::info;
 use statetest::{
-    load_statetests_suite, run_statetests_suite, run_test, CircuitsConfig, Results, StateTest,
+    load_blockchaintests_suite, load_statetests_suite, minimize, run_blockchain_test,
+    run_blockchaintest, run_blockchaintests_suite, run_in_pool, run_statetest,
+    run_statetests_suite, run_test, serve_worker, to_oneline_spec, to_test_context, BlockchainTest,
+    CircuitsConfig, FailureClass, PoolConfig, ResultInfo, Results, Shard, StateTest,
 };
 use std::{
     collections::{HashMap, HashSet},
@@ -20,7 +23,7 @@ use std::{
     fs::File,
     io::{BufRead, BufReader, Write},
     path::PathBuf,
-    time::SystemTime,
+    time::{Duration, SystemTime},
 };
 use strum::EnumString;
 
@@ -50,6 +53,11 @@ struct Args {
     #[clap(long)]
     inspect: Option<String>,
 
+    /// Shrink a failing state test while it fails the same way, and print it as a oneliner spec
+    /// or a `TestContext` unit test
+    #[clap(long)]
+    minimize: Option<String>,
+
     /// Do not execute any test, just list collected tests
     #[clap(long)]
     ls: bool,
@@ -86,6 +94,31 @@ struct Args {
     #[clap(long)]
     exclude_test_ids: Option<String>,
 
+    /// Run only the i-th (from 0) of n shards of the tests, given as `i/n`
+    #[clap(long)]
+    shard: Option<Shard>,
+
+    /// Run the tests in this many worker processes, as many as CPUs by default with `--shard`,
+    /// `--test-timeout` or `--max-memory`
+    #[clap(long)]
+    workers: Option<usize>,
+
+    /// Kill the worker of a test running for more than this many seconds
+    #[clap(long)]
+    test_timeout: Option<u64>,
+
+    /// Cap the memory (address space) of a worker to this many MiB
+    #[clap(long)]
+    max_memory: Option<u64>,
+
+    /// Merge result files (e.g. of the shards) into one report instead of running tests
+    #[clap(long, value_parser, value_delimiter = ',')]
+    merge: Vec<PathBuf>,
+
+    /// Run the tests requested on stdin, as a worker of the pool
+    #[clap(long, hide = true)]
+    worker: bool,
+
     /// Verbose
     #[clap(short, long)]
     v: bool,
@@ -140,6 +173,124 @@ fn run_single_test(test: StateTest, circuits_config: CircuitsConfig) -> Result<(
     Ok(())
 }
 
+fn run_single_blockchain_test(test: BlockchainTest, circuits_config: CircuitsConfig) -> Result<()> {
+    log::info!("run single test {}", &test);
+    let circuits_config = CircuitsConfig {
+        verbose: true,
+        super_circuit: circuits_config.super_circuit,
+    };
+    log::info!(
+        "result={:?}",
+        run_blockchain_test(test, TestSuite::default(), circuits_config)
+    );
+    Ok(())
+}
+
+/// Keep the tests in `test_ids` in its order if any, else the ones not excluded, in a
+/// deterministic order.
+fn select_tests<T: Clone>(
+    tests: &mut Vec<T>,
+    id: fn(&T) -> &String,
+    test_ids: Option<&[String]>,
+    exclude_test_ids: Option<&HashSet<String>>,
+) {
+    // It is better to sue deterministic testing order.
+    // If there is a list, follow list.
+    // If not, order by test id.
+    if let Some(test_ids) = test_ids {
+        let id_to_test: HashMap<_, _> = tests.iter().map(|t| (id(t).clone(), t.clone())).collect();
+        tests.clear();
+        tests.extend(
+            test_ids
+                .iter()
+                .filter_map(|test_id| id_to_test.get(test_id).cloned()),
+        );
+    } else {
+        // sorting with reversed id string to prevent similar tests go together, so that
+        // computing heavy tests will not trigger OOM.
+        if let Some(set) = exclude_test_ids {
+            tests.retain(|t| !set.contains(id(t)));
+        }
+        tests.sort_by_key(|t| id(t).chars().rev().collect::<String>());
+    }
+}
+
+/// Run the tests, in the worker processes of `pool` if any, inserting the results.
+fn run_tests(
+    state_tests: Vec<StateTest>,
+    blockchain_tests: Vec<BlockchainTest>,
+    pool: Option<&PoolConfig>,
+    circuits_config: &CircuitsConfig,
+    suite: &TestSuite,
+    results: &mut Results,
+) -> Result<()> {
+    match pool {
+        Some(pool) => {
+            let keys = state_tests
+                .iter()
+                .map(|t| format!("{}#{}", t.id, t.path))
+                .chain(
+                    blockchain_tests
+                        .iter()
+                        .map(|t| format!("{}#{}", t.id, t.path)),
+                )
+                .filter(|key| !results.contains(key))
+                .collect();
+            run_in_pool(keys, pool, results)
+        }
+        None => {
+            run_statetests_suite(state_tests, circuits_config, suite, results)?;
+            run_blockchaintests_suite(blockchain_tests, circuits_config, suite, results)
+        }
+    }
+}
+
+/// Write the html report of `results` and print it, with the diff from the previous results
+/// of `report_name`.
+fn write_report(report_name: &str, git_hash: &str, timestamp: u64, results: Results) -> Result<()> {
+    let git_submodule_tests_hash = utils::current_submodule_git_commit()?;
+    let html_filename = format!("{REPORT_FOLDER}/{report_name}.{timestamp}.{git_hash}.html");
+
+    // filter non-csv files and files from the same commit
+    let mut files: Vec<_> = std::fs::read_dir(REPORT_FOLDER)
+        .unwrap()
+        .filter_map(|f| {
+            let filename = f.unwrap().file_name().to_str().unwrap().to_string();
+            (filename.starts_with(&format!("{report_name}."))
+                && filename.ends_with(".csv")
+                && !filename.contains(&format!(".{git_hash}.")))
+            .then_some(filename)
+        })
+        .collect();
+
+    files.sort_by(|f, s| s.cmp(f));
+    let previous = if !files.is_empty() {
+        let file = files.remove(0);
+        let path = format!("{REPORT_FOLDER}/{file}");
+        info!("Comparing with previous results in {path}");
+        Some((file, Results::from_file(PathBuf::from(path))?))
+    } else {
+        None
+    };
+    let report = results.report(previous);
+    std::fs::write(
+        &html_filename,
+        report.gen_html(git_submodule_tests_hash.clone())?,
+    )?;
+    std::fs::write(
+        format!("{REPORT_FOLDER}/{report_name}.{timestamp}.{git_hash}.json"),
+        report.gen_json(git_submodule_tests_hash)?,
+    )?;
+    std::fs::write(
+        format!("{REPORT_FOLDER}/{report_name}.{timestamp}.{git_hash}.xml"),
+        report.gen_junit(report_name),
+    )?;
+
+    report.print_tty()?;
+    info!("{}", html_filename);
+    Ok(())
+}
+
 fn go() -> Result<()> {
     //  RAYON_NUM_THREADS=1 RUST_BACKTRACE=1 cargo run -- --path
     // "tests/src/GeneralStateTestsFiller/**/" --skip-state-circuit
@@ -161,15 +312,70 @@ fn go() -> Result<()> {
 
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
 
+    if !args.merge.is_empty() {
+        let git_hash = utils::current_git_commit()?;
+        let timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let mut results = Results::default();
+        for path in args.merge {
+            results.merge(Results::from_file(path)?);
+        }
+        std::fs::create_dir_all(REPORT_FOLDER)?;
+        results.set_cache(PathBuf::from(format!(
+            "{REPORT_FOLDER}/{}.{timestamp}.{git_hash}.csv",
+            args.suite
+        )));
+        results.write_cache()?;
+        return write_report(&args.suite, &git_hash, timestamp, results);
+    }
+
     log::info!("Using suite '{}'", args.suite);
     log::info!("Parsing and compliling tests...");
     let compiler = Compiler::new(true, Some(PathBuf::from(CODEHASH_FILE)))?;
     let suite = config.suite(&args.suite)?.clone();
+    let mut blockchain_tests = load_blockchaintests_suite(&suite, config.clone())?;
     let mut state_tests = load_statetests_suite(&suite, config, compiler)?;
-    log::info!("{} tests collected in {}", state_tests.len(), suite.path);
+    log::info!(
+        "{} tests collected in {}",
+        state_tests.len() + blockchain_tests.len(),
+        suite.path
+    );
+
+    if args.worker {
+        let state_tests: HashMap<_, _> = state_tests
+            .into_iter()
+            .map(|t| (format!("{}#{}", t.id, t.path), t))
+            .collect();
+        let blockchain_tests: HashMap<_, _> = blockchain_tests
+            .into_iter()
+            .map(|t| (format!("{}#{}", t.id, t.path), t))
+            .collect();
+        return serve_worker(args.max_memory, |key| {
+            if let Some(test) = state_tests.get(key) {
+                run_statetest(test, &suite, &circuits_config)
+            } else if let Some(test) = blockchain_tests.get(key) {
+                run_blockchaintest(test, &suite, &circuits_config)
+            } else {
+                let (test_id, path) = key.split_once('#').unwrap_or((key, ""));
+                ResultInfo {
+                    test_id: test_id.to_string(),
+                    level: ResultLevel::Panic,
+                    details: "not found by the worker".to_string(),
+                    path: path.to_string(),
+                }
+            }
+        });
+    }
 
     if args.ls {
-        let mut list: Vec<_> = state_tests.into_iter().map(|t| t.id).collect();
+        let mut list: Vec<_> = state_tests
+            .into_iter()
+            .map(|t| t.id)
+            .chain(blockchain_tests.into_iter().map(|t| t.id))
+            .collect();
         list.sort();
         write_test_ids(list.as_slice())?;
         for test in list {
@@ -179,6 +385,10 @@ fn go() -> Result<()> {
     }
     if let Some(test_id) = args.inspect {
         // Test only one and return
+        if let Some(test) = blockchain_tests.iter().find(|t| t.id == test_id) {
+            run_single_blockchain_test(test.clone(), circuits_config)?;
+            return Ok(());
+        }
         let mut state_tests_filtered: Vec<_> =
             state_tests.iter().filter(|t| t.id == test_id).collect();
         if state_tests_filtered.is_empty() {
@@ -186,47 +396,108 @@ fn go() -> Result<()> {
                 "Test '{}' not found but found some that partially matches:",
                 test_id
             );
-            for test in state_tests.iter().filter(|t| t.id.contains(&test_id)) {
-                info!("{}", test.id);
+            let ids = state_tests
+                .iter()
+                .map(|t| &t.id)
+                .chain(blockchain_tests.iter().map(|t| &t.id));
+            for id in ids.filter(|id| id.contains(&test_id)) {
+                info!("{}", id);
             }
             bail!("test '{}' not found", test_id);
         }
         run_single_test(state_tests_filtered.remove(0).clone(), circuits_config)?;
         return Ok(());
     };
+    if let Some(test_id) = args.minimize {
+        let test = match state_tests.into_iter().find(|t| t.id == test_id) {
+            Some(test) => test,
+            None => bail!("state test '{}' not found", test_id),
+        };
+        let (test, class) = minimize(test, &suite, &circuits_config)?;
+        println!("{test}");
+
+        // the oneliner spec sets the sender, the nonces and the block itself
+        let spec = to_oneline_spec(&test);
+        let oneliner = StateTest::parse_oneline_spec(&spec)?;
+        if FailureClass::of(&run_statetest(&oneliner, &suite, &circuits_config)) == Some(class) {
+            println!("oneliner spec:\n\n{spec}\n");
+        } else {
+            info!("the oneliner spec does not fail the same way: {}", spec);
+        }
+        println!("TestContext unit test:\n\n{}", to_test_context(&test));
+        return Ok(());
+    }
 
-    // It is better to sue deterministic testing order.
-    // If there is a list, follow list.
-    // If not, order by test id.
-    if let Some(test_ids_path) = args.test_ids {
-        if args.exclude_test_ids.is_some() {
-            log::warn!("--exclude-test-ids is ignored");
+    let test_ids = match args.test_ids {
+        Some(test_ids_path) => {
+            if args.exclude_test_ids.is_some() {
+                log::warn!("--exclude-test-ids is ignored");
+            }
+            Some(read_test_ids(&test_ids_path)?)
         }
-        let test_ids = read_test_ids(&test_ids_path)?;
-        let id_to_test: HashMap<_, _> = state_tests
-            .iter()
-            .map(|t| (t.id.clone(), t.clone()))
-            .collect();
-        state_tests.clear();
-        state_tests.extend(
-            test_ids
-                .into_iter()
-                .filter_map(|test_id| id_to_test.get(&test_id).cloned()),
-        );
-    } else {
-        // sorting with reversed id string to prevent similar tests go together, so that
-        // computing heavy tests will not trigger OOM.
-        if let Some(exclude_test_ids_path) = args.exclude_test_ids {
+        None => None,
+    };
+    let exclude_test_ids = match args.exclude_test_ids {
+        Some(exclude_test_ids_path) if test_ids.is_none() => {
             let buf = std::fs::read_to_string(exclude_test_ids_path)?;
-            let set = buf.lines().map(|s| s.trim()).collect::<HashSet<_>>();
-            state_tests.retain(|t| !set.contains(t.id.as_str()));
+            Some(
+                buf.lines()
+                    .map(|s| s.trim().to_string())
+                    .collect::<HashSet<_>>(),
+            )
         }
-        state_tests.sort_by_key(|t| t.id.chars().rev().collect::<String>());
+        _ => None,
+    };
+    select_tests(
+        &mut state_tests,
+        |t| &t.id,
+        test_ids.as_deref(),
+        exclude_test_ids.as_ref(),
+    );
+    select_tests(
+        &mut blockchain_tests,
+        |t| &t.id,
+        test_ids.as_deref(),
+        exclude_test_ids.as_ref(),
+    );
+    if let Some(shard) = args.shard {
+        state_tests = shard.select(state_tests);
+        blockchain_tests = shard.select(blockchain_tests);
+        log::info!(
+            "{} tests in shard {}/{}",
+            state_tests.len() + blockchain_tests.len(),
+            shard.index,
+            shard.count
+        );
     }
 
+    let use_pool = args.workers.is_some()
+        || args.shard.is_some()
+        || args.test_timeout.is_some()
+        || args.max_memory.is_some();
+    let pool = use_pool.then(|| {
+        let mut worker_args = vec!["--suite".to_string(), args.suite.clone()];
+        if circuits_config.super_circuit {
+            worker_args.extend(["--circuits".to_string(), "sc".to_string()]);
+        }
+        PoolConfig {
+            workers: args
+                .workers
+                .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()))
+                .max(1),
+            timeout: args.test_timeout.map(Duration::from_secs),
+            max_memory: args.max_memory,
+            worker_args,
+        }
+    });
+    // the results of a shard are kept apart from the ones of the whole suite
+    let report_name = match args.shard {
+        Some(shard) => format!("{}-shard{}of{}", args.suite, shard.index, shard.count),
+        None => args.suite.clone(),
+    };
+
     if args.report {
         let git_hash = utils::current_git_commit()?;
-        let git_submodule_tests_hash = utils::current_submodule_git_commit()?;
         let timestamp = SystemTime::now()
             .duration_since(SystemTime::UNIX_EPOCH)
             .unwrap()
@@ -235,18 +506,14 @@ fn go() -> Result<()> {
         std::fs::create_dir_all(REPORT_FOLDER)?;
         let csv_filename = format!(
             "{}/{}.{}.{}.csv",
-            REPORT_FOLDER, args.suite, timestamp, git_hash
-        );
-        let html_filename = format!(
-            "{}/{}.{}.{}.html",
-            REPORT_FOLDER, args.suite, timestamp, git_hash
+            REPORT_FOLDER, report_name, timestamp, git_hash
         );
 
         let cache_file_name = if !args.use_cache {
             None
         } else {
             let mut history_reports =
-                glob::glob(format!("{REPORT_FOLDER}/{}.*.*.csv", args.suite).as_str())?
+                glob::glob(format!("{REPORT_FOLDER}/{report_name}.*.*.csv").as_str())?
                     .collect::<Result<Vec<PathBuf>, glob::GlobError>>()?
                     .into_iter()
                     .map(|path| {
@@ -290,34 +557,16 @@ fn go() -> Result<()> {
 
         previous_results.set_cache(PathBuf::from(csv_filename));
         previous_results.write_cache()?;
-        run_statetests_suite(state_tests, &circuits_config, &suite, &mut previous_results)?;
-
-        // filter non-csv files and files from the same commit
-        let mut files: Vec<_> = std::fs::read_dir(REPORT_FOLDER)
-            .unwrap()
-            .filter_map(|f| {
-                let filename = f.unwrap().file_name().to_str().unwrap().to_string();
-                (filename.starts_with(&format!("{}.", args.suite))
-                    && filename.ends_with(".csv")
-                    && !filename.contains(&format!(".{git_hash}.")))
-                .then_some(filename)
-            })
-            .collect();
-
-        files.sort_by(|f, s| s.cmp(f));
-        let previous = if !files.is_empty() {
-            let file = files.remove(0);
-            let path = format!("{REPORT_FOLDER}/{file}");
-            info!("Comparing with previous results in {path}");
-            Some((file, Results::from_file(PathBuf::from(path))?))
-        } else {
-            None
-        };
-        let report = previous_results.report(previous);
-        std::fs::write(&html_filename, report.gen_html(git_submodule_tests_hash)?)?;
-
-        report.print_tty()?;
-        info!("{}", html_filename);
+        run_tests(
+            state_tests,
+            blockchain_tests,
+            pool.as_ref(),
+            &circuits_config,
+            &suite,
+            &mut previous_results,
+        )?;
+
+        write_report(&report_name, &git_hash, timestamp, previous_results)?;
     } else {
         let mut results = if let Some(cache_filename) = args.cache {
             Results::with_cache(cache_filename)?
@@ -326,7 +575,14 @@ fn go() -> Result<()> {
         };
 
         log::info!("Executing...");
-        run_statetests_suite(state_tests, &circuits_config, &suite, &mut results)?;
+        run_tests(
+            state_tests,
+            blockchain_tests,
+            pool.as_ref(),
+            &circuits_config,
+            &suite,
+            &mut results,
+        )?;
         let success = results.success();
 
         log::info!("Generating report...");
@@ -0,0 +1,485 @@
+use super::{
+    parse,
+    spec::{AccountMatch, Env, StateTestResult},
+};
+use crate::utils::{MainnetFork, TEST_FORK};
+use anyhow::{bail, Result};
+use eth_types::{geth_types::Account, Address, Bytes, H256, U256};
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BlockHeader {
+    base_fee_per_gas: Option<String>,
+    coinbase: String,
+    difficulty: String,
+    gas_limit: String,
+    hash: String,
+    mix_hash: String,
+    number: String,
+    parent_hash: String,
+    state_root: String,
+    timestamp: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Transaction {
+    data: String,
+    gas_limit: String,
+    gas_price: Option<String>,
+    nonce: String,
+    r: String,
+    s: String,
+    v: String,
+    sender: String,
+    to: String,
+    value: String,
+    #[serde(rename = "type")]
+    tx_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonBlock {
+    block_header: Option<BlockHeader>,
+    #[serde(default)]
+    transactions: Vec<Transaction>,
+    #[serde(default)]
+    withdrawals: Vec<serde_json::Value>,
+    expect_exception: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct AccountState {
+    balance: String,
+    code: String,
+    nonce: String,
+    storage: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonBlockchainTest {
+    blocks: Vec<JsonBlock>,
+    genesis_block_header: BlockHeader,
+    network: String,
+    pre: HashMap<String, AccountState>,
+    post_state: Option<HashMap<String, AccountState>>,
+}
+
+/// A signed legacy transaction of a [`BlockchainTestBlock`]
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct BlockchainTestTx {
+    pub from: Address,
+    pub to: Option<Address>,
+    pub nonce: U256,
+    pub value: U256,
+    pub gas_limit: u64,
+    pub gas_price: U256,
+    pub data: Bytes,
+    pub v: u64,
+    pub r: U256,
+    pub s: U256,
+}
+
+/// A valid block of a [`BlockchainTest`]
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct BlockchainTestBlock {
+    /// the header, `previous_hash` being the parent hash
+    pub env: Env,
+    pub hash: H256,
+    /// root of the (Ethereum MPT) state trie after the block
+    pub state_root: H256,
+    pub txs: Vec<BlockchainTestTx>,
+}
+
+/// A chain of blocks from a genesis state, i.e., a test of `BlockchainTests/**/*.json`.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct BlockchainTest {
+    pub path: String,
+    pub id: String,
+    pub genesis_hash: H256,
+    pub pre: BTreeMap<Address, Account>,
+    /// the valid blocks of the chain, in order
+    pub blocks: Vec<BlockchainTestBlock>,
+    /// the state after the last block
+    pub result: StateTestResult,
+}
+
+impl std::fmt::Display for BlockchainTest {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use prettytable::Table;
+        let mut table = Table::new();
+        table.add_row(row!["id", self.id]);
+        if !self.path.is_empty() {
+            table.add_row(row!["path", self.path]);
+        }
+        table.add_row(row!["genesis", format!("{:?}", self.genesis_hash)]);
+        for block in &self.blocks {
+            table.add_row(row![
+                format!("block {}", block.env.current_number),
+                format!(
+                    "hash {:?}, base fee {}, {} txs",
+                    block.hash,
+                    block.env.current_base_fee,
+                    block.txs.len()
+                )
+            ]);
+        }
+        table.add_row(row!["accounts", self.pre.len()]);
+        write!(f, "{table}")
+    }
+}
+
+/// Builds `BlockchainTest` vectors from the (filled) blockchain tests.
+#[derive(Default)]
+pub struct BlockchainTestBuilder;
+
+impl BlockchainTestBuilder {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// If `source` is a blockchain test, i.e., its tests have a chain of `blocks` from a genesis.
+    pub fn is_blockchain_test(source: &str) -> bool {
+        serde_json::from_str::<HashMap<String, serde_json::Value>>(source)
+            .map(|tests| {
+                tests.values().any(|test| {
+                    test.get("blocks").is_some() && test.get("genesisBlockHeader").is_some()
+                })
+            })
+            .unwrap_or(false)
+    }
+
+    /// generates `BlockchainTest` vectors of the [`TEST_FORK`] from a blockchain test file
+    pub fn load_json(&mut self, path: &str, source: &str) -> Result<Vec<BlockchainTest>> {
+        let mut blockchain_tests = Vec::new();
+        let tests: HashMap<String, JsonBlockchainTest> = serde_json::from_str(source)?;
+
+        for (test_name, test) in tests {
+            if MainnetFork::from_str(&test.network).ok() != Some(TEST_FORK) {
+                continue;
+            }
+            // post states too large to be inlined are only given as a hash
+            let post_state = match &test.post_state {
+                Some(post_state) => post_state,
+                None => {
+                    log::debug!(target: "testool", "{test_name}: skip test without post state");
+                    continue;
+                }
+            };
+
+            let genesis_hash = parse::parse_hash(&test.genesis_block_header.hash)?;
+            let mut blocks = Vec::new();
+            let mut parent_hash = genesis_hash;
+            let mut unsupported = None;
+            // invalid blocks are rejected by the clients, hence not part of the chain
+            for block in test.blocks.iter().filter(|b| b.expect_exception.is_none()) {
+                let header = match &block.block_header {
+                    Some(header) => header,
+                    None => bail!("{test_name}: valid block without header"),
+                };
+                if parse::parse_hash(&header.parent_hash)? != parent_hash {
+                    unsupported = Some("chain reorganization");
+                } else if !block.withdrawals.is_empty() {
+                    unsupported = Some("withdrawals");
+                } else if block
+                    .transactions
+                    .iter()
+                    .any(|tx| tx.gas_price.is_none() || Self::is_typed(tx))
+                {
+                    unsupported = Some("typed transaction");
+                }
+                if unsupported.is_some() {
+                    break;
+                }
+
+                let block = Self::parse_block(header, &block.transactions)?;
+                parent_hash = block.hash;
+                blocks.push(block);
+            }
+            if let Some(unsupported) = unsupported {
+                log::debug!(target: "testool", "{test_name}: skip {unsupported}");
+                continue;
+            }
+            if blocks.is_empty() {
+                continue;
+            }
+
+            blockchain_tests.push(BlockchainTest {
+                path: path.to_string(),
+                id: test_name,
+                genesis_hash,
+                pre: Self::parse_accounts(&test.pre)?,
+                blocks,
+                result: Self::parse_accounts(post_state)?
+                    .into_iter()
+                    .map(|(address, account)| {
+                        (
+                            address,
+                            AccountMatch {
+                                address,
+                                balance: Some(account.balance),
+                                code: Some(account.code),
+                                nonce: Some(account.nonce),
+                                storage: account.storage,
+                            },
+                        )
+                    })
+                    .collect(),
+            });
+        }
+
+        Ok(blockchain_tests)
+    }
+
+    fn is_typed(tx: &Transaction) -> bool {
+        tx.tx_type
+            .as_ref()
+            .map_or(false, |tx_type| parse::parse_u64(tx_type).ok() != Some(0))
+    }
+
+    /// parse a block header and its transactions
+    fn parse_block(header: &BlockHeader, txs: &[Transaction]) -> Result<BlockchainTestBlock> {
+        // since the merge, the difficulty is zero and the mix hash is the randomness
+        let difficulty = parse::parse_u256(&header.difficulty)?;
+        let current_difficulty = if difficulty.is_zero() {
+            U256::from_big_endian(parse::parse_hash(&header.mix_hash)?.as_bytes())
+        } else {
+            difficulty
+        };
+
+        let env = Env {
+            current_base_fee: header
+                .base_fee_per_gas
+                .as_ref()
+                .map(|v| parse::parse_u256(v))
+                .transpose()?
+                .unwrap_or_default(),
+            current_coinbase: parse::parse_address(&header.coinbase)?,
+            current_difficulty,
+            current_gas_limit: parse::parse_u64(&header.gas_limit)?,
+            current_number: parse::parse_u64(&header.number)?,
+            current_timestamp: parse::parse_u64(&header.timestamp)?,
+            previous_hash: parse::parse_hash(&header.parent_hash)?,
+        };
+
+        let txs = txs
+            .iter()
+            .map(|tx| {
+                Ok(BlockchainTestTx {
+                    from: parse::parse_address(&tx.sender)?,
+                    to: parse::parse_to_address(&tx.to)?,
+                    nonce: parse::parse_u256(&tx.nonce)?,
+                    value: parse::parse_u256(&tx.value)?,
+                    gas_limit: parse::parse_u64(&tx.gas_limit)?,
+                    gas_price: parse::parse_u256(tx.gas_price.as_deref().unwrap_or_default())?,
+                    data: parse::parse_bytes(&tx.data)?,
+                    v: parse::parse_u64(&tx.v)?,
+                    r: parse::parse_u256(&tx.r)?,
+                    s: parse::parse_u256(&tx.s)?,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(BlockchainTestBlock {
+            env,
+            hash: parse::parse_hash(&header.hash)?,
+            state_root: parse::parse_hash(&header.state_root)?,
+            txs,
+        })
+    }
+
+    /// parse a vector of address=>(storage,balance,code,nonce) entry
+    fn parse_accounts(
+        accounts: &HashMap<String, AccountState>,
+    ) -> Result<BTreeMap<Address, Account>> {
+        let mut res = BTreeMap::new();
+        for (address, acc) in accounts {
+            let address = parse::parse_address(address)?;
+            let mut storage = HashMap::new();
+            for (k, v) in &acc.storage {
+                storage.insert(parse::parse_u256(k)?, parse::parse_u256(v)?);
+            }
+            let account = Account {
+                address,
+                balance: parse::parse_u256(&acc.balance)?,
+                nonce: parse::parse_u256(&acc.nonce)?,
+                code: parse::parse_bytes(&acc.code)?,
+                storage,
+            };
+            res.insert(address, account);
+        }
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const JSON: &str = r#"
+{
+    "nonceChain_Shanghai" : {
+        "_info" : {
+            "comment" : "Two blocks of a transaction each"
+        },
+        "blocks" : [
+            {
+                "blockHeader" : {
+                    "baseFeePerGas" : "0x0a",
+                    "coinbase" : "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+                    "difficulty" : "0x00",
+                    "gasLimit" : "0x0f4240",
+                    "hash" : "0x1111111111111111111111111111111111111111111111111111111111111111",
+                    "mixHash" : "0x0000000000000000000000000000000000000000000000000000000000020000",
+                    "number" : "0x01",
+                    "parentHash" : "0x5e20a0453cecd065ea59c37ac63e079ee08998b6045136a8ce6635c7912ec0b6",
+                    "stateRoot" : "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+                    "timestamp" : "0x03e8"
+                },
+                "transactions" : [
+                    {
+                        "data" : "0x",
+                        "gasLimit" : "0x5208",
+                        "gasPrice" : "0x0a",
+                        "nonce" : "0x00",
+                        "r" : "0x01",
+                        "s" : "0x02",
+                        "sender" : "0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b",
+                        "to" : "0x095e7baea6a6c7c4c2dfeb977efac326af552d87",
+                        "v" : "0x1b",
+                        "value" : "0x01"
+                    }
+                ],
+                "uncleHeaders" : [],
+                "withdrawals" : []
+            },
+            {
+                "expectException" : "TransactionException.NONCE_MISMATCH_TOO_LOW",
+                "rlp" : "0x"
+            },
+            {
+                "blockHeader" : {
+                    "baseFeePerGas" : "0x09",
+                    "coinbase" : "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+                    "difficulty" : "0x00",
+                    "gasLimit" : "0x0f4240",
+                    "hash" : "0x2222222222222222222222222222222222222222222222222222222222222222",
+                    "mixHash" : "0x0000000000000000000000000000000000000000000000000000000000020000",
+                    "number" : "0x02",
+                    "parentHash" : "0x1111111111111111111111111111111111111111111111111111111111111111",
+                    "stateRoot" : "0xbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+                    "timestamp" : "0x03f2"
+                },
+                "transactions" : [
+                    {
+                        "data" : "0x",
+                        "gasLimit" : "0x5208",
+                        "gasPrice" : "0x0a",
+                        "nonce" : "0x01",
+                        "r" : "0x03",
+                        "s" : "0x04",
+                        "sender" : "0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b",
+                        "to" : "0x095e7baea6a6c7c4c2dfeb977efac326af552d87",
+                        "v" : "0x1c",
+                        "value" : "0x01"
+                    }
+                ],
+                "uncleHeaders" : [],
+                "withdrawals" : []
+            }
+        ],
+        "genesisBlockHeader" : {
+            "baseFeePerGas" : "0x0a",
+            "coinbase" : "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+            "difficulty" : "0x00",
+            "gasLimit" : "0x0f4240",
+            "hash" : "0x5e20a0453cecd065ea59c37ac63e079ee08998b6045136a8ce6635c7912ec0b6",
+            "mixHash" : "0x0000000000000000000000000000000000000000000000000000000000020000",
+            "number" : "0x00",
+            "parentHash" : "0x0000000000000000000000000000000000000000000000000000000000000000",
+            "stateRoot" : "0xcccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccc",
+            "timestamp" : "0x00"
+        },
+        "lastblockhash" : "0x2222222222222222222222222222222222222222222222222222222222222222",
+        "network" : "Shanghai",
+        "postState" : {
+            "0x095e7baea6a6c7c4c2dfeb977efac326af552d87" : {
+                "balance" : "0x02",
+                "code" : "0x",
+                "nonce" : "0x00",
+                "storage" : {
+                }
+            }
+        },
+        "pre" : {
+            "0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b" : {
+                "balance" : "0x0de0b6b3a7640000",
+                "code" : "0x",
+                "nonce" : "0x00",
+                "storage" : {
+                }
+            }
+        },
+        "sealEngine" : "NoProof"
+    }
+}
+"#;
+
+    #[test]
+    fn test_blockchain_json_parse() -> Result<()> {
+        assert!(BlockchainTestBuilder::is_blockchain_test(JSON));
+
+        let tests = BlockchainTestBuilder::new().load_json("test_path", JSON)?;
+        if !cfg!(feature = "shanghai") {
+            assert!(tests.is_empty());
+            return Ok(());
+        }
+        assert_eq!(tests.len(), 1);
+        let test = &tests[0];
+
+        let acc095e = Address::from_str("0x095e7baea6a6c7c4c2dfeb977efac326af552d87")?;
+        let acca94f = Address::from_str("0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b")?;
+
+        assert_eq!(test.id, "nonceChain_Shanghai");
+        assert_eq!(test.pre.keys().collect::<Vec<_>>(), vec![&acca94f]);
+        // the invalid block is not part of the chain
+        assert_eq!(test.blocks.len(), 2);
+        assert_eq!(test.blocks[1].env.previous_hash, test.blocks[0].hash);
+        assert_eq!(test.blocks[1].env.current_base_fee, U256::from(9));
+        assert_eq!(test.blocks[1].env.current_difficulty, U256::from(0x20000));
+        assert_eq!(
+            test.blocks[1].txs,
+            vec![BlockchainTestTx {
+                from: acca94f,
+                to: Some(acc095e),
+                nonce: U256::one(),
+                value: U256::one(),
+                gas_limit: 21000,
+                gas_price: U256::from(10),
+                data: Bytes::default(),
+                v: 28,
+                r: U256::from(3),
+                s: U256::from(4),
+            }]
+        );
+        assert_eq!(
+            test.result[&acc095e],
+            AccountMatch {
+                address: acc095e,
+                balance: Some(U256::from(2)),
+                code: Some(Bytes::default()),
+                nonce: Some(U256::zero()),
+                storage: HashMap::new(),
+            }
+        );
+
+        Ok(())
+    }
+}
@@ -1,28 +1,37 @@
-use super::{AccountMatch, StateTest, StateTestResult};
+use super::{
+    AccountMatch, BlockchainTest, BlockchainTestBlock, PostHashes, StateTest, StateTestResult,
+};
 use crate::config::TestSuite;
 use bus_mapping::{
-    circuit_input_builder::{CircuitInputBuilder, CircuitsParams, PrecompileEcParams},
+    circuit_input_builder::{
+        CircuitInputBuilder, CircuitsParams, CopyDataType, PrecompileEcParams,
+    },
+    operation::TxLogField,
     state_db::CodeDB,
 };
 use eth_types::{
-    geth_types, geth_types::TxType, Address, Bytes, GethExecTrace, ToBigEndian, ToWord, H256, U256,
-    U64,
+    geth_types, geth_types::TxType, Address, Bytes, GethExecTrace, ToAddress, ToBigEndian, ToWord,
+    Word, H256, U256, U64,
 };
 use ethers_core::{
-    types::{transaction::eip2718::TypedTransaction, TransactionRequest},
-    utils::keccak256,
+    types::{transaction::eip2718::TypedTransaction, Signature, TransactionRequest},
+    utils::{keccak256, rlp::RlpStream},
 };
 use ethers_signers::LocalWallet;
 use external_tracer::{LoggerConfig, TraceConfig};
 use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr, plonk::Circuit};
 use itertools::Itertools;
 use once_cell::sync::Lazy;
-use std::{collections::HashMap, env, str::FromStr};
+use std::{
+    collections::{BTreeMap, HashMap},
+    env,
+    str::FromStr,
+};
 use thiserror::Error;
 use zkevm_circuits::{
-    bytecode_circuit::circuit::BytecodeCircuit, ecc_circuit::EccCircuit,
-    modexp_circuit::ModExpCircuit, sig_circuit::SigCircuit, super_circuit::SuperCircuit,
-    test_util::CircuitTestBuilder, util::SubCircuit, witness::Block,
+    blake2f_circuit::NUM_ROWS_PER_ROUND, bytecode_circuit::circuit::BytecodeCircuit,
+    ecc_circuit::EccCircuit, modexp_circuit::ModExpCircuit, sig_circuit::SigCircuit,
+    super_circuit::SuperCircuit, test_util::CircuitTestBuilder, util::SubCircuit, witness::Block,
 };
 
 /// Read env var with default value
@@ -55,6 +64,8 @@ pub enum StateTestError {
     SkipTestMaxGasLimit(u64),
     #[error("SkipTestMaxSteps({0})")]
     SkipTestMaxSteps(usize),
+    #[error("SkipTestMaxInnerBlocks({0})")]
+    SkipTestMaxInnerBlocks(usize),
     #[error("SkipTestSelfDestruct")]
     SkipTestSelfDestruct,
     #[error("SkipTestDifficulty")]
@@ -64,6 +75,10 @@ pub enum StateTestError {
     SkipTestBalanceOverflow,
     #[error("Exception(expected:{expected:?}, found:{found:?})")]
     Exception { expected: bool, found: String },
+    #[error("StateRootMismatch(expected:{expected:?}, found:{found:?})")]
+    StateRootMismatch { expected: H256, found: H256 },
+    #[error("LogsHashMismatch(expected:{expected:?}, found:{found:?})")]
+    LogsHashMismatch { expected: H256, found: H256 },
 }
 
 impl StateTestError {
@@ -76,6 +91,7 @@ impl StateTestError {
         matches!(
             self,
             StateTestError::SkipTestMaxSteps(_)
+                | StateTestError::SkipTestMaxInnerBlocks(_)
                 | StateTestError::SkipTestMaxGasLimit(_)
                 | StateTestError::SkipTestSelfDestruct
                 | StateTestError::SkipTestBalanceOverflow
@@ -146,6 +162,154 @@ fn check_post(
     Ok(())
 }
 
+/// Keccak of the rlp of the logs of the transaction, as the `logs` of a filled test.
+fn logs_hash(builder: &CircuitInputBuilder) -> H256 {
+    #[derive(Default)]
+    struct Log {
+        address: Address,
+        topics: BTreeMap<usize, H256>,
+        data: BTreeMap<usize, Word>,
+    }
+
+    let mut logs: BTreeMap<usize, Log> = BTreeMap::new();
+    for op in builder.block.container.tx_log.iter().map(|op| op.op()) {
+        let log = logs.entry(op.log_id).or_default();
+        match op.field {
+            TxLogField::Address => log.address = op.value.to_address(),
+            TxLogField::Topic => {
+                log.topics.insert(op.index, H256(op.value.to_be_bytes()));
+            }
+            TxLogField::Data => {
+                log.data.insert(op.index, op.value);
+            }
+        }
+    }
+
+    // the data is written word by word, its length is the one of the copy to the log
+    let data_lens: HashMap<u64, u64> = builder
+        .block
+        .copy_events
+        .iter()
+        .filter(|event| event.dst_type == CopyDataType::TxLog)
+        .filter_map(|event| Some((event.log_id?, event.src_addr_end - event.src_addr)))
+        .collect();
+
+    let mut stream = RlpStream::new_list(logs.len());
+    for (log_id, log) in logs {
+        let mut data: Vec<u8> = log.data.values().flat_map(|v| v.to_be_bytes()).collect();
+        data.truncate(data_lens.get(&(log_id as u64)).copied().unwrap_or_default() as usize);
+
+        stream.begin_list(3);
+        stream.append(&log.address);
+        stream.append_list::<H256, H256>(&log.topics.into_values().collect::<Vec<_>>());
+        stream.append(&data);
+    }
+    H256(keccak256(stream.out()))
+}
+
+/// RLP of an account leaf of the Ethereum state trie.
+#[cfg(not(feature = "scroll"))]
+fn account_rlp(
+    nonce: Word,
+    balance: Word,
+    storage: &HashMap<Word, Word>,
+    keccak_code_hash: H256,
+) -> Vec<u8> {
+    use super::trie::secure_trie_root;
+
+    let storage = storage
+        .iter()
+        .filter(|(_, value)| !value.is_zero())
+        .map(|(key, value)| {
+            (
+                key.to_be_bytes().to_vec(),
+                ethers_core::utils::rlp::encode(value).to_vec(),
+            )
+        })
+        .collect();
+
+    let mut stream = RlpStream::new_list(4);
+    stream
+        .append(&nonce)
+        .append(&balance)
+        .append(&secure_trie_root(storage))
+        .append(&keccak_code_hash);
+    stream.out().to_vec()
+}
+
+/// Root of the Ethereum state trie of the post state, as the `hash` of a filled test.
+#[cfg(not(feature = "scroll"))]
+fn state_root(builder: &CircuitInputBuilder) -> H256 {
+    let accounts = builder
+        .sdb
+        .accounts()
+        // empty accounts are removed from the state (EIP-161)
+        .filter(|(_, account)| !account.is_empty())
+        .map(|(address, account)| {
+            (
+                address.as_bytes().to_vec(),
+                account_rlp(
+                    account.nonce,
+                    account.balance,
+                    &account.storage,
+                    account.keccak_code_hash,
+                ),
+            )
+        })
+        .collect();
+
+    super::trie::secure_trie_root(accounts)
+}
+
+/// Root of the Ethereum state trie of `accounts`, the state after a block of a blockchain test.
+#[cfg(not(feature = "scroll"))]
+fn accounts_state_root(accounts: &BTreeMap<Address, geth_types::Account>) -> H256 {
+    let accounts = accounts
+        .values()
+        .filter(|account| !account.is_empty())
+        .map(|account| {
+            (
+                account.address.as_bytes().to_vec(),
+                account_rlp(
+                    account.nonce,
+                    account.balance,
+                    &account.storage,
+                    H256(keccak256(&account.code)),
+                ),
+            )
+        })
+        .collect();
+
+    super::trie::secure_trie_root(accounts)
+}
+
+fn check_post_hashes(
+    builder: &CircuitInputBuilder,
+    post_hashes: &PostHashes,
+) -> Result<(), StateTestError> {
+    let logs_hash = logs_hash(builder);
+    if logs_hash != post_hashes.logs_hash {
+        return Err(StateTestError::LogsHashMismatch {
+            expected: post_hashes.logs_hash,
+            found: logs_hash,
+        });
+    }
+
+    // the state of scroll is a zktrie, whose root is not comparable to the Ethereum one
+    #[cfg(not(feature = "scroll"))]
+    {
+        let state_root = state_root(builder);
+        if state_root != post_hashes.state_root {
+            return Err(StateTestError::StateRootMismatch {
+                expected: post_hashes.state_root,
+                found: state_root,
+            });
+        }
+    }
+
+    Ok(())
+}
+
 fn into_traceconfig(st: StateTest) -> (String, TraceConfig, StateTestResult) {
     let chain_id = 1;
     let wallet = LocalWallet::from_str(&hex::encode(st.secret_key.0)).unwrap();
@@ -195,6 +359,8 @@ fn into_traceconfig(st: StateTest) -> (String, TraceConfig, StateTestResult) {
                 gas_tip_cap: U256::zero(),
                 call_data: st.data,
                 access_list: None,
+                max_fee_per_blob_gas: U256::zero(),
+                blob_versioned_hashes: vec![],
                 v: sig.v,
                 r: sig.r,
                 s: sig.s,
@@ -358,40 +524,7 @@ fn trace_config_to_witness_block_l1(
 
     check_geth_traces(&geth_traces, &suite, verbose)?;
 
-    let transactions = trace_config
-        .transactions
-        .into_iter()
-        .enumerate()
-        .map(|(index, tx)| eth_types::Transaction {
-            from: tx.from,
-            to: tx.to,
-            value: tx.value,
-            input: tx.call_data,
-            gas_price: Some(tx.gas_price),
-            access_list: tx.access_list,
-            nonce: tx.nonce,
-            gas: tx.gas_limit,
-            transaction_index: Some(U64::from(index)),
-            r: tx.r,
-            s: tx.s,
-            v: U64::from(tx.v),
-            block_number: Some(U64::from(trace_config.block_constants.number.as_u64())),
-            chain_id: Some(trace_config.chain_id.into()),
-            ..eth_types::Transaction::default()
-        })
-        .collect();
-
-    let eth_block = eth_types::Block {
-        author: Some(trace_config.block_constants.coinbase),
-        timestamp: trace_config.block_constants.timestamp,
-        number: Some(U64::from(trace_config.block_constants.number.as_u64())),
-        difficulty: trace_config.block_constants.difficulty,
-        gas_limit: trace_config.block_constants.gas_limit,
-        base_fee_per_gas: Some(trace_config.block_constants.base_fee),
-        transactions,
-        parent_hash: st.env.previous_hash,
-        ..eth_types::Block::default()
-    };
+    let eth_block = trace_config_to_eth_block(&trace_config, st.env.previous_hash);
 
     let wallet: LocalWallet = ethers_core::k256::ecdsa::SigningKey::from_slice(&st.secret_key)
         .unwrap()
@@ -425,6 +558,49 @@ fn trace_config_to_witness_block_l1(
     Ok(Some((block, builder)))
 }
 
+/// The block of `trace_config`, as expected by the [`CircuitInputBuilder`].
+#[cfg(not(feature = "scroll"))]
+fn trace_config_to_eth_block(
+    trace_config: &TraceConfig,
+    parent_hash: H256,
+) -> eth_types::Block<eth_types::Transaction> {
+    let transactions = trace_config
+        .transactions
+        .iter()
+        .cloned()
+        .enumerate()
+        .map(|(index, tx)| eth_types::Transaction {
+            from: tx.from,
+            to: tx.to,
+            value: tx.value,
+            input: tx.call_data,
+            gas_price: Some(tx.gas_price),
+            access_list: tx.access_list,
+            nonce: tx.nonce,
+            gas: tx.gas_limit,
+            transaction_index: Some(U64::from(index)),
+            r: tx.r,
+            s: tx.s,
+            v: U64::from(tx.v),
+            block_number: Some(U64::from(trace_config.block_constants.number.as_u64())),
+            chain_id: Some(trace_config.chain_id.into()),
+            ..eth_types::Transaction::default()
+        })
+        .collect();
+
+    eth_types::Block {
+        author: Some(trace_config.block_constants.coinbase),
+        timestamp: trace_config.block_constants.timestamp,
+        number: Some(U64::from(trace_config.block_constants.number.as_u64())),
+        difficulty: trace_config.block_constants.difficulty,
+        gas_limit: trace_config.block_constants.gas_limit,
+        base_fee_per_gas: Some(trace_config.block_constants.base_fee),
+        transactions,
+        parent_hash,
+        ..eth_types::Block::default()
+    }
+}
+
 ////// params for degree = 20 ////////////
 pub const MAX_TXS: usize = 100;
 pub const MAX_INNER_BLOCKS: usize = 100;
@@ -434,30 +610,37 @@ pub const MAX_RLP_ROWS: usize = 800_000;
 pub const MAX_BYTECODE: usize = 600_000;
 pub const MAX_MPT_ROWS: usize = 1_000_000;
 pub const MAX_KECCAK_ROWS: usize = 1_000_000;
+pub const MAX_SHA256_ROWS: usize = 1_000_000;
+pub const MAX_RIPEMD160_ROWS: usize = 1_000_000;
 pub const MAX_POSEIDON_ROWS: usize = 1_000_000;
 pub const MAX_VERTICAL_ROWS: usize = 1_000_000;
 pub const MAX_RWS: usize = 1_000_000;
 pub const MAX_PRECOMPILE_EC_ADD: usize = 50;
 pub const MAX_PRECOMPILE_EC_MUL: usize = 50;
 pub const MAX_PRECOMPILE_EC_PAIRING: usize = 2;
+pub const MAX_BLAKE2F_ROUNDS: usize = 15_000;
 
 // TODO: refactor & usage
 fn get_sub_circuit_limit_l2() -> Vec<usize> {
+    let max_blake2f_rows = MAX_BLAKE2F_ROUNDS * NUM_ROWS_PER_ROUND;
     vec![
-        MAX_RWS,           // evm
-        MAX_RWS,           // state
-        MAX_BYTECODE,      // bytecode
-        MAX_RWS,           // copy
-        MAX_KECCAK_ROWS,   // keccak
-        MAX_RWS,           // tx
-        MAX_RLP_ROWS,      // rlp
-        8 * MAX_EXP_STEPS, // exp
-        MAX_KECCAK_ROWS,   // modexp
-        MAX_RWS,           // pi
-        MAX_POSEIDON_ROWS, // poseidon
-        MAX_VERTICAL_ROWS, // sig
-        MAX_VERTICAL_ROWS, // ecc
-        MAX_MPT_ROWS,      // mpt
+        MAX_RWS,            // evm
+        MAX_RWS,            // state
+        MAX_BYTECODE,       // bytecode
+        MAX_RWS,            // copy
+        MAX_KECCAK_ROWS,    // keccak
+        MAX_RWS,            // tx
+        MAX_RLP_ROWS,       // rlp
+        8 * MAX_EXP_STEPS,  // exp
+        MAX_KECCAK_ROWS,    // modexp
+        MAX_RWS,            // pi
+        MAX_POSEIDON_ROWS,  // poseidon
+        MAX_VERTICAL_ROWS,  // sig
+        MAX_VERTICAL_ROWS,  // ecc
+        MAX_SHA256_ROWS,    // sha256
+        MAX_RIPEMD160_ROWS, // ripemd160
+        max_blake2f_rows,   // blake2f
+        MAX_MPT_ROWS,       // mpt
     ]
 }
 
@@ -471,6 +654,8 @@ fn get_params_for_super_circuit_test_l2() -> CircuitsParams {
         max_bytecode: MAX_BYTECODE,
         max_inner_blocks: MAX_INNER_BLOCKS,
         max_keccak_rows: MAX_KECCAK_ROWS,
+        max_sha256_rows: MAX_SHA256_ROWS,
+        max_ripemd160_rows: MAX_RIPEMD160_ROWS,
         max_poseidon_rows: MAX_POSEIDON_ROWS,
         max_vertical_circuit_rows: MAX_VERTICAL_ROWS,
         max_exp_steps: MAX_EXP_STEPS,
@@ -481,6 +666,7 @@ fn get_params_for_super_circuit_test_l2() -> CircuitsParams {
             ec_mul: MAX_PRECOMPILE_EC_MUL,
             ec_pairing: MAX_PRECOMPILE_EC_PAIRING,
         },
+        max_blake2f_rounds: MAX_BLAKE2F_ROUNDS,
     }
 }
 
@@ -495,6 +681,8 @@ fn get_params_for_super_circuit_test() -> CircuitsParams {
         max_bytecode: 512,
         max_evm_rows: 0,
         max_keccak_rows: 0,
+        max_sha256_rows: 0,
+        max_ripemd160_rows: 0,
         max_poseidon_rows: 0,
         max_vertical_circuit_rows: 0,
         max_inner_blocks: 64,
@@ -504,6 +692,7 @@ fn get_params_for_super_circuit_test() -> CircuitsParams {
             ec_mul: 50,
             ec_pairing: 2,
         },
+        max_blake2f_rounds: 200,
     }
 }
 
@@ -517,7 +706,9 @@ fn get_params_for_sub_circuit_test() -> CircuitsParams {
         max_copy_rows: 0, // dynamic
         max_evm_rows: 0,  // dynamic
         max_exp_steps: 5000,
-        max_keccak_rows: 0, // dynamic?
+        max_keccak_rows: 0,    // dynamic?
+        max_sha256_rows: 0,    // dynamic
+        max_ripemd160_rows: 0, // dynamic
         max_poseidon_rows: 0,
         max_vertical_circuit_rows: MAX_VERTICAL_ROWS, // is it good?
         max_inner_blocks: 64,
@@ -527,6 +718,20 @@ fn get_params_for_sub_circuit_test() -> CircuitsParams {
             ec_mul: 50,
             ec_pairing: 2,
         },
+        max_blake2f_rounds: 0, // dynamic
+    }
+}
+
+fn get_circuits_params(circuits_config: &CircuitsConfig) -> CircuitsParams {
+    if !circuits_config.super_circuit {
+        get_params_for_sub_circuit_test()
+    } else {
+        // params for super circuit
+        if cfg!(feature = "scroll") {
+            get_params_for_super_circuit_test_l2()
+        } else {
+            get_params_for_super_circuit_test()
+        }
     }
 }
 
@@ -545,64 +750,50 @@ fn test_with<C: SubCircuit<Fr> + Circuit<Fr>>(block: &Block<Fr>) {
 
 type ScrollSuperCircuit = SuperCircuit<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, 0x100>;
 
-pub fn run_test(
-    st: StateTest,
-    suite: TestSuite,
-    circuits_config: CircuitsConfig,
-) -> Result<(), StateTestError> {
-    let test_id = st.id.clone();
-    log::info!("{test_id}: run-test BEGIN - {circuits_config:?}");
-
-    // get the geth traces
-    let (_, mut trace_config, post) = into_traceconfig(st.clone());
-
-    let balance_overflow = trace_config
-        .accounts
-        .iter()
-        .any(|(_, acc)| acc.balance.to_be_bytes()[0] != 0u8);
-    #[cfg(feature = "scroll")]
-    for (_, acc) in trace_config.accounts.iter_mut() {
-        if acc.balance.to_be_bytes()[0] != 0u8 {
-            acc.balance = U256::from(1u128 << 127);
-            //return Err(StateTestError::SkipTestBalanceOverflow);
-        }
-    }
-    log::debug!("trace_config generated");
-    let circuits_params = if !circuits_config.super_circuit {
-        get_params_for_sub_circuit_test()
-    } else {
-        // params for super circuit
-        if cfg!(feature = "scroll") {
-            get_params_for_super_circuit_test_l2()
+/// Fill the accounts and storage slots of `accounts` not touched during the execution.
+fn fill_untouched_accounts(
+    builder: &mut CircuitInputBuilder,
+    accounts: &BTreeMap<Address, geth_types::Account>,
+) {
+    // fill these "untouched" storage slots
+    // It is better to fill these info after (instead of before) bus-mapping re-exec.
+    // To prevent these data being used unexpectedly.
+    // TODO: another method will be to skip empty account inside check_post?
+    for account in accounts.values() {
+        builder.code_db.insert(account.code.to_vec());
+        let (exist, acc_in_local_sdb) = builder.sdb.get_account_mut(&account.address);
+        if !exist {
+            // modified from bus-mapping/src/mock.rs
+            let keccak_code_hash = H256(keccak256(&account.code));
+            let code_hash = CodeDB::hash(&account.code);
+            *acc_in_local_sdb = bus_mapping::state_db::Account {
+                nonce: account.nonce,
+                balance: account.balance,
+                storage: account.storage.clone(),
+                code_hash,
+                keccak_code_hash,
+                code_size: account.code.len().to_word(),
+            };
         } else {
-            get_params_for_super_circuit_test()
+            for (k, v) in &account.storage {
+                if !acc_in_local_sdb.storage.contains_key(k) {
+                    acc_in_local_sdb.storage.insert(*k, *v);
+                }
+            }
         }
-    };
-
-    #[cfg(feature = "scroll")]
-    let result = trace_config_to_witness_block_l2(
-        trace_config.clone(),
-        st.clone(),
-        suite.clone(),
-        circuits_params,
-        circuits_config.verbose,
-    )?;
-    #[cfg(not(feature = "scroll"))]
-    let result = trace_config_to_witness_block_l1(
-        trace_config.clone(),
-        st.clone(),
-        suite.clone(),
-        circuits_params,
-        circuits_config.verbose,
-    )?;
-
-    let (witness_block, mut builder) = match result {
-        Some((witness_block, builder)) => (witness_block, builder),
-        None => return Ok(()),
-    };
+    }
+}
 
-    log::debug!("witness_block created");
-    //builder.sdb.list_accounts();
+/// Run the circuits selected by `circuits_config` on `witness_block`, panicking if unsatisfied.
+fn check_witness_block(
+    test_id: &str,
+    coinbase: &Address,
+    witness_block: Block<Fr>,
+    suite: &TestSuite,
+    circuits_config: &CircuitsConfig,
+) {
+    // Avoid lint `unused variable` if no feature inner-prove nor chunk-prove.
+    let _ = coinbase;
 
     let check_ccc = || {
         let row_usage = ScrollSuperCircuit::min_num_rows_block_subcircuits(&witness_block);
@@ -612,7 +803,7 @@ pub fn run_test(
                 log::warn!(
                     "ccc detail: suite.id {}, st.id {}, circuit {}, num {}, limit {}",
                     suite.id,
-                    st.id,
+                    test_id,
                     num.name,
                     num.row_num_real,
                     limit
@@ -624,7 +815,7 @@ pub fn run_test(
         if overflow {
             log::warn!(
                 "ccc overflow: st.id {}, detail {} {}",
-                st.id,
+                test_id,
                 max_row_usage.name,
                 max_row_usage.row_num_real
             );
@@ -632,7 +823,7 @@ pub fn run_test(
         } else {
             log::info!(
                 "ccc ok: st.id {}, detail {} {}",
-                st.id,
+                test_id,
                 max_row_usage.name,
                 max_row_usage.row_num_real
             );
@@ -659,7 +850,7 @@ pub fn run_test(
                     {
                         test_with::<SigCircuit<Fr>>(&witness_block);
                     } else {
-                        log::warn!("no ec recover event {}, skip", st.id);
+                        log::warn!("no ec recover event {}, skip", test_id);
                     }
                 }
                 _ => unimplemented!(),
@@ -672,18 +863,78 @@ pub fn run_test(
         } else {
             #[cfg(feature = "inner-prove")]
             {
-                set_env_coinbase(&st.env.current_coinbase);
-                prover::test::inner_prove(&test_id, &witness_block);
+                set_env_coinbase(coinbase);
+                prover::test::inner_prove(test_id, &witness_block);
             }
             #[cfg(feature = "chunk-prove")]
             {
-                set_env_coinbase(&st.env.current_coinbase);
-                prover::test::chunk_prove(&test_id, &witness_block);
+                set_env_coinbase(coinbase);
+                prover::test::chunk_prove(test_id, &witness_block);
             }
             #[cfg(not(any(feature = "inner-prove", feature = "chunk-prove")))]
-            mock_prove(&test_id, &witness_block);
+            mock_prove(test_id, &witness_block);
+        }
+    }
+}
+
+pub fn run_test(
+    st: StateTest,
+    suite: TestSuite,
+    circuits_config: CircuitsConfig,
+) -> Result<(), StateTestError> {
+    let test_id = st.id.clone();
+    log::info!("{test_id}: run-test BEGIN - {circuits_config:?}");
+
+    // get the geth traces
+    let (_, mut trace_config, post) = into_traceconfig(st.clone());
+
+    let balance_overflow = trace_config
+        .accounts
+        .iter()
+        .any(|(_, acc)| acc.balance.to_be_bytes()[0] != 0u8);
+    #[cfg(feature = "scroll")]
+    for (_, acc) in trace_config.accounts.iter_mut() {
+        if acc.balance.to_be_bytes()[0] != 0u8 {
+            acc.balance = U256::from(1u128 << 127);
+            //return Err(StateTestError::SkipTestBalanceOverflow);
         }
+    }
+    log::debug!("trace_config generated");
+    let circuits_params = get_circuits_params(&circuits_config);
+
+    #[cfg(feature = "scroll")]
+    let result = trace_config_to_witness_block_l2(
+        trace_config.clone(),
+        st.clone(),
+        suite.clone(),
+        circuits_params,
+        circuits_config.verbose,
+    )?;
+    #[cfg(not(feature = "scroll"))]
+    let result = trace_config_to_witness_block_l1(
+        trace_config.clone(),
+        st.clone(),
+        suite.clone(),
+        circuits_params,
+        circuits_config.verbose,
+    )?;
+
+    let (witness_block, mut builder) = match result {
+        Some((witness_block, builder)) => (witness_block, builder),
+        None => return Ok(()),
     };
+
+    log::debug!("witness_block created");
+    //builder.sdb.list_accounts();
+
+    check_witness_block(
+        &test_id,
+        &st.env.current_coinbase,
+        witness_block,
+        &suite,
+        &circuits_config,
+    );
+
     log::debug!("balance_overflow = {balance_overflow}");
     log::debug!(
         "has_l2_different_evm_behaviour_trace = {}",
@@ -698,36 +949,347 @@ pub fn run_test(
         log::warn!("skip post check");
     }
     if !skip_post_check {
-        {
-            // fill these "untouched" storage slots
-            // It is better to fill these info after (instead of before) bus-mapping re-exec.
-            // To prevent these data being used unexpectedly.
-            // TODO: another method will be to skip empty account inside check_post?
-            for account in trace_config.accounts.values() {
-                builder.code_db.insert(account.code.to_vec());
-                let (exist, acc_in_local_sdb) = builder.sdb.get_account_mut(&account.address);
-                if !exist {
-                    // modified from bus-mapping/src/mock.rs
-                    let keccak_code_hash = H256(keccak256(&account.code));
-                    let code_hash = CodeDB::hash(&account.code);
-                    *acc_in_local_sdb = bus_mapping::state_db::Account {
-                        nonce: account.nonce,
-                        balance: account.balance,
-                        storage: account.storage.clone(),
-                        code_hash,
-                        keccak_code_hash,
-                        code_size: account.code.len().to_word(),
-                    };
-                } else {
-                    for (k, v) in &account.storage {
-                        if !acc_in_local_sdb.storage.contains_key(k) {
-                            acc_in_local_sdb.storage.insert(*k, *v);
-                        }
+        fill_untouched_accounts(&mut builder, &trace_config.accounts);
+        check_post(&builder, &post)?;
+        if let Some(post_hashes) = &st.post_hashes {
+            check_post_hashes(&builder, post_hashes)?;
+        }
+    }
+    log::info!("{test_id}: run-test END");
+    Ok(())
+}
+
+/// Trace config of `block` on top of the state `accounts`, `history` being the hashes of the
+/// previous blocks of the chain.
+fn block_trace_config(
+    block: &BlockchainTestBlock,
+    accounts: &BTreeMap<Address, geth_types::Account>,
+    history: &[H256],
+) -> TraceConfig {
+    let chain_id = 1;
+    let transactions = block
+        .txs
+        .iter()
+        .map(|tx| {
+            let tx_type = match tx.v {
+                27 | 28 => TxType::PreEip155,
+                _ => TxType::Eip155,
+            };
+            let mut req = TransactionRequest::new()
+                .from(tx.from)
+                .nonce(tx.nonce)
+                .value(tx.value)
+                .data(tx.data.clone())
+                .gas(tx.gas_limit)
+                .gas_price(tx.gas_price);
+            if tx_type == TxType::Eip155 {
+                req = req.chain_id(chain_id);
+            }
+            if let Some(to) = tx.to {
+                req = req.to(to);
+            }
+            // the transactions of the test are already signed
+            let sig = Signature {
+                r: tx.r,
+                s: tx.s,
+                v: tx.v,
+            };
+            let rlp_signed = req.rlp_signed(&sig).to_vec();
+
+            geth_types::Transaction {
+                tx_type,
+                from: tx.from,
+                to: tx.to,
+                nonce: tx.nonce,
+                value: tx.value,
+                gas_limit: U256::from(tx.gas_limit),
+                gas_price: tx.gas_price,
+                gas_fee_cap: U256::zero(),
+                gas_tip_cap: U256::zero(),
+                call_data: tx.data.clone(),
+                access_list: None,
+                max_fee_per_blob_gas: U256::zero(),
+                blob_versioned_hashes: vec![],
+                v: tx.v,
+                r: tx.r,
+                s: tx.s,
+                hash: H256(keccak256(&rlp_signed)),
+                rlp_bytes: rlp_signed,
+                rlp_unsigned_bytes: req.rlp().to_vec(),
+            }
+        })
+        .collect();
+
+    TraceConfig {
+        chain_id,
+        // the most recent 256 block hashes
+        history_hashes: history[history.len().saturating_sub(256)..]
+            .iter()
+            .map(|hash| U256::from_big_endian(hash.as_bytes()))
+            .collect(),
+        block_constants: geth_types::BlockConstants {
+            coinbase: block.env.current_coinbase,
+            timestamp: U256::from(block.env.current_timestamp),
+            number: U64::from(block.env.current_number),
+            difficulty: block.env.current_difficulty,
+            gas_limit: U256::from(block.env.current_gas_limit),
+            base_fee: block.env.current_base_fee,
+        },
+        transactions,
+        accounts: accounts.clone(),
+        logger_config: LoggerConfig {
+            enable_memory: *bus_mapping::util::CHECK_MEM_STRICT,
+            ..Default::default()
+        },
+        #[cfg(feature = "shanghai")]
+        chain_config: Some(external_tracer::ChainConfig::shanghai()),
+        #[cfg(not(feature = "shanghai"))]
+        chain_config: None,
+        #[cfg(feature = "scroll")]
+        l1_queue_index: 0,
+    }
+}
+
+/// Apply the state of `builder` to `accounts`, which becomes the pre state of the next block.
+fn update_accounts(
+    accounts: &mut BTreeMap<Address, geth_types::Account>,
+    builder: &CircuitInputBuilder,
+) {
+    for (address, account) in builder.sdb.accounts() {
+        // empty accounts are removed from the state (EIP-161)
+        if account.is_empty() {
+            accounts.remove(address);
+            continue;
+        }
+        let entry = accounts
+            .entry(*address)
+            .or_insert_with(|| geth_types::Account {
+                address: *address,
+                ..Default::default()
+            });
+        entry.nonce = account.nonce;
+        entry.balance = account.balance;
+        entry.code = builder
+            .code_db
+            .0
+            .get(&account.code_hash)
+            .cloned()
+            .unwrap_or_default()
+            .into();
+        entry.storage.extend(account.storage.iter());
+        entry.storage.retain(|_, value| !value.is_zero());
+    }
+}
+
+#[cfg(not(feature = "scroll"))]
+fn blockchain_test_to_witness_block_l1(
+    bt: &BlockchainTest,
+    suite: &TestSuite,
+    circuits_params: CircuitsParams,
+    verbose: bool,
+) -> Result<(Block<Fr>, CircuitInputBuilder), StateTestError> {
+    use bus_mapping::circuit_input_builder::{get_state_accesses, AccessSet, BlockHead};
+
+    let mut accounts = bt.pre.clone();
+    let mut history = vec![bt.genesis_hash];
+    let mut builder: Option<CircuitInputBuilder> = None;
+
+    for (idx, block) in bt.blocks.iter().enumerate() {
+        let is_last = idx == bt.blocks.len() - 1;
+        let trace_config = block_trace_config(block, &accounts, &history);
+        let geth_traces = external_tracer::trace(&trace_config)
+            .map_err(|err| StateTestError::CircuitInput(err.to_string()))?;
+        if !geth_traces.is_empty() {
+            check_geth_traces(&geth_traces, suite, verbose)?;
+        }
+        let eth_block = trace_config_to_eth_block(&trace_config, block.env.previous_hash);
+
+        match builder.as_mut() {
+            Some(builder) => {
+                // as in `BlockData`, the accounts created by the block start from zero
+                let access_set: AccessSet = get_state_accesses(&eth_block, &geth_traces)
+                    .map_err(|err| StateTestError::CircuitInput(err.to_string()))?
+                    .into();
+                for address in access_set.state.keys() {
+                    if !builder.sdb.get_account(address).0 {
+                        builder
+                            .sdb
+                            .set_account(address, bus_mapping::state_db::Account::zero());
                     }
                 }
+                let header = BlockHead::new(
+                    trace_config.chain_id,
+                    trace_config.history_hashes.clone(),
+                    &eth_block,
+                )
+                .map_err(|err| StateTestError::CircuitInput(err.to_string()))?;
+                builder.block.headers.insert(header.number.as_u64(), header);
+            }
+            None => {
+                let geth_data = geth_types::GethData {
+                    chain_id: trace_config.chain_id,
+                    history_hashes: trace_config.history_hashes.clone(),
+                    geth_traces: geth_traces.clone(),
+                    accounts: trace_config.accounts.values().cloned().collect(),
+                    eth_block: eth_block.clone(),
+                };
+                builder = Some(
+                    bus_mapping::mock::BlockData::new_from_geth_data_with_params(
+                        geth_data,
+                        circuits_params,
+                    )
+                    .new_circuit_input_builder(),
+                );
             }
         }
-        check_post(&builder, &post)?;
+
+        let builder = builder.as_mut().unwrap();
+        builder
+            .handle_block_inner(&eth_block, &geth_traces, is_last, is_last)
+            .map_err(|err| StateTestError::CircuitInput(err.to_string()))?;
+        update_accounts(&mut accounts, builder);
+        let state_root = accounts_state_root(&accounts);
+        if state_root != block.state_root {
+            return Err(StateTestError::StateRootMismatch {
+                expected: block.state_root,
+                found: state_root,
+            });
+        }
+        history.push(block.hash);
+    }
+
+    let builder = builder.expect("blockchain test without blocks");
+    let block: Block<Fr> =
+        zkevm_circuits::evm_circuit::witness::block_convert(&builder.block, &builder.code_db)
+            .unwrap();
+    Ok((block, builder))
+}
+
+/// Use scroll l2 evm to get the l2 BlockTrace of each block
+#[cfg(feature = "scroll")]
+fn blockchain_test_to_witness_block_l2(
+    bt: &BlockchainTest,
+    suite: &TestSuite,
+    circuits_params: CircuitsParams,
+    verbose: bool,
+) -> Result<(Block<Fr>, CircuitInputBuilder), StateTestError> {
+    let mut accounts = bt.pre.clone();
+    for acc in accounts.values_mut() {
+        if acc.balance.to_be_bytes()[0] != 0u8 {
+            acc.balance = U256::from(1u128 << 127);
+        }
+    }
+    let mut history = vec![bt.genesis_hash];
+    let mut builder: Option<CircuitInputBuilder> = None;
+
+    for (idx, block) in bt.blocks.iter().enumerate() {
+        let more = idx != bt.blocks.len() - 1;
+        let trace_config = block_trace_config(block, &accounts, &history);
+        let block_trace =
+            external_tracer::l2trace(&trace_config).map_err(|err| StateTestError::Exception {
+                expected: false,
+                found: err.to_string(),
+            })?;
+
+        let geth_traces = block_trace
+            .execution_results
+            .clone()
+            .into_iter()
+            .map(From::from)
+            .collect::<Vec<_>>();
+        if !geth_traces.is_empty() {
+            check_geth_traces(&geth_traces, suite, verbose)?;
+        }
+
+        match builder.as_mut() {
+            Some(builder) => builder
+                .add_more_l2_trace(block_trace, more)
+                .map_err(|err| StateTestError::CircuitInput(err.to_string()))?,
+            None => {
+                set_env_coinbase(&block_trace.coinbase.address.unwrap());
+                env::set_var("CHAIN_ID", format!("{}", block_trace.chain_id));
+                let difficulty_be_bytes = [0u8; 32];
+                env::set_var("DIFFICULTY", hex::encode(difficulty_be_bytes));
+                builder = Some(
+                    CircuitInputBuilder::new_from_l2_trace(
+                        circuits_params,
+                        block_trace,
+                        more,
+                        false,
+                    )
+                    .map_err(|err| StateTestError::CircuitInput(err.to_string()))?,
+                );
+            }
+        }
+
+        // the state of scroll is a zktrie, whose root is not comparable to the Ethereum one, so
+        // the state roots of the blocks are not checked
+        update_accounts(&mut accounts, builder.as_ref().unwrap());
+        history.push(block.hash);
+    }
+
+    let mut builder = builder.expect("blockchain test without blocks");
+    builder
+        .finalize_building()
+        .map_err(|err| StateTestError::CircuitInput(err.to_string()))?;
+    let mut block = zkevm_circuits::witness::block_convert(&builder.block, &builder.code_db)
+        .map_err(|err| StateTestError::CircuitInput(err.to_string()))?;
+    zkevm_circuits::witness::block_apply_mpt_state(
+        &mut block,
+        builder.mpt_init_state.as_ref().unwrap(),
+    );
+    Ok((block, builder))
+}
+
+/// Run the blocks of `bt` as a single chunk, and check the state root after every block and the
+/// state after the last block.
+pub fn run_blockchain_test(
+    bt: BlockchainTest,
+    suite: TestSuite,
+    circuits_config: CircuitsConfig,
+) -> Result<(), StateTestError> {
+    let test_id = bt.id.clone();
+    log::info!("{test_id}: run-test BEGIN - {circuits_config:?}");
+
+    let mut circuits_params = get_circuits_params(&circuits_config);
+    if bt.blocks.len() > circuits_params.max_inner_blocks {
+        return Err(StateTestError::SkipTestMaxInnerBlocks(bt.blocks.len()));
+    }
+    if !circuits_config.super_circuit {
+        circuits_params.max_txs = bt.blocks.iter().map(|b| b.txs.len()).sum::<usize>().max(1);
+    }
+
+    #[cfg(feature = "scroll")]
+    let (witness_block, mut builder) =
+        blockchain_test_to_witness_block_l2(&bt, &suite, circuits_params, circuits_config.verbose)?;
+    #[cfg(not(feature = "scroll"))]
+    let (witness_block, mut builder) =
+        blockchain_test_to_witness_block_l1(&bt, &suite, circuits_params, circuits_config.verbose)?;
+    log::debug!("witness_block created");
+
+    let last_block = bt.blocks.last().expect("blockchain test without blocks");
+    check_witness_block(
+        &test_id,
+        &last_block.env.current_coinbase,
+        witness_block,
+        &suite,
+        &circuits_config,
+    );
+
+    let balance_overflow = bt
+        .pre
+        .values()
+        .any(|acc| acc.balance.to_be_bytes()[0] != 0u8);
+    let skip_post_check = if cfg!(feature = "scroll") {
+        balance_overflow || builder.has_l2_different_evm_behaviour_trace()
+    } else {
+        false
+    };
+    if skip_post_check {
+        log::warn!("skip post check");
+    } else {
+        fill_untouched_accounts(&mut builder, &bt.pre);
+        check_post(&builder, &bt.result)?;
     }
     log::info!("{test_id}: run-test END");
     Ok(())
@@ -0,0 +1,376 @@
+use super::{
+    parse,
+    spec::{Env, PostHashes, StateTest, DEFAULT_BASE_FEE},
+};
+use crate::utils::{MainnetFork, TEST_FORK};
+use anyhow::{Context, Result};
+use eth_types::{geth_types::Account, Address, U256};
+use ethers_core::{k256::ecdsa::SigningKey, utils::secret_key_to_address};
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TestEnv {
+    current_base_fee: Option<String>,
+    current_coinbase: String,
+    current_difficulty: Option<String>,
+    current_random: Option<String>,
+    current_gas_limit: String,
+    current_number: String,
+    current_timestamp: String,
+    previous_hash: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Indexes {
+    data: usize,
+    gas: usize,
+    value: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Post {
+    hash: String,
+    logs: String,
+    indexes: Indexes,
+    expect_exception: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct AccountPre {
+    balance: String,
+    code: String,
+    nonce: String,
+    storage: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Transaction {
+    data: Vec<String>,
+    gas_limit: Vec<String>,
+    gas_price: Option<String>,
+    nonce: String,
+    secret_key: String,
+    to: String,
+    value: Vec<String>,
+    access_lists: Option<Vec<Option<serde_json::Value>>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct FilledStateTest {
+    env: TestEnv,
+    transaction: Transaction,
+    pre: HashMap<String, AccountPre>,
+    post: HashMap<String, Vec<Post>>,
+}
+
+/// Builds `StateTest` vectors from the filled tests, i.e., `GeneralStateTests/**/*.json`.
+///
+/// Unlike the fillers, the filled tests have the code and the calldata already compiled, and
+/// the expected post state as the hashes of the state trie and of the logs, for each fork.
+#[derive(Default)]
+pub struct FilledStateTestBuilder;
+
+impl FilledStateTestBuilder {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// If `source` is a filled test, i.e., its tests have a `post` section instead of an
+    /// `expect` one.
+    pub fn is_filled(source: &str) -> bool {
+        serde_json::from_str::<HashMap<String, serde_json::Value>>(source)
+            .map(|tests| tests.values().any(|test| test.get("post").is_some()))
+            .unwrap_or(false)
+    }
+
+    /// generates `StateTest` vectors from the `post` entries of the [`TEST_FORK`]
+    pub fn load_json(&mut self, path: &str, source: &str) -> Result<Vec<StateTest>> {
+        let mut state_tests = Vec::new();
+        let tests: HashMap<String, FilledStateTest> = serde_json::from_str(source)?;
+
+        for (test_name, test) in tests {
+            let posts = test
+                .post
+                .iter()
+                .filter(|(fork, _)| MainnetFork::from_str(fork).ok() == Some(TEST_FORK))
+                .flat_map(|(_, posts)| posts)
+                .collect::<Vec<_>>();
+            if posts.is_empty() {
+                log::debug!(target: "testool", "{test_name}: no post state for {TEST_FORK:?}");
+                continue;
+            }
+
+            // the executor only signs legacy transactions
+            let gas_price = match &test.transaction.gas_price {
+                Some(gas_price) => parse::parse_u256(gas_price)?,
+                None => {
+                    log::debug!(target: "testool", "{test_name}: skip typed transaction");
+                    continue;
+                }
+            };
+
+            let env = Self::parse_env(&test.env)?;
+            let pre = Self::parse_accounts_pre(&test.pre)?;
+
+            let to = parse::parse_to_address(&test.transaction.to)?;
+            let secret_key = parse::parse_bytes(&test.transaction.secret_key)?;
+            let from = secret_key_to_address(&SigningKey::from_slice(&secret_key)?);
+            let nonce = parse::parse_u256(&test.transaction.nonce)?;
+
+            for post in posts {
+                let Indexes { data, gas, value } = post.indexes;
+
+                let access_list = test
+                    .transaction
+                    .access_lists
+                    .as_ref()
+                    .and_then(|access_lists| access_lists.get(data));
+                if matches!(access_list, Some(Some(_))) {
+                    log::debug!(target: "testool", "{test_name}: skip access list of d{data}");
+                    continue;
+                }
+
+                state_tests.push(StateTest {
+                    path: path.to_string(),
+                    id: format!("{test_name}_d{data}_g{gas}_v{value}"),
+                    env: env.clone(),
+                    pre: pre.clone(),
+                    result: HashMap::new(),
+                    from,
+                    to,
+                    secret_key: secret_key.clone(),
+                    nonce,
+                    gas_price,
+                    gas_limit: parse::parse_u64(
+                        test.transaction
+                            .gas_limit
+                            .get(gas)
+                            .context("gas index out of range")?,
+                    )?,
+                    value: parse::parse_u256(
+                        test.transaction
+                            .value
+                            .get(value)
+                            .context("value index out of range")?,
+                    )?,
+                    data: parse::parse_bytes(
+                        test.transaction
+                            .data
+                            .get(data)
+                            .context("data index out of range")?,
+                    )?,
+                    post_hashes: Some(PostHashes {
+                        state_root: parse::parse_hash(&post.hash)?,
+                        logs_hash: parse::parse_hash(&post.logs)?,
+                    }),
+                    exception: post.expect_exception.is_some(),
+                });
+            }
+        }
+
+        Ok(state_tests)
+    }
+
+    /// parse env section
+    fn parse_env(env: &TestEnv) -> Result<Env> {
+        // since the merge, the difficulty is superseded by the randomness
+        let difficulty = env
+            .current_difficulty
+            .as_ref()
+            .or(env.current_random.as_ref());
+
+        Ok(Env {
+            current_base_fee: env
+                .current_base_fee
+                .as_ref()
+                .map(|v| parse::parse_u256(v))
+                .transpose()?
+                .unwrap_or_else(|| U256::from(DEFAULT_BASE_FEE)),
+            current_coinbase: parse::parse_address(&env.current_coinbase)?,
+            current_difficulty: difficulty
+                .map(|v| parse::parse_u256(v))
+                .transpose()?
+                .unwrap_or_default(),
+            current_gas_limit: parse::parse_u64(&env.current_gas_limit)?,
+            current_number: parse::parse_u64(&env.current_number)?,
+            current_timestamp: parse::parse_u64(&env.current_timestamp)?,
+            previous_hash: env
+                .previous_hash
+                .as_ref()
+                .map(|v| parse::parse_hash(v))
+                .transpose()?
+                .unwrap_or_default(),
+        })
+    }
+
+    /// parse a vector of address=>(storage,balance,code,nonce) entry
+    fn parse_accounts_pre(
+        accounts_pre: &HashMap<String, AccountPre>,
+    ) -> Result<BTreeMap<Address, Account>> {
+        let mut accounts = BTreeMap::new();
+        for (address, acc) in accounts_pre {
+            let address = parse::parse_address(address)?;
+            let mut storage = HashMap::new();
+            for (k, v) in &acc.storage {
+                storage.insert(parse::parse_u256(k)?, parse::parse_u256(v)?);
+            }
+            let account = Account {
+                address,
+                balance: parse::parse_u256(&acc.balance)?,
+                nonce: parse::parse_u256(&acc.nonce)?,
+                code: parse::parse_bytes(&acc.code)?,
+                storage,
+            };
+            accounts.insert(address, account);
+        }
+        Ok(accounts)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use eth_types::{Bytes, H256};
+
+    const JSON: &str = r#"
+{
+    "add11" : {
+        "_info" : {
+            "comment" : "A test for (add 1 1) opcode result"
+        },
+        "env" : {
+            "currentBaseFee" : "0x07",
+            "currentCoinbase" : "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+            "currentDifficulty" : "0x020000",
+            "currentGasLimit" : "0xff112233445566",
+            "currentNumber" : "0x01",
+            "currentRandom" : "0x0000000000000000000000000000000000000000000000000000000000020000",
+            "currentTimestamp" : "0x03e8",
+            "previousHash" : "0x5e20a0453cecd065ea59c37ac63e079ee08998b6045136a8ce6635c7912ec0b6"
+        },
+        "post" : {
+            "Merge" : [
+                {
+                    "hash" : "0x1111111111111111111111111111111111111111111111111111111111111111",
+                    "indexes" : {
+                        "data" : 0,
+                        "gas" : 0,
+                        "value" : 0
+                    },
+                    "logs" : "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+                    "txbytes" : "0x"
+                }
+            ],
+            "Shanghai" : [
+                {
+                    "hash" : "0x2222222222222222222222222222222222222222222222222222222222222222",
+                    "indexes" : {
+                        "data" : 0,
+                        "gas" : 0,
+                        "value" : 0
+                    },
+                    "logs" : "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+                    "txbytes" : "0x"
+                }
+            ]
+        },
+        "pre" : {
+            "0x095e7baea6a6c7c4c2dfeb977efac326af552d87" : {
+                "balance" : "0x0de0b6b3a7640000",
+                "code" : "0x600160010160005500",
+                "nonce" : "0x00",
+                "storage" : {
+                }
+            }
+        },
+        "transaction" : {
+            "data" : [
+                "0x6001"
+            ],
+            "gasLimit" : [
+                "0x061a80"
+            ],
+            "gasPrice" : "0x0a",
+            "nonce" : "0x00",
+            "secretKey" : "0x45a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8",
+            "sender" : "0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b",
+            "to" : "0x095e7baea6a6c7c4c2dfeb977efac326af552d87",
+            "value" : [
+                "0x0186a0"
+            ]
+        }
+    }
+}
+"#;
+
+    #[test]
+    fn test_filled_json_parse() -> Result<()> {
+        assert!(FilledStateTestBuilder::is_filled(JSON));
+
+        let mut tests = FilledStateTestBuilder::new().load_json("test_path", JSON)?;
+        assert_eq!(tests.len(), 1);
+        let test = tests.remove(0);
+
+        let acc095e = Address::from_str("0x095e7baea6a6c7c4c2dfeb977efac326af552d87")?;
+        let state_root = if cfg!(feature = "shanghai") {
+            "0x2222222222222222222222222222222222222222222222222222222222222222"
+        } else {
+            "0x1111111111111111111111111111111111111111111111111111111111111111"
+        };
+
+        let expected = StateTest {
+            path: "test_path".to_string(),
+            id: "add11_d0_g0_v0".to_string(),
+            env: Env {
+                current_base_fee: U256::from(7u64),
+                current_coinbase: Address::from_str("0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba")?,
+                current_difficulty: U256::from(131072u64),
+                current_gas_limit: 0xFF112233445566,
+                current_number: 1,
+                current_timestamp: 1000,
+                previous_hash: H256::from_str(
+                    "0x5e20a0453cecd065ea59c37ac63e079ee08998b6045136a8ce6635c7912ec0b6",
+                )?,
+            },
+            secret_key: Bytes::from(hex::decode(
+                "45a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8",
+            )?),
+            from: Address::from_str("0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b")?,
+            to: Some(acc095e),
+            gas_limit: 400000,
+            gas_price: U256::from(10u64),
+            nonce: U256::from(0u64),
+            value: U256::from(100000u64),
+            data: Bytes::from(hex::decode("6001")?),
+            pre: BTreeMap::from([(
+                acc095e,
+                Account {
+                    address: acc095e,
+                    nonce: U256::from(0u64),
+                    balance: U256::from(1000000000000000000u64),
+                    code: Bytes::from(hex::decode("600160010160005500")?),
+                    storage: HashMap::new(),
+                },
+            )]),
+            result: HashMap::new(),
+            post_hashes: Some(PostHashes {
+                state_root: H256::from_str(state_root)?,
+                logs_hash: H256::from_str(
+                    "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+                )?,
+            }),
+            exception: false,
+        };
+
+        assert_eq!(expected, test);
+
+        Ok(())
+    }
+}
@@ -201,6 +201,7 @@ impl<'a> JsonStateTestBuilder<'a> {
                                 gas_limit: *gas_limit,
                                 value: *value,
                                 data: data.0.clone(),
+                                post_hashes: None,
                                 exception: false,
                             });
                         }
@@ -436,6 +437,7 @@ mod test {
                     storage: HashMap::from([(U256::zero(), U256::from(2u64))]),
                 },
             )]),
+            post_hashes: None,
             exception: false,
         };
 
@@ -0,0 +1,437 @@
+//! Shrink a failing state test, as long as it keeps failing the same way.
+
+use super::{
+    results::{ResultInfo, ResultLevel},
+    run_statetest, CircuitsConfig, StateTest,
+};
+use crate::config::TestSuite;
+use anyhow::{bail, Result};
+use eth_types::{evm_types::OpcodeId, Address, Bytes, U256};
+use std::fmt::Write;
+
+/// The way a test fails, to be kept while shrinking it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FailureClass {
+    /// a `StateTestError`, by its variant, e.g. `Exception`
+    Error(String),
+    /// a post state mismatch, i.e., the same account, slot and values
+    PostMismatch(String),
+    /// the circuits are not satisfied
+    Unsatisfied,
+    /// any other panic, by its message
+    Panic(String),
+}
+
+impl FailureClass {
+    /// The class of the failure of `result`, if failing.
+    pub fn of(result: &ResultInfo) -> Option<Self> {
+        let details = &result.details;
+        let class = match result.level {
+            ResultLevel::Success | ResultLevel::Ignored => return None,
+            _ if details.contains("circuit was not satisfied")
+                || details.contains("VerifyFailure") =>
+            {
+                Self::Unsatisfied
+            }
+            ResultLevel::Fail => {
+                let variant = details
+                    .split(|c: char| !c.is_alphanumeric())
+                    .next()
+                    .unwrap_or_default();
+                if variant.ends_with("Mismatch") {
+                    Self::PostMismatch(details.clone())
+                } else {
+                    Self::Error(variant.to_string())
+                }
+            }
+            // the values of a panic message, e.g. an index, change while shrinking
+            ResultLevel::Panic => Self::Panic(
+                details
+                    .split(':')
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string(),
+            ),
+        };
+        Some(class)
+    }
+}
+
+struct Minimizer<'a> {
+    suite: &'a TestSuite,
+    circuits_config: &'a CircuitsConfig,
+    class: FailureClass,
+    runs: usize,
+}
+
+impl Minimizer<'_> {
+    fn fails_alike(&mut self, st: &StateTest) -> bool {
+        self.runs += 1;
+        let result = run_statetest(st, self.suite, self.circuits_config);
+        FailureClass::of(&result).as_ref() == Some(&self.class)
+    }
+
+    /// Remove the accounts of the pre state, but the sender and the callee.
+    fn shrink_accounts(&mut self, st: StateTest) -> StateTest {
+        let removable: Vec<Address> = st
+            .pre
+            .keys()
+            .filter(|address| **address != st.from && Some(**address) != st.to)
+            .cloned()
+            .collect();
+        let with_accounts = |kept: &[Address]| {
+            let mut st = st.clone();
+            st.pre
+                .retain(|address, _| !removable.contains(address) || kept.contains(address));
+            st.result.retain(|address, _| st.pre.contains_key(address));
+            st
+        };
+        let kept = shrink_vec(removable.clone(), |kept| {
+            self.fails_alike(&with_accounts(kept))
+        });
+        with_accounts(&kept)
+    }
+
+    fn shrink_storage(&mut self, mut st: StateTest) -> StateTest {
+        let addresses: Vec<Address> = st.pre.keys().cloned().collect();
+        for address in addresses {
+            let mut slots: Vec<(U256, U256)> =
+                st.pre[&address].storage.clone().into_iter().collect();
+            slots.sort();
+            let with_slots = |slots: &[(U256, U256)]| {
+                let mut st = st.clone();
+                st.pre.get_mut(&address).unwrap().storage = slots.iter().cloned().collect();
+                st
+            };
+            let slots = shrink_vec(slots, |slots| self.fails_alike(&with_slots(slots)));
+            st = with_slots(&slots);
+        }
+        st
+    }
+
+    fn shrink_calldata(&mut self, st: StateTest) -> StateTest {
+        let with_data = |data: &[u8]| StateTest {
+            data: Bytes::from(data.to_vec()),
+            ..st.clone()
+        };
+        let data = shrink_vec(st.data.to_vec(), |data| self.fails_alike(&with_data(data)));
+        with_data(&data)
+    }
+
+    /// Remove instructions (i.e., opcodes with their push data) of the code of the accounts.
+    fn shrink_code(&mut self, mut st: StateTest) -> StateTest {
+        let addresses: Vec<Address> = st.pre.keys().cloned().collect();
+        for address in addresses {
+            let code = st.pre[&address].code.clone();
+            let with_code = |ops: &[&[u8]]| {
+                let mut st = st.clone();
+                st.pre.get_mut(&address).unwrap().code = Bytes::from(ops.concat());
+                st
+            };
+            let ops = shrink_vec(instructions(&code), |ops| self.fails_alike(&with_code(ops)));
+            st = with_code(&ops);
+        }
+        st
+    }
+
+    fn shrink_value(&mut self, st: StateTest) -> StateTest {
+        let candidate = StateTest {
+            value: U256::zero(),
+            ..st.clone()
+        };
+        if !st.value.is_zero() && self.fails_alike(&candidate) {
+            candidate
+        } else {
+            st
+        }
+    }
+}
+
+/// Split `code` into its instructions, i.e., an opcode followed by its push data.
+fn instructions(code: &[u8]) -> Vec<&[u8]> {
+    let mut instructions = Vec::new();
+    let mut pc = 0;
+    while pc < code.len() {
+        let end = (pc + 1 + OpcodeId::from(code[pc]).data_len()).min(code.len());
+        instructions.push(&code[pc..end]);
+        pc = end;
+    }
+    instructions
+}
+
+/// Remove chunks of `items`, from all of them down to single ones, as long as `fails` holds
+/// without them.
+fn shrink_vec<T: Clone>(mut items: Vec<T>, mut fails: impl FnMut(&[T]) -> bool) -> Vec<T> {
+    let mut chunk = items.len();
+    while chunk > 0 {
+        let mut start = 0;
+        while start < items.len() {
+            let end = (start + chunk).min(items.len());
+            let candidate: Vec<T> = items[..start]
+                .iter()
+                .chain(&items[end..])
+                .cloned()
+                .collect();
+            if fails(&candidate) {
+                items = candidate;
+            } else {
+                start = end;
+            }
+        }
+        chunk /= 2;
+    }
+    items
+}
+
+/// Shrink the pre state accounts, storage, calldata and code of `st`, as long as it keeps
+/// failing the same way.
+pub fn minimize(
+    st: StateTest,
+    suite: &TestSuite,
+    circuits_config: &CircuitsConfig,
+) -> Result<(StateTest, FailureClass)> {
+    let result = run_statetest(&st, suite, circuits_config);
+    let class = match FailureClass::of(&result) {
+        Some(class) => class,
+        None => bail!(
+            "{} does not fail: {:?} {}",
+            st.id,
+            result.level,
+            result.details
+        ),
+    };
+    log::info!("{}: minimizing while failing with {class:?}", st.id);
+
+    let mut minimizer = Minimizer {
+        suite,
+        circuits_config,
+        class,
+        runs: 1,
+    };
+
+    // the hashes of the post state change with any change of the test, and so do the expected
+    // values of the post state, unless they are the failure.
+    let mut st = st;
+    st.post_hashes = None;
+    if !matches!(minimizer.class, FailureClass::PostMismatch(_)) {
+        st.result.clear();
+    }
+    if !minimizer.fails_alike(&st) {
+        bail!(
+            "{} fails otherwise without its post state hashes or expected values",
+            st.id
+        );
+    }
+
+    loop {
+        let before = st.clone();
+        st = minimizer.shrink_accounts(st);
+        st = minimizer.shrink_storage(st);
+        st = minimizer.shrink_calldata(st);
+        st = minimizer.shrink_code(st);
+        st = minimizer.shrink_value(st);
+        log::info!(
+            "{}: {} accounts, {} bytes of code, {} bytes of calldata after {} runs",
+            st.id,
+            st.pre.len(),
+            st.pre.values().map(|acc| acc.code.len()).sum::<usize>(),
+            st.data.len(),
+            minimizer.runs
+        );
+        if st == before {
+            return Ok((st, minimizer.class));
+        }
+    }
+}
+
+/// `st` as a oneliner spec, see [`StateTest::parse_oneline_spec`].
+///
+/// The spec does not set the sender, the nonces, the gas price nor the block, which are the ones
+/// of [`StateTest::parse_oneline_spec`].
+pub fn to_oneline_spec(st: &StateTest) -> String {
+    let mut spec = format!(
+        "{};{};{:#x};{}",
+        if st.to.is_some() { "call" } else { "create" },
+        hex::encode(&st.data),
+        st.value,
+        st.gas_limit
+    );
+    // the callee is the first account
+    let accounts = st
+        .to
+        .iter()
+        .chain(st.pre.keys().filter(|address| Some(**address) != st.to))
+        .filter(|address| **address != st.from)
+        .filter_map(|address| st.pre.get(address));
+    for account in accounts {
+        write!(
+            spec,
+            " {};{};{:x}",
+            hex::encode(account.address),
+            hex::encode(&account.code),
+            account.balance
+        )
+        .unwrap();
+        let mut slots: Vec<_> = account.storage.iter().collect();
+        slots.sort();
+        for (key, value) in slots {
+            write!(spec, ";{key:#x}:{value:#x}").unwrap();
+        }
+    }
+    spec
+}
+
+/// `st` as a unit test of the circuits with a `TestContext`, e.g. in `zkevm-circuits`.
+pub fn to_test_context(st: &StateTest) -> String {
+    let name: String = st
+        .id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let word = |value: &U256| format!("word!(\"{value:#x}\")");
+    let bytes = |bytes: &Bytes| {
+        format!(
+            "Bytes::from(hex::decode(\"{}\").unwrap())",
+            hex::encode(bytes)
+        )
+    };
+    let index_of = |address: &Address| st.pre.keys().position(|a| a == address).unwrap();
+
+    let mut accounts = String::new();
+    for (i, account) in st.pre.values().enumerate() {
+        write!(
+            accounts,
+            "\n            accs[{i}]\n                .address(address!(\"{:?}\"))\n                .nonce({})\n                .balance({})",
+            account.address,
+            word(&account.nonce),
+            word(&account.balance),
+        )
+        .unwrap();
+        if !account.code.is_empty() {
+            write!(
+                accounts,
+                "\n                .code({})",
+                bytes(&account.code)
+            )
+            .unwrap();
+        }
+        if !account.storage.is_empty() {
+            let mut slots: Vec<_> = account.storage.iter().collect();
+            slots.sort();
+            let slots: Vec<_> = slots
+                .into_iter()
+                .map(|(key, value)| format!("({}, {})", word(key), word(value)))
+                .collect();
+            write!(
+                accounts,
+                "\n                .storage(vec![{}].into_iter())",
+                slots.join(", ")
+            )
+            .unwrap();
+        }
+        accounts.push(';');
+    }
+
+    let mut tx = String::from("\n            txs[0]\n                .from(sender)");
+    if let Some(to) = &st.to {
+        write!(tx, "\n                .to(accs[{}].address)", index_of(to)).unwrap();
+    }
+    write!(
+        tx,
+        "\n                .nonce({})\n                .gas({})\n                .gas_price({})\n                .value({})",
+        word(&st.nonce),
+        word(&U256::from(st.gas_limit)),
+        word(&st.gas_price),
+        word(&st.value),
+    )
+    .unwrap();
+    if !st.data.is_empty() {
+        write!(tx, "\n                .input({})", bytes(&st.data)).unwrap();
+    }
+    tx.push(';');
+
+    format!(
+        r#"#[test]
+fn {name}() {{
+    // minimized from {id} ({path})
+    let sender = LocalWallet::from(
+        SigningKey::from_slice(&hex::decode("{secret_key}").unwrap()).unwrap(),
+    )
+    .with_chain_id(*MOCK_CHAIN_ID);
+
+    let ctx = TestContext::<{num_accounts}, 1>::new(
+        None,
+        |accs| {{{accounts}
+        }},
+        |mut txs, accs| {{{tx}
+        }},
+        |block, _tx| {{
+            block
+                .author(address!("{coinbase:?}"))
+                .number({number})
+                .timestamp({timestamp})
+                .difficulty({difficulty})
+                .gas_limit({gas_limit})
+                .base_fee_per_gas({base_fee})
+        }},
+    )
+    .unwrap();
+
+    CircuitTestBuilder::new_from_test_ctx(ctx).run();
+}}
+"#,
+        id = st.id,
+        path = st.path,
+        secret_key = hex::encode(&st.secret_key),
+        num_accounts = st.pre.len(),
+        coinbase = st.env.current_coinbase,
+        number = st.env.current_number,
+        timestamp = word(&U256::from(st.env.current_timestamp)),
+        difficulty = word(&st.env.current_difficulty),
+        gas_limit = word(&U256::from(st.env.current_gas_limit)),
+        base_fee = word(&st.env.current_base_fee),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_shrink_vec() {
+        // keep the items needed to fail, i.e., 3 and 7
+        let fails = |items: &[usize]| items.contains(&3) && items.contains(&7);
+        assert_eq!(shrink_vec((0..10).collect(), fails), vec![3, 7]);
+        assert_eq!(shrink_vec(vec![], fails), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_instructions() {
+        // PUSH2 0x0102, PUSH1 0x03, ADD, PUSH1 (truncated)
+        let code = [0x61, 0x01, 0x02, 0x60, 0x03, 0x01, 0x60];
+        assert_eq!(
+            instructions(&code),
+            vec![&[0x61, 0x01, 0x02][..], &[0x60, 0x03], &[0x01], &[0x60]]
+        );
+    }
+
+    #[test]
+    fn test_oneline_spec_roundtrip() -> Result<()> {
+        let spec = "call;0102;0x10;100000 0x12;6001600201;0;0x1:0x2";
+        let st = StateTest::parse_oneline_spec(spec)?;
+        let spec = to_oneline_spec(&st);
+        assert_eq!(
+            spec,
+            format!("call;0102;0x10;100000 {:0>40};6001600201;0;0x1:0x2", "12")
+        );
+        assert_eq!(StateTest::parse_oneline_spec(&spec)?, st);
+        Ok(())
+    }
+}
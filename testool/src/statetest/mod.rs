@@ -1,14 +1,27 @@
+mod blockchain;
 mod executor;
+mod filled;
 mod json;
+mod minimize;
 mod parse;
+mod pool;
 mod results;
 pub mod spec;
 mod suite;
+#[cfg(not(feature = "scroll"))]
+mod trie;
 mod yaml;
 
-pub use executor::{run_test, CircuitsConfig, StateTestError};
+pub use blockchain::{BlockchainTest, BlockchainTestBlock, BlockchainTestBuilder};
+pub use executor::{run_blockchain_test, run_test, CircuitsConfig, StateTestError};
+pub use filled::FilledStateTestBuilder;
 pub use json::JsonStateTestBuilder;
-pub use results::{ResultLevel, Results};
-pub use spec::{AccountMatch, Env, StateTest, StateTestResult};
-pub use suite::{load_statetests_suite, run_statetests_suite};
+pub use minimize::{minimize, to_oneline_spec, to_test_context, FailureClass};
+pub use pool::{run_in_pool, serve as serve_worker, PoolConfig, Shard};
+pub use results::{ResultInfo, ResultLevel, Results};
+pub use spec::{AccountMatch, Env, PostHashes, StateTest, StateTestResult};
+pub use suite::{
+    load_blockchaintests_suite, load_statetests_suite, run_blockchaintest,
+    run_blockchaintests_suite, run_statetest, run_statetests_suite,
+};
 pub use yaml::YamlStateTestBuilder;
@@ -0,0 +1,274 @@
+//! Run the tests in worker processes, so that a test exceeding its time or memory budget only
+//! takes its worker down.
+//!
+//! A worker is the `testool` binary itself run with `--worker`: it reads the keys (`id#path`) of
+//! the tests to run from stdin, and writes their results to stdout.
+
+use super::{
+    results::{ResultInfo, ResultLevel},
+    Results,
+};
+use anyhow::{bail, Context, Result};
+use std::{
+    io::{BufRead, BufReader, Write},
+    process::{Child, ChildStdin, Command, Stdio},
+    str::FromStr,
+    sync::{mpsc, Mutex},
+    thread,
+    time::Duration,
+};
+
+/// Prefix of the stdout lines of a worker carrying a result.
+const RESULT_PREFIX: &str = "testool-result ";
+
+/// The `index`-th of `count` shards of the tests, given as `index/count`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    pub index: usize,
+    pub count: usize,
+}
+
+impl FromStr for Shard {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (index, count) = s
+            .split_once('/')
+            .with_context(|| format!("shard {s:?} is not i/n"))?;
+        let shard = Self {
+            index: index.trim().parse()?,
+            count: count.trim().parse()?,
+        };
+        if shard.index >= shard.count {
+            bail!("shard index {} is not in 0..{}", shard.index, shard.count);
+        }
+        Ok(shard)
+    }
+}
+
+impl Shard {
+    /// Keep the tests of this shard, i.e., every `count`-th one.
+    pub fn select<T>(&self, tests: Vec<T>) -> Vec<T> {
+        tests
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| i % self.count == self.index)
+            .map(|(_, test)| test)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    pub workers: usize,
+    /// time after which the worker of a test is killed
+    pub timeout: Option<Duration>,
+    /// cap of the address space of a worker, in MiB
+    pub max_memory: Option<u64>,
+    /// arguments of the workers, i.e., the suite and the circuits to load
+    pub worker_args: Vec<String>,
+}
+
+enum Failure {
+    Timeout,
+    Died,
+}
+
+struct Worker {
+    child: Child,
+    stdin: ChildStdin,
+    results: mpsc::Receiver<ResultInfo>,
+}
+
+impl Worker {
+    fn spawn(config: &PoolConfig) -> Result<Self> {
+        let mut child = Command::new(std::env::current_exe()?)
+            .args(&config.worker_args)
+            .arg("--worker")
+            .args(config.max_memory.map(|mb| format!("--max-memory={mb}")))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .context("failed to spawn worker")?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+
+        let (tx, results) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if let Some(result) = line.strip_prefix(RESULT_PREFIX) {
+                    match serde_json::from_str(result) {
+                        Ok(result) => {
+                            if tx.send(result).is_err() {
+                                break;
+                            }
+                        }
+                        Err(e) => log::error!("invalid worker result {result:?}: {e}"),
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            child,
+            stdin,
+            results,
+        })
+    }
+
+    /// Run the test `key`, waiting at most `timeout` for its result.
+    fn run(&mut self, key: &str, timeout: Option<Duration>) -> Result<ResultInfo, Failure> {
+        writeln!(self.stdin, "{key}")
+            .and_then(|_| self.stdin.flush())
+            .map_err(|_| Failure::Died)?;
+        match timeout {
+            Some(timeout) => self.results.recv_timeout(timeout).map_err(|e| match e {
+                mpsc::RecvTimeoutError::Timeout => Failure::Timeout,
+                mpsc::RecvTimeoutError::Disconnected => Failure::Died,
+            }),
+            None => self.results.recv().map_err(|_| Failure::Died),
+        }
+    }
+
+    /// Kill the worker, returning its exit status.
+    fn kill(&mut self) -> String {
+        let _ = self.child.kill();
+        match self.child.wait() {
+            Ok(status) => status.to_string(),
+            Err(e) => e.to_string(),
+        }
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        self.kill();
+    }
+}
+
+/// Run the tests `keys` (i.e., `id#path`) in a pool of workers, inserting their results.
+pub fn run_in_pool(keys: Vec<String>, config: &PoolConfig, results: &mut Results) -> Result<()> {
+    log::info!("running {} tests in {} workers", keys.len(), config.workers);
+    let test_count = keys.len();
+    let keys = &Mutex::new(keys.into_iter());
+    let results = &Mutex::new(results);
+
+    thread::scope(|s| {
+        let handles: Vec<_> = (0..config.workers)
+            .map(|_| {
+                s.spawn(move || -> Result<()> {
+                    let mut worker = Worker::spawn(config)?;
+                    loop {
+                        let key = match keys.lock().unwrap().next() {
+                            Some(key) => key,
+                            None => return Ok(()),
+                        };
+                        log::debug!(
+                            target : "testool",
+                            "🐕 running test (done {}/{}) {}...",
+                            results.lock().unwrap().tests.len(),
+                            test_count,
+                            key,
+                        );
+
+                        let result = match worker.run(&key, config.timeout) {
+                            Ok(result) => result,
+                            Err(failure) => {
+                                let status = worker.kill();
+                                worker = Worker::spawn(config)?;
+
+                                let (test_id, path) =
+                                    key.split_once('#').unwrap_or((key.as_str(), ""));
+                                let (level, details) = match failure {
+                                    // as too many steps, taking too long is not a failure
+                                    Failure::Timeout => (
+                                        ResultLevel::Ignored,
+                                        format!(
+                                            "SkipTestTimeout({}s)",
+                                            config.timeout.unwrap_or_default().as_secs()
+                                        ),
+                                    ),
+                                    // e.g. out of memory
+                                    Failure::Died => {
+                                        (ResultLevel::Panic, format!("WorkerDied({status})"))
+                                    }
+                                };
+                                ResultInfo {
+                                    test_id: test_id.to_string(),
+                                    level,
+                                    details,
+                                    path: path.to_string(),
+                                }
+                            }
+                        };
+                        results.lock().unwrap().insert(result)?;
+                    }
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .try_for_each(|handle| handle.join().expect("worker thread panicked"))
+    })
+}
+
+/// Serve as a worker, `run` giving the result of the test of a key.
+pub fn serve(max_memory: Option<u64>, run: impl Fn(&str) -> ResultInfo) -> Result<()> {
+    if let Some(max_memory) = max_memory {
+        limit_memory(max_memory)?;
+    }
+
+    for key in std::io::stdin().lock().lines() {
+        let result = run(&key?);
+        let mut stdout = std::io::stdout().lock();
+        writeln!(stdout, "{RESULT_PREFIX}{}", serde_json::to_string(&result)?)?;
+        stdout.flush()?;
+    }
+    Ok(())
+}
+
+/// Cap the address space of this process to `max_memory` MiB, so that an allocation beyond it
+/// aborts the process instead of exhausting the memory of the host.
+#[cfg(unix)]
+fn limit_memory(max_memory: u64) -> Result<()> {
+    let bytes = (max_memory * 1024 * 1024) as libc::rlim_t;
+    let limit = libc::rlimit {
+        rlim_cur: bytes,
+        rlim_max: bytes,
+    };
+    // SAFETY: `limit` is a valid `rlimit`, which `setrlimit` only reads.
+    if unsafe { libc::setrlimit(libc::RLIMIT_AS, &limit) } != 0 {
+        bail!("setrlimit: {}", std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn limit_memory(_max_memory: u64) -> Result<()> {
+    log::warn!("the memory of the workers can only be capped on unix");
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_shard() -> Result<()> {
+        let shard = Shard::from_str("1/3")?;
+        assert_eq!(shard, Shard { index: 1, count: 3 });
+        assert_eq!(shard.select((0..8).collect()), vec![1, 4, 7]);
+
+        // the shards partition the tests
+        let mut all: Vec<usize> = (0..3)
+            .flat_map(|index| Shard { index, count: 3 }.select((0..8).collect()))
+            .collect();
+        all.sort();
+        assert_eq!(all, (0..8).collect::<Vec<_>>());
+
+        assert!(Shard::from_str("3/3").is_err());
+        assert!(Shard::from_str("1").is_err());
+        Ok(())
+    }
+}
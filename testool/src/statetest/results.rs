@@ -1,10 +1,12 @@
 use anyhow::Result;
 use handlebars::Handlebars;
+use once_cell::sync::Lazy;
 use prettytable::{Row, Table};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     io::{Read, Write},
     path::PathBuf,
     str::FromStr,
@@ -16,6 +18,16 @@ const MAX_DETAILS_LEN: usize = 128;
 
 const OUTPUT_ALL_RESULT_LEVELS: [ResultLevel; 2] = [ResultLevel::Fail, ResultLevel::Panic];
 
+// halo2 `VerifyFailure`s, as debug printed by a failing `MockProver::verify`. The gates of the EVM
+// circuit are named after the execution state they constrain.
+static GATE_NAME: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"gate: Gate \{ index: \d+, name: "([^"]*)" \}"#).unwrap());
+static CONSTRAINT_NAME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"gate: Gate \{ index: \d+, name: "[^"]*" \}, index: \d+, name: "([^"]*)""#)
+        .unwrap()
+});
+static LOOKUP_NAME: Lazy<Regex> = Lazy::new(|| Regex::new(r#"Lookup \{ name: "([^"]*)""#).unwrap());
+
 #[derive(Clone, Copy, Debug, Hash, Eq, PartialEq, EnumIter, EnumString, Serialize, Deserialize)]
 pub enum ResultLevel {
     #[strum(ascii_case_insensitive)]
@@ -36,6 +48,59 @@ pub struct ResultInfo {
     pub path: String,
 }
 
+/// Where a test fails in the circuits, as far as its details tell.
+#[derive(Default, Eq, PartialEq, Clone, Debug, Serialize)]
+pub struct FailureLocation {
+    /// the execution state (e.g. `ADD_SUB`) of the failing step
+    pub execution_state: Option<String>,
+    /// the unsatisfied constraint or the failing lookup
+    pub constraint: Option<String>,
+}
+
+impl ResultInfo {
+    /// The location of the first circuit failure in the details, if any.
+    pub fn failure_location(&self) -> FailureLocation {
+        let capture = |regex: &Regex| {
+            regex
+                .captures(&self.details)
+                .map(|captures| captures[1].to_string())
+        };
+        FailureLocation {
+            execution_state: capture(&GATE_NAME),
+            constraint: capture(&CONSTRAINT_NAME).or_else(|| capture(&LOOKUP_NAME)),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ResultRecord<'a> {
+    #[serde(flatten)]
+    info: &'a ResultInfo,
+    #[serde(flatten)]
+    location: FailureLocation,
+}
+
+impl<'a> From<&'a ResultInfo> for ResultRecord<'a> {
+    fn from(info: &'a ResultInfo) -> Self {
+        Self {
+            info,
+            location: info.failure_location(),
+        }
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    // control characters (e.g. of colored panics) are not valid in XML 1.0
+    s.chars()
+        .filter(|c| !c.is_control() || matches!(c, '\n' | '\r' | '\t'))
+        .collect::<String>()
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
 impl ResultLevel {
     pub fn display_string(&self) -> String {
         use ResultLevel::*;
@@ -212,6 +277,117 @@ impl Report {
         let html = reg.render_template(template, data)?;
         Ok(html)
     }
+
+    /// The results, with the location of their failures, and the diffs from the previous ones.
+    pub fn gen_json(&self, githash: String) -> Result<String> {
+        let mut count_by_level: BTreeMap<String, usize> = ResultLevel::iter()
+            .map(|level| (format!("{level:?}"), 0))
+            .collect();
+        for result in self.tests.values() {
+            *count_by_level
+                .entry(format!("{:?}", result.level))
+                .or_default() += 1;
+        }
+        let mut tests: Vec<_> = self.tests.iter().collect();
+        tests.sort_by(|a, b| a.0.cmp(b.0));
+        let tests: Vec<ResultRecord> = tests.into_iter().map(|(_, info)| info.into()).collect();
+        let diffs: Vec<_> = self
+            .diffs
+            .tests
+            .iter()
+            .map(|diff| {
+                json!({
+                    "id": diff.id,
+                    "prev": diff.prev.as_ref().map(ResultRecord::from),
+                    "curr": diff.curr.as_ref().map(ResultRecord::from),
+                })
+            })
+            .collect();
+
+        Ok(serde_json::to_string_pretty(&json!({
+            "githash": githash,
+            "count_by_level": count_by_level,
+            "tests": tests,
+            "previous": self.diffs.previous,
+            "diffs": diffs,
+        }))?)
+    }
+
+    /// The results as JUnit XML, a test suite per folder: a `Fail` is a failure, a `Panic` an
+    /// error and an `Ignored` skipped.
+    pub fn gen_junit(&self, report_name: &str) -> String {
+        let mut by_folder: BTreeMap<&str, Vec<&ResultInfo>> = BTreeMap::new();
+        for result in self.tests.values() {
+            let folder = result
+                .path
+                .rsplit_once('/')
+                .map_or("", |(folder, _)| folder);
+            by_folder.entry(folder).or_default().push(result);
+        }
+
+        let count = |results: &[&ResultInfo], level: ResultLevel| {
+            results
+                .iter()
+                .filter(|result| result.level == level)
+                .count()
+        };
+        let all: Vec<_> = self.tests.values().collect();
+        let mut xml = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\">\n",
+            xml_escape(report_name),
+            all.len(),
+            count(&all, ResultLevel::Fail),
+            count(&all, ResultLevel::Panic),
+            count(&all, ResultLevel::Ignored),
+        );
+        for (folder, mut results) in by_folder {
+            results.sort_by(|a, b| (&a.test_id, &a.path).cmp(&(&b.test_id, &b.path)));
+            xml.push_str(&format!(
+                "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\">\n",
+                xml_escape(folder),
+                results.len(),
+                count(&results, ResultLevel::Fail),
+                count(&results, ResultLevel::Panic),
+                count(&results, ResultLevel::Ignored),
+            ));
+            for result in results {
+                xml.push_str(&format!(
+                    "    <testcase name=\"{}\" classname=\"{}\"",
+                    xml_escape(&result.test_id),
+                    xml_escape(&result.path),
+                ));
+                // the type of a failure is where it fails in the circuits, if known
+                let location = result.failure_location();
+                let kind = [location.execution_state, location.constraint]
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>()
+                    .join(": ");
+                let message = xml_escape(trim(&result.details, MAX_DETAILS_LEN));
+                let details = xml_escape(&result.details);
+                match result.level {
+                    ResultLevel::Success => xml.push_str("/>\n"),
+                    ResultLevel::Ignored => xml.push_str(&format!(
+                        ">\n      <skipped message=\"{message}\"/>\n    </testcase>\n"
+                    )),
+                    ResultLevel::Fail | ResultLevel::Panic => {
+                        let tag = if result.level == ResultLevel::Fail {
+                            "failure"
+                        } else {
+                            "error"
+                        };
+                        xml.push_str(&format!(
+                            ">\n      <{tag} message=\"{message}\" type=\"{}\">{details}</{tag}>\n    </testcase>\n",
+                            xml_escape(&kind)
+                        ));
+                    }
+                }
+            }
+            xml.push_str("  </testsuite>\n");
+        }
+        xml.push_str("</testsuites>\n");
+        xml
+    }
 }
 
 #[derive(Default, Clone)]
@@ -399,6 +575,11 @@ impl Results {
         self.tests.contains_key(test)
     }
 
+    /// Add the results of `other`, e.g. of another shard, overriding the ones of the same tests.
+    pub fn merge(&mut self, other: Results) {
+        self.tests.extend(other.tests);
+    }
+
     pub fn write_cache(&self) -> Result<()> {
         if let Some(path) = &self.cache {
             let mut file = std::fs::OpenOptions::new()
@@ -465,3 +646,45 @@ impl Results {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_failure_location() {
+        let info = |details: &str| ResultInfo {
+            test_id: "id".to_string(),
+            level: ResultLevel::Fail,
+            details: details.to_string(),
+            path: "tests/a/b.json".to_string(),
+        };
+
+        let unsatisfied = info(
+            r#"left: `Err([ConstraintNotSatisfied { constraint: Constraint { gate: Gate { index: 12, name: "ADD_SUB" }, index: 3, name: "Constrain gas left" }, location: InRegion { region: Region { index: 0, name: "Execution step" }, offset: 10 }, cell_values: [] }])`"#,
+        );
+        assert_eq!(
+            unsatisfied.failure_location(),
+            FailureLocation {
+                execution_state: Some("ADD_SUB".to_string()),
+                constraint: Some("Constrain gas left".to_string()),
+            }
+        );
+
+        let lookup = info(
+            r#"left: `Err([Lookup { name: "rw lookup", lookup_index: 4, location: OutsideRegion { row: 7 } }])`"#,
+        );
+        assert_eq!(
+            lookup.failure_location(),
+            FailureLocation {
+                execution_state: None,
+                constraint: Some("rw lookup".to_string()),
+            }
+        );
+
+        assert_eq!(
+            info("BalanceMismatch(expected:1, found:2)").failure_location(),
+            FailureLocation::default()
+        );
+    }
+}
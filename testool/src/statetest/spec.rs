@@ -44,6 +44,15 @@ impl TryInto<Account> for AccountMatch {
 
 pub type StateTestResult = HashMap<Address, AccountMatch>;
 
+/// Hashes of the post state of a filled test
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct PostHashes {
+    /// root of the (Ethereum MPT) state trie after the transaction
+    pub state_root: H256,
+    /// keccak of the rlp of the logs of the transaction
+    pub logs_hash: H256,
+}
+
 #[derive(PartialEq, Clone, Eq, Debug)]
 pub struct StateTest {
     pub path: String,
@@ -59,6 +68,7 @@ pub struct StateTest {
     pub data: Bytes,
     pub pre: BTreeMap<Address, Account>,
     pub result: StateTestResult,
+    pub post_hashes: Option<PostHashes>,
     pub exception: bool,
 }
 
@@ -118,6 +128,10 @@ impl std::fmt::Display for StateTest {
         table.add_row(row!["value", format!("{}", self.value)]);
         table.add_row(row!["data", format(&hex::encode(&self.data), "")]);
         table.add_row(row!["exception", self.exception]);
+        if let Some(post_hashes) = &self.post_hashes {
+            table.add_row(row!["state_root", format!("{:?}", post_hashes.state_root)]);
+            table.add_row(row!["logs_hash", format!("{:?}", post_hashes.logs_hash)]);
+        }
 
         let mut addrs: Vec<_> = self.pre.keys().collect();
         addrs.extend(self.result.keys());
@@ -284,6 +298,7 @@ impl StateTest {
             data: data.into(),
             pre,
             result: HashMap::new(),
+            post_hashes: None,
             exception: false,
         };
 
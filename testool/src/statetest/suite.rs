@@ -1,4 +1,8 @@
-use super::{executor::run_test, CircuitsConfig, JsonStateTestBuilder, Results, StateTest};
+use super::{
+    executor::{run_blockchain_test, run_test},
+    BlockchainTest, BlockchainTestBuilder, CircuitsConfig, FilledStateTestBuilder,
+    JsonStateTestBuilder, Results, StateTest, StateTestError,
+};
 use crate::{
     compiler::Compiler,
     config::{Config, TestSuite},
@@ -19,6 +23,51 @@ pub fn load_statetests_suite(
     config: Config,
     compiler: Compiler,
 ) -> Result<Vec<StateTest>> {
+    load_suite(
+        suite,
+        &config,
+        &["yml", "json"],
+        |t| &t.id,
+        |ext, path, src| match ext {
+            "yml" => YamlStateTestBuilder::new(&compiler).load_yaml(path, src),
+            // blockchain tests are loaded by `load_blockchaintests_suite`
+            "json" if BlockchainTestBuilder::is_blockchain_test(src) => Ok(vec![]),
+            "json" if FilledStateTestBuilder::is_filled(src) => {
+                FilledStateTestBuilder::new().load_json(path, src)
+            }
+            "json" => JsonStateTestBuilder::new(&compiler).load_json(path, src),
+            _ => unreachable!(),
+        },
+    )
+}
+
+pub fn load_blockchaintests_suite(
+    suite: &TestSuite,
+    config: Config,
+) -> Result<Vec<BlockchainTest>> {
+    load_suite(
+        suite,
+        &config,
+        &["json"],
+        |t| &t.id,
+        |_, path, src| {
+            if BlockchainTestBuilder::is_blockchain_test(src) {
+                BlockchainTestBuilder::new().load_json(path, src)
+            } else {
+                Ok(vec![])
+            }
+        },
+    )
+}
+
+/// Load the tests of the files of `suite` with one of `extensions`.
+fn load_suite<T: Send>(
+    suite: &TestSuite,
+    config: &Config,
+    extensions: &[&str],
+    id: fn(&T) -> &String,
+    load: impl Fn(&str, &str, &str) -> Result<Vec<T>> + Sync,
+) -> Result<Vec<T>> {
     let skip_paths: Vec<&String> = config.skip_paths.iter().flat_map(|t| &t.paths).collect();
     let skip_tests: Vec<&String> = config.skip_tests.iter().flat_map(|t| &t.tests).collect();
 
@@ -34,36 +83,31 @@ pub fn load_statetests_suite(
         .filter_map(|file| {
             file.extension().and_then(|ext| {
                 let ext = &*ext.to_string_lossy();
-                if !["yml", "json"].contains(&ext) {
+                if !extensions.contains(&ext) {
                     return None;
                 }
                 let path = file.as_path().to_string_lossy();
-                let tcs = (|| -> Result<Vec<StateTest>> {
+                let tcs = (|| -> Result<Vec<T>> {
                     let src = std::fs::read_to_string(&file)?;
                     log::debug!(target: "testool", "Reading file {:?}", file);
-                    let tcs = match ext {
-                        "yml" => YamlStateTestBuilder::new(&compiler).load_yaml(&path, &src),
-                        "json" => JsonStateTestBuilder::new(&compiler).load_json(&path, &src),
-                        _ => unreachable!(),
-                    };
-                    let mut tcs = match tcs {
+                    let mut tcs = match load(ext, &path, &src) {
                         Ok(tcs) => tcs,
                         Err(e) => {
                             panic!("fail to load {path:?}, err {e:?}");
                         }
                     };
 
-                    tcs.retain(|v| !skip_tests.contains(&&v.id) && suite.allowed(&v.id));
+                    tcs.retain(|v| !skip_tests.contains(&id(v)) && suite.allowed(id(v)));
                     Ok(tcs)
                 })();
 
                 Some(tcs)
             })
         })
-        .collect::<Result<Vec<Vec<StateTest>>>>()?
+        .collect::<Result<Vec<Vec<T>>>>()?
         .into_iter()
         .flatten()
-        .collect::<Vec<StateTest>>();
+        .collect::<Vec<T>>();
     Ok(tcs)
 }
 
@@ -72,12 +116,50 @@ pub fn run_statetests_suite(
     circuits_config: &CircuitsConfig,
     suite: &TestSuite,
     results: &mut Results,
+) -> Result<()> {
+    run_suite(
+        tcs,
+        |t| (&t.id, &t.path),
+        run_test,
+        circuits_config,
+        suite,
+        results,
+    )
+}
+
+pub fn run_blockchaintests_suite(
+    tcs: Vec<BlockchainTest>,
+    circuits_config: &CircuitsConfig,
+    suite: &TestSuite,
+    results: &mut Results,
+) -> Result<()> {
+    run_suite(
+        tcs,
+        |t| (&t.id, &t.path),
+        run_blockchain_test,
+        circuits_config,
+        suite,
+        results,
+    )
+}
+
+/// Run the tests `tcs` not cached in `results` yet, `id_path` being their id and path.
+fn run_suite<T: Clone + Send + Sync>(
+    tcs: Vec<T>,
+    id_path: fn(&T) -> (&String, &String),
+    run: fn(T, TestSuite, CircuitsConfig) -> Result<(), StateTestError>,
+    circuits_config: &CircuitsConfig,
+    suite: &TestSuite,
+    results: &mut Results,
 ) -> Result<()> {
     // Filter already cached entries
     let all_test_count = tcs.len();
-    let tcs: Vec<StateTest> = tcs
+    let tcs: Vec<T> = tcs
         .into_iter()
-        .filter(|t| !results.contains(&format!("{}#{}", t.id, t.path)))
+        .filter(|t| {
+            let (id, path) = id_path(t);
+            !results.contains(&format!("{id}#{path}"))
+        })
         .collect();
 
     log::info!(
@@ -90,24 +172,8 @@ pub fn run_statetests_suite(
 
     // for each test
     let test_count = tcs.len();
-    let run_state_test = |tc: &StateTest| {
-        let (test_id, path) = (tc.id.clone(), tc.path.clone());
-        if !suite.allowed(&test_id) {
-            results
-                .write()
-                .unwrap()
-                .insert(ResultInfo {
-                    test_id,
-                    level: ResultLevel::Ignored,
-                    details: "Ignored in config file".to_string(),
-                    path,
-                })
-                .unwrap();
-            return;
-        }
-
-        std::panic::set_hook(Box::new(|_info| {}));
-
+    let run_state_test = |tc: &T| {
+        let (test_id, path) = id_path(tc);
         log::debug!(
             target : "testool",
             "🐕 running test (done {}/{}) {}#{}...",
@@ -116,72 +182,8 @@ pub fn run_statetests_suite(
             test_id,
             path,
         );
-        let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
-            run_test(tc.clone(), suite.clone(), circuits_config.clone())
-        }));
-
-        // handle panic
-        let result = match result {
-            Ok(res) => res,
-            Err(err) => {
-                let panic_err = if let Some(s) = err.downcast_ref::<String>() {
-                    s.to_string()
-                } else if let Some(s) = err.downcast_ref::<&str>() {
-                    s.to_string()
-                } else {
-                    "unable to get panic info".into()
-                };
-
-                let level = if panic_err.contains("circuit was not satisfied") {
-                    ResultLevel::Fail
-                } else if panic_err.contains("evm_unimplemented") {
-                    ResultLevel::Ignored
-                } else {
-                    ResultLevel::Panic
-                };
-                results
-                    .write()
-                    .unwrap()
-                    .insert(ResultInfo {
-                        test_id,
-                        level,
-                        details: panic_err,
-                        path,
-                    })
-                    .unwrap();
-                return;
-            }
-        };
-
-        // handle known error
-        if let Err(err) = result {
-            results
-                .write()
-                .unwrap()
-                .insert(ResultInfo {
-                    test_id,
-                    level: if err.is_skip() {
-                        ResultLevel::Ignored
-                    } else {
-                        ResultLevel::Fail
-                    },
-                    details: err.to_string(),
-                    path,
-                })
-                .unwrap();
-            return;
-        }
-
-        results
-            .write()
-            .unwrap()
-            .insert(ResultInfo {
-                test_id,
-                level: ResultLevel::Success,
-                details: String::default(),
-                path,
-            })
-            .unwrap();
+        let result = run_one(tc, id_path, run, suite, circuits_config);
+        results.write().unwrap().insert(result).unwrap();
     };
 
     if circuits_config.super_circuit {
@@ -199,3 +201,102 @@ pub fn run_statetests_suite(
     }
     Ok(())
 }
+
+/// Run the state test `tc` in this process.
+pub fn run_statetest(
+    tc: &StateTest,
+    suite: &TestSuite,
+    circuits_config: &CircuitsConfig,
+) -> ResultInfo {
+    run_one(tc, |t| (&t.id, &t.path), run_test, suite, circuits_config)
+}
+
+/// Run the blockchain test `tc` in this process.
+pub fn run_blockchaintest(
+    tc: &BlockchainTest,
+    suite: &TestSuite,
+    circuits_config: &CircuitsConfig,
+) -> ResultInfo {
+    run_one(
+        tc,
+        |t| (&t.id, &t.path),
+        run_blockchain_test,
+        suite,
+        circuits_config,
+    )
+}
+
+/// Run `tc`, catching its panics, and classify the outcome.
+fn run_one<T: Clone>(
+    tc: &T,
+    id_path: fn(&T) -> (&String, &String),
+    run: fn(T, TestSuite, CircuitsConfig) -> Result<(), StateTestError>,
+    suite: &TestSuite,
+    circuits_config: &CircuitsConfig,
+) -> ResultInfo {
+    let (test_id, path) = id_path(tc);
+    let (test_id, path) = (test_id.clone(), path.clone());
+    if !suite.allowed(&test_id) {
+        return ResultInfo {
+            test_id,
+            level: ResultLevel::Ignored,
+            details: "Ignored in config file".to_string(),
+            path,
+        };
+    }
+
+    std::panic::set_hook(Box::new(|_info| {}));
+
+    let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+        run(tc.clone(), suite.clone(), circuits_config.clone())
+    }));
+
+    // handle panic
+    let result = match result {
+        Ok(res) => res,
+        Err(err) => {
+            let panic_err = if let Some(s) = err.downcast_ref::<String>() {
+                s.to_string()
+            } else if let Some(s) = err.downcast_ref::<&str>() {
+                s.to_string()
+            } else {
+                "unable to get panic info".into()
+            };
+
+            let level = if panic_err.contains("circuit was not satisfied") {
+                ResultLevel::Fail
+            } else if panic_err.contains("evm_unimplemented") {
+                ResultLevel::Ignored
+            } else {
+                ResultLevel::Panic
+            };
+            return ResultInfo {
+                test_id,
+                level,
+                details: panic_err,
+                path,
+            };
+        }
+    };
+
+    // handle known error
+    if let Err(err) = result {
+        return ResultInfo {
+            test_id,
+            level: if err.is_skip() {
+                ResultLevel::Ignored
+            } else {
+                ResultLevel::Fail
+            },
+            details: err.to_string(),
+            path,
+        };
+    }
+
+    ResultInfo {
+        test_id,
+        level: ResultLevel::Success,
+        details: String::default(),
+        path,
+    }
+}
@@ -0,0 +1,144 @@
+//! A minimal Merkle Patricia Trie, to compute the Ethereum state root of a post state.
+
+use eth_types::H256;
+use ethers_core::utils::{keccak256, rlp::RlpStream};
+
+/// Root of the trie of `items`, i.e., `(key, value)` with unique keys.
+pub fn trie_root(items: Vec<(Vec<u8>, Vec<u8>)>) -> H256 {
+    if items.is_empty() {
+        // keccak(rlp(""))
+        return H256(keccak256([0x80u8]));
+    }
+
+    let mut items: Vec<_> = items
+        .into_iter()
+        .map(|(key, value)| (to_nibbles(&key), value))
+        .collect();
+    items.sort();
+
+    H256(keccak256(encode_node(&items, 0)))
+}
+
+/// Root of the secure trie of `items`, i.e., keyed by the keccak of the keys.
+pub fn secure_trie_root(items: Vec<(Vec<u8>, Vec<u8>)>) -> H256 {
+    trie_root(
+        items
+            .into_iter()
+            .map(|(key, value)| (keccak256(key).to_vec(), value))
+            .collect(),
+    )
+}
+
+fn to_nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect()
+}
+
+/// Hex prefix encoding of `nibbles` for a leaf or an extension node.
+fn hex_prefix(nibbles: &[u8], is_leaf: bool) -> Vec<u8> {
+    let flag = if is_leaf { 2 } else { 0 };
+    let (mut res, rest) = if nibbles.len() % 2 == 1 {
+        (vec![((flag + 1) << 4) | nibbles[0]], &nibbles[1..])
+    } else {
+        (vec![flag << 4], nibbles)
+    };
+    res.extend(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+    res
+}
+
+/// RLP of the node of the sorted `items`, which share their first `depth` nibbles.
+fn encode_node(items: &[(Vec<u8>, Vec<u8>)], depth: usize) -> Vec<u8> {
+    if let [(key, value)] = items {
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&hex_prefix(&key[depth..], true));
+        stream.append(value);
+        return stream.out().to_vec();
+    }
+
+    // the items are sorted, so the common prefix of all is the one of the first and the last
+    let (first, last) = (&items[0].0, &items[items.len() - 1].0);
+    let prefix_len = first[depth..]
+        .iter()
+        .zip(last[depth..].iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    if prefix_len > 0 {
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&hex_prefix(&first[depth..depth + prefix_len], false));
+        append_node_ref(&mut stream, encode_node(items, depth + prefix_len));
+        return stream.out().to_vec();
+    }
+
+    // a key ending at the branch is the shortest, hence the first
+    let (value, mut rest) = if first.len() == depth {
+        (Some(&items[0].1), &items[1..])
+    } else {
+        (None, items)
+    };
+    let mut stream = RlpStream::new_list(17);
+    for nibble in 0..16 {
+        let n = rest
+            .iter()
+            .take_while(|(key, _)| key[depth] == nibble)
+            .count();
+        if n == 0 {
+            stream.append_empty_data();
+        } else {
+            append_node_ref(&mut stream, encode_node(&rest[..n], depth + 1));
+            rest = &rest[n..];
+        }
+    }
+    match value {
+        Some(value) => stream.append(value),
+        None => stream.append_empty_data(),
+    };
+    stream.out().to_vec()
+}
+
+/// Nodes shorter than a hash are embedded in their parent, others are referenced by hash.
+fn append_node_ref(stream: &mut RlpStream, node: Vec<u8>) {
+    if node.len() < 32 {
+        stream.append_raw(&node, 1);
+    } else {
+        stream.append(&keccak256(&node).to_vec());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    fn items(kvs: &[(&str, &str)]) -> Vec<(Vec<u8>, Vec<u8>)> {
+        kvs.iter()
+            .map(|(k, v)| (k.as_bytes().to_vec(), v.as_bytes().to_vec()))
+            .collect()
+    }
+
+    #[test]
+    fn test_trie_root() {
+        assert_eq!(
+            trie_root(vec![]),
+            H256::from_str("0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421")
+                .unwrap()
+        );
+        assert_eq!(
+            trie_root(items(&[
+                ("doe", "reindeer"),
+                ("dog", "puppy"),
+                ("dogglesworth", "cat")
+            ])),
+            H256::from_str("0x8aad789dff2f538bca5d8ea56e8abe10f4c7ba3a5dea95fea4cd6e7c3a1168d3")
+                .unwrap()
+        );
+        assert_eq!(
+            trie_root(items(&[
+                ("do", "verb"),
+                ("horse", "stallion"),
+                ("doge", "coin"),
+                ("dog", "puppy")
+            ])),
+            H256::from_str("0x5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84")
+                .unwrap()
+        );
+    }
+}
@@ -193,6 +193,7 @@ impl<'a> YamlStateTestBuilder<'a> {
                                 nonce,
                                 value: *value,
                                 data: data.0.clone(),
+                                post_hashes: None,
                                 exception: *exception,
                             });
                             break;
@@ -660,6 +661,7 @@ arith:
                     storage: HashMap::from([(U256::zero(), U256::one())]),
                 },
             )]),
+            post_hashes: None,
             exception: false,
         };
 
@@ -36,7 +36,7 @@ impl FromStr for MainnetFork {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(match s {
             "Shanghai" => Self::Shanghai,
-            "Merge" => Self::Merge,
+            "Merge" | "Paris" => Self::Merge,
             "Gray Glacier" => Self::GrayGlacier,
             "Arrow Glacier" => Self::ArrowGlacier,
             "Altair" => Self::Altair,
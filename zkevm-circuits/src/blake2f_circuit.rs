@@ -0,0 +1,807 @@
+//! The BLAKE2F circuit implementation, used to verify the calls to the
+//! BLAKE2 compression function F precompiled contract (EIP-152).
+/// Blake2f bit-level witness generation
+pub mod blake2f_bit;
+mod param;
+mod util;
+
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+mod dev;
+#[cfg(any(feature = "test", test))]
+mod test;
+
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+pub use dev::Blake2fCircuit as TestBlake2fCircuit;
+pub use param::NUM_ROWS_PER_ROUND;
+
+use std::marker::PhantomData;
+
+use self::{
+    blake2f_bit::{multi_blake2f, num_segments, Blake2fRow, Segment},
+    param::*,
+    util::*,
+};
+use crate::{
+    evm_circuit::util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+    table::{Blake2fTable, LookupTable},
+    util::{Challenges, SubCircuit, SubCircuitConfig, SubCircuitRowEstimator, WitnessDelta},
+    witness,
+};
+use bus_mapping::circuit_input_builder::{Blake2fEvent, CircuitsParams, PrecompileEvent};
+use eth_types::Field;
+use gadgets::util::{not, sum, Expr};
+use halo2_proofs::{
+    circuit::{Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, VirtualCells},
+    poly::Rotation,
+};
+
+#[cfg(feature = "onephase")]
+use halo2_proofs::plonk::FirstPhase as SecondPhase;
+#[cfg(not(feature = "onephase"))]
+use halo2_proofs::plonk::SecondPhase;
+
+/// Blake2fConfig
+///
+/// The rows are split in segments of `NUM_ROWS_PER_ROUND` rows, and a
+/// compression of `rounds` rounds takes `rounds + 2` segments: a setup
+/// segment holding the input words and the initial state, one segment per
+/// round, and a finalization segment computing the output words. Every row of
+/// a round writes one state word, so the rows read the state words they need
+/// at fixed rotations given by `round_layout`. The message words are carried
+/// from segment to segment and permuted by the `SIGMA` selected by the round
+/// index modulo 10.
+#[derive(Clone, Debug)]
+pub struct Blake2fCircuitConfig<F> {
+    q_enable: Column<Fixed>,
+    q_first: Column<Fixed>,
+    q_start: Column<Fixed>,
+    q_g: [Column<Fixed>; NUM_G],
+    q_op: [Column<Fixed>; NUM_ROWS_PER_G],
+    word: [Column<Advice>; NUM_BITS_PER_WORD],
+    operand: [Column<Advice>; NUM_BITS_PER_WORD],
+    carry: [Column<Advice>; NUM_CARRY_BITS],
+    value: Column<Advice>,
+    is_setup: Column<Advice>,
+    is_round: Column<Advice>,
+    is_finalization: Column<Advice>,
+    sel: [Column<Advice>; NUM_SIGMAS],
+    rounds_left: Column<Advice>,
+    m: Column<Advice>,
+    x: Column<Advice>,
+    h: Column<Advice>,
+    acc: Column<Advice>,
+    /// The columns for other circuits to lookup BLAKE2F results
+    pub blake2f_table: Blake2fTable,
+    _marker: PhantomData<F>,
+}
+
+/// Circuit configuration arguments
+#[derive(Debug, Clone)]
+pub struct Blake2fCircuitConfigArgs<F: Field> {
+    /// Blake2fTable
+    pub blake2f_table: Blake2fTable,
+    /// Challenges randomness
+    pub challenges: Challenges<Expression<F>>,
+}
+
+impl<F: Field> SubCircuitConfig<F> for Blake2fCircuitConfig<F> {
+    type ConfigArgs = Blake2fCircuitConfigArgs<F>;
+
+    /// Return a new Blake2fCircuitConfig
+    fn new(
+        meta: &mut ConstraintSystem<F>,
+        Self::ConfigArgs {
+            blake2f_table,
+            challenges,
+        }: Self::ConfigArgs,
+    ) -> Self {
+        let q_enable = blake2f_table.q_enable;
+        let q_first = meta.fixed_column();
+        let q_start = meta.fixed_column();
+        let q_g = array_init::array_init(|_| meta.fixed_column());
+        let q_op = array_init::array_init(|_| meta.fixed_column());
+        let word = array_init::array_init(|_| meta.advice_column());
+        let operand = array_init::array_init(|_| meta.advice_column());
+        let carry = array_init::array_init(|_| meta.advice_column());
+        let value = meta.advice_column();
+        let is_setup = meta.advice_column();
+        let is_round = meta.advice_column();
+        let is_finalization = meta.advice_column();
+        let sel = array_init::array_init(|_| meta.advice_column());
+        let rounds_left = meta.advice_column();
+        let m = meta.advice_column();
+        let x = meta.advice_column();
+        let h = meta.advice_column();
+        let acc = meta.advice_column_in(SecondPhase);
+
+        let is_final = blake2f_table.is_final;
+        let rounds = blake2f_table.rounds;
+        let input_rlc = blake2f_table.input_rlc;
+        let output_rlc = blake2f_table.output_rlc;
+
+        let (positions, reads) = round_layout();
+
+        // The selector of the `pos`-th row of a segment
+        let q_pos = |meta: &mut VirtualCells<F>, pos: usize| {
+            meta.query_fixed(q_g[pos / NUM_ROWS_PER_G], Rotation::cur())
+                * meta.query_fixed(q_op[pos % NUM_ROWS_PER_G], Rotation::cur())
+        };
+        // The previous segment is a setup or a round, so that its state words
+        // can be read
+        let is_state_prev = |meta: &mut VirtualCells<F>| {
+            meta.query_advice(is_setup, Rotation::prev())
+                + meta.query_advice(is_round, Rotation::prev())
+        };
+        let segment_rot = |rot: i32| Rotation(rot - NUM_ROWS_PER_ROUND as i32);
+
+        meta.create_gate("boolean checks", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            for column in word
+                .iter()
+                .chain(operand.iter())
+                .chain(carry.iter())
+                .chain(sel.iter())
+                .chain([is_setup, is_round, is_finalization].iter())
+            {
+                cb.require_boolean("boolean value", meta.query_advice(*column, Rotation::cur()));
+            }
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("segment", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let flags = [is_setup, is_round, is_finalization]
+                .map(|column| meta.query_advice(column, Rotation::cur()));
+            cb.require_equal(
+                "value = decode(word)",
+                meta.query_advice(value, Rotation::cur()),
+                decode(&query_bits(meta, &word, 0)),
+            );
+            cb.require_boolean("at most one segment kind", sum::expr(flags.iter()));
+            cb.require_equal(
+                "is_final is only set on the output row of a finalization",
+                meta.query_advice(is_final, Rotation::cur()),
+                flags[2].clone() * q_pos(meta, POS_OUTPUT_LAST),
+            );
+            cb.condition(meta.query_fixed(q_first, Rotation::cur()), |cb| {
+                cb.require_zero("the first segment is not a round", flags[1].clone());
+                cb.require_zero("the first segment is not a finalization", flags[2].clone());
+            });
+            cb.condition(flags[1].clone(), |cb| {
+                cb.require_equal(
+                    "a round uses exactly one permutation",
+                    sum::expr(sel.map(|column| meta.query_advice(column, Rotation::cur()))),
+                    1.expr(),
+                );
+            });
+            cb.condition(
+                not::expr(meta.query_fixed(q_start, Rotation::cur())),
+                |cb| {
+                    for column in [is_setup, is_round, is_finalization, rounds_left, rounds]
+                        .iter()
+                        .chain(sel.iter())
+                        .chain([input_rlc].iter())
+                    {
+                        cb.require_equal(
+                            "segment values are constant within a segment",
+                            meta.query_advice(*column, Rotation::cur()),
+                            meta.query_advice(*column, Rotation::prev()),
+                        );
+                    }
+                },
+            );
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("segment start", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let is_round = meta.query_advice(is_round, Rotation::cur());
+            let is_finalization = meta.query_advice(is_finalization, Rotation::cur());
+            cb.condition(is_round.clone() + is_finalization.clone(), |cb| {
+                cb.require_equal(
+                    "rounds and finalizations follow a setup or a round",
+                    is_state_prev(meta),
+                    1.expr(),
+                );
+                for column in [rounds, input_rlc] {
+                    cb.require_equal(
+                        "rounds and input rlc are kept within a compression",
+                        meta.query_advice(column, Rotation::cur()),
+                        meta.query_advice(column, Rotation::prev()),
+                    );
+                }
+            });
+            cb.condition(is_round, |cb| {
+                cb.require_equal(
+                    "rounds_left is decremented by every round",
+                    meta.query_advice(rounds_left, Rotation::cur()),
+                    meta.query_advice(rounds_left, Rotation::prev()) - 1.expr(),
+                );
+                for (idx, column) in sel.iter().enumerate() {
+                    let prev = sel[(idx + NUM_SIGMAS - 1) % NUM_SIGMAS];
+                    cb.require_equal(
+                        "the permutation of a round follows the one of the previous round",
+                        meta.query_advice(*column, Rotation::cur()),
+                        meta.query_advice(prev, Rotation::prev()),
+                    );
+                }
+            });
+            cb.condition(is_finalization, |cb| {
+                cb.require_zero(
+                    "the finalization follows the last round",
+                    meta.query_advice(rounds_left, Rotation::prev()),
+                );
+                cb.require_zero(
+                    "no rounds are left in the finalization",
+                    meta.query_advice(rounds_left, Rotation::cur()),
+                );
+            });
+            cb.gate(meta.query_fixed(q_start, Rotation::cur()))
+        });
+
+        meta.create_gate("setup", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let r = challenges.keccak_input();
+            // r^8, to shift the RLC by a word
+            let r_word = r.clone().square().square().square();
+            let word_bits = query_bits(meta, &word, 0);
+            let operand_bits = query_bits(meta, &operand, 0);
+            let value_at =
+                |meta: &mut VirtualCells<F>, rot: i32| meta.query_advice(value, Rotation(rot));
+            let acc_cur = meta.query_advice(acc, Rotation::cur());
+            let acc_prev = meta.query_advice(acc, Rotation::prev());
+
+            cb.require_equal(
+                "rounds_left starts from rounds",
+                meta.query_advice(rounds_left, Rotation::cur()),
+                meta.query_advice(rounds, Rotation::cur()),
+            );
+            cb.require_equal(
+                "the first round uses the first permutation",
+                meta.query_advice(sel[NUM_SIGMAS - 1], Rotation::cur()),
+                1.expr(),
+            );
+
+            // The input words, RLC'ed in the order of the precompile input
+            cb.condition(q_pos(meta, POS_ROUNDS), |cb| {
+                for bit in word_bits[NUM_BITS_PER_WORD / 2..].iter() {
+                    cb.require_zero("rounds is a 32-bit word", bit.clone());
+                }
+                cb.require_equal(
+                    "rounds",
+                    meta.query_advice(rounds, Rotation::cur()),
+                    value_at(meta, 0),
+                );
+                cb.require_equal(
+                    "input rlc starts with the big-endian rounds",
+                    acc_cur.clone(),
+                    (0..NUM_BYTES_PER_WORD / 2)
+                        .rev()
+                        .fold(0.expr(), |acc, idx| {
+                            acc * r.clone() + decode_byte(&word_bits, idx)
+                        }),
+                );
+            });
+            let q_words = sum::expr((POS_H..POS_F).map(|pos| q_pos(meta, pos)));
+            cb.condition(q_words, |cb| {
+                cb.require_equal(
+                    "input rlc absorbs the little-endian words",
+                    acc_cur.clone(),
+                    acc_prev.clone() * r_word.clone() + bytes_rlc(&word_bits, r.clone()),
+                );
+            });
+            cb.condition(q_pos(meta, POS_F), |cb| {
+                for bit in word_bits[1..].iter() {
+                    cb.require_equal("f is a mask", bit.clone(), word_bits[0].clone());
+                }
+                cb.require_equal(
+                    "input rlc ends with the f byte",
+                    acc_cur.clone(),
+                    acc_prev * r.clone() + word_bits[0].clone(),
+                );
+                cb.require_equal(
+                    "input rlc",
+                    meta.query_advice(input_rlc, Rotation::cur()),
+                    acc_cur,
+                );
+            });
+
+            // The message and chaining value words carried by the rounds
+            cb.condition(
+                meta.query_fixed(q_g[0], Rotation::cur())
+                    + meta.query_fixed(q_g[1], Rotation::cur()),
+                |cb| {
+                    cb.require_equal(
+                        "message words",
+                        meta.query_advice(m, Rotation::cur()),
+                        value_at(meta, POS_M as i32),
+                    );
+                },
+            );
+            cb.condition(meta.query_fixed(q_g[0], Rotation::cur()), |cb| {
+                cb.require_equal(
+                    "chaining value words",
+                    meta.query_advice(h, Rotation::cur()),
+                    value_at(meta, POS_H as i32),
+                );
+            });
+
+            // The initial state `h | IV`, with `t` and `f` xored into `v[12..15]`
+            for (idx, &pos) in positions.iter().enumerate() {
+                let q_pos = q_pos(meta, pos);
+                if idx < NUM_H_WORDS {
+                    let rot = (POS_H + idx) as i32 - pos as i32;
+                    cb.condition(q_pos, |cb| {
+                        cb.require_equal("v[i] = h[i]", value_at(meta, 0), value_at(meta, rot));
+                    });
+                } else if let Some(src) = [12, 13, 14].iter().position(|i| *i == idx) {
+                    let rot = (POS_T + src) as i32 - pos as i32;
+                    let iv = into_bits::<NUM_BITS_PER_WORD>(IV[idx - NUM_H_WORDS]);
+                    cb.condition(q_pos, |cb| {
+                        cb.require_equal(
+                            "operand is t or f",
+                            decode(&operand_bits),
+                            value_at(meta, rot),
+                        );
+                        for ((bit, operand), iv) in
+                            word_bits.iter().zip(operand_bits.iter()).zip(iv.iter())
+                        {
+                            cb.require_equal(
+                                "v[i] = IV[i - 8] ^ operand",
+                                bit.clone(),
+                                if *iv {
+                                    not::expr(operand.clone())
+                                } else {
+                                    operand.clone()
+                                },
+                            );
+                        }
+                    });
+                } else {
+                    cb.condition(q_pos, |cb| {
+                        cb.require_equal(
+                            "v[i] = IV[i - 8]",
+                            value_at(meta, 0),
+                            IV[idx - NUM_H_WORDS].expr(),
+                        );
+                    });
+                }
+            }
+            cb.gate(meta.query_advice(is_setup, Rotation::cur()))
+        });
+
+        meta.create_gate("round", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let word_bits = query_bits(meta, &word, 0);
+            let word_bits_prev = query_bits(meta, &word, -1);
+            let operand_bits = query_bits(meta, &operand, 0);
+            let value_at =
+                |meta: &mut VirtualCells<F>, rot: i32| meta.query_advice(value, Rotation(rot));
+
+            for (pos, reads) in reads.iter().enumerate() {
+                let (g, k) = (pos / NUM_ROWS_PER_G, pos % NUM_ROWS_PER_G);
+                let q_pos = q_pos(meta, pos);
+                if k % 2 == 0 {
+                    // a = a + b + m, c = c + d
+                    let msg = match k {
+                        0 => meta.query_advice(x, Rotation((2 * g) as i32 - pos as i32)),
+                        4 => meta.query_advice(x, Rotation((2 * g + 1) as i32 - pos as i32)),
+                        _ => 0.expr(),
+                    };
+                    let carries = carry
+                        .iter()
+                        .enumerate()
+                        .fold(0.expr(), |acc, (idx, column)| {
+                            acc + meta.query_advice(*column, Rotation::cur())
+                                * Expression::Constant(F::from_u128(1 << (NUM_BITS_PER_WORD + idx)))
+                        });
+                    let sum = value_at(meta, reads[0]) + value_at(meta, reads[1]) + msg;
+                    cb.condition(q_pos, |cb| {
+                        cb.require_equal("a + b + m, c + d", value_at(meta, 0) + carries, sum);
+                    });
+                } else {
+                    cb.condition(q_pos, |cb| {
+                        cb.require_equal(
+                            "operand is d or b",
+                            decode(&operand_bits),
+                            value_at(meta, reads[0]),
+                        );
+                    });
+                }
+            }
+            // d = (d ^ a) >>> n, b = (b ^ c) >>> n
+            for (idx, n) in G_ROTATIONS.iter().enumerate() {
+                let q_op = meta.query_fixed(q_op[2 * idx + 1], Rotation::cur());
+                cb.condition(q_op, |cb| {
+                    for (i, bit) in word_bits.iter().enumerate() {
+                        let j = (i + n) % NUM_BITS_PER_WORD;
+                        cb.require_equal(
+                            "xor and rotate",
+                            bit.clone(),
+                            xor(operand_bits[j].clone(), word_bits_prev[j].clone()),
+                        );
+                    }
+                });
+            }
+
+            // The permuted message words
+            for pos in 0..NUM_MSG_WORDS {
+                let permuted =
+                    sel.iter()
+                        .zip(SIGMA.iter())
+                        .fold(0.expr(), |acc, (column, sigma)| {
+                            acc + meta.query_advice(*column, Rotation::cur())
+                                * meta.query_advice(m, Rotation(sigma[pos] as i32 - pos as i32))
+                        });
+                let q_pos = q_pos(meta, pos);
+                cb.condition(q_pos, |cb| {
+                    cb.require_equal(
+                        "x = m[SIGMA[i]]",
+                        meta.query_advice(x, Rotation::cur()),
+                        permuted,
+                    );
+                });
+            }
+            cb.condition(
+                meta.query_fixed(q_g[0], Rotation::cur())
+                    + meta.query_fixed(q_g[1], Rotation::cur()),
+                |cb| {
+                    cb.require_equal(
+                        "message words are carried",
+                        meta.query_advice(m, Rotation::cur()),
+                        meta.query_advice(m, segment_rot(0)),
+                    );
+                },
+            );
+            cb.condition(meta.query_fixed(q_g[0], Rotation::cur()), |cb| {
+                cb.require_equal(
+                    "chaining value words are carried",
+                    meta.query_advice(h, Rotation::cur()),
+                    meta.query_advice(h, segment_rot(0)),
+                );
+            });
+            cb.gate(meta.query_advice(is_round, Rotation::cur()))
+        });
+
+        meta.create_gate("finalization", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let r = challenges.keccak_input();
+            let r_word = r.clone().square().square().square();
+            let word_bits = query_bits(meta, &word, 0);
+            let word_bits_prev = query_bits(meta, &word, -1);
+            let operand_bits = query_bits(meta, &operand, 0);
+            let acc_cur = meta.query_advice(acc, Rotation::cur());
+            let bytes_rlc = bytes_rlc(&word_bits, r.clone());
+
+            // h'[i] = h[i] ^ v[i] ^ v[i + 8]
+            for idx in 0..NUM_H_WORDS {
+                let pos = idx * NUM_ROWS_PER_OUTPUT_WORD;
+                let q_v = q_pos(meta, pos);
+                let rot = positions[idx] as i32 - pos as i32;
+                cb.condition(q_v, |cb| {
+                    cb.require_equal(
+                        "v[i]",
+                        meta.query_advice(value, Rotation::cur()),
+                        meta.query_advice(value, segment_rot(rot)),
+                    );
+                });
+                let q_h = q_pos(meta, pos + 1);
+                let rot = idx as i32 - (pos + 1) as i32;
+                cb.condition(q_h, |cb| {
+                    cb.require_equal(
+                        "operand is h[i]",
+                        decode(&operand_bits),
+                        meta.query_advice(h, segment_rot(rot)),
+                    );
+                });
+                let q_out = q_pos(meta, pos + 2);
+                let rot = positions[idx + NUM_H_WORDS] as i32 - (pos + 2) as i32;
+                cb.condition(q_out.clone(), |cb| {
+                    cb.require_equal(
+                        "operand is v[i + 8]",
+                        decode(&operand_bits),
+                        meta.query_advice(value, segment_rot(rot)),
+                    );
+                    let acc_prev = if idx == 0 {
+                        0.expr()
+                    } else {
+                        meta.query_advice(acc, Rotation(-(NUM_ROWS_PER_OUTPUT_WORD as i32)))
+                    };
+                    cb.require_equal(
+                        "output rlc absorbs the little-endian words",
+                        acc_cur.clone(),
+                        acc_prev * r_word.clone() + bytes_rlc.clone(),
+                    );
+                });
+            }
+            let q_xor = sum::expr((0..NUM_H_WORDS).flat_map(|idx| {
+                let pos = idx * NUM_ROWS_PER_OUTPUT_WORD;
+                [q_pos(meta, pos + 1), q_pos(meta, pos + 2)]
+            }));
+            cb.condition(q_xor, |cb| {
+                for ((bit, operand), prev) in word_bits
+                    .iter()
+                    .zip(operand_bits.iter())
+                    .zip(word_bits_prev.iter())
+                {
+                    cb.require_equal("xor", bit.clone(), xor(operand.clone(), prev.clone()));
+                }
+            });
+            cb.condition(q_pos(meta, POS_OUTPUT_LAST), |cb| {
+                cb.require_equal(
+                    "output rlc",
+                    meta.query_advice(output_rlc, Rotation::cur()),
+                    acc_cur,
+                );
+            });
+            cb.gate(meta.query_advice(is_finalization, Rotation::cur()))
+        });
+
+        log::info!("blake2f circuit degree: {}", meta.degree());
+
+        Blake2fCircuitConfig {
+            q_enable,
+            q_first,
+            q_start,
+            q_g,
+            q_op,
+            word,
+            operand,
+            carry,
+            value,
+            is_setup,
+            is_round,
+            is_finalization,
+            sel,
+            rounds_left,
+            m,
+            x,
+            h,
+            acc,
+            blake2f_table,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Field> Blake2fCircuitConfig<F> {
+    /// Assign the circuit for the compression function
+    pub(crate) fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        witness: &[Blake2fRow<F>],
+    ) -> Result<(), Error> {
+        if witness.is_empty() {
+            return Ok(());
+        }
+        let mut is_first_time = true;
+        layouter.assign_region(
+            || "assign blake2f rows",
+            |mut region| {
+                if is_first_time {
+                    is_first_time = false;
+                    let offset = witness.len() - 1;
+                    self.set_row(&mut region, offset, &witness[offset])?;
+                    return Ok(());
+                }
+                for (offset, blake2f_row) in witness.iter().enumerate() {
+                    self.set_row(&mut region, offset, blake2f_row)?;
+                }
+                self.blake2f_table.annotate_columns_in_region(&mut region);
+                self.annotate_circuit(&mut region);
+                Ok(())
+            },
+        )
+    }
+
+    /// Set the cells for a blake2f row
+    pub fn set_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        row: &Blake2fRow<F>,
+    ) -> Result<(), Error> {
+        let pos = offset % NUM_ROWS_PER_ROUND;
+
+        // Fixed selectors
+        for (name, column, value) in [
+            ("q_enable", self.q_enable, true),
+            ("q_first", self.q_first, offset == 0),
+            ("q_start", self.q_start, pos == 0),
+        ]
+        .into_iter()
+        .chain(
+            self.q_g
+                .iter()
+                .enumerate()
+                .map(|(g, column)| ("q_g", *column, pos / NUM_ROWS_PER_G == g)),
+        )
+        .chain(
+            self.q_op
+                .iter()
+                .enumerate()
+                .map(|(k, column)| ("q_op", *column, pos % NUM_ROWS_PER_G == k)),
+        ) {
+            region.assign_fixed(
+                || format!("assign {name} {offset}"),
+                column,
+                offset,
+                || Value::known(F::from(value)),
+            )?;
+        }
+
+        // Table values
+        self.blake2f_table.assign_row(
+            region,
+            offset,
+            [
+                Value::known(F::from(row.is_final)),
+                Value::known(F::from(row.rounds as u64)),
+                row.input_rlc,
+                row.output_rlc,
+            ],
+        )?;
+
+        // Bits
+        let sel: [bool; NUM_SIGMAS] = std::array::from_fn(|s| row.sigma == Some(s));
+        for (name, columns, bits) in [
+            ("word", &self.word[..], &row.word[..]),
+            ("operand", &self.operand[..], &row.operand[..]),
+            ("carry", &self.carry[..], &row.carry[..]),
+            ("sel", &self.sel[..], &sel[..]),
+            (
+                "segment",
+                &[self.is_setup, self.is_round, self.is_finalization][..],
+                &[
+                    row.segment == Segment::Setup,
+                    row.segment == Segment::Round,
+                    row.segment == Segment::Finalization,
+                ][..],
+            ),
+        ] {
+            for (idx, (column, bit)) in columns.iter().zip(bits.iter()).enumerate() {
+                region.assign_advice(
+                    || format!("assign {name} {idx} {offset}"),
+                    *column,
+                    offset,
+                    || Value::known(F::from(*bit)),
+                )?;
+            }
+        }
+
+        // Words
+        for (name, column, value) in [
+            ("value", self.value, row.value()),
+            ("rounds_left", self.rounds_left, row.rounds_left as u64),
+            ("m", self.m, row.m),
+            ("x", self.x, row.x),
+            ("h", self.h, row.h),
+        ] {
+            region.assign_advice(
+                || format!("assign {name} {offset}"),
+                column,
+                offset,
+                || Value::known(F::from(value)),
+            )?;
+        }
+        region.assign_advice(
+            || format!("assign acc {offset}"),
+            self.acc,
+            offset,
+            || row.acc,
+        )?;
+
+        Ok(())
+    }
+
+    /// Annotate the circuit
+    pub fn annotate_circuit(&self, region: &mut Region<F>) {
+        region.name_column(|| "BLAKE2F_q_first", self.q_first);
+        region.name_column(|| "BLAKE2F_q_start", self.q_start);
+        region.name_column(|| "BLAKE2F_value", self.value);
+        region.name_column(|| "BLAKE2F_is_setup", self.is_setup);
+        region.name_column(|| "BLAKE2F_is_round", self.is_round);
+        region.name_column(|| "BLAKE2F_is_finalization", self.is_finalization);
+        region.name_column(|| "BLAKE2F_rounds_left", self.rounds_left);
+        region.name_column(|| "BLAKE2F_m", self.m);
+        region.name_column(|| "BLAKE2F_x", self.x);
+        region.name_column(|| "BLAKE2F_h", self.h);
+        region.name_column(|| "BLAKE2F_acc", self.acc);
+    }
+}
+
+/// Blake2fCircuit
+#[derive(Default, Clone, Debug)]
+pub struct Blake2fCircuit<F: Field> {
+    // The compressions to verify
+    events: Vec<Blake2fEvent>,
+    // The maximum number of rounds, including two extra rounds for the setup
+    // and the finalization of every compression
+    max_rounds: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> SubCircuit<F> for Blake2fCircuit<F> {
+    type Config = Blake2fCircuitConfig<F>;
+
+    fn unusable_rows() -> usize {
+        // The message words are queried at 30 distinct rotations, so returns
+        // (30 - 3) + 6 unusable rows.
+        33
+    }
+
+    fn new_from_block(block: &witness::Block<F>) -> Self {
+        Self::new(
+            block.circuits_params.max_blake2f_rounds,
+            block.get_blake2f(),
+        )
+    }
+
+    /// Return the minimum number of rows required to prove the block
+    fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
+        let rows = block
+            .get_blake2f()
+            .iter()
+            .map(|event| num_segments(event.rounds) * NUM_ROWS_PER_ROUND)
+            .sum::<usize>();
+        (
+            rows,
+            rows.max(block.circuits_params.max_blake2f_rounds * NUM_ROWS_PER_ROUND),
+        )
+    }
+
+    /// Make the assignments to the Blake2fCircuit
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        challenges: &Challenges<Value<F>>,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let witness = self.generate_witness(*challenges);
+        config.assign(layouter, witness.as_slice())
+    }
+}
+
+impl<F: Field> SubCircuitRowEstimator<F> for Blake2fCircuit<F> {
+    fn marginal_rows(delta: &WitnessDelta, _params: &CircuitsParams) -> usize {
+        match delta {
+            WitnessDelta::PrecompileEvent(PrecompileEvent::Blake2F(event)) => {
+                num_segments(event.rounds) * NUM_ROWS_PER_ROUND
+            }
+            _ => 0,
+        }
+    }
+}
+
+impl<F: Field> Blake2fCircuit<F> {
+    /// Creates a new circuit instance
+    pub fn new(max_rounds: usize, events: Vec<Blake2fEvent>) -> Self {
+        Blake2fCircuit {
+            events,
+            max_rounds,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The number of rounds that can be proven in this circuit, where every
+    /// compression takes two more rounds than its `rounds` parameter
+    pub fn capacity(&self) -> Option<usize> {
+        if self.max_rounds > 0 {
+            Some(self.max_rounds)
+        } else {
+            None
+        }
+    }
+
+    /// The number of rounds that can be proven for a particular row number
+    pub fn capacity_for_row(num_rows: usize) -> usize {
+        num_rows.saturating_sub(Self::unusable_rows()) / NUM_ROWS_PER_ROUND
+    }
+
+    /// Sets the witness using the compressions to verify
+    pub(crate) fn generate_witness(&self, challenges: Challenges<Value<F>>) -> Vec<Blake2fRow<F>> {
+        multi_blake2f(self.events.as_slice(), challenges, self.capacity())
+            .expect("Too many rounds for given capacity")
+    }
+}
@@ -0,0 +1,287 @@
+use super::{
+    param::*,
+    util::{into_bits, round_layout},
+};
+use crate::util::Challenges;
+use bus_mapping::circuit_input_builder::Blake2fEvent;
+use eth_types::Field;
+use halo2_proofs::{circuit::Value, plonk::Error};
+use log::debug;
+
+/// The kind of a segment of `NUM_ROWS_PER_ROUND` rows
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Segment {
+    /// Unused rows
+    Padding,
+    /// The input words and the initial state of a compression
+    Setup,
+    /// A round of the compression
+    Round,
+    /// The output words of a compression
+    Finalization,
+}
+
+/// Blake2fRow
+#[derive(Clone, Debug)]
+pub struct Blake2fRow<F> {
+    pub(crate) word: [bool; NUM_BITS_PER_WORD],
+    pub(crate) operand: [bool; NUM_BITS_PER_WORD],
+    pub(crate) carry: [bool; NUM_CARRY_BITS],
+    pub(crate) segment: Segment,
+    /// The index in `SIGMA` of the message permutation of the segment
+    pub(crate) sigma: Option<usize>,
+    /// The number of rounds of the compression
+    pub rounds: u32,
+    pub(crate) rounds_left: u32,
+    pub(crate) m: u64,
+    pub(crate) x: u64,
+    pub(crate) h: u64,
+    pub(crate) acc: Value<F>,
+    /// if the row is the row holding the output of a compression
+    pub is_final: bool,
+    pub(crate) input_rlc: Value<F>,
+    pub(crate) output_rlc: Value<F>,
+}
+
+impl<F: Field> Blake2fRow<F> {
+    fn dummy() -> Self {
+        Self {
+            word: [false; NUM_BITS_PER_WORD],
+            operand: [false; NUM_BITS_PER_WORD],
+            carry: [false; NUM_CARRY_BITS],
+            segment: Segment::Padding,
+            sigma: None,
+            rounds: 0,
+            rounds_left: 0,
+            m: 0,
+            x: 0,
+            h: 0,
+            acc: Value::known(F::zero()),
+            is_final: false,
+            input_rlc: Value::known(F::zero()),
+            output_rlc: Value::known(F::zero()),
+        }
+    }
+
+    pub(crate) fn value(&self) -> u64 {
+        self.word
+            .iter()
+            .rev()
+            .fold(0, |acc, bit| (acc << 1) | *bit as u64)
+    }
+}
+
+/// The number of segments taken by a compression of `rounds` rounds
+pub fn num_segments(rounds: u32) -> usize {
+    rounds as usize + 2
+}
+
+/// The 213-byte precompile input of a compression:
+/// `rounds (BE) | h (LE) | m (LE) | t (LE) | f`
+pub fn input_bytes(event: &Blake2fEvent) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(NUM_BYTES_INPUT);
+    bytes.extend_from_slice(&event.rounds.to_be_bytes());
+    for word in event.h.iter().chain(event.m.iter()).chain(event.t.iter()) {
+        bytes.extend_from_slice(&word.to_le_bytes());
+    }
+    bytes.push(event.f as u8);
+    bytes
+}
+
+/// The BLAKE2b compression function F, as specified by EIP-152
+pub fn compress(rounds: u32, h: &[u64; 8], m: &[u64; 16], t: &[u64; 2], f: bool) -> [u64; 8] {
+    let mut v = [0; NUM_STATE_WORDS];
+    v[..NUM_H_WORDS].copy_from_slice(h);
+    v[NUM_H_WORDS..].copy_from_slice(&IV);
+    v[12] ^= t[0];
+    v[13] ^= t[1];
+    if f {
+        v[14] = !v[14];
+    }
+    for round in 0..rounds as usize {
+        let s = &SIGMA[round % NUM_SIGMAS];
+        for (g, &[a, b, c, d]) in G_INDICES.iter().enumerate() {
+            let [r0, r1, r2, r3] = G_ROTATIONS;
+            v[a] = v[a].wrapping_add(v[b]).wrapping_add(m[s[2 * g]]);
+            v[d] = (v[d] ^ v[a]).rotate_right(r0 as u32);
+            v[c] = v[c].wrapping_add(v[d]);
+            v[b] = (v[b] ^ v[c]).rotate_right(r1 as u32);
+            v[a] = v[a].wrapping_add(v[b]).wrapping_add(m[s[2 * g + 1]]);
+            v[d] = (v[d] ^ v[a]).rotate_right(r2 as u32);
+            v[c] = v[c].wrapping_add(v[d]);
+            v[b] = (v[b] ^ v[c]).rotate_right(r3 as u32);
+        }
+    }
+    std::array::from_fn(|i| h[i] ^ v[i] ^ v[i + 8])
+}
+
+fn blake2f<F: Field>(
+    rows: &mut Vec<Blake2fRow<F>>,
+    event: &Blake2fEvent,
+    challenges: Challenges<Value<F>>,
+) {
+    let (positions, reads) = round_layout();
+    let r = challenges.keccak_input();
+    let rlc = |acc: Value<F>, bytes: &[u8]| {
+        bytes.iter().fold(acc, |acc, byte| {
+            acc.zip(r).map(|(acc, r)| acc * r + F::from(*byte as u64))
+        })
+    };
+    let row = |segment: Segment, sigma: Option<usize>, rounds_left: u32| Blake2fRow {
+        segment,
+        sigma,
+        rounds: event.rounds,
+        rounds_left,
+        ..Blake2fRow::dummy()
+    };
+    let set_word = |row: &mut Blake2fRow<F>, word: u64| row.word = into_bits(word);
+    let set_operand = |row: &mut Blake2fRow<F>, operand: u64| row.operand = into_bits(operand);
+
+    // Setup: the input words, in the order of the precompile input, and the
+    // initial state words at the positions they are read from by the first
+    // round
+    let base = rows.len();
+    rows.extend(
+        (0..NUM_ROWS_PER_ROUND).map(|_| row(Segment::Setup, Some(NUM_SIGMAS - 1), event.rounds)),
+    );
+    let f = if event.f { u64::MAX } else { 0 };
+    let words = std::iter::once(event.rounds as u64)
+        .chain(event.h.iter().copied())
+        .chain(event.m.iter().copied())
+        .chain(event.t.iter().copied())
+        .chain(std::iter::once(f));
+    let mut input_rlc = Value::known(F::zero());
+    for (pos, word) in words.enumerate() {
+        let bytes = if pos == POS_ROUNDS {
+            event.rounds.to_be_bytes().to_vec()
+        } else if pos == POS_F {
+            vec![event.f as u8]
+        } else {
+            word.to_le_bytes().to_vec()
+        };
+        input_rlc = rlc(input_rlc, &bytes);
+        set_word(&mut rows[base + pos], word);
+        rows[base + pos].acc = input_rlc;
+    }
+    let mut v = [0; NUM_STATE_WORDS];
+    v[..NUM_H_WORDS].copy_from_slice(&event.h);
+    v[NUM_H_WORDS..].copy_from_slice(&IV);
+    for (idx, operand) in [(12, event.t[0]), (13, event.t[1]), (14, f)] {
+        set_operand(&mut rows[base + positions[idx]], operand);
+        v[idx] ^= operand;
+    }
+    for (idx, word) in v.iter().enumerate() {
+        set_word(&mut rows[base + positions[idx]], *word);
+    }
+    for (pos, row) in rows[base..].iter_mut().enumerate() {
+        row.input_rlc = input_rlc;
+        if pos < NUM_MSG_WORDS {
+            row.m = event.m[pos];
+        }
+        if pos < NUM_H_WORDS {
+            row.h = event.h[pos];
+        }
+    }
+
+    // Rounds
+    for round in 0..event.rounds {
+        let sigma = round as usize % NUM_SIGMAS;
+        let base = rows.len();
+        rows.extend((0..NUM_ROWS_PER_ROUND).map(|pos| {
+            let mut row = row(Segment::Round, Some(sigma), event.rounds - round - 1);
+            row.input_rlc = input_rlc;
+            if pos < NUM_MSG_WORDS {
+                row.m = event.m[pos];
+                row.x = event.m[SIGMA[sigma][pos]];
+            }
+            if pos < NUM_H_WORDS {
+                row.h = event.h[pos];
+            }
+            row
+        }));
+        for (pos, reads) in reads.iter().enumerate() {
+            let (g, k) = (pos / NUM_ROWS_PER_G, pos % NUM_ROWS_PER_G);
+            let value = |rot: i32| rows[((base + pos) as i32 + rot) as usize].value();
+            if k % 2 == 0 {
+                let x = match k {
+                    0 => rows[base + 2 * g].x,
+                    4 => rows[base + 2 * g + 1].x,
+                    _ => 0,
+                };
+                let sum = value(reads[0]) as u128 + value(reads[1]) as u128 + x as u128;
+                rows[base + pos].word = into_bits(sum as u64);
+                rows[base + pos].carry = into_bits((sum >> NUM_BITS_PER_WORD) as u64);
+            } else {
+                let operand = value(reads[0]);
+                let word = (operand ^ value(-1)).rotate_right(G_ROTATIONS[k / 2] as u32);
+                set_operand(&mut rows[base + pos], operand);
+                set_word(&mut rows[base + pos], word);
+            }
+        }
+    }
+
+    // Finalization: `v[i]`, `v[i] ^ h[i]` and `v[i] ^ h[i] ^ v[i + 8]` for
+    // every output word
+    let base = rows.len();
+    rows.extend((0..NUM_ROWS_PER_ROUND).map(|_| {
+        let mut row = row(Segment::Finalization, None, 0);
+        row.input_rlc = input_rlc;
+        row
+    }));
+    let state = |rows: &[Blake2fRow<F>], idx: usize| {
+        rows[base + positions[idx] - NUM_ROWS_PER_ROUND].value()
+    };
+    let mut output_rlc = Value::known(F::zero());
+    for idx in 0..NUM_H_WORDS {
+        let pos = base + idx * NUM_ROWS_PER_OUTPUT_WORD;
+        let word = state(rows, idx);
+        set_word(&mut rows[pos], word);
+        let word = word ^ event.h[idx];
+        set_operand(&mut rows[pos + 1], event.h[idx]);
+        set_word(&mut rows[pos + 1], word);
+        let operand = state(rows, idx + NUM_H_WORDS);
+        let word = word ^ operand;
+        set_operand(&mut rows[pos + 2], operand);
+        set_word(&mut rows[pos + 2], word);
+        output_rlc = rlc(output_rlc, &word.to_le_bytes());
+        rows[pos + 2].acc = output_rlc;
+    }
+    let last = &mut rows[base + POS_OUTPUT_LAST];
+    last.is_final = true;
+    last.output_rlc = output_rlc;
+}
+
+/// Generates the witness rows of the compressions of the events.
+/// When a capacity (in rounds) is given, the rows are padded with unused
+/// segments so that the fixed layout only depends on the capacity.
+pub fn multi_blake2f<F: Field>(
+    events: &[Blake2fEvent],
+    challenges: Challenges<Value<F>>,
+    capacity: Option<usize>,
+) -> Result<Vec<Blake2fRow<F>>, Error> {
+    let num_segments_used = events
+        .iter()
+        .map(|event| num_segments(event.rounds))
+        .sum::<usize>();
+    if let Some(capacity) = capacity {
+        if num_segments_used > capacity {
+            log::error!(
+                "blake2f circuit capacity exceeded: {} rounds needed, capacity {}",
+                num_segments_used,
+                capacity
+            );
+            return Err(Error::BoundsFailure);
+        }
+    }
+
+    let mut rows: Vec<Blake2fRow<F>> = Vec::new();
+    for event in events {
+        blake2f(&mut rows, event, challenges);
+    }
+    debug!("blake2f rows len without padding: {}", rows.len());
+    if let Some(capacity) = capacity {
+        let num_padding_rows = (capacity - num_segments_used) * NUM_ROWS_PER_ROUND;
+        rows.extend((0..num_padding_rows).map(|_| Blake2fRow::dummy()));
+    }
+    Ok(rows)
+}
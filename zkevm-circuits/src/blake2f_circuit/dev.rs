@@ -0,0 +1,47 @@
+pub use super::Blake2fCircuit;
+
+use crate::{
+    blake2f_circuit::{Blake2fCircuitConfig, Blake2fCircuitConfigArgs},
+    table::Blake2fTable,
+    util::{Challenges, SubCircuit, SubCircuitConfig},
+};
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+impl<F: Field> Circuit<F> for Blake2fCircuit<F> {
+    type Config = (Blake2fCircuitConfig<F>, Challenges);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let blake2f_table = Blake2fTable::construct(meta);
+        let challenges = Challenges::construct(meta);
+
+        let config = {
+            let challenges = challenges.exprs(meta);
+            Blake2fCircuitConfig::new(
+                meta,
+                Blake2fCircuitConfigArgs {
+                    blake2f_table,
+                    challenges,
+                },
+            )
+        };
+        (config, challenges)
+    }
+
+    fn synthesize(
+        &self,
+        (config, challenges): Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let challenges = challenges.values(&layouter);
+        self.synthesize_sub(&config, &challenges, &mut layouter)
+    }
+}
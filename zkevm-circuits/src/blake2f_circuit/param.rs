@@ -0,0 +1,66 @@
+pub(crate) const NUM_BYTES_PER_WORD: usize = 8;
+pub(crate) const NUM_BITS_PER_WORD: usize = 64;
+/// Two carry bits: the sum `a + b + m` of three 64-bit words.
+pub(crate) const NUM_CARRY_BITS: usize = 2;
+pub(crate) const NUM_STATE_WORDS: usize = 16;
+pub(crate) const NUM_H_WORDS: usize = 8;
+pub(crate) const NUM_MSG_WORDS: usize = 16;
+pub(crate) const NUM_G: usize = 8;
+pub(crate) const NUM_ROWS_PER_G: usize = 8;
+/// Number of rows of a round; the setup and the finalization of a compression
+/// take the same number of rows.
+pub const NUM_ROWS_PER_ROUND: usize = NUM_G * NUM_ROWS_PER_G;
+pub(crate) const NUM_SIGMAS: usize = 10;
+pub(crate) const NUM_BYTES_INPUT: usize = 213;
+pub(crate) const MAX_DEGREE: usize = 5;
+
+// The words of the input in the setup rows, in the order of the precompile
+// input: `rounds | h | m | t | f`
+pub(crate) const POS_ROUNDS: usize = 0;
+pub(crate) const POS_H: usize = 1;
+pub(crate) const POS_M: usize = POS_H + NUM_H_WORDS;
+pub(crate) const POS_T: usize = POS_M + NUM_MSG_WORDS;
+pub(crate) const POS_F: usize = POS_T + 2;
+// Every output word takes three rows in the finalization rows:
+// `v[i]`, `v[i] ^ h[i]` and `v[i] ^ h[i] ^ v[i + 8]`
+pub(crate) const NUM_ROWS_PER_OUTPUT_WORD: usize = 3;
+pub(crate) const POS_OUTPUT_LAST: usize = NUM_H_WORDS * NUM_ROWS_PER_OUTPUT_WORD - 1;
+
+pub(crate) const IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+pub(crate) const SIGMA: [[usize; NUM_MSG_WORDS]; NUM_SIGMAS] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// The state words `[a, b, c, d]` mixed by each G function of a round
+pub(crate) const G_INDICES: [[usize; 4]; NUM_G] = [
+    [0, 4, 8, 12],
+    [1, 5, 9, 13],
+    [2, 6, 10, 14],
+    [3, 7, 11, 15],
+    [0, 5, 10, 15],
+    [1, 6, 11, 12],
+    [2, 7, 8, 13],
+    [3, 4, 9, 14],
+];
+
+/// The right rotations of the four xor rows of a G function
+pub(crate) const G_ROTATIONS: [usize; 4] = [32, 24, 16, 63];
@@ -0,0 +1,103 @@
+use super::*;
+use crate::{
+    blake2f_circuit::blake2f_bit::{compress, input_bytes},
+    evm_circuit::util::rlc,
+    util::unusable_rows,
+};
+use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+use log::error;
+
+#[test]
+fn blake2f_circuit_unusable_rows() {
+    assert_eq!(
+        Blake2fCircuit::<Fr>::unusable_rows(),
+        unusable_rows::<Fr, Blake2fCircuit::<Fr>>(),
+    )
+}
+
+/// The compression of the single block of BLAKE2b-512("abc")
+fn abc_event(rounds: u32) -> Blake2fEvent {
+    let mut h = IV;
+    // parameter block: digest length 64, fanout 1, depth 1
+    h[0] ^= 0x01010040;
+    let mut block = [0u8; 128];
+    block[..3].copy_from_slice(b"abc");
+    Blake2fEvent {
+        rounds,
+        h,
+        m: std::array::from_fn(|idx| {
+            u64::from_le_bytes(block[idx * 8..(idx + 1) * 8].try_into().unwrap())
+        }),
+        t: [3, 0],
+        f: true,
+    }
+}
+
+fn verify<F: Field>(k: u32, events: Vec<Blake2fEvent>, success: bool) {
+    let circuit = Blake2fCircuit::new(Blake2fCircuit::<F>::capacity_for_row(1 << k), events);
+
+    let prover = MockProver::<F>::run(k, &circuit, vec![]).unwrap();
+    let verify_result = prover.verify();
+    if verify_result.is_ok() != success {
+        if let Some(errors) = verify_result.err() {
+            for error in errors.iter() {
+                error!("{}", error);
+            }
+        }
+        panic!();
+    }
+}
+
+#[test]
+fn blake2f_circuit_simple() {
+    let events = vec![
+        abc_event(12),
+        abc_event(0),
+        Blake2fEvent {
+            rounds: 3,
+            h: std::array::from_fn(|idx| u64::MAX - idx as u64),
+            m: std::array::from_fn(|idx| (idx as u64) << 60),
+            t: [u64::MAX, 1],
+            f: false,
+        },
+    ];
+    verify::<Fr>(12, events, true);
+}
+
+#[test]
+fn blake2f_circuit_no_inputs() {
+    verify::<Fr>(10, vec![], true);
+}
+
+#[test]
+fn blake2f_witness_matches_digest() {
+    let challenges = Challenges::mock(
+        Value::known(Fr::from(7)),
+        Value::known(Fr::from(11)),
+        Value::known(Fr::from(13)),
+    );
+    let event = abc_event(12);
+    let digest = hex::decode(
+        "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+         7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+    )
+    .unwrap();
+    let output = compress(event.rounds, &event.h, &event.m, &event.t, event.f);
+    assert_eq!(
+        output
+            .iter()
+            .flat_map(|h| h.to_le_bytes())
+            .collect::<Vec<_>>(),
+        digest
+    );
+
+    let rows = multi_blake2f::<Fr>(&[event.clone()], challenges, None).unwrap();
+    assert_eq!(rows.len(), num_segments(event.rounds) * NUM_ROWS_PER_ROUND);
+
+    let row = rows.iter().find(|row| row.is_final).unwrap();
+    let input_rlc = rlc::value(input_bytes(&event).iter().rev(), Fr::from(11));
+    let output_rlc = rlc::value(digest.iter().rev(), Fr::from(11));
+    assert_eq!(row.rounds, event.rounds);
+    row.input_rlc.assert_if_known(|rlc| *rlc == input_rlc);
+    row.output_rlc.assert_if_known(|rlc| *rlc == output_rlc);
+}
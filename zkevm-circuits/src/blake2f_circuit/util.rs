@@ -0,0 +1,52 @@
+use super::param::*;
+use eth_types::Field;
+use gadgets::util::Expr;
+use halo2_proofs::plonk::Expression;
+
+pub(crate) use crate::util::precompile_hash::{decode, decode_byte, into_bits, query_bits, xor};
+
+/// The RLC of the little-endian bytes of a word
+pub(crate) fn bytes_rlc<F: Field>(bits: &[Expression<F>], r: Expression<F>) -> Expression<F> {
+    (0..NUM_BYTES_PER_WORD).fold(0.expr(), |acc, idx| {
+        acc * r.clone() + decode_byte(bits, idx)
+    })
+}
+
+/// Runs the G functions of a round over the positions of the rows that last
+/// wrote each state word, returning the rotations from every row to the rows
+/// of the words it reads.
+fn mix(last: &mut [i32; NUM_STATE_WORDS]) -> Vec<Vec<i32>> {
+    let mut reads = Vec::with_capacity(NUM_ROWS_PER_ROUND);
+    for (g, &[a, b, c, d]) in G_INDICES.iter().enumerate() {
+        // a = a + b + m, d = (d ^ a) >>> 32, c = c + d, b = (b ^ c) >>> 24,
+        // then the same with the next message word and rotations 16 and 63
+        let rows = [(a, vec![a, b]), (d, vec![d]), (c, vec![c, d]), (b, vec![b])];
+        for (idx, (write, read)) in rows.iter().cycle().take(NUM_ROWS_PER_G).enumerate() {
+            let pos = (g * NUM_ROWS_PER_G + idx) as i32;
+            reads.push(read.iter().map(|word| last[*word] - pos).collect());
+            last[*write] = pos;
+        }
+    }
+    reads
+}
+
+/// The layout of a round, where every row writes one state word:
+/// - the rows at even positions add the two words they read (and a message word for the first and
+///   the fifth row of a G function),
+/// - the rows at odd positions xor the word they read with the word of the previous row, and rotate
+///   the result.
+///
+/// Returns the positions of the rows writing the state words at the end of a
+/// round, which are also the positions of the initial state words in the
+/// setup rows, and the rotations from every row of a round to the rows of the
+/// words it reads.
+pub(crate) fn round_layout() -> ([usize; NUM_STATE_WORDS], Vec<Vec<i32>>) {
+    let mut last = [0; NUM_STATE_WORDS];
+    mix(&mut last);
+    let positions = last.map(|pos| pos as usize);
+
+    let mut last = positions.map(|pos| pos as i32 - NUM_ROWS_PER_ROUND as i32);
+    let reads = mix(&mut last);
+    debug_assert_eq!(last.map(|pos| pos as usize), positions);
+    (positions, reads)
+}
@@ -5,10 +5,16 @@ use crate::{
         not, or, rlc, select,
     },
     table::{BytecodeFieldTag, BytecodeTable, KeccakTable, LookupTable},
-    util::{get_push_size, Challenges, Expr, SubCircuit, SubCircuitConfig},
+    util::{
+        get_push_size, Challenges, Expr, SubCircuit, SubCircuitConfig, SubCircuitRowEstimator,
+        WitnessDelta,
+    },
     witness,
 };
-use bus_mapping::{state_db::EMPTY_CODE_HASH_LE, util::POSEIDON_CODE_HASH_EMPTY};
+use bus_mapping::{
+    circuit_input_builder::CircuitsParams, state_db::EMPTY_CODE_HASH_LE,
+    util::POSEIDON_CODE_HASH_EMPTY,
+};
 use eth_types::{Field, ToLittleEndian, ToScalar, ToWord};
 use gadgets::is_zero::{IsZeroChip, IsZeroConfig, IsZeroInstruction};
 use halo2_proofs::{
@@ -1066,3 +1072,13 @@ impl<F: Field> SubCircuit<F> for BytecodeCircuit<F> {
         )
     }
 }
+
+impl<F: Field> SubCircuitRowEstimator<F> for BytecodeCircuit<F> {
+    // the bytecodes are deduplicated, so they are only counted as bytecode deltas
+    fn marginal_rows(delta: &WitnessDelta, _params: &CircuitsParams) -> usize {
+        match delta {
+            WitnessDelta::Bytecode(bytes) => bytes.len() + 1,
+            _ => 0,
+        }
+    }
+}
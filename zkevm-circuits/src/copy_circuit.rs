@@ -11,7 +11,7 @@ mod test;
 #[cfg(any(feature = "test", test, feature = "test-circuits"))]
 pub use dev::CopyCircuit as TestCopyCircuit;
 
-use bus_mapping::circuit_input_builder::{CopyDataType, CopyEvent};
+use bus_mapping::circuit_input_builder::{CircuitsParams, CopyDataType, CopyEvent};
 use eth_types::{Field, Word};
 
 use gadgets::{
@@ -25,6 +25,8 @@ use halo2_proofs::{
     poly::Rotation,
 };
 use itertools::Itertools;
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+use std::collections::HashMap;
 use std::{collections::BTreeMap, marker::PhantomData};
 
 #[cfg(feature = "onephase")]
@@ -38,16 +40,17 @@ use crate::{
         BytecodeFieldTag, BytecodeTable, CopyTable, LookupTable, RwTable, RwTableTag,
         TxContextFieldTag, TxTable,
     },
-    util::{Challenges, SubCircuit, SubCircuitConfig},
+    util::{Challenges, SubCircuit, SubCircuitConfig, SubCircuitRowEstimator, WitnessDelta},
     witness,
     witness::{Bytecode, RwMap, Transaction},
 };
 
 use self::copy_gadgets::{
     constrain_address, constrain_bytes_left, constrain_event_rlc_acc, constrain_first_last,
-    constrain_forward_parameters, constrain_is_pad, constrain_mask, constrain_masked_value,
-    constrain_must_terminate, constrain_non_pad_non_mask, constrain_rw_counter, constrain_tag,
-    constrain_value_rlc, constrain_word_index, constrain_word_rlc,
+    constrain_forward_parameters, constrain_is_memory_copy, constrain_is_pad, constrain_mask,
+    constrain_masked_value, constrain_must_terminate, constrain_non_pad_non_mask,
+    constrain_rw_counter, constrain_tag, constrain_value_rlc, constrain_word_index,
+    constrain_word_rlc,
 };
 
 /// The current row.
@@ -97,6 +100,8 @@ pub struct CopyCircuitConfig<F> {
     pub is_memory: Column<Advice>,
     /// Booleans to indicate what copy data type exists at the current row.
     pub is_tx_log: Column<Advice>,
+    /// Whether the event copies within the memory of a single call (MCOPY).
+    pub is_memory_copy: Column<Advice>,
     /// Whether the row is enabled or not.
     pub q_enable: Column<Fixed>,
     /// The Copy Table contains the columns that are exposed via the lookup
@@ -106,6 +111,8 @@ pub struct CopyCircuitConfig<F> {
     pub is_src_end: IsEqualConfig<F>,
     /// Whether this is the end of a word (last byte).
     pub is_word_end: IsEqualConfig<F>,
+    /// Whether the reader and the writer have the same id.
+    pub is_id_unchanged: IsEqualConfig<F>,
     /// non pad and non mask witness to reduce the degree of lookups.
     pub non_pad_non_mask: Column<Advice>,
     // External tables
@@ -165,6 +172,7 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
             meta.advice_column(),
             meta.advice_column(),
         );
+        let is_memory_copy = meta.advice_column();
         let is_pad = meta.advice_column();
         let is_first = copy_table.is_first;
         let id = copy_table.id;
@@ -200,6 +208,13 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
             |_meta| 31.expr(),
         );
 
+        let is_id_unchanged = IsEqualChip::configure(
+            meta,
+            |meta| meta.query_selector(q_step),
+            |meta| meta.query_advice(id, CURRENT),
+            |meta| meta.query_advice(id, NEXT_ROW),
+        );
+
         let non_pad_non_mask = meta.advice_column();
 
         constrain_tag(
@@ -326,13 +341,25 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
             {
                 let is_rw_type = meta.query_advice(is_memory, CURRENT) + is_tx_log.expr();
 
+                let is_memory_copy = constrain_is_memory_copy(
+                    cb,
+                    meta,
+                    is_reader.expr(),
+                    is_memory,
+                    &is_id_unchanged,
+                    is_memory_copy,
+                );
+
                 constrain_rw_counter(
                     cb,
                     meta,
+                    is_first.expr(),
                     is_last.expr(),
+                    is_continue.expr(),
                     is_last_step.expr(),
                     is_rw_type.expr(),
                     is_word_end.expr(),
+                    is_memory_copy,
                     rw_counter,
                     rwc_inc_left,
                 );
@@ -448,9 +475,11 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
             is_bytecode,
             is_memory,
             is_tx_log,
+            is_memory_copy,
             q_enable,
             is_src_end,
             is_word_end,
+            is_id_unchanged,
             non_pad_non_mask,
             copy_table,
             tx_table,
@@ -470,14 +499,12 @@ impl<F: Field> CopyCircuitConfig<F> {
         tag_chip: &BinaryNumberChip<F, CopyDataType, { CopyDataType::N_BITS }>,
         is_src_end_chip: &IsEqualChip<F>,
         lt_word_end_chip: &IsEqualChip<F>,
+        is_id_unchanged_chip: &IsEqualChip<F>,
         challenges: Challenges<Value<F>>,
         copy_event: &CopyEvent,
     ) -> Result<(), Error> {
-        for (step_idx, (tag, table_row, circuit_row)) in
-            CopyTable::assignments(copy_event, challenges)
-                .iter()
-                .enumerate()
-        {
+        let assignments = CopyTable::assignments(copy_event, challenges);
+        for (step_idx, (tag, table_row, circuit_row)) in assignments.iter().enumerate() {
             let is_read = step_idx % 2 == 0;
 
             // Copy table assignments
@@ -542,6 +569,9 @@ impl<F: Field> CopyCircuitConfig<F> {
                     addr,
                     Value::known(F::from(copy_event.src_addr_end)),
                 )?;
+
+                let (_, writer_row, _) = &assignments[step_idx + 1];
+                is_id_unchanged_chip.assign(region, *offset, table_row[1].0, writer_row[1].0)?;
             }
 
             lt_word_end_chip.assign(
@@ -585,6 +615,12 @@ impl<F: Field> CopyCircuitConfig<F> {
                 *offset,
                 || Value::known(F::from(tag.eq(&CopyDataType::TxLog))),
             )?;
+            region.assign_advice(
+                || format!("is_memory_copy at row: {}", *offset),
+                self.is_memory_copy,
+                *offset,
+                || Value::known(F::from(copy_event.is_memory_copy())),
+            )?;
 
             *offset += 1;
         }
@@ -599,6 +635,10 @@ impl<F: Field> CopyCircuitConfig<F> {
         copy_events: &[CopyEvent],
         max_copy_rows: usize,
         challenges: Challenges<Value<F>>,
+        #[cfg(any(feature = "test", test, feature = "test-circuits"))] overrides: &HashMap<
+            (dev::AdviceColumn, usize),
+            F,
+        >,
     ) -> Result<(), Error> {
         let copy_rows_needed = copy_events
             .iter()
@@ -619,6 +659,7 @@ impl<F: Field> CopyCircuitConfig<F> {
         let tag_chip = BinaryNumberChip::construct(self.copy_table.tag);
         let is_src_end_chip = IsEqualChip::construct(self.is_src_end.clone());
         let lt_word_end_chip = IsEqualChip::construct(self.is_word_end.clone());
+        let is_id_unchanged_chip = IsEqualChip::construct(self.is_id_unchanged.clone());
 
         layouter.assign_region(
             || "assign copy table",
@@ -633,6 +674,7 @@ impl<F: Field> CopyCircuitConfig<F> {
                 region.name_column(|| "front_mask", self.front_mask);
                 region.name_column(|| "is_pad", self.is_pad);
                 region.name_column(|| "non_pad_non_mask", self.non_pad_non_mask);
+                region.name_column(|| "is_memory_copy", self.is_memory_copy);
 
                 let mut offset = 0;
                 for (ev_idx, copy_event) in copy_events.iter().enumerate() {
@@ -654,6 +696,7 @@ impl<F: Field> CopyCircuitConfig<F> {
                         &tag_chip,
                         &is_src_end_chip,
                         &lt_word_end_chip,
+                        &is_id_unchanged_chip,
                         challenges,
                         copy_event,
                     )?;
@@ -668,6 +711,7 @@ impl<F: Field> CopyCircuitConfig<F> {
                         &tag_chip,
                         &is_src_end_chip,
                         &lt_word_end_chip,
+                        &is_id_unchanged_chip,
                     )?;
                 }
                 assert_eq!(offset % 2, 0, "enabled rows must come in pairs");
@@ -680,6 +724,17 @@ impl<F: Field> CopyCircuitConfig<F> {
                         &tag_chip,
                         &is_src_end_chip,
                         &lt_word_end_chip,
+                        &is_id_unchanged_chip,
+                    )?;
+                }
+
+                #[cfg(any(feature = "test", test, feature = "test-circuits"))]
+                for ((column, row_offset), &f) in overrides {
+                    region.assign_advice(
+                        || "override",
+                        column.value(self),
+                        *row_offset,
+                        || Value::known(f),
                     )?;
                 }
 
@@ -697,6 +752,7 @@ impl<F: Field> CopyCircuitConfig<F> {
         tag_chip: &BinaryNumberChip<F, CopyDataType, { CopyDataType::N_BITS }>,
         is_src_end_chip: &IsEqualChip<F>,
         lt_word_end_chip: &IsEqualChip<F>,
+        is_id_unchanged_chip: &IsEqualChip<F>,
     ) -> Result<(), Error> {
         // q_enable
         region.assign_fixed(
@@ -853,6 +909,12 @@ impl<F: Field> CopyCircuitConfig<F> {
             Value::known(F::zero()),
             Value::known(F::from(31u64)),
         )?;
+        is_id_unchanged_chip.assign(
+            region,
+            *offset,
+            Value::known(F::zero()),
+            Value::known(F::zero()),
+        )?;
         region.assign_advice(
             || format!("non_pad_non_mask at row: {offset}"),
             self.non_pad_non_mask,
@@ -865,6 +927,7 @@ impl<F: Field> CopyCircuitConfig<F> {
             self.is_bytecode,
             self.is_memory,
             self.is_tx_log,
+            self.is_memory_copy,
         ] {
             region.assign_advice(
                 || format!("assigning padding row: {}", *offset),
@@ -907,6 +970,8 @@ pub struct CopyCircuit<F: Field> {
     _marker: PhantomData<F>,
     /// Data for external lookup tables
     pub external_data: ExternalData,
+    #[cfg(any(feature = "test", test, feature = "test-circuits"))]
+    overrides: HashMap<(dev::AdviceColumn, usize), F>,
 }
 
 impl<F: Field> CopyCircuit<F> {
@@ -917,6 +982,8 @@ impl<F: Field> CopyCircuit<F> {
             max_copy_rows,
             _marker: PhantomData::default(),
             external_data: ExternalData::default(),
+            #[cfg(any(feature = "test", test, feature = "test-circuits"))]
+            overrides: HashMap::new(),
         }
     }
 
@@ -931,6 +998,8 @@ impl<F: Field> CopyCircuit<F> {
             max_copy_rows,
             _marker: PhantomData::default(),
             external_data,
+            #[cfg(any(feature = "test", test, feature = "test-circuits"))]
+            overrides: HashMap::new(),
         }
     }
 
@@ -989,7 +1058,27 @@ impl<F: Field> SubCircuit<F> for CopyCircuit<F> {
         challenges: &Challenges<Value<F>>,
         layouter: &mut impl Layouter<F>,
     ) -> Result<(), Error> {
-        config.assign_copy_events(layouter, &self.copy_events, self.max_copy_rows, *challenges)
+        config.assign_copy_events(
+            layouter,
+            &self.copy_events,
+            self.max_copy_rows,
+            *challenges,
+            #[cfg(any(feature = "test", test, feature = "test-circuits"))]
+            &self.overrides,
+        )
+    }
+}
+
+impl<F: Field> SubCircuitRowEstimator<F> for CopyCircuit<F> {
+    fn base_rows(_params: &CircuitsParams) -> usize {
+        UNUSED_ROWS + DISABLED_ROWS
+    }
+
+    fn marginal_rows(delta: &WitnessDelta, _params: &CircuitsParams) -> usize {
+        match delta {
+            WitnessDelta::CopyEvent(copy_event) => copy_event.full_length() as usize * 2,
+            _ => 0,
+        }
     }
 }
 
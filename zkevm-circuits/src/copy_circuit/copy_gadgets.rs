@@ -479,39 +479,100 @@ pub fn constrain_address<F: Field>(
     });
 }
 
+/// Detect a copy within the memory of a single call, where the reader and the writer are both
+/// memory with the same call_id. Return is_memory_copy.
+pub fn constrain_is_memory_copy<F: Field>(
+    cb: &mut BaseConstraintBuilder<F>,
+    meta: &mut VirtualCells<'_, F>,
+    is_reader: Expression<F>,
+    is_memory: Column<Advice>,
+    is_id_unchanged: &IsEqualConfig<F>,
+    is_memory_copy: Column<Advice>,
+) -> Expression<F> {
+    let [is_memory_copy, is_memory_copy_writer] =
+        [CURRENT, NEXT_ROW].map(|at| meta.query_advice(is_memory_copy, at));
+
+    cb.condition(is_reader, |cb| {
+        cb.require_equal(
+            "is_memory_copy == is_memory on reader and writer with the same id",
+            is_memory_copy.expr(),
+            and::expr([
+                meta.query_advice(is_memory, CURRENT),
+                meta.query_advice(is_memory, NEXT_ROW),
+                is_id_unchanged.expr(),
+            ]),
+        );
+        cb.require_equal(
+            "is_memory_copy is the same on reader and writer rows",
+            is_memory_copy.expr(),
+            is_memory_copy_writer,
+        );
+    });
+
+    is_memory_copy
+}
+
 /// Update the RW counter and verify that all RWs requested by the event are consumed.
 #[allow(clippy::too_many_arguments)]
 pub fn constrain_rw_counter<F: Field>(
     cb: &mut BaseConstraintBuilder<F>,
     meta: &mut VirtualCells<'_, F>,
+    is_first: Expression<F>,     // The first row.
     is_last: Expression<F>,      // The last row.
+    is_continue: Expression<F>,  // Not the last step.
     is_last_step: Expression<F>, // Both the last reader and writer rows.
     is_rw_type: Expression<F>,
     is_word_end: Expression<F>,
+    is_memory_copy: Expression<F>,
     rw_counter: Column<Advice>,
     rwc_inc_left: Column<Advice>,
 ) {
+    let [current, next_row, next_step] =
+        [CURRENT, NEXT_ROW, NEXT_STEP].map(|at| meta.query_advice(rwc_inc_left, at));
+
     // Decrement rwc_inc_left for the next row, when an RW operation happens.
     let rwc_diff = is_rw_type.expr() * is_word_end.expr();
-    let new_value = meta.query_advice(rwc_inc_left, CURRENT) - rwc_diff;
-    // At the end, it must reach 0.
-    let update_or_finish = select::expr(
-        not::expr(is_last.expr()),
-        meta.query_advice(rwc_inc_left, NEXT_ROW),
-        0.expr(),
-    );
-    cb.require_equal(
-        "rwc_inc_left[2] == rwc_inc_left[0] - rwc_diff, or 0 at the end",
-        new_value,
-        update_or_finish,
-    );
+    let new_value = current.expr() - rwc_diff;
+
+    cb.condition(not::expr(is_memory_copy.expr()), |cb| {
+        // At the end, it must reach 0.
+        let update_or_finish = select::expr(not::expr(is_last.expr()), next_row.expr(), 0.expr());
+        cb.require_equal(
+            "rwc_inc_left[2] == rwc_inc_left[0] - rwc_diff, or 0 at the end",
+            new_value.expr(),
+            update_or_finish,
+        );
+    });
+
+    // A copy within one memory reads every word before it writes any, so overlapping ranges see
+    // the original bytes. The reader and the writer each count down their own operations, and the
+    // writer starts with the second half of them.
+    cb.condition(is_memory_copy, |cb| {
+        cb.condition(is_first, |cb| {
+            cb.require_equal(
+                "memory copy: rwc_inc_left[0] == 2 * rwc_inc_left[1]",
+                current.expr(),
+                2.expr() * next_row.expr(),
+            );
+        });
+        cb.condition(is_continue, |cb| {
+            cb.require_equal(
+                "memory copy: rwc_inc_left[2] == rwc_inc_left[0] - rwc_diff",
+                new_value.expr(),
+                next_step,
+            );
+        });
+        cb.condition(is_last.expr(), |cb| {
+            cb.require_zero("memory copy: rwc_inc_left reaches 0 at the end", new_value);
+        });
+    });
 
     // Maintain rw_counter based on rwc_inc_left. Their sum remains constant in all cases.
     cb.condition(not::expr(is_last.expr()), |cb| {
         cb.require_equal(
             "rw_counter[0] + rwc_inc_left[0] == rw_counter[1] + rwc_inc_left[1]",
-            meta.query_advice(rw_counter, CURRENT) + meta.query_advice(rwc_inc_left, CURRENT),
-            meta.query_advice(rw_counter, NEXT_ROW) + meta.query_advice(rwc_inc_left, NEXT_ROW),
+            meta.query_advice(rw_counter, CURRENT) + current,
+            meta.query_advice(rw_counter, NEXT_ROW) + next_row,
         );
     });
 
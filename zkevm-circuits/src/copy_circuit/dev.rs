@@ -8,7 +8,7 @@ use crate::{
 use eth_types::Field;
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner},
-    plonk::{Challenge, Circuit, ConstraintSystem, Error},
+    plonk::{Advice, Challenge, Circuit, Column, ConstraintSystem, Error},
 };
 
 impl<F: Field> Circuit<F> for CopyCircuit<F> {
@@ -75,3 +75,19 @@ impl<F: Field> Circuit<F> for CopyCircuit<F> {
         self.synthesize_sub(&config.0, &challenge_values, &mut layouter)
     }
 }
+
+/// Advice columns whose assignment a test can override.
+#[derive(Hash, Eq, PartialEq, Clone, Debug)]
+pub enum AdviceColumn {
+    RwCounter,
+    RwcIncLeft,
+}
+
+impl AdviceColumn {
+    pub fn value<F: Field>(&self, config: &CopyCircuitConfig<F>) -> Column<Advice> {
+        match self {
+            Self::RwCounter => config.copy_table.rw_counter,
+            Self::RwcIncLeft => config.copy_table.rwc_inc_left,
+        }
+    }
+}
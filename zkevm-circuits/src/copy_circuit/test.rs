@@ -1,7 +1,7 @@
 #![allow(unused_imports)]
 
 use crate::{
-    copy_circuit::*,
+    copy_circuit::{dev::AdviceColumn, *},
     evm_circuit::{test::rand_bytes, witness::block_convert},
     util::unusable_rows,
     witness::Block,
@@ -18,6 +18,7 @@ use halo2_proofs::{
     halo2curves::bn256::Fr,
 };
 use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext, MOCK_ACCOUNTS};
+use std::collections::HashMap;
 
 const K: u32 = 20;
 
@@ -301,6 +302,30 @@ fn gen_return_data() -> CircuitInputBuilder {
     builder
 }
 
+fn gen_mcopy_data() -> CircuitInputBuilder {
+    // The destination overlaps the end of the source.
+    let code = bytecode! {
+        PUSH32(Word::from_big_endian(&(0x00..0x20).collect::<Vec<u8>>()))
+        PUSH1(0x00)
+        MSTORE
+        PUSH32(Word::from_big_endian(&(0x20..0x40).collect::<Vec<u8>>()))
+        PUSH1(0x20)
+        MSTORE
+        PUSH1(0x40) // length
+        PUSH1(0x00) // src_offset
+        PUSH1(0x20) // dst_offset
+        MCOPY
+        STOP
+    };
+    let test_ctx = TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap();
+    let block: GethData = test_ctx.into();
+    let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+    builder
+        .handle_block(&block.eth_block, &block.geth_traces)
+        .unwrap();
+    builder
+}
+
 #[test]
 fn copy_circuit_valid_calldatacopy() {
     let builder = gen_calldatacopy_data();
@@ -357,6 +382,13 @@ fn copy_circuit_valid_return() {
     assert_eq!(test_copy_circuit_from_block(block), Ok(()));
 }
 
+#[test]
+fn copy_circuit_valid_mcopy() {
+    let builder = gen_mcopy_data();
+    let block = block_convert::<Fr>(&builder.block, &builder.code_db).unwrap();
+    assert_eq!(test_copy_circuit_from_block(block), Ok(()));
+}
+
 #[test]
 fn copy_circuit_invalid_calldatacopy() {
     let mut builder = gen_calldatacopy_data();
@@ -449,6 +481,71 @@ fn copy_circuit_invalid_tx_log() {
         .expect("there should be a lookup error");
 }
 
+#[test]
+fn copy_circuit_invalid_mcopy() {
+    let mut builder = gen_mcopy_data();
+
+    // modify first byte of first copy event
+    builder.block.copy_events[0].copy_bytes.bytes[0].0 =
+        builder.block.copy_events[0].copy_bytes.bytes[0]
+            .0
+            .wrapping_add(1);
+
+    let block = block_convert::<Fr>(&builder.block, &builder.code_db).unwrap();
+    let result = test_copy_circuit_from_block(block);
+
+    let errors = result.expect_err("result is not an error");
+    errors
+        .iter()
+        .find(|err| matches!(err, VerifyFailure::Lookup { .. }))
+        .expect("there should be a lookup error");
+}
+
+#[test]
+fn copy_circuit_invalid_mcopy_writer_split() {
+    let builder = gen_mcopy_data();
+    let block = block_convert::<Fr>(&builder.block, &builder.code_db).unwrap();
+    let copy_event = &block.copy_events[0];
+    assert!(copy_event.is_memory_copy());
+
+    // The writer of a memory copy starts with half of the RW operations. Move its first row
+    // one operation earlier, keeping rw_counter + rwc_inc_left unchanged.
+    let half = copy_event.rw_counter_delta() / 2;
+    let writer_rw_counter = copy_event.rw_counter_start() + half;
+    let overrides = HashMap::from([
+        (
+            (AdviceColumn::RwCounter, 1),
+            Fr::from(writer_rw_counter - 1),
+        ),
+        ((AdviceColumn::RwcIncLeft, 1), Fr::from(half + 1)),
+    ]);
+
+    let mut circuit = CopyCircuit::<Fr>::new_with_external_data(
+        block.copy_events,
+        block.circuits_params.max_copy_rows,
+        ExternalData {
+            max_txs: block.circuits_params.max_txs,
+            max_calldata: block.circuits_params.max_calldata,
+            txs: block.txs,
+            max_rws: block.circuits_params.max_rws,
+            rws: block.rws,
+            bytecodes: block.bytecodes,
+        },
+    );
+    circuit.overrides = overrides;
+    let prover = MockProver::<Fr>::run(K, &circuit, vec![]).unwrap();
+
+    let errors = prover.verify_par().expect_err("result is not an error");
+    assert!(
+        errors.iter().any(|err| matches!(
+            err,
+            VerifyFailure::ConstraintNotSatisfied { constraint, .. }
+                if format!("{constraint}").contains("memory copy: rwc_inc_left[0] == 2 * rwc_inc_left[1]")
+        )),
+        "{errors:?}"
+    );
+}
+
 // todo: add invalid create/return/returndatacopy tests
 
 #[test]
@@ -4,7 +4,10 @@
 use std::{iter, marker::PhantomData};
 
 use bus_mapping::{
-    circuit_input_builder::{EcAddOp, EcMulOp, EcPairingOp, N_BYTES_PER_PAIR, N_PAIRING_PER_OP},
+    circuit_input_builder::{
+        CircuitsParams, EcAddOp, EcMulOp, EcPairingOp, PrecompileEvent, N_BYTES_PER_PAIR,
+        N_PAIRING_PER_OP,
+    },
     precompile::PrecompileCalls,
 };
 use eth_types::{Field, ToLittleEndian, ToScalar, U256};
@@ -41,7 +44,7 @@ use crate::{
     evm_circuit::{param::N_BYTES_WORD, EvmCircuit},
     keccak_circuit::KeccakCircuit,
     table::{EccTable, LookupTable},
-    util::{Challenges, SubCircuit, SubCircuitConfig},
+    util::{Challenges, SubCircuit, SubCircuitConfig, SubCircuitRowEstimator, WitnessDelta},
     witness::Block,
 };
 
@@ -1381,3 +1384,24 @@ impl<F: Field, const XI_0: i64> SubCircuit<F> for EccCircuit<F, XI_0> {
         (min_row_num, row_num)
     }
 }
+
+impl<F: Field, const XI_0: i64> SubCircuitRowEstimator<F> for EccCircuit<F, XI_0> {
+    // The share of the rows of an op of its kind. The rows of a witness are the max over the kinds,
+    // see `min_num_rows_block`, so the sum over the ops bounds them.
+    fn marginal_rows(delta: &WitnessDelta, params: &CircuitsParams) -> usize {
+        let row_num = if params.max_vertical_circuit_rows == 0 {
+            Self::min_num_rows()
+        } else {
+            params.max_vertical_circuit_rows
+        };
+        let max_ec_ops = &params.max_ec_ops;
+        match delta {
+            WitnessDelta::PrecompileEvent(PrecompileEvent::EcAdd(_)) => row_num / max_ec_ops.ec_add,
+            WitnessDelta::PrecompileEvent(PrecompileEvent::EcMul(_)) => row_num / max_ec_ops.ec_mul,
+            WitnessDelta::PrecompileEvent(PrecompileEvent::EcPairing(_)) => {
+                row_num / max_ec_ops.ec_pairing
+            }
+            _ => 0,
+        }
+    }
+}
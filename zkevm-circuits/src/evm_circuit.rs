@@ -22,12 +22,13 @@ pub use crate::witness;
 use crate::{
     evm_circuit::param::{MAX_STEP_HEIGHT, STEP_STATE_HEIGHT},
     table::{
-        BlockTable, BytecodeTable, CopyTable, EccTable, ExpTable, KeccakTable, LookupTable,
-        ModExpTable, PowOfRandTable, RwTable, SigTable, TxTable,
+        Blake2fTable, BlockTable, BytecodeTable, CopyTable, EccTable, ExpTable, KeccakTable,
+        LookupTable, ModExpTable, PowOfRandTable, Ripemd160Table, RwTable, Sha256Table, SigTable,
+        TxTable,
     },
-    util::{SubCircuit, SubCircuitConfig},
+    util::{SubCircuit, SubCircuitConfig, SubCircuitRowEstimator, WitnessDelta},
 };
-use bus_mapping::evm::OpcodeId;
+use bus_mapping::{circuit_input_builder::CircuitsParams, evm::OpcodeId};
 use eth_types::Field;
 use execution::ExecutionConfig;
 use itertools::Itertools;
@@ -53,6 +54,9 @@ pub struct EvmCircuitConfig<F> {
     modexp_table: ModExpTable,
     ecc_table: EccTable,
     pow_of_rand_table: PowOfRandTable,
+    sha256_table: Sha256Table,
+    ripemd160_table: Ripemd160Table,
+    blake2f_table: Blake2fTable,
 }
 
 /// Circuit configuration arguments
@@ -81,6 +85,12 @@ pub struct EvmCircuitConfigArgs<F: Field> {
     pub ecc_table: EccTable,
     // Power of Randomness Table.
     pub pow_of_rand_table: PowOfRandTable,
+    /// Sha256Table
+    pub sha256_table: Sha256Table,
+    /// Ripemd160Table
+    pub ripemd160_table: Ripemd160Table,
+    /// Blake2fTable
+    pub blake2f_table: Blake2fTable,
 }
 
 /// Circuit exported cells after synthesis, used for subcircuit
@@ -110,6 +120,9 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             modexp_table,
             ecc_table,
             pow_of_rand_table,
+            sha256_table,
+            ripemd160_table,
+            blake2f_table,
         }: Self::ConfigArgs,
     ) -> Self {
         let fixed_table = [(); 4].map(|_| meta.fixed_column());
@@ -130,6 +143,9 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             &modexp_table,
             &ecc_table,
             &pow_of_rand_table,
+            &sha256_table,
+            &ripemd160_table,
+            &blake2f_table,
         ));
 
         meta.annotate_lookup_any_column(byte_table[0], || "byte_range");
@@ -147,6 +163,9 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
         modexp_table.annotate_columns(meta);
         ecc_table.annotate_columns(meta);
         pow_of_rand_table.annotate_columns(meta);
+        sha256_table.annotate_columns(meta);
+        ripemd160_table.annotate_columns(meta);
+        blake2f_table.annotate_columns(meta);
 
         Self {
             fixed_table,
@@ -163,6 +182,9 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             modexp_table,
             ecc_table,
             pow_of_rand_table,
+            sha256_table,
+            ripemd160_table,
+            blake2f_table,
         }
     }
 }
@@ -339,6 +361,24 @@ impl<F: Field> SubCircuit<F> for EvmCircuit<F> {
     }
 }
 
+impl<F: Field> SubCircuitRowEstimator<F> for EvmCircuit<F> {
+    // the first unused row and the EndBlock step, see `get_num_rows_required_no_padding`
+    fn base_rows(_params: &CircuitsParams) -> usize {
+        2
+    }
+
+    fn marginal_rows(delta: &WitnessDelta, _params: &CircuitsParams) -> usize {
+        match delta {
+            WitnessDelta::Tx(tx) => tx
+                .steps
+                .iter()
+                .map(|step| step.execution_state.get_step_height())
+                .sum(),
+            _ => 0,
+        }
+    }
+}
+
 fn get_fixed_table_row_num(need_bitwise_lookup: bool) -> usize {
     if need_bitwise_lookup {
         FIXED_TABLE_ROWS
@@ -465,6 +505,9 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
         let modexp_table = ModExpTable::construct(meta);
         let ecc_table = EccTable::construct(meta);
         let pow_of_rand_table = PowOfRandTable::construct(meta, &challenges_expr);
+        let sha256_table = Sha256Table::construct(meta);
+        let ripemd160_table = Ripemd160Table::construct(meta);
+        let blake2f_table = Blake2fTable::construct(meta);
         (
             EvmCircuitConfig::new(
                 meta,
@@ -481,6 +524,9 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
                     modexp_table,
                     ecc_table,
                     pow_of_rand_table,
+                    sha256_table,
+                    ripemd160_table,
+                    blake2f_table,
                 },
             ),
             challenges,
@@ -539,6 +585,27 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
             &block.get_ec_pairing_ops(),
             &challenges,
         )?;
+        config.sha256_table.dev_load(
+            &mut layouter,
+            &block
+                .get_sha256()
+                .into_iter()
+                .map(|e| e.input)
+                .collect::<Vec<_>>(),
+            &challenges,
+        )?;
+        config.ripemd160_table.dev_load(
+            &mut layouter,
+            &block
+                .get_ripemd160()
+                .into_iter()
+                .map(|e| e.input)
+                .collect::<Vec<_>>(),
+            &challenges,
+        )?;
+        config
+            .blake2f_table
+            .dev_load(&mut layouter, &block.get_blake2f(), &challenges)?;
 
         self.synthesize_sub(&config, &challenges, &mut layouter)
     }
@@ -760,7 +827,13 @@ mod evm_circuit_stats {
             ecc_table,
             LOOKUP_CONFIG[9].1,
             pow_of_rand_table,
-            LOOKUP_CONFIG[10].1
+            LOOKUP_CONFIG[10].1,
+            sha256_table,
+            LOOKUP_CONFIG[12].1,
+            ripemd160_table,
+            LOOKUP_CONFIG[13].1,
+            blake2f_table,
+            LOOKUP_CONFIG[14].1
         );
     }
 
@@ -1,9 +1,10 @@
 use super::{
     param::{
-        BLOCK_TABLE_LOOKUPS, BYTECODE_TABLE_LOOKUPS, COPY_TABLE_LOOKUPS, ECC_TABLE_LOOKUPS,
-        EXP_TABLE_LOOKUPS, FIXED_TABLE_LOOKUPS, KECCAK_TABLE_LOOKUPS, MODEXP_TABLE_LOOKUPS,
-        N_BYTE_LOOKUPS, N_COPY_COLUMNS, N_PHASE1_COLUMNS, POW_OF_RAND_TABLE_LOOKUPS,
-        RW_TABLE_LOOKUPS, SIG_TABLE_LOOKUPS, TX_TABLE_LOOKUPS,
+        BLAKE2F_TABLE_LOOKUPS, BLOCK_TABLE_LOOKUPS, BYTECODE_TABLE_LOOKUPS, COPY_TABLE_LOOKUPS,
+        ECC_TABLE_LOOKUPS, EXP_TABLE_LOOKUPS, FIXED_TABLE_LOOKUPS, KECCAK_TABLE_LOOKUPS,
+        MODEXP_TABLE_LOOKUPS, N_BYTE_LOOKUPS, N_COPY_COLUMNS, N_PHASE1_COLUMNS,
+        POW_OF_RAND_TABLE_LOOKUPS, RIPEMD160_TABLE_LOOKUPS, RW_TABLE_LOOKUPS, SHA256_TABLE_LOOKUPS,
+        SIG_TABLE_LOOKUPS, TX_TABLE_LOOKUPS,
     },
     util::{instrumentation::Instrument, CachedRegion, CellManager, Inverter, StoredExpression},
     EvmCircuitExports,
@@ -62,6 +63,8 @@ mod address;
 mod balance;
 mod begin_tx;
 mod bitwise;
+mod blobbasefee;
+mod blobhash;
 mod block_ctx;
 mod blockhash;
 mod byte;
@@ -76,8 +79,6 @@ mod codecopy;
 mod codesize;
 mod comparator;
 mod create;
-#[cfg(not(feature = "scroll"))]
-mod dummy;
 mod dup;
 mod end_block;
 mod end_inner_block;
@@ -95,6 +96,8 @@ mod error_oog_exp;
 mod error_oog_log;
 mod error_oog_memory_copy;
 mod error_oog_precompile;
+#[cfg(not(feature = "scroll"))]
+mod error_oog_self_destruct;
 mod error_oog_sha3;
 mod error_oog_sload_sstore;
 mod error_oog_static_memory;
@@ -113,6 +116,7 @@ mod jump;
 mod jumpdest;
 mod jumpi;
 mod logs;
+mod mcopy;
 mod memory;
 mod msize;
 mod mul_div_mod;
@@ -130,6 +134,8 @@ mod returndatasize;
 mod sar;
 mod sdiv_smod;
 mod selfbalance;
+#[cfg(not(feature = "scroll"))]
+mod selfdestruct;
 mod sha3;
 mod shl_shr;
 mod signed_comparator;
@@ -138,14 +144,18 @@ mod sload;
 mod sstore;
 mod stop;
 mod swap;
+mod tload;
+mod tstore;
 
-use self::{logs::LogGadget, precompiles::BasePrecompileGadget, sha3::Sha3Gadget};
+use self::{logs::LogGadget, sha3::Sha3Gadget};
 use add_sub::AddSubGadget;
 use addmod::AddModGadget;
 use address::AddressGadget;
 use balance::BalanceGadget;
 use begin_tx::BeginTxGadget;
 use bitwise::BitwiseGadget;
+use blobbasefee::BlobBaseFeeGadget;
+use blobhash::BlobHashGadget;
 use block_ctx::{BlockCtxU160Gadget, BlockCtxU256Gadget, BlockCtxU64Gadget};
 use blockhash::BlockHashGadget;
 use byte::ByteGadget;
@@ -160,8 +170,6 @@ use codecopy::CodeCopyGadget;
 use codesize::CodesizeGadget;
 use comparator::ComparatorGadget;
 use create::CreateGadget;
-#[cfg(not(feature = "scroll"))]
-use dummy::DummyGadget;
 use dup::DupGadget;
 use end_block::EndBlockGadget;
 use end_inner_block::EndInnerBlockGadget;
@@ -178,6 +186,8 @@ use error_oog_dynamic_memory::ErrorOOGDynamicMemoryGadget;
 use error_oog_exp::ErrorOOGExpGadget;
 use error_oog_log::ErrorOOGLogGadget;
 use error_oog_memory_copy::ErrorOOGMemoryCopyGadget;
+#[cfg(not(feature = "scroll"))]
+use error_oog_self_destruct::ErrorOOGSelfDestructGadget;
 use error_oog_sha3::ErrorOOGSha3Gadget;
 use error_oog_sload_sstore::ErrorOOGSloadSstoreGadget;
 use error_oog_static_memory::ErrorOOGStaticMemoryGadget;
@@ -197,6 +207,7 @@ use jumpdest::JumpdestGadget;
 use jumpi::JumpiGadget;
 
 use crate::evm_circuit::execution::error_oog_precompile::ErrorOOGPrecompileGadget;
+use mcopy::MCopyGadget;
 use memory::MemoryGadget;
 use msize::MsizeGadget;
 use mul_div_mod::MulDivModGadget;
@@ -206,7 +217,8 @@ use origin::OriginGadget;
 use pc::PcGadget;
 use pop::PopGadget;
 use precompiles::{
-    EcAddGadget, EcMulGadget, EcPairingGadget, EcrecoverGadget, IdentityGadget, ModExpGadget,
+    Blake2fGadget, EcAddGadget, EcMulGadget, EcPairingGadget, EcrecoverGadget, IdentityGadget,
+    ModExpGadget, Ripemd160Gadget, Sha256Gadget,
 };
 use push::PushGadget;
 use return_revert::ReturnRevertGadget;
@@ -215,6 +227,8 @@ use returndatasize::ReturnDataSizeGadget;
 use sar::SarGadget;
 use sdiv_smod::SignedDivModGadget;
 use selfbalance::SelfbalanceGadget;
+#[cfg(not(feature = "scroll"))]
+use selfdestruct::SelfDestructGadget;
 use shl_shr::ShlShrGadget;
 use signed_comparator::SignedComparatorGadget;
 use signextend::SignextendGadget;
@@ -222,6 +236,8 @@ use sload::SloadGadget;
 use sstore::SstoreGadget;
 use stop::StopGadget;
 use swap::SwapGadget;
+use tload::TloadGadget;
+use tstore::TstoreGadget;
 
 pub(crate) trait ExecutionGadget<F: FieldExt> {
     const NAME: &'static str;
@@ -297,6 +313,7 @@ pub(crate) struct ExecutionConfig<F> {
     jumpdest_gadget: Box<JumpdestGadget<F>>,
     jumpi_gadget: Box<JumpiGadget<F>>,
     log_gadget: Box<LogGadget<F>>,
+    mcopy_gadget: Box<MCopyGadget<F>>,
     memory_gadget: Box<MemoryGadget<F>>,
     msize_gadget: Box<MsizeGadget<F>>,
     mul_div_mod_gadget: Box<MulDivModGadget<F>>,
@@ -317,14 +334,18 @@ pub(crate) struct ExecutionConfig<F> {
     create_gadget: Box<CreateGadget<F, false, { ExecutionState::CREATE }>>,
     create2_gadget: Box<CreateGadget<F, true, { ExecutionState::CREATE2 }>>,
     #[cfg(not(feature = "scroll"))]
-    selfdestruct_gadget: Box<DummyGadget<F, 1, 0, { ExecutionState::SELFDESTRUCT }>>,
+    selfdestruct_gadget: Box<SelfDestructGadget<F>>,
     signed_comparator_gadget: Box<SignedComparatorGadget<F>>,
     signextend_gadget: Box<SignextendGadget<F>>,
     sload_gadget: Box<SloadGadget<F>>,
     sstore_gadget: Box<SstoreGadget<F>>,
     stop_gadget: Box<StopGadget<F>>,
     swap_gadget: Box<SwapGadget<F>>,
+    tload_gadget: Box<TloadGadget<F>>,
+    tstore_gadget: Box<TstoreGadget<F>>,
     blockhash_gadget: Box<BlockHashGadget<F>>,
+    blobhash_gadget: Box<BlobHashGadget<F>>,
+    blobbasefee_gadget: Box<BlobBaseFeeGadget<F>>,
     block_ctx_u64_gadget: Box<BlockCtxU64Gadget<F>>,
     block_ctx_u160_gadget: Box<BlockCtxU160Gadget<F>>,
     block_ctx_u256_gadget: Box<BlockCtxU256Gadget<F>>,
@@ -345,8 +366,7 @@ pub(crate) struct ExecutionConfig<F> {
     error_oog_create: Box<ErrorOOGCreateGadget<F>>,
     error_code_store: Box<ErrorCodeStoreGadget<F>>,
     #[cfg(not(feature = "scroll"))]
-    error_oog_self_destruct:
-        Box<DummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasSELFDESTRUCT }>>,
+    error_oog_self_destruct: Box<ErrorOOGSelfDestructGadget<F>>,
     error_invalid_jump: Box<ErrorInvalidJumpGadget<F>>,
     error_invalid_opcode: Box<ErrorInvalidOpcodeGadget<F>>,
     error_invalid_creation_code: Box<ErrorInvalidCreationCodeGadget<F>>,
@@ -354,14 +374,14 @@ pub(crate) struct ExecutionConfig<F> {
     error_return_data_out_of_bound: Box<ErrorReturnDataOutOfBoundGadget<F>>,
     // precompile calls
     precompile_ecrecover_gadget: Box<EcrecoverGadget<F>>,
-    precompile_sha2_gadget: Box<BasePrecompileGadget<F, { ExecutionState::PrecompileSha256 }>>,
-    precompile_ripemd_gadget: Box<BasePrecompileGadget<F, { ExecutionState::PrecompileRipemd160 }>>,
+    precompile_sha2_gadget: Box<Sha256Gadget<F>>,
+    precompile_ripemd_gadget: Box<Ripemd160Gadget<F>>,
     precompile_identity_gadget: Box<IdentityGadget<F>>,
     precompile_modexp_gadget: Box<ModExpGadget<F>>,
     precompile_bn128add_gadget: Box<EcAddGadget<F>>,
     precompile_bn128mul_gadget: Box<EcMulGadget<F>>,
     precompile_bn128pairing_gadget: Box<EcPairingGadget<F>>,
-    precompile_blake2f_gadget: Box<BasePrecompileGadget<F, { ExecutionState::PrecompileBlake2f }>>,
+    precompile_blake2f_gadget: Box<Blake2fGadget<F>>,
 }
 
 impl<F: Field> ExecutionConfig<F> {
@@ -383,6 +403,9 @@ impl<F: Field> ExecutionConfig<F> {
         modexp_table: &dyn LookupTable<F>,
         ecc_table: &dyn LookupTable<F>,
         pow_of_rand_table: &dyn LookupTable<F>,
+        sha256_table: &dyn LookupTable<F>,
+        ripemd160_table: &dyn LookupTable<F>,
+        blake2f_table: &dyn LookupTable<F>,
     ) -> Self {
         let mut instrument = Instrument::default();
         let q_usable = meta.complex_selector();
@@ -571,6 +594,7 @@ impl<F: Field> ExecutionConfig<F> {
             jumpdest_gadget: configure_gadget!(),
             jumpi_gadget: configure_gadget!(),
             log_gadget: configure_gadget!(),
+            mcopy_gadget: configure_gadget!(),
             memory_gadget: configure_gadget!(),
             msize_gadget: configure_gadget!(),
             mul_div_mod_gadget: configure_gadget!(),
@@ -587,6 +611,8 @@ impl<F: Field> ExecutionConfig<F> {
             address_gadget: configure_gadget!(),
             balance_gadget: configure_gadget!(),
             blockhash_gadget: configure_gadget!(),
+            blobhash_gadget: configure_gadget!(),
+            blobbasefee_gadget: configure_gadget!(),
             exp_gadget: configure_gadget!(),
             sar_gadget: configure_gadget!(),
             extcodecopy_gadget: configure_gadget!(),
@@ -603,6 +629,8 @@ impl<F: Field> ExecutionConfig<F> {
             sstore_gadget: configure_gadget!(),
             stop_gadget: configure_gadget!(),
             swap_gadget: configure_gadget!(),
+            tload_gadget: configure_gadget!(),
+            tstore_gadget: configure_gadget!(),
             block_ctx_u64_gadget: configure_gadget!(),
             block_ctx_u160_gadget: configure_gadget!(),
             block_ctx_u256_gadget: configure_gadget!(),
@@ -661,6 +689,9 @@ impl<F: Field> ExecutionConfig<F> {
             modexp_table,
             ecc_table,
             pow_of_rand_table,
+            sha256_table,
+            ripemd160_table,
+            blake2f_table,
             &challenges,
             &cell_manager,
         );
@@ -927,6 +958,9 @@ impl<F: Field> ExecutionConfig<F> {
         modexp_table: &dyn LookupTable<F>,
         ecc_table: &dyn LookupTable<F>,
         pow_of_rand_table: &dyn LookupTable<F>,
+        sha256_table: &dyn LookupTable<F>,
+        ripemd160_table: &dyn LookupTable<F>,
+        blake2f_table: &dyn LookupTable<F>,
         challenges: &Challenges<Expression<F>>,
         cell_manager: &CellManager<F>,
     ) {
@@ -947,6 +981,9 @@ impl<F: Field> ExecutionConfig<F> {
                         Table::ModExp => modexp_table,
                         Table::Ecc => ecc_table,
                         Table::PowOfRand => pow_of_rand_table,
+                        Table::Sha256 => sha256_table,
+                        Table::Ripemd160 => ripemd160_table,
+                        Table::Blake2f => blake2f_table,
                     }
                     .table_exprs(meta);
                     vec![(
@@ -1249,6 +1286,9 @@ impl<F: Field> ExecutionConfig<F> {
             ("EVM_lookup_modexp", MODEXP_TABLE_LOOKUPS),
             ("EVM_lookup_ecc", ECC_TABLE_LOOKUPS),
             ("EVM_lookup_pow_of_rand", POW_OF_RAND_TABLE_LOOKUPS),
+            ("EVM_lookup_sha256", SHA256_TABLE_LOOKUPS),
+            ("EVM_lookup_ripemd160", RIPEMD160_TABLE_LOOKUPS),
+            ("EVM_lookup_blake2f", BLAKE2F_TABLE_LOOKUPS),
             ("EVM_adv_phase2", N_PHASE2_COLUMNS),
             ("EVM_copy", N_COPY_COLUMNS),
             ("EVM_lookup_byte", N_BYTE_LOOKUPS),
@@ -1421,6 +1461,7 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::JUMPDEST => assign_exec_step!(self.jumpdest_gadget),
             ExecutionState::JUMPI => assign_exec_step!(self.jumpi_gadget),
             ExecutionState::LOG => assign_exec_step!(self.log_gadget),
+            ExecutionState::MCOPY => assign_exec_step!(self.mcopy_gadget),
             ExecutionState::MEMORY => assign_exec_step!(self.memory_gadget),
             ExecutionState::MSIZE => assign_exec_step!(self.msize_gadget),
             ExecutionState::MUL_DIV_MOD => assign_exec_step!(self.mul_div_mod_gadget),
@@ -1440,16 +1481,16 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::BLOCKCTXU160 => assign_exec_step!(self.block_ctx_u160_gadget),
             ExecutionState::BLOCKCTXU256 => assign_exec_step!(self.block_ctx_u256_gadget),
             ExecutionState::BLOCKHASH => assign_exec_step!(self.blockhash_gadget),
+            ExecutionState::BLOBHASH => assign_exec_step!(self.blobhash_gadget),
+            ExecutionState::BLOBBASEFEE => assign_exec_step!(self.blobbasefee_gadget),
             ExecutionState::SELFBALANCE => assign_exec_step!(self.selfbalance_gadget),
             ExecutionState::CREATE => assign_exec_step!(self.create_gadget),
             ExecutionState::CREATE2 => assign_exec_step!(self.create2_gadget),
-            // dummy gadgets
             ExecutionState::EXTCODECOPY => assign_exec_step!(self.extcodecopy_gadget),
             ExecutionState::SELFDESTRUCT => {
                 #[cfg(not(feature = "scroll"))]
                 assign_exec_step!(self.selfdestruct_gadget)
             }
-            // end of dummy gadgets
             ExecutionState::SHA3 => assign_exec_step!(self.sha3_gadget),
             ExecutionState::SHL_SHR => assign_exec_step!(self.shl_shr_gadget),
             ExecutionState::SIGNEXTEND => assign_exec_step!(self.signextend_gadget),
@@ -1457,6 +1498,8 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::SSTORE => assign_exec_step!(self.sstore_gadget),
             ExecutionState::STOP => assign_exec_step!(self.stop_gadget),
             ExecutionState::SWAP => assign_exec_step!(self.swap_gadget),
+            ExecutionState::TLOAD => assign_exec_step!(self.tload_gadget),
+            ExecutionState::TSTORE => assign_exec_step!(self.tstore_gadget),
             // dummy errors
             ExecutionState::ErrorOutOfGasStaticMemoryExpansion => {
                 assign_exec_step!(self.error_oog_static_memory_gadget)
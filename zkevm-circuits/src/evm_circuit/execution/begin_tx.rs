@@ -420,6 +420,13 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 0.expr(),
                 Some(&mut reversion_info),
             );
+            cb.account_created_write(
+                tx_id.expr(),
+                call_callee_address.expr(),
+                1.expr(),
+                0.expr(),
+                Some(&mut reversion_info),
+            );
             for (field_tag, value) in [
                 (CallContextFieldTag::Depth, 1.expr()),
                 (CallContextFieldTag::CallerAddress, tx_caller_address.expr()),
@@ -445,7 +452,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             }
 
             cb.require_step_state_transition(StepStateTransition {
-                // 21 + a reads and writes:
+                // 22 + a reads and writes:
                 //   - a TxL1FeeGadget
                 //   - Write CallContext TxId
                 //   - Write CallContext RwCounterEndOfReversion
@@ -459,6 +466,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 //   - Read Account CodeHash
                 //   - a TransferWithGasFeeGadget
                 //   - Write Account (Callee) Nonce (Reversible)
+                //   - Write TxCreatedAccount (Callee) (Reversible)
                 //   - Write CallContext Depth
                 //   - Write CallContext CallerAddress
                 //   - Write CallContext CalleeAddress
@@ -473,7 +481,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 //   - Write CallContext IsCreate
                 //   - Write CallContext CodeHash
                 rw_counter: Delta(
-                    22.expr()
+                    23.expr()
                         + l1_rw_delta.expr()
                         + transfer_with_gas_fee.rw_delta()
                         + SHANGHAI_RW_DELTA.expr()
@@ -484,10 +492,11 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 is_create: To(tx_is_create.expr()),
                 code_hash: To(cb.curr.state.code_hash.expr()),
                 gas_left: To(gas_left.clone()),
-                // There are a + 1 reversible writes:
+                // There are a + 2 reversible writes:
                 //  - a TransferWithGasFeeGadget
                 //  - Callee Account Nonce
-                reversible_write_counter: To(transfer_with_gas_fee.reversible_w_delta() + 1.expr()),
+                //  - Callee TxCreatedAccount
+                reversible_write_counter: To(transfer_with_gas_fee.reversible_w_delta() + 2.expr()),
                 log_id: To(0.expr()),
                 ..StepStateTransition::new_context()
             });
@@ -0,0 +1,93 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{EVMConstraintBuilder, StepStateTransition, Transition::Delta},
+            CachedRegion, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::BlockContextFieldTag,
+    util::Expr,
+};
+use bus_mapping::evm::OpcodeId;
+use eth_types::{Field, ToLittleEndian};
+use halo2_proofs::plonk::Error;
+
+#[derive(Clone, Debug)]
+pub(crate) struct BlobBaseFeeGadget<F> {
+    same_context: SameContextGadget<F>,
+    blob_base_fee: Word<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for BlobBaseFeeGadget<F> {
+    const NAME: &'static str = "BLOBBASEFEE";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::BLOBBASEFEE;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let blob_base_fee = cb.query_word_rlc();
+        cb.block_lookup(
+            BlockContextFieldTag::BlobBaseFee.expr(),
+            cb.curr.state.block_number.expr(),
+            blob_base_fee.expr(),
+        );
+
+        cb.stack_push(blob_base_fee.expr());
+
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(1.expr()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta((-1).expr()),
+            gas_left: Delta(-OpcodeId::BLOBBASEFEE.constant_gas_cost().expr()),
+            ..Default::default()
+        };
+        let opcode = cb.query_cell();
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            blob_base_fee,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let blob_base_fee = block.rws[step.rw_indices[0]].stack_value();
+        self.blob_base_fee
+            .assign(region, offset, Some(blob_base_fee.to_le_bytes()))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::CircuitTestBuilder;
+    use eth_types::bytecode;
+    use mock::test_ctx::TestContext;
+
+    #[test]
+    fn blobbasefee_gadget_test() {
+        let bytecode = bytecode! {
+            BLOBBASEFEE
+            STOP
+        };
+
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+        )
+        .run();
+    }
+}
@@ -0,0 +1,222 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_U64,
+        step::ExecutionState,
+        util::{
+            common_gadget::{SameContextGadget, WordByteCapGadget},
+            constraint_builder::{
+                ConstrainBuilderCommon, EVMConstraintBuilder, StepStateTransition,
+                Transition::Delta,
+            },
+            CachedRegion, Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::{CallContextFieldTag, TxContextFieldTag},
+    util::Expr,
+};
+use bus_mapping::evm::OpcodeId;
+use eth_types::Field;
+use gadgets::util::not;
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct BlobHashGadget<F> {
+    same_context: SameContextGadget<F>,
+    tx_id: Cell<F>,
+    num_blobs: Cell<F>,
+    index: WordByteCapGadget<F, N_BYTES_U64>,
+    blob_hash: Cell<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for BlobHashGadget<F> {
+    const NAME: &'static str = "BLOBHASH";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::BLOBHASH;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+
+        // Lookup the number of versioned hashes carried by the tx.
+        let num_blobs = cb.query_cell();
+        cb.tx_context_lookup(
+            tx_id.expr(),
+            TxContextFieldTag::BlobVersionedHashesLength,
+            None,
+            num_blobs.expr(),
+        );
+
+        let index = WordByteCapGadget::construct(cb, num_blobs.expr());
+        cb.stack_pop(index.original_word());
+
+        let blob_hash = cb.query_cell_phase2();
+        cb.condition(index.lt_cap(), |cb| {
+            cb.tx_context_lookup(
+                tx_id.expr(),
+                TxContextFieldTag::BlobVersionedHash,
+                Some(index.valid_value()),
+                blob_hash.expr(),
+            );
+        });
+        cb.condition(not::expr(index.lt_cap()), |cb| {
+            cb.require_zero(
+                "BLOBHASH returns zero if index is out of range",
+                blob_hash.expr(),
+            );
+        });
+
+        cb.stack_push(blob_hash.expr());
+
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(3.expr()),
+            program_counter: Delta(1.expr()),
+            gas_left: Delta(-OpcodeId::BLOBHASH.constant_gas_cost().expr()),
+            ..Default::default()
+        };
+
+        let opcode = cb.query_cell();
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            tx_id,
+            num_blobs,
+            index,
+            blob_hash,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let [index, blob_hash] =
+            [step.rw_indices[1], step.rw_indices[2]].map(|idx| block.rws[idx].stack_value());
+        let num_blobs = F::from(tx.blob_versioned_hashes.len() as u64);
+
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
+        self.num_blobs
+            .assign(region, offset, Value::known(num_blobs))?;
+        self.index.assign(region, offset, index, num_blobs)?;
+        self.blob_hash
+            .assign(region, offset, region.word_rlc(blob_hash))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        evm_circuit::step::ExecutionState,
+        test_util::CircuitTestBuilder,
+        witness::{Block, Rw},
+    };
+    use eth_types::{bytecode, evm_types::MAX_BLOBS_PER_TX, ToWord, Word, H256};
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use mock::{test_ctx::helpers::account_0_code_wallet_0_no_code, TestContext, MOCK_WALLETS};
+
+    fn ctx(index: Word, blob_versioned_hashes: Option<Vec<H256>>) -> TestContext<2, 1> {
+        let bytecode = bytecode! {
+            PUSH32(index)
+            BLOBHASH
+            STOP
+        };
+        TestContext::new(
+            None,
+            account_0_code_wallet_0_no_code(bytecode),
+            |mut txs, accs| {
+                txs[0].from(MOCK_WALLETS[0].clone()).to(accs[0].address);
+                if let Some(hashes) = blob_versioned_hashes {
+                    txs[0]
+                        .transaction_type(3)
+                        .max_fee_per_gas(Word::from(1_000_000_000u64))
+                        .max_priority_fee_per_gas(Word::from(1_000_000_000u64))
+                        .max_fee_per_blob_gas(Word::one())
+                        .blob_versioned_hashes(hashes);
+                }
+            },
+            |block, _txs| block.number(0xcafeu64),
+        )
+        .unwrap()
+    }
+
+    fn blob_versioned_hashes() -> Vec<H256> {
+        (1..=MAX_BLOBS_PER_TX as u8)
+            .map(|i| {
+                let mut hash = H256::repeat_byte(i);
+                // VERSIONED_HASH_VERSION_KZG
+                hash.0[0] = 0x01;
+                hash
+            })
+            .collect()
+    }
+
+    #[test]
+    fn blobhash_gadget_non_blob_tx() {
+        CircuitTestBuilder::new_from_test_ctx(ctx(Word::zero(), None)).run();
+    }
+
+    #[test]
+    fn blobhash_gadget_index_in_range() {
+        for len in [1, 2, MAX_BLOBS_PER_TX] {
+            let hashes = blob_versioned_hashes()[..len].to_vec();
+            for index in [0, len - 1] {
+                CircuitTestBuilder::new_from_test_ctx(ctx(Word::from(index), Some(hashes.clone())))
+                    .run();
+            }
+        }
+    }
+
+    #[test]
+    fn blobhash_gadget_index_out_of_range() {
+        for hashes in [None, Some(blob_versioned_hashes()[..2].to_vec())] {
+            for index in [
+                Word::from(2),
+                Word::from(MAX_BLOBS_PER_TX),
+                Word::from(u64::MAX) + 1,
+                Word::MAX,
+            ] {
+                CircuitTestBuilder::new_from_test_ctx(ctx(index, hashes.clone())).run();
+            }
+        }
+    }
+
+    #[test]
+    fn blobhash_gadget_wrong_hash() {
+        let hashes = blob_versioned_hashes()[..2].to_vec();
+        // the hash at another index, and zero as if the index was out of range
+        for output in [hashes[0].to_word(), Word::zero()] {
+            CircuitTestBuilder::new_from_test_ctx(ctx(Word::one(), Some(hashes.clone())))
+                .block_modifier(Box::new(move |block: &mut Block<Fr>| {
+                    let step = block.txs[0]
+                        .steps
+                        .iter()
+                        .find(|step| step.execution_state == ExecutionState::BLOBHASH)
+                        .unwrap()
+                        .clone();
+                    let (tag, idx) = step.rw_indices[2];
+                    match &mut block.rws.0.get_mut(&tag).unwrap()[idx] {
+                        Rw::Stack { value, .. } => *value = output,
+                        rw => unreachable!("{rw:?}"),
+                    }
+                }))
+                .state_checks(None)
+                .evm_checks(Some(Box::new(|prover, gate_rows, lookup_rows| {
+                    assert!(prover
+                        .verify_at_rows_par(gate_rows.iter().cloned(), lookup_rows.iter().cloned())
+                        .is_err())
+                })))
+                .run();
+        }
+    }
+}
@@ -51,6 +51,7 @@ pub(crate) struct CreateGadget<F, const IS_CREATE2: bool, const S: ExecutionStat
     caller_balance: Word<F>,
     callee_reversion_info: ReversionInfo<F>,
     callee_nonce: Cell<F>,
+    callee_was_created: Cell<F>,
     prev_code_hash: Cell<F>,
     prev_code_hash_is_zero: IsZeroGadget<F>,
     transfer: TransferGadget<F>,
@@ -349,6 +350,7 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
         });
 
         let mut callee_reversion_info = cb.reversion_info_write(Some(callee_call_id.expr()));
+        let callee_was_created = cb.query_cell();
 
         // Case1: Handle the case where an error of ErrDepth, ErrInsufficientBalance or
         // ErrNonceUintOverflow occurred.
@@ -424,6 +426,14 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
                     0.expr(),
                     Some(&mut callee_reversion_info),
                 );
+                // EIP 6780, mark the contract as created in this tx so it can self destruct
+                cb.account_created_write(
+                    tx_id.expr(),
+                    contract_addr.clone(),
+                    1.expr(),
+                    callee_was_created.expr(),
+                    Some(&mut callee_reversion_info),
+                );
 
                 cb.condition(init_code.has_length(), |cb| {
                     for (field_tag, value) in [
@@ -461,7 +471,7 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
                         is_create: To(true.expr()),
                         code_hash: To(create.code_hash_word_rlc()),
                         gas_left: To(callee_gas_left),
-                        reversible_write_counter: To(1.expr() + transfer.reversible_w_delta()),
+                        reversible_write_counter: To(2.expr() + transfer.reversible_w_delta()),
                         ..StepStateTransition::new_context()
                     });
                 });
@@ -484,7 +494,7 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
                         program_counter: Delta(1.expr()),
                         stack_pointer: Delta(2.expr() + IS_CREATE2.expr()),
                         gas_left: Delta(-gas_cost.expr()),
-                        reversible_write_counter: Delta(3.expr() + transfer.reversible_w_delta()),
+                        reversible_write_counter: Delta(4.expr() + transfer.reversible_w_delta()),
                         ..Default::default()
                     })
                 });
@@ -553,6 +563,7 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
             is_insufficient_balance,
             is_nonce_in_range,
             callee_nonce,
+            callee_was_created,
             keccak_code_hash,
             keccak_output,
             not_address_collision,
@@ -726,6 +737,13 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
                 let _transfer_assign_result = self
                     .transfer
                     .assign_from_rws(region, offset, false, true, value, &mut rws)?;
+                rws.next(); // callee nonce += 1
+                let callee_was_created = rws.next().tx_created_account_value_pair().1;
+                self.callee_was_created.assign(
+                    region,
+                    offset,
+                    Value::known(F::from(callee_was_created as u64)),
+                )?;
 
                 #[cfg(feature = "scroll")]
                 self.prev_keccak_code_hash.assign(
@@ -790,7 +808,6 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
             } else if init_code_length.as_usize() == 0 {
                 F::one()
             } else {
-                rws.next(); // callee nonce += 1
                 rws.next(); // caller id
                 let rw = rws.next();
                 debug_assert_eq!(rw.tag(), RwTableTag::CallContext);
@@ -87,7 +87,7 @@ mod test {
             vec![0xf6],
             vec![0xfe],
             // Multiple invalid opcodes
-            vec![0x5c, 0x5e],
+            vec![0x0c, 0x0d],
         ];
     }
 
@@ -27,7 +27,8 @@ use halo2_proofs::{circuit::Value, plonk::Error};
 
 /// Gadget to implement the corresponding out of gas errors for
 /// [`OpcodeId::CALLDATACOPY`], [`OpcodeId::CODECOPY`],
-/// [`OpcodeId::EXTCODECOPY`] and [`OpcodeId::RETURNDATACOPY`].
+/// [`OpcodeId::EXTCODECOPY`], [`OpcodeId::RETURNDATACOPY`] and
+/// [`OpcodeId::MCOPY`].
 #[derive(Clone, Debug)]
 pub(crate) struct ErrorOOGMemoryCopyGadget<F> {
     opcode: Cell<F>,
@@ -36,14 +37,16 @@ pub(crate) struct ErrorOOGMemoryCopyGadget<F> {
     tx_id: Cell<F>,
     /// Extra stack pop for `EXTCODECOPY`
     external_address: Word<F>,
-    /// Source offset
-    src_offset: Word<F>,
+    /// Source offset and size to copy, which only expands the memory for
+    /// `MCOPY`
+    src_memory_addr: MemoryExpandedAddressGadget<F>,
     /// Destination offset and size to copy
     dst_memory_addr: MemoryExpandedAddressGadget<F>,
-    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    memory_expansion: MemoryExpansionGadget<F, 2, N_BYTES_MEMORY_WORD_SIZE>,
     memory_copier_gas: MemoryCopierGasGadget<F, { GasCost::COPY }>,
     insufficient_gas: LtGadget<F, N_BYTES_GAS>,
     is_extcodecopy: IsZeroGadget<F>,
+    is_mcopy: IsZeroGadget<F>,
     common_error_gadget: CommonErrorGadget<F>,
 }
 
@@ -55,23 +58,24 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGMemoryCopyGadget<F> {
     fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
         let opcode = cb.query_cell();
         cb.require_in_set(
-            "ErrorOutOfGasMemoryCopy opcode must be CALLDATACOPY, CODECOPY, EXTCODECOPY or RETURNDATACOPY",
+            "ErrorOutOfGasMemoryCopy opcode must be CALLDATACOPY, CODECOPY, EXTCODECOPY, RETURNDATACOPY or MCOPY",
             opcode.expr(),
             vec![
                 OpcodeId::CALLDATACOPY.expr(),
                 OpcodeId::CODECOPY.expr(),
                 OpcodeId::EXTCODECOPY.expr(),
                 OpcodeId::RETURNDATACOPY.expr(),
+                OpcodeId::MCOPY.expr(),
             ],
         );
 
-        let src_offset = cb.query_word_rlc();
         let external_address = cb.query_word_rlc();
         let is_warm = cb.query_bool();
         let tx_id = cb.query_cell();
 
         let is_extcodecopy =
             IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::EXTCODECOPY.expr());
+        let is_mcopy = IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::MCOPY.expr());
 
         cb.condition(is_extcodecopy.expr(), |cb| {
             cb.call_context_lookup(false.expr(), None, CallContextFieldTag::TxId, tx_id.expr());
@@ -88,12 +92,26 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGMemoryCopyGadget<F> {
         });
 
         let dst_memory_addr = MemoryExpandedAddressGadget::construct_self(cb);
+        let src_memory_addr = MemoryExpandedAddressGadget::construct_self(cb);
+        cb.require_equal(
+            "source and destination have the same length",
+            src_memory_addr.length_rlc(),
+            dst_memory_addr.length_rlc(),
+        );
 
         cb.stack_pop(dst_memory_addr.offset_rlc());
-        cb.stack_pop(src_offset.expr());
+        cb.stack_pop(src_memory_addr.offset_rlc());
         cb.stack_pop(dst_memory_addr.length_rlc());
 
-        let memory_expansion = MemoryExpansionGadget::construct(cb, [dst_memory_addr.end_offset()]);
+        // MCOPY also expands the memory to the source range, while the other
+        // opcodes copy from outside of the memory.
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            [
+                is_mcopy.expr() * src_memory_addr.end_offset(),
+                dst_memory_addr.end_offset(),
+            ],
+        );
         let memory_copier_gas = MemoryCopierGasGadget::construct(
             cb,
             dst_memory_addr.length(),
@@ -109,7 +127,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGMemoryCopyGadget<F> {
                 GasCost::WARM_ACCESS.expr(),
                 GasCost::COLD_ACCOUNT_ACCESS.expr(),
             ),
-            // Constant gas cost is same for CALLDATACOPY, CODECOPY and RETURNDATACOPY.
+            // Constant gas cost is same for CALLDATACOPY, CODECOPY, RETURNDATACOPY and MCOPY.
             OpcodeId::CALLDATACOPY.constant_gas_cost().expr(),
         );
 
@@ -121,7 +139,11 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGMemoryCopyGadget<F> {
 
         cb.require_equal(
             "Memory address is overflow or gas left is less than cost",
-            or::expr([dst_memory_addr.overflow(), insufficient_gas.expr()]),
+            or::expr([
+                dst_memory_addr.overflow(),
+                is_mcopy.expr() * src_memory_addr.overflow(),
+                insufficient_gas.expr(),
+            ]),
             1.expr(),
         );
 
@@ -138,12 +160,13 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGMemoryCopyGadget<F> {
             is_warm,
             tx_id,
             external_address,
-            src_offset,
+            src_memory_addr,
             dst_memory_addr,
             memory_expansion,
             memory_copier_gas,
             insufficient_gas,
             is_extcodecopy,
+            is_mcopy,
             common_error_gadget,
         }
     }
@@ -159,6 +182,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGMemoryCopyGadget<F> {
     ) -> Result<(), Error> {
         let opcode = step.opcode.unwrap();
         let is_extcodecopy = opcode == OpcodeId::EXTCODECOPY;
+        let is_mcopy = opcode == OpcodeId::MCOPY;
 
         log::debug!(
             "ErrorOutOfGasMemoryCopy: opcode = {}, gas_left = {}, gas_cost = {}",
@@ -188,14 +212,18 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGMemoryCopyGadget<F> {
             .assign(region, offset, Value::known(F::from(transaction.id as u64)))?;
         self.external_address
             .assign(region, offset, Some(external_address.to_le_bytes()))?;
-        self.src_offset
-            .assign(region, offset, Some(src_offset.to_le_bytes()))?;
-        let memory_addr = self
+        let src_memory_addr = self
+            .src_memory_addr
+            .assign(region, offset, src_offset, copy_size)?;
+        let dst_memory_addr = self
             .dst_memory_addr
             .assign(region, offset, dst_offset, copy_size)?;
-        let (_, memory_expansion_cost) =
-            self.memory_expansion
-                .assign(region, offset, step.memory_word_size(), [memory_addr])?;
+        let (_, memory_expansion_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [if is_mcopy { src_memory_addr } else { 0 }, dst_memory_addr],
+        )?;
         let memory_copier_gas = self.memory_copier_gas.assign(
             region,
             offset,
@@ -222,6 +250,11 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGMemoryCopyGadget<F> {
             offset,
             F::from(opcode.as_u64()) - F::from(OpcodeId::EXTCODECOPY.as_u64()),
         )?;
+        self.is_mcopy.assign(
+            region,
+            offset,
+            F::from(opcode.as_u64()) - F::from(OpcodeId::MCOPY.as_u64()),
+        )?;
         self.common_error_gadget.assign(
             region,
             offset,
@@ -291,6 +324,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_oog_memory_copy_for_mcopy() {
+        // The source range expands the memory further than the destination one
+        // in the last two cases.
+        for (src_offset, dst_offset, copy_size) in [
+            (0x20, 0x40, 0),
+            (0x00, 0x40, 20),
+            (0x20, 0x2000, 0x200),
+            (0x2000, 0x20, 0x200),
+            (0x4000, 0x2000, 0x200),
+        ] {
+            let testing_data = TestingData::new_for_mcopy(src_offset, dst_offset, copy_size, None);
+
+            test_root(&testing_data);
+            test_internal(&testing_data);
+        }
+    }
+
+    #[test]
+    fn test_oog_memory_copy_for_mcopy_max_src_address() {
+        let testing_data =
+            TestingData::new_for_mcopy(u64::MAX, 0x20, 0x20, Some(MOCK_BLOCK_GAS_LIMIT));
+
+        test_root(&testing_data);
+        test_internal(&testing_data);
+    }
+
     #[test]
     fn test_oog_memory_copy_max_expanded_address() {
         // 0xffffffff1 + 0xffffffff0 = 0x1fffffffe1
@@ -333,6 +393,34 @@ mod tests {
             Self { bytecode, gas_cost }
         }
 
+        pub fn new_for_mcopy(
+            src_offset: u64,
+            dst_offset: u64,
+            copy_size: u64,
+            gas_cost: Option<u64>,
+        ) -> Self {
+            let bytecode = bytecode! {
+                PUSH32(copy_size)
+                PUSH32(src_offset)
+                PUSH32(dst_offset)
+                MCOPY
+            };
+
+            let gas_cost = gas_cost.unwrap_or_else(|| {
+                let memory_word_size = if copy_size == 0 {
+                    0
+                } else {
+                    (src_offset.max(dst_offset) + copy_size + 31) / 32
+                };
+
+                OpcodeId::PUSH32.constant_gas_cost().0 * 3
+                    + OpcodeId::MCOPY.constant_gas_cost().0
+                    + memory_copier_gas_cost(0, memory_word_size, copy_size, GasCost::COPY.as_u64())
+            });
+
+            Self { bytecode, gas_cost }
+        }
+
         pub fn new_for_extcodecopy(
             is_warm: bool,
             dst_offset: u64,
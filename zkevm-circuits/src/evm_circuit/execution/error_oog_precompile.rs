@@ -54,13 +54,18 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGPrecompileGadget<F> {
                 )
             },
         );
-        let n_words = cb.condition(addr_bits.value_equals(PrecompileCalls::Identity), |cb| {
-            ConstantDivisionGadget::construct(
-                cb,
-                call_data_length.expr() + (N_BYTES_WORD - 1).expr(),
-                N_BYTES_WORD as u64,
-            )
-        });
+        let n_words = cb.condition(
+            addr_bits.value_equals(PrecompileCalls::Identity)
+                + addr_bits.value_equals(PrecompileCalls::Sha256)
+                + addr_bits.value_equals(PrecompileCalls::Ripemd160),
+            |cb| {
+                ConstantDivisionGadget::construct(
+                    cb,
+                    call_data_length.expr() + (N_BYTES_WORD - 1).expr(),
+                    N_BYTES_WORD as u64,
+                )
+            },
+        );
 
         // calculate required gas for precompile
         let precompiles_required_gas = vec![
@@ -68,9 +73,17 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGPrecompileGadget<F> {
                 addr_bits.value_equals(PrecompileCalls::Ecrecover),
                 GasCost::PRECOMPILE_ECRECOVER_BASE.expr(),
             ),
-            // These are handled in PrecompileFailedGadget
-            // addr_bits.value_equals(PrecompileCalls::Sha256),
-            // addr_bits.value_equals(PrecompileCalls::Ripemd160),
+            (
+                addr_bits.value_equals(PrecompileCalls::Sha256),
+                GasCost::PRECOMPILE_SHA256_BASE.expr()
+                    + n_words.quotient() * GasCost::PRECOMPILE_SHA256_PER_WORD.expr(),
+            ),
+            (
+                addr_bits.value_equals(PrecompileCalls::Ripemd160),
+                GasCost::PRECOMPILE_RIPEMD160_BASE.expr()
+                    + n_words.quotient() * GasCost::PRECOMPILE_RIPEMD160_PER_WORD.expr(),
+            ),
+            // This is handled in PrecompileFailedGadget
             // addr_bits.value_equals(PrecompileCalls::Blake2F),
             (
                 addr_bits.value_equals(PrecompileCalls::Identity),
@@ -192,6 +205,16 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGPrecompileGadget<F> {
                 precompile_call.base_gas_cost().as_u64()
                     + n_words * GasCost::PRECOMPILE_IDENTITY_PER_WORD.as_u64()
             }
+            PrecompileCalls::Sha256 => {
+                let n_words = (call.call_data_length + 31) / 32;
+                precompile_call.base_gas_cost().as_u64()
+                    + n_words * GasCost::PRECOMPILE_SHA256_PER_WORD.as_u64()
+            }
+            PrecompileCalls::Ripemd160 => {
+                let n_words = (call.call_data_length + 31) / 32;
+                precompile_call.base_gas_cost().as_u64()
+                    + n_words * GasCost::PRECOMPILE_RIPEMD160_PER_WORD.as_u64()
+            }
             PrecompileCalls::Bn128Add | PrecompileCalls::Bn128Mul | PrecompileCalls::Ecrecover => {
                 precompile_call.base_gas_cost().as_u64()
             }
@@ -253,6 +276,48 @@ mod test {
                         - 1).to_word(),
                     ..Default::default()
                 },
+                PrecompileCallArgs {
+                    name: "sha256 multi-word input",
+                    setup_code: bytecode! {
+                        // place params in memory
+                        PUSH30(word!("0x0123456789abcdef0f1e2d3c4b5a6978"))
+                        PUSH1(0x00) // place from 0x00 in memory
+                        MSTORE
+                        PUSH30(word!("0xaabbccdd001122331039abcdefefef84"))
+                        PUSH1(0x20) // place from 0x20 in memory
+                        MSTORE
+                    },
+                    call_data_offset: 0x00.into(),
+                    call_data_length: 0x3f.into(),
+                    ret_offset: 0x48.into(),
+                    ret_size: 0x20.into(),
+                    address: PrecompileCalls::Sha256.address().to_word(),
+                    gas: (PrecompileCalls::Sha256.base_gas_cost().as_u64()
+                        + 2 * GasCost::PRECOMPILE_SHA256_PER_WORD.as_u64()
+                        - 1).to_word(),
+                    ..Default::default()
+                },
+                PrecompileCallArgs {
+                    name: "ripemd160 multi-word input",
+                    setup_code: bytecode! {
+                        // place params in memory
+                        PUSH30(word!("0x0123456789abcdef0f1e2d3c4b5a6978"))
+                        PUSH1(0x00) // place from 0x00 in memory
+                        MSTORE
+                        PUSH30(word!("0xaabbccdd001122331039abcdefefef84"))
+                        PUSH1(0x20) // place from 0x20 in memory
+                        MSTORE
+                    },
+                    call_data_offset: 0x00.into(),
+                    call_data_length: 0x3f.into(),
+                    ret_offset: 0x48.into(),
+                    ret_size: 0x20.into(),
+                    address: PrecompileCalls::Ripemd160.address().to_word(),
+                    gas: (PrecompileCalls::Ripemd160.base_gas_cost().as_u64()
+                        + 2 * GasCost::PRECOMPILE_RIPEMD160_PER_WORD.as_u64()
+                        - 1).to_word(),
+                    ..Default::default()
+                },
                 PrecompileCallArgs {
                     name: "modexp length in u256",
                     setup_code: bytecode! {
@@ -0,0 +1,269 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS},
+        step::ExecutionState,
+        util::{
+            common_gadget::CommonErrorGadget,
+            constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+            from_bytes,
+            math_gadget::{IsZeroGadget, LtGadget},
+            not, CachedRegion, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::{AccountFieldTag, CallContextFieldTag},
+    util::Expr,
+};
+use eth_types::{
+    evm_types::{GasCost, OpcodeId},
+    Field, ToLittleEndian, ToScalar,
+};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Gadget to implement the corresponding out of gas errors for
+/// [`OpcodeId::SELFDESTRUCT`].
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGSelfDestructGadget<F> {
+    opcode: Cell<F>,
+    beneficiary: Word<F>,
+    tx_id: Cell<F>,
+    callee_address: Cell<F>,
+    is_warm: Cell<F>,
+    balance: Word<F>,
+    balance_is_zero: IsZeroGadget<F>,
+    beneficiary_code_hash: Cell<F>,
+    beneficiary_not_exists: IsZeroGadget<F>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorOOGSelfDestructGadget<F> {
+    const NAME: &'static str = "ErrorOutOfGasSELFDESTRUCT";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasSELFDESTRUCT;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.require_equal(
+            "ErrorOutOfGasSELFDESTRUCT opcode must be SELFDESTRUCT",
+            opcode.expr(),
+            OpcodeId::SELFDESTRUCT.expr(),
+        );
+
+        let beneficiary = cb.query_word_rlc();
+        let beneficiary_address = from_bytes::expr(&beneficiary.cells[..N_BYTES_ACCOUNT_ADDRESS]);
+        cb.stack_pop(beneficiary.expr());
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let callee_address = cb.call_context(None, CallContextFieldTag::CalleeAddress);
+
+        let is_warm = cb.query_bool();
+        cb.account_access_list_read(tx_id.expr(), beneficiary_address.expr(), is_warm.expr());
+
+        let balance = cb.query_word_rlc();
+        cb.account_read(
+            callee_address.expr(),
+            AccountFieldTag::Balance,
+            balance.expr(),
+        );
+        let balance_is_zero = IsZeroGadget::construct(cb, balance.expr());
+
+        let beneficiary_code_hash = cb.query_cell_phase2();
+        cb.account_read(
+            beneficiary_address.expr(),
+            AccountFieldTag::CodeHash,
+            beneficiary_code_hash.expr(),
+        );
+        let beneficiary_not_exists = IsZeroGadget::construct(cb, beneficiary_code_hash.expr());
+
+        let gas_cost = GasCost::SELFDESTRUCT.expr()
+            + not::expr(is_warm.expr()) * GasCost::COLD_ACCOUNT_ACCESS.expr()
+            + beneficiary_not_exists.expr()
+                * not::expr(balance_is_zero.expr())
+                * GasCost::NEW_ACCOUNT.expr();
+
+        let insufficient_gas = LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_cost);
+        cb.require_equal(
+            "Gas left is less than gas cost",
+            insufficient_gas.expr(),
+            1.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr(), 8.expr());
+
+        Self {
+            opcode,
+            beneficiary,
+            tx_id,
+            callee_address,
+            is_warm,
+            balance,
+            balance_is_zero,
+            beneficiary_code_hash,
+            beneficiary_not_exists,
+            insufficient_gas,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+
+        let beneficiary = block.rws[step.rw_indices[0]].stack_value();
+        self.beneficiary
+            .assign(region, offset, Some(beneficiary.to_le_bytes()))?;
+
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
+        self.callee_address.assign(
+            region,
+            offset,
+            Value::known(call.address.to_scalar().unwrap()),
+        )?;
+
+        let (_, is_warm) = block.rws[step.rw_indices[3]].tx_access_list_value_pair();
+        self.is_warm
+            .assign(region, offset, Value::known(F::from(is_warm)))?;
+
+        let balance = block.rws[step.rw_indices[4]].account_value_pair().0;
+        self.balance
+            .assign(region, offset, Some(balance.to_le_bytes()))?;
+        self.balance_is_zero
+            .assign_value(region, offset, region.word_rlc(balance))?;
+
+        let beneficiary_code_hash = block.rws[step.rw_indices[5]].account_value_pair().0;
+        self.beneficiary_code_hash.assign(
+            region,
+            offset,
+            region.code_hash(beneficiary_code_hash),
+        )?;
+        self.beneficiary_not_exists.assign_value(
+            region,
+            offset,
+            region.code_hash(beneficiary_code_hash),
+        )?;
+
+        let mut gas_cost = GasCost::SELFDESTRUCT.as_u64();
+        if !is_warm {
+            gas_cost += GasCost::COLD_ACCOUNT_ACCESS.as_u64();
+        }
+        if beneficiary_code_hash.is_zero() && !balance.is_zero() {
+            gas_cost += GasCost::NEW_ACCOUNT.as_u64();
+        }
+        self.insufficient_gas
+            .assign(region, offset, F::from(step.gas_left), F::from(gas_cost))?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 8)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::CircuitTestBuilder;
+    use eth_types::{address, bytecode, evm_types::GasCost, Address, Bytecode, ToWord, Word};
+    use mock::{eth, TestContext};
+
+    const CONTRACT: Address = Address::repeat_byte(0xcc);
+    const EXISTING: Address = Address::repeat_byte(0xee);
+    const NON_EXISTING: Address = Address::repeat_byte(0x0e);
+
+    // PUSH20 + SELFDESTRUCT static cost + cold access of the beneficiary
+    const COST_WITHOUT_NEW_ACCOUNT: u64 = 3 + 5000 + 2600;
+
+    fn code(beneficiary: Address) -> Bytecode {
+        bytecode! {
+            PUSH20(beneficiary.to_word())
+            SELFDESTRUCT
+        }
+    }
+
+    fn test_root(beneficiary: Address, gas: u64) {
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000123"))
+                    .balance(eth(10));
+                accs[1]
+                    .address(CONTRACT)
+                    .balance(eth(1))
+                    .code(code(beneficiary));
+                accs[2].address(EXISTING).balance(eth(1));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(accs[1].address)
+                    .gas(Word::from(GasCost::TX.as_u64() + gas));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    fn test_internal(beneficiary: Address, gas: u64) {
+        let caller_code = bytecode! {
+            PUSH1(0) // retLength
+            PUSH1(0) // retOffset
+            PUSH1(0) // argsLength
+            PUSH1(0) // argsOffset
+            PUSH1(0) // value
+            PUSH20(CONTRACT.to_word())
+            PUSH32(gas)
+            CALL
+            STOP
+        };
+        let ctx = TestContext::<4, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000123"))
+                    .balance(eth(10));
+                accs[1]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .balance(eth(1))
+                    .code(caller_code);
+                accs[2]
+                    .address(CONTRACT)
+                    .balance(eth(1))
+                    .code(code(beneficiary));
+                accs[3].address(EXISTING).balance(eth(1));
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[0].address).to(accs[1].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    #[test]
+    fn error_oog_self_destruct_cold_account() {
+        test_root(EXISTING, COST_WITHOUT_NEW_ACCOUNT - 1);
+        test_internal(EXISTING, COST_WITHOUT_NEW_ACCOUNT - 1);
+    }
+
+    #[test]
+    fn error_oog_self_destruct_new_account() {
+        test_root(NON_EXISTING, COST_WITHOUT_NEW_ACCOUNT);
+        test_internal(NON_EXISTING, COST_WITHOUT_NEW_ACCOUNT);
+    }
+}
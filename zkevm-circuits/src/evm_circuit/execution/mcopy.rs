@@ -0,0 +1,222 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_MEMORY_WORD_SIZE,
+        step::ExecutionState,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{
+                ConstrainBuilderCommon, EVMConstraintBuilder, StepStateTransition,
+                Transition::{Delta, To},
+            },
+            memory_gadget::{
+                CommonMemoryAddressGadget, MemoryAddressGadget, MemoryCopierGasGadget,
+                MemoryExpansionGadget,
+            },
+            not, CachedRegion, Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use bus_mapping::{circuit_input_builder::CopyDataType, evm::OpcodeId};
+use eth_types::{evm_types::GasCost, Field, ToScalar};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct MCopyGadget<F> {
+    same_context: SameContextGadget<F>,
+    /// The memory range read from.
+    src_memory_addr: MemoryAddressGadget<F>,
+    /// The memory range written to.
+    dst_memory_addr: MemoryAddressGadget<F>,
+    copy_rwc_inc: Cell<F>,
+    /// Memory expands to cover both the source and the destination.
+    memory_expansion: MemoryExpansionGadget<F, 2, N_BYTES_MEMORY_WORD_SIZE>,
+    memory_copier_gas: MemoryCopierGasGadget<F, { GasCost::COPY }>,
+}
+
+impl<F: Field> ExecutionGadget<F> for MCopyGadget<F> {
+    const NAME: &'static str = "MCOPY";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::MCOPY;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let dst_offset = cb.query_cell_phase2();
+        let src_offset = cb.query_cell_phase2();
+        let length = cb.query_word_rlc();
+
+        // Pop dst_offset, src_offset, length from stack
+        cb.stack_pop(dst_offset.expr());
+        cb.stack_pop(src_offset.expr());
+        cb.stack_pop(length.expr());
+
+        let dst_memory_addr = MemoryAddressGadget::construct(cb, dst_offset, length);
+        let src_length = cb.query_word_rlc();
+        let src_memory_addr = MemoryAddressGadget::construct(cb, src_offset, src_length);
+        cb.require_equal(
+            "source and destination have the same length",
+            src_memory_addr.length_rlc(),
+            dst_memory_addr.length_rlc(),
+        );
+
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            [src_memory_addr.end_offset(), dst_memory_addr.end_offset()],
+        );
+        let memory_copier_gas = MemoryCopierGasGadget::construct(
+            cb,
+            dst_memory_addr.length(),
+            memory_expansion.gas_cost(),
+        );
+
+        // The copy circuit reads the whole source range before writing the
+        // destination, so overlapping ranges are allowed.
+        let copy_rwc_inc = cb.query_cell();
+        cb.condition(dst_memory_addr.has_length(), |cb| {
+            cb.copy_table_lookup(
+                cb.curr.state.call_id.expr(),
+                CopyDataType::Memory.expr(),
+                cb.curr.state.call_id.expr(),
+                CopyDataType::Memory.expr(),
+                src_memory_addr.offset(),
+                src_memory_addr.end_offset(),
+                dst_memory_addr.offset(),
+                dst_memory_addr.length(),
+                0.expr(), // for MCOPY rlc_acc is 0
+                copy_rwc_inc.expr(),
+            );
+        });
+        cb.condition(not::expr(dst_memory_addr.has_length()), |cb| {
+            cb.require_zero(
+                "if no bytes to copy, copy table rwc inc == 0",
+                copy_rwc_inc.expr(),
+            );
+        });
+
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(cb.rw_counter_offset()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(3.expr()),
+            gas_left: Delta(
+                -(OpcodeId::MCOPY.constant_gas_cost().expr() + memory_copier_gas.gas_cost()),
+            ),
+            memory_word_size: To(memory_expansion.next_memory_word_size()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            src_memory_addr,
+            dst_memory_addr,
+            copy_rwc_inc,
+            memory_expansion,
+            memory_copier_gas,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _tx: &Transaction,
+        _call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let [dst_offset, src_offset, length] =
+            [0, 1, 2].map(|idx| block.rws[step.rw_indices[idx]].stack_value());
+
+        let src_address = self
+            .src_memory_addr
+            .assign(region, offset, src_offset, length)?;
+        let dst_address = self
+            .dst_memory_addr
+            .assign(region, offset, dst_offset, length)?;
+
+        let (_, memory_expansion_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [src_address, dst_address],
+        )?;
+        self.memory_copier_gas
+            .assign(region, offset, length.as_u64(), memory_expansion_cost)?;
+
+        self.copy_rwc_inc.assign(
+            region,
+            offset,
+            Value::known(
+                step.copy_rw_counter_delta
+                    .to_scalar()
+                    .expect("unexpected U256 -> Scalar conversion failure"),
+            ),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::CircuitTestBuilder;
+    use eth_types::{bytecode, Word};
+    use mock::TestContext;
+
+    fn test_ok(src_offset: Word, dst_offset: Word, length: usize) {
+        let code = bytecode! {
+            PUSH32(Word::from_big_endian(&(0x00..0x20).collect::<Vec<u8>>()))
+            PUSH1(0x00)
+            MSTORE
+            PUSH32(Word::from_big_endian(&(0x20..0x40).collect::<Vec<u8>>()))
+            PUSH1(0x20)
+            MSTORE
+            PUSH32(Word::from(length))
+            PUSH32(src_offset)
+            PUSH32(dst_offset)
+            MCOPY
+            STOP
+        };
+
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap(),
+        )
+        .run();
+    }
+
+    #[test]
+    fn mcopy_gadget_disjoint() {
+        test_ok(0x00.into(), 0x40.into(), 0x20);
+        test_ok(0x05.into(), 0x43.into(), 0x1a);
+    }
+
+    #[test]
+    fn mcopy_gadget_overlap_forward() {
+        test_ok(0x00.into(), 0x10.into(), 0x30);
+    }
+
+    #[test]
+    fn mcopy_gadget_overlap_backward() {
+        test_ok(0x10.into(), 0x00.into(), 0x30);
+    }
+
+    #[test]
+    fn mcopy_gadget_zero_length() {
+        test_ok(0x100.into(), 0x200.into(), 0);
+    }
+
+    #[test]
+    fn mcopy_gadget_zero_length_overflow_offsets() {
+        test_ok(Word::MAX, Word::MAX, 0);
+    }
+
+    #[test]
+    fn mcopy_gadget_expands_from_source() {
+        test_ok(0xa0.into(), 0x00.into(), 0x20);
+    }
+}
@@ -0,0 +1,391 @@
+use bus_mapping::precompile::{PrecompileAuxData, BLAKE2F_INPUT_LEN};
+use eth_types::{Field, ToScalar};
+use gadgets::util::{not, select, Expr};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_U64,
+        step::ExecutionState,
+        util::{
+            common_gadget::RestoreContextGadget,
+            constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+            from_bytes,
+            math_gadget::LtGadget,
+            rlc, CachedRegion, Cell,
+        },
+    },
+    table::CallContextFieldTag,
+    witness::{Block, Call, ExecStep, Transaction},
+};
+
+/// Number of bytes of the `rounds` parameter, the first bytes of the input.
+const N_BYTES_ROUNDS: usize = 4;
+/// Number of bytes of `h`, `m` and `t`, between `rounds` and the final flag.
+const N_BYTES_BODY: usize = 208;
+/// Number of bytes of the output, the new state vector `h`.
+const N_BYTES_OUTPUT: usize = 64;
+
+#[derive(Clone, Debug)]
+pub struct Blake2fGadget<F> {
+    input_bytes_rlc: Cell<F>,
+    output_bytes_rlc: Cell<F>,
+    rounds_bytes: [Cell<F>; N_BYTES_ROUNDS],
+    body_rlc: Cell<F>,
+    is_final: Cell<F>,
+    is_oog: LtGadget<F, N_BYTES_U64>,
+    is_success: Cell<F>,
+    callee_address: Cell<F>,
+    caller_id: Cell<F>,
+    call_data_offset: Cell<F>,
+    call_data_length: Cell<F>,
+    return_data_offset: Cell<F>,
+    return_data_length: Cell<F>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for Blake2fGadget<F> {
+    const EXECUTION_STATE: ExecutionState = ExecutionState::PrecompileBlake2f;
+
+    const NAME: &'static str = "BLAKE2F";
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        // the RLCs are copied from the call_op step, so they must be the first queried cells
+        let input_bytes_rlc = cb.query_cell_phase2();
+        let output_bytes_rlc = cb.query_cell_phase2();
+
+        let [is_success, callee_address, caller_id, call_data_offset, call_data_length, return_data_offset, return_data_length] =
+            [
+                CallContextFieldTag::IsSuccess,
+                CallContextFieldTag::CalleeAddress,
+                CallContextFieldTag::CallerId,
+                CallContextFieldTag::CallDataOffset,
+                CallContextFieldTag::CallDataLength,
+                CallContextFieldTag::ReturnDataOffset,
+                CallContextFieldTag::ReturnDataLength,
+            ]
+            .map(|tag| cb.call_context(None, tag));
+
+        // inputs of any other length, or with a final flag other than 0 or 1, fail in the
+        // ErrorPrecompileFailed state instead.
+        cb.require_equal(
+            "input is rounds (4 bytes) | h (64 bytes) | m (128 bytes) | t (16 bytes) | f (1 byte)",
+            call_data_length.expr(),
+            BLAKE2F_INPUT_LEN.expr(),
+        );
+        let rounds_bytes = cb.query_bytes();
+        let body_rlc = cb.query_cell_phase2();
+        let is_final = cb.query_bool();
+        let (r_pow_208, r) = {
+            let challenges = cb.challenges().keccak_powers_of_randomness::<16>();
+            let r_pow_16 = challenges[15].clone();
+            let r_pow_64 = r_pow_16.clone().square().square();
+            let r_pow_128 = r_pow_64.clone().square();
+            (r_pow_128 * r_pow_64 * r_pow_16, challenges[0].clone())
+        };
+        cb.require_equal(
+            "input bytes (RLC) = [ rounds | h | m | t | f ]",
+            input_bytes_rlc.expr(),
+            (rlc::expr(
+                &rounds_bytes
+                    .iter()
+                    .rev()
+                    .map(|byte| byte.expr())
+                    .collect::<Vec<_>>(),
+                r.clone(),
+            ) * r_pow_208
+                + body_rlc.expr())
+                * r
+                + is_final.expr(),
+        );
+
+        // rounds is big-endian, the gas cost is one per round.
+        let rounds = from_bytes::expr(&rounds_bytes.iter().rev().collect::<Vec<_>>());
+        let is_oog = LtGadget::construct(cb, cb.curr.state.gas_left.expr(), rounds.expr());
+        cb.require_equal(
+            "call succeeds iff there is enough gas for all the rounds",
+            is_success.expr(),
+            not::expr(is_oog.expr()),
+        );
+
+        cb.condition(is_success.expr(), |cb| {
+            cb.blake2f_table_lookup(
+                rounds.expr(),
+                input_bytes_rlc.expr(),
+                output_bytes_rlc.expr(),
+            );
+        });
+
+        let gas_cost = select::expr(
+            is_success.expr(),
+            cb.execution_state().precompile_base_gas_cost().expr() + rounds,
+            cb.curr.state.gas_left.expr(),
+        );
+
+        cb.precompile_info_lookup(
+            cb.execution_state().as_u64().expr(),
+            callee_address.expr(),
+            cb.execution_state().precompile_base_gas_cost().expr(),
+        );
+
+        let restore_context = RestoreContextGadget::construct2(
+            cb,
+            is_success.expr(),
+            gas_cost.expr(),
+            0.expr(),
+            0x00.expr(), // ReturnDataOffset
+            select::expr(is_success.expr(), N_BYTES_OUTPUT.expr(), 0.expr()), // ReturnDataLength
+            0.expr(),
+            0.expr(),
+        );
+
+        Self {
+            input_bytes_rlc,
+            output_bytes_rlc,
+            rounds_bytes,
+            body_rlc,
+            is_final,
+            is_oog,
+            is_success,
+            callee_address,
+            caller_id,
+            call_data_offset,
+            call_data_length,
+            return_data_offset,
+            return_data_length,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        if let Some(PrecompileAuxData::Blake2F(aux_data)) = &step.aux_data {
+            let keccak_rand = region.challenges().keccak_input();
+            let input = &aux_data.input_bytes;
+            self.input_bytes_rlc.assign(
+                region,
+                offset,
+                keccak_rand.map(|r| rlc::value(input.iter().rev(), r)),
+            )?;
+            self.output_bytes_rlc.assign(
+                region,
+                offset,
+                keccak_rand.map(|r| rlc::value(aux_data.output_bytes.iter().rev(), r)),
+            )?;
+            for (cell, byte) in self.rounds_bytes.iter().zip(input.iter()) {
+                cell.assign(region, offset, Value::known(F::from(*byte as u64)))?;
+            }
+            let body = &input[N_BYTES_ROUNDS..N_BYTES_ROUNDS + N_BYTES_BODY];
+            self.body_rlc.assign(
+                region,
+                offset,
+                keccak_rand.map(|r| rlc::value(body.iter().rev(), r)),
+            )?;
+            self.is_final.assign(
+                region,
+                offset,
+                Value::known(F::from(input[BLAKE2F_INPUT_LEN - 1] as u64)),
+            )?;
+            self.is_oog.assign(
+                region,
+                offset,
+                F::from(step.gas_left),
+                F::from(aux_data.rounds as u64),
+            )?;
+        } else {
+            log::error!("unexpected aux_data {:?} for blake2f", step.aux_data);
+            return Err(Error::Synthesis);
+        }
+
+        self.is_success.assign(
+            region,
+            offset,
+            Value::known(F::from(u64::from(call.is_success))),
+        )?;
+        self.callee_address.assign(
+            region,
+            offset,
+            Value::known(call.code_address.unwrap().to_scalar().unwrap()),
+        )?;
+        self.caller_id
+            .assign(region, offset, Value::known(F::from(call.caller_id as u64)))?;
+        self.call_data_offset.assign(
+            region,
+            offset,
+            Value::known(F::from(call.call_data_offset)),
+        )?;
+        self.call_data_length.assign(
+            region,
+            offset,
+            Value::known(F::from(call.call_data_length)),
+        )?;
+        self.return_data_offset.assign(
+            region,
+            offset,
+            Value::known(F::from(call.return_data_offset)),
+        )?;
+        self.return_data_length.assign(
+            region,
+            offset,
+            Value::known(F::from(call.return_data_length)),
+        )?;
+        self.restore_context
+            .assign(region, offset, block, call, step, 7)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bus_mapping::{
+        evm::{OpcodeId, PrecompileCallArgs},
+        precompile::PrecompileCalls,
+    };
+    use eth_types::{bytecode, word, Bytecode, ToWord};
+    use itertools::Itertools;
+    use mock::TestContext;
+
+    use crate::test_util::CircuitTestBuilder;
+
+    /// Places the EIP-152 input of the compression of BLAKE2b-512("abc") in memory, with the
+    /// given rounds and final flag.
+    fn setup_code(rounds: u32, f: u8) -> Bytecode {
+        let rounds =
+            format!("0x{rounds:08x}48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f");
+        let last =
+            format!("0x0000000003000000000000000000000000000000{f:02x}0000000000000000000000");
+        bytecode! {
+            PUSH32(word!(&rounds))
+            PUSH1(0x00)
+            MSTORE
+            PUSH32(word!("0x3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e13"))
+            PUSH1(0x20)
+            MSTORE
+            PUSH32(word!("0x19cde05b61626300000000000000000000000000000000000000000000000000"))
+            PUSH1(0x40)
+            MSTORE
+            PUSH32(word!(&last))
+            PUSH1(0xc0)
+            MSTORE
+        }
+    }
+
+    lazy_static::lazy_static! {
+        static ref TEST_VECTOR: Vec<PrecompileCallArgs> = {
+            vec![
+                PrecompileCallArgs {
+                    name: "12 rounds",
+                    setup_code: setup_code(12, 1),
+                    call_data_offset: 0x00.into(),
+                    call_data_length: 0xd5.into(),
+                    ret_offset: 0x00.into(),
+                    ret_size: 0x40.into(),
+                    address: PrecompileCalls::Blake2F.address().to_word(),
+                    ..Default::default()
+                },
+                PrecompileCallArgs {
+                    name: "0 rounds, not final",
+                    setup_code: setup_code(0, 0),
+                    call_data_offset: 0x00.into(),
+                    call_data_length: 0xd5.into(),
+                    // only return the last 16 bytes of the output
+                    ret_offset: 0x100.into(),
+                    ret_size: 0x10.into(),
+                    address: PrecompileCalls::Blake2F.address().to_word(),
+                    ..Default::default()
+                },
+                PrecompileCallArgs {
+                    name: "out of gas for the rounds",
+                    setup_code: setup_code(0xffff, 1),
+                    call_data_offset: 0x00.into(),
+                    call_data_length: 0xd5.into(),
+                    ret_offset: 0x00.into(),
+                    ret_size: 0x40.into(),
+                    address: PrecompileCalls::Blake2F.address().to_word(),
+                    gas: 0xfffe.into(),
+                    ..Default::default()
+                },
+            ]
+        };
+
+        static ref TEST_VECTOR_FAILED: Vec<PrecompileCallArgs> = {
+            vec![
+                PrecompileCallArgs {
+                    name: "input one byte short",
+                    setup_code: setup_code(12, 1),
+                    call_data_offset: 0x00.into(),
+                    call_data_length: 0xd4.into(),
+                    ret_offset: 0x00.into(),
+                    ret_size: 0x40.into(),
+                    address: PrecompileCalls::Blake2F.address().to_word(),
+                    ..Default::default()
+                },
+                PrecompileCallArgs {
+                    name: "input one byte long",
+                    setup_code: setup_code(12, 1),
+                    call_data_offset: 0x00.into(),
+                    call_data_length: 0xd6.into(),
+                    ret_offset: 0x00.into(),
+                    ret_size: 0x40.into(),
+                    address: PrecompileCalls::Blake2F.address().to_word(),
+                    ..Default::default()
+                },
+                PrecompileCallArgs {
+                    name: "invalid final flag",
+                    setup_code: setup_code(12, 2),
+                    call_data_offset: 0x00.into(),
+                    call_data_length: 0xd5.into(),
+                    ret_offset: 0x00.into(),
+                    ret_size: 0x40.into(),
+                    address: PrecompileCalls::Blake2F.address().to_word(),
+                    ..Default::default()
+                },
+            ]
+        };
+    }
+
+    #[test]
+    fn precompile_blake2f_test() {
+        let call_kinds = vec![
+            OpcodeId::CALL,
+            OpcodeId::STATICCALL,
+            OpcodeId::DELEGATECALL,
+            OpcodeId::CALLCODE,
+        ];
+
+        for (test_vector, &call_kind) in TEST_VECTOR.iter().cartesian_product(&call_kinds) {
+            let bytecode = test_vector.with_call_op(call_kind);
+
+            CircuitTestBuilder::new_from_test_ctx(
+                TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+            )
+            .run();
+        }
+    }
+
+    #[test]
+    fn precompile_blake2f_failed_test() {
+        let call_kinds = vec![
+            OpcodeId::CALL,
+            OpcodeId::STATICCALL,
+            OpcodeId::DELEGATECALL,
+            OpcodeId::CALLCODE,
+        ];
+
+        for (test_vector, &call_kind) in TEST_VECTOR_FAILED.iter().cartesian_product(&call_kinds) {
+            let bytecode = test_vector.with_call_op(call_kind);
+
+            CircuitTestBuilder::new_from_test_ctx(
+                TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+            )
+            .run();
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use std::marker::PhantomData;
+
+use bus_mapping::precompile::PrecompileAuxData;
+use eth_types::{evm_types::GasCost, Field, ToScalar};
+use gadgets::util::{select, Expr};
+use halo2_proofs::{
+    circuit::Value,
+    plonk::{Error, Expression},
+};
+
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_MEMORY_WORD_SIZE, N_BYTES_WORD},
+        step::ExecutionState,
+        util::{
+            common_gadget::RestoreContextGadget, constraint_builder::EVMConstraintBuilder,
+            math_gadget::ConstantDivisionGadget, rlc, CachedRegion, Cell,
+        },
+    },
+    table::CallContextFieldTag,
+    witness::{Block, Call, ExecStep, Transaction},
+};
+
+/// A hash precompile, whose input and digest RLCs are looked up in the table of
+/// its circuit.
+pub(crate) trait HashPrecompile {
+    const EXECUTION_STATE: ExecutionState;
+    const NAME: &'static str;
+    const BASE_GAS_COST: GasCost;
+    const GAS_COST_PER_WORD: GasCost;
+
+    /// Looks the hash up in the table of the precompile circuit
+    fn table_lookup<F: Field>(
+        cb: &mut EVMConstraintBuilder<F>,
+        input_rlc: Expression<F>,
+        input_len: Expression<F>,
+        output_rlc: Expression<F>,
+    );
+
+    /// Returns the input and output bytes of a call, if the aux data is the
+    /// one of this precompile
+    fn input_output_bytes(aux_data: &PrecompileAuxData) -> Option<(&[u8], &[u8])>;
+}
+
+#[derive(Clone, Debug)]
+pub struct PrecompileHashGadget<F, H> {
+    input_bytes_rlc: Cell<F>,
+    output_bytes_rlc: Cell<F>,
+    input_word_size: ConstantDivisionGadget<F, N_BYTES_MEMORY_WORD_SIZE>,
+    is_success: Cell<F>,
+    callee_address: Cell<F>,
+    caller_id: Cell<F>,
+    call_data_offset: Cell<F>,
+    call_data_length: Cell<F>,
+    return_data_offset: Cell<F>,
+    return_data_length: Cell<F>,
+    restore_context: RestoreContextGadget<F>,
+    _marker: PhantomData<H>,
+}
+
+impl<F: Field, H: HashPrecompile> ExecutionGadget<F> for PrecompileHashGadget<F, H> {
+    const EXECUTION_STATE: ExecutionState = H::EXECUTION_STATE;
+
+    const NAME: &'static str = H::NAME;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        // the RLCs are copied from the call_op step, so they must be the first queried cells
+        let input_bytes_rlc = cb.query_cell_phase2();
+        let output_bytes_rlc = cb.query_cell_phase2();
+
+        let [is_success, callee_address, caller_id, call_data_offset, call_data_length, return_data_offset, return_data_length] =
+            [
+                CallContextFieldTag::IsSuccess,
+                CallContextFieldTag::CalleeAddress,
+                CallContextFieldTag::CallerId,
+                CallContextFieldTag::CallDataOffset,
+                CallContextFieldTag::CallDataLength,
+                CallContextFieldTag::ReturnDataOffset,
+                CallContextFieldTag::ReturnDataLength,
+            ]
+            .map(|tag| cb.call_context(None, tag));
+
+        let input_word_size = ConstantDivisionGadget::construct(
+            cb,
+            call_data_length.expr() + (N_BYTES_WORD - 1).expr(),
+            N_BYTES_WORD as u64,
+        );
+
+        cb.condition(is_success.expr(), |cb| {
+            H::table_lookup(
+                cb,
+                input_bytes_rlc.expr(),
+                call_data_length.expr(),
+                output_bytes_rlc.expr(),
+            );
+        });
+
+        let gas_cost = select::expr(
+            is_success.expr(),
+            H::BASE_GAS_COST.expr() + input_word_size.quotient() * H::GAS_COST_PER_WORD.expr(),
+            cb.curr.state.gas_left.expr(),
+        );
+
+        cb.precompile_info_lookup(
+            cb.execution_state().as_u64().expr(),
+            callee_address.expr(),
+            cb.execution_state().precompile_base_gas_cost().expr(),
+        );
+
+        let restore_context = RestoreContextGadget::construct2(
+            cb,
+            is_success.expr(),
+            gas_cost.expr(),
+            0.expr(),
+            0x00.expr(),                                            // ReturnDataOffset
+            select::expr(is_success.expr(), 0x20.expr(), 0.expr()), // ReturnDataLength
+            0.expr(),
+            0.expr(),
+        );
+
+        Self {
+            input_bytes_rlc,
+            output_bytes_rlc,
+            input_word_size,
+            is_success,
+            callee_address,
+            caller_id,
+            call_data_offset,
+            call_data_length,
+            return_data_offset,
+            return_data_length,
+            restore_context,
+            _marker: PhantomData,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        if let Some((input_bytes, output_bytes)) =
+            step.aux_data.as_ref().and_then(H::input_output_bytes)
+        {
+            let keccak_rand = region.challenges().keccak_input();
+            self.input_bytes_rlc.assign(
+                region,
+                offset,
+                keccak_rand.map(|r| rlc::value(input_bytes.iter().rev(), r)),
+            )?;
+            self.output_bytes_rlc.assign(
+                region,
+                offset,
+                keccak_rand.map(|r| rlc::value(output_bytes.iter().rev(), r)),
+            )?;
+        } else {
+            log::error!("unexpected aux_data {:?} for {}", step.aux_data, H::NAME);
+            return Err(Error::Synthesis);
+        }
+
+        self.input_word_size.assign(
+            region,
+            offset,
+            (call.call_data_length + (N_BYTES_WORD as u64) - 1).into(),
+        )?;
+        self.is_success.assign(
+            region,
+            offset,
+            Value::known(F::from(u64::from(call.is_success))),
+        )?;
+        self.callee_address.assign(
+            region,
+            offset,
+            Value::known(call.code_address.unwrap().to_scalar().unwrap()),
+        )?;
+        self.caller_id
+            .assign(region, offset, Value::known(F::from(call.caller_id as u64)))?;
+        self.call_data_offset.assign(
+            region,
+            offset,
+            Value::known(F::from(call.call_data_offset)),
+        )?;
+        self.call_data_length.assign(
+            region,
+            offset,
+            Value::known(F::from(call.call_data_length)),
+        )?;
+        self.return_data_offset.assign(
+            region,
+            offset,
+            Value::known(F::from(call.return_data_offset)),
+        )?;
+        self.return_data_length.assign(
+            region,
+            offset,
+            Value::known(F::from(call.return_data_length)),
+        )?;
+        self.restore_context
+            .assign(region, offset, block, call, step, 7)
+    }
+}
@@ -0,0 +1,125 @@
+use bus_mapping::precompile::{PrecompileAuxData, Sha256AuxData};
+use eth_types::{evm_types::GasCost, Field};
+use halo2_proofs::plonk::Expression;
+
+use super::hash::{HashPrecompile, PrecompileHashGadget};
+use crate::evm_circuit::{step::ExecutionState, util::constraint_builder::EVMConstraintBuilder};
+
+/// The SHA2-256 precompile
+#[derive(Clone, Debug)]
+pub struct Sha256;
+
+impl HashPrecompile for Sha256 {
+    const EXECUTION_STATE: ExecutionState = ExecutionState::PrecompileSha256;
+    const NAME: &'static str = "SHA256";
+    const BASE_GAS_COST: GasCost = GasCost::PRECOMPILE_SHA256_BASE;
+    const GAS_COST_PER_WORD: GasCost = GasCost::PRECOMPILE_SHA256_PER_WORD;
+
+    fn table_lookup<F: Field>(
+        cb: &mut EVMConstraintBuilder<F>,
+        input_rlc: Expression<F>,
+        input_len: Expression<F>,
+        output_rlc: Expression<F>,
+    ) {
+        cb.sha256_table_lookup(input_rlc, input_len, output_rlc);
+    }
+
+    fn input_output_bytes(aux_data: &PrecompileAuxData) -> Option<(&[u8], &[u8])> {
+        match aux_data {
+            PrecompileAuxData::Sha256(Sha256AuxData {
+                input_bytes,
+                output_bytes,
+            }) => Some((input_bytes.as_slice(), output_bytes.as_slice())),
+            _ => None,
+        }
+    }
+}
+
+/// Gadget of the SHA2-256 precompile
+pub type Sha256Gadget<F> = PrecompileHashGadget<F, Sha256>;
+
+#[cfg(test)]
+mod test {
+    use bus_mapping::{
+        evm::{OpcodeId, PrecompileCallArgs},
+        precompile::PrecompileCalls,
+    };
+    use eth_types::{bytecode, word, ToWord};
+    use itertools::Itertools;
+    use mock::TestContext;
+
+    use crate::test_util::CircuitTestBuilder;
+
+    lazy_static::lazy_static! {
+        static ref TEST_VECTOR: Vec<PrecompileCallArgs> = {
+            vec![
+                PrecompileCallArgs {
+                    name: "empty input",
+                    setup_code: bytecode! {},
+                    call_data_offset: 0x00.into(),
+                    call_data_length: 0x00.into(),
+                    ret_offset: 0x00.into(),
+                    ret_size: 0x20.into(),
+                    address: PrecompileCalls::Sha256.address().to_word(),
+                    ..Default::default()
+                },
+                PrecompileCallArgs {
+                    name: "single-byte input",
+                    setup_code: bytecode! {
+                        // place params in memory
+                        PUSH1(0xff)
+                        PUSH1(0x00)
+                        MSTORE
+                    },
+                    call_data_offset: 0x1f.into(),
+                    call_data_length: 0x01.into(),
+                    ret_offset: 0x20.into(),
+                    ret_size: 0x20.into(),
+                    address: PrecompileCalls::Sha256.address().to_word(),
+                    ..Default::default()
+                },
+                PrecompileCallArgs {
+                    name: "multi-block input (more than 64 bytes)",
+                    setup_code: bytecode! {
+                        // place params in memory
+                        PUSH30(word!("0x0123456789abcdef0f1e2d3c4b5a6978"))
+                        PUSH1(0x00)
+                        MSTORE
+                        PUSH30(word!("0xaabbccdd001122331039abcdefefef84"))
+                        PUSH1(0x20)
+                        MSTORE
+                        PUSH30(word!("0x00112233445566778899aabbccddeeff"))
+                        PUSH1(0x40)
+                        MSTORE
+                    },
+                    call_data_offset: 0x00.into(),
+                    call_data_length: 0x5a.into(),
+                    // only return the first 16 bytes of the digest
+                    ret_offset: 0x80.into(),
+                    ret_size: 0x10.into(),
+                    address: PrecompileCalls::Sha256.address().to_word(),
+                    ..Default::default()
+                },
+            ]
+        };
+    }
+
+    #[test]
+    fn precompile_sha256_test() {
+        let call_kinds = vec![
+            OpcodeId::CALL,
+            OpcodeId::STATICCALL,
+            OpcodeId::DELEGATECALL,
+            OpcodeId::CALLCODE,
+        ];
+
+        for (test_vector, &call_kind) in TEST_VECTOR.iter().cartesian_product(&call_kinds) {
+            let bytecode = test_vector.with_call_op(call_kind);
+
+            CircuitTestBuilder::new_from_test_ctx(
+                TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+            )
+            .run();
+        }
+    }
+}
@@ -0,0 +1,475 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, STACK_CAPACITY},
+        step::ExecutionState,
+        util::{
+            common_gadget::{RestoreContextGadget, TransferGadget, TransferGadgetInfo},
+            constraint_builder::{
+                ConstrainBuilderCommon, EVMConstraintBuilder, ReversionInfo, StepStateTransition,
+                Transition::{Delta, To},
+            },
+            from_bytes,
+            math_gadget::{IsEqualGadget, IsZeroGadget, RangeCheckGadget},
+            not, CachedRegion, Cell, StepRws, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::{AccountFieldTag, CallContextFieldTag},
+    util::Expr,
+};
+use eth_types::{
+    evm_types::{GasCost, OpcodeId},
+    Field, ToAddress, ToLittleEndian, ToScalar,
+};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Gadget for SELFDESTRUCT, with the EIP-6780 semantics: the whole balance is
+/// sent to the beneficiary, but the account is only destructed when it has
+/// been created in the same transaction. No gas is refunded since EIP-3529.
+#[derive(Clone, Debug)]
+pub(crate) struct SelfDestructGadget<F> {
+    opcode: Cell<F>,
+    tx_id: Cell<F>,
+    is_static: Cell<F>,
+    reversion_info: ReversionInfo<F>,
+    callee_address: Cell<F>,
+    beneficiary: Word<F>,
+    is_warm: Cell<F>,
+    is_created: Cell<F>,
+    balance: Word<F>,
+    beneficiary_code_hash: Cell<F>,
+    beneficiary_not_exists: IsZeroGadget<F>,
+    is_self_beneficiary: IsEqualGadget<F>,
+    transfer: TransferGadget<F>,
+    nonce: Cell<F>,
+    code_hash: Cell<F>,
+    sufficient_gas_left: RangeCheckGadget<F, N_BYTES_GAS>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for SelfDestructGadget<F> {
+    const NAME: &'static str = "SELFDESTRUCT";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::SELFDESTRUCT;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+        // We do the responsible opcode check explicitly here because we're not using
+        // the `SameContextGadget` for `SELFDESTRUCT`.
+        cb.require_equal(
+            "Opcode should be SELFDESTRUCT",
+            opcode.expr(),
+            OpcodeId::SELFDESTRUCT.expr(),
+        );
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let is_static = cb.call_context(None, CallContextFieldTag::IsStatic);
+        cb.require_zero("is_static is false", is_static.expr());
+        let mut reversion_info = cb.reversion_info_read(None);
+        let callee_address = cb.call_context(None, CallContextFieldTag::CalleeAddress);
+
+        let beneficiary = cb.query_word_rlc();
+        let beneficiary_address = from_bytes::expr(&beneficiary.cells[..N_BYTES_ACCOUNT_ADDRESS]);
+        cb.stack_pop(beneficiary.expr());
+
+        let is_warm = cb.query_bool();
+        cb.account_access_list_write(
+            tx_id.expr(),
+            beneficiary_address.expr(),
+            1.expr(),
+            is_warm.expr(),
+            Some(&mut reversion_info),
+        );
+
+        let is_created = cb.query_bool();
+        cb.account_created_read(tx_id.expr(), callee_address.expr(), is_created.expr());
+
+        let balance = cb.query_word_rlc();
+        cb.account_read(
+            callee_address.expr(),
+            AccountFieldTag::Balance,
+            balance.expr(),
+        );
+
+        // For non-existing accounts the code_hash must be 0 in the rw_table.
+        let beneficiary_code_hash = cb.query_cell_phase2();
+        cb.account_read(
+            beneficiary_address.expr(),
+            AccountFieldTag::CodeHash,
+            beneficiary_code_hash.expr(),
+        );
+        let beneficiary_not_exists = IsZeroGadget::construct(cb, beneficiary_code_hash.expr());
+
+        let is_self_beneficiary =
+            IsEqualGadget::construct(cb, callee_address.expr(), beneficiary_address.expr());
+
+        let transfer = TransferGadget::construct(
+            cb,
+            callee_address.expr(),
+            beneficiary_address.expr(),
+            not::expr(beneficiary_not_exists.expr()),
+            0.expr(),
+            beneficiary_code_hash.expr(),
+            balance.clone(),
+            &mut reversion_info,
+        );
+
+        // The account is only destructed when it has been created in the same
+        // tx. Its balance is non-zero here only when it is its own beneficiary,
+        // in which case the ether is burnt.
+        let nonce = cb.query_cell();
+        let code_hash = cb.query_cell_phase2();
+        cb.condition(is_created.expr(), |cb| {
+            for (field_tag, value_prev) in [
+                (
+                    AccountFieldTag::Balance,
+                    is_self_beneficiary.expr() * balance.expr(),
+                ),
+                (AccountFieldTag::Nonce, nonce.expr()),
+                (AccountFieldTag::CodeHash, code_hash.expr()),
+            ] {
+                cb.account_write(
+                    callee_address.expr(),
+                    field_tag,
+                    0.expr(),
+                    value_prev,
+                    Some(&mut reversion_info),
+                );
+            }
+        });
+
+        let gas_cost = GasCost::SELFDESTRUCT.expr()
+            + not::expr(is_warm.expr()) * GasCost::COLD_ACCOUNT_ACCESS.expr()
+            + beneficiary_not_exists.expr()
+                * not::expr(transfer.value_is_zero())
+                * GasCost::NEW_ACCOUNT.expr();
+        let sufficient_gas_left =
+            RangeCheckGadget::construct(cb, cb.curr.state.gas_left.expr() - gas_cost.expr());
+
+        // Call ends with SELFDESTRUCT must be successful
+        cb.call_context_lookup(false.expr(), None, CallContextFieldTag::IsSuccess, 1.expr());
+
+        // When it's a root call
+        cb.condition(cb.curr.state.is_root.expr(), |cb| {
+            cb.require_next_state(ExecutionState::EndTx);
+            cb.require_step_state_transition(StepStateTransition {
+                program_counter: To(0.expr()),
+                stack_pointer: To(STACK_CAPACITY.expr()),
+                rw_counter: Delta(cb.rw_counter_offset()),
+                gas_left: Delta(-gas_cost.expr()),
+                reversible_write_counter: To(0.expr()),
+                memory_word_size: To(0.expr()),
+                end_tx: To(1.expr()),
+                ..StepStateTransition::default()
+            });
+        });
+
+        // When it's an internal call
+        let restore_context = cb.condition(not::expr(cb.curr.state.is_root.expr()), |cb| {
+            RestoreContextGadget::construct2(
+                cb,
+                true.expr(),
+                gas_cost,
+                0.expr(),
+                0.expr(),
+                0.expr(),
+                0.expr(),
+                // access list write, transfer and account destruction
+                1.expr() + transfer.reversible_w_delta() + is_created.expr() * 3.expr(),
+            )
+        });
+
+        Self {
+            opcode,
+            tx_id,
+            is_static,
+            reversion_info,
+            callee_address,
+            beneficiary,
+            is_warm,
+            is_created,
+            balance,
+            beneficiary_code_hash,
+            beneficiary_not_exists,
+            is_self_beneficiary,
+            transfer,
+            nonce,
+            code_hash,
+            sufficient_gas_left,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
+        self.is_static
+            .assign(region, offset, Value::known(F::from(call.is_static as u64)))?;
+        self.reversion_info.assign(
+            region,
+            offset,
+            call.rw_counter_end_of_reversion,
+            call.is_persistent,
+        )?;
+        let callee_address = call.address.to_scalar().unwrap();
+        self.callee_address
+            .assign(region, offset, Value::known(callee_address))?;
+
+        let mut rws = StepRws::new(block, step);
+        rws.offset_add(5);
+
+        let beneficiary = rws.next().stack_value();
+        self.beneficiary
+            .assign(region, offset, Some(beneficiary.to_le_bytes()))?;
+        let beneficiary_address = beneficiary.to_address().to_scalar().unwrap();
+        self.is_self_beneficiary
+            .assign(region, offset, callee_address, beneficiary_address)?;
+
+        let (_, is_warm) = rws.next().tx_access_list_value_pair();
+        self.is_warm
+            .assign(region, offset, Value::known(F::from(is_warm)))?;
+
+        let (is_created, _) = rws.next().tx_created_account_value_pair();
+        self.is_created
+            .assign(region, offset, Value::known(F::from(is_created)))?;
+
+        let balance = rws.next().account_value_pair().0;
+        self.balance
+            .assign(region, offset, Some(balance.to_le_bytes()))?;
+
+        let beneficiary_code_hash = rws.next().account_value_pair().0;
+        self.beneficiary_code_hash.assign(
+            region,
+            offset,
+            region.code_hash(beneficiary_code_hash),
+        )?;
+        self.beneficiary_not_exists.assign_value(
+            region,
+            offset,
+            region.code_hash(beneficiary_code_hash),
+        )?;
+
+        self.transfer.assign_from_rws(
+            region,
+            offset,
+            !beneficiary_code_hash.is_zero(),
+            false,
+            balance,
+            &mut rws,
+        )?;
+
+        if is_created {
+            rws.next(); // balance
+            let nonce = rws.next().account_nonce_pair().1;
+            self.nonce
+                .assign(region, offset, Value::known(nonce.to_scalar().unwrap()))?;
+            let code_hash = rws.next().account_codehash_pair().1;
+            self.code_hash
+                .assign(region, offset, region.code_hash(code_hash))?;
+        }
+
+        let gas_cost = step.gas_cost;
+        self.sufficient_gas_left
+            .assign(region, offset, F::from(step.gas_left - gas_cost))?;
+
+        if !call.is_root {
+            // 10 reads and writes before the transfer, and is_success after
+            // the account destruction.
+            let transfer_rws = if balance.is_zero() {
+                0
+            } else if beneficiary_code_hash.is_zero() {
+                4
+            } else {
+                2
+            };
+            let rw_offset = 11 + transfer_rws + if is_created { 3 } else { 0 };
+            self.restore_context
+                .assign(region, offset, block, call, step, rw_offset)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::CircuitTestBuilder;
+    use eth_types::{address, bytecode, evm_types::OpcodeId, Address, Bytecode, ToWord, Word};
+    use mock::{eth, TestContext};
+
+    const CONTRACT: Address = Address::repeat_byte(0xcc);
+    const EXISTING: Address = Address::repeat_byte(0xee);
+    const NON_EXISTING: Address = Address::repeat_byte(0x0e);
+
+    fn selfdestruct_bytecode(beneficiary: Option<Address>) -> Bytecode {
+        let mut code = match beneficiary {
+            Some(beneficiary) => bytecode! { PUSH20(beneficiary.to_word()) },
+            // the contract is its own beneficiary
+            None => bytecode! { ADDRESS },
+        };
+        code.write_op(OpcodeId::SELFDESTRUCT);
+        code
+    }
+
+    // CREATE a contract with `init_code` and, when `is_persistent` is false,
+    // revert everything afterwards.
+    fn creator_bytecode(init_code: Bytecode, is_persistent: bool) -> Bytecode {
+        let init_bytes = init_code.code();
+        let mut code = bytecode! {
+            PUSH32(Word::from_big_endian(&init_bytes))
+            PUSH1(0)
+            MSTORE
+            PUSH1(init_bytes.len()) // length
+            PUSH1(32 - init_bytes.len()) // offset
+            PUSH2(0x100) // value
+            CREATE
+        };
+        if is_persistent {
+            code.write_op(OpcodeId::STOP);
+        } else {
+            code.append(&bytecode! {
+                PUSH1(0)
+                PUSH1(0)
+                REVERT
+            });
+        }
+        code
+    }
+
+    fn test_root_ok(code: Bytecode) {
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000123"))
+                    .balance(eth(10));
+                accs[1].address(CONTRACT).balance(eth(1)).code(code);
+                accs[2].address(EXISTING).balance(eth(1));
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[0].address).to(accs[1].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    fn test_internal_ok(code: Bytecode, is_persistent: bool) {
+        let mut caller_code = bytecode! {
+            PUSH1(0) // retLength
+            PUSH1(0) // retOffset
+            PUSH1(0) // argsLength
+            PUSH1(0) // argsOffset
+            PUSH1(0) // value
+            PUSH20(CONTRACT.to_word())
+            GAS
+            CALL
+        };
+        if is_persistent {
+            caller_code.write_op(OpcodeId::STOP);
+        } else {
+            caller_code.append(&bytecode! {
+                PUSH1(0)
+                PUSH1(0)
+                REVERT
+            });
+        }
+        let ctx = TestContext::<4, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000123"))
+                    .balance(eth(10));
+                accs[1]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .balance(eth(1))
+                    .code(caller_code);
+                accs[2].address(CONTRACT).balance(eth(1)).code(code);
+                accs[3].address(EXISTING).balance(eth(1));
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[0].address).to(accs[1].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    fn test_deploy_ok(init_code: Bytecode) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000123"))
+                    .balance(eth(10));
+                accs[1].address(EXISTING).balance(eth(1));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .value(eth(1))
+                    .input(init_code.into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    #[test]
+    fn selfdestruct_gadget_root() {
+        test_root_ok(selfdestruct_bytecode(Some(EXISTING)));
+        test_root_ok(selfdestruct_bytecode(Some(NON_EXISTING)));
+        test_root_ok(selfdestruct_bytecode(None));
+    }
+
+    #[test]
+    fn selfdestruct_gadget_internal() {
+        for is_persistent in [true, false] {
+            test_internal_ok(selfdestruct_bytecode(Some(EXISTING)), is_persistent);
+            test_internal_ok(selfdestruct_bytecode(Some(NON_EXISTING)), is_persistent);
+            test_internal_ok(selfdestruct_bytecode(None), is_persistent);
+        }
+    }
+
+    #[test]
+    fn selfdestruct_gadget_created_in_tx_root() {
+        test_deploy_ok(selfdestruct_bytecode(Some(EXISTING)));
+        test_deploy_ok(selfdestruct_bytecode(Some(NON_EXISTING)));
+        test_deploy_ok(selfdestruct_bytecode(None));
+    }
+
+    #[test]
+    fn selfdestruct_gadget_created_in_tx_internal() {
+        for is_persistent in [true, false] {
+            test_root_ok(creator_bytecode(
+                selfdestruct_bytecode(Some(EXISTING)),
+                is_persistent,
+            ));
+            test_root_ok(creator_bytecode(
+                selfdestruct_bytecode(Some(NON_EXISTING)),
+                is_persistent,
+            ));
+            test_root_ok(creator_bytecode(selfdestruct_bytecode(None), is_persistent));
+        }
+    }
+}
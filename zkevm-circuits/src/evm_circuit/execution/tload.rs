@@ -0,0 +1,174 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{EVMConstraintBuilder, StepStateTransition, Transition::Delta},
+            CachedRegion, Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::CallContextFieldTag,
+    util::Expr,
+};
+use eth_types::{evm_types::OpcodeId, Field, ToScalar};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct TloadGadget<F> {
+    same_context: SameContextGadget<F>,
+    tx_id: Cell<F>,
+    callee_address: Cell<F>,
+    phase2_key: Cell<F>,
+    phase2_value: Cell<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for TloadGadget<F> {
+    const NAME: &'static str = "TLOAD";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::TLOAD;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let callee_address = cb.call_context(None, CallContextFieldTag::CalleeAddress);
+
+        let phase2_key = cb.query_cell_phase2();
+        // Pop the key from the stack
+        cb.stack_pop(phase2_key.expr());
+
+        let phase2_value = cb.query_cell_phase2();
+        cb.transient_storage_read(
+            tx_id.expr(),
+            callee_address.expr(),
+            phase2_key.expr(),
+            phase2_value.expr(),
+        );
+
+        cb.stack_push(phase2_value.expr());
+
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(5.expr()),
+            program_counter: Delta(1.expr()),
+            gas_left: Delta(-OpcodeId::TLOAD.constant_gas_cost().expr()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            tx_id,
+            callee_address,
+            phase2_key,
+            phase2_value,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
+        self.callee_address.assign(
+            region,
+            offset,
+            Value::known(
+                call.callee_address
+                    .to_scalar()
+                    .expect("unexpected Address -> Scalar conversion failure"),
+            ),
+        )?;
+
+        let [key, value] =
+            [step.rw_indices[2], step.rw_indices[4]].map(|idx| block.rws[idx].stack_value());
+        self.phase2_key
+            .assign(region, offset, region.word_rlc(key))?;
+        self.phase2_value
+            .assign(region, offset, region.word_rlc(value))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::CircuitTestBuilder;
+    use eth_types::{bytecode, evm_types::OpcodeId, GethExecTrace, Word};
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
+
+    /// Values pushed by the TLOADs of a transaction, in execution order.
+    fn tload_results(trace: &GethExecTrace) -> Vec<Word> {
+        trace
+            .struct_logs
+            .windows(2)
+            .filter(|steps| steps[0].op == OpcodeId::TLOAD)
+            .map(|steps| steps[1].stack.last().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn tload_gadget_after_tstore() {
+        let key = Word::from(0x030201);
+        let value = Word::from(0x060504);
+        let bytecode = bytecode! {
+            // unset slot
+            .op_tload(key)
+            POP
+            .op_tstore(key, value)
+            .op_tload(key)
+            STOP
+        };
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode),
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[1].address);
+            },
+            |block, _txs| block,
+        )
+        .unwrap();
+
+        assert_eq!(
+            tload_results(&ctx.geth_traces[0]),
+            vec![Word::zero(), value]
+        );
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    #[test]
+    fn tload_gadget_isolated_across_txs() {
+        let key = Word::from(0x030201);
+        // The second tx must not see the value stored by the first one.
+        let bytecode = bytecode! {
+            .op_tload(key)
+            POP
+            .op_tstore(key, 0x060504)
+            STOP
+        };
+        let ctx = TestContext::<2, 2>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode),
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[1].address);
+                txs[1].to(accs[0].address).from(accs[1].address);
+            },
+            |block, _txs| block,
+        )
+        .unwrap();
+
+        for trace in ctx.geth_traces.iter() {
+            assert_eq!(tload_results(trace), vec![Word::zero()]);
+        }
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+}
@@ -0,0 +1,224 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{
+                EVMConstraintBuilder, ReversionInfo, StepStateTransition, Transition::Delta,
+            },
+            CachedRegion, Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::CallContextFieldTag,
+    util::Expr,
+};
+use eth_types::{evm_types::OpcodeId, Field, ToScalar};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct TstoreGadget<F> {
+    same_context: SameContextGadget<F>,
+    tx_id: Cell<F>,
+    is_static: Cell<F>,
+    reversion_info: ReversionInfo<F>,
+    callee_address: Cell<F>,
+    phase2_key: Cell<F>,
+    phase2_value: Cell<F>,
+    phase2_value_prev: Cell<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for TstoreGadget<F> {
+    const NAME: &'static str = "TSTORE";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::TSTORE;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+
+        // constrain not in static call
+        let is_static = cb.call_context(None, CallContextFieldTag::IsStatic);
+        cb.require_zero("is_static is false", is_static.expr());
+
+        let mut reversion_info = cb.reversion_info_read(None);
+        let callee_address = cb.call_context(None, CallContextFieldTag::CalleeAddress);
+
+        let phase2_key = cb.query_cell_phase2();
+        // Pop the key from the stack
+        cb.stack_pop(phase2_key.expr());
+
+        let phase2_value = cb.query_cell_phase2();
+        // Pop the value from the stack
+        cb.stack_pop(phase2_value.expr());
+
+        let phase2_value_prev = cb.query_cell_phase2();
+        cb.transient_storage_write(
+            tx_id.expr(),
+            callee_address.expr(),
+            phase2_key.expr(),
+            phase2_value.expr(),
+            phase2_value_prev.expr(),
+            Some(&mut reversion_info),
+        );
+
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(8.expr()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(2.expr()),
+            reversible_write_counter: Delta(1.expr()),
+            gas_left: Delta(-OpcodeId::TSTORE.constant_gas_cost().expr()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            tx_id,
+            is_static,
+            reversion_info,
+            callee_address,
+            phase2_key,
+            phase2_value,
+            phase2_value_prev,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
+        self.is_static
+            .assign(region, offset, Value::known(F::from(call.is_static as u64)))?;
+        self.reversion_info.assign(
+            region,
+            offset,
+            call.rw_counter_end_of_reversion,
+            call.is_persistent,
+        )?;
+        self.callee_address.assign(
+            region,
+            offset,
+            Value::known(
+                call.callee_address
+                    .to_scalar()
+                    .expect("unexpected Address -> Scalar conversion failure"),
+            ),
+        )?;
+
+        let [key, value] =
+            [step.rw_indices[5], step.rw_indices[6]].map(|idx| block.rws[idx].stack_value());
+        self.phase2_key
+            .assign(region, offset, region.word_rlc(key))?;
+        self.phase2_value
+            .assign(region, offset, region.word_rlc(value))?;
+
+        let (_, value_prev) = block.rws[step.rw_indices[7]].transient_storage_value_pair();
+        self.phase2_value_prev
+            .assign(region, offset, region.word_rlc(value_prev))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::CircuitTestBuilder;
+    use eth_types::{bytecode, bytecode::Bytecode, evm_types::OpcodeId, Word};
+    use mock::{TestContext, MOCK_ACCOUNTS};
+
+    const KEY: u64 = 0x030201;
+
+    fn test_ctx(caller_code: Bytecode, callee_code: Bytecode) -> TestContext<3, 1> {
+        TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .balance(Word::from(10u64.pow(19)))
+                    .code(caller_code);
+                accs[1]
+                    .address(MOCK_ACCOUNTS[1])
+                    .balance(Word::from(10u64.pow(19)))
+                    .code(callee_code);
+                accs[2]
+                    .address(MOCK_ACCOUNTS[2])
+                    .balance(Word::from(10u64.pow(19)));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _txs| block,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn tstore_gadget_reverted_by_failed_sub_call() {
+        // The delegated call writes to the caller's transient storage and
+        // reverts, which restores the value stored before the call.
+        let caller_code = bytecode! {
+            .op_tstore(KEY, 0x060504)
+            .op_delegatecall(0xffff, MOCK_ACCOUNTS[1], 0, 0, 0, 0)
+            POP
+            .op_tload(KEY)
+            STOP
+        };
+        let callee_code = bytecode! {
+            .op_tstore(KEY, 0x060505)
+            .op_revert(0, 0)
+        };
+        let ctx = test_ctx(caller_code, callee_code);
+
+        let trace = &ctx.geth_traces[0];
+        let tload = trace
+            .struct_logs
+            .iter()
+            .position(|step| step.op == OpcodeId::TLOAD)
+            .expect("TLOAD executed");
+        assert_eq!(
+            trace.struct_logs[tload + 1].stack.last().unwrap(),
+            Word::from(0x060504)
+        );
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    #[test]
+    fn tstore_gadget_in_static_call() {
+        // TSTORE in a static context fails with ErrorWriteProtection.
+        let caller_code = bytecode! {
+            .op_staticcall(0xffff, MOCK_ACCOUNTS[1], 0, 0, 0, 0)
+            STOP
+        };
+        let callee_code = bytecode! {
+            .op_tstore(KEY, 0x060504)
+            STOP
+        };
+        let ctx = test_ctx(caller_code, callee_code);
+
+        let trace = &ctx.geth_traces[0];
+        let tstore = trace
+            .struct_logs
+            .iter()
+            .find(|step| step.op == OpcodeId::TSTORE)
+            .expect("TSTORE executed");
+        assert!(tstore.error.is_some());
+        // STATICCALL returns failure
+        assert_eq!(
+            trace.struct_logs.last().unwrap().stack.last().unwrap(),
+            Word::zero()
+        );
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+}
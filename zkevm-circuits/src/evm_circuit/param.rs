@@ -7,7 +7,7 @@ use halo2_proofs::{
 use std::collections::HashMap;
 
 // Step dimension
-pub(crate) const STEP_WIDTH: usize = 140;
+pub(crate) const STEP_WIDTH: usize = 143;
 /// Step height
 pub const MAX_STEP_HEIGHT: usize = 21;
 /// The height of the state of a step, used by gates that connect two
@@ -41,7 +41,10 @@ pub(crate) const EVM_LOOKUP_COLS: usize = FIXED_TABLE_LOOKUPS
     + SIG_TABLE_LOOKUPS
     + MODEXP_TABLE_LOOKUPS
     + ECC_TABLE_LOOKUPS
-    + POW_OF_RAND_TABLE_LOOKUPS;
+    + POW_OF_RAND_TABLE_LOOKUPS
+    + SHA256_TABLE_LOOKUPS
+    + RIPEMD160_TABLE_LOOKUPS
+    + BLAKE2F_TABLE_LOOKUPS;
 
 /// Lookups done per row.
 pub(crate) const LOOKUP_CONFIG: &[(Table, usize)] = &[
@@ -57,6 +60,9 @@ pub(crate) const LOOKUP_CONFIG: &[(Table, usize)] = &[
     (Table::ModExp, MODEXP_TABLE_LOOKUPS),
     (Table::Ecc, ECC_TABLE_LOOKUPS),
     (Table::PowOfRand, POW_OF_RAND_TABLE_LOOKUPS),
+    (Table::Sha256, SHA256_TABLE_LOOKUPS),
+    (Table::Ripemd160, RIPEMD160_TABLE_LOOKUPS),
+    (Table::Blake2f, BLAKE2F_TABLE_LOOKUPS),
 ];
 
 /// Fixed Table lookups done in EVMCircuit
@@ -94,6 +100,15 @@ pub const ECC_TABLE_LOOKUPS: usize = 1;
 /// Power of Randomness lookups done from EVM Circuit.
 pub const POW_OF_RAND_TABLE_LOOKUPS: usize = 1;
 
+/// Sha256 Table lookups done in EVMCircuit
+pub const SHA256_TABLE_LOOKUPS: usize = 1;
+
+/// Ripemd160 Table lookups done in EVMCircuit
+pub const RIPEMD160_TABLE_LOOKUPS: usize = 1;
+
+/// Blake2f Table lookups done in EVMCircuit
+pub const BLAKE2F_TABLE_LOOKUPS: usize = 1;
+
 /// Maximum number of bytes that an integer can fit in field without wrapping
 /// around.
 pub(crate) const MAX_N_BYTES_INTEGER: usize = 31;
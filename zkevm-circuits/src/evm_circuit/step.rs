@@ -81,12 +81,17 @@ pub enum ExecutionState {
     BLOCKCTXU64,  // TIMESTAMP, NUMBER, GASLIMIT
     BLOCKCTXU160, // COINBASE
     BLOCKCTXU256, // DIFFICULTY, BASEFEE
+    BLOBHASH,
+    BLOBBASEFEE,
     CHAINID,
     SELFBALANCE,
     POP,
     MEMORY, // MLOAD, MSTORE, MSTORE8
+    MCOPY,
     SLOAD,
     SSTORE,
+    TLOAD,
+    TSTORE,
     JUMP,
     JUMPI,
     PC,
@@ -275,14 +280,19 @@ impl ExecutionState {
                     vec![OpcodeId::DIFFICULTY, OpcodeId::BASEFEE]
                 }
             }
+            Self::BLOBHASH => vec![OpcodeId::BLOBHASH],
+            Self::BLOBBASEFEE => vec![OpcodeId::BLOBBASEFEE],
             Self::CHAINID => vec![OpcodeId::CHAINID],
             Self::SELFBALANCE => vec![OpcodeId::SELFBALANCE],
             Self::POP => vec![OpcodeId::POP],
             Self::MEMORY => {
                 vec![OpcodeId::MLOAD, OpcodeId::MSTORE, OpcodeId::MSTORE8]
             }
+            Self::MCOPY => vec![OpcodeId::MCOPY],
             Self::SLOAD => vec![OpcodeId::SLOAD],
             Self::SSTORE => vec![OpcodeId::SSTORE],
+            Self::TLOAD => vec![OpcodeId::TLOAD],
+            Self::TSTORE => vec![OpcodeId::TSTORE],
             Self::JUMP => vec![OpcodeId::JUMP],
             Self::JUMPI => vec![OpcodeId::JUMPI],
             Self::PC => vec![OpcodeId::PC],
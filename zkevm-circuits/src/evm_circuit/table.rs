@@ -159,6 +159,9 @@ pub(crate) enum Table {
     ModExp,
     Ecc,
     PowOfRand,
+    Sha256,
+    Ripemd160,
+    Blake2f,
 }
 
 #[derive(Clone, Debug)]
@@ -338,6 +341,33 @@ pub(crate) enum Lookup<F> {
         exponent: Expression<F>,
         pow_of_rand: Expression<F>,
     },
+    /// Lookup to sha256 table.
+    Sha256Table {
+        /// RLC of the input bytes.
+        input_rlc: Expression<F>,
+        /// Length of input that is being hashed.
+        input_len: Expression<F>,
+        /// RLC of the digest bytes.
+        output_rlc: Expression<F>,
+    },
+    /// Lookup to ripemd160 table.
+    Ripemd160Table {
+        /// RLC of the input bytes.
+        input_rlc: Expression<F>,
+        /// Length of input that is being hashed.
+        input_len: Expression<F>,
+        /// RLC of the digest bytes.
+        output_rlc: Expression<F>,
+    },
+    /// Lookup to blake2f table.
+    Blake2fTable {
+        /// Number of rounds of the compression.
+        rounds: Expression<F>,
+        /// RLC of the 213 input bytes.
+        input_rlc: Expression<F>,
+        /// RLC of the 64 output bytes.
+        output_rlc: Expression<F>,
+    },
     /// Conditional lookup enabled by the first element.
     Conditional(Expression<F>, Box<Lookup<F>>),
 }
@@ -361,6 +391,9 @@ impl<F: Field> Lookup<F> {
             Self::ModExpTable { .. } => Table::ModExp,
             Self::EccTable { .. } => Table::Ecc,
             Self::PowOfRandTable { .. } => Table::PowOfRand,
+            Self::Sha256Table { .. } => Table::Sha256,
+            Self::Ripemd160Table { .. } => Table::Ripemd160,
+            Self::Blake2fTable { .. } => Table::Blake2f,
             Self::Conditional(_, lookup) => lookup.table(),
         }
     }
@@ -546,6 +579,33 @@ impl<F: Field> Lookup<F> {
                 exponent.clone(),
                 pow_of_rand.clone(),
             ],
+            Self::Sha256Table {
+                input_rlc,
+                input_len,
+                output_rlc,
+            }
+            | Self::Ripemd160Table {
+                input_rlc,
+                input_len,
+                output_rlc,
+            } => vec![
+                1.expr(), // q_enable
+                1.expr(), // is_final
+                input_rlc.clone(),
+                input_len.clone(),
+                output_rlc.clone(),
+            ],
+            Self::Blake2fTable {
+                rounds,
+                input_rlc,
+                output_rlc,
+            } => vec![
+                1.expr(), // q_enable
+                1.expr(), // is_final
+                rounds.clone(),
+                input_rlc.clone(),
+                output_rlc.clone(),
+            ],
             Self::Conditional(condition, lookup) => lookup
                 .input_exprs()
                 .into_iter()
@@ -961,6 +961,56 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         );
     }
 
+    // Created account
+
+    pub(crate) fn account_created_write(
+        &mut self,
+        tx_id: Expression<F>,
+        account_address: Expression<F>,
+        value: Expression<F>,
+        value_prev: Expression<F>,
+        reversion_info: Option<&mut ReversionInfo<F>>,
+    ) {
+        self.reversible_write(
+            "TxCreatedAccount write",
+            RwTableTag::TxCreatedAccount,
+            RwValues::new(
+                tx_id,
+                account_address,
+                0.expr(),
+                0.expr(),
+                value,
+                value_prev,
+                0.expr(),
+                0.expr(),
+            ),
+            reversion_info,
+        );
+    }
+
+    pub(crate) fn account_created_read(
+        &mut self,
+        tx_id: Expression<F>,
+        account_address: Expression<F>,
+        value: Expression<F>,
+    ) {
+        self.rw_lookup(
+            "TxCreatedAccount read",
+            false.expr(),
+            RwTableTag::TxCreatedAccount,
+            RwValues::new(
+                tx_id,
+                account_address,
+                0.expr(),
+                0.expr(),
+                value.clone(),
+                value,
+                0.expr(),
+                0.expr(),
+            ),
+        );
+    }
+
     // Tx Refund
 
     pub(crate) fn tx_refund_read(&mut self, tx_id: Expression<F>, value: Expression<F>) {
@@ -1110,6 +1160,58 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         );
     }
 
+    // Transient storage
+
+    pub(crate) fn transient_storage_read(
+        &mut self,
+        tx_id: Expression<F>,
+        account_address: Expression<F>,
+        key: Expression<F>,
+        value: Expression<F>,
+    ) {
+        self.rw_lookup(
+            "transient_storage_read",
+            false.expr(),
+            RwTableTag::TransientStorage,
+            RwValues::new(
+                tx_id,
+                account_address,
+                0.expr(),
+                key,
+                value.clone(),
+                value,
+                0.expr(),
+                0.expr(),
+            ),
+        );
+    }
+
+    pub(crate) fn transient_storage_write(
+        &mut self,
+        tx_id: Expression<F>,
+        account_address: Expression<F>,
+        key: Expression<F>,
+        value: Expression<F>,
+        value_prev: Expression<F>,
+        reversion_info: Option<&mut ReversionInfo<F>>,
+    ) {
+        self.reversible_write(
+            "TransientStorage write",
+            RwTableTag::TransientStorage,
+            RwValues::new(
+                tx_id,
+                account_address,
+                0.expr(),
+                key,
+                value,
+                value_prev,
+                0.expr(),
+                0.expr(),
+            ),
+            reversion_info,
+        );
+    }
+
     // Call context
 
     pub(crate) fn call_context(
@@ -1497,6 +1599,57 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         );
     }
 
+    // Sha256 table
+    pub(crate) fn sha256_table_lookup(
+        &mut self,
+        input_rlc: Expression<F>,
+        input_len: Expression<F>,
+        output_rlc: Expression<F>,
+    ) {
+        self.add_lookup(
+            "sha256 lookup",
+            Lookup::Sha256Table {
+                input_rlc,
+                input_len,
+                output_rlc,
+            },
+        );
+    }
+
+    // Ripemd160 table
+    pub(crate) fn ripemd160_table_lookup(
+        &mut self,
+        input_rlc: Expression<F>,
+        input_len: Expression<F>,
+        output_rlc: Expression<F>,
+    ) {
+        self.add_lookup(
+            "ripemd160 lookup",
+            Lookup::Ripemd160Table {
+                input_rlc,
+                input_len,
+                output_rlc,
+            },
+        );
+    }
+
+    // Blake2f table
+    pub(crate) fn blake2f_table_lookup(
+        &mut self,
+        rounds: Expression<F>,
+        input_rlc: Expression<F>,
+        output_rlc: Expression<F>,
+    ) {
+        self.add_lookup(
+            "blake2f lookup",
+            Lookup::Blake2fTable {
+                rounds,
+                input_rlc,
+                output_rlc,
+            },
+        );
+    }
+
     // Validation
 
     pub(crate) fn validate_degree(&self, degree: usize, name: &'static str) {
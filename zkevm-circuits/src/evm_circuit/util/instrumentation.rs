@@ -115,6 +115,15 @@ impl Instrument {
                     CellType::Lookup(Table::PowOfRand) => {
                         report.pow_of_rand_table = data_entry;
                     }
+                    CellType::Lookup(Table::Sha256) => {
+                        report.sha256_table = data_entry;
+                    }
+                    CellType::Lookup(Table::Ripemd160) => {
+                        report.ripemd160_table = data_entry;
+                    }
+                    CellType::Lookup(Table::Blake2f) => {
+                        report.blake2f_table = data_entry;
+                    }
                 }
             }
             report_collection.push(report);
@@ -145,6 +154,9 @@ pub(crate) struct ExecStateReport {
     pub(crate) modexp_table: StateReportRow,
     pub(crate) ecc_table: StateReportRow,
     pub(crate) pow_of_rand_table: StateReportRow,
+    pub(crate) sha256_table: StateReportRow,
+    pub(crate) ripemd160_table: StateReportRow,
+    pub(crate) blake2f_table: StateReportRow,
 }
 
 impl From<ExecutionState> for ExecStateReport {
@@ -148,8 +148,10 @@ impl<F: Field> PrecompileGadget<F> {
                     cb.require_zero("output bytes == 0", output_bytes_rlc.expr());
                 });
             }),
-            Box::new(|_cb| { /* Sha256 */ }),
-            Box::new(|_cb| { /* Ripemd160 */ }),
+            /* Sha256 */
+            copy_input_output(padding_gadget.padded_rlc(), output_bytes_rlc.expr()),
+            /* Ripemd160 */
+            copy_input_output(padding_gadget.padded_rlc(), output_bytes_rlc.expr()),
             Box::new(|cb| {
                 /* Identity */
                 cb.require_equal(
@@ -256,7 +258,8 @@ impl<F: Field> PrecompileGadget<F> {
                     output.expr(),
                 );
             }),
-            Box::new(|_cb| { /* Blake2F */ }),
+            /* Blake2F */
+            copy_input_output(padding_gadget.padded_rlc(), output_bytes_rlc.expr()),
         ];
         cb.constrain_mutually_exclusive_next_step(conditions, next_states, constraints);
 
@@ -287,6 +290,29 @@ impl<F: Field> PrecompileGadget<F> {
     }
 }
 
+/// The constraints of the hash precompiles, whose execution gadgets query the
+/// padded input RLC and the output RLC copied from the call as their first
+/// cells.
+fn copy_input_output<'a, F: Field>(
+    padded_input_rlc: Expression<F>,
+    output_bytes_rlc: Expression<F>,
+) -> BoxedClosure<'a, F> {
+    Box::new(move |cb| {
+        let (input_bytes_rlc_copied, output_bytes_rlc_copied) =
+            (cb.query_cell_phase2(), cb.query_cell_phase2());
+        cb.require_equal(
+            "copy input bytes",
+            padded_input_rlc,
+            input_bytes_rlc_copied.expr(),
+        );
+        cb.require_equal(
+            "copy output bytes",
+            output_bytes_rlc,
+            output_bytes_rlc_copied.expr(),
+        );
+    })
+}
+
 #[derive(Clone, Debug)]
 pub struct PaddingGadget<F> {
     is_cd_len_zero: IsZeroGadget<F>,
@@ -11,10 +11,10 @@ pub use dev::ExpCircuit as TestExpCircuit;
 use crate::{
     evm_circuit::util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
     table::{ExpTable, LookupTable, U16Table},
-    util::{Challenges, SubCircuit, SubCircuitConfig},
+    util::{Challenges, SubCircuit, SubCircuitConfig, SubCircuitRowEstimator, WitnessDelta},
     witness,
 };
-use bus_mapping::circuit_input_builder::{ExpEvent, ExpStep};
+use bus_mapping::circuit_input_builder::{CircuitsParams, ExpEvent, ExpStep};
 use eth_types::{Field, ToScalar, U256};
 use gadgets::{
     mul_add::{MulAddChip, MulAddConfig},
@@ -595,3 +595,16 @@ impl<F: Field> SubCircuit<F> for ExpCircuit<F> {
         config.assign_exp_events(layouter, &self.exp_events, self.max_exp_rows)
     }
 }
+
+impl<F: Field> SubCircuitRowEstimator<F> for ExpCircuit<F> {
+    fn base_rows(_params: &CircuitsParams) -> usize {
+        UNUSABLE_EXP_ROWS
+    }
+
+    fn marginal_rows(delta: &WitnessDelta, _params: &CircuitsParams) -> usize {
+        match delta {
+            WitnessDelta::ExpEvent(exp_event) => exp_event.steps.len() * OFFSET_INCREMENT,
+            _ => 0,
+        }
+    }
+}
@@ -16,7 +16,7 @@ mod test;
 pub use dev::KeccakCircuit as TestKeccakCircuit;
 use std::cmp::max;
 
-use std::marker::PhantomData;
+use std::{iter, marker::PhantomData};
 pub use KeccakCircuitConfig as KeccakConfig;
 
 use self::{
@@ -33,11 +33,13 @@ use crate::{
         get_num_bits_per_rho_pi_lookup, get_num_bits_per_theta_c_lookup, get_num_rows_per_round,
         split, split_uniform, transform, transform_to, Part,
     },
+    pi_circuit::PiCircuit,
     table::{KeccakTable, LookupTable},
-    util::{Challenges, SubCircuit, SubCircuitConfig},
+    util::{Challenges, SubCircuit, SubCircuitConfig, SubCircuitRowEstimator, WitnessDelta},
     witness,
 };
-use eth_types::Field;
+use bus_mapping::circuit_input_builder::{keccak_inputs_sign_verify, CircuitsParams};
+use eth_types::{sign_types::get_dummy_tx, Field};
 use gadgets::util::{and, not, select, sum, Expr};
 use halo2_proofs::{
     circuit::{AssignedCell, Layouter, Region, Value},
@@ -1026,7 +1028,6 @@ impl<F: Field> SubCircuit<F> for KeccakCircuit<F> {
 
     /// Return the minimum number of rows required to prove the block
     fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
-        let rows_per_chunk = (NUM_ROUNDS + 1) * get_num_rows_per_round();
         let aux_tables_rows = vec![
             normalize_table_size(6),
             normalize_table_size(4),
@@ -1037,7 +1038,7 @@ impl<F: Field> SubCircuit<F> for KeccakCircuit<F> {
             block
                 .keccak_inputs
                 .iter()
-                .map(|bytes| (bytes.len() as f64 / 136.0).ceil() as usize * rows_per_chunk)
+                .map(|bytes| num_rows_of_input(bytes))
                 .sum::<usize>()
                 + get_num_rows_per_round(), // reserved for first 12 dummy rows
             max(
@@ -1060,6 +1061,40 @@ impl<F: Field> SubCircuit<F> for KeccakCircuit<F> {
     }
 }
 
+impl<F: Field> SubCircuitRowEstimator<F> for KeccakCircuit<F> {
+    // The reserved rows, and the inputs that do not depend on the txs, see
+    // `keccak_inputs_block_level`, with the inputs of the PiCircuit at their longest for `params`.
+    fn base_rows(params: &CircuitsParams) -> usize {
+        let dummy_hash_data = {
+            let (dummy_tx, dummy_sig) = get_dummy_tx();
+            dummy_tx.rlp_signed(&dummy_sig).len()
+        };
+        iter::once(dummy_hash_data)
+            .chain(keccak_inputs_sign_verify(&[]).iter().map(Vec::len))
+            .chain(PiCircuit::<F>::max_keccak_input_lens(params))
+            .map(num_rows_of_input_len)
+            .sum::<usize>()
+            + get_num_rows_per_round()
+    }
+
+    fn marginal_rows(delta: &WitnessDelta, _params: &CircuitsParams) -> usize {
+        match delta {
+            WitnessDelta::KeccakInput(bytes) => num_rows_of_input(bytes),
+            _ => 0,
+        }
+    }
+}
+
+/// Return the number of rows required to hash `bytes`
+fn num_rows_of_input(bytes: &[u8]) -> usize {
+    num_rows_of_input_len(bytes.len())
+}
+
+fn num_rows_of_input_len(len: usize) -> usize {
+    let rows_per_chunk = (NUM_ROUNDS + 1) * get_num_rows_per_round();
+    (len as f64 / 136.0).ceil() as usize * rows_per_chunk
+}
+
 impl<F: Field> KeccakCircuit<F> {
     /// Creates a new circuit instance
     pub fn new(num_rows: usize, inputs: Vec<Vec<u8>>) -> Self {
@@ -1,9 +1,9 @@
 //! # zk_evm
 
 // We should try not to use incomplete_features unless it is really really needed and cannot be
-// avoided like `adt_const_params` used by DummyGadget
+// avoided like `adt_const_params` used by MemoryCopierGasGadget
 #![allow(incomplete_features)]
-// Needed by DummyGadget in evm circuit
+// Needed by MemoryCopierGasGadget in evm circuit
 #![feature(adt_const_params)]
 #![feature(array_zip)]
 #![feature(slice_group_by)]
@@ -24,6 +24,7 @@
 #[cfg(not(target_pointer_width = "64"))]
 compile_error!("This program requires a 64-bit target architecture.");
 
+pub mod blake2f_circuit;
 pub mod bytecode_circuit;
 pub mod copy_circuit;
 pub mod ecc_circuit;
@@ -33,7 +34,9 @@ pub mod keccak_circuit;
 pub mod mpt_circuit;
 pub mod pi_circuit;
 pub mod poseidon_circuit;
+pub mod ripemd160_circuit;
 pub mod rlp_circuit_fsm;
+pub mod sha256_circuit;
 pub mod sig_circuit;
 // we don't use this for aggregation
 //pub mod root_circuit;
@@ -13,10 +13,10 @@ use halo2_proofs::{
 
 use crate::{
     table::ModExpTable,
-    util::{Challenges, SubCircuit, SubCircuitConfig},
+    util::{Challenges, SubCircuit, SubCircuitConfig, SubCircuitRowEstimator, WitnessDelta},
     witness,
 };
-use bus_mapping::circuit_input_builder::BigModExp;
+use bus_mapping::circuit_input_builder::{BigModExp, CircuitsParams, PrecompileEvent};
 use eth_types::{Field, Word};
 
 //use misc_precompiled_circuit::value_for_assign;
@@ -210,3 +210,14 @@ impl<F: Field> SubCircuit<F> for ModExpCircuit<F> {
         config.modexp_table.fill_blank(layouter)
     }
 }
+
+impl<F: Field> SubCircuitRowEstimator<F> for ModExpCircuit<F> {
+    fn marginal_rows(delta: &WitnessDelta, _params: &CircuitsParams) -> usize {
+        match delta {
+            WitnessDelta::PrecompileEvent(PrecompileEvent::ModExp(_)) => {
+                MODEXPCONFIG_EACH_CHIP_ROWS
+            }
+            _ => 0,
+        }
+    }
+}
@@ -4,9 +4,10 @@
 // use crate::mpt_circuit::mpt;
 use crate::{
     table::{LookupTable, MptTable, PoseidonTable},
-    util::{Challenges, SubCircuit, SubCircuitConfig},
+    util::{Challenges, SubCircuit, SubCircuitConfig, SubCircuitRowEstimator, WitnessDelta},
     witness,
 };
+use bus_mapping::circuit_input_builder::CircuitsParams;
 use eth_types::Field;
 #[cfg(test)]
 use halo2_proofs::{circuit::SimpleFloorPlanner, plonk::Circuit};
@@ -153,6 +154,20 @@ impl SubCircuit<Fr> for MptCircuit<Fr> {
     }
 }
 
+impl SubCircuitRowEstimator<Fr> for MptCircuit<Fr> {
+    // the read of the withdraw root by the EndBlock step
+    fn base_rows(_params: &CircuitsParams) -> usize {
+        3 * 32
+    }
+
+    fn marginal_rows(delta: &WitnessDelta, _params: &CircuitsParams) -> usize {
+        match delta {
+            WitnessDelta::MptUpdate => 3 * 32,
+            _ => 0,
+        }
+    }
+}
+
 #[cfg(test)]
 impl Circuit<Fr> for MptCircuit<Fr> {
     type Config = (MptCircuitConfig<Fr>, PoseidonTable, Challenges);
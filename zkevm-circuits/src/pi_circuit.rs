@@ -7,17 +7,28 @@ mod param;
 #[cfg(any(feature = "test", test, feature = "test-circuits"))]
 mod test;
 
-use std::{cell::RefCell, collections::BTreeMap, iter, marker::PhantomData, str::FromStr};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    iter,
+    marker::PhantomData,
+    str::FromStr,
+};
 
 use crate::{evm_circuit::util::constraint_builder::ConstrainBuilderCommon, table::KeccakTable};
-use bus_mapping::circuit_input_builder::get_dummy_tx_hash;
-use eth_types::{Address, Field, Hash, ToBigEndian, ToWord, Word, H256};
+use bus_mapping::circuit_input_builder::{get_dummy_tx_hash, CircuitsParams};
+use eth_types::{
+    evm_types::MIN_BLOB_BASE_FEE,
+    geth_types::TxType,
+    pi_schema::{blob_data_commitment_preimage, PiField, PiSchema, PiValues},
+    Address, Field, Hash, ToBigEndian, ToWord, Word, H256,
+};
 use ethers_core::utils::keccak256;
 use halo2_proofs::plonk::{Assigned, Expression, Fixed, Instance};
 
 use crate::{
     table::{BlockTable, LookupTable, TxTable},
-    util::{Challenges, SubCircuit, SubCircuitConfig},
+    util::{Challenges, SubCircuit, SubCircuitConfig, SubCircuitRowEstimator, WitnessDelta},
 };
 #[cfg(feature = "onephase")]
 use halo2_proofs::plonk::FirstPhase as SecondPhase;
@@ -32,7 +43,9 @@ use crate::{
         RPI_LENGTH_ACC_CELL_IDX, RPI_RLC_ACC_CELL_IDX, TIMESTAMP_OFFSET,
     },
     state_circuit::StateCircuitExports,
-    tx_circuit::{CHAIN_ID_OFFSET as CHAIN_ID_OFFSET_IN_TX, TX_HASH_OFFSET, TX_LEN},
+    tx_circuit::{
+        CHAIN_ID_OFFSET as CHAIN_ID_OFFSET_IN_TX, TX_HASH_OFFSET, TX_LEN, TX_TYPE_OFFSET,
+    },
     witness::{self, Block, BlockContext, BlockContexts, Transaction},
 };
 use bus_mapping::util::read_env_var;
@@ -44,13 +57,15 @@ use halo2_proofs::{
 };
 
 use crate::{
-    evm_circuit::param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_U64, N_BYTES_WORD},
-    pi_circuit::param::{COINBASE_OFFSET, DIFFICULTY_OFFSET, NUM_ALL_TXS_OFFSET},
+    evm_circuit::param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_WORD},
+    pi_circuit::param::{
+        BLOB_BASE_FEE_OFFSET, COINBASE_OFFSET, DIFFICULTY_OFFSET, NUM_ALL_TXS_OFFSET,
+    },
     table::{
         BlockContextFieldTag,
         BlockContextFieldTag::{
-            BaseFee, ChainId, Coinbase, CumNumTxs, Difficulty, GasLimit, NumAllTxs, NumTxs, Number,
-            Timestamp,
+            BaseFee, BlobBaseFee, ChainId, Coinbase, CumNumTxs, Difficulty, GasLimit, NumAllTxs,
+            NumTxs, Number, Timestamp,
         },
     },
     util::rlc_be_bytes,
@@ -89,6 +104,10 @@ pub struct PublicData {
     pub next_state_root: Hash,
     /// Withdraw Trie Root
     pub withdraw_trie_root: Hash,
+    /// Layout of the public input hash preimage
+    pub pi_schema: PiSchema,
+    /// Rolling hash of the L1 message queue after the chunk
+    pub l1_msg_queue_hash: Hash,
 }
 
 impl Default for PublicData {
@@ -100,6 +119,8 @@ impl Default for PublicData {
             prev_state_root: H256::zero(),
             next_state_root: H256::zero(),
             withdraw_trie_root: H256::zero(),
+            pi_schema: PiSchema::default(),
+            l1_msg_queue_hash: H256::zero(),
             block_ctxs: Default::default(),
         }
     }
@@ -200,16 +221,34 @@ impl PublicData {
         H256(keccak256(self.data_bytes()))
     }
 
+    /// Compute the preimage of the blob data commitment, i.e., the hashes of the L2 txs.
+    fn blob_data_bytes(&self) -> Vec<u8> {
+        blob_data_commitment_preimage(
+            self.transactions
+                .iter()
+                .filter(|tx| !tx.tx_type.is_l1_msg())
+                .map(|tx| tx.hash),
+        )
+    }
+
+    fn get_blob_data_commitment(&self) -> H256 {
+        H256(keccak256(self.blob_data_bytes()))
+    }
+
+    fn pi_values(&self, data_hash: H256) -> PiValues {
+        PiValues {
+            chain_id: self.chain_id,
+            prev_state_root: self.prev_state_root,
+            post_state_root: self.next_state_root,
+            withdraw_root: self.withdraw_trie_root,
+            data_hash,
+            l1_msg_queue_hash: self.l1_msg_queue_hash,
+            blob_data_commitment: self.get_blob_data_commitment(),
+        }
+    }
+
     fn pi_bytes(&self, data_hash: H256) -> Vec<u8> {
-        iter::empty()
-            .chain(self.chain_id.to_be_bytes())
-            // state roots
-            .chain(self.prev_state_root.to_fixed_bytes())
-            .chain(self.next_state_root.to_fixed_bytes())
-            .chain(self.withdraw_trie_root.to_fixed_bytes())
-            // data hash
-            .chain(data_hash.to_fixed_bytes())
-            .collect::<Vec<u8>>()
+        self.pi_schema.preimage(&self.pi_values(data_hash))
     }
 
     fn get_pi(&self) -> H256 {
@@ -236,6 +275,7 @@ impl BlockContext {
             number: Default::default(),
             timestamp: Default::default(),
             base_fee: Default::default(),
+            blob_base_fee: MIN_BLOB_BASE_FEE.into(),
             history_hashes: vec![],
             eth_block: Default::default(),
         }
@@ -273,6 +313,13 @@ pub struct PiCircuitConfig<F: Field> {
     q_tx_hashes: Column<Fixed>,
     q_block_context: Column<Fixed>,
 
+    // columns for the blob data commitment, i.e., keccak over the hashes of the L2 txs
+    tx_type: Column<Advice>,
+    tx_type_diff_inv: Column<Advice>,
+    is_l1_msg: Column<Advice>,
+    blob_data_rlc_acc: Column<Advice>,
+    blob_data_length_acc: Column<Advice>,
+
     // columns for assertion about cum_num_txs in block table
     cum_num_txs: Column<Advice>,
     is_block_num_txs: Column<Fixed>,
@@ -371,6 +418,14 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
         let q_block_context = meta.fixed_column();
         let q_tx_hashes = meta.fixed_column();
 
+        // the type of the tx of the hash, copied from the tx table
+        let tx_type = meta.advice_column();
+        let tx_type_diff_inv = meta.advice_column();
+        let is_l1_msg = meta.advice_column();
+        // hold the accumulated rlc(hashes of L2 txs, keccak_input) and their length
+        let blob_data_rlc_acc = meta.advice_column_in(SecondPhase);
+        let blob_data_length_acc = meta.advice_column();
+
         let q_not_end = meta.complex_selector();
         // We are accumulating bytes for three different purposes
         // 1. input_rlc for hashing data bytes using keccak_input_rand
@@ -390,6 +445,9 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
         meta.enable_equality(rpi_length_acc);
         meta.enable_equality(rpi_rlc_acc);
         meta.enable_equality(real_rpi);
+        meta.enable_equality(tx_type);
+        meta.enable_equality(blob_data_rlc_acc);
+        meta.enable_equality(blob_data_length_acc);
         meta.enable_equality(block_table.value); // copy block to rpi
         meta.enable_equality(block_table.index);
         meta.enable_equality(tx_table.value); // copy tx hashes to rpi
@@ -531,6 +589,55 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
             cb.gate(meta.query_fixed(q_tx_hashes, Rotation::cur()))
         });
 
+        // The blob data commitment is keccak over the hashes of the L2 txs, i.e., the tx hashes
+        // which are not padding and whose tx is not an L1 msg. Their rlc and length are
+        // accumulated along the tx hashes, from zeros on the row before the first tx hash.
+        meta.create_gate("blob data of tx hashes", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let tx_type_diff =
+                meta.query_advice(tx_type, Rotation::cur()) - (TxType::L1Msg as u64).expr();
+            let is_l1_msg = meta.query_advice(is_l1_msg, Rotation::cur());
+            cb.require_equal(
+                "is_l1_msg == (tx_type == L1Msg)",
+                is_l1_msg.expr(),
+                1.expr() - tx_type_diff.expr() * meta.query_advice(tx_type_diff_inv, Rotation::cur()),
+            );
+            cb.require_zero("is_l1_msg => tx_type == L1Msg", is_l1_msg.expr() * tx_type_diff);
+
+            // the bytes of a tx hash share the type of the tx
+            cb.condition(meta.query_selector(q_field_step), |cb| {
+                cb.require_equal(
+                    "tx_type' == tx_type",
+                    meta.query_advice(tx_type, Rotation::next()),
+                    meta.query_advice(tx_type, Rotation::cur()),
+                );
+            });
+
+            let is_l2_tx = and::expr([
+                not::expr(meta.query_advice(is_rpi_padding, Rotation::cur())),
+                not::expr(is_l1_msg),
+            ]);
+            let rlc_acc_prev = meta.query_advice(blob_data_rlc_acc, Rotation::prev());
+            cb.require_equal(
+                "blob_data_rlc_acc = is_l2_tx ? blob_data_rlc_acc_prev * r + rpi_bytes : blob_data_rlc_acc_prev",
+                meta.query_advice(blob_data_rlc_acc, Rotation::cur()),
+                select::expr(
+                    is_l2_tx.expr(),
+                    rlc_acc_prev.expr() * challenges.keccak_input()
+                        + meta.query_advice(rpi_bytes, Rotation::cur()),
+                    rlc_acc_prev,
+                ),
+            );
+            cb.require_equal(
+                "blob_data_length_acc = blob_data_length_acc_prev + (is_l2_tx ? 1 : 0)",
+                meta.query_advice(blob_data_length_acc, Rotation::cur()),
+                meta.query_advice(blob_data_length_acc, Rotation::prev()) + is_l2_tx,
+            );
+
+            cb.gate(meta.query_fixed(q_tx_hashes, Rotation::cur()))
+        });
+
         // We reuse the layout for rpi to compute the keccak output.
         // The 32 bytes of keccak output are combined into (hi, lo)
         //  where r = challenges.evm_word().
@@ -553,16 +660,22 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
         // 3. copy the RLC(data_hash_bytes, word_rand) in the `rpi_rlc_acc` column
         //     to the `rpi_rlc_acc` column on the row that q_keccak = 1 for data hash.
 
-        // The layout for entire pi circuit looks like
+        // The layout for entire pi circuit looks like (the pi bytes follow the order of the
+        // fields in the `PiSchema`, and pi_len is the length of its preimage; bbs_rlc and bbs_len
+        // are the rlc and length of the hashes of the L2 txs, from the blob data columns)
         // data bytes:      |   rpi   | rpi_bytes | rpi_bytes_acc | rpi_rlc_acc | rpi_length_acc |
         //                  |   ..    |     ..    |      ...      |   dbs_rlc   |    input_len   |
         // q_keccak = 1     | dbs_rlc |     ..    |      ...      |   dh_rlc    |    input_len   |
+        // q_keccak = 1     | bbs_rlc |     ..    |      ...      |   bdc_rlc   |     bbs_len    |
         //  chain_id        | chain_id|     ..    |      ...      |     ...     |      ...       |
         // prev_state_root  |   ..    |     ..    |      ...      |     ...     |      ...       |
         // after_state_root |   ..    |     ..    |      ...      |     ...     |      ...       |
         // withdraw_root    |   ..    |     ..    |      ...      |     ...     |      ...       |
-        // data hash        |  dh_rlc |     ..    |      ...      |  pi_bs_rlc  |      136       |
-        // q_keccak = 1     |pi_bs_rlc|     ..    |      ...      | pi_hash_rlc |      136       |
+        // data hash        |  dh_rlc |     ..    |      ...      |     ...     |      ...       |
+        // (fields of V2)   |   ..    |     ..    |      ...      |  pi_bs_rlc  |     pi_len     |
+        //                  |         |           |               |             |                |
+        // where the blob data commitment field of V2 is bdc_rlc.
+        // q_keccak = 1     |pi_bs_rlc|     ..    |      ...      | pi_hash_rlc |     pi_len     |
         //   pi hash        |   hi    |     ..    |      ...      |     ...     |       16       |
         //                  |   lo    |     ..    |      ...      | pi_hash_rlc |       32       |
         meta.lookup_any("keccak(rpi)", |meta| {
@@ -637,6 +750,11 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
             is_rpi_padding,
             real_rpi,
             q_tx_hashes,
+            tx_type,
+            tx_type_diff_inv,
+            is_l1_msg,
+            blob_data_rlc_acc,
+            blob_data_length_acc,
             q_field_step,
             is_field_rlc,
             q_not_end,
@@ -680,19 +798,21 @@ impl<F: Field> PiCircuitConfig<F> {
         let tx_hashes = public_data
             .transactions
             .iter()
-            .map(|tx| tx.hash)
-            .collect::<Vec<H256>>();
+            .map(|tx| (tx.hash, tx.tx_type))
+            .collect::<Vec<(H256, TxType)>>();
         let num_all_txs_in_blocks = public_data.get_num_all_txs();
 
         let mut offset = 0;
         let mut block_copy_cells = vec![];
         let mut tx_copy_cells = vec![];
+        let mut tx_type_copy_cells = vec![];
         let mut block_table_offset = 1; // first row of block is all-zeros.
 
         let mut rpi_length_acc = 0u64;
         let mut rpi_rlc_acc = Value::known(F::zero());
 
         let dummy_tx_hash = get_dummy_tx_hash();
+        let padding_tx_type = Transaction::dummy(chain_id).tx_type;
 
         ///////////////////////////////////
         ///////  assign data bytes ////////
@@ -777,12 +897,27 @@ impl<F: Field> PiCircuitConfig<F> {
         let num_txs = tx_hashes.len();
         let mut data_bytes_rlc = None;
         let mut data_bytes_length = None;
-        for (i, tx_hash) in tx_hashes
+        // the blob data accumulators start from zeros on the row before the first tx hash
+        let mut blob_data_rlc_acc = Value::known(F::zero());
+        let mut blob_data_length_acc = 0u64;
+        let mut blob_data_rlc_cell = region.assign_advice_from_constant(
+            || "blob_data_rlc_acc[0]",
+            self.blob_data_rlc_acc,
+            q_tx_hashes_start_row - 1,
+            F::zero(),
+        )?;
+        let mut blob_data_length_cell = region.assign_advice_from_constant(
+            || "blob_data_length_acc[0]",
+            self.blob_data_length_acc,
+            q_tx_hashes_start_row - 1,
+            F::zero(),
+        )?;
+        for (i, (tx_hash, tx_type)) in tx_hashes
             .into_iter()
             .chain(
                 (0..self.max_txs - num_txs)
                     .into_iter()
-                    .map(|_| dummy_tx_hash),
+                    .map(|_| (dummy_tx_hash, padding_tx_type)),
             )
             .enumerate()
         {
@@ -801,6 +936,52 @@ impl<F: Field> PiCircuitConfig<F> {
             )?;
             tx_copy_cells.push(cells[RPI_CELL_IDX].clone());
 
+            let is_l2_tx = !is_rpi_padding && !tx_type.is_l1_msg();
+            let tx_type_diff = F::from(tx_type as u64) - F::from(TxType::L1Msg as u64);
+            for (j, byte) in tx_hash.to_fixed_bytes().into_iter().enumerate() {
+                let row_offset = offset - KECCAK_DIGEST_SIZE + j;
+                let tx_type_cell = region.assign_advice(
+                    || "tx_type",
+                    self.tx_type,
+                    row_offset,
+                    || Value::known(F::from(tx_type as u64)),
+                )?;
+                if j == 0 {
+                    tx_type_copy_cells.push(tx_type_cell);
+                }
+                region.assign_advice(
+                    || "tx_type_diff_inv",
+                    self.tx_type_diff_inv,
+                    row_offset,
+                    || Value::known(tx_type_diff.invert().unwrap_or(F::zero())),
+                )?;
+                region.assign_advice(
+                    || "is_l1_msg",
+                    self.is_l1_msg,
+                    row_offset,
+                    || Value::known(F::from(tx_type.is_l1_msg() as u64)),
+                )?;
+
+                if is_l2_tx {
+                    blob_data_rlc_acc = blob_data_rlc_acc
+                        .zip(challenges.keccak_input())
+                        .map(|(acc, rand)| acc * rand + F::from(byte as u64));
+                    blob_data_length_acc += 1;
+                }
+                blob_data_rlc_cell = region.assign_advice(
+                    || "blob_data_rlc_acc",
+                    self.blob_data_rlc_acc,
+                    row_offset,
+                    || blob_data_rlc_acc,
+                )?;
+                blob_data_length_cell = region.assign_advice(
+                    || "blob_data_length_acc",
+                    self.blob_data_length_acc,
+                    row_offset,
+                    || Value::known(F::from(blob_data_length_acc)),
+                )?;
+            }
+
             if i == self.max_txs - 1 {
                 data_bytes_rlc = Some(cells[RPI_RLC_ACC_CELL_IDX].clone());
                 data_bytes_length = Some(cells[RPI_LENGTH_ACC_CELL_IDX].clone());
@@ -837,6 +1018,13 @@ impl<F: Field> PiCircuitConfig<F> {
                 tx_value_cells[i * TX_LEN + TX_HASH_OFFSET - 1].cell(),
             )?;
         }
+        // copy tx types to tx table
+        for (i, tx_type_cell) in tx_type_copy_cells.into_iter().enumerate() {
+            region.constrain_equal(
+                tx_type_cell.cell(),
+                tx_value_cells[i * TX_LEN + TX_TYPE_OFFSET - 1].cell(),
+            )?;
+        }
 
         // assign keccak row for computing data_hash = keccak256(data bytes)
         let data_hash_row = offset;
@@ -863,25 +1051,66 @@ impl<F: Field> PiCircuitConfig<F> {
         self.q_keccak.enable(region, data_hash_row)?;
         offset += 1;
 
+        // assign keccak row for computing blob_data_commitment = keccak256(hashes of L2 txs)
+        let blob_data_commitment_row = offset;
+        blob_data_rlc_cell.copy_advice(
+            || "blob_data_rlc in the rpi col",
+            region,
+            self.raw_public_inputs,
+            blob_data_commitment_row,
+        )?;
+        blob_data_length_cell.copy_advice(
+            || "blob_data_length in the rpi_length_acc col",
+            region,
+            self.rpi_length_acc,
+            blob_data_commitment_row,
+        )?;
+        let blob_data_commitment = public_data.get_blob_data_commitment();
+        let blob_data_commitment_rlc_cell = region.assign_advice(
+            || "blob_data_commitment_rlc",
+            self.rpi_rlc_acc,
+            blob_data_commitment_row,
+            || {
+                rlc_be_bytes(
+                    &blob_data_commitment.to_fixed_bytes(),
+                    challenges.evm_word(),
+                )
+            },
+        )?;
+        self.q_keccak.enable(region, blob_data_commitment_row)?;
+        offset += 1;
+
         /////////////////////////////////
         ///////// assign pi bytes ///////
         /////////////////////////////////
+        let pi_schema = public_data.pi_schema;
         let pi_bytes_start_row = offset;
-        let pi_bytes_end_row = pi_bytes_start_row + N_BYTES_U64 + N_BYTES_WORD * 4;
+        let pi_bytes_end_row = pi_bytes_start_row + pi_schema.preimage_len();
         self.assign_rlc_start(region, &mut offset, &mut rpi_rlc_acc, &mut rpi_length_acc)?;
-        // assign chain_id
-        let cells = self.assign_field_in_pi(
-            region,
-            &mut offset,
-            &public_data.chain_id.to_be_bytes(),
-            &mut rpi_rlc_acc,
-            &mut rpi_length_acc,
-            false,
-            false,
-            false,
-            challenges,
-        )?;
-        let chain_id_cell = cells[RPI_CELL_IDX].clone();
+        // assign the fields in the order of the pi schema
+        let pi_values = public_data.pi_values(data_hash);
+        let mut field_cells = HashMap::new();
+        let mut last_cells = vec![];
+        for &field in pi_schema.fields() {
+            let cells = self.assign_field_in_pi(
+                region,
+                &mut offset,
+                &pi_values.field_bytes(field),
+                &mut rpi_rlc_acc,
+                &mut rpi_length_acc,
+                false,
+                false,
+                false,
+                challenges,
+            )?;
+            field_cells.insert(field, cells[RPI_CELL_IDX].clone());
+            last_cells = cells;
+        }
+        let field_cell = |field: PiField| field_cells[&field].clone();
+        let pi_bytes_rlc = last_cells[RPI_RLC_ACC_CELL_IDX].clone();
+        let pi_bytes_length = last_cells[RPI_LENGTH_ACC_CELL_IDX].clone();
+
+        let chain_id_cell = field_cell(PiField::ChainId);
         // copy chain_id to block table
         for block_idx in 0..self.max_inner_blocks {
             region.constrain_equal(
@@ -897,56 +1126,25 @@ impl<F: Field> PiCircuitConfig<F> {
             )?;
         }
 
-        // assign roots
-        //  1. prev_state_root
-        //  2. after_state_root
-        //  3. withdraw_trie_root
-        let roots = vec![
-            public_data.prev_state_root.to_fixed_bytes(),
-            public_data.next_state_root.to_fixed_bytes(),
-            public_data.withdraw_trie_root.to_fixed_bytes(),
-        ];
-        let root_cells = roots
-            .into_iter()
-            .map(|root_be_bytes| -> Result<AssignedCell<F, F>, Error> {
-                let cells = self.assign_field_in_pi(
-                    region,
-                    &mut offset,
-                    root_be_bytes.as_slice(),
-                    &mut rpi_rlc_acc,
-                    &mut rpi_length_acc,
-                    false,
-                    false,
-                    false,
-                    challenges,
-                )?;
-                Ok(cells[RPI_CELL_IDX].clone())
-            })
-            .collect::<Result<Vec<_>, Error>>()?;
+        // roots are connected to the state circuit and the evm circuit
         let connections = Connections {
-            start_state_root: root_cells[0].clone(),
-            end_state_root: root_cells[1].clone(),
-            withdraw_root: root_cells[2].clone(),
+            start_state_root: field_cell(PiField::PrevStateRoot),
+            end_state_root: field_cell(PiField::PostStateRoot),
+            withdraw_root: field_cell(PiField::WithdrawRoot),
         };
 
-        // assign data_hash
-        let cells = self.assign_field_in_pi(
-            region,
-            &mut offset,
-            &data_hash.to_fixed_bytes(),
-            &mut rpi_rlc_acc,
-            &mut rpi_length_acc,
-            false,
-            false,
-            false,
-            challenges,
-        )?;
-        let data_hash_cell = cells[RPI_CELL_IDX].clone();
-        let pi_bytes_rlc = cells[RPI_RLC_ACC_CELL_IDX].clone();
-        let pi_bytes_length = cells[RPI_LENGTH_ACC_CELL_IDX].clone();
-
         // copy data_hash down here
-        region.constrain_equal(data_hash_rlc_cell.cell(), data_hash_cell.cell())?;
+        region.constrain_equal(
+            data_hash_rlc_cell.cell(),
+            field_cell(PiField::DataHash).cell(),
+        )?;
+        // copy blob_data_commitment down here
+        if pi_schema.offset(PiField::BlobDataCommitment).is_some() {
+            region.constrain_equal(
+                blob_data_commitment_rlc_cell.cell(),
+                field_cell(PiField::BlobDataCommitment).cell(),
+            )?;
+        }
 
         for i in pi_bytes_start_row..pi_bytes_end_row {
             self.q_not_end.enable(region, i)?;
@@ -1078,6 +1276,12 @@ impl<F: Field> PiCircuitConfig<F> {
                 difficulty_cell.cell(),
                 block_value_cells[BLOCK_LEN * block_idx + DIFFICULTY_OFFSET].cell(),
             )?;
+            // L2 blocks carry no blobs, so their excess blob gas is zero and their blob base fee
+            // is the minimum one, whose rlc is itself.
+            region.constrain_constant(
+                block_value_cells[BLOCK_LEN * block_idx + BLOB_BASE_FEE_OFFSET].cell(),
+                F::from(MIN_BLOB_BASE_FEE),
+            )?;
         }
 
         assert_eq!(
@@ -1086,9 +1290,9 @@ impl<F: Field> PiCircuitConfig<F> {
             1 + self.max_inner_blocks * BLOCK_HEADER_BYTES_NUM
                 + self.max_txs * KECCAK_DIGEST_SIZE
                 + 1 // for data hash row
+                + 1 // for blob data commitment row
                 + 1 // for pi bytes start row
-                + N_BYTES_U64
-                + 4 * KECCAK_DIGEST_SIZE
+                + pi_schema.preimage_len()
                 + 1 // for pi hash row
                 + 1 // for pi hash bytes start row
                 + KECCAK_DIGEST_SIZE
@@ -1378,8 +1582,17 @@ impl<F: Field> PiCircuitConfig<F> {
                 .cloned()
                 .unwrap_or(0);
             let tag = [
-                Coinbase, Timestamp, Number, Difficulty, GasLimit, BaseFee, ChainId, NumTxs,
-                CumNumTxs, NumAllTxs,
+                Coinbase,
+                Timestamp,
+                Number,
+                Difficulty,
+                GasLimit,
+                BaseFee,
+                ChainId,
+                NumTxs,
+                CumNumTxs,
+                NumAllTxs,
+                BlobBaseFee,
             ];
 
             // index_cells of same block are equal to block_number.
@@ -1483,6 +1696,21 @@ pub struct PiCircuit<F: Field> {
 }
 
 impl<F: Field> PiCircuit<F> {
+    /// Return the lengths of the keccak inputs of the circuit, i.e., of the chunk data, of the
+    /// blob data commitment preimage and of the pi bytes, with the most blocks and txs of `params`
+    /// and the longest pi schema.
+    pub(crate) fn max_keccak_input_lens(params: &CircuitsParams) -> [usize; 3] {
+        [
+            params.max_inner_blocks * BLOCK_HEADER_BYTES_NUM + params.max_txs * KECCAK_DIGEST_SIZE,
+            params.max_txs * KECCAK_DIGEST_SIZE,
+            PiSchema::ALL
+                .iter()
+                .map(PiSchema::preimage_len)
+                .max()
+                .unwrap_or_default(),
+        ]
+    }
+
     /// Creates a new PiCircuit
     pub fn new(
         max_txs: usize,
@@ -1512,6 +1740,8 @@ impl<F: Field> PiCircuit<F> {
             prev_state_root: H256(block.mpt_updates.old_root().to_be_bytes()),
             next_state_root,
             withdraw_trie_root: H256(block.withdraw_root.to_be_bytes()),
+            pi_schema: block.pi_schema,
+            l1_msg_queue_hash: block.l1_msg_queue_hash,
         };
 
         Self {
@@ -1611,9 +1841,9 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
         let num_rows = 1 + max_inner_blocks * BLOCK_HEADER_BYTES_NUM
             + max_txs * KECCAK_DIGEST_SIZE
             + 1 // for data hash row
+            + 1 // for blob data commitment row
             + 1 // for pi bytes start row
-            + N_BYTES_U64 // chain_id
-            + 4 * KECCAK_DIGEST_SIZE // state_roots & data hash
+            + block.pi_schema.preimage_len() // pi bytes
             + 1 // for pi hash row
             + 1 // for pi hash bytes start row
             + KECCAK_DIGEST_SIZE // pi hash bytes
@@ -1692,3 +1922,16 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
         Ok(())
     }
 }
+
+impl<F: Field> SubCircuitRowEstimator<F> for PiCircuit<F> {
+    // the share of the vertical rows of a tx, rounded up per tx
+    fn marginal_rows(delta: &WitnessDelta, params: &CircuitsParams) -> usize {
+        match delta {
+            WitnessDelta::Tx(_) => {
+                let tx_usage = 1.0 / params.max_txs as f32;
+                (tx_usage * params.max_vertical_circuit_rows as f32).ceil() as usize
+            }
+            _ => 0,
+        }
+    }
+}
@@ -100,10 +100,17 @@ fn serial_test_simple_pi() {
     let block = block_1tx();
 
     let k = 16;
-    assert_eq!(
-        run::<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS>(k, block),
-        Ok(())
-    );
+    for pi_schema in PiSchema::ALL {
+        let block = Block::<Fr> {
+            pi_schema,
+            l1_msg_queue_hash: H256::repeat_byte(1),
+            ..block.clone()
+        };
+        assert_eq!(
+            run::<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS>(k, block),
+            Ok(())
+        );
+    }
 }
 
 fn run_size_check<
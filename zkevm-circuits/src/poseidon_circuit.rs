@@ -2,10 +2,11 @@
 use crate::{
     bytecode_circuit::bytecode_unroller::HASHBLOCK_BYTES_IN_FIELD,
     table::PoseidonTable,
-    util::{Challenges, SubCircuit, SubCircuitConfig},
+    util::{Challenges, SubCircuit, SubCircuitConfig, SubCircuitRowEstimator, WitnessDelta},
     witness::{self},
 };
 //use bus_mapping::state_db::CodeDB;
+use bus_mapping::circuit_input_builder::CircuitsParams;
 use eth_types::Field;
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner, Value},
@@ -53,47 +54,9 @@ impl<F: Field> SubCircuitConfig<F> for PoseidonCircuitConfig<F> {
 }
 
 #[cfg(any(feature = "test", test))]
-impl<F: Field> SubCircuit<F> for PoseidonCircuit<F> {
-    type Config = PoseidonCircuitConfig<F>;
-
-    fn new_from_block(block: &witness::Block<F>) -> Self {
-        let max_hashes = block.circuits_params.max_poseidon_rows / F::hash_block_size();
-        #[allow(unused_mut)]
-        let mut poseidon_table_data: PoseidonHashTable<F> = PoseidonHashTable::default();
-        // without any feature we just synthesis an empty poseidon circuit
-        #[cfg(feature = "zktrie")]
-        {
-            let mpt_hashes = get_storage_poseidon_witness(block);
-            if mpt_hashes.len() > max_hashes {
-                log::error!(
-                    "poseidon max_hashes: {:?} not enough. {:?} needed by zktrie proof",
-                    max_hashes,
-                    mpt_hashes.len()
-                );
-            }
-            poseidon_table_data.fixed_inputs(&mpt_hashes);
-        }
-        #[cfg(feature = "poseidon-codehash")]
-        {
-            use crate::bytecode_circuit::bytecode_unroller::unroll_to_hash_input_default;
-            for bytecode in block.bytecodes.values() {
-                // must skip empty bytecode
-                if !bytecode.bytes.is_empty() {
-                    let unrolled_inputs =
-                        unroll_to_hash_input_default::<F>(bytecode.bytes.iter().copied());
-                    poseidon_table_data.stream_inputs(
-                        &unrolled_inputs,
-                        bytecode.bytes.len() as u64,
-                        HASH_BLOCK_STEP_SIZE,
-                    );
-                }
-            }
-        }
-
-        Self(poseidon_table_data, max_hashes)
-    }
-
-    fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
+impl<F: Field> PoseidonCircuit<F> {
+    /// Return the number of rows of the hashes of the mpt updates of the block, deduplicated
+    fn num_rows_mpt_hashes(block: &witness::Block<F>) -> usize {
         let mut path_hash_counter: std::collections::HashMap<[u8; 32], usize> = Default::default();
         let mut account_counter: std::collections::HashMap<[u8; 32], usize> = Default::default();
         let mut storage_counter: std::collections::HashMap<[u8; 32], usize> = Default::default();
@@ -162,13 +125,63 @@ impl<F: Field> SubCircuit<F> for PoseidonCircuit<F> {
             + storage_counter.len();
         log::debug!("poseidon circuit row num: dedup mpt from {prev_dedup_size} to {after_dedup_size}, mpt update len {}, smt trace len {}",
         block.mpt_updates.len(), block.mpt_updates.smt_traces.len());
-        let mpt_row_num = after_dedup_size * F::hash_block_size();
+        after_dedup_size * F::hash_block_size()
+    }
+
+    /// Return the number of rows of the code hash of a bytecode of `len` bytes
+    fn num_rows_bytecode_hash(len: usize) -> usize {
+        (len / HASH_BLOCK_STEP_SIZE + 1) * F::hash_block_size()
+    }
+}
+
+#[cfg(any(feature = "test", test))]
+impl<F: Field> SubCircuit<F> for PoseidonCircuit<F> {
+    type Config = PoseidonCircuitConfig<F>;
+
+    fn new_from_block(block: &witness::Block<F>) -> Self {
+        let max_hashes = block.circuits_params.max_poseidon_rows / F::hash_block_size();
+        #[allow(unused_mut)]
+        let mut poseidon_table_data: PoseidonHashTable<F> = PoseidonHashTable::default();
+        // without any feature we just synthesis an empty poseidon circuit
+        #[cfg(feature = "zktrie")]
+        {
+            let mpt_hashes = get_storage_poseidon_witness(block);
+            if mpt_hashes.len() > max_hashes {
+                log::error!(
+                    "poseidon max_hashes: {:?} not enough. {:?} needed by zktrie proof",
+                    max_hashes,
+                    mpt_hashes.len()
+                );
+            }
+            poseidon_table_data.fixed_inputs(&mpt_hashes);
+        }
+        #[cfg(feature = "poseidon-codehash")]
+        {
+            use crate::bytecode_circuit::bytecode_unroller::unroll_to_hash_input_default;
+            for bytecode in block.bytecodes.values() {
+                // must skip empty bytecode
+                if !bytecode.bytes.is_empty() {
+                    let unrolled_inputs =
+                        unroll_to_hash_input_default::<F>(bytecode.bytes.iter().copied());
+                    poseidon_table_data.stream_inputs(
+                        &unrolled_inputs,
+                        bytecode.bytes.len() as u64,
+                        HASH_BLOCK_STEP_SIZE,
+                    );
+                }
+            }
+        }
+
+        Self(poseidon_table_data, max_hashes)
+    }
+
+    fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
+        let mpt_row_num = Self::num_rows_mpt_hashes(block);
         let byte_row_num = block
             .bytecodes
             .values()
-            .map(|bytecode| bytecode.bytes.len() / HASH_BLOCK_STEP_SIZE + 1)
-            .sum::<usize>()
-            * F::hash_block_size();
+            .map(|bytecode| Self::num_rows_bytecode_hash(bytecode.bytes.len()))
+            .sum::<usize>();
         let total_row_num = mpt_row_num + byte_row_num;
         log::debug!("poseidon circuit row num: {mpt_row_num}(mpt) + {byte_row_num}(bytecode) = {total_row_num}");
         (
@@ -200,6 +213,18 @@ impl<F: Field> SubCircuit<F> for PoseidonCircuit<F> {
     }
 }
 
+#[cfg(any(feature = "test", test))]
+impl<F: Field> SubCircuitRowEstimator<F> for PoseidonCircuit<F> {
+    // The hashes of an mpt update depend on the zktrie, which a delta has not, so they are left to
+    // the caller, see `num_rows_mpt_hashes`.
+    fn marginal_rows(delta: &WitnessDelta, _params: &CircuitsParams) -> usize {
+        match delta {
+            WitnessDelta::Bytecode(bytes) => Self::num_rows_bytecode_hash(bytes.len()),
+            _ => 0,
+        }
+    }
+}
+
 #[cfg(any(feature = "test", test))]
 impl<F: Field + Hashable> Circuit<F> for PoseidonCircuit<F> {
     type Config = (PoseidonCircuitConfig<F>, Challenges);
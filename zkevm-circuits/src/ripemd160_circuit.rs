@@ -0,0 +1,804 @@
+//! The RIPEMD-160 circuit implementation, used to verify the calls to the
+//! RIPEMD-160 precompiled contract.
+mod param;
+/// Ripemd160 bit-level witness generation
+pub mod ripemd160_bit;
+mod util;
+
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+mod dev;
+#[cfg(any(feature = "test", test))]
+mod test;
+
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+pub use dev::Ripemd160Circuit as TestRipemd160Circuit;
+
+use std::marker::PhantomData;
+
+use self::{
+    param::*,
+    ripemd160_bit::{multi_ripemd160, num_blocks, Ripemd160Row},
+    util::*,
+};
+use crate::{
+    evm_circuit::util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+    table::{LookupTable, Ripemd160Table},
+    util::{Challenges, SubCircuit, SubCircuitConfig, SubCircuitRowEstimator, WitnessDelta},
+    witness,
+};
+use bus_mapping::circuit_input_builder::{CircuitsParams, PrecompileEvent};
+use eth_types::Field;
+use gadgets::util::{not, select, sum, Expr};
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, VirtualCells},
+    poly::Rotation,
+};
+
+#[cfg(feature = "onephase")]
+use halo2_proofs::plonk::FirstPhase as SecondPhase;
+#[cfg(not(feature = "onephase"))]
+use halo2_proofs::plonk::SecondPhase;
+
+/// Ripemd160Config
+///
+/// Each 64-byte block takes `NUM_ROWS_PER_BLOCK` rows: the start rows hold the
+/// chaining value, every round row computes one step of both the left and the
+/// right line, and the end rows hold the new chaining value. The message words
+/// are absorbed in the first 16 rounds and copied to the later rounds by
+/// equality constraints, following the fixed permutations `R_LEFT` and
+/// `R_RIGHT`.
+#[derive(Clone, Debug)]
+pub struct Ripemd160CircuitConfig<F> {
+    q_enable: Column<Fixed>,
+    q_first: Column<Fixed>,
+    q_start: Column<Fixed>,
+    q_round: Column<Fixed>,
+    q_input: Column<Fixed>,
+    q_input_last: Column<Fixed>,
+    q_length: Column<Fixed>,
+    q_end: Column<Fixed>,
+    q_end_last: Column<Fixed>,
+    q_groups: [Column<Fixed>; NUM_GROUPS],
+    q_rotations_left: [Column<Fixed>; NUM_ROTATIONS],
+    q_rotations_right: [Column<Fixed>; NUM_ROTATIONS],
+    h: Column<Fixed>,
+    word_x: [Column<Advice>; NUM_BITS_PER_WORD],
+    x_left: Column<Advice>,
+    x_right: Column<Advice>,
+    sum_left: [Column<Advice>; NUM_BITS_PER_WORD_EXT],
+    sum_right: [Column<Advice>; NUM_BITS_PER_WORD_EXT],
+    word_left: [Column<Advice>; NUM_BITS_PER_WORD_EXT],
+    word_right: [Column<Advice>; NUM_BITS_PER_WORD_EXT],
+    is_final_block: Column<Advice>,
+    is_paddings: [Column<Advice>; NUM_BYTES_PER_WORD],
+    data_rlcs: [Column<Advice>; NUM_BYTES_PER_WORD],
+    /// The columns for other circuits to lookup RIPEMD-160 hash results
+    pub ripemd160_table: Ripemd160Table,
+    _marker: PhantomData<F>,
+}
+
+/// Circuit configuration arguments
+#[derive(Debug, Clone)]
+pub struct Ripemd160CircuitConfigArgs<F: Field> {
+    /// Ripemd160Table
+    pub ripemd160_table: Ripemd160Table,
+    /// Challenges randomness
+    pub challenges: Challenges<Expression<F>>,
+}
+
+impl<F: Field> SubCircuitConfig<F> for Ripemd160CircuitConfig<F> {
+    type ConfigArgs = Ripemd160CircuitConfigArgs<F>;
+
+    /// Return a new Ripemd160CircuitConfig
+    fn new(
+        meta: &mut ConstraintSystem<F>,
+        Self::ConfigArgs {
+            ripemd160_table,
+            challenges,
+        }: Self::ConfigArgs,
+    ) -> Self {
+        let q_enable = ripemd160_table.q_enable;
+        let q_first = meta.fixed_column();
+        let q_start = meta.fixed_column();
+        let q_round = meta.fixed_column();
+        let q_input = meta.fixed_column();
+        let q_input_last = meta.fixed_column();
+        let q_length = meta.fixed_column();
+        let q_end = meta.fixed_column();
+        let q_end_last = meta.fixed_column();
+        let q_groups = array_init::array_init(|_| meta.fixed_column());
+        let q_rotations_left = array_init::array_init(|_| meta.fixed_column());
+        let q_rotations_right = array_init::array_init(|_| meta.fixed_column());
+        let h = meta.fixed_column();
+        let word_x = array_init::array_init(|_| meta.advice_column());
+        let x_left = meta.advice_column();
+        let x_right = meta.advice_column();
+        let sum_left = array_init::array_init(|_| meta.advice_column());
+        let sum_right = array_init::array_init(|_| meta.advice_column());
+        let word_left = array_init::array_init(|_| meta.advice_column());
+        let word_right = array_init::array_init(|_| meta.advice_column());
+        let is_final_block = meta.advice_column();
+        let is_paddings = array_init::array_init(|_| meta.advice_column());
+        let data_rlcs = array_init::array_init(|_| meta.advice_column_in(SecondPhase));
+        meta.enable_equality(x_left);
+        meta.enable_equality(x_right);
+
+        let is_final = ripemd160_table.is_final;
+        let length = ripemd160_table.input_len;
+        let data_rlc = ripemd160_table.input_rlc;
+        let hash_rlc = ripemd160_table.output_rlc;
+
+        // The working registers `[A, B, C, D, E]` of a line before the current
+        // row, read back from the words of the five previous rows
+        let registers = |meta: &mut VirtualCells<F>, word: &[Column<Advice>]| {
+            [-5, -1, -2, -3, -4].map(|rot| {
+                let bits = query_bits(meta, word, rot);
+                if [-5, -3, -4].contains(&rot) {
+                    decode(&rotl(&bits, ROTATION_C))
+                } else {
+                    decode(&bits[..NUM_BITS_PER_WORD])
+                }
+            })
+        };
+
+        // The value of the chaining value word held by a start row
+        let start_value = |bits: &[Expression<F>], idx: usize| {
+            if idx < NUM_ROTATED_START_ROWS {
+                decode(&rotl(bits, ROTATION_C))
+            } else {
+                decode(&bits[..NUM_BITS_PER_WORD])
+            }
+        };
+
+        // A new hash is started on the first block or after the final block of the
+        // previous hash
+        let start_new_hash = |meta: &mut VirtualCells<F>| {
+            let q_first = meta.query_fixed(q_first, Rotation::cur());
+            q_first.clone() + not::expr(q_first) * meta.query_advice(is_final, Rotation::prev())
+        };
+
+        meta.create_gate("boolean checks", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            for column in word_x
+                .iter()
+                .chain(sum_left.iter())
+                .chain(sum_right.iter())
+                .chain(word_left.iter())
+                .chain(word_right.iter())
+                .chain(is_paddings.iter())
+                .chain(std::iter::once(&is_final_block))
+            {
+                cb.require_boolean("boolean value", meta.query_advice(*column, Rotation::cur()));
+            }
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("block flags", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let q_start = meta.query_fixed(q_start, Rotation::cur());
+            let q_end_last = meta.query_fixed(q_end_last, Rotation::cur());
+            let is_final_block_cur = meta.query_advice(is_final_block, Rotation::cur());
+            let is_final_block_prev = meta.query_advice(is_final_block, Rotation::prev());
+            cb.require_equal(
+                "is_final is only set on the last row of the final block",
+                meta.query_advice(is_final, Rotation::cur()),
+                q_end_last * is_final_block_cur.clone(),
+            );
+            cb.condition(not::expr(q_start), |cb| {
+                cb.require_equal(
+                    "is_final_block is constant within a block",
+                    is_final_block_cur,
+                    is_final_block_prev,
+                );
+            });
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("round", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let q_groups = q_groups.map(|column| meta.query_fixed(column, Rotation::cur()));
+            for (names, word, sum, x, q_rotations, is_left) in [
+                (
+                    [
+                        "left: sum = A + f(B, C, D) + X + K",
+                        "left: new B = rol(sum, s) + E",
+                    ],
+                    word_left,
+                    sum_left,
+                    x_left,
+                    q_rotations_left,
+                    true,
+                ),
+                (
+                    [
+                        "right: sum = A + f(B, C, D) + X + K",
+                        "right: new B = rol(sum, s) + E",
+                    ],
+                    word_right,
+                    sum_right,
+                    x_right,
+                    q_rotations_right,
+                    false,
+                ),
+            ] {
+                let [a, _, _, _, e] = registers(meta, &word);
+                let [b, c, d] = [-1, -2, -3].map(|rot| query_bits(meta, &word, rot));
+                let d = rotl(&d, ROTATION_C);
+                let (f_value, k) = q_groups.iter().enumerate().fold(
+                    (0.expr(), 0.expr()),
+                    |(f_acc, k_acc), (group, q_group)| {
+                        let (group, k) = if is_left {
+                            (group, K_LEFT[group])
+                        } else {
+                            (NUM_GROUPS - 1 - group, K_RIGHT[group])
+                        };
+                        (
+                            f_acc + q_group.clone() * decode(&f(group, &b, &c, &d)),
+                            k_acc + q_group.clone() * k.expr(),
+                        )
+                    },
+                );
+                let sum = query_bits(meta, &sum, 0);
+                cb.require_equal(
+                    names[0],
+                    decode(&sum),
+                    a + f_value + meta.query_advice(x, Rotation::cur()) + k,
+                );
+                let rotated =
+                    q_rotations
+                        .iter()
+                        .enumerate()
+                        .fold(0.expr(), |acc, (idx, q_rotation)| {
+                            acc + meta.query_fixed(*q_rotation, Rotation::cur())
+                                * decode(&rotl(&sum, MIN_ROTATION + idx))
+                        });
+                cb.require_equal(names[1], decode(&query_bits(meta, &word, 0)), rotated + e);
+            }
+            cb.gate(meta.query_fixed(q_round, Rotation::cur()))
+        });
+
+        meta.create_gate("start", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let start_new_hash = start_new_hash(meta);
+            for idx in 0..NUM_START_ROWS {
+                let rot = idx as i32;
+                let prev = query_bits(meta, &word_left, rot - NUM_END_ROWS as i32);
+                let expected = select::expr(
+                    start_new_hash.clone(),
+                    meta.query_fixed(h, Rotation(rot)),
+                    decode(&prev[..NUM_BITS_PER_WORD]),
+                );
+                for word in [word_left, word_right] {
+                    let value = query_bits(meta, &word, rot);
+                    for bit in value[NUM_BITS_PER_WORD..].iter() {
+                        cb.require_zero("start words have no carry", bit.clone());
+                    }
+                    cb.require_equal(
+                        "start from the initial hash or the previous block",
+                        start_value(&value, idx),
+                        expected.clone(),
+                    );
+                }
+            }
+            let is_padding_prev = meta.query_advice(is_paddings[3], Rotation::prev());
+            for is_padding in is_paddings.iter() {
+                cb.require_equal(
+                    "padding is reset on a new hash",
+                    meta.query_advice(*is_padding, Rotation::cur()),
+                    not::expr(start_new_hash.clone()) * is_padding_prev.clone(),
+                );
+            }
+            for column in [length, data_rlc] {
+                cb.require_equal(
+                    "length and data rlc are reset on a new hash",
+                    meta.query_advice(column, Rotation::cur()),
+                    not::expr(start_new_hash.clone()) * meta.query_advice(column, Rotation::prev()),
+                );
+            }
+            cb.gate(meta.query_fixed(q_start, Rotation::cur()))
+        });
+
+        meta.create_gate("end", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            // The chaining value the block started from, indexed as `H`
+            let mut hs = [(); 5].map(|_| 0.expr());
+            for (idx, &h) in H_ORDER.iter().enumerate() {
+                let rot = idx as i32 - (NUM_ROUNDS + NUM_START_ROWS) as i32;
+                hs[h] = start_value(&query_bits(meta, &word_left, rot), idx);
+            }
+            let [a, b, c, d, e] = registers(meta, &word_left);
+            let [a_r, b_r, c_r, d_r, e_r] = registers(meta, &word_right);
+            let new_hs = [
+                hs[1].clone() + c + d_r,
+                hs[2].clone() + d + e_r,
+                hs[3].clone() + e + a_r,
+                hs[4].clone() + a + b_r,
+                hs[0].clone() + b + c_r,
+            ];
+            for (idx, &h) in H_ORDER.iter().enumerate() {
+                cb.require_equal(
+                    "chaining value = previous chaining value + both lines",
+                    decode(&query_bits(meta, &word_left, idx as i32)),
+                    new_hs[h].clone(),
+                );
+            }
+            cb.gate(meta.query_fixed(q_end, Rotation::cur()))
+        });
+
+        meta.create_gate("input", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let x = query_bits(meta, &word_x, 0);
+            let q_length = meta.query_fixed(q_length, Rotation::cur());
+            let is_final_block = meta.query_advice(is_final_block, Rotation::cur());
+            let r = challenges.keccak_input();
+
+            cb.require_equal(
+                "the left line reads the message words in order",
+                meta.query_advice(x_left, Rotation::cur()),
+                decode(&x),
+            );
+
+            let mut is_padding_prev = meta.query_advice(is_paddings[3], Rotation::prev());
+            let mut data_rlc_prev = meta.query_advice(data_rlc, Rotation::prev());
+            for (idx, (is_padding, rlc)) in is_paddings.iter().zip(data_rlcs.iter()).enumerate() {
+                let is_padding = meta.query_advice(*is_padding, Rotation::cur());
+                let rlc = meta.query_advice(*rlc, Rotation::cur());
+                let byte = decode_byte(&x, idx);
+                cb.require_zero(
+                    "padding is monotonic",
+                    is_padding_prev.clone() * not::expr(is_padding.clone()),
+                );
+                cb.require_zero(
+                    "the first padding byte is 0x80",
+                    (is_padding.clone() - is_padding_prev.clone()) * (byte.clone() - 0x80.expr()),
+                );
+                cb.require_zero(
+                    "the other padding bytes are 0 except for the length",
+                    is_padding_prev.clone()
+                        * byte.clone()
+                        * not::expr(q_length.clone() * is_final_block.clone()),
+                );
+                cb.require_equal(
+                    "data rlc only absorbs non padding bytes",
+                    rlc.clone(),
+                    select::expr(
+                        is_padding.clone(),
+                        data_rlc_prev.clone(),
+                        data_rlc_prev * r.clone() + byte,
+                    ),
+                );
+                is_padding_prev = is_padding;
+                data_rlc_prev = rlc;
+            }
+            cb.require_equal(
+                "data rlc",
+                meta.query_advice(data_rlc, Rotation::cur()),
+                data_rlc_prev,
+            );
+            cb.require_equal(
+                "length only counts non padding bytes",
+                meta.query_advice(length, Rotation::cur()),
+                meta.query_advice(length, Rotation::prev())
+                    + sum::expr(
+                        is_paddings
+                            .iter()
+                            .map(|column| not::expr(meta.query_advice(*column, Rotation::cur()))),
+                    ),
+            );
+            cb.gate(meta.query_fixed(q_input, Rotation::cur()))
+        });
+
+        meta.create_gate("non input", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let is_padding_prev = meta.query_advice(is_paddings[3], Rotation::prev());
+            for is_padding in is_paddings.iter() {
+                cb.require_equal(
+                    "padding state is kept",
+                    meta.query_advice(*is_padding, Rotation::cur()),
+                    is_padding_prev.clone(),
+                );
+            }
+            for column in [length, data_rlc] {
+                cb.require_equal(
+                    "length and data rlc are kept",
+                    meta.query_advice(column, Rotation::cur()),
+                    meta.query_advice(column, Rotation::prev()),
+                );
+            }
+            cb.gate(
+                meta.query_fixed(q_enable, Rotation::cur())
+                    - meta.query_fixed(q_input, Rotation::cur())
+                    - meta.query_fixed(q_start, Rotation::cur()),
+            )
+        });
+
+        meta.create_gate("length", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let is_final_block = meta.query_advice(is_final_block, Rotation::cur());
+            // The final block is the one that has space for the 8 length bytes
+            // after the first padding byte.
+            cb.require_equal(
+                "final block iff padding started before the length words",
+                is_final_block.clone(),
+                meta.query_advice(is_paddings[3], Rotation(-2)),
+            );
+            cb.condition(is_final_block, |cb| {
+                let x_lo = query_bits(meta, &word_x, -1);
+                let x_hi = query_bits(meta, &word_x, 0);
+                cb.require_equal(
+                    "padded length in bits",
+                    decode(&x_hi) * (1u64 << NUM_BITS_PER_WORD).expr() + decode(&x_lo),
+                    meta.query_advice(length, Rotation::cur()) * 8.expr(),
+                );
+            });
+            cb.gate(meta.query_fixed(q_input_last, Rotation::cur()))
+        });
+
+        meta.create_gate("hash rlc", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let r = challenges.keccak_input();
+            // The digest is the chaining value words in `H` order, each one
+            // in little-endian bytes.
+            let hash_bytes_rlc = (0..H.len())
+                .map(|h| {
+                    let idx = H_ORDER.iter().position(|&order| order == h).unwrap();
+                    query_bits(meta, &word_left, idx as i32 - (NUM_END_ROWS as i32 - 1))
+                })
+                .flat_map(|bits| {
+                    (0..NUM_BYTES_PER_WORD)
+                        .map(|idx| decode_byte(&bits, idx))
+                        .collect::<Vec<_>>()
+                })
+                .fold(0.expr(), |acc, byte| acc * r.clone() + byte);
+            cb.condition(meta.query_advice(is_final_block, Rotation::cur()), |cb| {
+                cb.require_equal(
+                    "hash rlc",
+                    meta.query_advice(hash_rlc, Rotation::cur()),
+                    hash_bytes_rlc,
+                );
+            });
+            cb.gate(meta.query_fixed(q_end_last, Rotation::cur()))
+        });
+
+        log::info!("ripemd160 circuit degree: {}", meta.degree());
+
+        Ripemd160CircuitConfig {
+            q_enable,
+            q_first,
+            q_start,
+            q_round,
+            q_input,
+            q_input_last,
+            q_length,
+            q_end,
+            q_end_last,
+            q_groups,
+            q_rotations_left,
+            q_rotations_right,
+            h,
+            word_x,
+            x_left,
+            x_right,
+            sum_left,
+            sum_right,
+            word_left,
+            word_right,
+            is_final_block,
+            is_paddings,
+            data_rlcs,
+            ripemd160_table,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Field> Ripemd160CircuitConfig<F> {
+    /// Assign the circuit for hash function
+    pub(crate) fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        witness: &[Ripemd160Row<F>],
+    ) -> Result<(), Error> {
+        let mut is_first_time = true;
+        layouter.assign_region(
+            || "assign ripemd160 rows",
+            |mut region| {
+                if is_first_time {
+                    is_first_time = false;
+                    let offset = witness.len() - 1;
+                    self.set_row(&mut region, offset, &witness[offset])?;
+                    return Ok(());
+                }
+                let mut x_cells = Vec::with_capacity(witness.len());
+                for (offset, ripemd160_row) in witness.iter().enumerate() {
+                    x_cells.push(self.set_row(&mut region, offset, ripemd160_row)?);
+                }
+                // The message words of the later rounds are copied from the
+                // input rounds
+                for block in x_cells[NUM_END_ROWS..].chunks(NUM_ROWS_PER_BLOCK) {
+                    let rounds = &block[NUM_START_ROWS..NUM_START_ROWS + NUM_ROUNDS];
+                    for (round, (x_left, x_right)) in rounds.iter().enumerate() {
+                        region.constrain_equal(x_left.cell(), rounds[R_LEFT[round]].0.cell())?;
+                        region.constrain_equal(x_right.cell(), rounds[R_RIGHT[round]].0.cell())?;
+                    }
+                }
+                self.ripemd160_table.annotate_columns_in_region(&mut region);
+                self.annotate_circuit(&mut region);
+                Ok(())
+            },
+        )
+    }
+
+    /// Set the cells for a ripemd160 row, returning the cells of the message
+    /// words read by the left and right lines
+    pub fn set_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        row: &Ripemd160Row<F>,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), Error> {
+        // The first rows are dummy rows only queried as the previous block
+        let (q_enable, idx) = if offset < NUM_END_ROWS {
+            (false, 0)
+        } else {
+            (true, (offset - NUM_END_ROWS) % NUM_ROWS_PER_BLOCK)
+        };
+        let round = idx
+            .checked_sub(NUM_START_ROWS)
+            .filter(|r| q_enable && *r < NUM_ROUNDS);
+        let is_round = |f: fn(usize) -> bool| round.map_or(false, f);
+        let is_end = q_enable && idx >= NUM_START_ROWS + NUM_ROUNDS;
+        let group = round.map(|r| r / NUM_ROUNDS_PER_GROUP);
+        let rotation = |s: &[usize; NUM_ROUNDS]| round.map(|r| s[r] - MIN_ROTATION);
+
+        // Fixed selectors
+        for (name, column, value) in [
+            ("q_enable", self.q_enable, q_enable),
+            ("q_first", self.q_first, offset == NUM_END_ROWS),
+            ("q_start", self.q_start, q_enable && idx == 0),
+            ("q_round", self.q_round, is_round(|_| true)),
+            (
+                "q_input",
+                self.q_input,
+                is_round(|r| r < NUM_WORDS_TO_ABSORB),
+            ),
+            (
+                "q_input_last",
+                self.q_input_last,
+                is_round(|r| r == NUM_WORDS_TO_ABSORB - 1),
+            ),
+            (
+                "q_length",
+                self.q_length,
+                is_round(|r| r >= NUM_WORDS_TO_ABSORB - 2 && r < NUM_WORDS_TO_ABSORB),
+            ),
+            (
+                "q_end",
+                self.q_end,
+                is_end && idx == NUM_START_ROWS + NUM_ROUNDS,
+            ),
+            (
+                "q_end_last",
+                self.q_end_last,
+                is_end && idx == NUM_ROWS_PER_BLOCK - 1,
+            ),
+        ]
+        .into_iter()
+        .chain(
+            self.q_groups
+                .iter()
+                .enumerate()
+                .map(|(g, column)| ("q_group", *column, group == Some(g))),
+        )
+        .chain(
+            self.q_rotations_left
+                .iter()
+                .enumerate()
+                .map(|(s, column)| ("q_rotation_left", *column, rotation(&S_LEFT) == Some(s))),
+        )
+        .chain(
+            self.q_rotations_right
+                .iter()
+                .enumerate()
+                .map(|(s, column)| ("q_rotation_right", *column, rotation(&S_RIGHT) == Some(s))),
+        ) {
+            region.assign_fixed(
+                || format!("assign {name} {offset}"),
+                column,
+                offset,
+                || Value::known(F::from(value)),
+            )?;
+        }
+        region.assign_fixed(
+            || format!("assign h {offset}"),
+            self.h,
+            offset,
+            || {
+                Value::known(F::from(if q_enable && idx < NUM_START_ROWS {
+                    H[H_ORDER[idx]] as u64
+                } else {
+                    0
+                }))
+            },
+        )?;
+
+        // Table values
+        self.ripemd160_table.assign_row(
+            region,
+            offset,
+            [
+                Value::known(F::from(row.is_final)),
+                row.data_rlc,
+                Value::known(F::from(row.length as u64)),
+                row.hash_rlc,
+            ],
+        )?;
+
+        // Bits
+        for (name, columns, bits) in [
+            ("x", &self.word_x[..], &row.x[..]),
+            ("sum_left", &self.sum_left[..], &row.sum_left[..]),
+            ("sum_right", &self.sum_right[..], &row.sum_right[..]),
+            ("word_left", &self.word_left[..], &row.word_left[..]),
+            ("word_right", &self.word_right[..], &row.word_right[..]),
+            ("is_padding", &self.is_paddings[..], &row.is_paddings[..]),
+            (
+                "is_final_block",
+                &[self.is_final_block][..],
+                &[row.is_final_block][..],
+            ),
+        ] {
+            for (idx, (column, bit)) in columns.iter().zip(bits.iter()).enumerate() {
+                region.assign_advice(
+                    || format!("assign {name} {idx} {offset}"),
+                    *column,
+                    offset,
+                    || Value::known(F::from(*bit)),
+                )?;
+            }
+        }
+        for (idx, (column, rlc)) in self.data_rlcs.iter().zip(row.data_rlcs.iter()).enumerate() {
+            region.assign_advice(
+                || format!("assign data rlc {idx} {offset}"),
+                *column,
+                offset,
+                || *rlc,
+            )?;
+        }
+
+        let x_left = region.assign_advice(
+            || format!("assign x_left {offset}"),
+            self.x_left,
+            offset,
+            || Value::known(F::from(row.x_left as u64)),
+        )?;
+        let x_right = region.assign_advice(
+            || format!("assign x_right {offset}"),
+            self.x_right,
+            offset,
+            || Value::known(F::from(row.x_right as u64)),
+        )?;
+
+        Ok((x_left, x_right))
+    }
+
+    /// Annotate the circuit
+    pub fn annotate_circuit(&self, region: &mut Region<F>) {
+        region.name_column(|| "RIPEMD160_q_first", self.q_first);
+        region.name_column(|| "RIPEMD160_q_start", self.q_start);
+        region.name_column(|| "RIPEMD160_q_round", self.q_round);
+        region.name_column(|| "RIPEMD160_q_input", self.q_input);
+        region.name_column(|| "RIPEMD160_q_input_last", self.q_input_last);
+        region.name_column(|| "RIPEMD160_q_length", self.q_length);
+        region.name_column(|| "RIPEMD160_q_end", self.q_end);
+        region.name_column(|| "RIPEMD160_q_end_last", self.q_end_last);
+        region.name_column(|| "RIPEMD160_h", self.h);
+        region.name_column(|| "RIPEMD160_x_left", self.x_left);
+        region.name_column(|| "RIPEMD160_x_right", self.x_right);
+        region.name_column(|| "RIPEMD160_is_final_block", self.is_final_block);
+    }
+}
+
+/// Ripemd160Circuit
+#[derive(Default, Clone, Debug)]
+pub struct Ripemd160Circuit<F: Field> {
+    // Each input is a pre-image of the hash
+    inputs: Vec<Vec<u8>>,
+    // The maximum number of rows, for example, 2^20
+    num_rows: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> SubCircuit<F> for Ripemd160Circuit<F> {
+    type Config = Ripemd160CircuitConfig<F>;
+
+    fn unusable_rows() -> usize {
+        // The bits of the left line words are queried at 15 distinct
+        // rotations, so returns (15 - 3) + 6 unusable rows.
+        18
+    }
+
+    fn new_from_block(block: &witness::Block<F>) -> Self {
+        Self::new(
+            block.circuits_params.max_ripemd160_rows,
+            block
+                .get_ripemd160()
+                .into_iter()
+                .map(|event| event.input)
+                .collect(),
+        )
+    }
+
+    /// Return the minimum number of rows required to prove the block
+    fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
+        let rows = block
+            .get_ripemd160()
+            .iter()
+            .map(|event| num_blocks(event.input.len()) * NUM_ROWS_PER_BLOCK)
+            .sum::<usize>()
+            + NUM_END_ROWS;
+        (rows, rows.max(block.circuits_params.max_ripemd160_rows))
+    }
+
+    /// Make the assignments to the Ripemd160Circuit
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        challenges: &Challenges<Value<F>>,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let witness = self.generate_witness(*challenges);
+        config.assign(layouter, witness.as_slice())
+    }
+}
+
+impl<F: Field> SubCircuitRowEstimator<F> for Ripemd160Circuit<F> {
+    fn base_rows(_params: &CircuitsParams) -> usize {
+        NUM_END_ROWS
+    }
+
+    fn marginal_rows(delta: &WitnessDelta, _params: &CircuitsParams) -> usize {
+        match delta {
+            WitnessDelta::PrecompileEvent(PrecompileEvent::Ripemd160(event)) => {
+                num_blocks(event.input.len()) * NUM_ROWS_PER_BLOCK
+            }
+            _ => 0,
+        }
+    }
+}
+
+impl<F: Field> Ripemd160Circuit<F> {
+    /// Creates a new circuit instance
+    pub fn new(num_rows: usize, inputs: Vec<Vec<u8>>) -> Self {
+        Ripemd160Circuit {
+            inputs,
+            num_rows,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The number of 64-byte blocks that can be hashed in this circuit
+    pub fn capacity(&self) -> Option<usize> {
+        Self::capacity_for_row(self.num_rows)
+    }
+
+    /// The number of 64-byte blocks that can be hashed for a particular row
+    /// number
+    pub fn capacity_for_row(num_rows: usize) -> Option<usize> {
+        if num_rows > 0 {
+            Some(num_rows.saturating_sub(NUM_END_ROWS + Self::unusable_rows()) / NUM_ROWS_PER_BLOCK)
+        } else {
+            None
+        }
+    }
+
+    /// Sets the witness using the data to be hashed
+    pub(crate) fn generate_witness(
+        &self,
+        challenges: Challenges<Value<F>>,
+    ) -> Vec<Ripemd160Row<F>> {
+        multi_ripemd160(self.inputs.as_slice(), challenges, self.capacity())
+            .expect("Too many inputs for given capacity")
+    }
+}
@@ -0,0 +1,252 @@
+use super::{param::*, util::into_bits};
+use crate::{evm_circuit::util::rlc, util::Challenges};
+use eth_types::Field;
+use halo2_proofs::{circuit::Value, plonk::Error};
+use log::debug;
+
+/// Ripemd160Row
+#[derive(Clone, Debug)]
+pub struct Ripemd160Row<F> {
+    pub(crate) x: [bool; NUM_BITS_PER_WORD],
+    pub(crate) x_left: u32,
+    pub(crate) x_right: u32,
+    pub(crate) sum_left: [bool; NUM_BITS_PER_WORD_EXT],
+    pub(crate) sum_right: [bool; NUM_BITS_PER_WORD_EXT],
+    pub(crate) word_left: [bool; NUM_BITS_PER_WORD_EXT],
+    pub(crate) word_right: [bool; NUM_BITS_PER_WORD_EXT],
+    /// if the row is the last row of the last block of a hash
+    pub is_final: bool,
+    pub(crate) is_final_block: bool,
+    /// The input length of the hash function
+    pub length: usize,
+    pub(crate) data_rlc: Value<F>,
+    pub(crate) hash_rlc: Value<F>,
+    pub(crate) is_paddings: [bool; NUM_BYTES_PER_WORD],
+    pub(crate) data_rlcs: [Value<F>; NUM_BYTES_PER_WORD],
+}
+
+impl<F: Field> Ripemd160Row<F> {
+    fn dummy() -> Self {
+        Self {
+            x: [false; NUM_BITS_PER_WORD],
+            x_left: 0,
+            x_right: 0,
+            sum_left: [false; NUM_BITS_PER_WORD_EXT],
+            sum_right: [false; NUM_BITS_PER_WORD_EXT],
+            word_left: [false; NUM_BITS_PER_WORD_EXT],
+            word_right: [false; NUM_BITS_PER_WORD_EXT],
+            is_final: false,
+            is_final_block: false,
+            length: 0,
+            data_rlc: Value::known(F::zero()),
+            hash_rlc: Value::known(F::zero()),
+            is_paddings: [false; NUM_BYTES_PER_WORD],
+            data_rlcs: [Value::known(F::zero()); NUM_BYTES_PER_WORD],
+        }
+    }
+}
+
+/// The number of 64-byte blocks required to hash `len` bytes
+pub fn num_blocks(len: usize) -> usize {
+    (len + 1 + NUM_BYTES_PADDING_LENGTH + RATE - 1) / RATE
+}
+
+fn f(group: usize, x: u32, y: u32, z: u32) -> u32 {
+    match group {
+        0 => x ^ y ^ z,
+        1 => (x & y) | (!x & z),
+        2 => (x | !y) ^ z,
+        3 => (x & z) | (y & !z),
+        4 => x ^ (y | !z),
+        _ => unreachable!(),
+    }
+}
+
+/// The working registers `[A, B, C, D, E]` of a line, read back from the
+/// words produced by its last five rounds.
+fn registers(words: &[u64]) -> [u32; 5] {
+    let word = |rot: usize| words[words.len() - rot] as u32;
+    [
+        word(5).rotate_left(ROTATION_C as u32),
+        word(1),
+        word(2),
+        word(3).rotate_left(ROTATION_C as u32),
+        word(4).rotate_left(ROTATION_C as u32),
+    ]
+}
+
+fn ripemd160<F: Field>(
+    rows: &mut Vec<Ripemd160Row<F>>,
+    bytes: &[u8],
+    challenges: Challenges<Value<F>>,
+) {
+    let mut padded = bytes.to_vec();
+    padded.push(0x80);
+    while padded.len() % RATE != RATE - NUM_BYTES_PADDING_LENGTH {
+        padded.push(0);
+    }
+    padded.extend_from_slice(&((bytes.len() as u64) * 8).to_le_bytes());
+
+    let mut hs = H;
+    let mut length = 0usize;
+    let mut data_rlc = Value::known(F::zero());
+    let mut in_padding = false;
+    let num_blocks = padded.len() / RATE;
+
+    for (idx, block) in padded.chunks(RATE).enumerate() {
+        let is_final_block = idx == num_blocks - 1;
+        let xs = block
+            .chunks(NUM_BYTES_PER_WORD)
+            .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+            .collect::<Vec<_>>();
+        let row = |word_left: u64,
+                   word_right: u64,
+                   length: usize,
+                   data_rlc: Value<F>,
+                   in_padding: bool| Ripemd160Row {
+            word_left: into_bits(word_left),
+            word_right: into_bits(word_right),
+            is_final_block,
+            length,
+            data_rlc,
+            is_paddings: [in_padding; NUM_BYTES_PER_WORD],
+            ..Ripemd160Row::dummy()
+        };
+
+        // Start rows: the words from which the registers of the first round
+        // are read
+        let start = H_ORDER
+            .iter()
+            .enumerate()
+            .map(|(i, &h)| {
+                if i < NUM_ROTATED_START_ROWS {
+                    hs[h].rotate_right(ROTATION_C as u32) as u64
+                } else {
+                    hs[h] as u64
+                }
+            })
+            .collect::<Vec<_>>();
+        for word in start.iter() {
+            rows.push(row(*word, *word, length, data_rlc, in_padding));
+        }
+
+        // Rounds
+        let mut words_left = start.clone();
+        let mut words_right = start;
+        for r in 0..NUM_ROUNDS {
+            let group = r / NUM_ROUNDS_PER_GROUP;
+            let [a, b, c, d, e] = registers(&words_left);
+            let sum_left =
+                a as u64 + f(group, b, c, d) as u64 + xs[R_LEFT[r]] as u64 + K_LEFT[group] as u64;
+            let word_left = (sum_left as u32).rotate_left(S_LEFT[r] as u32) as u64 + e as u64;
+            let [a, b, c, d, e] = registers(&words_right);
+            let sum_right = a as u64
+                + f(NUM_GROUPS - 1 - group, b, c, d) as u64
+                + xs[R_RIGHT[r]] as u64
+                + K_RIGHT[group] as u64;
+            let word_right = (sum_right as u32).rotate_left(S_RIGHT[r] as u32) as u64 + e as u64;
+            words_left.push(word_left);
+            words_right.push(word_right);
+
+            let (x, is_paddings, data_rlcs) = if r < NUM_WORDS_TO_ABSORB {
+                let mut is_paddings = [false; NUM_BYTES_PER_WORD];
+                let mut data_rlcs = [Value::known(F::zero()); NUM_BYTES_PER_WORD];
+                for k in 0..NUM_BYTES_PER_WORD {
+                    let byte_idx = idx * RATE + r * NUM_BYTES_PER_WORD + k;
+                    in_padding = byte_idx >= bytes.len();
+                    if !in_padding {
+                        length += 1;
+                        let byte = bytes[byte_idx];
+                        data_rlc = data_rlc
+                            .zip(challenges.keccak_input())
+                            .map(|(acc, r)| acc * r + F::from(byte as u64));
+                    }
+                    is_paddings[k] = in_padding;
+                    data_rlcs[k] = data_rlc;
+                }
+                (into_bits(xs[r] as u64), is_paddings, data_rlcs)
+            } else {
+                (
+                    [false; NUM_BITS_PER_WORD],
+                    [in_padding; NUM_BYTES_PER_WORD],
+                    [Value::known(F::zero()); NUM_BYTES_PER_WORD],
+                )
+            };
+            rows.push(Ripemd160Row {
+                x,
+                x_left: xs[R_LEFT[r]],
+                x_right: xs[R_RIGHT[r]],
+                sum_left: into_bits(sum_left),
+                sum_right: into_bits(sum_right),
+                is_paddings,
+                data_rlcs,
+                ..row(word_left, word_right, length, data_rlc, in_padding)
+            });
+        }
+
+        // End rows: the chaining value after this block
+        let [a, b, c, d, e] = registers(&words_left);
+        let [a_r, b_r, c_r, d_r, e_r] = registers(&words_right);
+        let new_hs_ext = [
+            hs[1] as u64 + c as u64 + d_r as u64,
+            hs[2] as u64 + d as u64 + e_r as u64,
+            hs[3] as u64 + e as u64 + a_r as u64,
+            hs[4] as u64 + a as u64 + b_r as u64,
+            hs[0] as u64 + b as u64 + c_r as u64,
+        ];
+        hs = new_hs_ext.map(|h| h as u32);
+        for (k, &h) in H_ORDER.iter().enumerate() {
+            let is_last = k == NUM_END_ROWS - 1;
+            let hash_rlc = if is_final_block && is_last {
+                let digest = hs.iter().flat_map(|h| h.to_le_bytes()).collect::<Vec<_>>();
+                challenges
+                    .keccak_input()
+                    .map(|r| rlc::value(digest.iter().rev(), r))
+            } else {
+                Value::known(F::zero())
+            };
+            rows.push(Ripemd160Row {
+                is_final: is_final_block && is_last,
+                hash_rlc,
+                ..row(new_hs_ext[h], 0, length, data_rlc, in_padding)
+            });
+        }
+    }
+}
+
+/// Generates the witness rows for hashing `bytes` with RIPEMD-160.
+/// When a capacity (in blocks) is given, the rows are padded with hashes of
+/// the empty input so that the fixed layout only depends on the capacity.
+pub fn multi_ripemd160<F: Field>(
+    bytes: &[Vec<u8>],
+    challenges: Challenges<Value<F>>,
+    capacity: Option<usize>,
+) -> Result<Vec<Ripemd160Row<F>>, Error> {
+    let num_blocks_used = bytes.iter().map(|b| num_blocks(b.len())).sum::<usize>();
+    if let Some(capacity) = capacity {
+        if num_blocks_used > capacity {
+            log::error!(
+                "ripemd160 circuit capacity exceeded: {} blocks needed, capacity {}",
+                num_blocks_used,
+                capacity
+            );
+            return Err(Error::BoundsFailure);
+        }
+    }
+
+    let mut rows: Vec<Ripemd160Row<F>> = Vec::new();
+    // Dummy rows before the first block, queried as the "previous block"
+    for _ in 0..NUM_END_ROWS {
+        rows.push(Ripemd160Row::dummy());
+    }
+    for bytes in bytes {
+        ripemd160(&mut rows, bytes, challenges);
+    }
+    debug!("ripemd160 rows len without padding: {}", rows.len());
+    if let Some(capacity) = capacity {
+        for _ in num_blocks_used..capacity {
+            ripemd160(&mut rows, &[], challenges);
+        }
+    }
+    Ok(rows)
+}
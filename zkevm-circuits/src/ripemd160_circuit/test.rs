@@ -0,0 +1,67 @@
+use super::*;
+use crate::{evm_circuit::util::rlc, util::unusable_rows};
+use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+use log::error;
+
+#[test]
+fn ripemd160_circuit_unusable_rows() {
+    assert_eq!(
+        Ripemd160Circuit::<Fr>::unusable_rows(),
+        unusable_rows::<Fr, Ripemd160Circuit::<Fr>>(),
+    )
+}
+
+fn verify<F: Field>(k: u32, inputs: Vec<Vec<u8>>, success: bool) {
+    let circuit = Ripemd160Circuit::new(2usize.pow(k), inputs);
+
+    let prover = MockProver::<F>::run(k, &circuit, vec![]).unwrap();
+    let verify_result = prover.verify();
+    if verify_result.is_ok() != success {
+        if let Some(errors) = verify_result.err() {
+            for error in errors.iter() {
+                error!("{}", error);
+            }
+        }
+        panic!();
+    }
+}
+
+#[test]
+fn ripemd160_circuit_simple() {
+    let inputs = vec![
+        vec![],
+        b"abc".to_vec(),
+        (0u8..55).collect::<Vec<_>>(),
+        (0u8..56).collect::<Vec<_>>(),
+        (0u8..64).collect::<Vec<_>>(),
+        (0u8..200).collect::<Vec<_>>(),
+    ];
+    verify::<Fr>(12, inputs, true);
+}
+
+#[test]
+fn ripemd160_circuit_no_inputs() {
+    verify::<Fr>(10, vec![], true);
+}
+
+#[test]
+fn ripemd160_witness_matches_digest() {
+    use ripemd::{Digest, Ripemd160};
+
+    let challenges = Challenges::mock(
+        Value::known(Fr::from(7)),
+        Value::known(Fr::from(11)),
+        Value::known(Fr::from(13)),
+    );
+    let input = (0u8..130).collect::<Vec<_>>();
+    let rows = multi_ripemd160::<Fr>(&[input.clone()], challenges, None).unwrap();
+    assert_eq!(rows.len(), NUM_END_ROWS + 3 * NUM_ROWS_PER_BLOCK);
+
+    let digest = Ripemd160::digest(&input);
+    let expected = rlc::value(digest.iter().rev(), Fr::from(11));
+    let last = rows.last().unwrap();
+    assert!(last.is_final);
+    assert_eq!(last.length, input.len());
+    last.hash_rlc
+        .assert_if_known(|hash_rlc| *hash_rlc == expected);
+}
@@ -0,0 +1,52 @@
+use super::param::*;
+use eth_types::Field;
+use gadgets::util::Expr;
+use halo2_proofs::plonk::Expression;
+
+use crate::util::precompile_hash::xor;
+pub(crate) use crate::util::precompile_hash::{decode, decode_byte, into_bits, query_bits};
+
+/// Rotates the 32 lowest bits to the left
+pub(crate) fn rotl<F: Field>(bits: &[Expression<F>], n: usize) -> Vec<Expression<F>> {
+    (0..NUM_BITS_PER_WORD)
+        .map(|idx| bits[(idx + NUM_BITS_PER_WORD - n) % NUM_BITS_PER_WORD].clone())
+        .collect()
+}
+
+fn or<F: Field>(a: Expression<F>, b: Expression<F>) -> Expression<F> {
+    a.clone() + b.clone() - a * b
+}
+
+fn not<F: Field>(a: Expression<F>) -> Expression<F> {
+    1.expr() - a
+}
+
+/// The bitwise boolean function of the `group`-th group of rounds:
+/// - 0: `x ^ y ^ z`
+/// - 1: `(x & y) | (!x & z)`
+/// - 2: `(x | !y) ^ z`
+/// - 3: `(x & z) | (y & !z)`
+/// - 4: `x ^ (y | !z)`
+pub(crate) fn f<F: Field>(
+    group: usize,
+    x: &[Expression<F>],
+    y: &[Expression<F>],
+    z: &[Expression<F>],
+) -> Vec<Expression<F>> {
+    x.iter()
+        .zip(y.iter())
+        .zip(z.iter())
+        .take(NUM_BITS_PER_WORD)
+        .map(|((x, y), z)| {
+            let (x, y, z) = (x.clone(), y.clone(), z.clone());
+            match group {
+                0 => xor(xor(x, y), z),
+                1 => x.clone() * y + not(x) * z,
+                2 => xor(or(x, not(y)), z),
+                3 => x * z.clone() + y * not(z),
+                4 => xor(x, or(y, not(z))),
+                _ => unreachable!(),
+            }
+        })
+        .collect()
+}
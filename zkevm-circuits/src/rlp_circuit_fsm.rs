@@ -7,10 +7,10 @@ mod test;
 
 use crate::{
     evm_circuit::util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
-    table::{LookupTable, RlpFsmRlpTable, U8Table},
+    table::{LookupTable, RlpFsmRlpTable, TxFieldTag, TxTable, U8Table},
     util::{
         is_zero::{IsZeroChip, IsZeroConfig},
-        Challenges, SubCircuit, SubCircuitConfig,
+        Challenges, SubCircuit, SubCircuitConfig, SubCircuitRowEstimator,
     },
     witness::{
         Block, DataTable, Format, RlpFsmWitnessGen, RlpFsmWitnessRow, RlpTag, RomTableRow, State,
@@ -20,7 +20,8 @@ use crate::{
         Transaction,
     },
 };
-use eth_types::Field;
+use bus_mapping::circuit_input_builder::CircuitsParams;
+use eth_types::{evm_types::MAX_BLOBS_PER_TX, Field};
 use gadgets::{
     binary_number::{BinaryNumberChip, BinaryNumberConfig},
     comparator::{ComparatorChip, ComparatorConfig, ComparatorInstruction},
@@ -197,7 +198,7 @@ pub struct RlpCircuitConfig<F> {
     /// The tag, i.e. what field is being decoded at the current row.
     tag: Column<Advice>,
     /// A utility gadget to compare/query what tag we are at.
-    tag_bits: BinaryNumberConfig<Tag, 5>,
+    tag_bits: BinaryNumberConfig<Tag, 6>,
     /// The tag that will be decoded next after the current tag is done decoding.
     tag_next: Column<Advice>,
     /// Boolean check whether or not the current tag represents a list or not.
@@ -237,10 +238,16 @@ pub struct RlpCircuitConfig<F> {
     /// Check equality between format' and format in sm.
     format_check_in_sm: IsEqualConfig<F>,
 
-    /// Booleans to reduce the circuit's degree as tag_bits's degree is 5.
+    /// Booleans to reduce the circuit's degree as tag_bits's degree is 6.
     is_tag_end: Column<Advice>,
     is_tag_begin: Column<Advice>,
     /// Boolean to reduce the circuit's degree
+    /// is_blob_versioned_hash = tag in [BlobVersionedHash0, ..., BlobVersionedHash5]
+    is_blob_versioned_hash: Column<Advice>,
+    /// Boolean to reduce the circuit's degree
+    /// is_not_end = (state != End)
+    is_not_end: Column<Advice>,
+    /// Boolean to reduce the circuit's degree
     /// is_case3 = (0xc0 <= byte_value < 0xf8) && (is_tag_end == false)
     is_case3: Column<Advice>,
     /// Boolean to reduce the circuit's degree
@@ -285,6 +292,8 @@ pub struct RlpCircuitConfig<F> {
     rom_table: RlpFsmRomTable,
     /// Range u8 table
     u8_table: U8Table,
+    /// Tx table, against which the blob versioned hashes are checked
+    tx_table: TxTable,
 }
 
 impl<F: Field> RlpCircuitConfig<F> {
@@ -295,6 +304,7 @@ impl<F: Field> RlpCircuitConfig<F> {
         data_table: RlpFsmDataTable,
         u8_table: U8Table,
         rlp_table: RlpFsmRlpTable,
+        tx_table: TxTable,
         challenges: &Challenges<Expression<F>>,
     ) -> Self {
         let (tx_id, format) = (rlp_table.tx_id, rlp_table.format);
@@ -316,6 +326,8 @@ impl<F: Field> RlpCircuitConfig<F> {
             depth,
             is_tag_begin,
             is_tag_end,
+            is_blob_versioned_hash,
+            is_not_end,
             is_case3,
             transit_to_new_rlp_instance,
             is_same_rlp_instance,
@@ -338,6 +350,8 @@ impl<F: Field> RlpCircuitConfig<F> {
             meta.advice_column(),
             meta.advice_column(),
             meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
         );
 
         let tag_value_acc = meta.advice_column_in(SecondPhase);
@@ -622,6 +636,32 @@ impl<F: Field> RlpCircuitConfig<F> {
             .collect()
         });
 
+        // The tx circuit looks up the first BlobVersionedHashesLength hashes of a tx in the RLP
+        // table. Each hash decoded here must also be in the tx table at the index given by its
+        // tag, which bounds the number of hashes by BlobVersionedHashesLength as the tx table
+        // holds zero beyond it.
+        meta.lookup_any("blob versioned hash in tx table", |meta| {
+            let cond = and::expr([
+                meta.query_fixed(q_enabled, Rotation::cur()),
+                meta.query_advice(rlp_table.is_output, Rotation::cur()),
+                meta.query_advice(is_blob_versioned_hash, Rotation::cur()),
+            ]);
+            let index = meta.query_advice(tag, Rotation::cur())
+                - usize::from(Tag::blob_versioned_hash(0)).expr();
+
+            vec![
+                1.expr(), // q_enable = true
+                meta.query_advice(tx_id, Rotation::cur()),
+                TxFieldTag::BlobVersionedHash.expr(),
+                index,
+                meta.query_advice(rlp_table.tag_value, Rotation::cur()),
+            ]
+            .into_iter()
+            .zip_eq(tx_table.table_exprs(meta).into_iter())
+            .map(|(arg, table)| (cond.expr() * arg, table))
+            .collect()
+        });
+
         debug_assert!(meta.degree() <= 9);
 
         ///////////////////////////////////////////////////////////////////
@@ -802,6 +842,13 @@ impl<F: Field> RlpCircuitConfig<F> {
                 meta.query_advice(is_tag_begin, Rotation::cur()),
                 sum::expr([is_tag_begin_list(meta), is_tag_begin_vector(meta)]),
             );
+            cb.require_equal(
+                "is_blob_versioned_hash = tag in [BlobVersionedHash0, ..., BlobVersionedHash5]",
+                meta.query_advice(is_blob_versioned_hash, Rotation::cur()),
+                sum::expr((0..MAX_BLOBS_PER_TX).map(|index| {
+                    tag_bits.value_equals(Tag::blob_versioned_hash(index), Rotation::cur())(meta)
+                })),
+            );
             cb.require_equal(
                 "is_case3 = (0xc0 <= byte_value < 0xf8) && (is_tag_end == false)",
                 meta.query_advice(is_case3, Rotation::cur()),
@@ -814,12 +861,18 @@ impl<F: Field> RlpCircuitConfig<F> {
 
             cb.gate(and::expr([
                 meta.query_fixed(q_enabled, Rotation::cur()),
-                not::expr(is_end(meta)),
+                meta.query_advice(is_not_end, Rotation::cur()),
             ]))
         });
         meta.create_gate("booleans for reducing degree (part two)", |meta| {
             let mut cb = BaseConstraintBuilder::default();
 
+            cb.require_equal(
+                "is_not_end = (state != End)",
+                meta.query_advice(is_not_end, Rotation::cur()),
+                not::expr(is_end(meta)),
+            );
+
             cb.require_equal(
                 "transit_to_new = (is_tag_end == true) && (depth == 0) && (state' != End)",
                 meta.query_advice(transit_to_new_rlp_instance, Rotation::cur()),
@@ -1435,6 +1488,8 @@ impl<F: Field> RlpCircuitConfig<F> {
 
             is_tag_begin,
             is_tag_end,
+            is_blob_versioned_hash,
+            is_not_end,
             is_case3,
             transit_to_new_rlp_instance,
             is_same_rlp_instance,
@@ -1459,6 +1514,7 @@ impl<F: Field> RlpCircuitConfig<F> {
             data_table,
             rom_table,
             u8_table,
+            tx_table,
         }
     }
 
@@ -1628,6 +1684,22 @@ impl<F: Field> RlpCircuitConfig<F> {
             row,
             || Value::known(F::from(witness.state_machine.tag.is_end() as u64)),
         )?;
+        region.assign_advice(
+            || "is_blob_versioned_hash",
+            self.is_blob_versioned_hash,
+            row,
+            || {
+                Value::known(F::from(
+                    witness.state_machine.tag.is_blob_versioned_hash() as u64
+                ))
+            },
+        )?;
+        region.assign_advice(
+            || "is_not_end",
+            self.is_not_end,
+            row,
+            || Value::known(F::one()),
+        )?;
         region.assign_advice(
             || "is_case3",
             self.is_case3,
@@ -1895,6 +1967,8 @@ pub struct RlpCircuitConfigArgs<F: Field> {
     pub rlp_table: RlpFsmRlpTable,
     /// u8 table
     pub u8_table: U8Table,
+    /// Tx table
+    pub tx_table: TxTable,
     /// Challenge API.
     pub challenges: Challenges<Expression<F>>,
 }
@@ -1912,6 +1986,7 @@ impl<F: Field> SubCircuitConfig<F> for RlpCircuitConfig<F> {
             data_table,
             args.u8_table,
             args.rlp_table,
+            args.tx_table,
             &args.challenges,
         )
     }
@@ -1988,3 +2063,16 @@ impl<F: Field> SubCircuit<F> for RlpCircuit<F, Transaction> {
         (sm_rows, max_num_rows)
     }
 }
+
+impl<F: Field> SubCircuitRowEstimator<F> for RlpCircuit<F, Transaction> {
+    fn marginal_rows(delta: &WitnessDelta, _params: &CircuitsParams) -> usize {
+        match delta {
+            WitnessDelta::Tx(tx) => {
+                let challenges: Challenges<Value<F>> =
+                    Challenges::mock(Value::unknown(), Value::unknown(), Value::unknown());
+                tx.gen_sm_witness(&challenges).len()
+            }
+            _ => 0,
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
     rlp_circuit_fsm::{RlpCircuit, RlpCircuitConfig, RlpCircuitConfigArgs},
-    table::{RlpFsmRlpTable, U8Table},
+    table::{RlpFsmRlpTable, TxTable, U8Table},
     util::{Challenges, SubCircuit, SubCircuitConfig},
     witness::Transaction,
 };
@@ -23,12 +23,14 @@ impl<F: Field> Circuit<F> for RlpCircuit<F, Transaction> {
         let challenges = Challenges::construct(meta);
         let challenge_exprs = challenges.exprs(meta);
         let u8_table = U8Table::construct(meta);
+        let tx_table = TxTable::construct(meta);
 
         let config = RlpCircuitConfig::new(
             meta,
             RlpCircuitConfigArgs {
                 rlp_table,
                 u8_table,
+                tx_table,
                 challenges: challenge_exprs,
             },
         );
@@ -44,6 +46,14 @@ impl<F: Field> Circuit<F> for RlpCircuit<F, Transaction> {
     ) -> Result<(), Error> {
         let challenges = &config.1.values(&layouter);
         config.0.u8_table.load(&mut layouter)?;
+        config.0.tx_table.load(
+            &mut layouter,
+            &self.txs,
+            self.max_txs,
+            0, // max_calldata
+            0, // chain id
+            challenges,
+        )?;
 
         self.synthesize_sub(&config.0, challenges, &mut layouter)
     }
@@ -1,6 +1,9 @@
 #![allow(unused_imports)]
 use crate::{rlp_circuit_fsm::RlpCircuit, witness::Transaction};
-use eth_types::{geth_types::TxType, word, Address};
+use eth_types::{
+    geth_types::{get_blob_versioned_hashes, get_rlp_signed, get_rlp_unsigned, TxType},
+    word, Address,
+};
 use ethers_core::{
     types::{
         transaction::eip2718::TypedTransaction, Eip1559TransactionRequest,
@@ -166,3 +169,96 @@ fn test_eip2930_tx() {
 
     mock_prover.assert_satisfied_par();
 }
+
+#[cfg(test)]
+fn get_eip4844_tx() -> Transaction {
+    let eth_tx: EthTransaction = serde_json::from_value(serde_json::json!({
+        "type": "0x3",
+        "hash": "0x3e2a4c9a6f8fb2d5ad8b2c1e4f1dd3b4d2fbd5b0b9f33b2aa5bcc7ee1e3e6e4f",
+        "chainId": "0x1",
+        "nonce": "0x7f",
+        "maxPriorityFeePerGas": "0x3b9aca00",
+        "maxFeePerGas": "0x4a817c800",
+        "gas": "0x77320",
+        "from": "0x0000000000000000000000000000000000cafe01",
+        "to": "0x5f65f7b609678448494de4c87521cdf6cef1e932",
+        "value": "0x0",
+        "input": "0xfa558b71",
+        "accessList": [{
+            "address": "0x95ad61b0a150d79219dcf64e1e6cc01f0b64c4ce",
+            "storageKeys": [
+                "0xba03decd934aae936605e9d437c401439ec4cefbad5795e0965100f929fe339c"
+            ]
+        }],
+        "maxFeePerBlobGas": "0x3",
+        "blobVersionedHashes": [
+            "0x01a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8",
+            "0x0100c3a9b4b1ab4c6f2fe0d9e5b6c0d36cd0d3c2bbf5f4b8e9a1f6c7d8e9f0a1"
+        ],
+        "v": "0x1",
+        "r": "0x821193127789b107351f670025dd3b862f5836e5155f627a29741a251e8d28e8",
+        "s": "0x7ea1e82b1bf6f29c5d0f1e4024acdb698086ac40c353704d7d5e301fb916f2e3"
+    }))
+    .expect("deserialize blob tx shall not fail");
+    assert_eq!(TxType::get_tx_type(&eth_tx), TxType::Eip4844);
+
+    let mut tx = Transaction::new_from_rlp_bytes(
+        TxType::Eip4844,
+        get_rlp_signed(&eth_tx),
+        get_rlp_unsigned(&eth_tx),
+    );
+    // the decoded hashes are looked up in the tx table
+    tx.blob_versioned_hashes = get_blob_versioned_hashes(&eth_tx);
+
+    tx
+}
+
+#[test]
+fn test_eip4844_tx() {
+    let tx = get_eip4844_tx();
+    let rlp_circuit = RlpCircuit::<Fr, Transaction> {
+        txs: vec![tx],
+        max_txs: 10,
+        size: 1000,
+        _marker: Default::default(),
+    };
+
+    let mock_prover = MockProver::run(14, &rlp_circuit, vec![]);
+    assert!(mock_prover.is_ok());
+    let mock_prover = mock_prover.unwrap();
+    if let Err(errors) = mock_prover.verify_par() {
+        log::debug!("errors.len() = {}", errors.len());
+    }
+
+    mock_prover.assert_satisfied_par();
+}
+
+#[test]
+fn test_eip4844_tx_blob_hashes_out_of_order() {
+    let mut tx = get_eip4844_tx();
+    tx.blob_versioned_hashes.reverse();
+    let rlp_circuit = RlpCircuit::<Fr, Transaction> {
+        txs: vec![tx],
+        max_txs: 10,
+        size: 1000,
+        _marker: Default::default(),
+    };
+
+    let mock_prover = MockProver::run(14, &rlp_circuit, vec![]).unwrap();
+    assert!(mock_prover.verify_par().is_err());
+}
+
+#[test]
+fn test_eip4844_tx_blob_hashes_truncated() {
+    let mut tx = get_eip4844_tx();
+    tx.blob_versioned_hashes.pop();
+    let rlp_circuit = RlpCircuit::<Fr, Transaction> {
+        txs: vec![tx],
+        max_txs: 10,
+        size: 1000,
+        _marker: Default::default(),
+    };
+
+    let mock_prover = MockProver::run(14, &rlp_circuit, vec![]).unwrap();
+    assert!(mock_prover.verify_par().is_err());
+}
@@ -0,0 +1,690 @@
+//! The SHA-256 circuit implementation, used to verify the calls to the SHA2-256
+//! precompiled contract.
+mod param;
+/// Sha256 bit-level witness generation
+pub mod sha256_bit;
+mod util;
+
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+mod dev;
+#[cfg(any(feature = "test", test))]
+mod test;
+
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+pub use dev::Sha256Circuit as TestSha256Circuit;
+
+use std::marker::PhantomData;
+
+use self::{
+    param::*,
+    sha256_bit::{multi_sha256, num_blocks, ShaRow},
+    util::*,
+};
+use crate::{
+    evm_circuit::util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+    table::{LookupTable, Sha256Table},
+    util::{Challenges, SubCircuit, SubCircuitConfig, SubCircuitRowEstimator, WitnessDelta},
+    witness,
+};
+use bus_mapping::circuit_input_builder::{CircuitsParams, PrecompileEvent};
+use eth_types::Field;
+use gadgets::util::{not, select, sum, Expr};
+use halo2_proofs::{
+    circuit::{Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, VirtualCells},
+    poly::Rotation,
+};
+
+#[cfg(feature = "onephase")]
+use halo2_proofs::plonk::FirstPhase as SecondPhase;
+#[cfg(not(feature = "onephase"))]
+use halo2_proofs::plonk::SecondPhase;
+
+/// Sha256Config
+#[derive(Clone, Debug)]
+pub struct Sha256CircuitConfig<F> {
+    q_enable: Column<Fixed>,
+    q_first: Column<Fixed>,
+    q_start: Column<Fixed>,
+    q_extend: Column<Fixed>,
+    q_compression: Column<Fixed>,
+    q_input: Column<Fixed>,
+    q_input_last: Column<Fixed>,
+    q_length: Column<Fixed>,
+    q_end: Column<Fixed>,
+    q_end_last: Column<Fixed>,
+    round_cst: Column<Fixed>,
+    h_a: Column<Fixed>,
+    h_e: Column<Fixed>,
+    word_w: [Column<Advice>; NUM_BITS_PER_WORD_W],
+    word_a: [Column<Advice>; NUM_BITS_PER_WORD_EXT],
+    word_e: [Column<Advice>; NUM_BITS_PER_WORD_EXT],
+    is_final_block: Column<Advice>,
+    is_paddings: [Column<Advice>; NUM_BYTES_PER_WORD],
+    data_rlcs: [Column<Advice>; NUM_BYTES_PER_WORD],
+    /// The columns for other circuits to lookup SHA-256 hash results
+    pub sha256_table: Sha256Table,
+    _marker: PhantomData<F>,
+}
+
+/// Circuit configuration arguments
+#[derive(Debug, Clone)]
+pub struct Sha256CircuitConfigArgs<F: Field> {
+    /// Sha256Table
+    pub sha256_table: Sha256Table,
+    /// Challenges randomness
+    pub challenges: Challenges<Expression<F>>,
+}
+
+impl<F: Field> SubCircuitConfig<F> for Sha256CircuitConfig<F> {
+    type ConfigArgs = Sha256CircuitConfigArgs<F>;
+
+    /// Return a new Sha256CircuitConfig
+    fn new(
+        meta: &mut ConstraintSystem<F>,
+        Self::ConfigArgs {
+            sha256_table,
+            challenges,
+        }: Self::ConfigArgs,
+    ) -> Self {
+        let q_enable = sha256_table.q_enable;
+        let q_first = meta.fixed_column();
+        let q_start = meta.fixed_column();
+        let q_extend = meta.fixed_column();
+        let q_compression = meta.fixed_column();
+        let q_input = meta.fixed_column();
+        let q_input_last = meta.fixed_column();
+        let q_length = meta.fixed_column();
+        let q_end = meta.fixed_column();
+        let q_end_last = meta.fixed_column();
+        let round_cst = meta.fixed_column();
+        let h_a = meta.fixed_column();
+        let h_e = meta.fixed_column();
+        let word_w = array_init::array_init(|_| meta.advice_column());
+        let word_a = array_init::array_init(|_| meta.advice_column());
+        let word_e = array_init::array_init(|_| meta.advice_column());
+        let is_final_block = meta.advice_column();
+        let is_paddings = array_init::array_init(|_| meta.advice_column());
+        let data_rlcs = array_init::array_init(|_| meta.advice_column_in(SecondPhase));
+
+        let is_final = sha256_table.is_final;
+        let length = sha256_table.input_len;
+        let data_rlc = sha256_table.input_rlc;
+        let hash_rlc = sha256_table.output_rlc;
+
+        // A new hash is started on the first block or after the final block of the
+        // previous hash
+        let start_new_hash = |meta: &mut VirtualCells<F>| {
+            let q_first = meta.query_fixed(q_first, Rotation::cur());
+            q_first.clone() + not::expr(q_first) * meta.query_advice(is_final, Rotation::prev())
+        };
+
+        meta.create_gate("boolean checks", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            for column in word_w
+                .iter()
+                .chain(word_a.iter())
+                .chain(word_e.iter())
+                .chain(is_paddings.iter())
+                .chain(std::iter::once(&is_final_block))
+            {
+                cb.require_boolean("boolean value", meta.query_advice(*column, Rotation::cur()));
+            }
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("block flags", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let q_start = meta.query_fixed(q_start, Rotation::cur());
+            let q_end_last = meta.query_fixed(q_end_last, Rotation::cur());
+            let is_final_block_cur = meta.query_advice(is_final_block, Rotation::cur());
+            let is_final_block_prev = meta.query_advice(is_final_block, Rotation::prev());
+            cb.require_equal(
+                "is_final is only set on the last row of the final block",
+                meta.query_advice(is_final, Rotation::cur()),
+                q_end_last * is_final_block_cur.clone(),
+            );
+            cb.condition(not::expr(q_start), |cb| {
+                cb.require_equal(
+                    "is_final_block is constant within a block",
+                    is_final_block_cur,
+                    is_final_block_prev,
+                );
+            });
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("message schedule", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let w_ext = query_bits(meta, &word_w, 0);
+            let w_2 = query_bits(meta, &word_w, -2);
+            let w_7 = query_bits(meta, &word_w, -7);
+            let w_15 = query_bits(meta, &word_w, -15);
+            let w_16 = query_bits(meta, &word_w, -16);
+            cb.condition(meta.query_fixed(q_extend, Rotation::cur()), |cb| {
+                cb.require_equal(
+                    "w = σ1(w[-2]) + w[-7] + σ0(w[-15]) + w[-16]",
+                    decode(&w_ext),
+                    decode(&lower_sigma_1(&w_2))
+                        + decode(&w_7[..NUM_BITS_PER_WORD])
+                        + decode(&lower_sigma_0(&w_15))
+                        + decode(&w_16[..NUM_BITS_PER_WORD]),
+                );
+            });
+            cb.condition(meta.query_fixed(q_input, Rotation::cur()), |cb| {
+                for bit in w_ext[NUM_BITS_PER_WORD..].iter() {
+                    cb.require_zero("input words have no carry", bit.clone());
+                }
+            });
+            cb.gate(1.expr())
+        });
+
+        meta.create_gate("compression", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let new_a_ext = query_bits(meta, &word_a, 0);
+            let new_e_ext = query_bits(meta, &word_e, 0);
+            let [a, b, c, d] = [-1, -2, -3, -4].map(|rot| query_bits(meta, &word_a, rot));
+            let [e, f, g, h] = [-1, -2, -3, -4].map(|rot| query_bits(meta, &word_e, rot));
+            let w = query_bits(meta, &word_w, 0);
+            let round_cst = meta.query_fixed(round_cst, Rotation::cur());
+
+            let t1 = decode(&h[..NUM_BITS_PER_WORD])
+                + decode(&upper_sigma_1(&e))
+                + decode(&ch(&e, &f, &g))
+                + round_cst
+                + decode(&w[..NUM_BITS_PER_WORD]);
+            let t2 = decode(&upper_sigma_0(&a)) + decode(&maj(&a, &b, &c));
+            cb.require_equal("new a = t1 + t2", decode(&new_a_ext), t1.clone() + t2);
+            cb.require_equal(
+                "new e = d + t1",
+                decode(&new_e_ext),
+                decode(&d[..NUM_BITS_PER_WORD]) + t1,
+            );
+            cb.gate(meta.query_fixed(q_compression, Rotation::cur()))
+        });
+
+        meta.create_gate("start", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let start_new_hash = start_new_hash(meta);
+            for j in 0..NUM_START_ROWS as i32 {
+                for (word, h) in [(word_a, h_a), (word_e, h_e)] {
+                    let value = query_bits(meta, &word, j);
+                    let prev = query_bits(meta, &word, j - NUM_END_ROWS as i32);
+                    cb.require_equal(
+                        "start from the initial hash or the previous block",
+                        decode(&value),
+                        select::expr(
+                            start_new_hash.clone(),
+                            meta.query_fixed(h, Rotation(j)),
+                            decode(&prev[..NUM_BITS_PER_WORD]),
+                        ),
+                    );
+                }
+            }
+            let is_padding_prev = meta.query_advice(is_paddings[3], Rotation::prev());
+            for is_padding in is_paddings.iter() {
+                cb.require_equal(
+                    "padding is reset on a new hash",
+                    meta.query_advice(*is_padding, Rotation::cur()),
+                    not::expr(start_new_hash.clone()) * is_padding_prev.clone(),
+                );
+            }
+            for column in [length, data_rlc] {
+                cb.require_equal(
+                    "length and data rlc are reset on a new hash",
+                    meta.query_advice(column, Rotation::cur()),
+                    not::expr(start_new_hash.clone()) * meta.query_advice(column, Rotation::prev()),
+                );
+            }
+            cb.gate(meta.query_fixed(q_start, Rotation::cur()))
+        });
+
+        meta.create_gate("end", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            for word in [word_a, word_e] {
+                let value = query_bits(meta, &word, 0);
+                let state = query_bits(meta, &word, -(NUM_END_ROWS as i32));
+                let hash = query_bits(
+                    meta,
+                    &word,
+                    -(NUM_ROWS_PER_BLOCK as i32 - NUM_END_ROWS as i32),
+                );
+                cb.require_equal(
+                    "intermediate hash = state + previous hash",
+                    decode(&value),
+                    decode(&state[..NUM_BITS_PER_WORD]) + decode(&hash[..NUM_BITS_PER_WORD]),
+                );
+            }
+            cb.gate(meta.query_fixed(q_end, Rotation::cur()))
+        });
+
+        meta.create_gate("input", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let w = query_bits(meta, &word_w, 0);
+            let q_length = meta.query_fixed(q_length, Rotation::cur());
+            let is_final_block = meta.query_advice(is_final_block, Rotation::cur());
+            let r = challenges.keccak_input();
+
+            let mut is_padding_prev = meta.query_advice(is_paddings[3], Rotation::prev());
+            let mut data_rlc_prev = meta.query_advice(data_rlc, Rotation::prev());
+            for (idx, (is_padding, rlc)) in is_paddings.iter().zip(data_rlcs.iter()).enumerate() {
+                let is_padding = meta.query_advice(*is_padding, Rotation::cur());
+                let rlc = meta.query_advice(*rlc, Rotation::cur());
+                let byte = decode_byte(&w, idx);
+                cb.require_zero(
+                    "padding is monotonic",
+                    is_padding_prev.clone() * not::expr(is_padding.clone()),
+                );
+                cb.require_zero(
+                    "the first padding byte is 0x80",
+                    (is_padding.clone() - is_padding_prev.clone()) * (byte.clone() - 0x80.expr()),
+                );
+                cb.require_zero(
+                    "the other padding bytes are 0 except for the length",
+                    is_padding_prev.clone()
+                        * byte.clone()
+                        * not::expr(q_length.clone() * is_final_block.clone()),
+                );
+                cb.require_equal(
+                    "data rlc only absorbs non padding bytes",
+                    rlc.clone(),
+                    select::expr(
+                        is_padding.clone(),
+                        data_rlc_prev.clone(),
+                        data_rlc_prev * r.clone() + byte,
+                    ),
+                );
+                is_padding_prev = is_padding;
+                data_rlc_prev = rlc;
+            }
+            cb.require_equal(
+                "data rlc",
+                meta.query_advice(data_rlc, Rotation::cur()),
+                data_rlc_prev,
+            );
+            cb.require_equal(
+                "length only counts non padding bytes",
+                meta.query_advice(length, Rotation::cur()),
+                meta.query_advice(length, Rotation::prev())
+                    + sum::expr(
+                        is_paddings
+                            .iter()
+                            .map(|column| not::expr(meta.query_advice(*column, Rotation::cur()))),
+                    ),
+            );
+            cb.gate(meta.query_fixed(q_input, Rotation::cur()))
+        });
+
+        meta.create_gate("non input", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let is_padding_prev = meta.query_advice(is_paddings[3], Rotation::prev());
+            for is_padding in is_paddings.iter() {
+                cb.require_equal(
+                    "padding state is kept",
+                    meta.query_advice(*is_padding, Rotation::cur()),
+                    is_padding_prev.clone(),
+                );
+            }
+            for column in [length, data_rlc] {
+                cb.require_equal(
+                    "length and data rlc are kept",
+                    meta.query_advice(column, Rotation::cur()),
+                    meta.query_advice(column, Rotation::prev()),
+                );
+            }
+            cb.gate(
+                meta.query_fixed(q_enable, Rotation::cur())
+                    - meta.query_fixed(q_input, Rotation::cur())
+                    - meta.query_fixed(q_start, Rotation::cur()),
+            )
+        });
+
+        meta.create_gate("length", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let is_final_block = meta.query_advice(is_final_block, Rotation::cur());
+            // The final block is the one that has space for the 8 length bytes
+            // after the first padding byte.
+            cb.require_equal(
+                "final block iff padding started before the length words",
+                is_final_block.clone(),
+                meta.query_advice(is_paddings[3], Rotation(-2)),
+            );
+            cb.condition(is_final_block, |cb| {
+                let w_hi = query_bits(meta, &word_w, -1);
+                let w_lo = query_bits(meta, &word_w, 0);
+                cb.require_equal(
+                    "padded length in bits",
+                    decode(&w_hi[..NUM_BITS_PER_WORD]) * (1u64 << NUM_BITS_PER_WORD).expr()
+                        + decode(&w_lo[..NUM_BITS_PER_WORD]),
+                    meta.query_advice(length, Rotation::cur()) * 8.expr(),
+                );
+            });
+            cb.gate(meta.query_fixed(q_input_last, Rotation::cur()))
+        });
+
+        meta.create_gate("hash rlc", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let r = challenges.keccak_input();
+            let hs = (0..NUM_END_ROWS as i32)
+                .map(|rot| query_bits(meta, &word_a, -rot))
+                .chain((0..NUM_END_ROWS as i32).map(|rot| query_bits(meta, &word_e, -rot)))
+                .collect::<Vec<_>>();
+            let hash_bytes_rlc = hs
+                .iter()
+                .flat_map(|h| (0..NUM_BYTES_PER_WORD).map(|idx| decode_byte(h, idx)))
+                .fold(0.expr(), |acc, byte| acc * r.clone() + byte);
+            cb.condition(meta.query_advice(is_final_block, Rotation::cur()), |cb| {
+                cb.require_equal(
+                    "hash rlc",
+                    meta.query_advice(hash_rlc, Rotation::cur()),
+                    hash_bytes_rlc,
+                );
+            });
+            cb.gate(meta.query_fixed(q_end_last, Rotation::cur()))
+        });
+
+        log::info!("sha256 circuit degree: {}", meta.degree());
+
+        Sha256CircuitConfig {
+            q_enable,
+            q_first,
+            q_start,
+            q_extend,
+            q_compression,
+            q_input,
+            q_input_last,
+            q_length,
+            q_end,
+            q_end_last,
+            round_cst,
+            h_a,
+            h_e,
+            word_w,
+            word_a,
+            word_e,
+            is_final_block,
+            is_paddings,
+            data_rlcs,
+            sha256_table,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Field> Sha256CircuitConfig<F> {
+    /// Assign the circuit for hash function
+    pub(crate) fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        witness: &[ShaRow<F>],
+    ) -> Result<(), Error> {
+        let mut is_first_time = true;
+        layouter.assign_region(
+            || "assign sha256 rows",
+            |mut region| {
+                if is_first_time {
+                    is_first_time = false;
+                    let offset = witness.len() - 1;
+                    self.set_row(&mut region, offset, &witness[offset])?;
+                    return Ok(());
+                }
+                for (offset, sha256_row) in witness.iter().enumerate() {
+                    self.set_row(&mut region, offset, sha256_row)?;
+                }
+                self.sha256_table.annotate_columns_in_region(&mut region);
+                self.annotate_circuit(&mut region);
+                Ok(())
+            },
+        )
+    }
+
+    /// Set the cells for a sha256 row
+    pub fn set_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        row: &ShaRow<F>,
+    ) -> Result<(), Error> {
+        // The first rows are dummy rows only queried as the previous block
+        let (q_enable, idx) = if offset < NUM_END_ROWS {
+            (false, 0)
+        } else {
+            (true, (offset - NUM_END_ROWS) % NUM_ROWS_PER_BLOCK)
+        };
+        let round = idx.checked_sub(NUM_START_ROWS).filter(|r| *r < NUM_ROUNDS);
+        let is_round = |f: fn(usize) -> bool| q_enable && round.map_or(false, f);
+        let is_end = q_enable && idx >= NUM_START_ROWS + NUM_ROUNDS;
+
+        // Fixed selectors
+        for (name, column, value) in [
+            ("q_enable", self.q_enable, q_enable),
+            ("q_first", self.q_first, offset == NUM_END_ROWS),
+            ("q_start", self.q_start, q_enable && idx == 0),
+            (
+                "q_extend",
+                self.q_extend,
+                is_round(|r| r >= NUM_WORDS_TO_ABSORB),
+            ),
+            ("q_compression", self.q_compression, is_round(|_| true)),
+            (
+                "q_input",
+                self.q_input,
+                is_round(|r| r < NUM_WORDS_TO_ABSORB),
+            ),
+            (
+                "q_input_last",
+                self.q_input_last,
+                is_round(|r| r == NUM_WORDS_TO_ABSORB - 1),
+            ),
+            (
+                "q_length",
+                self.q_length,
+                is_round(|r| r >= NUM_WORDS_TO_ABSORB - 2 && r < NUM_WORDS_TO_ABSORB),
+            ),
+            ("q_end", self.q_end, is_end),
+            (
+                "q_end_last",
+                self.q_end_last,
+                is_end && idx == NUM_ROWS_PER_BLOCK - 1,
+            ),
+        ] {
+            region.assign_fixed(
+                || format!("assign {name} {offset}"),
+                column,
+                offset,
+                || Value::known(F::from(value)),
+            )?;
+        }
+        for (name, column, value) in [
+            (
+                "round_cst",
+                self.round_cst,
+                round.filter(|_| q_enable).map_or(0, |r| ROUND_CST[r]),
+            ),
+            (
+                "h_a",
+                self.h_a,
+                if q_enable && idx < NUM_START_ROWS {
+                    H[3 - idx]
+                } else {
+                    0
+                },
+            ),
+            (
+                "h_e",
+                self.h_e,
+                if q_enable && idx < NUM_START_ROWS {
+                    H[7 - idx]
+                } else {
+                    0
+                },
+            ),
+        ] {
+            region.assign_fixed(
+                || format!("assign {name} {offset}"),
+                column,
+                offset,
+                || Value::known(F::from(value as u64)),
+            )?;
+        }
+
+        // Table values
+        self.sha256_table.assign_row(
+            region,
+            offset,
+            [
+                Value::known(F::from(row.is_final)),
+                row.data_rlc,
+                Value::known(F::from(row.length as u64)),
+                row.hash_rlc,
+            ],
+        )?;
+
+        // Bits
+        for (name, columns, bits) in [
+            ("w", &self.word_w[..], &row.w[..]),
+            ("a", &self.word_a[..], &row.a[..]),
+            ("e", &self.word_e[..], &row.e[..]),
+            ("is_padding", &self.is_paddings[..], &row.is_paddings[..]),
+            (
+                "is_final_block",
+                &[self.is_final_block][..],
+                &[row.is_final_block][..],
+            ),
+        ] {
+            for (idx, (column, bit)) in columns.iter().zip(bits.iter()).enumerate() {
+                region.assign_advice(
+                    || format!("assign {name} {idx} {offset}"),
+                    *column,
+                    offset,
+                    || Value::known(F::from(*bit)),
+                )?;
+            }
+        }
+        for (idx, (column, rlc)) in self.data_rlcs.iter().zip(row.data_rlcs.iter()).enumerate() {
+            region.assign_advice(
+                || format!("assign data rlc {idx} {offset}"),
+                *column,
+                offset,
+                || *rlc,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Annotate the circuit
+    pub fn annotate_circuit(&self, region: &mut Region<F>) {
+        region.name_column(|| "SHA256_q_first", self.q_first);
+        region.name_column(|| "SHA256_q_start", self.q_start);
+        region.name_column(|| "SHA256_q_extend", self.q_extend);
+        region.name_column(|| "SHA256_q_compression", self.q_compression);
+        region.name_column(|| "SHA256_q_input", self.q_input);
+        region.name_column(|| "SHA256_q_input_last", self.q_input_last);
+        region.name_column(|| "SHA256_q_length", self.q_length);
+        region.name_column(|| "SHA256_q_end", self.q_end);
+        region.name_column(|| "SHA256_q_end_last", self.q_end_last);
+        region.name_column(|| "SHA256_round_cst", self.round_cst);
+        region.name_column(|| "SHA256_is_final_block", self.is_final_block);
+    }
+}
+
+/// Sha256Circuit
+#[derive(Default, Clone, Debug)]
+pub struct Sha256Circuit<F: Field> {
+    // Each input is a pre-image of the hash
+    inputs: Vec<Vec<u8>>,
+    // The maximum number of rows, for example, 2^20
+    num_rows: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> SubCircuit<F> for Sha256Circuit<F> {
+    type Config = Sha256CircuitConfig<F>;
+
+    fn unusable_rows() -> usize {
+        // The bits of `a` and `e` are queried at 9 distinct rotations, so
+        // returns (9 - 3) + 6 unusable rows.
+        12
+    }
+
+    fn new_from_block(block: &witness::Block<F>) -> Self {
+        Self::new(
+            block.circuits_params.max_sha256_rows,
+            block
+                .get_sha256()
+                .into_iter()
+                .map(|event| event.input)
+                .collect(),
+        )
+    }
+
+    /// Return the minimum number of rows required to prove the block
+    fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
+        let rows = block
+            .get_sha256()
+            .iter()
+            .map(|event| num_blocks(event.input.len()) * NUM_ROWS_PER_BLOCK)
+            .sum::<usize>()
+            + NUM_END_ROWS;
+        (rows, rows.max(block.circuits_params.max_sha256_rows))
+    }
+
+    /// Make the assignments to the Sha256Circuit
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        challenges: &Challenges<Value<F>>,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let witness = self.generate_witness(*challenges);
+        config.assign(layouter, witness.as_slice())
+    }
+}
+
+impl<F: Field> SubCircuitRowEstimator<F> for Sha256Circuit<F> {
+    fn base_rows(_params: &CircuitsParams) -> usize {
+        NUM_END_ROWS
+    }
+
+    fn marginal_rows(delta: &WitnessDelta, _params: &CircuitsParams) -> usize {
+        match delta {
+            WitnessDelta::PrecompileEvent(PrecompileEvent::Sha256(event)) => {
+                num_blocks(event.input.len()) * NUM_ROWS_PER_BLOCK
+            }
+            _ => 0,
+        }
+    }
+}
+
+impl<F: Field> Sha256Circuit<F> {
+    /// Creates a new circuit instance
+    pub fn new(num_rows: usize, inputs: Vec<Vec<u8>>) -> Self {
+        Sha256Circuit {
+            inputs,
+            num_rows,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The number of 64-byte blocks that can be hashed in this circuit
+    pub fn capacity(&self) -> Option<usize> {
+        Self::capacity_for_row(self.num_rows)
+    }
+
+    /// The number of 64-byte blocks that can be hashed for a particular row
+    /// number
+    pub fn capacity_for_row(num_rows: usize) -> Option<usize> {
+        if num_rows > 0 {
+            Some(num_rows.saturating_sub(NUM_END_ROWS + Self::unusable_rows()) / NUM_ROWS_PER_BLOCK)
+        } else {
+            None
+        }
+    }
+
+    /// Sets the witness using the data to be hashed
+    pub(crate) fn generate_witness(&self, challenges: Challenges<Value<F>>) -> Vec<ShaRow<F>> {
+        multi_sha256(self.inputs.as_slice(), challenges, self.capacity())
+            .expect("Too many inputs for given capacity")
+    }
+}
@@ -0,0 +1,47 @@
+pub use super::Sha256Circuit;
+
+use crate::{
+    sha256_circuit::{Sha256CircuitConfig, Sha256CircuitConfigArgs},
+    table::Sha256Table,
+    util::{Challenges, SubCircuit, SubCircuitConfig},
+};
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+impl<F: Field> Circuit<F> for Sha256Circuit<F> {
+    type Config = (Sha256CircuitConfig<F>, Challenges);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let sha256_table = Sha256Table::construct(meta);
+        let challenges = Challenges::construct(meta);
+
+        let config = {
+            let challenges = challenges.exprs(meta);
+            Sha256CircuitConfig::new(
+                meta,
+                Sha256CircuitConfigArgs {
+                    sha256_table,
+                    challenges,
+                },
+            )
+        };
+        (config, challenges)
+    }
+
+    fn synthesize(
+        &self,
+        (config, challenges): Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let challenges = challenges.values(&layouter);
+        self.synthesize_sub(&config, &challenges, &mut layouter)
+    }
+}
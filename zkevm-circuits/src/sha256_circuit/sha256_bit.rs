@@ -0,0 +1,247 @@
+use super::{param::*, util::into_bits};
+use crate::{evm_circuit::util::rlc, util::Challenges};
+use eth_types::Field;
+use halo2_proofs::{circuit::Value, plonk::Error};
+use log::debug;
+
+/// ShaRow
+#[derive(Clone, Debug)]
+pub struct ShaRow<F> {
+    pub(crate) w: [bool; NUM_BITS_PER_WORD_W],
+    pub(crate) a: [bool; NUM_BITS_PER_WORD_EXT],
+    pub(crate) e: [bool; NUM_BITS_PER_WORD_EXT],
+    /// if the row is the last row of the last block of a hash
+    pub is_final: bool,
+    pub(crate) is_final_block: bool,
+    /// The input length of the hash function
+    pub length: usize,
+    pub(crate) data_rlc: Value<F>,
+    pub(crate) hash_rlc: Value<F>,
+    pub(crate) is_paddings: [bool; NUM_BYTES_PER_WORD],
+    pub(crate) data_rlcs: [Value<F>; NUM_BYTES_PER_WORD],
+}
+
+impl<F: Field> ShaRow<F> {
+    fn dummy() -> Self {
+        Self {
+            w: [false; NUM_BITS_PER_WORD_W],
+            a: [false; NUM_BITS_PER_WORD_EXT],
+            e: [false; NUM_BITS_PER_WORD_EXT],
+            is_final: false,
+            is_final_block: false,
+            length: 0,
+            data_rlc: Value::known(F::zero()),
+            hash_rlc: Value::known(F::zero()),
+            is_paddings: [false; NUM_BYTES_PER_WORD],
+            data_rlcs: [Value::known(F::zero()); NUM_BYTES_PER_WORD],
+        }
+    }
+}
+
+/// The number of 64-byte blocks required to hash `len` bytes
+pub fn num_blocks(len: usize) -> usize {
+    (len + 1 + NUM_BYTES_PADDING_LENGTH + RATE - 1) / RATE
+}
+
+fn upper_sigma_0(v: u32) -> u32 {
+    v.rotate_right(2) ^ v.rotate_right(13) ^ v.rotate_right(22)
+}
+
+fn upper_sigma_1(v: u32) -> u32 {
+    v.rotate_right(6) ^ v.rotate_right(11) ^ v.rotate_right(25)
+}
+
+fn lower_sigma_0(v: u32) -> u32 {
+    v.rotate_right(7) ^ v.rotate_right(18) ^ (v >> 3)
+}
+
+fn lower_sigma_1(v: u32) -> u32 {
+    v.rotate_right(17) ^ v.rotate_right(19) ^ (v >> 10)
+}
+
+fn sha256<F: Field>(rows: &mut Vec<ShaRow<F>>, bytes: &[u8], challenges: Challenges<Value<F>>) {
+    let mut padded = bytes.to_vec();
+    padded.push(0x80);
+    while padded.len() % RATE != RATE - NUM_BYTES_PADDING_LENGTH {
+        padded.push(0);
+    }
+    padded.extend_from_slice(&((bytes.len() as u64) * 8).to_be_bytes());
+
+    let mut hs = H;
+    let mut length = 0usize;
+    let mut data_rlc = Value::known(F::zero());
+    let mut in_padding = false;
+    let num_blocks = padded.len() / RATE;
+
+    for (idx, block) in padded.chunks(RATE).enumerate() {
+        let is_final_block = idx == num_blocks - 1;
+        let mut add_row = |w: u64,
+                           a: u64,
+                           e: u64,
+                           is_final: bool,
+                           length: usize,
+                           data_rlc: Value<F>,
+                           hash_rlc: Value<F>,
+                           is_paddings: [bool; NUM_BYTES_PER_WORD],
+                           data_rlcs: [Value<F>; NUM_BYTES_PER_WORD]| {
+            rows.push(ShaRow {
+                w: into_bits(w),
+                a: into_bits(a),
+                e: into_bits(e),
+                is_final,
+                is_final_block,
+                length,
+                data_rlc,
+                hash_rlc,
+                is_paddings,
+                data_rlcs,
+            });
+        };
+        let no_rlcs = [Value::known(F::zero()); NUM_BYTES_PER_WORD];
+
+        // Start rows: the working variables before the first round
+        for j in 0..NUM_START_ROWS {
+            add_row(
+                0,
+                hs[3 - j] as u64,
+                hs[7 - j] as u64,
+                false,
+                length,
+                data_rlc,
+                Value::known(F::zero()),
+                [in_padding; NUM_BYTES_PER_WORD],
+                no_rlcs,
+            );
+        }
+
+        // Message schedule
+        let mut ws = [0u32; NUM_ROUNDS];
+        let mut ws_ext = [0u64; NUM_ROUNDS];
+        for (r, chunk) in block.chunks(NUM_BYTES_PER_WORD).enumerate() {
+            ws[r] = u32::from_be_bytes(chunk.try_into().unwrap());
+            ws_ext[r] = ws[r] as u64;
+        }
+        for r in NUM_WORDS_TO_ABSORB..NUM_ROUNDS {
+            ws_ext[r] = lower_sigma_1(ws[r - 2]) as u64
+                + ws[r - 7] as u64
+                + lower_sigma_0(ws[r - 15]) as u64
+                + ws[r - 16] as u64;
+            ws[r] = ws_ext[r] as u32;
+        }
+
+        // Rounds
+        let mut state = hs;
+        for r in 0..NUM_ROUNDS {
+            let [a, b, c, d, e, f, g, h] = state;
+            let ch = (e & f) ^ (!e & g);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t1 =
+                h as u64 + upper_sigma_1(e) as u64 + ch as u64 + ROUND_CST[r] as u64 + ws[r] as u64;
+            let t2 = upper_sigma_0(a) as u64 + maj as u64;
+            let new_a_ext = t1 + t2;
+            let new_e_ext = d as u64 + t1;
+            state = [new_a_ext as u32, a, b, c, new_e_ext as u32, e, f, g];
+
+            let (is_paddings, data_rlcs) = if r < NUM_WORDS_TO_ABSORB {
+                let mut is_paddings = [false; NUM_BYTES_PER_WORD];
+                let mut data_rlcs = no_rlcs;
+                for k in 0..NUM_BYTES_PER_WORD {
+                    let byte_idx = idx * RATE + r * NUM_BYTES_PER_WORD + k;
+                    in_padding = byte_idx >= bytes.len();
+                    if !in_padding {
+                        length += 1;
+                        let byte = bytes[byte_idx];
+                        data_rlc = data_rlc
+                            .zip(challenges.keccak_input())
+                            .map(|(acc, r)| acc * r + F::from(byte as u64));
+                    }
+                    is_paddings[k] = in_padding;
+                    data_rlcs[k] = data_rlc;
+                }
+                (is_paddings, data_rlcs)
+            } else {
+                ([in_padding; NUM_BYTES_PER_WORD], no_rlcs)
+            };
+            add_row(
+                ws_ext[r],
+                new_a_ext,
+                new_e_ext,
+                false,
+                length,
+                data_rlc,
+                Value::known(F::zero()),
+                is_paddings,
+                data_rlcs,
+            );
+        }
+
+        // End rows: the intermediate hash after this block
+        let mut new_hs = hs;
+        for (h, s) in new_hs.iter_mut().zip(state.iter()) {
+            *h = h.wrapping_add(*s);
+        }
+        for j in 0..NUM_END_ROWS {
+            let is_last = j == NUM_END_ROWS - 1;
+            let hash_rlc = if is_final_block && is_last {
+                let digest = new_hs
+                    .iter()
+                    .flat_map(|h| h.to_be_bytes())
+                    .collect::<Vec<_>>();
+                challenges
+                    .keccak_input()
+                    .map(|r| rlc::value(digest.iter().rev(), r))
+            } else {
+                Value::known(F::zero())
+            };
+            add_row(
+                0,
+                state[3 - j] as u64 + hs[3 - j] as u64,
+                state[7 - j] as u64 + hs[7 - j] as u64,
+                is_final_block && is_last,
+                length,
+                data_rlc,
+                hash_rlc,
+                [in_padding; NUM_BYTES_PER_WORD],
+                no_rlcs,
+            );
+        }
+        hs = new_hs;
+    }
+}
+
+/// Generates the witness rows for hashing `bytes` with SHA-256.
+/// When a capacity (in blocks) is given, the rows are padded with hashes of
+/// the empty input so that the fixed layout only depends on the capacity.
+pub fn multi_sha256<F: Field>(
+    bytes: &[Vec<u8>],
+    challenges: Challenges<Value<F>>,
+    capacity: Option<usize>,
+) -> Result<Vec<ShaRow<F>>, Error> {
+    let num_blocks_used = bytes.iter().map(|b| num_blocks(b.len())).sum::<usize>();
+    if let Some(capacity) = capacity {
+        if num_blocks_used > capacity {
+            log::error!(
+                "sha256 circuit capacity exceeded: {} blocks needed, capacity {}",
+                num_blocks_used,
+                capacity
+            );
+            return Err(Error::BoundsFailure);
+        }
+    }
+
+    let mut rows: Vec<ShaRow<F>> = Vec::new();
+    // Dummy rows before the first block, queried as the "previous block"
+    for _ in 0..NUM_END_ROWS {
+        rows.push(ShaRow::dummy());
+    }
+    for bytes in bytes {
+        sha256(&mut rows, bytes, challenges);
+    }
+    debug!("sha256 rows len without padding: {}", rows.len());
+    if let Some(capacity) = capacity {
+        for _ in num_blocks_used..capacity {
+            sha256(&mut rows, &[], challenges);
+        }
+    }
+    Ok(rows)
+}
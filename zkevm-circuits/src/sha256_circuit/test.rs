@@ -0,0 +1,78 @@
+use super::*;
+use crate::{evm_circuit::util::rlc, util::unusable_rows};
+use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+use log::error;
+
+#[test]
+fn sha256_circuit_unusable_rows() {
+    assert_eq!(
+        Sha256Circuit::<Fr>::unusable_rows(),
+        unusable_rows::<Fr, Sha256Circuit::<Fr>>(),
+    )
+}
+
+fn verify<F: Field>(k: u32, inputs: Vec<Vec<u8>>, success: bool) {
+    let circuit = Sha256Circuit::new(2usize.pow(k), inputs);
+
+    let prover = MockProver::<F>::run(k, &circuit, vec![]).unwrap();
+    let verify_result = prover.verify();
+    if verify_result.is_ok() != success {
+        if let Some(errors) = verify_result.err() {
+            for error in errors.iter() {
+                error!("{}", error);
+            }
+        }
+        panic!();
+    }
+}
+
+#[test]
+fn sha256_circuit_simple() {
+    let inputs = vec![
+        vec![],
+        (0u8..1).collect::<Vec<_>>(),
+        (0u8..55).collect::<Vec<_>>(),
+        (0u8..56).collect::<Vec<_>>(),
+        (0u8..64).collect::<Vec<_>>(),
+        (0u8..200).collect::<Vec<_>>(),
+    ];
+    verify::<Fr>(12, inputs, true);
+}
+
+#[test]
+fn sha256_circuit_multi_block() {
+    // The words of the later rounds and blocks carry out of 32 bits.
+    let inputs = vec![
+        vec![0xff; 64],
+        vec![0xff; 300],
+        (0u8..=255).cycle().take(1000).collect::<Vec<_>>(),
+    ];
+    verify::<Fr>(12, inputs, true);
+}
+
+#[test]
+fn sha256_circuit_no_inputs() {
+    verify::<Fr>(10, vec![], true);
+}
+
+#[test]
+fn sha256_witness_matches_digest() {
+    use sha2::{Digest, Sha256};
+
+    let challenges = Challenges::mock(
+        Value::known(Fr::from(7)),
+        Value::known(Fr::from(11)),
+        Value::known(Fr::from(13)),
+    );
+    let input = (0u8..130).collect::<Vec<_>>();
+    let rows = multi_sha256::<Fr>(&[input.clone()], challenges, None).unwrap();
+    assert_eq!(rows.len(), NUM_END_ROWS + 3 * NUM_ROWS_PER_BLOCK);
+
+    let digest = Sha256::digest(&input);
+    let expected = rlc::value(digest.iter().rev(), Fr::from(11));
+    let last = rows.last().unwrap();
+    assert!(last.is_final);
+    assert_eq!(last.length, input.len());
+    last.hash_rlc
+        .assert_if_known(|hash_rlc| *hash_rlc == expected);
+}
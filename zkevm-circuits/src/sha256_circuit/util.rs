@@ -0,0 +1,97 @@
+use super::param::*;
+use eth_types::Field;
+use gadgets::util::Expr;
+use halo2_proofs::plonk::Expression;
+
+use crate::util::precompile_hash::xor;
+pub(crate) use crate::util::precompile_hash::{decode, into_bits, query_bits};
+
+/// Decodes the `idx`-th big-endian byte of a word given as little-endian bits
+pub(crate) fn decode_byte<F: Field>(bits: &[Expression<F>], idx: usize) -> Expression<F> {
+    let start = (NUM_BYTES_PER_WORD - 1 - idx) * NUM_BITS_PER_BYTE;
+    decode(&bits[start..start + NUM_BITS_PER_BYTE])
+}
+
+/// Rotates the 32 lowest bits to the right
+pub(crate) fn rotr<F: Field>(bits: &[Expression<F>], n: usize) -> Vec<Expression<F>> {
+    (0..NUM_BITS_PER_WORD)
+        .map(|idx| bits[(idx + n) % NUM_BITS_PER_WORD].clone())
+        .collect()
+}
+
+/// Shifts the 32 lowest bits to the right
+pub(crate) fn shr<F: Field>(bits: &[Expression<F>], n: usize) -> Vec<Expression<F>> {
+    (0..NUM_BITS_PER_WORD)
+        .map(|idx| {
+            if idx + n < NUM_BITS_PER_WORD {
+                bits[idx + n].clone()
+            } else {
+                0.expr()
+            }
+        })
+        .collect()
+}
+
+/// Bitwise `a ^ b ^ c`
+pub(crate) fn xor3<F: Field>(
+    a: &[Expression<F>],
+    b: &[Expression<F>],
+    c: &[Expression<F>],
+) -> Vec<Expression<F>> {
+    a.iter()
+        .zip(b.iter())
+        .zip(c.iter())
+        .map(|((a, b), c)| xor(xor(a.clone(), b.clone()), c.clone()))
+        .collect()
+}
+
+/// Bitwise `(e & f) ^ (!e & g)` of the 32 lowest bits
+pub(crate) fn ch<F: Field>(
+    e: &[Expression<F>],
+    f: &[Expression<F>],
+    g: &[Expression<F>],
+) -> Vec<Expression<F>> {
+    e.iter()
+        .zip(f.iter())
+        .zip(g.iter())
+        .take(NUM_BITS_PER_WORD)
+        .map(|((e, f), g)| e.clone() * f.clone() + (1.expr() - e.clone()) * g.clone())
+        .collect()
+}
+
+/// Bitwise `(a & b) ^ (a & c) ^ (b & c)` of the 32 lowest bits
+pub(crate) fn maj<F: Field>(
+    a: &[Expression<F>],
+    b: &[Expression<F>],
+    c: &[Expression<F>],
+) -> Vec<Expression<F>> {
+    a.iter()
+        .zip(b.iter())
+        .zip(c.iter())
+        .take(NUM_BITS_PER_WORD)
+        .map(|((a, b), c)| {
+            a.clone() * b.clone() + a.clone() * c.clone() + b.clone() * c.clone()
+                - 2.expr() * a.clone() * b.clone() * c.clone()
+        })
+        .collect()
+}
+
+/// Σ0(a)
+pub(crate) fn upper_sigma_0<F: Field>(bits: &[Expression<F>]) -> Vec<Expression<F>> {
+    xor3(&rotr(bits, 2), &rotr(bits, 13), &rotr(bits, 22))
+}
+
+/// Σ1(e)
+pub(crate) fn upper_sigma_1<F: Field>(bits: &[Expression<F>]) -> Vec<Expression<F>> {
+    xor3(&rotr(bits, 6), &rotr(bits, 11), &rotr(bits, 25))
+}
+
+/// σ0(w)
+pub(crate) fn lower_sigma_0<F: Field>(bits: &[Expression<F>]) -> Vec<Expression<F>> {
+    xor3(&rotr(bits, 7), &rotr(bits, 18), &shr(bits, 3))
+}
+
+/// σ1(w)
+pub(crate) fn lower_sigma_1<F: Field>(bits: &[Expression<F>]) -> Vec<Expression<F>> {
+    xor3(&rotr(bits, 17), &rotr(bits, 19), &shr(bits, 10))
+}
@@ -22,8 +22,9 @@ use crate::{
     keccak_circuit::KeccakCircuit,
     sig_circuit::ecdsa::ecdsa_verify_no_pubkey_check,
     table::{KeccakTable, SigTable},
-    util::{Challenges, Expr, SubCircuit, SubCircuitConfig},
+    util::{Challenges, Expr, SubCircuit, SubCircuitConfig, SubCircuitRowEstimator, WitnessDelta},
 };
+use bus_mapping::circuit_input_builder::{CircuitsParams, PrecompileEvent};
 use eth_types::{
     self,
     sign_types::{pk_bytes_le, pk_bytes_swap_endianness, SignData},
@@ -285,6 +286,24 @@ impl<F: Field> SubCircuit<F> for SigCircuit<F> {
     }
 }
 
+impl<F: Field> SubCircuitRowEstimator<F> for SigCircuit<F> {
+    // the share of the rows of an ecdsa verification, see `min_num_rows_block`
+    fn marginal_rows(delta: &WitnessDelta, params: &CircuitsParams) -> usize {
+        let row_num = if params.max_vertical_circuit_rows == 0 {
+            Self::min_num_rows()
+        } else {
+            params.max_vertical_circuit_rows
+        };
+        match delta {
+            WitnessDelta::Tx(tx) if !tx.tx_type.is_l1_msg() => row_num / (MAX_NUM_SIG - 1),
+            WitnessDelta::PrecompileEvent(PrecompileEvent::Ecrecover(_)) => {
+                row_num / (MAX_NUM_SIG - 1)
+            }
+            _ => 0,
+        }
+    }
+}
+
 impl<F: Field> SigCircuit<F> {
     /// Return a new SigCircuit
     pub fn new(max_verif: usize) -> Self {
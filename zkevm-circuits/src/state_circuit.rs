@@ -21,9 +21,10 @@ use self::{
 use crate::{
     evm_circuit::{param::N_BYTES_WORD, util::rlc},
     table::{AccountFieldTag, LookupTable, MptTable, RwTable, RwTableTag},
-    util::{Challenges, Expr, SubCircuit, SubCircuitConfig},
+    util::{Challenges, Expr, SubCircuit, SubCircuitConfig, SubCircuitRowEstimator, WitnessDelta},
     witness::{self, MptUpdates, Rw, RwMap},
 };
+use bus_mapping::circuit_input_builder::CircuitsParams;
 use constraint_builder::{ConstraintBuilder, Queries};
 use eth_types::{Address, Field, ToLittleEndian};
 use gadgets::{
@@ -1034,6 +1035,32 @@ impl<F: Field> SubCircuit<F> for StateCircuit<F> {
     }
 }
 
+impl<F: Field> SubCircuitRowEstimator<F> for StateCircuit<F> {
+    // The extra row of the circuit and the rws of the EndBlock step, i.e., the read of the last
+    // TxId, the read of the withdraw root and the 2 Start rws, less the write of the TxId of a next
+    // tx by the last tx, and less the read of the cumulative gas used by a previous tx counted by
+    // the first tx, see `marginal_rows`.
+    fn base_rows(_params: &CircuitsParams) -> usize {
+        1 + 4 - 2
+    }
+
+    // The rws of the steps of a tx followed by another tx. The EndTx step of a tx reads the
+    // cumulative gas used by the previous tx, which the first tx of a block of deltas has not, so
+    // it is counted for the first tx, whichever txs precede it in the witness.
+    fn marginal_rows(delta: &WitnessDelta, _params: &CircuitsParams) -> usize {
+        match delta {
+            WitnessDelta::Tx(tx) => {
+                tx.steps
+                    .iter()
+                    .map(|step| step.rw_indices.len())
+                    .sum::<usize>()
+                    + usize::from(tx.id == 1)
+            }
+            _ => 0,
+        }
+    }
+}
+
 fn queries<F: Field>(meta: &mut VirtualCells<'_, F>, c: &StateCircuitConfig<F>) -> Queries<F> {
     let first_different_limb = c.lexicographic_ordering.first_different_limb;
     let final_bits_sum = meta.query_advice(first_different_limb.bits[3], Rotation::cur())
@@ -296,6 +296,40 @@ fn storage_key_rlc() {
     assert_eq!(verify(rows), Ok(()));
 }
 
+#[test]
+fn transient_storage_ok() {
+    let rows = vec![
+        Rw::TransientStorage {
+            rw_counter: 1,
+            is_write: true,
+            tx_id: 1,
+            account_address: Address::default(),
+            storage_key: U256::from(256),
+            value: U256::from(300),
+            value_prev: U256::zero(),
+        },
+        Rw::TransientStorage {
+            rw_counter: 2,
+            is_write: false,
+            tx_id: 1,
+            account_address: Address::default(),
+            storage_key: U256::from(256),
+            value: U256::from(300),
+            value_prev: U256::from(300),
+        },
+        Rw::TransientStorage {
+            rw_counter: 3,
+            is_write: false,
+            tx_id: 2,
+            account_address: Address::default(),
+            storage_key: U256::from(256),
+            value: U256::zero(),
+            value_prev: U256::zero(),
+        },
+    ];
+    assert_eq!(verify(rows), Ok(()));
+}
+
 #[test]
 fn tx_log_ok() {
     let rows = vec![
@@ -943,6 +977,32 @@ fn bad_initial_tx_refund_value() {
     );
 }
 
+#[test]
+fn bad_initial_transient_storage_value() {
+    let rows = vec![Rw::TransientStorage {
+        rw_counter: 1,
+        is_write: false,
+        tx_id: 1,
+        account_address: Address::default(),
+        storage_key: U256::from(256),
+        value: U256::zero(),
+        value_prev: U256::zero(),
+    }];
+    let v = Fr::from(10);
+    let overrides = HashMap::from([
+        ((AdviceColumn::Value, 0), v),
+        ((AdviceColumn::ValuePrev, 0), v),
+        ((AdviceColumn::IsZero, 0), Fr::zero()),
+        ((AdviceColumn::NonEmptyWitness, 0), v.invert().unwrap()),
+        ((AdviceColumn::InitialValue, 0), v),
+    ]);
+
+    assert_error_matches(
+        verify_with_overrides(rows, overrides),
+        "initial TransientStorage value is 0",
+    );
+}
+
 #[test]
 fn bad_initial_tx_log_value() {
     let rows = vec![Rw::TxLog {
@@ -60,7 +60,7 @@ impl<const N: usize> DisplayTable<N> {
 /// Generate the prefix bytecode to trigger a big amount of rw operations
 pub(crate) fn bytecode_prefix_op_big_rws(opcode: OpcodeId) -> Bytecode {
     match opcode {
-        OpcodeId::CODECOPY | OpcodeId::CALLDATACOPY => {
+        OpcodeId::CODECOPY | OpcodeId::CALLDATACOPY | OpcodeId::MCOPY => {
             bytecode! {
                 PUSH4(0x1000) // size
                 PUSH2(0x00) // offset
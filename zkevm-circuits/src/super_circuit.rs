@@ -60,6 +60,7 @@ use crate::bytecode_circuit::circuit::to_poseidon_hash::{
 #[cfg(not(feature = "poseidon-codehash"))]
 use crate::bytecode_circuit::circuit::BytecodeCircuitConfig;
 use crate::{
+    blake2f_circuit::{Blake2fCircuit, Blake2fCircuitConfig, Blake2fCircuitConfigArgs},
     bytecode_circuit::circuit::{BytecodeCircuit, BytecodeCircuitConfigArgs},
     copy_circuit::{CopyCircuit, CopyCircuitConfig, CopyCircuitConfigArgs},
     ecc_circuit::{EccCircuit, EccCircuitConfig, EccCircuitConfigArgs},
@@ -72,16 +73,21 @@ use crate::{
     modexp_circuit::{ModExpCircuit, ModExpCircuitConfig},
     pi_circuit::{PiCircuit, PiCircuitConfig, PiCircuitConfigArgs},
     poseidon_circuit::{PoseidonCircuit, PoseidonCircuitConfig, PoseidonCircuitConfigArgs},
+    ripemd160_circuit::{Ripemd160Circuit, Ripemd160CircuitConfig, Ripemd160CircuitConfigArgs},
     rlp_circuit_fsm::{RlpCircuit, RlpCircuitConfig, RlpCircuitConfigArgs},
+    sha256_circuit::{Sha256Circuit, Sha256CircuitConfig, Sha256CircuitConfigArgs},
     sig_circuit::{SigCircuit, SigCircuitConfig, SigCircuitConfigArgs},
     state_circuit::{StateCircuit, StateCircuitConfig, StateCircuitConfigArgs},
     table::{
-        BlockTable, BytecodeTable, CopyTable, EccTable, ExpTable, KeccakTable, ModExpTable,
-        MptTable, PoseidonTable, PowOfRandTable, RlpFsmRlpTable as RlpTable, RwTable, SigTable,
-        TxTable, U16Table, U8Table,
+        Blake2fTable, BlockTable, BytecodeTable, CopyTable, EccTable, ExpTable, KeccakTable,
+        ModExpTable, MptTable, PoseidonTable, PowOfRandTable, Ripemd160Table,
+        RlpFsmRlpTable as RlpTable, RwTable, Sha256Table, SigTable, TxTable, U16Table, U8Table,
     },
     tx_circuit::{TxCircuit, TxCircuitConfig, TxCircuitConfigArgs},
-    util::{circuit_stats, log2_ceil, Challenges, SubCircuit, SubCircuitConfig},
+    util::{
+        circuit_stats, log2_ceil, Challenges, SubCircuit, SubCircuitConfig, SubCircuitRowEstimator,
+        WitnessDelta,
+    },
     witness::{block_convert, Block, Transaction},
 };
 
@@ -123,6 +129,9 @@ pub struct SuperCircuitConfig<F: Field> {
     bytecode_circuit: ToHashBlockCircuitConfig<F, HASHBLOCK_BYTES_IN_FIELD>,
     copy_circuit: CopyCircuitConfig<F>,
     keccak_circuit: KeccakCircuitConfig<F>,
+    sha256_circuit: Sha256CircuitConfig<F>,
+    ripemd160_circuit: Ripemd160CircuitConfig<F>,
+    blake2f_circuit: Blake2fCircuitConfig<F>,
     poseidon_circuit: PoseidonCircuitConfig<F>,
     pi_circuit: PiCircuitConfig<F>,
     exp_circuit: ExpCircuitConfig<F>,
@@ -195,6 +204,12 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
         log_circuit_info(meta, "modexp table");
         let ecc_table = EccTable::construct(meta);
         log_circuit_info(meta, "ecc table");
+        let sha256_table = Sha256Table::construct(meta);
+        log_circuit_info(meta, "sha256 table");
+        let ripemd160_table = Ripemd160Table::construct(meta);
+        log_circuit_info(meta, "ripemd160 table");
+        let blake2f_table = Blake2fTable::construct(meta);
+        log_circuit_info(meta, "blake2f table");
         let pow_of_rand_table = PowOfRandTable::construct(meta, &challenges_expr);
         log_circuit_info(meta, "power of randomness table");
 
@@ -213,6 +228,33 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
         );
         log_circuit_info(meta, "keccak circuit");
 
+        let sha256_circuit = Sha256CircuitConfig::new(
+            meta,
+            Sha256CircuitConfigArgs {
+                sha256_table: sha256_table.clone(),
+                challenges: challenges_expr.clone(),
+            },
+        );
+        log_circuit_info(meta, "sha256 circuit");
+
+        let ripemd160_circuit = Ripemd160CircuitConfig::new(
+            meta,
+            Ripemd160CircuitConfigArgs {
+                ripemd160_table: ripemd160_table.clone(),
+                challenges: challenges_expr.clone(),
+            },
+        );
+        log_circuit_info(meta, "ripemd160 circuit");
+
+        let blake2f_circuit = Blake2fCircuitConfig::new(
+            meta,
+            Blake2fCircuitConfigArgs {
+                blake2f_table: blake2f_table.clone(),
+                challenges: challenges_expr.clone(),
+            },
+        );
+        log_circuit_info(meta, "blake2f circuit");
+
         let poseidon_circuit =
             PoseidonCircuitConfig::new(meta, PoseidonCircuitConfigArgs { poseidon_table });
         log_circuit_info(meta, "poseidon circuit");
@@ -222,6 +264,7 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
             RlpCircuitConfigArgs {
                 rlp_table,
                 u8_table,
+                tx_table: tx_table.clone(),
                 challenges: challenges_expr.clone(),
             },
         );
@@ -340,6 +383,9 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
                 sig_table,
                 modexp_table,
                 ecc_table,
+                sha256_table,
+                ripemd160_table,
+                blake2f_table,
                 pow_of_rand_table,
             },
         );
@@ -385,6 +431,9 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
             copy_circuit,
             bytecode_circuit,
             keccak_circuit,
+            sha256_circuit,
+            ripemd160_circuit,
+            blake2f_circuit,
             poseidon_circuit,
             pi_circuit,
             rlp_circuit,
@@ -436,6 +485,12 @@ pub struct SuperCircuit<
     pub exp_circuit: ExpCircuit<F>,
     /// Keccak Circuit
     pub keccak_circuit: KeccakCircuit<F>,
+    /// Sha256 Circuit
+    pub sha256_circuit: Sha256Circuit<F>,
+    /// Ripemd160 Circuit
+    pub ripemd160_circuit: Ripemd160Circuit<F>,
+    /// Blake2f Circuit
+    pub blake2f_circuit: Blake2fCircuit<F>,
     /// Poseidon hash Circuit
     pub poseidon_circuit: PoseidonCircuit<F>,
     /// Sig Circuit
@@ -503,6 +558,12 @@ impl<
         push("sig", sig);
         let ecc = EccCircuit::<Fr, 9>::min_num_rows_block(block);
         push("ecc", ecc);
+        let sha256 = Sha256Circuit::min_num_rows_block(block);
+        push("sha256", sha256);
+        let ripemd160 = Ripemd160Circuit::min_num_rows_block(block);
+        push("ripemd160", ripemd160);
+        let blake2f = Blake2fCircuit::min_num_rows_block(block);
+        push("blake2f", blake2f);
         #[cfg(feature = "zktrie")]
         {
             let mpt = MptCircuit::<Fr>::min_num_rows_block(block);
@@ -527,6 +588,80 @@ impl<
         }
         row_usage_details
     }
+    /// Return the rows each sub circuit requires for `deltas` with `params`, in addition to the
+    /// rows of the witness before them, in the same order as
+    /// [`Self::min_num_rows_block_subcircuits`]. Padding is not included, i.e., `row_num_total` is
+    /// `row_num_real`.
+    pub fn marginal_rows_subcircuits(
+        deltas: &[WitnessDelta],
+        params: &CircuitsParams,
+    ) -> Vec<SubcircuitRowUsage> {
+        let mut rows = Vec::new();
+        let mut push = |name: &str, marginal_rows: fn(&WitnessDelta, &CircuitsParams) -> usize| {
+            let row_num = deltas
+                .iter()
+                .map(|delta| marginal_rows(delta, params))
+                .sum();
+            rows.push(SubcircuitRowUsage {
+                name: name.to_string(),
+                row_num_real: row_num,
+                row_num_total: row_num,
+            });
+        };
+        push("evm", EvmCircuit::<Fr>::marginal_rows);
+        push("state", StateCircuit::<Fr>::marginal_rows);
+        push("bytecode", BytecodeCircuit::<Fr>::marginal_rows);
+        push("copy", CopyCircuit::<Fr>::marginal_rows);
+        push("keccak", KeccakCircuit::<Fr>::marginal_rows);
+        push("tx", TxCircuit::<Fr>::marginal_rows);
+        push("rlp", RlpCircuit::<Fr, Transaction>::marginal_rows);
+        push("exp", ExpCircuit::<Fr>::marginal_rows);
+        push("mod_exp", ModExpCircuit::<Fr>::marginal_rows);
+        push("pi", PiCircuit::<Fr>::marginal_rows);
+        push("poseidon", PoseidonCircuit::<Fr>::marginal_rows);
+        push("sig", SigCircuit::<Fr>::marginal_rows);
+        push("ecc", EccCircuit::<Fr, 9>::marginal_rows);
+        push("sha256", Sha256Circuit::<Fr>::marginal_rows);
+        push("ripemd160", Ripemd160Circuit::<Fr>::marginal_rows);
+        push("blake2f", Blake2fCircuit::<Fr>::marginal_rows);
+        #[cfg(feature = "zktrie")]
+        push("mpt", MptCircuit::<Fr>::marginal_rows);
+
+        rows
+    }
+    /// Return the rows each sub circuit requires for any witness with `params` that do not
+    /// depend on its deltas, in the same order as [`Self::marginal_rows_subcircuits`]. They are
+    /// required once by a witness built from several blocks of deltas.
+    pub fn base_rows_subcircuits(params: &CircuitsParams) -> Vec<SubcircuitRowUsage> {
+        let mut rows = Vec::new();
+        let mut push = |name: &str, row_num: usize| {
+            rows.push(SubcircuitRowUsage {
+                name: name.to_string(),
+                row_num_real: row_num,
+                row_num_total: row_num,
+            });
+        };
+        push("evm", EvmCircuit::<Fr>::base_rows(params));
+        push("state", StateCircuit::<Fr>::base_rows(params));
+        push("bytecode", BytecodeCircuit::<Fr>::base_rows(params));
+        push("copy", CopyCircuit::<Fr>::base_rows(params));
+        push("keccak", KeccakCircuit::<Fr>::base_rows(params));
+        push("tx", TxCircuit::<Fr>::base_rows(params));
+        push("rlp", RlpCircuit::<Fr, Transaction>::base_rows(params));
+        push("exp", ExpCircuit::<Fr>::base_rows(params));
+        push("mod_exp", ModExpCircuit::<Fr>::base_rows(params));
+        push("pi", PiCircuit::<Fr>::base_rows(params));
+        push("poseidon", PoseidonCircuit::<Fr>::base_rows(params));
+        push("sig", SigCircuit::<Fr>::base_rows(params));
+        push("ecc", EccCircuit::<Fr, 9>::base_rows(params));
+        push("sha256", Sha256Circuit::<Fr>::base_rows(params));
+        push("ripemd160", Ripemd160Circuit::<Fr>::base_rows(params));
+        push("blake2f", Blake2fCircuit::<Fr>::base_rows(params));
+        #[cfg(feature = "zktrie")]
+        push("mpt", MptCircuit::<Fr>::base_rows(params));
+
+        rows
+    }
 }
 
 // Eventhough the SuperCircuit is not a subcircuit we implement the SubCircuit
@@ -554,6 +689,9 @@ impl<
             CopyCircuit::<Fr>::unusable_rows(),
             ExpCircuit::<Fr>::unusable_rows(),
             KeccakCircuit::<Fr>::unusable_rows(),
+            Sha256Circuit::<Fr>::unusable_rows(),
+            Ripemd160Circuit::<Fr>::unusable_rows(),
+            Blake2fCircuit::<Fr>::unusable_rows(),
         ])
         .unwrap()
     }
@@ -568,6 +706,9 @@ impl<
         let exp_circuit = ExpCircuit::new_from_block(block);
         let modexp_circuit = ModExpCircuit::new_from_block(block);
         let keccak_circuit = KeccakCircuit::new_from_block(block);
+        let sha256_circuit = Sha256Circuit::new_from_block(block);
+        let ripemd160_circuit = Ripemd160Circuit::new_from_block(block);
+        let blake2f_circuit = Blake2fCircuit::new_from_block(block);
         let poseidon_circuit = PoseidonCircuit::new_from_block(block);
         let rlp_circuit = RlpCircuit::new_from_block(block);
         let sig_circuit = SigCircuit::new_from_block(block);
@@ -583,6 +724,9 @@ impl<
             copy_circuit,
             exp_circuit,
             keccak_circuit,
+            sha256_circuit,
+            ripemd160_circuit,
+            blake2f_circuit,
             poseidon_circuit,
             rlp_circuit,
             sig_circuit,
@@ -658,6 +802,15 @@ impl<
         log::debug!("assigning modexp_circuit");
         self.modexp_circuit
             .synthesize_sub(&config.modexp_circuit, challenges, layouter)?;
+        log::debug!("assigning sha256_circuit");
+        self.sha256_circuit
+            .synthesize_sub(&config.sha256_circuit, challenges, layouter)?;
+        log::debug!("assigning ripemd160_circuit");
+        self.ripemd160_circuit
+            .synthesize_sub(&config.ripemd160_circuit, challenges, layouter)?;
+        log::debug!("assigning blake2f_circuit");
+        self.blake2f_circuit
+            .synthesize_sub(&config.blake2f_circuit, challenges, layouter)?;
         log::debug!("assigning state_circuit");
         self.state_circuit
             .synthesize_sub(&config.state_circuit, challenges, layouter)?;
@@ -15,12 +15,15 @@ use mock::MOCK_DIFFICULTY_L2GETH as MOCK_DIFFICULTY;
 use mock::{eth, TestContext, MOCK_CHAIN_ID};
 use rand::SeedableRng;
 use rand_chacha::ChaCha20Rng;
-use std::env::set_var;
+use std::{
+    collections::{HashMap, HashSet},
+    env::set_var,
+};
 
-use crate::witness::block_apply_mpt_state;
+use crate::witness::{block_apply_mpt_state, BlockDeltas};
 #[cfg(feature = "scroll")]
 use eth_types::l2_types::BlockTrace;
-use eth_types::{address, bytecode, word, Bytecode, ToWord, Word};
+use eth_types::{address, bytecode, word, Address, Bytecode, ToWord, Word};
 
 #[test]
 fn super_circuit_degree() {
@@ -31,6 +34,174 @@ fn super_circuit_degree() {
     assert!(cs.degree() <= 9);
 }
 
+#[test]
+fn super_circuit_marginal_rows() {
+    type Circuit = SuperCircuit<Fr, 1, 32, 64, 0x100>;
+    let params = CircuitsParams::default();
+    let bytes = vec![0x5b; 100];
+
+    // a new bytecode is only hashed and unrolled
+    for row in Circuit::marginal_rows_subcircuits(&[WitnessDelta::Bytecode(&bytes)], &params) {
+        match row.name.as_str() {
+            "bytecode" => assert_eq!(row.row_num_real, bytes.len() + 1),
+            "poseidon" => assert!(row.row_num_real > 0),
+            _ => assert_eq!(row.row_num_real, 0, "{} rows of a bytecode", row.name),
+        }
+    }
+    // a new keccak input is only hashed
+    for row in Circuit::marginal_rows_subcircuits(&[WitnessDelta::KeccakInput(&bytes)], &params) {
+        match row.name.as_str() {
+            "keccak" => assert!(row.row_num_real > 0),
+            _ => assert_eq!(row.row_num_real, 0, "{} rows of a keccak input", row.name),
+        }
+    }
+    // a new mpt update is only proven, its hashes are left to the caller
+    for row in Circuit::marginal_rows_subcircuits(&[WitnessDelta::MptUpdate], &params) {
+        match row.name.as_str() {
+            "mpt" => assert_eq!(row.row_num_real, 3 * 32),
+            _ => assert_eq!(row.row_num_real, 0, "{} rows of an mpt update", row.name),
+        }
+    }
+}
+
+// A builder of the txs of `senders` out of two, each hashing its own calldata, followed by more txs
+// if `more`.
+#[cfg(feature = "scroll")]
+fn block_sha3_calldata_builder<const NTX: usize>(
+    senders: [usize; NTX],
+    more: bool,
+) -> CircuitInputBuilder {
+    set_var("COINBASE", "0x0000000000000000000000000000000000000000");
+    set_var("CHAIN_ID", MOCK_CHAIN_ID.to_string());
+    let mut rng = ChaCha20Rng::seed_from_u64(2);
+    let wallets = [
+        LocalWallet::new(&mut rng).with_chain_id(*MOCK_CHAIN_ID),
+        LocalWallet::new(&mut rng).with_chain_id(*MOCK_CHAIN_ID),
+    ];
+    let bytecode = bytecode! {
+        CALLDATASIZE
+        PUSH1(0)
+        PUSH1(0)
+        CALLDATACOPY
+        CALLDATASIZE
+        PUSH1(0)
+        SHA3
+        POP
+        STOP
+    };
+
+    let l2_trace = TestContext::<4, NTX>::new(
+        Some(vec![Word::zero()]),
+        |accs| {
+            accs[0]
+                .address(address!("0x000000000000000000000000000000000000BBBB"))
+                .code(bytecode);
+            accs[1].address(wallets[0].address()).balance(eth(10));
+            accs[2].address(wallets[1].address()).balance(eth(10));
+            // an existing coinbase, so that the rws of its reward do not depend on the txs
+            // before
+            accs[3].address(Address::zero()).balance(eth(1));
+        },
+        |mut txs, accs| {
+            for (tx, sender) in txs.iter_mut().zip(senders) {
+                tx.from(wallets[sender].clone())
+                    .to(accs[0].address)
+                    .input(vec![sender as u8 + 1; 40].into())
+                    .gas(Word::from(1_000_000u64));
+            }
+        },
+        |block, _tx| block.number(0xcafeu64),
+    )
+    .unwrap()
+    .l2_trace()
+    .clone();
+
+    let circuits_params = CircuitsParams {
+        max_txs: 2,
+        max_calldata: 1024,
+        max_rws: 1024,
+        max_copy_rows: 1024,
+        ..Default::default()
+    };
+    CircuitInputBuilder::new_from_l2_trace(circuits_params, l2_trace, more, false)
+        .expect("could not handle block tx")
+}
+
+// A block of the txs of `senders` out of two, see `block_sha3_calldata_builder`.
+#[cfg(feature = "scroll")]
+fn block_sha3_calldata_witness<const NTX: usize>(senders: [usize; NTX]) -> Block<Fr> {
+    let mut builder = block_sha3_calldata_builder(senders, false);
+    builder
+        .finalize_building()
+        .expect("could not finalize building block");
+    let mut block = block_convert(&builder.block, &builder.code_db).unwrap();
+    block_apply_mpt_state(
+        &mut block,
+        &builder.mpt_init_state.expect("used non-light mode"),
+    );
+    block
+}
+
+#[cfg(feature = "scroll")]
+#[test]
+fn super_circuit_marginal_rows_add_up() {
+    type Circuit = SuperCircuit<Fr, 2, 1024, 64, 0x100>;
+    let full_block = block_sha3_calldata_witness([0, 1]);
+    let params = full_block.circuits_params;
+    let full = Circuit::min_num_rows_block_subcircuits(&full_block);
+
+    // each tx in a builder of its own, as the capacity checker estimates traces
+    let builders = [[0], [1]].map(|senders| block_sha3_calldata_builder(senders, true));
+    let mut estimate = Circuit::base_rows_subcircuits(&params);
+    let mut add = |deltas: &[WitnessDelta]| {
+        let marginal = Circuit::marginal_rows_subcircuits(deltas, &params);
+        for (row, marginal_row) in estimate.iter_mut().zip_eq(marginal) {
+            row.row_num_real += marginal_row.row_num_real;
+        }
+    };
+    let mut bytecodes = HashMap::new();
+    let mut keccak_inputs = HashSet::new();
+    let mut mpt_update_keys = HashSet::new();
+    let mut num_txs = 0;
+    for builder in &builders {
+        let block_deltas = BlockDeltas::new(&builder.block, &builder.code_db, num_txs).unwrap();
+        add(&block_deltas.deltas().collect_vec());
+        num_txs += block_deltas.txs.len();
+        bytecodes.extend(builder.code_db.0.clone());
+        keccak_inputs.extend(block_deltas.keccak_inputs);
+        mpt_update_keys.extend(block_deltas.mpt_update_keys);
+    }
+    // the witness data shared by the txs is counted once
+    add(&bytecodes
+        .values()
+        .map(|bytes| WitnessDelta::Bytecode(bytes))
+        .chain(
+            keccak_inputs
+                .iter()
+                .map(|bytes| WitnessDelta::KeccakInput(bytes)),
+        )
+        .chain(mpt_update_keys.iter().map(|_| WitnessDelta::MptUpdate))
+        .collect_vec());
+
+    for (row, estimate) in full.iter().zip_eq(&estimate) {
+        match row.name.as_str() {
+            "evm" | "state" | "bytecode" | "copy" | "rlp" => {
+                assert_eq!(estimate.row_num_real, row.row_num_real, "{} rows", row.name)
+            }
+            // the inputs of the PiCircuit are reserved at their longest
+            "keccak" => assert!(estimate.row_num_real > row.row_num_real, "keccak rows"),
+            "tx" | "pi" | "mpt" => {
+                assert!(
+                    estimate.row_num_real >= row.row_num_real,
+                    "{} rows",
+                    row.name
+                )
+            }
+            _ => {}
+        }
+    }
+}
+
 #[cfg(feature = "scroll")]
 fn test_super_circuit<
     const MAX_TXS: usize,
@@ -1,6 +1,7 @@
 //! Table definitions used cross-circuits
 
 use crate::{
+    blake2f_circuit::blake2f_bit::{compress, input_bytes},
     copy_circuit::util::number_or_hash_to_field,
     evm_circuit::util::{
         constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
@@ -16,8 +17,8 @@ use crate::{
 };
 use bus_mapping::{
     circuit_input_builder::{
-        BigModExp, CopyDataType, CopyEvent, CopyStep, EcAddOp, EcMulOp, EcPairingOp, ExpEvent,
-        PrecompileEcParams, N_BYTES_PER_PAIR, N_PAIRING_PER_OP,
+        BigModExp, Blake2fEvent, CopyDataType, CopyEvent, CopyStep, EcAddOp, EcMulOp, EcPairingOp,
+        ExpEvent, PrecompileEcParams, N_BYTES_PER_PAIR, N_PAIRING_PER_OP,
     },
     precompile::PrecompileCalls,
 };
@@ -46,7 +47,9 @@ use halo2_proofs::plonk::SecondPhase;
 use halo2_proofs::plonk::TableColumn;
 use itertools::Itertools;
 use keccak256::plain::Keccak;
-use std::array;
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
+use std::{array, marker::PhantomData};
 use strum_macros::{EnumCount, EnumIter};
 
 /// Trait used to define lookup tables
@@ -177,6 +180,12 @@ pub enum TxFieldTag {
     TxType,
     /// The block number in which this tx is included.
     BlockNumber,
+    /// MaxFeePerBlobGas of an EIP-4844 tx
+    MaxFeePerBlobGas,
+    /// Number of blob versioned hashes of an EIP-4844 tx
+    BlobVersionedHashesLength,
+    /// Blob versioned hash at a given index of an EIP-4844 tx
+    BlobVersionedHash,
 }
 impl_expr!(TxFieldTag);
 
@@ -409,6 +418,10 @@ pub enum RwTableTag {
     TxLog,
     /// Tx Receipt operation
     TxReceipt,
+    /// Tx Created Account operation
+    TxCreatedAccount,
+    /// Transient Storage operation
+    TransientStorage,
 }
 impl_expr!(RwTableTag);
 
@@ -422,6 +435,8 @@ impl RwTableTag {
                 | RwTableTag::TxRefund
                 | RwTableTag::Account
                 | RwTableTag::AccountStorage
+                | RwTableTag::TxCreatedAccount
+                | RwTableTag::TransientStorage
         )
     }
 }
@@ -1220,6 +1235,8 @@ pub enum BlockContextFieldTag {
     /// included in this block which also taking skipped l1 msgs into account.
     /// This could possibly be larger than NumTxs.
     NumAllTxs,
+    /// Blob base fee of the block, as returned by BLOBBASEFEE (EIP-7516).
+    BlobBaseFee,
 }
 impl_expr!(BlockContextFieldTag);
 
@@ -1488,6 +1505,285 @@ impl KeccakTable {
     }
 }
 
+/// Assign the advice columns of a precompile table row. q_enable is assigned
+/// inside the precompile circuit.
+fn assign_precompile_table_row<F: Field>(
+    region: &mut Region<F>,
+    offset: usize,
+    columns: &[Column<Advice>],
+    values: [Value<F>; 4],
+) -> Result<Vec<AssignedCell<F, F>>, Error> {
+    columns
+        .iter()
+        .zip_eq(values)
+        .map(|(&column, value)| {
+            region.assign_advice(|| format!("assign {offset}"), column, offset, || value)
+        })
+        .collect()
+}
+
+/// Load a precompile table with an all-zero row followed by the given rows,
+/// enabling each of them. Used only for dev_load
+fn dev_load_precompile_table<F: Field>(
+    layouter: &mut impl Layouter<F>,
+    name: &str,
+    q_enable: Column<Fixed>,
+    columns: &[Column<Advice>],
+    rows: &[[Value<F>; 4]],
+) -> Result<(), Error> {
+    layouter.assign_region(
+        || name,
+        |mut region| {
+            let all_zero_row = [Value::known(F::zero()); 4];
+            for (offset, row) in once(&all_zero_row).chain(rows).enumerate() {
+                region.assign_fixed(
+                    || format!("{name} row {offset}"),
+                    q_enable,
+                    offset,
+                    || Value::known(F::one()),
+                )?;
+                assign_precompile_table_row(&mut region, offset, columns, *row)?;
+            }
+            Ok(())
+        },
+    )
+}
+
+/// Precompile hash table, used to verify a hash precompile from RLC'ed input,
+/// where `H` is the hash function of the precompile.
+#[derive(Clone, Debug)]
+pub struct PrecompileHashTable<H> {
+    /// True when the row is enabled
+    pub q_enable: Column<Fixed>,
+    /// True when the row is final
+    pub is_final: Column<Advice>,
+    /// Byte array input as `RLC(input)`
+    pub input_rlc: Column<Advice>,
+    /// Byte array input length
+    pub input_len: Column<Advice>,
+    /// RLC of the digest bytes
+    pub output_rlc: Column<Advice>,
+    _marker: PhantomData<H>,
+}
+
+/// Sha256 Table, used to verify the SHA2-256 precompile from RLC'ed input.
+pub type Sha256Table = PrecompileHashTable<Sha256>;
+
+/// Ripemd160 Table, used to verify the RIPEMD-160 precompile from RLC'ed input.
+pub type Ripemd160Table = PrecompileHashTable<Ripemd160>;
+
+impl<F: Field, H> LookupTable<F> for PrecompileHashTable<H> {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.q_enable.into(),
+            self.is_final.into(),
+            self.input_rlc.into(),
+            self.input_len.into(),
+            self.output_rlc.into(),
+        ]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("q_enable"),
+            String::from("is_final"),
+            String::from("input_rlc"),
+            String::from("input_len"),
+            String::from("output_rlc"),
+        ]
+    }
+}
+
+impl<H: Digest> PrecompileHashTable<H> {
+    /// Construct a new PrecompileHashTable
+    pub fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            q_enable: meta.fixed_column(),
+            is_final: meta.advice_column(),
+            input_rlc: meta.advice_column_in(SecondPhase),
+            input_len: meta.advice_column(),
+            output_rlc: meta.advice_column_in(SecondPhase),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Generate the hash table assignments from a byte array input.
+    /// Both the input and the digest are RLC'ed in order with the
+    /// keccak_input challenge, the same as the RLC accumulators of the copy
+    /// circuit. Used only for dev_load
+    pub fn assignments<F: Field>(
+        input: &[u8],
+        challenges: &Challenges<Value<F>>,
+    ) -> Vec<[Value<F>; 4]> {
+        let input_rlc = challenges
+            .keccak_input()
+            .map(|challenge| rlc::value(input.iter().rev(), challenge));
+        let input_len = F::from(input.len() as u64);
+        let digest = H::digest(input);
+        let output_rlc = challenges
+            .keccak_input()
+            .map(|challenge| rlc::value(digest.iter().rev(), challenge));
+
+        vec![[
+            Value::known(F::one()),
+            input_rlc,
+            Value::known(input_len),
+            output_rlc,
+        ]]
+    }
+
+    /// Assign a table row for the hash table
+    /// q_enable assigned inside the hash circuit
+    pub fn assign_row<F: Field>(
+        &self,
+        region: &mut Region<F>,
+        offset: usize,
+        values: [Value<F>; 4],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        assign_precompile_table_row(
+            region,
+            offset,
+            &<Self as LookupTable<F>>::advice_columns(self),
+            values,
+        )
+    }
+
+    /// Provide this function for the case that we want to consume a hash
+    /// table but without running the full hash circuit
+    pub fn dev_load<'a, F: Field>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        inputs: impl IntoIterator<Item = &'a Vec<u8>> + Clone,
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<(), Error> {
+        let rows = inputs
+            .into_iter()
+            .flat_map(|input| Self::assignments(input, challenges))
+            .collect::<Vec<_>>();
+        dev_load_precompile_table(
+            layouter,
+            "precompile hash table",
+            self.q_enable,
+            &<Self as LookupTable<F>>::advice_columns(self),
+            &rows,
+        )
+    }
+}
+
+/// Blake2f Table, used to verify the BLAKE2F precompile from RLC'ed input.
+#[derive(Clone, Debug)]
+pub struct Blake2fTable {
+    /// True when the row is enabled
+    pub q_enable: Column<Fixed>,
+    /// True when the row holds the output of a compression
+    pub is_final: Column<Advice>,
+    /// The number of rounds of the compression
+    pub rounds: Column<Advice>,
+    /// The 213-byte precompile input as `RLC(input)`
+    pub input_rlc: Column<Advice>,
+    /// RLC of the 64 output bytes
+    pub output_rlc: Column<Advice>,
+}
+
+impl<F: Field> LookupTable<F> for Blake2fTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.q_enable.into(),
+            self.is_final.into(),
+            self.rounds.into(),
+            self.input_rlc.into(),
+            self.output_rlc.into(),
+        ]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("q_enable"),
+            String::from("is_final"),
+            String::from("rounds"),
+            String::from("input_rlc"),
+            String::from("output_rlc"),
+        ]
+    }
+}
+
+impl Blake2fTable {
+    /// Construct a new Blake2fTable
+    pub fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            q_enable: meta.fixed_column(),
+            is_final: meta.advice_column(),
+            rounds: meta.advice_column(),
+            input_rlc: meta.advice_column_in(SecondPhase),
+            output_rlc: meta.advice_column_in(SecondPhase),
+        }
+    }
+
+    /// Generate the blake2f table assignments from a compression. Both the
+    /// input and the output are RLC'ed in order with the keccak_input
+    /// challenge. Used only for dev_load
+    pub fn assignments<F: Field>(
+        event: &Blake2fEvent,
+        challenges: &Challenges<Value<F>>,
+    ) -> Vec<[Value<F>; 4]> {
+        let input = input_bytes(event);
+        let input_rlc = challenges
+            .keccak_input()
+            .map(|challenge| rlc::value(input.iter().rev(), challenge));
+        let output = compress(event.rounds, &event.h, &event.m, &event.t, event.f)
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect::<Vec<_>>();
+        let output_rlc = challenges
+            .keccak_input()
+            .map(|challenge| rlc::value(output.iter().rev(), challenge));
+
+        vec![[
+            Value::known(F::one()),
+            Value::known(F::from(event.rounds as u64)),
+            input_rlc,
+            output_rlc,
+        ]]
+    }
+
+    /// Assign a table row for blake2f table
+    /// q_enable assigned inside blake2f circuit
+    pub fn assign_row<F: Field>(
+        &self,
+        region: &mut Region<F>,
+        offset: usize,
+        values: [Value<F>; 4],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        assign_precompile_table_row(
+            region,
+            offset,
+            &<Self as LookupTable<F>>::advice_columns(self),
+            values,
+        )
+    }
+
+    /// Provide this function for the case that we want to consume a blake2f
+    /// table but without running the full blake2f circuit
+    pub fn dev_load<'a, F: Field>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        events: impl IntoIterator<Item = &'a Blake2fEvent> + Clone,
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<(), Error> {
+        let rows = events
+            .into_iter()
+            .flat_map(|event| Self::assignments(event, challenges))
+            .collect::<Vec<_>>();
+        dev_load_precompile_table(
+            layouter,
+            "blake2f table",
+            self.q_enable,
+            &<Self as LookupTable<F>>::advice_columns(self),
+            &rows,
+        )
+    }
+}
+
 /// Copy Table, used to verify copies of byte chunks between Memory, Bytecode,
 /// TxLogs and TxCallData.
 #[derive(Clone, Copy, Debug)]
@@ -1609,6 +1905,11 @@ impl CopyTable {
 
         let mut rw_counter = copy_event.rw_counter_start();
         let mut rwc_inc_left = copy_event.rw_counter_delta();
+        // A copy within the memory of one call reads all words before writing
+        // any, so the writer counts its own RW operations from the middle.
+        let is_memory_copy = copy_event.is_memory_copy();
+        let mut write_rw_counter = rw_counter + rwc_inc_left / 2;
+        let mut write_rwc_inc_left = rwc_inc_left / 2;
 
         let mut reader = CopyThread {
             tag: copy_event.src_type,
@@ -1696,6 +1997,12 @@ impl CopyTable {
 
             let word_index = (step_idx as u64 / 2) % 32;
 
+            let (row_rw_counter, row_rwc_inc_left) = if is_memory_copy && !is_read_step {
+                (write_rw_counter, write_rwc_inc_left)
+            } else {
+                (rw_counter, rwc_inc_left)
+            };
+
             // For LOG, format the address including the log_id.
             let addr = if thread.tag == CopyDataType::TxLog {
                 build_tx_log_address(thread.addr, TxLogFieldTag::Data, copy_event.log_id.unwrap())
@@ -1714,8 +2021,8 @@ impl CopyTable {
                     (Value::known(F::from(thread.addr_end)), "src_addr_end"),
                     (Value::known(F::from(thread.bytes_left)), "real_bytes_left"),
                     (rlc_acc, "rlc_acc"),
-                    (Value::known(F::from(rw_counter)), "rw_counter"),
-                    (Value::known(F::from(rwc_inc_left)), "rwc_inc_left"),
+                    (Value::known(F::from(row_rw_counter)), "rw_counter"),
+                    (Value::known(F::from(row_rwc_inc_left)), "rwc_inc_left"),
                 ],
                 [
                     (Value::known(F::from(is_last)), "is_last"),
@@ -1742,8 +2049,13 @@ impl CopyTable {
             // Update the RW counter.
             let is_word_end = (step_idx / 2) % 32 == 31;
             if is_word_end && thread.is_rw {
-                rw_counter += 1;
-                rwc_inc_left -= 1;
+                if is_memory_copy && !is_read_step {
+                    write_rw_counter += 1;
+                    write_rwc_inc_left -= 1;
+                } else {
+                    rw_counter += 1;
+                    rwc_inc_left -= 1;
+                }
             }
         }
         assignments
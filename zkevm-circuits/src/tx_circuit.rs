@@ -13,37 +13,45 @@ mod test;
 pub use dev::TxCircuitTester as TestTxCircuit;
 
 use crate::{
-    evm_circuit::util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+    evm_circuit::{
+        param::N_BYTES_WORD,
+        util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+    },
     sig_circuit::SigCircuit,
     table::{
         BlockContextFieldTag::{CumNumTxs, NumAllTxs, NumTxs},
         BlockTable, KeccakTable, LookupTable, RlpFsmRlpTable as RlpTable, SigTable, TxFieldTag,
         TxFieldTag::{
-            BlockNumber, CallData, CallDataGasCost, CallDataLength, CallDataRLC, CalleeAddress,
-            CallerAddress, ChainID, Gas, GasPrice, IsCreate, Nonce, SigR, SigS, SigV,
-            TxDataGasCost, TxHashLength, TxHashRLC, TxSignHash, TxSignLength, TxSignRLC,
+            BlobVersionedHash, BlobVersionedHashesLength, BlockNumber, CallData, CallDataGasCost,
+            CallDataLength, CallDataRLC, CalleeAddress, CallerAddress, ChainID, Gas, GasPrice,
+            IsCreate, MaxFeePerBlobGas, Nonce, SigR, SigS, SigV, TxDataGasCost, TxHashLength,
+            TxHashRLC, TxSignHash, TxSignLength, TxSignRLC,
         },
         TxTable, U16Table, U8Table,
     },
     util::{
         is_zero::{IsZeroChip, IsZeroConfig},
-        keccak, rlc_be_bytes, SubCircuit, SubCircuitConfig,
+        keccak, rlc_be_bytes, SubCircuit, SubCircuitConfig, SubCircuitRowEstimator,
     },
     witness,
     witness::{
         rlp_fsm::{Tag, ValueTagLength},
-        Format::{L1MsgHash, TxHashEip155, TxHashPreEip155, TxSignEip155, TxSignPreEip155},
+        Format::{
+            L1MsgHash, TxHashEip155, TxHashEip4844, TxHashPreEip155, TxSignEip155, TxSignEip4844,
+            TxSignPreEip155,
+        },
         RlpTag,
         RlpTag::{GasCost, Len, Null, RLC},
         Tag::TxType as RLPTxType,
         Transaction,
     },
 };
-use bus_mapping::circuit_input_builder::keccak_inputs_sign_verify;
+use bus_mapping::circuit_input_builder::{keccak_inputs_sign_verify, CircuitsParams};
 use eth_types::{
+    evm_types::MAX_BLOBS_PER_TX,
     geth_types::{
         TxType,
-        TxType::{Eip155, L1Msg, PreEip155},
+        TxType::{Eip155, Eip4844, L1Msg, PreEip155},
     },
     sign_types::SignData,
     Address, Field, ToAddress, ToBigEndian, ToScalar,
@@ -79,9 +87,11 @@ use halo2_proofs::plonk::SecondPhase;
 use itertools::Itertools;
 
 /// Number of rows of one tx occupies in the fixed part of tx table
-pub const TX_LEN: usize = 23;
+pub const TX_LEN: usize = 25 + MAX_BLOBS_PER_TX;
 /// Offset of TxHash tag in the tx table
 pub const TX_HASH_OFFSET: usize = 21;
+/// Offset of TxType tag in the tx table
+pub const TX_TYPE_OFFSET: usize = 22;
 /// Offset of ChainID tag in the tx table
 pub const CHAIN_ID_OFFSET: usize = 12;
 
@@ -144,7 +154,9 @@ pub struct TxCircuitConfig<F: Field> {
     is_calldata: Column<Advice>,
     is_caller_address: Column<Advice>,
     is_l1_msg: Column<Advice>,
+    is_eip4844: Column<Advice>,
     is_chain_id: Column<Advice>,
+    is_blob_versioned_hash: Column<Advice>,
     lookup_conditions: HashMap<LookupCondition, Column<Advice>>,
 
     /// Columns for computing num_all_txs
@@ -278,10 +290,12 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
 
         // booleans to reduce degree
         let is_l1_msg = meta.advice_column();
+        let is_eip4844 = meta.advice_column();
         let is_calldata = meta.advice_column();
         let is_caller_address = meta.advice_column();
         let is_chain_id = meta.advice_column();
         let is_tag_block_num = meta.advice_column();
+        let is_blob_versioned_hash = meta.advice_column();
         let lookup_conditions = [
             LookupCondition::TxCalldata,
             LookupCondition::L1MsgHash,
@@ -345,6 +359,9 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
         is_tx_tag!(is_hash, TxHash);
         is_tx_tag!(is_block_num, BlockNumber);
         is_tx_tag!(is_tx_type, TxType);
+        is_tx_tag!(is_max_fee_per_blob_gas, MaxFeePerBlobGas);
+        is_tx_tag!(is_blob_hashes_length, BlobVersionedHashesLength);
+        is_tx_tag!(is_blob_hash, BlobVersionedHash);
 
         let tx_id_unchanged = IsEqualChip::configure(
             meta,
@@ -366,6 +383,8 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
                         is_data_length(meta),
                         // if call data byte is zero, then gas_cost = 4 (16 otherwise)
                         is_data(meta),
+                        // if blob versioned hash is zero, then it is out of the hashes of the tx
+                        is_blob_hash(meta),
                     ]),
                 ])
             },
@@ -472,6 +491,8 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
                 (is_block_num(meta), Null),
                 (is_chain_id_expr(meta), Tag::ChainId.into()),
                 (is_tx_type(meta), Null),
+                (is_max_fee_per_blob_gas(meta), Tag::MaxFeePerBlobGas.into()),
+                (is_blob_hashes_length(meta), Null),
             ];
 
             cb.require_boolean(
@@ -486,6 +507,7 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
                     usize::from(PreEip155).expr(),
                     usize::from(Eip155).expr(),
                     usize::from(L1Msg).expr(),
+                    usize::from(Eip4844).expr(),
                 ],
             );
 
@@ -497,12 +519,18 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
                 );
             });
 
+            // the versioned hash at each index has its own tag in the RLP table
+            let blob_hash_rlp_tag = meta.query_advice(is_blob_versioned_hash, Rotation::cur())
+                * (usize::from(Tag::blob_versioned_hash(0)).expr()
+                    + meta.query_advice(tx_table.index, Rotation::cur()));
             cb.require_equal(
                 "associated rlp_tag",
                 meta.query_advice(rlp_tag, Rotation::cur()),
-                rlp_tag_map.into_iter().fold(0.expr(), |acc, (expr, tag)| {
-                    acc + usize::from(tag).expr() * expr
-                }),
+                rlp_tag_map
+                    .into_iter()
+                    .fold(blob_hash_rlp_tag, |acc, (expr, tag)| {
+                        acc + usize::from(tag).expr() * expr
+                    }),
             );
 
             cb.condition(is_to(meta), |cb| {
@@ -514,6 +542,24 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
                 );
             });
 
+            // the blob fields of a non-blob tx are zero, e.g. BLOBHASH returns zero for any index
+            cb.condition(
+                and::expr([
+                    not::expr(meta.query_advice(is_eip4844, Rotation::cur())),
+                    sum::expr([
+                        is_max_fee_per_blob_gas(meta),
+                        is_blob_hashes_length(meta),
+                        is_blob_hash(meta),
+                    ]),
+                ]),
+                |cb| {
+                    cb.require_zero(
+                        "blob field of non-blob tx == 0",
+                        meta.query_advice(tx_table.value, Rotation::cur()),
+                    );
+                },
+            );
+
             let is_none_expr = meta.query_advice(is_none, Rotation::cur());
             // is_none == true
             cb.condition(is_none_expr.expr(), |cb| {
@@ -608,6 +654,60 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
         });
 
+        meta.create_gate("is_blob_versioned_hash", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            cb.require_equal(
+                "is_blob_versioned_hash = (tag == BlobVersionedHash)",
+                is_blob_hash(meta),
+                meta.query_advice(is_blob_versioned_hash, Rotation::cur()),
+            );
+
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("blob versioned hashes", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let is_hash = meta.query_advice(is_blob_versioned_hash, Rotation::cur());
+            let is_hash_next = meta.query_advice(is_blob_versioned_hash, Rotation::next());
+            let is_zero = value_is_zero.expr(Rotation::cur())(meta);
+            let is_zero_next = value_is_zero.expr(Rotation::next())(meta);
+
+            // the hashes are indexed from 0 to MAX_BLOBS_PER_TX - 1
+            cb.condition(
+                and::expr([not::expr(is_hash.expr()), is_hash_next.expr()]),
+                |cb| {
+                    cb.require_zero(
+                        "index of the first hash == 0",
+                        meta.query_advice(tx_table.index, Rotation::next()),
+                    );
+                },
+            );
+            cb.condition(and::expr([is_hash, is_hash_next]), |cb| {
+                cb.require_equal(
+                    "index' == index + 1",
+                    meta.query_advice(tx_table.index, Rotation::next()),
+                    meta.query_advice(tx_table.index, Rotation::cur()) + 1.expr(),
+                );
+                // the hashes of the tx come first and are followed by zeros
+                cb.require_zero("hash == 0 => hash' == 0", is_zero * not::expr(is_zero_next));
+            });
+            // the hash rows follow the BlobVersionedHashesLength row
+            cb.condition(is_blob_hashes_length(meta), |cb| {
+                cb.require_equal(
+                    "BlobVersionedHashesLength == number of non-zero hashes",
+                    meta.query_advice(tx_table.value, Rotation::cur()),
+                    sum::expr(
+                        (1..=MAX_BLOBS_PER_TX)
+                            .map(|i| not::expr(value_is_zero.expr(Rotation(i as i32))(meta))),
+                    ),
+                );
+            });
+
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
         meta.create_gate("is_l1_msg", |meta| {
             let mut cb = BaseConstraintBuilder::default();
 
@@ -620,6 +720,18 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
         });
 
+        meta.create_gate("is_eip4844", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            cb.require_equal(
+                "is_eip4844 = (tx_type == Eip4844)",
+                meta.query_advice(is_eip4844, Rotation::cur()),
+                tx_type_bits.value_equals(Eip4844, Rotation::cur())(meta),
+            );
+
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
         meta.create_gate("calldata lookup into tx table condition", |meta| {
             let mut cb = BaseConstraintBuilder::default();
 
@@ -641,9 +753,11 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
         meta.create_gate("sign tag lookup into RLP table condition", |meta| {
             let mut cb = BaseConstraintBuilder::default();
 
+            let is_eip4844 = meta.query_advice(is_eip4844, Rotation::cur());
             let is_tag_in_tx_sign = sum::expr([
                 is_nonce(meta),
-                is_gas_price(meta),
+                // eip4844 tx only has max_fee_per_gas and max_priority_fee_per_gas
+                is_gas_price(meta) * not::expr(is_eip4844.expr()),
                 is_gas(meta),
                 is_to(meta),
                 is_value(meta),
@@ -652,6 +766,16 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
                     meta.query_advice(is_chain_id, Rotation::cur()),
                     tx_type_bits.value_equals(Eip155, Rotation::cur())(meta),
                 ]),
+                and::expr([
+                    meta.query_advice(is_chain_id, Rotation::cur()),
+                    is_eip4844.expr(),
+                ]),
+                is_max_fee_per_blob_gas(meta) * is_eip4844.expr(),
+                and::expr([
+                    meta.query_advice(is_blob_versioned_hash, Rotation::cur()),
+                    is_eip4844,
+                    not::expr(value_is_zero.expr(Rotation::cur())(meta)),
+                ]),
                 is_sign_length(meta),
                 is_sign_rlc(meta),
             ]);
@@ -674,9 +798,16 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
         meta.create_gate("hash tag lookup into RLP table condition", |meta| {
             let mut cb = BaseConstraintBuilder::default();
 
+            let is_eip4844 = meta.query_advice(is_eip4844, Rotation::cur());
             let is_tag_in_tx_hash = sum::expr([
                 is_nonce(meta),
-                is_gas_price(meta),
+                is_gas_price(meta) * not::expr(is_eip4844.expr()),
+                is_max_fee_per_blob_gas(meta) * is_eip4844.expr(),
+                and::expr([
+                    meta.query_advice(is_blob_versioned_hash, Rotation::cur()),
+                    is_eip4844,
+                    not::expr(value_is_zero.expr(Rotation::cur())(meta)),
+                ]),
                 is_gas(meta),
                 is_to(meta),
                 is_value(meta),
@@ -1303,9 +1434,21 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
                 },
             );
 
+            //  4. eip4844 tx: v Є {0, 1}
+            cb.condition(
+                and::expr([
+                    is_chain_id.expr(),
+                    tx_type_bits.value_equals(Eip4844, Rotation::cur())(meta),
+                ]),
+                |cb| {
+                    let v = meta.query_advice(tx_table.value, Rotation::next());
+                    cb.require_boolean("V Є {0, 1}", v);
+                },
+            );
+
             // TODO:
-            //  4. eip1559 tx: v Є {0, 1}
-            //  5. eip2930 tx: v Є {0, 1}
+            //  5. eip1559 tx: v Є {0, 1}
+            //  6. eip2930 tx: v Є {0, 1}
 
             cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
         });
@@ -1363,7 +1506,9 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             num_all_txs_acc,
             total_l1_popped_before,
             is_l1_msg,
+            is_eip4844,
             is_chain_id,
+            is_blob_versioned_hash,
             is_final,
             calldata_gas_cost_acc,
             calldata_rlc,
@@ -1516,6 +1661,7 @@ impl<F: Field> TxCircuitConfig<F> {
         is_tx_type!(is_pre_eip155, PreEip155);
         is_tx_type!(is_eip155, Eip155);
         is_tx_type!(is_l1_msg, L1Msg);
+        is_tx_type!(is_eip4844, Eip4844);
 
         // lookup tx type in RLP table for L1Msg only
         meta.lookup_any("lookup tx type in RLP table", |meta| {
@@ -1557,7 +1703,8 @@ impl<F: Field> TxCircuitConfig<F> {
             let rlp_tag = meta.query_advice(rlp_tag, Rotation::cur());
             let is_none = meta.query_advice(is_none, Rotation::cur());
             let sign_format = is_pre_eip155(meta) * TxSignPreEip155.expr()
-                + is_eip155(meta) * TxSignEip155.expr();
+                + is_eip155(meta) * TxSignEip155.expr()
+                + is_eip4844(meta) * TxSignEip4844.expr();
 
             // q_enable, tx_id, format, rlp_tag, tag_value, is_output, is_none
             vec![
@@ -1596,7 +1743,8 @@ impl<F: Field> TxCircuitConfig<F> {
             let is_none = meta.query_advice(is_none, Rotation::cur());
             let hash_format = is_pre_eip155(meta) * TxHashPreEip155.expr()
                 + is_eip155(meta) * TxHashEip155.expr()
-                + is_l1_msg(meta) * L1MsgHash.expr();
+                + is_l1_msg(meta) * L1MsgHash.expr()
+                + is_eip4844(meta) * TxHashEip4844.expr();
 
             vec![
                 1.expr(), // q_enable = true
@@ -1635,8 +1783,10 @@ impl<F: Field> TxCircuitConfig<F> {
             let sig_s = meta.query_advice(tx_table.value, Rotation(3));
             let sv_address = meta.query_advice(sv_address, Rotation::cur());
 
+            // the sig_v of an eip4844 tx is the y-parity itself
             let v = is_eip155(meta) * (sig_v.expr() - 2.expr() * chain_id - 35.expr())
-                + is_pre_eip155(meta) * (sig_v.expr() - 27.expr());
+                + is_pre_eip155(meta) * (sig_v.expr() - 27.expr())
+                + is_eip4844(meta) * sig_v;
 
             let input_exprs = vec![
                 1.expr(),     // q_enable = true
@@ -1932,10 +2082,54 @@ impl<F: Field> TxCircuitConfig<F> {
                 None,
                 Value::known(F::from(tx.tx_type as u64)),
             ),
-            (BlockNumber, None, Value::known(F::from(tx.block_number))),
+            (
+                MaxFeePerBlobGas,
+                Some(RlpTableInputValue {
+                    tag: Tag::MaxFeePerBlobGas.into(),
+                    is_none: tx.max_fee_per_blob_gas.is_zero(),
+                    be_bytes_len: tx.max_fee_per_blob_gas.tag_length(),
+                    be_bytes_rlc: rlc_be_bytes(
+                        &tx.max_fee_per_blob_gas.to_be_bytes(),
+                        keccak_input,
+                    ),
+                }),
+                rlc_be_bytes(&tx.max_fee_per_blob_gas.to_be_bytes(), evm_word),
+            ),
+            (
+                BlobVersionedHashesLength,
+                None,
+                Value::known(F::from(tx.blob_versioned_hashes.len() as u64)),
+            ),
         ];
+        let fixed_rows = fixed_rows
+            .into_iter()
+            .map(|(tx_tag, rlp_input, tx_value)| (tx_tag, 0, rlp_input, tx_value))
+            .chain((0..MAX_BLOBS_PER_TX).map(|idx| {
+                let hash = tx
+                    .blob_versioned_hashes
+                    .get(idx)
+                    .cloned()
+                    .unwrap_or_default();
+                (
+                    BlobVersionedHash,
+                    idx as u64,
+                    Some(RlpTableInputValue {
+                        tag: Tag::blob_versioned_hash(idx).into(),
+                        is_none: false,
+                        be_bytes_len: N_BYTES_WORD as u32,
+                        be_bytes_rlc: rlc_be_bytes(hash.as_bytes(), keccak_input),
+                    }),
+                    rlc_be_bytes(hash.as_bytes(), evm_word),
+                )
+            }))
+            .chain(iter::once((
+                BlockNumber,
+                0,
+                None,
+                Value::known(F::from(tx.block_number)),
+            )));
 
-        for (tx_tag, rlp_input, tx_value) in fixed_rows {
+        for (tx_tag, tx_index, rlp_input, tx_value) in fixed_rows {
             let rlp_tag = rlp_input.clone().map_or(Null, |input| input.tag);
             let rlp_is_none = rlp_input.clone().map_or(false, |input| input.is_none);
             let rlp_be_bytes_len = rlp_input.clone().map_or(0, |input| input.be_bytes_len);
@@ -1943,6 +2137,18 @@ impl<F: Field> TxCircuitConfig<F> {
                 .clone()
                 .map_or(zero_rlc, |input| input.be_bytes_rlc);
             let is_l1_msg = tx.tx_type.is_l1_msg();
+            let is_eip4844 = tx.tx_type.is_eip4844_tx();
+            // eip4844 tx has MaxFeePerBlobGas instead of GasPrice in its RLP encoding
+            let is_fee_tag = |tag: TxFieldTag| {
+                if is_eip4844 {
+                    tag == MaxFeePerBlobGas
+                } else {
+                    tag == GasPrice
+                }
+            };
+            // the versioned hashes of a blob tx are in the RLP table, the zeros beyond them are not
+            let is_blob_hash_of_tx =
+                tx_tag == BlobVersionedHash && (tx_index as usize) < tx.blob_versioned_hashes.len();
             // it's the tx_id of next row
             let tx_id_next = if tx_tag == BlockNumber {
                 next_tx.map_or(0, |tx| tx.id)
@@ -1956,7 +2162,7 @@ impl<F: Field> TxCircuitConfig<F> {
                 Some(tx),
                 tx_id_next,
                 tx_tag,
-                0,
+                tx_index,
                 tx_value,
             )?);
 
@@ -2021,6 +2227,11 @@ impl<F: Field> TxCircuitConfig<F> {
                     self.is_caller_address,
                     F::from((tx_tag == CallerAddress) as u64),
                 ),
+                (
+                    "is_tag_blob_versioned_hash",
+                    self.is_blob_versioned_hash,
+                    F::from((tx_tag == BlobVersionedHash) as u64),
+                ),
             ] {
                 region.assign_advice(|| col_anno, col, *offset, || Value::known(col_val))?;
             }
@@ -2047,7 +2258,6 @@ impl<F: Field> TxCircuitConfig<F> {
             conditions.insert(LookupCondition::RlpSignTag, {
                 let sign_set = [
                     Nonce,
-                    GasPrice,
                     Gas,
                     CalleeAddress,
                     TxFieldTag::Value,
@@ -2057,14 +2267,14 @@ impl<F: Field> TxCircuitConfig<F> {
                 ];
                 let is_tag_in_set = sign_set.into_iter().filter(|tag| tx_tag == *tag).count() == 1;
                 let case1 = is_tag_in_set && !is_l1_msg;
-                let case2 = tx.tx_type.is_eip155_tx() && (tx_tag == ChainID);
-                F::from((case1 || case2) as u64)
+                let case2 = (tx.tx_type.is_eip155_tx() || is_eip4844) && (tx_tag == ChainID);
+                let case3 = is_fee_tag(tx_tag) && !is_l1_msg;
+                F::from((case1 || case2 || case3 || is_blob_hash_of_tx) as u64)
             });
             // 3. lookup to RLP table for hashing (non L1 msg)
             conditions.insert(LookupCondition::RlpHashTag, {
                 let hash_set = [
                     Nonce,
-                    GasPrice,
                     Gas,
                     CalleeAddress,
                     TxFieldTag::Value,
@@ -2077,7 +2287,10 @@ impl<F: Field> TxCircuitConfig<F> {
                     TxHashRLC,
                 ];
                 let is_tag_in_set = hash_set.into_iter().filter(|tag| tx_tag == *tag).count() == 1;
-                F::from((!is_l1_msg && is_tag_in_set) as u64)
+                F::from(
+                    (!is_l1_msg && (is_tag_in_set || is_fee_tag(tx_tag)) || is_blob_hash_of_tx)
+                        as u64,
+                )
             });
             // 4. lookup to RLP table for hashing (L1 msg)
             conditions.insert(LookupCondition::L1MsgHash, {
@@ -2262,6 +2475,11 @@ impl<F: Field> TxCircuitConfig<F> {
                 self.is_l1_msg,
                 F::from(tx_type.is_l1_msg() as u64),
             ),
+            (
+                "is_eip4844",
+                self.is_eip4844,
+                F::from(tx_type.is_eip4844_tx() as u64),
+            ),
         ] {
             region.assign_advice(|| col_anno, col, offset, || Value::known(col_val))?;
         }
@@ -2811,6 +3029,19 @@ impl<F: Field> SubCircuit<F> for TxCircuit<F> {
     }
 }
 
+impl<F: Field> SubCircuitRowEstimator<F> for TxCircuit<F> {
+    // the share of the vertical rows of the call data of a tx, rounded up per tx
+    fn marginal_rows(delta: &WitnessDelta, params: &CircuitsParams) -> usize {
+        match delta {
+            WitnessDelta::Tx(tx) => {
+                let tx_usage = tx.call_data.len() as f32 / params.max_calldata as f32;
+                (tx_usage * params.max_vertical_circuit_rows as f32).ceil() as usize
+            }
+            _ => 0,
+        }
+    }
+}
+
 pub(crate) fn get_sign_data(
     txs: &[Transaction],
     max_txs: usize,
@@ -1,7 +1,10 @@
 //! Common utility traits and functions.
 use std::collections::BTreeSet;
 
-use bus_mapping::evm::OpcodeId;
+use bus_mapping::{
+    circuit_input_builder::{CircuitsParams, CopyEvent, ExpEvent, PrecompileEvent},
+    evm::OpcodeId,
+};
 use halo2_proofs::{
     arithmetic::FieldExt,
     circuit::{Layouter, Value},
@@ -22,6 +25,8 @@ pub use gadgets::util::Expr;
 /// A wrapper of is_zero in gadgets which gives is_zero at any rotation
 pub mod is_zero;
 
+pub(crate) mod precompile_hash;
+
 pub(crate) fn query_expression<F: FieldExt, T>(
     meta: &mut ConstraintSystem<F>,
     mut f: impl FnMut(&mut VirtualCells<F>) -> T,
@@ -239,6 +244,44 @@ pub trait SubCircuit<F: Field> {
     fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize);
 }
 
+/// Witness data added to the witness of a SubCircuit, e.g., by a new tx. The deltas of the txs of
+/// a block are given by [`witness::BlockDeltas`], without converting the whole block.
+#[derive(Debug, Clone, Copy)]
+pub enum WitnessDelta<'a> {
+    /// A tx with its execution steps, built as followed by another tx, see
+    /// [`witness::BlockDeltas`]
+    Tx(&'a witness::Transaction),
+    /// A copy event of a step
+    CopyEvent(&'a CopyEvent),
+    /// An exponentiation event of a step
+    ExpEvent(&'a ExpEvent),
+    /// A precompile call of a step
+    PrecompileEvent(&'a PrecompileEvent),
+    /// A bytecode not yet in the witness
+    Bytecode(&'a [u8]),
+    /// A keccak input not yet in the witness
+    KeccakInput(&'a [u8]),
+    /// An update of an account field or a storage slot not yet updated in the witness
+    MptUpdate,
+}
+
+/// Incremental row estimation of a SubCircuit.
+///
+/// The rows of the deltas of distinct witness data, plus the base rows, add up to the rows of the
+/// whole witness, so that an estimate does not depend on the order the witness data is added in.
+pub trait SubCircuitRowEstimator<F: Field>: SubCircuit<F> {
+    /// Return the rows of any witness with `params` that do not depend on its deltas, e.g.,
+    /// reserved rows or the rows of the end of the block. A witness requires them once, however
+    /// many deltas it is made of.
+    fn base_rows(_params: &CircuitsParams) -> usize {
+        0
+    }
+
+    /// Return the rows required by `delta` in a witness with `params`, in addition to the rows of
+    /// the witness before it.
+    fn marginal_rows(delta: &WitnessDelta, params: &CircuitsParams) -> usize;
+}
+
 /// SubCircuit configuration
 pub trait SubCircuitConfig<F: Field> {
     /// Config constructor arguments
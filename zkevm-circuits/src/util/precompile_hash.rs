@@ -0,0 +1,48 @@
+//! Bit-level helpers shared by the circuits of the hash precompiles.
+use eth_types::Field;
+use gadgets::util::Expr;
+use halo2_proofs::{
+    plonk::{Advice, Column, Expression, VirtualCells},
+    poly::Rotation,
+};
+
+const NUM_BITS_PER_BYTE: usize = 8;
+
+/// Queries the bit columns of a word at the rotation `rot`
+pub(crate) fn query_bits<F: Field>(
+    meta: &mut VirtualCells<F>,
+    columns: &[Column<Advice>],
+    rot: i32,
+) -> Vec<Expression<F>> {
+    columns
+        .iter()
+        .map(|column| meta.query_advice(*column, Rotation(rot)))
+        .collect()
+}
+
+/// Decodes little-endian bits into a value
+pub(crate) fn decode<F: Field>(bits: &[Expression<F>]) -> Expression<F> {
+    bits.iter()
+        .rev()
+        .fold(0.expr(), |acc, bit| acc * 2.expr() + bit.clone())
+}
+
+/// Decodes the `idx`-th little-endian byte of a word given as little-endian
+/// bits
+pub(crate) fn decode_byte<F: Field>(bits: &[Expression<F>], idx: usize) -> Expression<F> {
+    decode(&bits[idx * NUM_BITS_PER_BYTE..(idx + 1) * NUM_BITS_PER_BYTE])
+}
+
+/// Bitwise `a ^ b` of two bits
+pub(crate) fn xor<F: Field>(a: Expression<F>, b: Expression<F>) -> Expression<F> {
+    a.clone() + b.clone() - 2.expr() * a * b
+}
+
+/// Splits a value into `N` little-endian bits
+pub(crate) fn into_bits<const N: usize>(value: u64) -> [bool; N] {
+    let mut bits = [false; N];
+    for (idx, bit) in bits.iter_mut().enumerate() {
+        *bit = (value >> idx) & 1 == 1;
+    }
+    bits
+}
@@ -5,7 +5,7 @@
 mod block;
 pub use block::{
     block_apply_mpt_state, block_convert, block_convert_with_l1_queue_index, Block, BlockContext,
-    BlockContexts,
+    BlockContexts, BlockDeltas,
 };
 
 mod bytecode;
@@ -15,7 +15,7 @@ mod call;
 pub use call::Call;
 
 mod mpt;
-pub use mpt::{MptUpdate, MptUpdateRow, MptUpdates, WithdrawProof};
+pub use mpt::{mpt_update_keys, MptUpdate, MptUpdateKey, MptUpdateRow, MptUpdates, WithdrawProof};
 
 mod receipt;
 pub use receipt::Receipt;
@@ -1,5 +1,8 @@
 use ethers_core::types::Signature;
-use std::collections::{BTreeMap, HashMap};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    iter,
+};
 
 #[cfg(any(feature = "test", test))]
 use crate::evm_circuit::{detect_fixed_table_tags, EvmCircuit};
@@ -7,22 +10,25 @@ use crate::evm_circuit::{detect_fixed_table_tags, EvmCircuit};
 use crate::{
     evm_circuit::util::rlc,
     table::{BlockContextFieldTag, RwTableTag},
-    util::SubCircuit,
+    util::{SubCircuit, WitnessDelta},
 };
 use bus_mapping::{
     circuit_input_builder::{
-        self, BigModExp, CircuitsParams, CopyEvent, EcAddOp, EcMulOp, EcPairingOp, ExpEvent,
-        PrecompileEvents,
+        self, BigModExp, Blake2fEvent, CircuitsParams, CopyEvent, EcAddOp, EcMulOp, EcPairingOp,
+        ExpEvent, PrecompileEvents, Ripemd160Event, Sha256Event,
     },
     Error,
 };
-use eth_types::{sign_types::SignData, Address, Field, ToLittleEndian, ToScalar, Word, U256};
+use eth_types::{
+    pi_schema::PiSchema, sign_types::SignData, Address, Field, ToLittleEndian, ToScalar, Word,
+    H256, U256,
+};
 use halo2_proofs::circuit::Value;
 use itertools::Itertools;
 
 use super::{
-    mpt::ZktrieState as MptState, step::step_convert, tx::tx_convert, Bytecode, ExecStep,
-    MptUpdates, RwMap, Transaction,
+    mpt::ZktrieState as MptState, mpt_update_keys, step::step_convert, tx::tx_convert, Bytecode,
+    ExecStep, MptUpdateKey, MptUpdates, RwMap, Transaction,
 };
 use crate::util::Challenges;
 
@@ -72,6 +78,10 @@ pub struct Block<F> {
     pub chain_id: u64,
     /// StartL1QueueIndex
     pub start_l1_queue_index: u64,
+    /// Layout of the public input hash preimage of the chunk
+    pub pi_schema: PiSchema,
+    /// Rolling hash of the L1 message queue after the chunk
+    pub l1_msg_queue_hash: H256,
     /// IO to/from precompile calls.
     pub precompile_events: PrecompileEvents,
 }
@@ -136,6 +146,21 @@ impl<F: Field> Block<F> {
         self.precompile_events.get_modexp_events()
     }
 
+    /// Get SHA-256 hash events from all precompiled contract calls in this block.
+    pub(crate) fn get_sha256(&self) -> Vec<Sha256Event> {
+        self.precompile_events.get_sha256_events()
+    }
+
+    /// Get RIPEMD-160 hash events from all precompiled contract calls in this block.
+    pub(crate) fn get_ripemd160(&self) -> Vec<Ripemd160Event> {
+        self.precompile_events.get_ripemd160_events()
+    }
+
+    /// Get BLAKE2F compression events from all precompiled contract calls in this block.
+    pub(crate) fn get_blake2f(&self) -> Vec<Blake2fEvent> {
+        self.precompile_events.get_blake2f_events()
+    }
+
     pub(crate) fn print_evm_circuit_row_usage(&self) {
         let mut num_rows = 0;
         let mut counter = HashMap::new();
@@ -228,6 +253,14 @@ impl<F: Field> Block<F> {
         );
         log::debug!("tx_receipt num: {}", self.rws.rw_num(RwTableTag::TxReceipt));
         log::debug!("tx_log num: {}", self.rws.rw_num(RwTableTag::TxLog));
+        log::debug!(
+            "tx_created_account num: {}",
+            self.rws.rw_num(RwTableTag::TxCreatedAccount)
+        );
+        log::debug!(
+            "transient_storage num: {}",
+            self.rws.rw_num(RwTableTag::TransientStorage)
+        );
         log::debug!("start num: {}", self.rws.rw_num(RwTableTag::Start));
     }
 }
@@ -315,6 +348,8 @@ pub struct BlockContext {
     pub difficulty: Word,
     /// The base fee, the minimum amount of gas fee for a transaction
     pub base_fee: Word,
+    /// The blob base fee, derived from the excess blob gas of the block
+    pub blob_base_fee: Word,
     /// The hash of previous blocks
     pub history_hashes: Vec<Word>,
     /// The chain id
@@ -387,6 +422,13 @@ impl BlockContext {
                     Value::known(current_block_number),
                     Value::known(F::from(num_all_txs)),
                 ],
+                [
+                    Value::known(F::from(BlockContextFieldTag::BlobBaseFee as u64)),
+                    Value::known(current_block_number),
+                    randomness.map(|randomness| {
+                        rlc::value(&self.blob_base_fee.to_le_bytes(), randomness)
+                    }),
+                ],
             ],
             self.block_hash_assignments(randomness),
         ]
@@ -441,6 +483,7 @@ impl From<&circuit_input_builder::Block> for BlockContexts {
                             timestamp: block.timestamp,
                             difficulty: block.difficulty,
                             base_fee: block.base_fee,
+                            blob_base_fee: block.blob_base_fee,
                             history_hashes: block.history_hashes.clone(),
                             chain_id: block.chain_id,
                             eth_block: block.eth_block.clone(),
@@ -567,6 +610,8 @@ pub fn block_convert<F: Field>(
         mpt_updates,
         chain_id,
         start_l1_queue_index: block.start_l1_queue_index,
+        pi_schema: block.pi_schema,
+        l1_msg_queue_hash: block.l1_msg_queue_hash,
         precompile_events: block.precompile_events.clone(),
     })
 }
@@ -586,6 +631,71 @@ pub fn block_convert_with_l1_queue_index<F: Field>(
     Ok(witness_block)
 }
 
+/// The witness data of the txs of a [`circuit_input_builder::Block`] not yet finalized, to estimate
+/// the rows each SubCircuit requires for them by [`WitnessDelta`]s, without converting the whole
+/// block, see [`SubCircuitRowEstimator`](crate::util::SubCircuitRowEstimator).
+///
+/// The txs are expected to be built as followed by more txs, e.g., by
+/// `CircuitInputBuilder::add_more_l2_trace` with `more`, so that each of them has the rws linking
+/// it to the next one.
+#[derive(Debug, Clone)]
+pub struct BlockDeltas<'a> {
+    block: &'a circuit_input_builder::Block,
+    /// The txs of the block, converted one by one
+    pub txs: Vec<Transaction>,
+    /// The keccak inputs of the txs, see [`circuit_input_builder::keccak_inputs_tx_level`]
+    pub keccak_inputs: Vec<Vec<u8>>,
+    /// The keys of the mpt updates of the txs
+    pub mpt_update_keys: BTreeSet<MptUpdateKey>,
+}
+
+impl<'a> BlockDeltas<'a> {
+    /// Collect the witness data of the txs of `block`, which follow `num_prev_txs` txs in the
+    /// witness.
+    pub fn new(
+        block: &'a circuit_input_builder::Block,
+        code_db: &bus_mapping::state_db::CodeDB,
+        num_prev_txs: usize,
+    ) -> Result<Self, Error> {
+        let last_block_num = block.headers.keys().last().copied().unwrap_or_default();
+        let txs = block
+            .txs()
+            .iter()
+            .enumerate()
+            .map(|(idx, tx)| {
+                let next_block_num = block
+                    .txs()
+                    .get(idx + 1)
+                    .map_or(last_block_num + 1, |next_tx| next_tx.block_num);
+                tx_convert(tx, idx + 1, block.chain_id, next_block_num)
+            })
+            .collect();
+
+        Ok(Self {
+            block,
+            txs,
+            keccak_inputs: circuit_input_builder::keccak_inputs_tx_level(block, code_db)?,
+            mpt_update_keys: mpt_update_keys(&RwMap::from(&block.container), num_prev_txs),
+        })
+    }
+
+    /// Return the deltas of the txs and of the events of their steps. The bytecodes, keccak
+    /// inputs and mpt updates are left to the caller, to add those not yet in the witness.
+    pub fn deltas(&self) -> impl Iterator<Item = WitnessDelta<'_>> {
+        iter::empty()
+            .chain(self.txs.iter().map(WitnessDelta::Tx))
+            .chain(self.block.copy_events.iter().map(WitnessDelta::CopyEvent))
+            .chain(self.block.exp_events.iter().map(WitnessDelta::ExpEvent))
+            .chain(
+                self.block
+                    .precompile_events
+                    .events
+                    .iter()
+                    .map(WitnessDelta::PrecompileEvent),
+            )
+    }
+}
+
 /// Attach witness block with mpt states
 pub fn block_apply_mpt_state<F: Field>(block: &mut Block<F>, mpt_state: &MptState) {
     block.mpt_updates.fill_state_roots(mpt_state);
@@ -1,5 +1,8 @@
 use crate::{
-    evm_circuit::{util::rlc, witness::Rw},
+    evm_circuit::{
+        util::rlc,
+        witness::{Rw, RwMap},
+    },
     table::AccountFieldTag,
 };
 use eth_types::{Address, Field, ToLittleEndian, ToScalar, Word, U256};
@@ -14,7 +17,7 @@ use mpt_zktrie::{
 };
 use serde::{Deserialize, Serialize};
 pub use state::ZktrieState;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 /// Used to store withdraw proof
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
@@ -499,6 +502,35 @@ impl<F: Field> MptUpdateRow<Value<F>> {
     }
 }
 
+/// The key of an mpt update, to tell the updates of distinct witness data apart
+#[derive(Eq, PartialEq, Hash, Clone, Debug, Copy, PartialOrd, Ord)]
+pub struct MptUpdateKey(Key);
+
+/// Return the keys of the mpt updates of `rws`, in a witness where the txs of `rws` follow
+/// `num_prev_txs` txs. A storage slot has an update per tx, an account field one for all txs.
+pub fn mpt_update_keys(rws: &RwMap, num_prev_txs: usize) -> BTreeSet<MptUpdateKey> {
+    rws.0
+        .values()
+        .flatten()
+        .filter_map(key)
+        .map(|key| match key {
+            Key::AccountStorage {
+                tx_id,
+                address,
+                storage_key,
+                exists,
+            } => Key::AccountStorage {
+                tx_id: tx_id + num_prev_txs,
+                address,
+                storage_key,
+                exists,
+            },
+            key => key,
+        })
+        .map(MptUpdateKey)
+        .collect()
+}
+
 fn key(row: &Rw) -> Option<Key> {
     match row {
         Rw::Account {
@@ -1,6 +1,7 @@
-use eth_types::{Address, Field, H160, U256};
+use eth_types::{evm_types::MAX_BLOBS_PER_TX, Address, Field, H160, U256};
 use gadgets::{impl_expr, util::Expr};
 use halo2_proofs::{arithmetic::FieldExt, circuit::Value, plonk::Expression};
+use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
 use crate::util::Challenges;
@@ -135,6 +136,22 @@ pub enum Tag {
     // L1MsgHash
     /// Sender
     Sender,
+
+    // EIP-4844
+    /// Max fee per blob gas
+    MaxFeePerBlobGas,
+    /// Versioned hash at index 0 in blob_versioned_hashes
+    BlobVersionedHash0,
+    /// Versioned hash at index 1 in blob_versioned_hashes
+    BlobVersionedHash1,
+    /// Versioned hash at index 2 in blob_versioned_hashes
+    BlobVersionedHash2,
+    /// Versioned hash at index 3 in blob_versioned_hashes
+    BlobVersionedHash3,
+    /// Versioned hash at index 4 in blob_versioned_hashes
+    BlobVersionedHash4,
+    /// Versioned hash at index 5 in blob_versioned_hashes
+    BlobVersionedHash5,
 }
 
 impl From<Tag> for usize {
@@ -167,6 +184,28 @@ impl Tag {
     pub fn is_end(&self) -> bool {
         matches!(self, Self::EndList | Self::EndVector)
     }
+
+    /// If the tag is the versioned hash at some index in blob_versioned_hashes
+    pub fn is_blob_versioned_hash(&self) -> bool {
+        matches!(
+            self,
+            Self::BlobVersionedHash0
+                | Self::BlobVersionedHash1
+                | Self::BlobVersionedHash2
+                | Self::BlobVersionedHash3
+                | Self::BlobVersionedHash4
+                | Self::BlobVersionedHash5
+        )
+    }
+
+    /// The tag of the versioned hash at `index` in blob_versioned_hashes. Each index has its own
+    /// tag so that the RLP table binds the order of the hashes.
+    pub fn blob_versioned_hash(index: usize) -> Self {
+        assert!(index < MAX_BLOBS_PER_TX, "blob index out of range: {index}");
+        Self::iter()
+            .find(|tag| usize::from(*tag) == usize::from(Self::BlobVersionedHash0) + index)
+            .expect("MAX_BLOBS_PER_TX versioned hash tags")
+    }
 }
 
 /// RLP tags
@@ -208,13 +247,14 @@ use crate::{
     witness::{
         l1_msg,
         Format::{
-            TxHashEip155, TxHashEip1559, TxHashEip2930, TxHashPreEip155, TxSignEip155,
-            TxSignEip1559, TxSignEip2930, TxSignPreEip155,
+            TxHashEip155, TxHashEip1559, TxHashEip2930, TxHashEip4844, TxHashPreEip155,
+            TxSignEip155, TxSignEip1559, TxSignEip2930, TxSignEip4844, TxSignPreEip155,
         },
         Tag::{
             AccessListAddress, AccessListStorageKey, BeginList, BeginVector, ChainId, Data,
-            EndList, EndVector, Gas, GasPrice, MaxFeePerGas, MaxPriorityFeePerGas, Nonce, SigR,
-            SigS, SigV, To, TxType, Value as TxValue, Zero1, Zero2,
+            EndList, EndVector, Gas, GasPrice, MaxFeePerBlobGas, MaxFeePerGas,
+            MaxPriorityFeePerGas, Nonce, SigR, SigS, SigV, To, TxType, Value as TxValue, Zero1,
+            Zero2,
         },
     },
 };
@@ -534,6 +574,170 @@ pub fn eip1559_tx_sign_rom_table_rows() -> Vec<RomTableRow> {
         .collect()
 }
 
+// Rows of the versioned hashes in blob_versioned_hashes, starting at row `first`. `end` is the row
+// of the EndVector that closes the list. The i-th hash has its own tag, so the RLP table tells the
+// index of each hash and the list holds at most MAX_BLOBS_PER_TX hashes.
+fn blob_versioned_hash_rows(first: usize, end: usize) -> Vec<(Tag, Tag, usize, Vec<usize>)> {
+    let last = MAX_BLOBS_PER_TX - 1;
+    (0..MAX_BLOBS_PER_TX)
+        .flat_map(|i| {
+            let tag = Tag::blob_versioned_hash(i);
+            let row = first + 2 * i;
+            // finished parsing hashes
+            let mut rows = vec![(tag, EndVector, N_BYTES_WORD, vec![end])];
+            if i < last {
+                // keep parsing hashes, the last one can only be followed by EndVector
+                let next = if i + 1 == last {
+                    vec![row + 2]
+                } else {
+                    vec![row + 2, row + 3]
+                };
+                rows.push((tag, Tag::blob_versioned_hash(i + 1), N_BYTES_WORD, next));
+            }
+            rows
+        })
+        .collect()
+}
+
+pub fn eip4844_tx_sign_rom_table_rows() -> Vec<RomTableRow> {
+    let mut rows = vec![
+        (TxType, BeginList, 1, vec![1]),
+        (BeginList, ChainId, MAX_TAG_LENGTH_OF_LIST, vec![2]),
+        (ChainId, Nonce, N_BYTES_U64, vec![3]),
+        (Nonce, MaxPriorityFeePerGas, N_BYTES_U64, vec![4]),
+        (MaxPriorityFeePerGas, MaxFeePerGas, N_BYTES_WORD, vec![5]),
+        (MaxFeePerGas, Gas, N_BYTES_WORD, vec![6]),
+        (Gas, To, N_BYTES_U64, vec![7]),
+        (To, TxValue, N_BYTES_ACCOUNT_ADDRESS, vec![8]),
+        (TxValue, Data, N_BYTES_WORD, vec![9]),
+        (Data, BeginVector, N_BYTES_CALLDATA, vec![10, 11]),
+        (BeginVector, EndVector, MAX_TAG_LENGTH_OF_LIST, vec![21]), // access_list is none
+        (BeginVector, BeginList, MAX_TAG_LENGTH_OF_LIST, vec![12]),
+        (
+            BeginList,
+            AccessListAddress,
+            MAX_TAG_LENGTH_OF_LIST,
+            vec![13],
+        ),
+        (
+            AccessListAddress,
+            BeginVector,
+            N_BYTES_ACCOUNT_ADDRESS,
+            vec![14, 15],
+        ),
+        (BeginVector, EndVector, MAX_TAG_LENGTH_OF_LIST, vec![18]), /* access_list.storage_keys
+                                                                     * is none */
+        (
+            BeginVector,
+            AccessListStorageKey,
+            MAX_TAG_LENGTH_OF_LIST,
+            vec![16, 17],
+        ),
+        (AccessListStorageKey, EndVector, N_BYTES_WORD, vec![18]), // finished parsing storage keys
+        (
+            AccessListStorageKey,
+            AccessListStorageKey,
+            N_BYTES_WORD,
+            vec![16, 17],
+        ), // keep parsing storage_keys
+        (EndVector, EndList, 0, vec![19, 20]),
+        (EndList, EndVector, 0, vec![21]), // finished parsing access_list
+        (EndList, BeginList, 0, vec![12]), // parse another access_list entry
+        (EndVector, MaxFeePerBlobGas, 0, vec![22]),
+        (MaxFeePerBlobGas, BeginVector, N_BYTES_WORD, vec![23, 24]),
+        (BeginVector, EndVector, MAX_TAG_LENGTH_OF_LIST, vec![36]), /* blob_versioned_hashes is
+                                                                     * none */
+        (
+            BeginVector,
+            Tag::blob_versioned_hash(0),
+            MAX_TAG_LENGTH_OF_LIST,
+            vec![25, 26],
+        ),
+    ];
+    rows.extend(blob_versioned_hash_rows(25, 36));
+    rows.extend(vec![
+        (EndVector, EndList, 0, vec![37]),
+        (EndList, EndList, 0, vec![38]),
+        // used to emit TxGasCostInL1
+        (EndList, BeginList, 0, vec![]),
+    ]);
+
+    rows.into_iter()
+        .map(|row| (row.0, row.1, row.2, TxSignEip4844, row.3).into())
+        .collect()
+}
+
+pub fn eip4844_tx_hash_rom_table_rows() -> Vec<RomTableRow> {
+    let mut rows = vec![
+        (TxType, BeginList, 1, vec![1]),
+        (BeginList, ChainId, MAX_TAG_LENGTH_OF_LIST, vec![2]),
+        (ChainId, Nonce, N_BYTES_U64, vec![3]),
+        (Nonce, MaxPriorityFeePerGas, N_BYTES_U64, vec![4]),
+        (MaxPriorityFeePerGas, MaxFeePerGas, N_BYTES_WORD, vec![5]),
+        (MaxFeePerGas, Gas, N_BYTES_WORD, vec![6]),
+        (Gas, To, N_BYTES_U64, vec![7]),
+        (To, TxValue, N_BYTES_ACCOUNT_ADDRESS, vec![8]),
+        (TxValue, Data, N_BYTES_WORD, vec![9]),
+        (Data, BeginVector, N_BYTES_CALLDATA, vec![10, 11]),
+        (BeginVector, EndVector, MAX_TAG_LENGTH_OF_LIST, vec![21]), // access_list is none
+        (BeginVector, BeginList, MAX_TAG_LENGTH_OF_LIST, vec![12]),
+        (
+            BeginList,
+            AccessListAddress,
+            MAX_TAG_LENGTH_OF_LIST,
+            vec![13],
+        ),
+        (
+            AccessListAddress,
+            BeginVector,
+            N_BYTES_ACCOUNT_ADDRESS,
+            vec![14, 15],
+        ),
+        (BeginVector, EndVector, MAX_TAG_LENGTH_OF_LIST, vec![18]), /* access_list.storage_keys
+                                                                     * is none */
+        (
+            BeginVector,
+            AccessListStorageKey,
+            MAX_TAG_LENGTH_OF_LIST,
+            vec![16, 17],
+        ),
+        (AccessListStorageKey, EndVector, N_BYTES_WORD, vec![18]), // finished parsing storage keys
+        (
+            AccessListStorageKey,
+            AccessListStorageKey,
+            N_BYTES_WORD,
+            vec![16, 17],
+        ), // keep parsing storage_keys
+        (EndVector, EndList, 0, vec![19, 20]),
+        (EndList, EndVector, 0, vec![21]), // finished parsing access_list
+        (EndList, BeginList, 0, vec![12]), // parse another access_list entry
+        (EndVector, MaxFeePerBlobGas, 0, vec![22]),
+        (MaxFeePerBlobGas, BeginVector, N_BYTES_WORD, vec![23, 24]),
+        (BeginVector, EndVector, MAX_TAG_LENGTH_OF_LIST, vec![36]), /* blob_versioned_hashes is
+                                                                     * none */
+        (
+            BeginVector,
+            Tag::blob_versioned_hash(0),
+            MAX_TAG_LENGTH_OF_LIST,
+            vec![25, 26],
+        ),
+    ];
+    rows.extend(blob_versioned_hash_rows(25, 36));
+    rows.extend(vec![
+        (EndVector, SigV, 0, vec![37]),
+        (SigV, SigR, N_BYTES_U64, vec![38]),
+        (SigR, SigS, N_BYTES_WORD, vec![39]),
+        (SigS, EndList, N_BYTES_WORD, vec![40]),
+        (EndList, EndList, 0, vec![41]),
+        // used to emit TxGasCostInL1
+        (EndList, BeginList, 0, vec![]),
+    ]);
+
+    rows.into_iter()
+        .map(|row| (row.0, row.1, row.2, TxHashEip4844, row.3).into())
+        .collect()
+}
+
 /// Read-only Memory table row.
 #[derive(Debug, Clone)]
 pub struct RomTableRow {
@@ -592,6 +796,10 @@ pub enum Format {
     TxHashEip2930,
     /// L1 Msg
     L1MsgHash,
+    /// Sign for EIP4844 tx
+    TxSignEip4844,
+    /// Hash for EIP4844 tx
+    TxHashEip4844,
 }
 
 impl From<Format> for usize {
@@ -613,6 +821,8 @@ impl Format {
             TxSignEip2930 => eip2930_tx_sign_rom_table_rows(),
             TxHashEip2930 => eip2930_tx_hash_rom_table_rows(),
             Self::L1MsgHash => l1_msg::rom_table_rows(),
+            TxSignEip4844 => eip4844_tx_sign_rom_table_rows(),
+            TxHashEip4844 => eip4844_tx_hash_rom_table_rows(),
         }
     }
 }
@@ -319,6 +319,25 @@ pub enum Rw {
         field_tag: TxReceiptFieldTag,
         value: u64,
     },
+    /// TxCreatedAccount
+    TxCreatedAccount {
+        rw_counter: usize,
+        is_write: bool,
+        tx_id: usize,
+        account_address: Address,
+        is_created: bool,
+        is_created_prev: bool,
+    },
+    /// TransientStorage
+    TransientStorage {
+        rw_counter: usize,
+        is_write: bool,
+        tx_id: usize,
+        account_address: Address,
+        storage_key: Word,
+        value: Word,
+        value_prev: Word,
+    },
 }
 
 /// Rw table row assignment
@@ -383,6 +402,26 @@ impl Rw {
         }
     }
 
+    pub fn tx_created_account_value_pair(&self) -> (bool, bool) {
+        match self {
+            Self::TxCreatedAccount {
+                is_created,
+                is_created_prev,
+                ..
+            } => (*is_created, *is_created_prev),
+            _ => unreachable!("{:?}", self),
+        }
+    }
+
+    pub fn transient_storage_value_pair(&self) -> (Word, Word) {
+        match self {
+            Self::TransientStorage {
+                value, value_prev, ..
+            } => (*value, *value_prev),
+            _ => unreachable!("{:?}", self),
+        }
+    }
+
     pub fn tx_refund_value_pair(&self) -> (u64, u64) {
         match self {
             Self::TxRefund {
@@ -555,7 +594,9 @@ impl Rw {
             | Self::Account { rw_counter, .. }
             | Self::CallContext { rw_counter, .. }
             | Self::TxLog { rw_counter, .. }
-            | Self::TxReceipt { rw_counter, .. } => *rw_counter,
+            | Self::TxReceipt { rw_counter, .. }
+            | Self::TxCreatedAccount { rw_counter, .. }
+            | Self::TransientStorage { rw_counter, .. } => *rw_counter,
         }
     }
 
@@ -571,7 +612,9 @@ impl Rw {
             | Self::Account { is_write, .. }
             | Self::CallContext { is_write, .. }
             | Self::TxLog { is_write, .. }
-            | Self::TxReceipt { is_write, .. } => *is_write,
+            | Self::TxReceipt { is_write, .. }
+            | Self::TxCreatedAccount { is_write, .. }
+            | Self::TransientStorage { is_write, .. } => *is_write,
         }
     }
 
@@ -588,6 +631,8 @@ impl Rw {
             Self::CallContext { .. } => RwTableTag::CallContext,
             Self::TxLog { .. } => RwTableTag::TxLog,
             Self::TxReceipt { .. } => RwTableTag::TxReceipt,
+            Self::TxCreatedAccount { .. } => RwTableTag::TxCreatedAccount,
+            Self::TransientStorage { .. } => RwTableTag::TransientStorage,
         }
     }
 
@@ -599,7 +644,9 @@ impl Rw {
             | Self::TxAccessListAccountStorage { tx_id, .. }
             | Self::TxRefund { tx_id, .. }
             | Self::TxLog { tx_id, .. }
-            | Self::TxReceipt { tx_id, .. } => Some(*tx_id),
+            | Self::TxReceipt { tx_id, .. }
+            | Self::TxCreatedAccount { tx_id, .. }
+            | Self::TransientStorage { tx_id, .. } => Some(*tx_id),
             Self::CallContext { call_id, .. }
             | Self::Stack { call_id, .. }
             | Self::Memory { call_id, .. } => Some(*call_id),
@@ -621,6 +668,12 @@ impl Rw {
             }
             | Self::AccountStorage {
                 account_address, ..
+            }
+            | Self::TxCreatedAccount {
+                account_address, ..
+            }
+            | Self::TransientStorage {
+                account_address, ..
             } => Some(*account_address),
             Self::Memory { memory_address, .. } => Some(Address::from_low_u64_be(*memory_address)),
             Self::Stack { stack_pointer, .. } => {
@@ -657,7 +710,9 @@ impl Rw {
             | Self::TxAccessListAccount { .. }
             | Self::TxAccessListAccountStorage { .. }
             | Self::TxRefund { .. }
-            | Self::TxLog { .. } => None,
+            | Self::TxLog { .. }
+            | Self::TxCreatedAccount { .. }
+            | Self::TransientStorage { .. } => None,
         }
     }
 
@@ -665,7 +720,8 @@ impl Rw {
     pub fn storage_key(&self) -> Option<Word> {
         match self {
             Self::AccountStorage { storage_key, .. }
-            | Self::TxAccessListAccountStorage { storage_key, .. } => Some(*storage_key),
+            | Self::TxAccessListAccountStorage { storage_key, .. }
+            | Self::TransientStorage { storage_key, .. } => Some(*storage_key),
             Self::Start { .. }
             | Self::CallContext { .. }
             | Self::Stack { .. }
@@ -674,7 +730,8 @@ impl Rw {
             | Self::Account { .. }
             | Self::TxAccessListAccount { .. }
             | Self::TxLog { .. }
-            | Self::TxReceipt { .. } => None,
+            | Self::TxReceipt { .. }
+            | Self::TxCreatedAccount { .. } => None,
         }
     }
 
@@ -715,9 +772,9 @@ impl Rw {
                 | AccountFieldTag::NonExisting
                 | AccountFieldTag::CodeSize => value.to_scalar().unwrap(),
             },
-            Self::AccountStorage { value, .. } | Self::Stack { value, .. } => {
-                rlc::value(&value.to_le_bytes(), randomness)
-            }
+            Self::AccountStorage { value, .. }
+            | Self::TransientStorage { value, .. }
+            | Self::Stack { value, .. } => rlc::value(&value.to_le_bytes(), randomness),
 
             Self::TxLog {
                 field_tag, value, ..
@@ -729,6 +786,7 @@ impl Rw {
 
             Self::TxAccessListAccount { is_warm, .. }
             | Self::TxAccessListAccountStorage { is_warm, .. } => F::from(*is_warm as u64),
+            Self::TxCreatedAccount { is_created, .. } => F::from(*is_created as u64),
             Self::Memory { value, .. } => rlc::value(&value.to_le_bytes(), randomness),
             Self::TxRefund { value, .. } | Self::TxReceipt { value, .. } => F::from(*value),
         }
@@ -740,11 +798,13 @@ impl Rw {
             Self::CallContext { value, .. } => *value,
             Self::Account { value, .. }
             | Self::AccountStorage { value, .. }
+            | Self::TransientStorage { value, .. }
             | Self::Stack { value, .. }
             | Self::Memory { value, .. }
             | Self::TxLog { value, .. } => *value,
             Self::TxAccessListAccount { is_warm, .. }
             | Self::TxAccessListAccountStorage { is_warm, .. } => U256::from(*is_warm as u64),
+            Self::TxCreatedAccount { is_created, .. } => U256::from(*is_created as u64),
             Self::TxRefund { value, .. } | Self::TxReceipt { value, .. } => U256::from(*value),
         }
     }
@@ -770,7 +830,7 @@ impl Rw {
                 | AccountFieldTag::NonExisting
                 | AccountFieldTag::CodeSize => value_prev.to_scalar().unwrap(),
             }),
-            Self::AccountStorage { value_prev, .. } => {
+            Self::AccountStorage { value_prev, .. } | Self::TransientStorage { value_prev, .. } => {
                 Some(rlc::value(&value_prev.to_le_bytes(), randomness))
             }
             Self::Memory { value_prev, .. } => {
@@ -780,6 +840,9 @@ impl Rw {
             | Self::TxAccessListAccountStorage { is_warm_prev, .. } => {
                 Some(F::from(*is_warm_prev as u64))
             }
+            Self::TxCreatedAccount {
+                is_created_prev, ..
+            } => Some(F::from(*is_created_prev as u64)),
             Self::TxRefund { value_prev, .. } => Some(F::from(*value_prev)),
             Self::Start { .. }
             | Self::Stack { .. }
@@ -839,6 +902,37 @@ impl From<&operation::OperationContainer> for RwMap {
                 })
                 .collect(),
         );
+        rws.insert(
+            RwTableTag::TxCreatedAccount,
+            container
+                .tx_created_account
+                .iter()
+                .map(|op| Rw::TxCreatedAccount {
+                    rw_counter: op.rwc().into(),
+                    is_write: op.rw().is_write(),
+                    tx_id: op.op().tx_id,
+                    account_address: op.op().address,
+                    is_created: op.op().is_created,
+                    is_created_prev: op.op().is_created_prev,
+                })
+                .collect(),
+        );
+        rws.insert(
+            RwTableTag::TransientStorage,
+            container
+                .transient_storage
+                .iter()
+                .map(|op| Rw::TransientStorage {
+                    rw_counter: op.rwc().into(),
+                    is_write: op.rw().is_write(),
+                    tx_id: op.op().tx_id,
+                    account_address: op.op().address,
+                    storage_key: op.op().key,
+                    value: op.op().value,
+                    value_prev: op.op().value_prev,
+                })
+                .collect(),
+        );
         rws.insert(
             RwTableTag::TxAccessListAccountStorage,
             container
@@ -143,13 +143,6 @@ impl From<&circuit_input_builder::ExecStep> for ExecutionState {
                     return ExecutionState::LOG;
                 }
 
-                macro_rules! dummy {
-                    ($name:expr) => {{
-                        log::warn!("{:?} is implemented with DummyGadget", $name);
-                        $name
-                    }};
-                }
-
                 match op {
                     OpcodeId::ADD | OpcodeId::SUB => ExecutionState::ADD_SUB,
                     OpcodeId::ADDMOD => ExecutionState::ADDMOD,
@@ -172,6 +165,7 @@ impl From<&circuit_input_builder::ExecStep> for ExecutionState {
                     OpcodeId::MLOAD => ExecutionState::MEMORY,
                     OpcodeId::MSTORE => ExecutionState::MEMORY,
                     OpcodeId::MSTORE8 => ExecutionState::MEMORY,
+                    OpcodeId::MCOPY => ExecutionState::MCOPY,
                     OpcodeId::JUMPDEST => ExecutionState::JUMPDEST,
                     OpcodeId::JUMP => ExecutionState::JUMP,
                     OpcodeId::JUMPI => ExecutionState::JUMPI,
@@ -188,6 +182,8 @@ impl From<&circuit_input_builder::ExecStep> for ExecutionState {
                     }
                     OpcodeId::COINBASE => ExecutionState::BLOCKCTXU160,
                     OpcodeId::DIFFICULTY | OpcodeId::BASEFEE => ExecutionState::BLOCKCTXU256,
+                    OpcodeId::BLOBHASH => ExecutionState::BLOBHASH,
+                    OpcodeId::BLOBBASEFEE => ExecutionState::BLOBBASEFEE,
                     OpcodeId::GAS => ExecutionState::GAS,
                     OpcodeId::SAR => ExecutionState::SAR,
                     OpcodeId::SELFBALANCE => ExecutionState::SELFBALANCE,
@@ -195,6 +191,8 @@ impl From<&circuit_input_builder::ExecStep> for ExecutionState {
                     OpcodeId::SHL | OpcodeId::SHR => ExecutionState::SHL_SHR,
                     OpcodeId::SLOAD => ExecutionState::SLOAD,
                     OpcodeId::SSTORE => ExecutionState::SSTORE,
+                    OpcodeId::TLOAD => ExecutionState::TLOAD,
+                    OpcodeId::TSTORE => ExecutionState::TSTORE,
                     OpcodeId::CALLDATASIZE => ExecutionState::CALLDATASIZE,
                     OpcodeId::CALLDATACOPY => ExecutionState::CALLDATACOPY,
                     OpcodeId::CHAINID => ExecutionState::CHAINID,
@@ -213,8 +211,7 @@ impl From<&circuit_input_builder::ExecStep> for ExecutionState {
                     OpcodeId::RETURNDATACOPY => ExecutionState::RETURNDATACOPY,
                     OpcodeId::CREATE => ExecutionState::CREATE,
                     OpcodeId::CREATE2 => ExecutionState::CREATE2,
-                    // dummy ops
-                    OpcodeId::SELFDESTRUCT => dummy!(ExecutionState::SELFDESTRUCT),
+                    OpcodeId::SELFDESTRUCT => ExecutionState::SELFDESTRUCT,
                     _ => unimplemented!("unimplemented opcode {:?}", op),
                 }
             }
@@ -256,6 +253,8 @@ pub(super) fn step_convert(step: &circuit_input_builder::ExecStep, block_num: u6
                     operation::Target::CallContext => RwTableTag::CallContext,
                     operation::Target::TxReceipt => RwTableTag::TxReceipt,
                     operation::Target::TxLog => RwTableTag::TxLog,
+                    operation::Target::TxCreatedAccount => RwTableTag::TxCreatedAccount,
+                    operation::Target::TransientStorage => RwTableTag::TransientStorage,
                     operation::Target::Start => RwTableTag::Start,
                 };
                 (tag, x.as_usize())
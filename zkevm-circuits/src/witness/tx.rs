@@ -6,8 +6,8 @@ use crate::{
         rlp_fsm::SmState,
         DataTable, Format,
         Format::{
-            L1MsgHash, TxHashEip155, TxHashEip1559, TxHashEip2930, TxHashPreEip155, TxSignEip155,
-            TxSignEip1559, TxSignEip2930, TxSignPreEip155,
+            L1MsgHash, TxHashEip155, TxHashEip1559, TxHashEip2930, TxHashEip4844, TxHashPreEip155,
+            TxSignEip155, TxSignEip1559, TxSignEip2930, TxSignEip4844, TxSignPreEip155,
         },
         RlpFsmWitnessGen, RlpFsmWitnessRow, RlpTable, RlpTag, State,
         State::DecodeTagStart,
@@ -17,7 +17,7 @@ use crate::{
 };
 use bus_mapping::circuit_input_builder::{self, get_dummy_tx_hash, TxL1Fee};
 use eth_types::{
-    evm_types::gas_utils::tx_data_gas_cost,
+    evm_types::{gas_utils::tx_data_gas_cost, MAX_BLOBS_PER_TX},
     geth_types::{TxType, TxType::PreEip155},
     sign_types::{
         biguint_to_32bytes_le, ct_option_ok_or, get_dummy_tx, recover_pk, SignData, SECP256K1_Q,
@@ -75,6 +75,10 @@ pub struct Transaction {
     pub rlp_unsigned: Vec<u8>,
     /// Rlp-encoded bytes of signed tx
     pub rlp_signed: Vec<u8>,
+    /// Max fee per blob gas (EIP-4844)
+    pub max_fee_per_blob_gas: Word,
+    /// Blob versioned hashes (EIP-4844)
+    pub blob_versioned_hashes: Vec<H256>,
     /// "v" value of the transaction signature
     pub v: u64,
     /// "r" value of the transaction signature
@@ -309,11 +313,40 @@ impl Transaction {
             ],
             [
                 Value::known(F::from(self.id as u64)),
-                Value::known(F::from(TxContextFieldTag::BlockNumber as u64)),
+                Value::known(F::from(TxContextFieldTag::MaxFeePerBlobGas as u64)),
                 Value::known(F::zero()),
-                Value::known(F::from(self.block_number)),
+                challenges.evm_word().map(|challenge| {
+                    rlc::value(&self.max_fee_per_blob_gas.to_le_bytes(), challenge)
+                }),
             ],
-        ];
+            [
+                Value::known(F::from(self.id as u64)),
+                Value::known(F::from(TxContextFieldTag::BlobVersionedHashesLength as u64)),
+                Value::known(F::zero()),
+                Value::known(F::from(self.blob_versioned_hashes.len() as u64)),
+            ],
+        ]
+        .into_iter()
+        .chain((0..MAX_BLOBS_PER_TX).map(|idx| {
+            let hash = self
+                .blob_versioned_hashes
+                .get(idx)
+                .cloned()
+                .unwrap_or_default();
+            [
+                Value::known(F::from(self.id as u64)),
+                Value::known(F::from(TxContextFieldTag::BlobVersionedHash as u64)),
+                Value::known(F::from(idx as u64)),
+                rlc_be_bytes(hash.as_bytes(), challenges.evm_word()),
+            ]
+        }))
+        .chain(std::iter::once([
+            Value::known(F::from(self.id as u64)),
+            Value::known(F::from(TxContextFieldTag::BlockNumber as u64)),
+            Value::known(F::zero()),
+            Value::known(F::from(self.block_number)),
+        ]))
+        .collect::<Vec<_>>();
 
         ret
     }
@@ -351,6 +384,7 @@ impl Transaction {
                     TxType::Eip1559 => TxHashEip1559,
                     TxType::L1Msg => L1MsgHash,
                     TxType::Eip2930 => TxHashEip2930,
+                    TxType::Eip4844 => TxHashEip4844,
                 },
             )
         } else {
@@ -361,6 +395,7 @@ impl Transaction {
                     TxType::PreEip155 => TxSignPreEip155,
                     TxType::Eip1559 => TxSignEip1559,
                     TxType::Eip2930 => TxSignEip2930,
+                    TxType::Eip4844 => TxSignEip4844,
                     _ => unreachable!("tx type {:?} not supported", self.tx_type),
                 },
             )
@@ -789,6 +824,7 @@ impl<F: Field> RlpFsmWitnessGen<F> for Transaction {
             TxType::Eip1559 => (TxHashEip1559, Some(TxSignEip1559)),
             TxType::Eip2930 => (TxHashEip2930, Some(TxSignEip2930)),
             TxType::L1Msg => (L1MsgHash, None),
+            TxType::Eip4844 => (TxHashEip4844, Some(TxSignEip4844)),
         };
 
         let get_table = |rlp_bytes: &Vec<u8>, format: Format| {
@@ -871,6 +907,8 @@ impl From<MockTransaction> for Transaction {
             chain_id: mock_tx.chain_id,
             rlp_unsigned,
             rlp_signed,
+            max_fee_per_blob_gas: Word::zero(),
+            blob_versioned_hashes: vec![],
             v: sig.v,
             r: sig.r,
             s: sig.s,
@@ -922,6 +960,8 @@ pub(super) fn tx_convert(
         chain_id,
         rlp_unsigned: tx.rlp_unsigned_bytes.clone(),
         rlp_signed: tx.rlp_bytes.clone(),
+        max_fee_per_blob_gas: tx.max_fee_per_blob_gas,
+        blob_versioned_hashes: tx.blob_versioned_hashes.clone(),
         v: tx.signature.v,
         r: tx.signature.r,
         s: tx.signature.s,